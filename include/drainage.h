@@ -0,0 +1,31 @@
+/*
+ * C interface to drainage, available when the library is built with
+ * `cargo build --release --features capi`. That build is a plain C library without the
+ * Python module and loads without Python.
+ *
+ * Reports are returned as JSON strings matching `HealthReport.to_json()` in Python.
+ * Failures return a JSON object with a single "error" field.
+ */
+#ifndef DRAINAGE_H
+#define DRAINAGE_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Analyze the table at `s3_path` (e.g. "s3://bucket/table").
+ * `table_type` ("delta" or "iceberg") and `aws_region` may be NULL to auto-detect
+ * the format and use the default region. Credentials come from the default AWS chain.
+ * The returned string must be released with drainage_string_free().
+ */
+char *drainage_analyze_json(const char *s3_path, const char *table_type, const char *aws_region);
+
+/* Release a string returned by drainage_analyze_json(). Passing NULL is a no-op. */
+void drainage_string_free(char *value);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* DRAINAGE_H */
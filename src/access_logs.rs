@@ -0,0 +1,346 @@
+//! Access-pattern analysis from S3 server access logs or CloudTrail data events.
+//!
+//! Object reads recorded in the logs are joined against the table's data files to find
+//! files nobody read during the log window, partitions with no reads at all, and hot
+//! files that are read over and over.
+
+use crate::s3_client::{relative_key, ObjectInfo, S3ClientWrapper};
+use crate::types::{AccessPatternMetrics, FileAccess};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Log objects read per analysis; older objects beyond this are skipped
+const MAX_LOG_OBJECTS: usize = 5000;
+/// Reads during the log window that make a file hot
+pub const HOT_FILE_MIN_READS: usize = 100;
+const MAX_LISTED_FILES: usize = 100;
+const MAX_LISTED_HOT_FILES: usize = 20;
+
+/// One successful object read
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessRecord {
+    pub bucket: String,
+    pub key: String,
+    pub time: DateTime<Utc>,
+}
+
+/// Split an access log line into fields; `[...]` and `"..."` groups are single fields
+fn log_fields(line: &str) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let mut rest = line.trim();
+    while !rest.is_empty() {
+        let (field, remainder) = match rest.as_bytes()[0] {
+            b'[' => rest[1..].split_once(']').unwrap_or((&rest[1..], "")),
+            b'"' => rest[1..].split_once('"').unwrap_or((&rest[1..], "")),
+            _ => rest.split_once(' ').unwrap_or((rest, "")),
+        };
+        fields.push(field);
+        rest = remainder.trim_start();
+    }
+    fields
+}
+
+/// Parse S3 server access log lines, keeping successful GETs
+pub fn parse_server_access_log(content: &str) -> Vec<AccessRecord> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let fields = log_fields(line);
+            // owner bucket [time] ip requester request-id operation key "uri" status ...
+            let (bucket, time, operation, key, status) = (
+                fields.get(1)?,
+                fields.get(2)?,
+                fields.get(6)?,
+                fields.get(7)?,
+                fields.get(9)?,
+            );
+            if *operation != "REST.GET.OBJECT" || !matches!(*status, "200" | "206") {
+                return None;
+            }
+            let time = DateTime::parse_from_str(time, "%d/%b/%Y:%H:%M:%S %z").ok()?;
+            let key = percent_encoding::percent_decode_str(key)
+                .decode_utf8()
+                .ok()?
+                .to_string();
+            Some(AccessRecord {
+                bucket: bucket.to_string(),
+                key,
+                time: time.with_timezone(&Utc),
+            })
+        })
+        .collect()
+}
+
+/// Parse a CloudTrail log file, keeping successful `GetObject` data events
+pub fn parse_cloudtrail_log(content: &[u8]) -> Vec<AccessRecord> {
+    let Ok(log) = serde_json::from_slice::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    log.get("Records")
+        .and_then(|r| r.as_array())
+        .map(|records| {
+            records
+                .iter()
+                .filter(|r| r.get("eventName").and_then(|e| e.as_str()) == Some("GetObject"))
+                .filter(|r| r.get("errorCode").is_none())
+                .filter_map(|record| {
+                    let params = record.get("requestParameters")?;
+                    Some(AccessRecord {
+                        bucket: params.get("bucketName")?.as_str()?.to_string(),
+                        key: params.get("key")?.as_str()?.to_string(),
+                        time: record.get("eventTime")?.as_str()?.parse().ok()?,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Decompress a gzip member (CloudTrail delivers `.json.gz` files, S3 Inventory `.csv.gz`)
+pub fn gunzip(data: &[u8]) -> Result<Vec<u8>> {
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+    const FHCRC: u8 = 0x02;
+    anyhow::ensure!(
+        data.len() >= 18 && data[0] == 0x1f && data[1] == 0x8b,
+        "Not a gzip file"
+    );
+    let flags = data[3];
+    let mut offset = 10;
+    if flags & FEXTRA != 0 {
+        let extra = u16::from_le_bytes([data[offset], data[offset + 1]]) as usize;
+        offset += 2 + extra;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let end = data[offset..]
+                .iter()
+                .position(|b| *b == 0)
+                .context("Truncated gzip header")?;
+            offset += end + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        offset += 2;
+    }
+    let body = data
+        .get(offset..data.len() - 8)
+        .context("Truncated gzip file")?;
+    miniz_oxide::inflate::decompress_to_vec(body)
+        .map_err(|e| anyhow::anyhow!("Invalid gzip data: {:?}", e))
+}
+
+/// Read the reads recorded under `location` (`s3://bucket/prefix`). CloudTrail files
+/// (`.json`/`.json.gz`) and server access logs can be mixed. Returns the records, the
+/// number of log objects read and whether the newest `MAX_LOG_OBJECTS` cut the log short.
+pub async fn load_access_records(
+    s3_client: &S3ClientWrapper,
+    location: &str,
+) -> Result<(Vec<AccessRecord>, usize, bool)> {
+    let logs = s3_client
+        .with_location(location)
+        .context("Invalid access log location")?;
+    let mut objects = logs.list_objects(logs.get_prefix()).await?;
+    objects.sort_by_key(|object| std::cmp::Reverse(object.last_modified));
+    let truncated = objects.len() > MAX_LOG_OBJECTS;
+    objects.truncate(MAX_LOG_OBJECTS);
+
+    let mut records = Vec::new();
+    for object in &objects {
+        let content = logs.get_object(&object.key).await?;
+        if object.key.ends_with(".json.gz") {
+            records.extend(parse_cloudtrail_log(&gunzip(&content)?));
+        } else if object.key.ends_with(".json") {
+            records.extend(parse_cloudtrail_log(&content));
+        } else {
+            records.extend(parse_server_access_log(&String::from_utf8_lossy(&content)));
+        }
+    }
+    Ok((records, objects.len(), truncated))
+}
+
+/// Partition directory of a table-relative path (`date=2024-01-01/region=eu`), or "" if none
+fn partition_dir(relative: &str) -> String {
+    let mut segments: Vec<&str> = relative.split('/').collect();
+    segments.pop();
+    segments
+        .into_iter()
+        .filter(|segment| segment.contains('='))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Join reads against the table's data files
+pub fn summarize_access(
+    records: &[AccessRecord],
+    data_files: &[&ObjectInfo],
+    bucket: &str,
+    table_prefix: &str,
+    log_objects_read: usize,
+    truncated: bool,
+) -> AccessPatternMetrics {
+    let mut reads: HashMap<&str, (usize, DateTime<Utc>)> = HashMap::new();
+    for record in records.iter().filter(|r| r.bucket == bucket) {
+        let entry = reads.entry(record.key.as_str()).or_insert((0, record.time));
+        entry.0 += 1;
+        entry.1 = entry.1.max(record.time);
+    }
+
+    let mut metrics = AccessPatternMetrics {
+        log_objects_read,
+        truncated,
+        table_reads: 0,
+        window_start: records.iter().map(|r| r.time).min(),
+        window_end: records.iter().map(|r| r.time).max(),
+        never_read_file_count: 0,
+        never_read_size_bytes: 0,
+        never_read_files: Vec::new(),
+        cold_partitions: Vec::new(),
+        cold_partition_size_bytes: 0,
+        hot_files: Vec::new(),
+    };
+
+    // Per partition directory: (bytes, reads)
+    let mut partitions: HashMap<String, (u64, usize)> = HashMap::new();
+    for file in data_files {
+        let (read_count, last_read) = reads
+            .get(file.key.as_str())
+            .map(|(count, last)| (*count, Some(*last)))
+            .unwrap_or((0, None));
+        metrics.table_reads += read_count;
+
+        let relative = relative_key(table_prefix, &file.key);
+        let partition = partitions.entry(partition_dir(relative)).or_default();
+        partition.0 += file.size as u64;
+        partition.1 += read_count;
+
+        if read_count == 0 {
+            metrics.never_read_file_count += 1;
+            metrics.never_read_size_bytes += file.size as u64;
+            if metrics.never_read_files.len() < MAX_LISTED_FILES {
+                metrics.never_read_files.push(file.key.clone());
+            }
+        } else if read_count >= HOT_FILE_MIN_READS {
+            metrics.hot_files.push(FileAccess {
+                path: file.key.clone(),
+                size_bytes: file.size as u64,
+                read_count,
+                last_read,
+            });
+        }
+    }
+    metrics
+        .hot_files
+        .sort_by_key(|file| std::cmp::Reverse(file.read_count));
+    metrics.hot_files.truncate(MAX_LISTED_HOT_FILES);
+
+    let mut cold: Vec<(String, u64)> = partitions
+        .into_iter()
+        .filter(|(dir, (_, reads))| !dir.is_empty() && *reads == 0)
+        .map(|(dir, (bytes, _))| (dir, bytes))
+        .collect();
+    cold.sort();
+    metrics.cold_partition_size_bytes = cold.iter().map(|(_, bytes)| bytes).sum();
+    metrics.cold_partitions = cold.into_iter().map(|(dir, _)| dir).collect();
+
+    metrics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(key: &str, size: i64) -> ObjectInfo {
+        ObjectInfo {
+            key: key.to_string(),
+            size,
+            last_modified: None,
+            etag: None,
+            storage_class: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_server_access_log() {
+        let log = concat!(
+            "owner lake [06/Feb/2024:00:00:38 +0000] 192.0.2.3 arn:aws:iam::1:user/etl 3E57 REST.GET.OBJECT ",
+            "events/date%3D2024-01-01/part-0.parquet \"GET /events/date%3D2024-01-01/part-0.parquet HTTP/1.1\" ",
+            "200 - 1024 1024 70 10 \"-\" \"spark\" -\n",
+            "owner lake [06/Feb/2024:00:01:00 +0000] 192.0.2.3 - 3E58 REST.HEAD.OBJECT ",
+            "events/a.parquet \"HEAD /events/a.parquet HTTP/1.1\" 200 - - 1024 5 - \"-\" \"spark\" -\n",
+            "owner lake [06/Feb/2024:00:02:00 +0000] 192.0.2.3 - 3E59 REST.GET.OBJECT ",
+            "events/b.parquet \"GET /events/b.parquet HTTP/1.1\" 403 AccessDenied 243 - 5 - \"-\" \"spark\" -\n",
+        );
+        let records = parse_server_access_log(log);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].bucket, "lake");
+        assert_eq!(records[0].key, "events/date=2024-01-01/part-0.parquet");
+        assert_eq!(records[0].time.to_rfc3339(), "2024-02-06T00:00:38+00:00");
+    }
+
+    #[test]
+    fn test_parse_cloudtrail_log() {
+        let log = serde_json::json!({"Records": [
+            {"eventName": "GetObject", "eventTime": "2024-02-06T00:00:38Z",
+             "requestParameters": {"bucketName": "lake", "key": "events/a.parquet"}},
+            {"eventName": "GetObject", "eventTime": "2024-02-06T00:00:39Z", "errorCode": "AccessDenied",
+             "requestParameters": {"bucketName": "lake", "key": "events/b.parquet"}},
+            {"eventName": "PutObject", "eventTime": "2024-02-06T00:00:40Z",
+             "requestParameters": {"bucketName": "lake", "key": "events/c.parquet"}}
+        ]});
+        let records = parse_cloudtrail_log(log.to_string().as_bytes());
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].key, "events/a.parquet");
+    }
+
+    #[test]
+    fn test_gunzip() {
+        // `printf 'hello' | gzip -n`
+        let data = [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xcb, 0x48, 0xcd, 0xc9,
+            0xc9, 0x07, 0x00, 0x86, 0xa6, 0x10, 0x36, 0x05, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(gunzip(&data).unwrap(), b"hello");
+        assert!(gunzip(b"plain text, not gzip").is_err());
+    }
+
+    #[test]
+    fn test_summarize_access_finds_cold_and_hot_data() {
+        let time = "2024-02-06T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let read = |key: &str| AccessRecord {
+            bucket: "lake".to_string(),
+            key: key.to_string(),
+            time,
+        };
+        let mut records: Vec<AccessRecord> = (0..HOT_FILE_MIN_READS)
+            .map(|_| read("events/date=2024-01-02/hot.parquet"))
+            .collect();
+        records.push(read("events/date=2024-01-02/warm.parquet"));
+        records.push(AccessRecord {
+            bucket: "other".to_string(),
+            ..read("events/date=2024-01-01/cold.parquet")
+        });
+
+        let files = [
+            object("events/date=2024-01-01/cold.parquet", 100),
+            object("events/date=2024-01-02/hot.parquet", 200),
+            object("events/date=2024-01-02/warm.parquet", 300),
+            object("events/date=2024-01-02/unread.parquet", 400),
+        ];
+        let data_files: Vec<&ObjectInfo> = files.iter().collect();
+        let metrics = summarize_access(&records, &data_files, "lake", "events", 1, false);
+
+        assert_eq!(metrics.table_reads, HOT_FILE_MIN_READS + 1);
+        assert_eq!(metrics.never_read_file_count, 2);
+        assert_eq!(metrics.never_read_size_bytes, 500);
+        assert_eq!(metrics.cold_partitions, vec!["date=2024-01-01"]);
+        assert_eq!(metrics.cold_partition_size_bytes, 100);
+        assert_eq!(metrics.hot_files.len(), 1);
+        assert_eq!(
+            metrics.hot_files[0].path,
+            "events/date=2024-01-02/hot.parquet"
+        );
+    }
+}
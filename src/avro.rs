@@ -0,0 +1,393 @@
+//! Avro object container files, as Iceberg uses for manifests and manifest lists.
+//!
+//! Values are `serde_json::Value`s shaped by the schema: records are objects keyed by field
+//! name, unions are null or their value, bytes and fixed are arrays of numbers. Files are
+//! written uncompressed; reading also supports the `deflate` codec.
+
+use anyhow::{Context, Result};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+const MAGIC: &[u8; 4] = b"Obj\x01";
+
+fn write_long(out: &mut Vec<u8>, value: i64) {
+    let mut zigzag = ((value << 1) ^ (value >> 63)) as u64;
+    loop {
+        let byte = (zigzag & 0x7f) as u8;
+        zigzag >>= 7;
+        if zigzag == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_long(out, bytes.len() as i64);
+    out.extend_from_slice(bytes);
+}
+
+/// Named types of a schema, for fields referring to a record by name
+type Names = HashMap<String, Value>;
+
+/// Every named type defined in `schema`
+fn named_types(schema: &Value, names: &mut Names) {
+    match schema {
+        Value::Array(branches) => branches.iter().for_each(|b| named_types(b, names)),
+        Value::Object(object) => {
+            if let (Some(Value::String(name)), Some("record" | "enum" | "fixed")) = (
+                object.get("name"),
+                object.get("type").and_then(Value::as_str),
+            ) {
+                names.insert(name.clone(), schema.clone());
+            }
+            for field in object
+                .get("fields")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                named_types(&field["type"], names);
+            }
+            for nested in ["items", "values"] {
+                if let Some(nested) = object.get(nested) {
+                    named_types(nested, names);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The type of `schema`: a primitive name, a named type's definition, or the schema itself
+fn resolve<'a>(schema: &'a Value, names: &'a Names) -> Result<&'a Value> {
+    match schema.as_str() {
+        Some(name) if !is_primitive(name) => names
+            .get(name)
+            .with_context(|| format!("Unknown Avro type {}", name)),
+        _ => Ok(schema),
+    }
+}
+
+fn is_primitive(name: &str) -> bool {
+    matches!(
+        name,
+        "null" | "boolean" | "int" | "long" | "float" | "double" | "bytes" | "string"
+    )
+}
+
+fn type_name(schema: &Value) -> &str {
+    match schema {
+        Value::String(name) => name,
+        Value::Object(object) => object.get("type").and_then(Value::as_str).unwrap_or(""),
+        _ => "union",
+    }
+}
+
+fn encode(schema: &Value, value: &Value, names: &Names, out: &mut Vec<u8>) -> Result<()> {
+    let schema = resolve(schema, names)?;
+    let mismatch = || anyhow::anyhow!("Value {} doesn't match Avro type {}", value, schema);
+    match type_name(schema) {
+        "null" => {}
+        "boolean" => out.push(u8::from(value.as_bool().ok_or_else(mismatch)?)),
+        "int" | "long" => write_long(out, value.as_i64().ok_or_else(mismatch)?),
+        "float" => out.extend((value.as_f64().ok_or_else(mismatch)? as f32).to_le_bytes()),
+        "double" => out.extend(value.as_f64().ok_or_else(mismatch)?.to_le_bytes()),
+        "string" => write_bytes(out, value.as_str().ok_or_else(mismatch)?.as_bytes()),
+        "bytes" | "fixed" => {
+            let bytes: Vec<u8> = value
+                .as_array()
+                .ok_or_else(mismatch)?
+                .iter()
+                .map(|b| b.as_u64().map(|b| b as u8))
+                .collect::<Option<_>>()
+                .ok_or_else(mismatch)?;
+            match type_name(schema) {
+                "bytes" => write_bytes(out, &bytes),
+                _ => out.extend(bytes),
+            }
+        }
+        "record" => {
+            for field in schema["fields"].as_array().ok_or_else(mismatch)? {
+                let name = field["name"].as_str().unwrap_or_default();
+                let field_value = value.get(name).unwrap_or(&Value::Null);
+                encode(&field["type"], field_value, names, out)
+                    .with_context(|| format!("In field {}", name))?;
+            }
+        }
+        "enum" => {
+            let symbols = schema["symbols"].as_array().ok_or_else(mismatch)?;
+            let index = symbols
+                .iter()
+                .position(|s| s == value)
+                .ok_or_else(mismatch)?;
+            write_long(out, index as i64);
+        }
+        "array" => {
+            let items = value.as_array().ok_or_else(mismatch)?;
+            if !items.is_empty() {
+                write_long(out, items.len() as i64);
+                for item in items {
+                    encode(&schema["items"], item, names, out)?;
+                }
+            }
+            write_long(out, 0);
+        }
+        "map" => {
+            let entries = value.as_object().ok_or_else(mismatch)?;
+            if !entries.is_empty() {
+                write_long(out, entries.len() as i64);
+                for (key, item) in entries {
+                    write_bytes(out, key.as_bytes());
+                    encode(&schema["values"], item, names, out)?;
+                }
+            }
+            write_long(out, 0);
+        }
+        "union" => {
+            let branches = schema.as_array().ok_or_else(mismatch)?;
+            // Null goes to the null branch, anything else to the first other branch
+            let index = branches
+                .iter()
+                .position(|branch| (type_name(branch) == "null") == value.is_null())
+                .ok_or_else(mismatch)?;
+            write_long(out, index as i64);
+            encode(&branches[index], value, names, out)?;
+        }
+        other => anyhow::bail!("Unsupported Avro type {}", other),
+    }
+    Ok(())
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.data.len())
+            .context("Truncated Avro data")?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn long(&mut self) -> Result<i64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok((value >> 1) as i64 ^ -((value & 1) as i64));
+            }
+        }
+        anyhow::bail!("Invalid Avro varint")
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let length = usize::try_from(self.long()?).context("Negative Avro length")?;
+        self.take(length)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        Ok(String::from_utf8(self.bytes()?.to_vec())?)
+    }
+
+    /// Item count of the next array or map block, skipping the byte size of negative counts
+    fn block_count(&mut self) -> Result<usize> {
+        let count = self.long()?;
+        if count < 0 {
+            self.long()?;
+        }
+        Ok(count.unsigned_abs() as usize)
+    }
+
+    fn decode(&mut self, schema: &Value, names: &Names) -> Result<Value> {
+        let schema = resolve(schema, names)?;
+        Ok(match type_name(schema) {
+            "null" => Value::Null,
+            "boolean" => Value::Bool(self.take(1)?[0] != 0),
+            "int" | "long" => json!(self.long()?),
+            "float" => json!(f32::from_le_bytes(self.take(4)?.try_into()?)),
+            "double" => json!(f64::from_le_bytes(self.take(8)?.try_into()?)),
+            "string" => Value::String(self.string()?),
+            "bytes" => json!(self.bytes()?),
+            "fixed" => {
+                let size = schema["size"].as_u64().context("Avro fixed without size")?;
+                json!(self.take(size as usize)?)
+            }
+            "record" => {
+                let mut record = Map::new();
+                for field in schema["fields"]
+                    .as_array()
+                    .context("Avro record without fields")?
+                {
+                    let name = field["name"].as_str().unwrap_or_default().to_string();
+                    record.insert(name, self.decode(&field["type"], names)?);
+                }
+                Value::Object(record)
+            }
+            "enum" => {
+                let index = self.long()? as usize;
+                schema["symbols"]
+                    .get(index)
+                    .cloned()
+                    .context("Avro enum index out of range")?
+            }
+            "array" => {
+                let mut items = Vec::new();
+                loop {
+                    let count = self.block_count()?;
+                    if count == 0 {
+                        break;
+                    }
+                    for _ in 0..count {
+                        items.push(self.decode(&schema["items"], names)?);
+                    }
+                }
+                Value::Array(items)
+            }
+            "map" => {
+                let mut entries = Map::new();
+                loop {
+                    let count = self.block_count()?;
+                    if count == 0 {
+                        break;
+                    }
+                    for _ in 0..count {
+                        let key = self.string()?;
+                        entries.insert(key, self.decode(&schema["values"], names)?);
+                    }
+                }
+                Value::Object(entries)
+            }
+            "union" => {
+                let index = self.long()? as usize;
+                let branch = schema.get(index).context("Avro union index out of range")?;
+                self.decode(branch, names)?
+            }
+            other => anyhow::bail!("Unsupported Avro type {}", other),
+        })
+    }
+}
+
+/// An object container file of `records` in `schema`, with `metadata` in its header
+pub fn write(schema: &Value, metadata: &[(&str, String)], records: &[Value]) -> Result<Vec<u8>> {
+    let mut out = MAGIC.to_vec();
+    write_long(&mut out, metadata.len() as i64 + 2);
+    write_bytes(&mut out, b"avro.schema");
+    write_bytes(&mut out, schema.to_string().as_bytes());
+    write_bytes(&mut out, b"avro.codec");
+    write_bytes(&mut out, b"null");
+    for (key, value) in metadata {
+        write_bytes(&mut out, key.as_bytes());
+        write_bytes(&mut out, value.as_bytes());
+    }
+    write_long(&mut out, 0);
+    // The sync marker only has to be unlikely to appear in the data
+    let sync = Sha256::digest(&out)[..16].to_vec();
+    out.extend(&sync);
+
+    let mut block = Vec::new();
+    let mut names = Names::new();
+    named_types(schema, &mut names);
+    for record in records {
+        encode(schema, record, &names, &mut block)?;
+    }
+    write_long(&mut out, records.len() as i64);
+    write_long(&mut out, block.len() as i64);
+    out.extend(block);
+    out.extend(&sync);
+    Ok(out)
+}
+
+/// The header metadata (without the `avro.` keys) and records of an object container file
+pub fn read(data: &[u8]) -> Result<(HashMap<String, String>, Vec<Value>)> {
+    anyhow::ensure!(data.starts_with(MAGIC), "Not an Avro object container file");
+    let mut reader = Reader { data, position: 4 };
+    let mut metadata = HashMap::new();
+    loop {
+        let count = reader.block_count()?;
+        if count == 0 {
+            break;
+        }
+        for _ in 0..count {
+            let key = reader.string()?;
+            metadata.insert(key, String::from_utf8_lossy(reader.bytes()?).into_owned());
+        }
+    }
+    let schema: Value = serde_json::from_str(
+        &metadata
+            .remove("avro.schema")
+            .context("Avro file without a schema")?,
+    )?;
+    let codec = metadata
+        .remove("avro.codec")
+        .unwrap_or_else(|| "null".to_string());
+    let sync = reader.take(16)?;
+
+    let mut records = Vec::new();
+    let mut names = Names::new();
+    named_types(&schema, &mut names);
+    while reader.position < data.len() {
+        let count = reader.long()?;
+        let block = reader.bytes()?;
+        let block = match codec.as_str() {
+            "null" => block.to_vec(),
+            "deflate" => miniz_oxide::inflate::decompress_to_vec(block)
+                .map_err(|e| anyhow::anyhow!("Invalid deflate block: {:?}", e))?,
+            other => anyhow::bail!("Unsupported Avro codec {}", other),
+        };
+        let mut block_reader = Reader {
+            data: &block,
+            position: 0,
+        };
+        for _ in 0..count {
+            records.push(block_reader.decode(&schema, &names)?);
+        }
+        anyhow::ensure!(reader.take(16)? == sync, "Avro sync marker mismatch");
+    }
+    Ok((metadata, records))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_avro_round_trip() {
+        let schema = json!({
+            "type": "record",
+            "name": "manifest_file",
+            "fields": [
+                {"name": "manifest_path", "type": "string"},
+                {"name": "added_snapshot_id", "type": "long"},
+                {"name": "key_metadata", "type": ["null", "bytes"]},
+                {"name": "partitions", "type": ["null", {"type": "array", "items": {
+                    "type": "record", "name": "field_summary",
+                    "fields": [{"name": "contains_null", "type": "boolean"}]}}]},
+                {"name": "summary", "type": ["null", "field_summary"]},
+                {"name": "sizes", "type": {"type": "map", "values": "double"}},
+            ]
+        });
+        let records = vec![
+            json!({"manifest_path": "s3://lake/t/metadata/m0.avro", "added_snapshot_id": -7,
+                   "key_metadata": null, "partitions": [{"contains_null": true}],
+                   "summary": {"contains_null": false}, "sizes": {"a": 1.5}}),
+            json!({"manifest_path": "m1.avro", "added_snapshot_id": 1_i64 << 40,
+                   "key_metadata": [1, 2], "partitions": null, "summary": null, "sizes": {}}),
+        ];
+        let file = write(&schema, &[("format-version", "2".to_string())], &records).unwrap();
+        let (metadata, read_back) = read(&file).unwrap();
+        assert_eq!(metadata["format-version"], "2");
+        assert_eq!(read_back, records);
+        assert!(write(&schema, &[], &[json!({"manifest_path": 1})]).is_err());
+        assert!(read(b"PAR1").is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//! Health badges: small flat SVGs in the style of shields.io for wikis and READMEs.
+
+/// Scores above this are healthy (green), as in `print_health_report`
+pub const HEALTHY_SCORE: f64 = 0.8;
+/// Scores above this and up to `HEALTHY_SCORE` need attention (yellow); lower ones are red
+pub const FAIR_SCORE: f64 = 0.6;
+
+const HEALTHY_COLOR: &str = "#4c1";
+const FAIR_COLOR: &str = "#dfb317";
+const POOR_COLOR: &str = "#e05d44";
+const LABEL_COLOR: &str = "#555";
+
+/// Letter grade of a 0.0-1.0 health score
+pub fn grade(score: f64) -> &'static str {
+    match score {
+        s if s >= 0.9 => "A",
+        s if s >= 0.8 => "B",
+        s if s >= 0.7 => "C",
+        s if s >= 0.6 => "D",
+        _ => "F",
+    }
+}
+
+fn color(score: f64) -> &'static str {
+    if score > HEALTHY_SCORE {
+        HEALTHY_COLOR
+    } else if score > FAIR_SCORE {
+        FAIR_COLOR
+    } else {
+        POOR_COLOR
+    }
+}
+
+/// Escape text for XML and HTML
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Approximate width of `text` in 11px Verdana, plus padding
+fn text_width(text: &str) -> usize {
+    text.chars().count() * 7 + 10
+}
+
+/// Badge reading "`label` | 87% B", colored by score
+pub fn render(label: &str, score: f64) -> String {
+    let value = format!("{:.0}% {}", score * 100.0, grade(score));
+    let (label_width, value_width) = (text_width(label), text_width(&value));
+    let width = label_width + value_width;
+    let label = escape(label);
+    format!(
+        concat!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {value}">"##,
+            r##"<title>{label}: {value}</title>"##,
+            r##"<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>"##,
+            r##"<clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath>"##,
+            r##"<g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="{label_color}"/><rect x="{label_width}" width="{value_width}" height="20" fill="{color}"/><rect width="{width}" height="20" fill="url(#s)"/></g>"##,
+            r##"<g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">"##,
+            r##"<text x="{label_x}" y="14">{label}</text><text x="{value_x}" y="14">{value}</text></g></svg>"##,
+        ),
+        width = width,
+        label = label,
+        value = value,
+        label_width = label_width,
+        value_width = value_width,
+        label_color = LABEL_COLOR,
+        color = color(score),
+        label_x = label_width / 2,
+        value_x = label_width + value_width / 2,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_badge() {
+        let svg = render("events <prod>", 0.87);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(">87% B</text>"));
+        assert!(svg.contains("events &lt;prod&gt;"));
+        assert!(svg.contains(HEALTHY_COLOR));
+
+        assert!(render("t", 0.65).contains(FAIR_COLOR));
+        assert!(render("t", 0.6).contains(POOR_COLOR));
+        assert_eq!(grade(0.59), "F");
+        assert_eq!(grade(1.0), "A");
+    }
+}
@@ -0,0 +1,412 @@
+//! Report bundles (`report.save_bundle(path)`): one zip archive with everything needed to
+//! look into a table's health without drainage installed, for incident tickets and support
+//! requests.
+//!
+//! - `report.json`: the full report, as `to_json()` writes it
+//! - `report.html`: a standalone page with the score, key metrics and recommendations
+//! - `inventories/data_files.parquet`, `inventories/unreferenced_files.parquet` and
+//!   `inventories/partitions.parquet`: the report's file and partition lists
+//! - `analysis.log`: the stages and warnings of the analysis
+
+use crate::badge::escape;
+use crate::types::{FileInfo, HealthReport, PartitionInfo};
+use anyhow::Result;
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use std::fmt::Write as _;
+use std::io::{Seek, Write};
+use std::sync::Arc;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const FILES_SCHEMA: &str = "message file {
+    required binary path (UTF8);
+    required binary partition (UTF8);
+    required int64 size_bytes;
+    optional int64 last_modified (TIMESTAMP(MILLIS,true));
+    required boolean is_referenced;
+    optional int64 commit_id;
+}";
+
+const PARTITIONS_SCHEMA: &str = "message partition {
+    required binary partition (UTF8);
+    required int64 file_count;
+    required int64 total_size_bytes;
+}";
+
+fn strings(values: impl Iterator<Item = String>) -> Vec<ByteArray> {
+    values
+        .map(|value| ByteArray::from(value.into_bytes()))
+        .collect()
+}
+
+/// Values and definition levels of an optional column
+fn optional(values: impl Iterator<Item = Option<i64>>) -> (Vec<i64>, Vec<i16>) {
+    let mut present = Vec::new();
+    let mut levels = Vec::new();
+    for value in values {
+        levels.push(i16::from(value.is_some()));
+        present.extend(value);
+    }
+    (present, levels)
+}
+
+/// One row per file, with the partition it was listed under ("" for none)
+fn files_parquet(files: &[(String, &FileInfo)]) -> Result<Vec<u8>> {
+    let schema = Arc::new(parse_message_type(FILES_SCHEMA)?);
+    let mut buffer = Vec::new();
+    let mut writer = SerializedFileWriter::new(
+        &mut buffer,
+        schema,
+        Arc::new(WriterProperties::builder().build()),
+    )?;
+    let mut row_group = writer.next_row_group()?;
+
+    let mut column = row_group.next_column()?.expect("path column");
+    column.typed::<ByteArrayType>().write_batch(
+        &strings(files.iter().map(|(_, file)| file.path.clone())),
+        None,
+        None,
+    )?;
+    column.close()?;
+    let mut column = row_group.next_column()?.expect("partition column");
+    column.typed::<ByteArrayType>().write_batch(
+        &strings(files.iter().map(|(partition, _)| partition.clone())),
+        None,
+        None,
+    )?;
+    column.close()?;
+    let mut column = row_group.next_column()?.expect("size_bytes column");
+    let sizes: Vec<i64> = files
+        .iter()
+        .map(|(_, file)| file.size_bytes as i64)
+        .collect();
+    column
+        .typed::<Int64Type>()
+        .write_batch(&sizes, None, None)?;
+    column.close()?;
+    let mut column = row_group.next_column()?.expect("last_modified column");
+    let (modified, levels) = optional(
+        files
+            .iter()
+            .map(|(_, file)| file.last_modified.map(|time| time.timestamp_millis())),
+    );
+    column
+        .typed::<Int64Type>()
+        .write_batch(&modified, Some(&levels), None)?;
+    column.close()?;
+    let mut column = row_group.next_column()?.expect("is_referenced column");
+    let referenced: Vec<bool> = files.iter().map(|(_, file)| file.is_referenced).collect();
+    column
+        .typed::<BoolType>()
+        .write_batch(&referenced, None, None)?;
+    column.close()?;
+    let mut column = row_group.next_column()?.expect("commit_id column");
+    let (commits, levels) = optional(files.iter().map(|(_, file)| file.commit_id));
+    column
+        .typed::<Int64Type>()
+        .write_batch(&commits, Some(&levels), None)?;
+    column.close()?;
+
+    row_group.close()?;
+    writer.close()?;
+    Ok(buffer)
+}
+
+fn partitions_parquet(partitions: &[PartitionInfo]) -> Result<Vec<u8>> {
+    let schema = Arc::new(parse_message_type(PARTITIONS_SCHEMA)?);
+    let mut buffer = Vec::new();
+    let mut writer = SerializedFileWriter::new(
+        &mut buffer,
+        schema,
+        Arc::new(WriterProperties::builder().build()),
+    )?;
+    let mut row_group = writer.next_row_group()?;
+
+    let mut column = row_group.next_column()?.expect("partition column");
+    column.typed::<ByteArrayType>().write_batch(
+        &strings(partitions.iter().map(PartitionInfo::path)),
+        None,
+        None,
+    )?;
+    column.close()?;
+    let mut column = row_group.next_column()?.expect("file_count column");
+    let counts: Vec<i64> = partitions.iter().map(|p| p.file_count as i64).collect();
+    column
+        .typed::<Int64Type>()
+        .write_batch(&counts, None, None)?;
+    column.close()?;
+    let mut column = row_group.next_column()?.expect("total_size_bytes column");
+    let sizes: Vec<i64> = partitions
+        .iter()
+        .map(|p| p.total_size_bytes as i64)
+        .collect();
+    column
+        .typed::<Int64Type>()
+        .write_batch(&sizes, None, None)?;
+    column.close()?;
+
+    row_group.close()?;
+    writer.close()?;
+    Ok(buffer)
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.2} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// The analysis log, one line per stage or warning
+pub fn render_log(report: &HealthReport) -> String {
+    let mut log = String::new();
+    for entry in report.analysis_log.iter().flatten() {
+        if let Some(elapsed) = entry.elapsed_seconds {
+            let _ = write!(log, "+{:>9.3}s ", elapsed);
+        }
+        let _ = writeln!(log, "{:<7} {}", entry.level, entry.message);
+    }
+    for failure in report.failed_phases.iter().flatten() {
+        let _ = writeln!(log, "ERROR   {} skipped: {}", failure.phase, failure.error);
+    }
+    if let Some(ref truncated) = report.truncated {
+        let _ = writeln!(
+            log,
+            "WARNING cut short after {}s: {}",
+            truncated.max_duration_seconds,
+            truncated.stages.join(", ")
+        );
+    }
+    log
+}
+
+/// A standalone HTML page with the score, key metrics and recommendations
+pub fn render_html(report: &HealthReport) -> String {
+    let metrics = &report.metrics;
+    let small_files_label = format!(
+        "Small files (< {})",
+        crate::size_label(metrics.file_size_buckets().small_bytes)
+    );
+    let mut rows = vec![
+        ("Table type", report.table_type.clone()),
+        ("Analyzed at", report.analysis_timestamp.to_rfc3339()),
+        ("Data files", metrics.total_files.to_string()),
+        ("Total size", megabytes(metrics.total_size_bytes)),
+        (
+            "Average file size",
+            megabytes(metrics.avg_file_size_bytes as u64),
+        ),
+        ("Partitions", metrics.partition_count.to_string()),
+        (
+            "Unreferenced files",
+            format!(
+                "{} ({})",
+                report.unreferenced_file_count(),
+                megabytes(metrics.unreferenced_size_bytes)
+            ),
+        ),
+        (
+            small_files_label.as_str(),
+            metrics.file_size_distribution.small_files.to_string(),
+        ),
+    ];
+    if let Some(ref sub_scores) = metrics.sub_scores {
+        rows.extend([
+            (
+                "Storage cost score",
+                format!("{:.0}%", sub_scores.storage_cost_score * 100.0),
+            ),
+            (
+                "Query performance score",
+                format!("{:.0}%", sub_scores.query_performance_score * 100.0),
+            ),
+            (
+                "Reliability score",
+                format!("{:.0}%", sub_scores.reliability_score * 100.0),
+            ),
+        ]);
+    }
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        concat!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title>",
+            "<style>body{{font-family:sans-serif;margin:2em;max-width:60em}}",
+            "table{{border-collapse:collapse}}td{{padding:.3em 1em;border-bottom:1px solid #ddd}}",
+            "pre{{background:#f6f8fa;padding:1em;overflow-x:auto}}</style></head><body>\n",
+            "<h1>{title}</h1>\n<p>{badge}</p>\n<table>\n"
+        ),
+        title = escape(&report.table_path),
+        badge = crate::badge::render("table health", report.health_score),
+    );
+    for (label, value) in rows {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td></tr>",
+            label,
+            escape(&value)
+        );
+    }
+    html.push_str("</table>\n<h2>Recommendations</h2>\n<ol>\n");
+    for recommendation in &metrics.recommendations {
+        let _ = writeln!(html, "<li>{}</li>", escape(recommendation));
+    }
+    html.push_str("</ol>\n");
+    let log = render_log(report);
+    if !log.is_empty() {
+        let _ = writeln!(html, "<h2>Analysis Log</h2>\n<pre>{}</pre>", escape(&log));
+    }
+    html.push_str("</body></html>\n");
+    html
+}
+
+/// Write the bundle of `report` as a zip archive
+pub fn write_bundle<W: Write + Seek>(report: &HealthReport, output: W) -> Result<()> {
+    let data_files: Vec<(String, &FileInfo)> = report
+        .metrics
+        .partitions
+        .iter()
+        .flat_map(|partition| {
+            let path = partition.path();
+            partition.files.iter().map(move |file| (path.clone(), file))
+        })
+        .collect();
+    let unreferenced_files: Vec<(String, &FileInfo)> = report
+        .metrics
+        .unreferenced_files
+        .iter()
+        .map(|file| (String::new(), file))
+        .collect();
+
+    let entries = [
+        (
+            "report.json",
+            serde_json::to_vec_pretty(report)?,
+            CompressionMethod::Deflated,
+        ),
+        (
+            "report.html",
+            render_html(report).into_bytes(),
+            CompressionMethod::Deflated,
+        ),
+        (
+            "analysis.log",
+            render_log(report).into_bytes(),
+            CompressionMethod::Deflated,
+        ),
+        // Parquet pages are compressed already
+        (
+            "inventories/data_files.parquet",
+            files_parquet(&data_files)?,
+            CompressionMethod::Stored,
+        ),
+        (
+            "inventories/unreferenced_files.parquet",
+            files_parquet(&unreferenced_files)?,
+            CompressionMethod::Stored,
+        ),
+        (
+            "inventories/partitions.parquet",
+            partitions_parquet(&report.metrics.partitions)?,
+            CompressionMethod::Stored,
+        ),
+    ];
+    let mut zip = ZipWriter::new(output);
+    for (name, contents, compression) in entries {
+        zip.start_file(name, FileOptions::default().compression_method(compression))?;
+        zip.write_all(&contents)?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::LogEntry;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use std::collections::HashMap;
+    use std::io::{Cursor, Read};
+
+    #[test]
+    fn test_write_bundle() {
+        let mut report = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        let file = FileInfo::new(
+            "events/day=2024-01-01/part-0.parquet".to_string(),
+            1024,
+            None,
+            true,
+            chrono::Utc::now(),
+        );
+        report.metrics.partitions = vec![PartitionInfo {
+            partition_values: HashMap::from([("day".to_string(), "2024-01-01".to_string())]),
+            file_count: 1,
+            total_size_bytes: 1024,
+            avg_file_size_bytes: 1024.0,
+            files: vec![file],
+        }];
+        report.metrics.recommendations = vec!["Compact <small> files".to_string()];
+        report.metrics.file_size_histogram = crate::types::FileSizeHistogram::from_sizes(
+            vec![1024],
+            &crate::config::FileSizeBuckets::from_mb(&[128.0, 1024.0, 4096.0]).unwrap(),
+        );
+        report.analysis_log = Some(vec![
+            LogEntry {
+                elapsed_seconds: Some(0.0),
+                level: "INFO".to_string(),
+                message: "listing".to_string(),
+            },
+            LogEntry {
+                elapsed_seconds: Some(1.5),
+                level: "WARNING".to_string(),
+                message: "Access logs were truncated".to_string(),
+            },
+        ]);
+
+        let mut buffer = Cursor::new(Vec::new());
+        write_bundle(&report, &mut buffer).unwrap();
+        let mut archive = zip::ZipArchive::new(buffer).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "analysis.log",
+                "inventories/data_files.parquet",
+                "inventories/partitions.parquet",
+                "inventories/unreferenced_files.parquet",
+                "report.html",
+                "report.json",
+            ]
+        );
+
+        let mut log = String::new();
+        archive
+            .by_name("analysis.log")
+            .unwrap()
+            .read_to_string(&mut log)
+            .unwrap();
+        assert_eq!(
+            log,
+            "+    0.000s INFO    listing\n+    1.500s WARNING Access logs were truncated\n"
+        );
+        let mut html = String::new();
+        archive
+            .by_name("report.html")
+            .unwrap()
+            .read_to_string(&mut html)
+            .unwrap();
+        assert!(html.contains("<li>Compact &lt;small&gt; files</li>"));
+        assert!(html.contains("<td>Small files (< 128MB)</td>"));
+
+        let mut inventory = Vec::new();
+        archive
+            .by_name("inventories/data_files.parquet")
+            .unwrap()
+            .read_to_end(&mut inventory)
+            .unwrap();
+        let reader = SerializedFileReader::new(bytes::Bytes::from(inventory)).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 1);
+    }
+}
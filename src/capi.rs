@@ -0,0 +1,93 @@
+//! C-compatible entry points for embedding drainage in non-Python services.
+//!
+//! Reports cross the boundary as JSON strings. Every string returned by this module
+//! must be released with `drainage_string_free`. See `include/drainage.h`.
+
+use crate::config::AnalysisConfig;
+use crate::health_analyzer::TableTarget;
+use anyhow::Result;
+use std::ffi::{c_char, CStr, CString};
+
+/// Read an optional, NUL-terminated UTF-8 argument
+///
+/// # Safety
+/// `value` must be null or point to a valid NUL-terminated string.
+unsafe fn optional_arg(value: *const c_char) -> Result<Option<String>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    Ok(Some(CStr::from_ptr(value).to_str()?.to_string()))
+}
+
+fn analyze_to_json(
+    s3_path: *const c_char,
+    table_type: *const c_char,
+    aws_region: *const c_char,
+) -> Result<String> {
+    // SAFETY: the caller guarantees each argument is null or a valid C string
+    let (s3_path, table_type, aws_region) = unsafe {
+        (
+            optional_arg(s3_path)?.ok_or_else(|| anyhow::anyhow!("s3_path is required"))?,
+            optional_arg(table_type)?,
+            optional_arg(aws_region)?,
+        )
+    };
+    let runtime = tokio::runtime::Runtime::new()?;
+    let target = TableTarget {
+        s3_path,
+        table_type,
+        aws_region,
+        ..Default::default()
+    };
+    let report = runtime.block_on(target.analyze(AnalysisConfig::default(), |_| {}))?;
+    Ok(serde_json::to_string(&report)?)
+}
+
+/// Analyze a table and return its health report as JSON. On failure the JSON is an
+/// object with a single `error` field. Credentials come from the default AWS chain.
+///
+/// # Safety
+/// `s3_path` must point to a valid NUL-terminated string; `table_type` and `aws_region`
+/// may be null (auto-detect / default region). Free the result with `drainage_string_free`.
+#[no_mangle]
+pub unsafe extern "C" fn drainage_analyze_json(
+    s3_path: *const c_char,
+    table_type: *const c_char,
+    aws_region: *const c_char,
+) -> *mut c_char {
+    let json = analyze_to_json(s3_path, table_type, aws_region).unwrap_or_else(|e| {
+        serde_json::json!({ "error": crate::sanitize::sanitize(&e.to_string()) }).to_string()
+    });
+    CString::new(json).unwrap_or_default().into_raw()
+}
+
+/// Release a string returned by this library.
+///
+/// # Safety
+/// `value` must be null or a pointer previously returned by `drainage_analyze_json`,
+/// and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn drainage_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drainage_analyze_json_reports_argument_errors() {
+        let result =
+            unsafe { drainage_analyze_json(std::ptr::null(), std::ptr::null(), std::ptr::null()) };
+        let json = unsafe { CStr::from_ptr(result) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        unsafe { drainage_string_free(result) };
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["error"], "s3_path is required");
+    }
+}
@@ -0,0 +1,181 @@
+//! Metadata captures (`capture_metadata`): a table's transaction log or metadata directory
+//! copied to a local directory, without any data files, as a shareable reproducer for
+//! parsing bugs.
+//!
+//! The capture keeps the table-relative layout (`_delta_log/...`, `metadata/...`) and adds
+//! `listing.csv`, the key, size and modification time of every object in the table, and
+//! `capture.json`, where and when it was taken. Uploaded to any bucket, for example a local
+//! MinIO, the capture is analyzed with `listing="listing.csv"` like the original table.
+//! Object keys, including partition values in data file paths, are kept as they are.
+
+use crate::config::AnalysisConfig;
+use crate::s3_client::{relative_key, ObjectInfo, S3ClientWrapper};
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use pyo3::prelude::*;
+use serde_json::json;
+use std::path::{Component, Path, PathBuf};
+
+const METADATA_DIRS: [&str; 2] = ["_delta_log/", "metadata/"];
+const DOWNLOAD_CONCURRENCY: usize = 16;
+const LISTING_FILE: &str = "listing.csv";
+const SUMMARY_FILE: &str = "capture.json";
+
+/// What `capture_metadata` wrote
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct MetadataCapture {
+    #[pyo3(get)]
+    pub output_dir: PathBuf,
+    #[pyo3(get)]
+    pub table_type: String, // as `detect_table_type` classifies the table
+    #[pyo3(get)]
+    pub metadata_files: usize,
+    #[pyo3(get)]
+    pub metadata_bytes: u64,
+    #[pyo3(get)]
+    pub listed_objects: usize, // rows in listing.csv
+}
+
+fn csv_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// `listing.csv` for `objects`, keyed relative to the table root
+fn listing_csv(objects: &[ObjectInfo], table_prefix: &str) -> String {
+    let mut csv = String::from("key,size,last_modified\n");
+    for object in objects {
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            csv_quote(relative_key(table_prefix, &object.key)),
+            object.size,
+            object
+                .last_modified
+                .map(|time| time.to_rfc3339())
+                .unwrap_or_default()
+        ));
+    }
+    csv
+}
+
+/// Local path of table-relative `key` under `output_dir`, refusing keys that would escape it
+fn local_path(output_dir: &Path, key: &str) -> Result<PathBuf> {
+    let relative = Path::new(key);
+    anyhow::ensure!(
+        relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_))),
+        "Refusing to write object {} outside the capture directory",
+        key
+    );
+    Ok(output_dir.join(relative))
+}
+
+/// Copy the table's metadata files to `output_dir`, which must be empty or missing
+pub async fn capture_metadata(
+    s3_client: &S3ClientWrapper,
+    config: &AnalysisConfig,
+    output_dir: &Path,
+) -> Result<MetadataCapture> {
+    let is_empty = match std::fs::read_dir(output_dir) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => true,
+        Err(e) => return Err(e).with_context(|| format!("Cannot read {}", output_dir.display())),
+    };
+    anyhow::ensure!(is_empty, "{} is not empty", output_dir.display());
+    std::fs::create_dir_all(output_dir)
+        .with_context(|| format!("Cannot create {}", output_dir.display()))?;
+
+    let table_prefix = s3_client.get_prefix();
+    let objects = s3_client.list_objects(table_prefix).await?;
+    let detection =
+        crate::health_analyzer::classify_table(&objects, table_prefix, &config.detection);
+    let metadata: Vec<String> = objects
+        .iter()
+        .map(|object| object.key.clone())
+        .filter(|key| {
+            let relative = relative_key(table_prefix, key);
+            METADATA_DIRS.iter().any(|dir| relative.starts_with(dir)) && !relative.ends_with('/')
+        })
+        .collect();
+
+    let mut downloads = futures::stream::iter(metadata.iter().cloned().map(|key| async move {
+        let path = local_path(output_dir, relative_key(table_prefix, &key))?;
+        let content = s3_client.get_object(&key).await?;
+        anyhow::Ok((path, content))
+    }))
+    .buffer_unordered(DOWNLOAD_CONCURRENCY);
+    let mut metadata_bytes = 0;
+    while let Some(download) = downloads.next().await {
+        let (path, content) = download?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, &content)
+            .with_context(|| format!("Cannot write {}", path.display()))?;
+        metadata_bytes += content.len() as u64;
+    }
+
+    std::fs::write(
+        output_dir.join(LISTING_FILE),
+        listing_csv(&objects, table_prefix),
+    )?;
+    let summary = json!({
+        "source": format!("s3://{}/{}", s3_client.get_bucket(), table_prefix),
+        "captured_at": config.clock.now().to_rfc3339(),
+        "drainage_version": env!("CARGO_PKG_VERSION"),
+        "table_type": detection.table_type,
+        "evidence": detection.evidence,
+        "metadata_files": metadata.len(),
+        "metadata_bytes": metadata_bytes,
+        "listed_objects": objects.len(),
+    });
+    std::fs::write(
+        output_dir.join(SUMMARY_FILE),
+        serde_json::to_string_pretty(&summary)?,
+    )?;
+
+    Ok(MetadataCapture {
+        output_dir: output_dir.to_path_buf(),
+        table_type: detection.table_type,
+        metadata_files: metadata.len(),
+        metadata_bytes,
+        listed_objects: objects.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listing_csv_round_trips_and_paths_stay_inside() {
+        let object = |key: &str, size: i64| ObjectInfo {
+            key: key.to_string(),
+            size,
+            last_modified: Some("2024-01-01T00:00:00Z".parse().unwrap()),
+            etag: None,
+            storage_class: None,
+        };
+        let objects = [
+            object("lake/t/_delta_log/00000000000000000000.json", 812),
+            object("lake/t/city=\"Paris, FR\"/part-0.parquet", 1024),
+        ];
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LISTING_FILE);
+        std::fs::write(&path, listing_csv(&objects, "lake/t")).unwrap();
+        let listed = crate::listing::read_file(&path).unwrap();
+        assert_eq!(listed[0].key, "_delta_log/00000000000000000000.json");
+        assert_eq!(listed[1].key, "city=\"Paris, FR\"/part-0.parquet");
+        assert_eq!(listed[1].size, 1024);
+        assert_eq!(listed[1].last_modified, objects[1].last_modified);
+
+        let out = Path::new("/tmp/capture");
+        assert_eq!(
+            local_path(out, "metadata/v1.metadata.json").unwrap(),
+            out.join("metadata/v1.metadata.json")
+        );
+        assert!(local_path(out, "_delta_log/../../etc/passwd").is_err());
+        assert!(local_path(out, "/etc/passwd").is_err());
+    }
+}
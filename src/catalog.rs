@@ -0,0 +1,778 @@
+//! Catalogs tables are resolved through by name (`analyze_catalog_table`) and crawled by
+//! pattern (`analyze_namespace`), behind the `Catalog` trait: this module's Iceberg REST
+//! client, which resolves a table to its storage location and the storage credentials the
+//! catalog vends for it, so catalog-secured tables can be analyzed without static cloud
+//! keys, and the AWS Glue, Unity Catalog and Hive Metastore clients of their own modules.
+
+use crate::error::{DrainageError, ErrorContext};
+use crate::glue_catalog::{GlueCatalogClient, GlueCatalogConfig};
+use crate::hive_metastore::{HiveMetastoreClient, HiveMetastoreConfig};
+use crate::network::NetworkSettings;
+use crate::types::{ColumnInfo, TableSchema};
+use crate::unity_catalog::{UnityCatalogClient, UnityCatalogConfig};
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use pyo3::prelude::*;
+use regex::Regex;
+use serde_json::Value;
+use url::Url;
+
+/// OAuth2 scope requested when none is configured
+pub const DEFAULT_OAUTH2_SCOPE: &str = "catalog";
+
+/// Polaris serves the Iceberg REST API under this path of its host
+const POLARIS_API_PATH: &str = "api/catalog";
+
+/// Gravitino's Iceberg REST service serves the API under this path of its host
+const GRAVITINO_API_PATH: &str = "iceberg";
+
+/// Namespace levels are joined with the unit separator in REST catalog paths
+const NAMESPACE_SEPARATOR: &str = "\u{1f}";
+
+/// How to reach and authenticate against a REST catalog
+#[derive(Debug, Clone, Default)]
+#[pyclass(name = "RestCatalog")]
+pub struct RestCatalogConfig {
+    #[pyo3(get)]
+    pub uri: String,
+    #[pyo3(get)]
+    pub warehouse: Option<String>,
+    /// `client_id:client_secret` for the OAuth2 client-credentials flow
+    pub credential: Option<String>,
+    /// Pre-issued bearer token; skips the OAuth2 exchange
+    pub token: Option<String>,
+    #[pyo3(get)]
+    pub scope: Option<String>,
+    /// Token endpoint; defaults to `{uri}/v1/oauth/tokens`
+    #[pyo3(get)]
+    pub oauth2_server_uri: Option<String>,
+    /// User for HTTP Basic "simple" authentication (Gravitino)
+    #[pyo3(get)]
+    pub simple_auth_user: Option<String>,
+    /// Which catalog implementation this connects to: "rest", "polaris" or "gravitino"
+    #[pyo3(get)]
+    pub flavor: String,
+}
+
+/// Append `path` to a catalog host URI unless it already ends with it
+fn with_api_path(uri: &str, path: &str) -> String {
+    let trimmed = uri.trim_end_matches('/');
+    if trimmed.ends_with(path) {
+        trimmed.to_string()
+    } else {
+        format!("{}/{}", trimmed, path)
+    }
+}
+
+#[pymethods]
+impl RestCatalogConfig {
+    #[new]
+    #[pyo3(signature = (uri, warehouse=None, credential=None, token=None, scope=None, oauth2_server_uri=None))]
+    pub fn py_new(
+        uri: String,
+        warehouse: Option<String>,
+        credential: Option<String>,
+        token: Option<String>,
+        scope: Option<String>,
+        oauth2_server_uri: Option<String>,
+    ) -> Self {
+        Self {
+            uri,
+            warehouse,
+            credential,
+            token,
+            scope,
+            oauth2_server_uri,
+            simple_auth_user: None,
+            flavor: "rest".to_string(),
+        }
+    }
+
+    /// Apache Polaris: `uri` is the Polaris host, `catalog` the Polaris catalog name.
+    /// Authenticates as a service principal with the given principal role.
+    #[staticmethod]
+    #[pyo3(signature = (uri, catalog, client_id, client_secret, principal_role="ALL"))]
+    pub fn polaris(
+        uri: &str,
+        catalog: String,
+        client_id: &str,
+        client_secret: &str,
+        principal_role: &str,
+    ) -> Self {
+        Self {
+            uri: with_api_path(uri, POLARIS_API_PATH),
+            warehouse: Some(catalog),
+            credential: Some(format!("{}:{}", client_id, client_secret)),
+            scope: Some(format!("PRINCIPAL_ROLE:{}", principal_role)),
+            flavor: "polaris".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Apache Gravitino's Iceberg REST service: `uri` is the service host, `catalog`
+    /// selects a catalog when the service fronts several. Authenticates with simple
+    /// auth (`user`), or with OAuth2 through Gravitino's own token server.
+    #[staticmethod]
+    #[pyo3(signature = (uri, catalog=None, user=None, credential=None, token=None, oauth2_server_uri=None, scope=None))]
+    pub fn gravitino(
+        uri: &str,
+        catalog: Option<String>,
+        user: Option<String>,
+        credential: Option<String>,
+        token: Option<String>,
+        oauth2_server_uri: Option<String>,
+        scope: Option<String>,
+    ) -> Self {
+        Self {
+            uri: with_api_path(uri, GRAVITINO_API_PATH),
+            warehouse: catalog,
+            credential,
+            token,
+            scope,
+            oauth2_server_uri,
+            simple_auth_user: user,
+            flavor: "gravitino".to_string(),
+        }
+    }
+}
+
+/// Temporary storage credentials returned with a table
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StorageCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+    pub region: Option<String>,
+}
+
+/// A table resolved through the catalog
+#[derive(Debug, Clone)]
+pub struct LoadedTable {
+    pub location: String,
+    pub table_type: Option<&'static str>, // "delta" or "iceberg"; detected from storage when None
+    pub credentials: Option<StorageCredentials>,
+    pub schema: Option<TableSchema>, // current schema as registered in the catalog
+}
+
+/// Table listing and lookup of a catalog
+pub trait Catalog: Send + Sync {
+    /// Namespaces one level below `parent` (the top level when empty), as all their levels
+    fn namespaces<'a>(&'a self, parent: &'a [String]) -> BoxFuture<'a, Result<Vec<Vec<String>>>>;
+
+    /// Names of the tables in `namespace`
+    fn tables<'a>(&'a self, namespace: &'a [String]) -> BoxFuture<'a, Result<Vec<String>>>;
+
+    /// Resolve `namespace.table` to its location, format and any vended credentials
+    fn load_table<'a>(&'a self, identifier: &'a str) -> BoxFuture<'a, Result<LoadedTable>>;
+}
+
+/// How to reach any of the supported catalogs
+#[derive(Debug, Clone, FromPyObject)]
+pub enum CatalogConfig {
+    Rest(RestCatalogConfig),
+    Glue(GlueCatalogConfig),
+    Unity(UnityCatalogConfig),
+    Hive(HiveMetastoreConfig),
+}
+
+impl CatalogConfig {
+    /// Connect to the catalog. Glue is called in `aws_region` unless configured with its own.
+    pub async fn connect(
+        &self,
+        aws_region: Option<String>,
+        network: &NetworkSettings,
+    ) -> Result<Box<dyn Catalog>> {
+        Ok(match self {
+            Self::Rest(config) => Box::new(RestCatalogClient::connect(config, network).await?),
+            Self::Glue(config) => {
+                Box::new(GlueCatalogClient::connect(config, aws_region, network).await?)
+            }
+            Self::Unity(config) => Box::new(UnityCatalogClient::connect(config, network)?),
+            Self::Hive(config) => Box::new(HiveMetastoreClient::connect(config, network).await?),
+        })
+    }
+}
+
+/// Tables of `catalog` matching `pattern` (see `table_pattern`), as sorted `namespace.table`
+/// identifiers
+pub async fn find_tables(catalog: &dyn Catalog, pattern: &str) -> Result<Vec<String>> {
+    let matcher = table_pattern(pattern)?;
+    let namespace_depth = pattern.split('.').count() - 1;
+
+    // List down from the pattern's literal levels to the namespaces holding its tables
+    let mut namespaces = Vec::new();
+    let mut pending = vec![literal_namespace(pattern)];
+    while let Some(namespace) = pending.pop() {
+        if namespace.len() >= namespace_depth {
+            namespaces.push(namespace);
+        } else {
+            pending.extend(catalog.namespaces(&namespace).await?);
+        }
+    }
+
+    let mut tables = Vec::new();
+    for namespace in namespaces.iter().filter(|ns| ns.len() == namespace_depth) {
+        for name in catalog.tables(namespace).await? {
+            let full = format!("{}.{}", namespace.join("."), name);
+            if matcher.is_match(&full) {
+                tables.push(full);
+            }
+        }
+    }
+    tables.sort();
+    Ok(tables)
+}
+
+/// The format of a Glue or Hive Metastore table from its parameters: Iceberg's `table_type`
+/// or Spark's data source provider for Delta
+pub fn table_format<'a>(parameter: impl Fn(&str) -> Option<&'a str>) -> Option<&'static str> {
+    let is = |key: &str, value: &str| parameter(key).is_some_and(|v| v.eq_ignore_ascii_case(value));
+    if is("table_type", "iceberg") {
+        Some("iceberg")
+    } else if is("spark.sql.sources.provider", "delta") || is("table_type", "delta") {
+        Some("delta")
+    } else {
+        None
+    }
+}
+
+/// The schema of a Glue or Hive Metastore table. Spark's data source schema in the table
+/// parameters comes first, since Spark registers Delta tables with a placeholder column and
+/// keeps the real schema there; otherwise the Hive `columns` followed by the `partition_keys`,
+/// as (name, type) pairs.
+pub fn hive_schema<'a>(
+    parameter: impl Fn(&str) -> Option<&'a str>,
+    columns: &[(String, String)],
+    partition_keys: &[(String, String)],
+) -> Option<TableSchema> {
+    let spark_schema = parameter("spark.sql.sources.schema")
+        .map(str::to_string)
+        .or_else(|| {
+            // Long schemas are split across numbered parameters
+            let parts: usize = parameter("spark.sql.sources.schema.numParts")?
+                .parse()
+                .ok()?;
+            (0..parts)
+                .map(|i| parameter(&format!("spark.sql.sources.schema.part.{}", i)))
+                .collect()
+        });
+    let spark_fields = spark_schema
+        .and_then(|schema| serde_json::from_str::<Value>(&schema).ok())
+        .and_then(|schema| schema.get("fields")?.as_array().cloned());
+    if let Some(fields) = spark_fields {
+        let partition_count: usize = parameter("spark.sql.sources.schema.numPartCols")
+            .and_then(|count| count.parse().ok())
+            .unwrap_or(0);
+        let partition_columns = (0..partition_count)
+            .filter_map(|i| parameter(&format!("spark.sql.sources.schema.partCol.{}", i)))
+            .map(str::to_string)
+            .collect();
+        return Some(TableSchema::from_fields(None, &fields, partition_columns));
+    }
+
+    if columns.is_empty() && partition_keys.is_empty() {
+        return None;
+    }
+    Some(TableSchema {
+        schema_id: None,
+        columns: columns
+            .iter()
+            .chain(partition_keys)
+            .map(|(name, data_type)| ColumnInfo {
+                name: name.clone(),
+                data_type: data_type.clone(),
+                nullable: true,
+                field_id: None,
+            })
+            .collect(),
+        partition_columns: partition_keys
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect(),
+    })
+}
+
+pub struct RestCatalogClient {
+    http: reqwest::Client,
+    uri: Url,
+    prefix: Option<String>,
+    token: Option<String>,
+    simple_auth_user: Option<String>,
+}
+
+/// The error of the catalog request `what` answered with `status`
+pub fn request_failed(what: &str, status: reqwest::StatusCode, body: &str) -> anyhow::Error {
+    let context = ErrorContext::new(format!(
+        "Catalog request {} failed with {}: {}",
+        what, status, body
+    ));
+    DrainageError::from_status(status.as_u16(), context.clone())
+        .unwrap_or(DrainageError::Catalog(context))
+        .into()
+}
+
+/// Split `ns1.ns2.table` into its namespace levels and table name
+pub fn parse_table_identifier(identifier: &str) -> Result<(Vec<String>, String)> {
+    let mut parts: Vec<String> = identifier.split('.').map(str::to_string).collect();
+    let table = parts.pop().unwrap_or_default();
+    if parts.is_empty() || table.is_empty() || parts.iter().any(|p| p.is_empty()) {
+        return Err(anyhow::anyhow!(
+            "Invalid table identifier '{}': expected namespace.table",
+            identifier
+        ));
+    }
+    Ok((parts, table))
+}
+
+/// Compile a table pattern such as `analytics.*` into a regex over `namespace.table`
+/// identifiers. `*` and `?` match within one level, never across a dot.
+pub fn table_pattern(pattern: &str) -> Result<Regex> {
+    let mut regex = String::from("^");
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str("[^.]*"),
+            '?' => regex.push_str("[^.]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Ok(Regex::new(&regex)?)
+}
+
+/// Namespace levels of a pattern that can be listed directly: everything before the
+/// table level, up to the first level with a wildcard
+fn literal_namespace(pattern: &str) -> Vec<String> {
+    let mut levels: Vec<&str> = pattern.split('.').collect();
+    levels.pop();
+    levels
+        .into_iter()
+        .take_while(|level| !level.contains(['*', '?']))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Split a `client_id:client_secret` credential; a bare secret has no client id
+fn split_credential(credential: &str) -> (Option<&str>, &str) {
+    match credential.split_once(':') {
+        Some((client_id, secret)) => (Some(client_id), secret),
+        None => (None, credential),
+    }
+}
+
+fn endpoint(base: &Url, segments: &[&str]) -> Result<Url> {
+    let mut url = base.clone();
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("Catalog URI cannot be a base: {}", base))?
+        .pop_if_empty()
+        .extend(segments);
+    Ok(url)
+}
+
+fn string_property<'a>(properties: &'a Value, key: &str) -> Option<&'a str> {
+    properties.get(key).and_then(|v| v.as_str())
+}
+
+fn credentials_from_properties(properties: &Value) -> Option<StorageCredentials> {
+    Some(StorageCredentials {
+        access_key_id: string_property(properties, "s3.access-key-id")?.to_string(),
+        secret_access_key: string_property(properties, "s3.secret-access-key")?.to_string(),
+        session_token: string_property(properties, "s3.session-token").map(str::to_string),
+        region: string_property(properties, "client.region")
+            .or_else(|| string_property(properties, "s3.region"))
+            .map(str::to_string),
+    })
+}
+
+/// Pick the vended credentials for `location` from a LoadTable response: the
+/// `storage-credentials` entry with the longest matching prefix, else the table `config`
+pub fn vended_credentials(response: &Value, location: &str) -> Option<StorageCredentials> {
+    let scoped = response
+        .get("storage-credentials")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let prefix = string_property(entry, "prefix")?;
+            if !location.starts_with(prefix) {
+                return None;
+            }
+            let credentials = credentials_from_properties(entry.get("config")?)?;
+            Some((prefix.len(), credentials))
+        })
+        .max_by_key(|(prefix_len, _)| *prefix_len)
+        .map(|(_, credentials)| credentials);
+
+    scoped.or_else(|| credentials_from_properties(response.get("config")?))
+}
+
+/// Read the table location and credentials out of a LoadTable response
+pub fn parse_load_table(response: &Value) -> Result<LoadedTable> {
+    let location = response
+        .get("metadata")
+        .and_then(|m| string_property(m, "location"))
+        .context("LoadTable response has no metadata.location")?
+        .to_string();
+    Ok(LoadedTable {
+        table_type: Some("iceberg"),
+        credentials: vended_credentials(response, &location),
+        schema: response.get("metadata").map(crate::iceberg::current_schema),
+        location,
+    })
+}
+
+impl RestCatalogClient {
+    /// Authenticate (when a credential is configured) and read the catalog's `/v1/config`
+    pub async fn connect(config: &RestCatalogConfig, network: &NetworkSettings) -> Result<Self> {
+        let uri = Url::parse(&config.uri).context("Invalid catalog URI")?;
+        let mut catalog = Self {
+            http: network.http_client()?,
+            uri,
+            prefix: None,
+            token: config.token.clone(),
+            simple_auth_user: config.simple_auth_user.clone(),
+        };
+
+        if catalog.token.is_none() {
+            if let Some(ref credential) = config.credential {
+                crate::sanitize::register(split_credential(credential).1);
+                catalog.token = Some(catalog.fetch_token(config, credential).await?);
+            }
+        }
+        if let Some(ref token) = catalog.token {
+            crate::sanitize::register(token);
+        }
+
+        let mut url = endpoint(&catalog.uri, &["v1", "config"])?;
+        if let Some(ref warehouse) = config.warehouse {
+            url.query_pairs_mut().append_pair("warehouse", warehouse);
+        }
+        let catalog_config = catalog.get_json(url).await?;
+        catalog.prefix = ["overrides", "defaults"]
+            .iter()
+            .find_map(|section| {
+                catalog_config
+                    .get(section)
+                    .and_then(|properties| string_property(properties, "prefix"))
+            })
+            .map(str::to_string);
+        Ok(catalog)
+    }
+
+    /// OAuth2 client-credentials exchange
+    async fn fetch_token(&self, config: &RestCatalogConfig, credential: &str) -> Result<String> {
+        let url = match config.oauth2_server_uri {
+            Some(ref uri) => Url::parse(uri).context("Invalid OAuth2 server URI")?,
+            None => endpoint(&self.uri, &["v1", "oauth", "tokens"])?,
+        };
+        let (client_id, client_secret) = split_credential(credential);
+        let scope = config.scope.as_deref().unwrap_or(DEFAULT_OAUTH2_SCOPE);
+        let mut form = vec![
+            ("grant_type", "client_credentials"),
+            ("client_secret", client_secret),
+            ("scope", scope),
+        ];
+        if let Some(client_id) = client_id {
+            form.push(("client_id", client_id));
+        }
+
+        let response: Value = self
+            .http
+            .post(url)
+            .form(&form)
+            .send()
+            .await?
+            .error_for_status()
+            .context("OAuth2 token request failed")?
+            .json()
+            .await?;
+        string_property(&response, "access_token")
+            .map(str::to_string)
+            .context("OAuth2 token response has no access_token")
+    }
+
+    async fn get_json(&self, url: Url) -> Result<Value> {
+        let mut request = self
+            .http
+            .get(url.clone())
+            .header("X-Iceberg-Access-Delegation", "vended-credentials");
+        if let Some(ref token) = self.token {
+            request = request.bearer_auth(token);
+        } else if let Some(ref user) = self.simple_auth_user {
+            request = request.basic_auth(user, None::<&str>);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(request_failed(url.path(), status, &body));
+        }
+        Ok(response.json().await?)
+    }
+
+    fn catalog_path<'a>(&'a self, segments: &[&'a str]) -> Vec<&'a str> {
+        let mut path = vec!["v1"];
+        if let Some(ref prefix) = self.prefix {
+            path.push(prefix);
+        }
+        path.extend(segments);
+        path
+    }
+
+    /// GET a paginated listing, collecting `key` from every page
+    async fn get_paginated(
+        &self,
+        segments: &[&str],
+        parent: Option<&str>,
+        key: &str,
+    ) -> Result<Vec<Value>> {
+        let mut items = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut url = endpoint(&self.uri, &self.catalog_path(segments))?;
+            if let Some(parent) = parent {
+                url.query_pairs_mut().append_pair("parent", parent);
+            }
+            if let Some(ref token) = page_token {
+                url.query_pairs_mut().append_pair("pageToken", token);
+            }
+            let page = self.get_json(url).await?;
+            if let Some(values) = page.get(key).and_then(|v| v.as_array()) {
+                items.extend(values.iter().cloned());
+            }
+            page_token = string_property(&page, "next-page-token").map(str::to_string);
+            if page_token.is_none() {
+                return Ok(items);
+            }
+        }
+    }
+}
+
+impl Catalog for RestCatalogClient {
+    fn namespaces<'a>(&'a self, parent: &'a [String]) -> BoxFuture<'a, Result<Vec<Vec<String>>>> {
+        async move {
+            let joined = parent.join(NAMESPACE_SEPARATOR);
+            let parent = (!parent.is_empty()).then_some(joined.as_str());
+            let namespaces = self
+                .get_paginated(&["namespaces"], parent, "namespaces")
+                .await?;
+            Ok(namespaces
+                .iter()
+                .map(|namespace| {
+                    namespace
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|level| level.as_str().map(str::to_string))
+                        .collect::<Vec<String>>()
+                })
+                .filter(|levels| !levels.is_empty())
+                .collect())
+        }
+        .boxed()
+    }
+
+    fn tables<'a>(&'a self, namespace: &'a [String]) -> BoxFuture<'a, Result<Vec<String>>> {
+        async move {
+            let joined = namespace.join(NAMESPACE_SEPARATOR);
+            let identifiers = self
+                .get_paginated(
+                    &["namespaces", joined.as_str(), "tables"],
+                    None,
+                    "identifiers",
+                )
+                .await?;
+            Ok(identifiers
+                .iter()
+                .filter_map(|identifier| string_property(identifier, "name").map(str::to_string))
+                .collect())
+        }
+        .boxed()
+    }
+
+    fn load_table<'a>(&'a self, identifier: &'a str) -> BoxFuture<'a, Result<LoadedTable>> {
+        async move {
+            let (namespace, table) = parse_table_identifier(identifier)?;
+            let namespace = namespace.join(NAMESPACE_SEPARATOR);
+            let segments =
+                self.catalog_path(&["namespaces", namespace.as_str(), "tables", table.as_str()]);
+            let response = self.get_json(endpoint(&self.uri, &segments)?).await?;
+            parse_load_table(&response)
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_table_identifier() {
+        let (namespace, table) = parse_table_identifier("analytics.web.events").unwrap();
+        assert_eq!(namespace, vec!["analytics", "web"]);
+        assert_eq!(table, "events");
+        assert!(parse_table_identifier("events").is_err());
+        assert!(parse_table_identifier("analytics.").is_err());
+    }
+
+    #[test]
+    fn test_table_pattern() {
+        let pattern = table_pattern("analytics.*").unwrap();
+        assert!(pattern.is_match("analytics.events"));
+        assert!(!pattern.is_match("analytics.web.events"));
+        assert!(!pattern.is_match("analyticsXevents"));
+        assert!(table_pattern("*.event?").unwrap().is_match("web.events"));
+
+        assert_eq!(
+            literal_namespace("analytics.web.*"),
+            vec!["analytics", "web"]
+        );
+        assert_eq!(literal_namespace("analytics.*.events"), vec!["analytics"]);
+        assert!(literal_namespace("*.events").is_empty());
+    }
+
+    /// Catalog of `catalog.schema.table` tables, as Unity Catalog lays them out
+    struct FakeCatalog(Vec<&'static str>);
+
+    impl Catalog for FakeCatalog {
+        fn namespaces<'a>(
+            &'a self,
+            parent: &'a [String],
+        ) -> BoxFuture<'a, Result<Vec<Vec<String>>>> {
+            let mut namespaces: Vec<Vec<String>> = self
+                .0
+                .iter()
+                .map(|table| {
+                    table
+                        .split('.')
+                        .take(parent.len() + 1)
+                        .map(str::to_string)
+                        .collect()
+                })
+                .filter(|namespace: &Vec<String>| {
+                    namespace.len() == parent.len() + 1 && namespace.starts_with(parent)
+                })
+                .collect();
+            namespaces.dedup();
+            futures::future::ready(Ok(namespaces)).boxed()
+        }
+
+        fn tables<'a>(&'a self, namespace: &'a [String]) -> BoxFuture<'a, Result<Vec<String>>> {
+            let prefix = format!("{}.", namespace.join("."));
+            let tables = self
+                .0
+                .iter()
+                .filter_map(|table| table.strip_prefix(&prefix))
+                .filter(|name| !name.contains('.'))
+                .map(str::to_string)
+                .collect();
+            futures::future::ready(Ok(tables)).boxed()
+        }
+
+        fn load_table<'a>(&'a self, identifier: &'a str) -> BoxFuture<'a, Result<LoadedTable>> {
+            futures::future::ready(Err(anyhow::anyhow!("no table {}", identifier))).boxed()
+        }
+    }
+
+    #[test]
+    fn test_find_tables() {
+        let catalog = FakeCatalog(vec![
+            "main.analytics.events",
+            "main.analytics.orders",
+            "main.raw.clicks",
+            "dev.analytics.events",
+        ]);
+        let find = |pattern: &str| futures::executor::block_on(find_tables(&catalog, pattern));
+        assert_eq!(
+            find("main.*.*").unwrap(),
+            vec![
+                "main.analytics.events",
+                "main.analytics.orders",
+                "main.raw.clicks"
+            ]
+        );
+        assert_eq!(
+            find("*.analytics.events").unwrap(),
+            vec!["dev.analytics.events", "main.analytics.events"]
+        );
+        assert!(find("main.*").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_catalog_flavors() {
+        let polaris = RestCatalogConfig::polaris(
+            "https://polaris.example.com/",
+            "analytics".to_string(),
+            "id",
+            "secret",
+            "ALL",
+        );
+        assert_eq!(polaris.uri, "https://polaris.example.com/api/catalog");
+        assert_eq!(polaris.warehouse.as_deref(), Some("analytics"));
+        assert_eq!(polaris.credential.as_deref(), Some("id:secret"));
+        assert_eq!(polaris.scope.as_deref(), Some("PRINCIPAL_ROLE:ALL"));
+
+        let gravitino = RestCatalogConfig::gravitino(
+            "http://gravitino:9001/iceberg/",
+            None,
+            Some("etl".to_string()),
+            None,
+            None,
+            None,
+            None,
+        );
+        assert_eq!(gravitino.uri, "http://gravitino:9001/iceberg");
+        assert_eq!(gravitino.simple_auth_user.as_deref(), Some("etl"));
+        assert_eq!(gravitino.flavor, "gravitino");
+    }
+
+    #[test]
+    fn test_endpoint_encodes_namespace_levels() {
+        let base = Url::parse("https://catalog.example.com/api/catalog/").unwrap();
+        let url = endpoint(&base, &["v1", "namespaces", "a\u{1f}b", "tables", "t"]).unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://catalog.example.com/api/catalog/v1/namespaces/a%1Fb/tables/t"
+        );
+        assert_eq!(split_credential("id:secret"), (Some("id"), "secret"));
+        assert_eq!(split_credential("secret"), (None, "secret"));
+    }
+
+    #[test]
+    fn test_parse_load_table_prefers_most_specific_storage_credentials() {
+        let response = json!({
+            "metadata-location": "s3://lake/analytics/events/metadata/00001.metadata.json",
+            "metadata": { "location": "s3://lake/analytics/events" },
+            "config": {
+                "s3.access-key-id": "table-key",
+                "s3.secret-access-key": "table-secret",
+            },
+            "storage-credentials": [
+                { "prefix": "s3://lake/", "config": {
+                    "s3.access-key-id": "bucket-key", "s3.secret-access-key": "bucket-secret",
+                }},
+                { "prefix": "s3://lake/analytics/", "config": {
+                    "s3.access-key-id": "ns-key", "s3.secret-access-key": "ns-secret",
+                    "s3.session-token": "ns-token", "client.region": "eu-west-1",
+                }},
+                { "prefix": "s3://other/", "config": {
+                    "s3.access-key-id": "other-key", "s3.secret-access-key": "other-secret",
+                }},
+            ],
+        });
+
+        let table = parse_load_table(&response).unwrap();
+        assert_eq!(table.location, "s3://lake/analytics/events");
+        let credentials = table.credentials.unwrap();
+        assert_eq!(credentials.access_key_id, "ns-key");
+        assert_eq!(credentials.session_token.as_deref(), Some("ns-token"));
+        assert_eq!(credentials.region.as_deref(), Some("eu-west-1"));
+
+        let fallback = vended_credentials(&response, "s3://elsewhere/t").unwrap();
+        assert_eq!(fallback.access_key_id, "table-key");
+        assert!(vended_credentials(&json!({}), "s3://lake/t").is_none());
+        assert!(parse_load_table(&json!({ "metadata": {} })).is_err());
+    }
+}
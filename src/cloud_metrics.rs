@@ -0,0 +1,322 @@
+//! Metrics publishing (`cloudwatch_namespace=`, `gcp_project=`): the key numbers of each
+//! report pushed to CloudWatch or Google Cloud Monitoring, one data point per metric with the
+//! table's path, format and tags as dimensions, so existing alarms and dashboards can use
+//! them. CloudWatch takes the first tags by name up to its dimension limit.
+//!
+//! CloudWatch gets a SigV4-signed `PutMetricData` call in the region and with the
+//! credentials of the analysis's S3 client. Cloud Monitoring gets `timeSeries.create` calls
+//! for `custom.googleapis.com/drainage/<metric>` on the `global` resource, authorized with
+//! `gcp_access_token` or, without one, the token of the instance's service account from the
+//! metadata server. A failed publish is a warning on the report.
+
+use crate::config::AnalysisConfig;
+use crate::network::sign_aws_request;
+use crate::s3_client::S3ClientWrapper;
+use crate::stream::EventSink;
+use crate::types::HealthReport;
+use anyhow::{Context, Result};
+use aws_credential_types::cache::ProvideCachedCredentials;
+use aws_credential_types::Credentials;
+use serde_json::{json, Value};
+use std::time::SystemTime;
+
+const GCP_METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+
+/// CloudWatch's limit on dimensions per metric
+const MAX_CLOUDWATCH_DIMENSIONS: usize = 30;
+
+/// The report's tags sorted by name, bar those named like the `reserved` dimensions
+fn tags<'a>(report: &'a HealthReport, reserved: [&str; 2]) -> Vec<(&'a str, &'a str)> {
+    let mut tags: Vec<(&str, &str)> = report
+        .tags
+        .iter()
+        .flatten()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .filter(|(key, _)| !reserved.contains(key))
+        .collect();
+    tags.sort();
+    tags
+}
+
+/// (name, CloudWatch unit, value): the published metrics of a report
+fn metric_values(report: &HealthReport) -> Vec<(&'static str, &'static str, f64)> {
+    let metrics = &report.metrics;
+    let small_file_ratio = match metrics.total_files {
+        0 => 0.0,
+        files => metrics.file_size_distribution.small_files as f64 / files as f64,
+    };
+    let mut values = vec![
+        ("health_score", "None", report.health_score),
+        ("total_files", "Count", metrics.total_files as f64),
+        ("total_size_bytes", "Bytes", metrics.total_size_bytes as f64),
+        (
+            "unreferenced_files",
+            "Count",
+            report.unreferenced_file_count() as f64,
+        ),
+        (
+            "unreferenced_size_bytes",
+            "Bytes",
+            metrics.unreferenced_size_bytes as f64,
+        ),
+        ("small_file_ratio", "None", small_file_ratio),
+        ("avg_file_size_bytes", "Bytes", metrics.avg_file_size_bytes),
+        ("partition_count", "Count", metrics.partition_count as f64),
+        (
+            "recommendation_count",
+            "Count",
+            metrics.recommendations.len() as f64,
+        ),
+    ];
+    if let Some(ref sub_scores) = metrics.sub_scores {
+        values.extend([
+            ("storage_cost_score", "None", sub_scores.storage_cost_score),
+            (
+                "query_performance_score",
+                "None",
+                sub_scores.query_performance_score,
+            ),
+            ("reliability_score", "None", sub_scores.reliability_score),
+        ]);
+    }
+    values
+}
+
+/// The form body of a `PutMetricData` call with the report's metrics under `namespace`
+fn put_metric_data_body(namespace: &str, report: &HealthReport) -> String {
+    let mut form = url::form_urlencoded::Serializer::new(String::new());
+    form.append_pair("Action", "PutMetricData")
+        .append_pair("Version", "2010-08-01")
+        .append_pair("Namespace", namespace);
+    let timestamp = report.analysis_timestamp.to_rfc3339();
+    let mut dimensions = vec![
+        ("TablePath", report.table_path.as_str()),
+        ("TableType", report.table_type.as_str()),
+    ];
+    dimensions.extend(tags(report, ["TablePath", "TableType"]));
+    dimensions.truncate(MAX_CLOUDWATCH_DIMENSIONS);
+    for (i, (name, unit, value)) in metric_values(report).into_iter().enumerate() {
+        let member = format!("MetricData.member.{}", i + 1);
+        form.append_pair(&format!("{}.MetricName", member), name)
+            .append_pair(&format!("{}.Unit", member), unit)
+            .append_pair(&format!("{}.Value", member), &value.to_string())
+            .append_pair(&format!("{}.Timestamp", member), &timestamp);
+        for (j, (dimension, value)) in dimensions.iter().enumerate() {
+            let prefix = format!("{}.Dimensions.member.{}", member, j + 1);
+            form.append_pair(&format!("{}.Name", prefix), dimension)
+                .append_pair(&format!("{}.Value", prefix), value);
+        }
+    }
+    form.finish()
+}
+
+/// A `PutMetricData` request to `region`, signed with `credentials` at `time`
+fn signed_put_metric_data(
+    region: &str,
+    credentials: &Credentials,
+    body: String,
+    time: SystemTime,
+) -> Result<hyper::http::Request<String>> {
+    let mut request =
+        hyper::http::Request::post(format!("https://monitoring.{}.amazonaws.com/", region))
+            .header(
+                hyper::header::CONTENT_TYPE,
+                "application/x-www-form-urlencoded; charset=utf-8",
+            )
+            .body(body)?;
+    sign_aws_request(&mut request, "monitoring", region, credentials, time)?;
+    Ok(request)
+}
+
+async fn publish_cloudwatch(
+    s3_client: &S3ClientWrapper,
+    config: &AnalysisConfig,
+    namespace: &str,
+    report: &HealthReport,
+) -> Result<()> {
+    let conf = s3_client.client.conf();
+    let region = conf.region().context("No AWS region for CloudWatch")?;
+    let credentials = conf
+        .credentials_cache()
+        .provide_cached_credentials()
+        .await
+        .context("No AWS credentials for CloudWatch")?;
+    let request = signed_put_metric_data(
+        region.as_ref(),
+        &credentials,
+        put_metric_data_body(namespace, report),
+        SystemTime::now(),
+    )?;
+    config
+        .network
+        .http_client()?
+        .execute(reqwest::Request::try_from(request)?)
+        .await
+        .and_then(|response| response.error_for_status())
+        .context("CloudWatch PutMetricData failed")?;
+    Ok(())
+}
+
+/// The `timeSeries.create` body with the report's metrics in `project`
+fn time_series_body(project: &str, report: &HealthReport) -> Value {
+    let end_time = report.analysis_timestamp.to_rfc3339();
+    let mut labels = json!({ "table_path": report.table_path, "table_type": report.table_type });
+    for (key, value) in tags(report, ["table_path", "table_type"]) {
+        labels[key] = json!(value);
+    }
+    let series: Vec<Value> = metric_values(report)
+        .into_iter()
+        .map(|(name, _, value)| {
+            json!({
+                "metric": {
+                    "type": format!("custom.googleapis.com/drainage/{}", name),
+                    "labels": labels,
+                },
+                "resource": { "type": "global", "labels": { "project_id": project } },
+                "points": [{ "interval": { "endTime": end_time }, "value": { "doubleValue": value } }],
+            })
+        })
+        .collect();
+    json!({ "timeSeries": series })
+}
+
+async fn publish_cloud_monitoring(
+    config: &AnalysisConfig,
+    project: &str,
+    report: &HealthReport,
+) -> Result<()> {
+    let http = config.network.http_client()?;
+    let token = match config.gcp_access_token {
+        Some(ref token) => token.clone(),
+        None => {
+            let response: Value = http
+                .get(GCP_METADATA_TOKEN_URL)
+                .header("Metadata-Flavor", "Google")
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .context("No gcp_access_token, and no token from the GCP metadata server")?
+                .json()
+                .await?;
+            let token = response["access_token"]
+                .as_str()
+                .context("No access_token from the GCP metadata server")?;
+            crate::sanitize::register(token);
+            token.to_string()
+        }
+    };
+    http.post(format!(
+        "https://monitoring.googleapis.com/v3/projects/{}/timeSeries",
+        project
+    ))
+    .bearer_auth(token)
+    .json(&time_series_body(project, report))
+    .send()
+    .await
+    .and_then(|response| response.error_for_status())
+    .context("Cloud Monitoring timeSeries.create failed")?;
+    Ok(())
+}
+
+/// Push the report's metrics to the configured CloudWatch namespace and GCP project, if any
+pub async fn publish(
+    s3_client: &S3ClientWrapper,
+    config: &AnalysisConfig,
+    events: &EventSink,
+    report: &HealthReport,
+) {
+    if let Some(ref namespace) = config.cloudwatch_namespace {
+        if let Err(e) = publish_cloudwatch(s3_client, config, namespace, report).await {
+            events.warning(format!("{:#}", e));
+        }
+    }
+    if let Some(ref project) = config.gcp_project {
+        if let Err(e) = publish_cloud_monitoring(config, project, report).await {
+            events.warning(format!("{:#}", e));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_metrics_are_rendered_for_each_service() {
+        let mut report = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        report.health_score = 0.8;
+        report.metrics.total_files = 4;
+        report.metrics.file_size_distribution.small_files = 1;
+        report.analysis_timestamp = "2024-06-01T00:00:00Z".parse().unwrap();
+        let mut tags: HashMap<String, String> = (0..40)
+            .map(|i| (format!("tag_{:02}", i), i.to_string()))
+            .collect();
+        tags.insert("TableType".to_string(), "shadowed".to_string());
+        tags.insert("team".to_string(), "growth".to_string());
+        report.tags = Some(tags);
+
+        let body = put_metric_data_body("Drainage", &report);
+        let pairs: Vec<(String, String)> = url::form_urlencoded::parse(body.as_bytes())
+            .into_owned()
+            .collect();
+        let get = |name: &str| {
+            pairs
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(get("Namespace"), Some("Drainage"));
+        assert_eq!(get("MetricData.member.1.MetricName"), Some("health_score"));
+        assert_eq!(get("MetricData.member.1.Value"), Some("0.8"));
+        assert_eq!(
+            get("MetricData.member.6.MetricName"),
+            Some("small_file_ratio")
+        );
+        assert_eq!(get("MetricData.member.6.Value"), Some("0.25"));
+        assert_eq!(
+            get("MetricData.member.3.Dimensions.member.1.Value"),
+            Some("s3://lake/events")
+        );
+        assert_eq!(get("MetricData.member.10.MetricName"), None);
+        // Path, format and the first 28 tags by name
+        assert_eq!(
+            get("MetricData.member.1.Dimensions.member.3.Name"),
+            Some("tag_00")
+        );
+        assert_eq!(
+            get("MetricData.member.1.Dimensions.member.30.Name"),
+            Some("tag_27")
+        );
+        assert_eq!(get("MetricData.member.1.Dimensions.member.31.Name"), None);
+
+        let credentials = Credentials::new("AKIDEXAMPLE", "secret", None, None, "test");
+        let request =
+            signed_put_metric_data("eu-west-1", &credentials, body, SystemTime::UNIX_EPOCH)
+                .unwrap();
+        assert_eq!(request.uri(), "https://monitoring.eu-west-1.amazonaws.com/");
+        let authorization = request.headers()["authorization"].to_str().unwrap();
+        assert!(authorization.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/19700101/eu-west-1/monitoring/aws4_request"
+        ));
+
+        let series = time_series_body("ops-project", &report);
+        assert_eq!(
+            series["timeSeries"][0]["metric"]["type"],
+            "custom.googleapis.com/drainage/health_score"
+        );
+        assert_eq!(
+            series["timeSeries"][0]["points"][0]["interval"]["endTime"],
+            "2024-06-01T00:00:00+00:00"
+        );
+        let labels = &series["timeSeries"][0]["metric"]["labels"];
+        assert_eq!(labels["table_type"], "delta");
+        assert_eq!(labels["team"], "growth");
+        assert_eq!(labels["tag_39"], "39");
+        assert_eq!(
+            series["timeSeries"][1]["resource"]["labels"]["project_id"],
+            "ops-project"
+        );
+    }
+}
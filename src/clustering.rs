@@ -0,0 +1,194 @@
+//! Z-order / clustering column candidates from file statistics: the min and max each data
+//! file records per column (Delta `stats`, Iceberg `lower-bounds` / `upper-bounds`).
+//!
+//! A column gains from Z-ordering when its files' value ranges overlap, so a filter on it
+//! can skip few files, and it has enough distinct values for clustering to separate them
+//! (a handful of values is better served by partitioning). Overlap is the share of other
+//! files whose range meets a file's range, averaged over files; cardinality is the number
+//! of distinct bounds against the two per file a column of unique values would have.
+
+use crate::types::ZOrderCandidate;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Min and max of each column in one data file
+pub type ColumnBounds = HashMap<String, (Value, Value)>;
+
+/// Columns scoring below this already skip well or have too few values to cluster
+const MIN_Z_ORDER_SCORE: f64 = 0.3;
+/// Z-ordering on more columns dilutes the clustering of each
+pub const MAX_Z_ORDER_COLUMNS: usize = 4;
+
+/// Bounds in a Delta `stats` document: `minValues` and `maxValues`, nested columns
+/// flattened as `parent.child`
+pub fn delta_bounds(stats: &Value) -> ColumnBounds {
+    fn flatten(prefix: &str, value: &Value, into: &mut HashMap<String, Value>) {
+        match value {
+            Value::Object(fields) => {
+                for (name, value) in fields {
+                    let name = match prefix {
+                        "" => name.clone(),
+                        prefix => format!("{}.{}", prefix, name),
+                    };
+                    flatten(&name, value, into);
+                }
+            }
+            value => {
+                into.insert(prefix.to_string(), value.clone());
+            }
+        }
+    }
+    let (mut mins, mut maxes) = (HashMap::new(), HashMap::new());
+    if let Some(min_values) = stats.get("minValues") {
+        flatten("", min_values, &mut mins);
+    }
+    if let Some(max_values) = stats.get("maxValues") {
+        flatten("", max_values, &mut maxes);
+    }
+    mins.into_iter()
+        .filter_map(|(column, min)| Some((column.clone(), (min, maxes.remove(&column)?))))
+        .collect()
+}
+
+/// Bounds of an Iceberg manifest's data file, keyed as written (field ids or names)
+pub fn iceberg_bounds(data_file: &Value) -> ColumnBounds {
+    let bounds = |kebab: &str, snake: &str| {
+        data_file
+            .get(kebab)
+            .or_else(|| data_file.get(snake))
+            .and_then(|b| b.as_object())
+    };
+    let (Some(lower), Some(upper)) = (
+        bounds("lower-bounds", "lower_bounds"),
+        bounds("upper-bounds", "upper_bounds"),
+    ) else {
+        return ColumnBounds::new();
+    };
+    lower
+        .iter()
+        .filter_map(|(column, min)| {
+            Some((column.clone(), (min.clone(), upper.get(column)?.clone())))
+        })
+        .collect()
+}
+
+/// (overlap, distinct bounds) of file ranges of any ordered type
+fn spread<T: PartialOrd + Clone>(ranges: &[(T, T)]) -> (f64, usize) {
+    let order = |a: &T, b: &T| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal);
+    let mut mins: Vec<T> = ranges.iter().map(|(min, _)| min.clone()).collect();
+    let mut maxes: Vec<T> = ranges.iter().map(|(_, max)| max.clone()).collect();
+    mins.sort_by(order);
+    maxes.sort_by(order);
+
+    // A file's range misses those starting after its max and ending before its min
+    let files = ranges.len();
+    let overlapping: usize = ranges
+        .iter()
+        .map(|(min, max)| {
+            let after = files - mins.partition_point(|m| m <= max);
+            let before = maxes.partition_point(|m| m < min);
+            files - after - before - 1
+        })
+        .sum();
+    let overlap = overlapping as f64 / (files * (files - 1)) as f64;
+
+    let mut bounds: Vec<T> = mins.into_iter().chain(maxes).collect();
+    bounds.sort_by(order);
+    bounds.dedup_by(|a, b| a == b);
+    (overlap, bounds.len())
+}
+
+/// Candidate columns ranked by how much Z-ordering would help, best first
+pub fn rank_candidates<'a>(
+    files: impl IntoIterator<Item = &'a ColumnBounds>,
+) -> Vec<ZOrderCandidate> {
+    let mut columns: BTreeMap<&str, Vec<&(Value, Value)>> = BTreeMap::new();
+    for file in files {
+        for (column, range) in file {
+            columns.entry(column).or_default().push(range);
+        }
+    }
+
+    let mut candidates: Vec<ZOrderCandidate> = columns
+        .into_iter()
+        .filter(|(_, ranges)| ranges.len() > 1)
+        .filter_map(|(column, ranges)| {
+            let numbers: Option<Vec<(f64, f64)>> = ranges
+                .iter()
+                .map(|(min, max)| Some((min.as_f64()?, max.as_f64()?)))
+                .collect();
+            let texts = || -> Option<Vec<(&str, &str)>> {
+                ranges
+                    .iter()
+                    .map(|(min, max)| Some((min.as_str()?, max.as_str()?)))
+                    .collect()
+            };
+            // Other types (booleans, nulls of all-null files) aren't worth clustering on
+            let (overlap, distinct) = match numbers {
+                Some(numbers) => spread(&numbers),
+                None => spread(&texts()?),
+            };
+            let cardinality = (distinct as f64 / (2 * ranges.len()) as f64).min(1.0);
+            Some(ZOrderCandidate {
+                column: column.to_string(),
+                files_with_stats: ranges.len(),
+                distinct_bound_values: distinct,
+                file_overlap_ratio: overlap,
+                score: overlap * cardinality,
+            })
+        })
+        .filter(|candidate| candidate.score >= MIN_Z_ORDER_SCORE)
+        .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.column.cmp(&b.column)));
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_overlapping_high_cardinality_columns_rank_first() {
+        // `user_id` is scattered across files, `event_time` was written in order and
+        // `country` holds two values
+        let files: Vec<ColumnBounds> = (0..10)
+            .map(|i| {
+                let stats = json!({
+                    "numRecords": 100,
+                    "minValues": {
+                        "user_id": i,
+                        "event_time": format!("2024-01-{:02}T00:00:00Z", i + 1),
+                        "country": "DE",
+                        "payload": { "size": 10 + i },
+                        "is_test": false,
+                    },
+                    "maxValues": {
+                        "user_id": 1000 - i,
+                        "event_time": format!("2024-01-{:02}T23:59:59Z", i + 1),
+                        "country": "US",
+                        "payload": { "size": 500 + i },
+                        "is_test": true,
+                    },
+                });
+                delta_bounds(&stats)
+            })
+            .collect();
+        assert_eq!(files[0]["payload.size"], (json!(10), json!(500)));
+
+        let candidates = rank_candidates(&files);
+        let columns: Vec<&str> = candidates.iter().map(|c| c.column.as_str()).collect();
+        assert_eq!(columns, ["payload.size", "user_id"]);
+        assert_eq!(candidates[0].file_overlap_ratio, 1.0);
+        assert_eq!(candidates[0].distinct_bound_values, 20);
+
+        let data_file = json!({
+            "lower-bounds": { "1": 5, "2": "a" },
+            "upper-bounds": { "1": 9 },
+        });
+        assert_eq!(
+            iceberg_bounds(&data_file),
+            ColumnBounds::from([("1".to_string(), (json!(5), json!(9)))])
+        );
+    }
+}
@@ -0,0 +1,1164 @@
+use crate::network::NetworkSettings;
+use crate::provider::StorageProvider;
+use crate::s3_client::ObjectInfo;
+use crate::types::HealthReport;
+use chrono::{DateTime, Utc};
+use pyo3::prelude::*;
+use pyo3::types::{IntoPyDict, PyCFunction, PyDict, PyList, PyTuple};
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Grace window applied to unreferenced files by default. Writers upload data files
+/// before committing them, so very recent unreferenced files are usually in flight.
+pub const DEFAULT_UNREFERENCED_GRACE_PERIOD_HOURS: f64 = 24.0;
+
+/// Number of Parquet footers read by a deep scan unless configured otherwise
+pub const DEFAULT_FOOTER_SAMPLE_SIZE: usize = 20;
+
+const MB: u64 = 1024 * 1024;
+
+/// Upper bounds of the small, medium and large data file buckets; larger files are very large
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileSizeBuckets {
+    pub small_bytes: u64,
+    pub medium_bytes: u64,
+    pub large_bytes: u64,
+}
+
+impl Default for FileSizeBuckets {
+    fn default() -> Self {
+        Self {
+            small_bytes: 16 * MB,
+            medium_bytes: 128 * MB,
+            large_bytes: 1024 * MB,
+        }
+    }
+}
+
+impl FileSizeBuckets {
+    /// Buckets from three increasing bounds in MB, e.g. `[128, 1024, 4096]` for 1GB targets
+    pub fn from_mb(bounds: &[f64]) -> Result<Self, String> {
+        let invalid = || {
+            format!(
+                "Invalid file_size_buckets_mb {:?}: expected three increasing sizes in MB",
+                bounds
+            )
+        };
+        match *bounds {
+            [small, medium, large] if 0.0 < small && small < medium && medium < large => {
+                let bytes = |mb: f64| (mb * MB as f64) as u64;
+                Ok(Self {
+                    small_bytes: bytes(small),
+                    medium_bytes: bytes(medium),
+                    large_bytes: bytes(large),
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Throughput assumptions used to estimate how long remediations take on an engine, and
+/// how many files one of its queries can plan
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineProfile {
+    pub name: String,
+    /// Compressed MB one core reads, rewrites and writes back per second
+    pub rewrite_mb_per_core_second: f64,
+    /// Cores available to a remediation job
+    pub cores: usize,
+    /// Object deletions per second (batched delete requests)
+    pub deletes_per_second: f64,
+    /// Data files one query can scan before planning fails or stalls; None: no limit
+    pub max_files_per_query: Option<usize>,
+}
+
+impl EngineProfile {
+    /// Defaults for a known engine: "spark", "trino", "athena" or "duckdb"
+    pub fn named(name: &str) -> Option<Self> {
+        // File limits are rules of thumb: a Spark driver holds the status of every file it
+        // plans (about 1M files exhaust a default-sized driver), Athena queries over ~100k
+        // files tend to exhaust resources or time out while planning, and DuckDB lists and
+        // opens every file from a single process.
+        let (rewrite_mb_per_core_second, cores, deletes_per_second, max_files_per_query) =
+            match name.to_lowercase().as_str() {
+                "spark" => (16.0, 64, 1000.0, 1_000_000),
+                "trino" => (24.0, 64, 500.0, 1_000_000),
+                "athena" => (24.0, 64, 500.0, 100_000),
+                "duckdb" => (48.0, 8, 200.0, 100_000),
+                _ => return None,
+            };
+        Some(Self {
+            name: name.to_lowercase(),
+            rewrite_mb_per_core_second,
+            cores,
+            deletes_per_second,
+            max_files_per_query: Some(max_files_per_query),
+        })
+    }
+}
+
+impl Default for EngineProfile {
+    fn default() -> Self {
+        Self::named("spark").expect("spark is a known engine")
+    }
+}
+
+/// Monthly storage prices per GB by storage class, used by the cost estimate, and the
+/// request prices used to estimate what an analysis itself cost
+#[derive(Debug, Clone, PartialEq)]
+pub struct PricingProfile {
+    pub name: String,                       // e.g. "aws:us-east-1"
+    pub currency: String,                   // e.g. "USD"; all prices are in it
+    pub per_gb_month: HashMap<String, f64>, // by storage class
+    pub list_per_1000: f64,                 // LIST requests (GCS class A)
+    pub get_per_1000: f64,                  // GET and HEAD requests (GCS class B)
+}
+
+impl PricingProfile {
+    /// List prices (first tier) for a provider and region in the bundled catalog:
+    /// "aws:us-east-1", "aws:us-west-2", "aws:eu-west-1", "aws:eu-central-1",
+    /// "gcp:us-central1" or "gcp:europe-west1"
+    pub fn named(name: &str) -> Option<Self> {
+        PricingCatalog::bundled().profile(name)
+    }
+
+    /// Price of a storage class; unknown classes are priced as STANDARD
+    pub fn price(&self, storage_class: &str) -> f64 {
+        self.per_gb_month
+            .get(storage_class)
+            .or_else(|| self.per_gb_month.get("STANDARD"))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// `amount` in `currency`: "$1.50" for USD, "1.50 EUR" otherwise
+pub fn format_amount(amount: f64, currency: &str, decimals: usize) -> String {
+    match currency {
+        "USD" => format!("${:.*}", decimals, amount),
+        currency => format!("{:.*} {}", decimals, amount, currency),
+    }
+}
+
+/// The bundled list prices (`pricing.toml`)
+const BUNDLED_PRICING: &str = include_str!("pricing.toml");
+
+/// One profile of a pricing catalog; fields left out keep the value of the profile it
+/// overrides
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CatalogEntry {
+    currency: Option<String>,
+    list_per_1000: Option<f64>,
+    get_per_1000: Option<f64>,
+    #[serde(default)]
+    storage: HashMap<String, f64>,
+}
+
+/// Pricing profiles by name: the bundled list prices, with any user catalog merged over
+/// them so negotiated rates and other currencies replace the defaults
+#[derive(Debug, Clone)]
+pub struct PricingCatalog {
+    profiles: HashMap<String, PricingProfile>,
+}
+
+impl PricingCatalog {
+    pub fn bundled() -> &'static Self {
+        static BUNDLED: std::sync::OnceLock<PricingCatalog> = std::sync::OnceLock::new();
+        BUNDLED.get_or_init(|| {
+            let mut catalog = Self {
+                profiles: HashMap::new(),
+            };
+            catalog
+                .merge_toml(BUNDLED_PRICING)
+                .expect("the bundled pricing catalog is valid");
+            catalog
+        })
+    }
+
+    fn merge(&mut self, entries: HashMap<String, CatalogEntry>) -> Result<(), String> {
+        for (name, entry) in entries {
+            let name = name.to_lowercase();
+            let profile = self
+                .profiles
+                .entry(name.clone())
+                .or_insert_with(|| PricingProfile {
+                    name: name.clone(),
+                    currency: "USD".to_string(),
+                    per_gb_month: HashMap::new(),
+                    list_per_1000: 0.0,
+                    get_per_1000: 0.0,
+                });
+            if let Some(currency) = entry.currency {
+                profile.currency = currency.to_uppercase();
+            }
+            if let Some(price) = entry.list_per_1000 {
+                profile.list_per_1000 = price;
+            }
+            if let Some(price) = entry.get_per_1000 {
+                profile.get_per_1000 = price;
+            }
+            for (class, price) in entry.storage {
+                profile.per_gb_month.insert(class.to_uppercase(), price);
+            }
+            if !profile.per_gb_month.contains_key("STANDARD") {
+                return Err(format!("Pricing profile {} has no STANDARD price", name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Merge profiles from a catalog in the `pricing.toml` format
+    pub fn merge_toml(&mut self, text: &str) -> Result<(), String> {
+        let entries: HashMap<String, CatalogEntry> =
+            toml::from_str(text).map_err(|e| format!("Invalid pricing catalog: {}", e))?;
+        self.merge(entries)
+    }
+
+    /// Merge the `pricing_catalog` option: the path of a TOML catalog, or the catalog itself
+    /// as an object
+    pub fn merge_option(&mut self, value: &serde_json::Value) -> Result<(), String> {
+        match value {
+            serde_json::Value::String(path) => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Cannot read pricing catalog {}: {}", path, e))?;
+                self.merge_toml(&text)
+            }
+            value => {
+                let entries: HashMap<String, CatalogEntry> = serde_json::from_value(value.clone())
+                    .map_err(|e| format!("Invalid pricing catalog: {}", e))?;
+                self.merge(entries)
+            }
+        }
+    }
+
+    pub fn profile(&self, name: &str) -> Option<PricingProfile> {
+        self.profiles.get(&name.to_lowercase()).cloned()
+    }
+
+    /// The profile `name` (the default profile when None), from the bundled catalog with
+    /// the `pricing_catalog` option merged over it
+    pub fn resolve(
+        catalog: Option<&serde_json::Value>,
+        name: Option<&str>,
+    ) -> Result<PricingProfile, String> {
+        let name = name.unwrap_or(DEFAULT_PRICING);
+        let profile = match catalog {
+            Some(catalog) => {
+                let mut merged = Self::bundled().clone();
+                merged.merge_option(catalog)?;
+                merged.profile(name)
+            }
+            None => Self::bundled().profile(name),
+        };
+        profile.ok_or_else(|| format!("Unknown pricing: {}", name))
+    }
+}
+
+const DEFAULT_PRICING: &str = "aws:us-east-1";
+
+impl Default for PricingProfile {
+    fn default() -> Self {
+        Self::named(DEFAULT_PRICING).expect("the default pricing is in the bundled catalog")
+    }
+}
+
+/// Canonical name of a table format: "delta" or "iceberg"
+pub fn table_format(name: &str) -> Option<&'static str> {
+    match name.to_lowercase().as_str() {
+        "delta" | "delta_lake" => Some("delta"),
+        "iceberg" | "apache_iceberg" => Some("iceberg"),
+        _ => None,
+    }
+}
+
+/// User additions to table format detection, for layouts the built-in checks get wrong
+#[derive(Debug, Clone, Default)]
+pub struct DetectionRules {
+    /// (format, pattern on table-relative keys); matches take precedence over built-in checks
+    pub signatures: Vec<(&'static str, Regex)>,
+    /// Format to pick when files of both are present (Delta UniForm, converted tables)
+    pub prefer: Option<&'static str>,
+}
+
+impl DetectionRules {
+    fn add_signature(&mut self, format: &str, pattern: &str) -> Result<(), String> {
+        let format =
+            table_format(format).ok_or_else(|| format!("Unknown table type: {}", format))?;
+        let pattern = Regex::new(pattern)
+            .map_err(|e| format!("Invalid detection signature {}: {}", pattern, e))?;
+        self.signatures.push((format, pattern));
+        Ok(())
+    }
+
+    fn set_prefer(&mut self, format: &str) -> Result<(), String> {
+        self.prefer =
+            Some(table_format(format).ok_or_else(|| format!("Unknown table type: {}", format))?);
+        Ok(())
+    }
+}
+
+/// Tag keys must be usable as metric label names: `[a-zA-Z_][a-zA-Z0-9_]*`
+fn check_tag_key(key: &str) -> Result<(), String> {
+    let valid = key
+        .chars()
+        .enumerate()
+        .all(|(i, c)| c == '_' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit()));
+    if key.is_empty() || !valid {
+        return Err(format!(
+            "Invalid tag name {:?}: use letters, digits and underscores, not starting with a digit",
+            key
+        ));
+    }
+    Ok(())
+}
+
+/// Restricts an analysis to the data files under matching Hive-style partition
+/// directories (`year=2024/month=01`). Keys keep the order they were given in, which is
+/// taken as the directory order when building a targeted listing prefix.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PartitionFilter {
+    pub values: Vec<(String, String)>,
+}
+
+impl PartitionFilter {
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Whether a table-relative object path lies under directories matching every filter key
+    pub fn matches(&self, relative_path: &str) -> bool {
+        let segments: Vec<(&str, &str)> = relative_path
+            .split('/')
+            .filter_map(|segment| segment.split_once('='))
+            .collect();
+        self.values
+            .iter()
+            .all(|(key, value)| segments.iter().any(|(k, v)| k == key && v == value))
+    }
+
+    /// Relative directory holding the matching files when the filter keys are the
+    /// leading partition columns, in order: `year=2024/month=01/`
+    pub fn directory(&self) -> String {
+        self.values
+            .iter()
+            .map(|(key, value)| format!("{}={}/", key, value))
+            .collect()
+    }
+
+    pub fn to_map(&self) -> HashMap<String, String> {
+        self.values.iter().cloned().collect()
+    }
+}
+
+/// Where the analysis time comes from. File ages, freshness, snapshot retention and
+/// partition anomalies are all measured from it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Clock {
+    #[default]
+    System,
+    Fixed(DateTime<Utc>),
+}
+
+impl Clock {
+    pub fn now(&self) -> DateTime<Utc> {
+        match self {
+            Clock::System => Utc::now(),
+            Clock::Fixed(time) => *time,
+        }
+    }
+}
+
+/// Caps on the lists a report returns, so large tables still give reports small enough to
+/// send over an API. Counts and sizes on the report keep covering everything.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResultLimits {
+    pub max_unreferenced_files: Option<usize>,
+    pub max_partitions: Option<usize>,
+    pub max_recommendations: Option<usize>,
+    pub max_files_in_report: Option<usize>, // per file list: unreferenced and each partition's
+    pub omit_file_lists: bool,              // `include_file_lists=False`
+}
+
+impl ResultLimits {
+    pub fn is_set(&self) -> bool {
+        *self != Self::default()
+    }
+
+    /// Files kept in each file list, if they are capped
+    pub fn file_list_limit(&self) -> Option<usize> {
+        match self.omit_file_lists {
+            true => Some(0),
+            false => self.max_files_in_report,
+        }
+    }
+}
+
+/// Every analysis option `from_json` and the Python keyword options accept
+pub const ANALYSIS_OPTIONS: [&str; 55] = [
+    "engine",
+    "pricing",
+    "pricing_catalog",
+    "storage_prices",
+    "engine_cores",
+    "rewrite_mb_per_core_second",
+    "deletes_per_second",
+    "max_files_per_query",
+    "freshness_sla_hours",
+    "previous_report",
+    "access_logs",
+    "history_path",
+    "observability_table",
+    "cloudwatch_namespace",
+    "gcp_project",
+    "gcp_access_token",
+    "observability_format",
+    "detection_signatures",
+    "prefer_table_type",
+    "listing",
+    "inventory",
+    "proxy_url",
+    "ca_bundle_path",
+    "endpoint_url",
+    "connect_timeout_seconds",
+    "read_timeout_seconds",
+    "pool_idle_timeout_seconds",
+    "max_duration_seconds",
+    "pool_max_idle_per_host",
+    "http2",
+    "provider",
+    "unreferenced_grace_period_hours",
+    "orphan_min_age_hours",
+    "deep_scan",
+    "deterministic",
+    "allow_partial",
+    "otlp_endpoint",
+    "otlp_headers",
+    "webhooks",
+    "notify_rules",
+    "policy",
+    "now",
+    "verify_referenced_files",
+    "check_multipart_uploads",
+    "assess_iceberg_migration",
+    "footer_sample_size",
+    "file_size_buckets_mb",
+    "adaptive_thresholds",
+    "max_unreferenced_files",
+    "max_partitions",
+    "max_recommendations",
+    "max_files_in_report",
+    "include_file_lists",
+    "tags",
+    "partition_filter",
+];
+
+/// Tunable settings shared by the Delta Lake and Iceberg analyzers
+#[derive(Debug, Clone)]
+pub struct AnalysisConfig {
+    /// Unreferenced files younger than this are treated as in-flight writes and not penalized
+    pub unreferenced_grace_period_hours: f64,
+    /// Read Parquet footers of sampled data files (extra range GETs per sampled file)
+    pub deep_scan: bool,
+    /// Maximum number of data files whose footers a deep scan reads
+    pub footer_sample_size: usize,
+    /// Bounds of the file size distribution's buckets, also what counts as a small file;
+    /// None: the defaults, with the small bound of the table's size class
+    pub file_size_buckets: Option<FileSizeBuckets>,
+    /// Judge small files, snapshot counts and metadata size by the table's size class
+    pub adaptive_thresholds: bool,
+    /// Only analyze data files in these partitions
+    pub partition_filter: PartitionFilter,
+    /// Engine whose throughput is assumed by remediation estimates
+    pub engine: EngineProfile,
+    /// Flag the table as stale when its last commit is older than this
+    pub freshness_sla_hours: Option<f64>,
+    /// `s3://bucket/prefix` of S3 server access logs or CloudTrail data events to join
+    /// against the table's data files
+    pub access_logs: Option<String>,
+    /// Local directory or `s3://bucket/prefix` each report is saved to, for `history()`
+    pub history_path: Option<String>,
+    /// Local directory or `s3://bucket/prefix` of a table each report is appended to as a row
+    pub observability_table: Option<String>,
+    /// Format of `observability_table`
+    pub observability_format: crate::observability::TableFormat,
+    /// Metadata cache shared by the analyses of a batch; each analysis has its own if None
+    pub object_cache: Option<Arc<crate::s3_client::ObjectCache>>,
+    /// Earlier report of the same table; partitions unchanged since then aren't listed again
+    pub previous_report: Option<Arc<HealthReport>>,
+    /// Storage prices the monthly cost estimate uses
+    pub pricing: PricingProfile,
+    /// Check with HEAD requests that every file the current snapshot references exists
+    pub verify_referenced_files: bool,
+    /// List incomplete multipart uploads under the table (ListMultipartUploads and ListParts)
+    pub check_multipart_uploads: bool,
+    /// Assess whether Delta UniForm can generate Iceberg metadata for a Delta table
+    pub assess_iceberg_migration: bool,
+    /// S3 Inventory report (`manifest.json` or the prefix deliveries go to) to take the data
+    /// files from instead of listing them
+    pub inventory: Option<String>,
+    /// Objects to analyze instead of listing the table (see `listing`)
+    pub listing: Option<Arc<Vec<ObjectInfo>>>,
+    /// Extra signatures and a tie-break for table format detection
+    pub detection: DetectionRules,
+    /// User labels copied onto the report (team, tier, pipeline id)
+    pub tags: HashMap<String, String>,
+    /// HTTP proxy and extra CA certificates for S3 and catalog requests
+    pub network: NetworkSettings,
+    /// Time the analysis treats as now
+    pub clock: Clock,
+    /// Reproducible reports: a fixed clock, objects and lists in a stable order, no timings
+    pub deterministic: bool,
+    /// Stop listing and skip optional stages after this long, returning a truncated report
+    pub max_duration_seconds: Option<f64>,
+    /// Record failures of optional phases on the report instead of failing the analysis
+    pub allow_partial: bool,
+    /// Caps on the unreferenced files, partitions and recommendations returned
+    pub result_limits: ResultLimits,
+    /// OTLP/HTTP collector (e.g. `http://localhost:4318`) the analysis's spans are exported to
+    pub otlp_endpoint: Option<String>,
+    /// Headers sent with the export, e.g. an API key
+    pub otlp_headers: HashMap<String, String>,
+    /// Webhooks posted to when the report breaches `notify_rules`
+    pub webhooks: Vec<crate::notify::Webhook>,
+    pub notify_rules: crate::notify::NotifyRules,
+    /// Rules the report's `compliance` section checks, read from a YAML file
+    pub policy: Option<Arc<crate::policy::Policy>>,
+    /// CloudWatch namespace the report's metrics are published under
+    pub cloudwatch_namespace: Option<String>,
+    /// GCP project the report's metrics are published to with Cloud Monitoring
+    pub gcp_project: Option<String>,
+    /// OAuth token for Cloud Monitoring; the metadata server's when None
+    pub gcp_access_token: Option<String>,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        Self {
+            unreferenced_grace_period_hours: DEFAULT_UNREFERENCED_GRACE_PERIOD_HOURS,
+            deep_scan: false,
+            footer_sample_size: DEFAULT_FOOTER_SAMPLE_SIZE,
+            file_size_buckets: None,
+            adaptive_thresholds: true,
+            partition_filter: PartitionFilter::default(),
+            engine: EngineProfile::default(),
+            freshness_sla_hours: None,
+            access_logs: None,
+            history_path: None,
+            observability_table: None,
+            observability_format: Default::default(),
+            object_cache: None,
+            previous_report: None,
+            pricing: PricingProfile::default(),
+            verify_referenced_files: false,
+            check_multipart_uploads: false,
+            assess_iceberg_migration: false,
+            inventory: None,
+            listing: None,
+            detection: DetectionRules::default(),
+            tags: HashMap::new(),
+            network: NetworkSettings::default(),
+            clock: Clock::default(),
+            deterministic: false,
+            max_duration_seconds: None,
+            allow_partial: false,
+            result_limits: ResultLimits::default(),
+            otlp_endpoint: None,
+            otlp_headers: HashMap::new(),
+            webhooks: Vec::new(),
+            notify_rules: Default::default(),
+            policy: None,
+            cloudwatch_namespace: None,
+            gcp_project: None,
+            gcp_access_token: None,
+        }
+    }
+}
+
+/// Parse an RFC 3339 time such as `2024-06-01T00:00:00Z`
+fn parse_time(text: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(text)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| format!("Invalid time {}: {}", text, e))
+}
+
+/// The JSON form of a keyword option value `json.dumps` can't serialize: a `HealthReport`
+/// as its `to_json()`, a timezone-aware datetime as RFC 3339, a path as a string, an
+/// iterable as a list, and anything else (such as a non-string tag value) as its `str()`
+fn json_default(value: &PyAny) -> PyResult<PyObject> {
+    let py = value.py();
+    if let Ok(report) = value.extract::<HealthReport>() {
+        let json = serde_json::to_string(&report)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        return Ok(py.import("json")?.call_method1("loads", (json,))?.into());
+    }
+    if let Ok(time) = value.extract::<DateTime<Utc>>() {
+        return Ok(time.to_rfc3339().into_py(py));
+    }
+    if let Ok(path) = value.extract::<PathBuf>() {
+        return Ok(path.to_string_lossy().into_py(py));
+    }
+    // Generators and other iterables, such as a `listing`
+    if let Ok(items) = value.iter() {
+        let items = items.collect::<PyResult<Vec<&PyAny>>>()?;
+        return Ok(PyList::new(py, items).into());
+    }
+    Ok(value.str()?.into_py(py))
+}
+
+/// A duration option, which has to be a positive number of seconds
+fn positive_seconds(key: &str, seconds: f64) -> Result<f64, String> {
+    if seconds.is_finite() && seconds > 0.0 {
+        Ok(seconds)
+    } else {
+        Err(format!("{} must be a positive number of seconds", key))
+    }
+}
+
+impl AnalysisConfig {
+    /// Bounds of the file size buckets of a table of `size_class`
+    pub fn file_size_buckets(
+        &self,
+        size_class: Option<&crate::types::SizeClass>,
+    ) -> FileSizeBuckets {
+        match (self.file_size_buckets, size_class) {
+            (Some(buckets), _) => buckets,
+            (None, Some(class)) => FileSizeBuckets {
+                small_bytes: class.small_file_bytes,
+                ..FileSizeBuckets::default()
+            },
+            (None, None) => FileSizeBuckets::default(),
+        }
+    }
+
+    /// A deterministic analysis can't read the system clock
+    fn check_deterministic(&self) -> Result<(), String> {
+        if self.deterministic && self.clock == Clock::System {
+            return Err("deterministic=True needs a fixed time: pass now=".to_string());
+        }
+        Ok(())
+    }
+
+    /// Build the settings from the keyword arguments of a Python `analyze_*` call, read as
+    /// the JSON object `from_json` takes
+    pub fn from_options(options: Option<&PyDict>) -> PyResult<Self> {
+        let Some(options) = options else {
+            return Ok(Self::default());
+        };
+        let py = options.py();
+        let default = PyCFunction::new_closure(py, None, None, |args: &PyTuple, _| {
+            json_default(args.get_item(0)?)
+        })?;
+        let json: String = py
+            .import("json")?
+            .call_method(
+                "dumps",
+                (options,),
+                Some([("default", default)].into_py_dict(py)),
+            )?
+            .extract()?;
+        let options: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&json)
+            .map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Invalid analysis options: {}", e))
+            })?;
+        Self::from_json(&options).map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Build the settings from a JSON object using the same keys as the Python options
+    pub fn from_json(options: &serde_json::Map<String, serde_json::Value>) -> Result<Self, String> {
+        let mut config = Self::default();
+        if let Some(engine) = options.get("engine") {
+            let engine = engine
+                .as_str()
+                .ok_or_else(|| format!("Invalid value for engine: {}", engine))?;
+            config.engine = EngineProfile::named(engine)
+                .ok_or_else(|| format!("Unknown engine: {}", engine))?;
+        }
+
+        let pricing = options
+            .get("pricing")
+            .map(|pricing| {
+                pricing
+                    .as_str()
+                    .ok_or_else(|| format!("Invalid value for pricing: {}", pricing))
+            })
+            .transpose()?;
+        let pricing_catalog = options.get("pricing_catalog");
+        if pricing.is_some() || pricing_catalog.is_some() {
+            config.pricing = PricingCatalog::resolve(pricing_catalog, pricing)?;
+        }
+
+        for (key, value) in options {
+            let invalid = || format!("Invalid value for {}: {}", key, value);
+            match key.as_str() {
+                "engine" | "pricing" | "pricing_catalog" => {}
+                "storage_prices" => {
+                    for (class, price) in value.as_object().ok_or_else(invalid)? {
+                        let price = price.as_f64().ok_or_else(invalid)?;
+                        config
+                            .pricing
+                            .per_gb_month
+                            .insert(class.to_uppercase(), price);
+                    }
+                }
+                "engine_cores" => {
+                    config.engine.cores = (value.as_u64().ok_or_else(invalid)? as usize).max(1);
+                }
+                "rewrite_mb_per_core_second" | "deletes_per_second" => {
+                    // Remediation runtimes divide by these
+                    let rate = value.as_f64().ok_or_else(invalid)?;
+                    if !(rate.is_finite() && rate > 0.0) {
+                        return Err(format!("{} must be a positive number", key));
+                    }
+                    match key.as_str() {
+                        "rewrite_mb_per_core_second" => {
+                            config.engine.rewrite_mb_per_core_second = rate
+                        }
+                        _ => config.engine.deletes_per_second = rate,
+                    }
+                }
+                "max_files_per_query" => {
+                    config.engine.max_files_per_query = match value {
+                        serde_json::Value::Null => None,
+                        value => Some(value.as_u64().ok_or_else(invalid)? as usize),
+                    };
+                }
+                "freshness_sla_hours" => {
+                    config.freshness_sla_hours = match value {
+                        serde_json::Value::Null => None,
+                        value => Some(value.as_f64().ok_or_else(invalid)?),
+                    };
+                }
+                "previous_report" => {
+                    // A report object, or one serialized with `to_json()`
+                    let report = match value {
+                        serde_json::Value::String(json) => serde_json::from_str(json),
+                        value => serde_json::from_value(value.clone()),
+                    }
+                    .map_err(|e| format!("Invalid previous_report: {}", e))?;
+                    config.previous_report = Some(Arc::new(report));
+                }
+                "access_logs"
+                | "history_path"
+                | "observability_table"
+                | "cloudwatch_namespace"
+                | "gcp_project"
+                | "gcp_access_token" => {
+                    let location = match value {
+                        serde_json::Value::Null => None,
+                        value => Some(value.as_str().ok_or_else(invalid)?.to_string()),
+                    };
+                    match key.as_str() {
+                        "access_logs" => config.access_logs = location,
+                        "history_path" => config.history_path = location,
+                        "observability_table" => config.observability_table = location,
+                        "cloudwatch_namespace" => config.cloudwatch_namespace = location,
+                        "gcp_project" => config.gcp_project = location,
+                        _ => config.gcp_access_token = location,
+                    }
+                }
+                "observability_format" => {
+                    config.observability_format = crate::observability::TableFormat::parse(
+                        value.as_str().ok_or_else(invalid)?,
+                    )?;
+                }
+                "detection_signatures" => {
+                    for (format, patterns) in value.as_object().ok_or_else(invalid)? {
+                        for pattern in patterns.as_array().ok_or_else(invalid)? {
+                            config
+                                .detection
+                                .add_signature(format, pattern.as_str().ok_or_else(invalid)?)?;
+                        }
+                    }
+                }
+                "prefer_table_type" => config
+                    .detection
+                    .set_prefer(value.as_str().ok_or_else(invalid)?)?,
+                "listing" => {
+                    config.listing = match value {
+                        serde_json::Value::Null => None,
+                        value => Some(Arc::new(crate::listing::from_json(value)?)),
+                    };
+                }
+                "inventory" => {
+                    config.inventory = match value {
+                        serde_json::Value::Null => None,
+                        value => Some(value.as_str().ok_or_else(invalid)?.to_string()),
+                    };
+                }
+                "proxy_url" => {
+                    config.network.proxy_url = match value {
+                        serde_json::Value::Null => None,
+                        value => Some(value.as_str().ok_or_else(invalid)?.to_string()),
+                    };
+                }
+                "ca_bundle_path" => {
+                    config.network.ca_bundle_path = match value {
+                        serde_json::Value::Null => None,
+                        value => Some(value.as_str().ok_or_else(invalid)?.to_string()),
+                    };
+                }
+                "endpoint_url" => {
+                    config.network.endpoint_url = match value {
+                        serde_json::Value::Null => None,
+                        value => Some(value.as_str().ok_or_else(invalid)?.to_string()),
+                    };
+                }
+                "connect_timeout_seconds"
+                | "read_timeout_seconds"
+                | "pool_idle_timeout_seconds"
+                | "max_duration_seconds" => {
+                    let seconds = match value {
+                        serde_json::Value::Null => None,
+                        value => Some(positive_seconds(key, value.as_f64().ok_or_else(invalid)?)?),
+                    };
+                    match key.as_str() {
+                        "connect_timeout_seconds" => {
+                            config.network.connect_timeout_seconds = seconds
+                        }
+                        "read_timeout_seconds" => config.network.read_timeout_seconds = seconds,
+                        "pool_idle_timeout_seconds" => {
+                            config.network.pool_idle_timeout_seconds = seconds
+                        }
+                        _ => config.max_duration_seconds = seconds,
+                    }
+                }
+                "pool_max_idle_per_host" => {
+                    config.network.pool_max_idle_per_host = match value {
+                        serde_json::Value::Null => None,
+                        value => Some(value.as_u64().ok_or_else(invalid)? as usize),
+                    };
+                }
+                "http2" => {
+                    config.network.http2 = match value {
+                        serde_json::Value::Null => None,
+                        value => Some(value.as_bool().ok_or_else(invalid)?),
+                    };
+                }
+                "provider" => {
+                    let provider = value.as_str().ok_or_else(invalid)?;
+                    config.network.provider = Some(
+                        StorageProvider::named(provider)
+                            .ok_or_else(|| format!("Unknown provider: {}", provider))?,
+                    );
+                }
+                "unreferenced_grace_period_hours" | "orphan_min_age_hours" => {
+                    config.unreferenced_grace_period_hours =
+                        value.as_f64().ok_or_else(invalid)?.max(0.0);
+                }
+                "deep_scan" => config.deep_scan = value.as_bool().ok_or_else(invalid)?,
+                "deterministic" => config.deterministic = value.as_bool().ok_or_else(invalid)?,
+                "allow_partial" => config.allow_partial = value.as_bool().ok_or_else(invalid)?,
+                "otlp_endpoint" => {
+                    config.otlp_endpoint = match value {
+                        serde_json::Value::Null => None,
+                        value => Some(value.as_str().ok_or_else(invalid)?.to_string()),
+                    };
+                }
+                "otlp_headers" => {
+                    for (name, value) in value.as_object().ok_or_else(invalid)? {
+                        let value = value.as_str().ok_or_else(invalid)?;
+                        config.otlp_headers.insert(name.clone(), value.to_string());
+                    }
+                }
+                "webhooks" => {
+                    for webhook in value.as_array().ok_or_else(invalid)? {
+                        let (url, format) = match webhook {
+                            serde_json::Value::String(url) => (url.as_str(), None),
+                            webhook => (
+                                webhook["url"].as_str().ok_or_else(invalid)?,
+                                webhook.get("format").and_then(|f| f.as_str()),
+                            ),
+                        };
+                        config
+                            .webhooks
+                            .push(crate::notify::Webhook::new(url, format)?);
+                    }
+                }
+                "notify_rules" => {
+                    for (rule, threshold) in value.as_object().ok_or_else(invalid)? {
+                        config
+                            .notify_rules
+                            .set(rule, threshold.as_f64().ok_or_else(invalid)?)?;
+                    }
+                }
+                "policy" => {
+                    config.policy = match value {
+                        serde_json::Value::Null => None,
+                        value => Some(Arc::new(crate::policy::Policy::read_file(Path::new(
+                            value.as_str().ok_or_else(invalid)?,
+                        ))?)),
+                    };
+                }
+                "now" => {
+                    config.clock = Clock::Fixed(parse_time(value.as_str().ok_or_else(invalid)?)?);
+                }
+                "verify_referenced_files" => {
+                    config.verify_referenced_files = value.as_bool().ok_or_else(invalid)?;
+                }
+                "check_multipart_uploads" => {
+                    config.check_multipart_uploads = value.as_bool().ok_or_else(invalid)?;
+                }
+                "assess_iceberg_migration" => {
+                    config.assess_iceberg_migration = value.as_bool().ok_or_else(invalid)?;
+                }
+                "footer_sample_size" => {
+                    config.footer_sample_size = value.as_u64().ok_or_else(invalid)? as usize;
+                }
+                "file_size_buckets_mb" => {
+                    let bounds = value
+                        .as_array()
+                        .ok_or_else(invalid)?
+                        .iter()
+                        .map(|bound| bound.as_f64().ok_or_else(invalid))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    config.file_size_buckets = Some(FileSizeBuckets::from_mb(&bounds)?);
+                }
+                "adaptive_thresholds" => {
+                    config.adaptive_thresholds = value.as_bool().ok_or_else(invalid)?;
+                }
+                "max_unreferenced_files"
+                | "max_partitions"
+                | "max_recommendations"
+                | "max_files_in_report" => {
+                    let limit = match value {
+                        serde_json::Value::Null => None,
+                        value => Some(value.as_u64().ok_or_else(invalid)? as usize),
+                    };
+                    match key.as_str() {
+                        "max_unreferenced_files" => {
+                            config.result_limits.max_unreferenced_files = limit
+                        }
+                        "max_partitions" => config.result_limits.max_partitions = limit,
+                        "max_files_in_report" => config.result_limits.max_files_in_report = limit,
+                        _ => config.result_limits.max_recommendations = limit,
+                    }
+                }
+                "include_file_lists" => {
+                    config.result_limits.omit_file_lists = !value.as_bool().ok_or_else(invalid)?;
+                }
+                "tags" => {
+                    for (key, value) in value.as_object().ok_or_else(invalid)? {
+                        check_tag_key(key)?;
+                        let value = match value {
+                            serde_json::Value::String(value) => value.clone(),
+                            other => other.to_string(),
+                        };
+                        config.tags.insert(key.clone(), value);
+                    }
+                }
+                "partition_filter" => {
+                    for (key, value) in value.as_object().ok_or_else(invalid)? {
+                        let value = match value {
+                            serde_json::Value::String(value) => value.clone(),
+                            other => other.to_string(),
+                        };
+                        config.partition_filter.values.push((key.clone(), value));
+                    }
+                }
+                _ => return Err(format!("Unknown analysis option: {}", key)),
+            }
+        }
+        config.check_deterministic()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_filter() {
+        let filter = PartitionFilter {
+            values: vec![
+                ("year".to_string(), "2024".to_string()),
+                ("month".to_string(), "01".to_string()),
+            ],
+        };
+        assert!(filter.matches("year=2024/month=01/part-0.parquet"));
+        assert!(filter.matches("data/month=01/year=2024/part-0.parquet"));
+        assert!(!filter.matches("year=2024/month=02/part-0.parquet"));
+        assert!(!filter.matches("year=2024/part-0.parquet"));
+        assert_eq!(filter.directory(), "year=2024/month=01/");
+        assert!(PartitionFilter::default().matches("part-0.parquet"));
+    }
+
+    #[test]
+    fn test_analysis_options_are_known() {
+        for option in ANALYSIS_OPTIONS {
+            let options =
+                serde_json::Map::from_iter([(option.to_string(), serde_json::Value::Null)]);
+            if let Err(error) = AnalysisConfig::from_json(&options) {
+                assert!(!error.starts_with("Unknown analysis option"), "{}", error);
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_json_tags() {
+        let options = serde_json::json!({ "tags": { "team": "growth", "tier": 1 } });
+        let config = AnalysisConfig::from_json(options.as_object().unwrap()).unwrap();
+        assert_eq!(config.tags["team"], "growth");
+        assert_eq!(config.tags["tier"], "1");
+
+        let options = serde_json::json!({ "tags": { "cost-center": "x" } });
+        assert!(AnalysisConfig::from_json(options.as_object().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_from_json_previous_report() {
+        let report = HealthReport::new("s3://lake/t".to_string(), "delta".to_string());
+        let json = serde_json::to_value(&report).unwrap();
+        // As `to_json()` text, as from Python, or as an object, as in a JSON request
+        for previous_report in [serde_json::Value::String(json.to_string()), json] {
+            let options = serde_json::json!({ "previous_report": previous_report });
+            let config = AnalysisConfig::from_json(options.as_object().unwrap()).unwrap();
+            assert_eq!(config.previous_report.unwrap().table_path, "s3://lake/t");
+        }
+        let options = serde_json::json!({ "previous_report": "{" });
+        assert!(AnalysisConfig::from_json(options.as_object().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_from_json_time_limits() {
+        let options =
+            serde_json::json!({ "read_timeout_seconds": 30, "max_duration_seconds": 0.5 });
+        let config = AnalysisConfig::from_json(options.as_object().unwrap()).unwrap();
+        assert_eq!(config.network.read_timeout_seconds, Some(30.0));
+        assert_eq!(config.max_duration_seconds, Some(0.5));
+        assert!(config.network.aws_timeouts().is_some());
+
+        let options = serde_json::json!({ "connect_timeout_seconds": 0 });
+        assert!(AnalysisConfig::from_json(options.as_object().unwrap()).is_err());
+
+        let options = serde_json::json!({ "orphan_min_age_hours": 6 });
+        let config = AnalysisConfig::from_json(options.as_object().unwrap()).unwrap();
+        assert_eq!(config.unreferenced_grace_period_hours, 6.0);
+        assert!(config.network.aws_connector().unwrap().is_none());
+
+        let options = serde_json::json!({
+            "pool_max_idle_per_host": 64,
+            "pool_idle_timeout_seconds": 300,
+            "http2": false,
+        });
+        let config = AnalysisConfig::from_json(options.as_object().unwrap()).unwrap();
+        assert_eq!(config.network.pool_max_idle_per_host, Some(64));
+        assert_eq!(config.network.pool_idle_timeout_seconds, Some(300.0));
+        assert!(config.network.aws_connector().unwrap().is_some());
+        assert!(config.network.http_client().is_ok());
+        let options = serde_json::json!({ "pool_idle_timeout_seconds": -1 });
+        assert!(AnalysisConfig::from_json(options.as_object().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_from_json_deterministic_needs_fixed_time() {
+        let options = serde_json::json!({ "deterministic": true });
+        assert!(AnalysisConfig::from_json(options.as_object().unwrap()).is_err());
+
+        let options =
+            serde_json::json!({ "deterministic": true, "now": "2024-06-01T12:00:00+02:00" });
+        let config = AnalysisConfig::from_json(options.as_object().unwrap()).unwrap();
+        assert_eq!(config.clock.now().to_rfc3339(), "2024-06-01T10:00:00+00:00");
+    }
+
+    #[test]
+    fn test_from_json_partition_filter() {
+        let options = serde_json::json!({ "partition_filter": { "year": 2024 } });
+        let config = AnalysisConfig::from_json(options.as_object().unwrap()).unwrap();
+        assert_eq!(
+            config.partition_filter.values,
+            vec![("year".to_string(), "2024".to_string())]
+        );
+        let invalid = serde_json::json!({ "partition_filter": "year=2024" });
+        assert!(AnalysisConfig::from_json(invalid.as_object().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_from_json_engine_overrides() {
+        let options = serde_json::json!({ "engine_cores": 16, "engine": "Trino" });
+        let config = AnalysisConfig::from_json(options.as_object().unwrap()).unwrap();
+        assert_eq!(config.engine.name, "trino");
+        assert_eq!(config.engine.cores, 16);
+        assert_eq!(config.engine.rewrite_mb_per_core_second, 24.0);
+        assert_eq!(config.engine.max_files_per_query, Some(1_000_000));
+
+        let options = serde_json::json!({ "deletes_per_second": 250.5 });
+        let config = AnalysisConfig::from_json(options.as_object().unwrap()).unwrap();
+        assert_eq!(config.engine.deletes_per_second, 250.5);
+        for (key, rate) in [
+            ("rewrite_mb_per_core_second", 0.0),
+            ("deletes_per_second", -5.0),
+        ] {
+            let options = serde_json::json!({ key: rate });
+            assert_eq!(
+                AnalysisConfig::from_json(options.as_object().unwrap()).unwrap_err(),
+                format!("{} must be a positive number", key)
+            );
+        }
+
+        let options = serde_json::json!({ "engine": "athena", "max_files_per_query": null });
+        let config = AnalysisConfig::from_json(options.as_object().unwrap()).unwrap();
+        assert_eq!(config.engine.name, "athena");
+        assert_eq!(config.engine.max_files_per_query, None);
+
+        let unknown = serde_json::json!({ "engine": "hadoop" });
+        assert_eq!(
+            AnalysisConfig::from_json(unknown.as_object().unwrap()).unwrap_err(),
+            "Unknown engine: hadoop"
+        );
+    }
+
+    #[test]
+    fn test_pricing_catalog_overrides_bundled_prices() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pricing.toml");
+        std::fs::write(
+            &path,
+            r#"
+["aws:us-east-1"]
+storage = { STANDARD = 0.018 }
+
+["ovh:gra"]
+currency = "eur"
+get_per_1000 = 0.0
+storage = { STANDARD = 0.007 }
+"#,
+        )
+        .unwrap();
+        let options = serde_json::json!({ "pricing_catalog": path.to_str().unwrap() });
+        let config = AnalysisConfig::from_json(options.as_object().unwrap()).unwrap();
+        assert_eq!(config.pricing.name, "aws:us-east-1");
+        assert_eq!(config.pricing.price("STANDARD"), 0.018);
+        assert_eq!(config.pricing.price("GLACIER"), 0.0036);
+        assert_eq!(config.pricing.list_per_1000, 0.005);
+
+        let options = serde_json::json!({
+            "pricing": "ovh:gra",
+            "pricing_catalog": {"ovh:gra": {"currency": "eur", "storage": {"standard": 0.007}}},
+        });
+        let config = AnalysisConfig::from_json(options.as_object().unwrap()).unwrap();
+        assert_eq!(config.pricing.currency, "EUR");
+        assert_eq!(config.pricing.price("COLD"), 0.007);
+
+        let options = serde_json::json!({ "pricing_catalog": {"ovh:gra": {"currency": "EUR"}} });
+        assert!(AnalysisConfig::from_json(options.as_object().unwrap()).is_err());
+        let options = serde_json::json!({ "pricing": "ovh:gra" });
+        assert!(AnalysisConfig::from_json(options.as_object().unwrap()).is_err());
+        assert_eq!(PricingProfile::default().currency, "USD");
+    }
+
+    #[test]
+    fn test_from_json_pricing_overrides() {
+        let options = serde_json::json!({ "storage_prices": {"standard": 0.03}, "pricing": "gcp:us-central1" });
+        let config = AnalysisConfig::from_json(options.as_object().unwrap()).unwrap();
+        assert_eq!(config.pricing.name, "gcp:us-central1");
+        assert_eq!(config.pricing.price("STANDARD"), 0.03);
+        assert_eq!(config.pricing.price("NEARLINE"), 0.01);
+        // Classes without a price fall back to STANDARD
+        assert_eq!(config.pricing.price("UNKNOWN"), 0.03);
+    }
+}
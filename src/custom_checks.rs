@@ -0,0 +1,337 @@
+//! Custom checks (`register_check`): Python functions run at the end of every analysis with
+//! the table's parsed metadata, whose findings join the report's recommendations.
+//!
+//! A check gets a dict with `table_path`, `table_type`, `files` (the data files' `path`,
+//! `size` and `last_modified`), `snapshots` (each commit or snapshot's `id`, `timestamp` and
+//! `operation`), `schema` and `metrics`, and returns nothing, a finding or a list of them. A
+//! finding is a message, or a dict with a `message` and optionally a `score` between 0.0 and
+//! 1.0. A check registered with a `weight` is also scored: 1.0 without findings, else the
+//! lowest score of its findings (0.0 when they have none), averaged into the health score
+//! with the built-in score weighted 1.0. A check that raises is a warning on the report.
+
+use crate::s3_client::{relative_key, ObjectInfo};
+use crate::stream::EventSink;
+use crate::types::{CustomCheckResult, HealthReport};
+use chrono::{DateTime, Utc};
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+use std::sync::{Mutex, OnceLock};
+
+struct CustomCheck {
+    name: String,
+    function: PyObject,
+    weight: f64,
+}
+
+/// Registered checks, in registration order
+static CHECKS: Mutex<Vec<CustomCheck>> = Mutex::new(Vec::new());
+
+/// Runs the registered checks on the input built by its argument
+type Runner = fn(&dyn Fn() -> Value) -> Vec<Result<CustomCheckResult, String>>;
+
+/// Set by the first `register_check`, so only the Python module ever calls into Python
+static RUN: OnceLock<Runner> = OnceLock::new();
+
+/// A commit (Delta) or snapshot (Iceberg) as checks see it
+pub struct Snapshot {
+    pub id: i64,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub operation: Option<String>,
+}
+
+/// Snapshots of a Delta log replay: each version with its `commitInfo`
+pub fn delta_snapshots(commits: &[(u64, Vec<Value>)]) -> Vec<Snapshot> {
+    commits
+        .iter()
+        .map(|(version, actions)| {
+            let commit_info = actions.iter().find_map(|action| action.get("commitInfo"));
+            Snapshot {
+                id: *version as i64,
+                timestamp: commit_info
+                    .and_then(|info| info.get("timestamp")?.as_i64())
+                    .and_then(DateTime::from_timestamp_millis),
+                operation: commit_info
+                    .and_then(|info| info.get("operation")?.as_str())
+                    .map(str::to_string),
+            }
+        })
+        .collect()
+}
+
+/// Snapshots listed in Iceberg table metadata
+pub fn iceberg_snapshots(metadata: &Value) -> Vec<Snapshot> {
+    metadata
+        .get("snapshots")
+        .and_then(|s| s.as_array())
+        .into_iter()
+        .flatten()
+        .map(|snapshot| Snapshot {
+            id: snapshot
+                .get("snapshot-id")
+                .and_then(|id| id.as_i64())
+                .unwrap_or_default(),
+            timestamp: snapshot
+                .get("timestamp-ms")
+                .and_then(|t| t.as_i64())
+                .and_then(DateTime::from_timestamp_millis),
+            operation: snapshot
+                .pointer("/summary/operation")
+                .and_then(|op| op.as_str())
+                .map(str::to_string),
+        })
+        .collect()
+}
+
+/// The metadata a check is called with
+fn check_input(
+    report: &HealthReport,
+    table_prefix: &str,
+    files: &[&ObjectInfo],
+    snapshots: &[Snapshot],
+) -> Value {
+    let files: Vec<Value> = files
+        .iter()
+        .map(|file| {
+            json!({
+                "path": relative_key(table_prefix, &file.key),
+                "size": file.size,
+                "last_modified": file.last_modified.map(|t| t.to_rfc3339()),
+            })
+        })
+        .collect();
+    let snapshots: Vec<Value> = snapshots
+        .iter()
+        .map(|snapshot| {
+            json!({
+                "id": snapshot.id,
+                "timestamp": snapshot.timestamp.map(|t| t.to_rfc3339()),
+                "operation": snapshot.operation,
+            })
+        })
+        .collect();
+    json!({
+        "table_path": report.table_path,
+        "table_type": report.table_type,
+        "files": files,
+        "snapshots": snapshots,
+        "schema": report.current_schema,
+        "metrics": report.metrics,
+    })
+}
+
+/// (messages, lowest score) of what a check returned
+fn parse_findings(returned: &Value) -> Result<(Vec<String>, Option<f64>), String> {
+    let items = match returned {
+        Value::Null => return Ok((Vec::new(), None)),
+        Value::Array(items) => items.iter().collect(),
+        item => vec![item],
+    };
+    let mut messages = Vec::new();
+    let mut lowest: Option<f64> = None;
+    for item in items {
+        match item {
+            Value::String(message) => messages.push(message.clone()),
+            Value::Object(finding) => {
+                let message = finding
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .ok_or("a finding dict needs a 'message' string")?;
+                messages.push(message.to_string());
+                if let Some(score) = finding.get("score") {
+                    let score = score
+                        .as_f64()
+                        .filter(|s| (0.0..=1.0).contains(s))
+                        .ok_or("a finding's 'score' must be between 0.0 and 1.0")?;
+                    lowest = Some(lowest.map_or(score, |l| l.min(score)));
+                }
+            }
+            other => return Err(format!("unsupported finding: {}", other)),
+        }
+    }
+    Ok((messages, lowest))
+}
+
+fn call(py: Python<'_>, function: &PyObject, input: &Value) -> PyResult<Value> {
+    let json = py.import("json")?;
+    let input = json.call_method1("loads", (input.to_string(),))?;
+    let returned = function.call1(py, (input,))?;
+    let returned: String = json.call_method1("dumps", (returned,))?.extract()?;
+    serde_json::from_str(&returned)
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+}
+
+/// Call the registered checks with the input `input` builds, if there are any
+fn run_registered(input: &dyn Fn() -> Value) -> Vec<Result<CustomCheckResult, String>> {
+    if CHECKS.lock().unwrap().is_empty() {
+        return Vec::new();
+    }
+    let input = input();
+    Python::with_gil(|py| {
+        // Called without the lock held, so checks may register others
+        let checks: Vec<(String, PyObject, f64)> = CHECKS
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|check| {
+                (
+                    check.name.clone(),
+                    check.function.clone_ref(py),
+                    check.weight,
+                )
+            })
+            .collect();
+        checks
+            .into_iter()
+            .map(|(name, function, weight)| {
+                let (findings, lowest) = call(py, &function, &input)
+                    .map_err(|e| e.to_string())
+                    .and_then(|returned| parse_findings(&returned))
+                    .map_err(|e| format!("Custom check {} failed: {}", name, e))?;
+                Ok(CustomCheckResult {
+                    name,
+                    weight,
+                    score: match findings.len() {
+                        0 => 1.0,
+                        _ => lowest.unwrap_or(0.0),
+                    },
+                    findings,
+                })
+            })
+            .collect()
+    })
+}
+
+/// Run the registered checks against `report`, adding their findings to its recommendations
+/// and, for weighted checks, their scores to its health score
+pub fn run(
+    events: &EventSink,
+    report: &mut HealthReport,
+    table_prefix: &str,
+    files: &[&ObjectInfo],
+    snapshots: impl Fn() -> Vec<Snapshot>,
+) {
+    let Some(run) = RUN.get() else {
+        return;
+    };
+    let outcomes = run(&|| check_input(report, table_prefix, files, &snapshots()));
+    if outcomes.is_empty() {
+        return;
+    }
+    let mut results = Vec::new();
+    for outcome in outcomes {
+        match outcome {
+            Ok(result) => results.push(result),
+            Err(e) => events.warning(e),
+        }
+    }
+    apply(report, results);
+}
+
+/// Merge check results into `report`
+fn apply(report: &mut HealthReport, results: Vec<CustomCheckResult>) {
+    for result in &results {
+        for finding in &result.findings {
+            report
+                .metrics
+                .recommendations
+                .push(format!("[{}] {}", result.name, finding));
+        }
+    }
+    let total_weight: f64 = results.iter().map(|r| r.weight).sum();
+    if total_weight > 0.0 {
+        let weighted: f64 = results.iter().map(|r| r.weight * r.score).sum();
+        report.health_score = (report.health_score + weighted) / (1.0 + total_weight);
+        report.metrics.health_score = report.health_score;
+    }
+    report.custom_checks = Some(results);
+}
+
+/// Register `function` as a custom check run by every analysis, replacing any check of the
+/// same `name` (by default the function's `__name__`). A positive `weight` also averages the
+/// check's score into the health score, against 1.0 for the built-in score.
+#[pyfunction]
+#[pyo3(signature = (function, name=None, weight=0.0))]
+pub fn register_check(function: &PyAny, name: Option<String>, weight: f64) -> PyResult<()> {
+    if !function.is_callable() {
+        return Err(pyo3::exceptions::PyTypeError::new_err(
+            "A custom check must be callable",
+        ));
+    }
+    if !weight.is_finite() || weight < 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "A custom check's weight must be 0 or more",
+        ));
+    }
+    let name = match name {
+        Some(name) => name,
+        None => function.getattr("__name__")?.extract()?,
+    };
+    RUN.get_or_init(|| run_registered);
+    let mut checks = CHECKS.lock().unwrap();
+    checks.retain(|check| check.name != name);
+    checks.push(CustomCheck {
+        name,
+        function: function.into(),
+        weight,
+    });
+    Ok(())
+}
+
+/// Remove the custom check registered as `name`, returning whether there was one
+#[pyfunction]
+pub fn unregister_check(name: &str) -> bool {
+    let mut checks = CHECKS.lock().unwrap();
+    let before = checks.len();
+    checks.retain(|check| check.name != name);
+    checks.len() != before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_findings_merge_into_recommendations_and_score() {
+        let returned = json!(["Too many columns", { "message": "No owner tag", "score": 0.4 }]);
+        let (findings, lowest) = parse_findings(&returned).unwrap();
+        assert_eq!(findings, ["Too many columns", "No owner tag"]);
+        assert_eq!(lowest, Some(0.4));
+        assert_eq!(parse_findings(&Value::Null).unwrap(), (Vec::new(), None));
+        assert!(parse_findings(&json!({ "score": 0.5 })).is_err());
+        assert!(parse_findings(&json!({ "message": "x", "score": 2 })).is_err());
+
+        let mut report = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        report.health_score = 0.9;
+        apply(
+            &mut report,
+            vec![
+                CustomCheckResult {
+                    name: "naming".to_string(),
+                    weight: 0.0,
+                    score: 0.0,
+                    findings: vec!["Too many columns".to_string()],
+                },
+                CustomCheckResult {
+                    name: "ownership".to_string(),
+                    weight: 1.0,
+                    score: 0.4,
+                    findings: vec!["No owner tag".to_string()],
+                },
+            ],
+        );
+        assert_eq!(
+            report.metrics.recommendations,
+            ["[naming] Too many columns", "[ownership] No owner tag"]
+        );
+        assert!((report.health_score - 0.65).abs() < 1e-9);
+
+        let commits = vec![(
+            3,
+            vec![json!({ "commitInfo": { "timestamp": 0, "operation": "WRITE" } })],
+        )];
+        let snapshots = delta_snapshots(&commits);
+        assert_eq!(snapshots[0].id, 3);
+        assert_eq!(snapshots[0].operation.as_deref(), Some("WRITE"));
+        assert_eq!(snapshots[0].timestamp, DateTime::from_timestamp_millis(0));
+    }
+}
@@ -0,0 +1,224 @@
+//! Data-quality tool output: a report's findings as a dbt `sources.yml` or a Great
+//! Expectations validation result, so table health sits next to the tests teams already run.
+//!
+//! The dbt source carries the table's location, its freshness SLA as a `freshness` stanza
+//! and one `drainage_finding` test per finding (the findings of the SARIF output), warning or
+//! erroring like the SARIF result. The test is a one-line generic test the project defines
+//! (see the README); it always fails, so `dbt test` surfaces each finding. The Great
+//! Expectations result has one expectation per score, one for recommendations and, when they
+//! were checked, ones for freshness and history regressions.
+
+use crate::badge::HEALTHY_SCORE;
+use crate::sarif::findings;
+use crate::types::HealthReport;
+use serde_json::{json, Value};
+
+/// dbt identifier of the table: the last segment of its path, lowercased, with anything
+/// but letters, digits and underscores replaced
+fn dbt_table_name(table_path: &str) -> String {
+    let segment = table_path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default();
+    segment
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// A dbt `sources.yml` document declaring the table under `source_name`
+pub fn to_dbt_sources(report: &HealthReport, source_name: &str, table_name: Option<&str>) -> Value {
+    let findings = findings(report);
+    let tests: Vec<Value> = findings
+        .iter()
+        .map(|finding| {
+            json!({
+                "drainage_finding": {
+                    "check": finding.rule_id,
+                    "message": finding.message,
+                    "config": { "severity": if finding.level == "error" { "error" } else { "warn" } },
+                }
+            })
+        })
+        .collect();
+    let mut table = json!({
+        "name": table_name.map(str::to_string).unwrap_or_else(|| dbt_table_name(&report.table_path)),
+        "description": format!(
+            "{} table at {}, health score {:.1}%",
+            report.table_type,
+            report.table_path,
+            report.health_score * 100.0
+        ),
+        "external": { "location": report.table_path },
+        "meta": {
+            "drainage": {
+                "table_type": report.table_type,
+                "health_score": report.health_score,
+                "analyzed_at": report.analysis_timestamp.to_rfc3339(),
+                "findings": findings.len(),
+            }
+        },
+    });
+    if let Some(sla) = report.metrics.freshness.as_ref().and_then(|f| f.sla_hours) {
+        // dbt counts in whole periods; warn at the SLA and error once it is missed twice over
+        let hours = sla.ceil().max(1.0) as u64;
+        table["freshness"] = json!({
+            "warn_after": { "count": hours, "period": "hour" },
+            "error_after": { "count": hours * 2, "period": "hour" },
+        });
+    }
+    if !tests.is_empty() {
+        table["tests"] = json!(tests);
+    }
+    json!({ "version": 2, "sources": [{ "name": source_name, "tables": [table] }] })
+}
+
+/// One validation result of an expectation
+fn expectation(expectation_type: &str, kwargs: Value, success: bool, observed: Value) -> Value {
+    json!({
+        "success": success,
+        "expectation_config": { "expectation_type": expectation_type, "kwargs": kwargs, "meta": {} },
+        "result": { "observed_value": observed },
+        "meta": {},
+        "exception_info": {
+            "raised_exception": false,
+            "exception_message": null,
+            "exception_traceback": null,
+        },
+    })
+}
+
+fn score_expectation(expectation_type: &str, score: f64) -> Value {
+    expectation(
+        expectation_type,
+        json!({ "min_value": HEALTHY_SCORE, "max_value": 1.0, "strict_min": true }),
+        score > HEALTHY_SCORE,
+        json!(score),
+    )
+}
+
+/// A Great Expectations `ExpectationSuiteValidationResult` of the report
+pub fn to_great_expectations(report: &HealthReport) -> Value {
+    let metrics = &report.metrics;
+    let mut results = vec![score_expectation(
+        "expect_table_health_score_to_be_between",
+        report.health_score,
+    )];
+    if let Some(ref sub_scores) = metrics.sub_scores {
+        results.extend([
+            score_expectation(
+                "expect_table_storage_cost_score_to_be_between",
+                sub_scores.storage_cost_score,
+            ),
+            score_expectation(
+                "expect_table_query_performance_score_to_be_between",
+                sub_scores.query_performance_score,
+            ),
+            score_expectation(
+                "expect_table_reliability_score_to_be_between",
+                sub_scores.reliability_score,
+            ),
+        ]);
+    }
+    results.push(expectation(
+        "expect_table_to_have_no_maintenance_recommendations",
+        json!({}),
+        metrics.recommendations.is_empty(),
+        json!(metrics.recommendations.len()),
+    ));
+    if let Some(ref freshness) = metrics.freshness {
+        if let Some(sla) = freshness.sla_hours {
+            results.push(expectation(
+                "expect_table_last_commit_to_be_within_sla",
+                json!({ "sla_hours": sla }),
+                !freshness.is_stale,
+                json!(freshness.hours_since_last_commit),
+            ));
+        }
+    }
+    if let Some(ref regressions) = report.history_regressions {
+        let metrics: Vec<&String> = regressions.iter().map(|r| &r.metric).collect();
+        results.push(expectation(
+            "expect_table_health_to_not_regress",
+            json!({}),
+            regressions.is_empty(),
+            json!(metrics),
+        ));
+    }
+
+    let evaluated = results.len();
+    let successful = results.iter().filter(|r| r["success"] == true).count();
+    let validation_time = report.analysis_timestamp.to_rfc3339();
+    json!({
+        "success": successful == evaluated,
+        "results": results,
+        "evaluation_parameters": {},
+        "statistics": {
+            "evaluated_expectations": evaluated,
+            "successful_expectations": successful,
+            "unsuccessful_expectations": evaluated - successful,
+            "success_percent": successful as f64 * 100.0 / evaluated as f64,
+        },
+        "meta": {
+            "expectation_suite_name": format!("drainage.{}", dbt_table_name(&report.table_path)),
+            "run_id": { "run_name": "drainage", "run_time": validation_time },
+            "batch_spec": { "path": report.table_path, "table_type": report.table_type },
+            "validation_time": validation_time,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_findings_render_as_dbt_tests_and_expectations() {
+        let mut report =
+            HealthReport::new("s3://lake/Sales-Events/".to_string(), "delta".to_string());
+        report.health_score = 0.7;
+        report.metrics.recommendations = vec!["Run OPTIMIZE".to_string()];
+        report.analysis_timestamp = "2024-06-01T00:00:00Z".parse().unwrap();
+
+        let sources = to_dbt_sources(&report, "lake", None);
+        let table = &sources["sources"][0]["tables"][0];
+        assert_eq!(table["name"], "sales_events");
+        assert_eq!(table["external"]["location"], "s3://lake/Sales-Events/");
+        assert_eq!(
+            table["tests"][0]["drainage_finding"]["check"],
+            "health-score"
+        );
+        assert_eq!(
+            table["tests"][1]["drainage_finding"]["config"]["severity"],
+            "warn"
+        );
+        assert!(table.get("freshness").is_none());
+        let yaml = serde_yaml::to_string(&sources).unwrap();
+        assert!(yaml.contains("\nversion: 2\n"));
+        assert!(yaml.starts_with("sources:\n- name: lake\n"));
+        assert_eq!(
+            to_dbt_sources(&report, "lake", Some("events"))["sources"][0]["tables"][0]["name"],
+            "events"
+        );
+
+        let validation = to_great_expectations(&report);
+        assert_eq!(validation["success"], false);
+        assert_eq!(validation["statistics"]["evaluated_expectations"], 2);
+        assert_eq!(validation["statistics"]["success_percent"], 0.0);
+        assert_eq!(
+            validation["results"][1]["result"]["observed_value"],
+            json!(1)
+        );
+        assert_eq!(
+            validation["meta"]["expectation_suite_name"],
+            "drainage.sales_events"
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! Overall time limit of one analysis (`max_duration_seconds`).
+//!
+//! The table's metadata (Delta log, Iceberg metadata and manifests) is always read in full,
+//! since nothing can be said about the table without it. What takes long on large or slow
+//! buckets is listing data files and the optional stages (footer sampling, HEAD checks,
+//! access logs): once the deadline passes, the listing stops paging and those stages are cut
+//! short or skipped, and the report is flagged as truncated with the stages affected.
+
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// When an analysis has to wrap up, and which of its stages it cut short
+#[derive(Debug, Default)]
+pub struct Deadline {
+    at: Option<Instant>, // None: no limit
+    max_duration_seconds: f64,
+    cut_stages: Mutex<Vec<String>>,
+}
+
+impl Deadline {
+    /// A deadline `max_duration_seconds` from now, or none
+    pub fn after(max_duration_seconds: Option<f64>) -> Self {
+        Self {
+            at: max_duration_seconds
+                .map(|seconds| Instant::now() + Duration::from_secs_f64(seconds.max(0.0))),
+            max_duration_seconds: max_duration_seconds.unwrap_or(0.0),
+            cut_stages: Mutex::default(),
+        }
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.at.is_some()
+    }
+
+    pub fn expired(&self) -> bool {
+        self.at.is_some_and(|at| Instant::now() >= at)
+    }
+
+    /// Record that `stage` was cut short or skipped
+    pub fn cut(&self, stage: &str) {
+        let mut stages = self.cut_stages.lock().unwrap_or_else(|e| e.into_inner());
+        if !stages.iter().any(|s| s == stage) {
+            stages.push(stage.to_string());
+        }
+    }
+
+    /// Whether `stage` was cut short or skipped
+    pub fn was_cut(&self, stage: &str) -> bool {
+        self.cut_stages
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(|s| s == stage)
+    }
+
+    /// Run `stage` until the deadline; None (and the stage recorded as cut) when the
+    /// deadline passed first, in which case its in-flight requests are dropped
+    pub async fn run<F: Future>(&self, stage: &str, future: F) -> Option<F::Output> {
+        let Some(at) = self.at else {
+            return Some(future.await);
+        };
+        if self.expired() {
+            self.cut(stage);
+            return None;
+        }
+        match tokio::time::timeout_at(at, future).await {
+            Ok(output) => Some(output),
+            Err(_) => {
+                self.cut(stage);
+                None
+            }
+        }
+    }
+
+    /// What the report records when a stage was cut, None when the analysis finished
+    pub fn truncation(&self) -> Option<crate::types::Truncation> {
+        let stages = self
+            .cut_stages
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        (!stages.is_empty()).then_some(crate::types::Truncation {
+            max_duration_seconds: self.max_duration_seconds,
+            stages,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadline_cuts_slow_stages() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let unlimited = Deadline::after(None);
+            assert_eq!(unlimited.run("listing", async { 1 }).await, Some(1));
+            assert!(unlimited.truncation().is_none());
+
+            let deadline = Deadline::after(Some(0.05));
+            assert_eq!(deadline.run("access_logs", async { 2 }).await, Some(2));
+            let slow = tokio::time::sleep(Duration::from_secs(5));
+            assert_eq!(deadline.run("scanning_footers", slow).await, None);
+            assert!(deadline.expired());
+            // Stages after the deadline are skipped without starting
+            assert_eq!(deadline.run("access_logs", async { 3 }).await, None);
+
+            let truncation = deadline.truncation().unwrap();
+            assert_eq!(truncation.stages, ["scanning_footers", "access_logs"]);
+            assert_eq!(truncation.max_duration_seconds, 0.05);
+        });
+    }
+}
@@ -1,11 +1,15 @@
+use crate::clustering::{delta_bounds, rank_candidates, ColumnBounds, MAX_Z_ORDER_COLUMNS};
+use crate::config::AnalysisConfig;
+use crate::error::DrainageError;
 use crate::s3_client::S3ClientWrapper;
+use crate::stream::EventSink;
 use crate::types::*;
 use anyhow::Result;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone)]
-struct SchemaChange {
+struct SchemaSnapshot {
     #[allow(dead_code)]
     version: u64,
     timestamp: u64,
@@ -15,14 +19,870 @@ struct SchemaChange {
 
 pub struct DeltaLakeAnalyzer {
     s3_client: S3ClientWrapper,
+    config: AnalysisConfig,
+    // Single clock reading shared by every age calculation in one analysis
+    analysis_time: chrono::DateTime<chrono::Utc>,
+    events: EventSink,
+    catalog_schema: Option<TableSchema>,
+}
+
+/// Parse a transaction log file into its JSON actions. Commits are newline-delimited
+/// JSON, but files holding a single JSON document are accepted as well.
+fn parse_log_actions(content: &[u8]) -> Vec<Value> {
+    let content_str = String::from_utf8_lossy(content);
+    let mut actions = Vec::new();
+
+    for line in content_str.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str::<Value>(line) {
+            Ok(json) => actions.push(json),
+            Err(_) => {
+                // If individual line parsing fails, try parsing the entire content as a single JSON
+                return serde_json::from_slice::<Value>(content)
+                    .map(|json| vec![json])
+                    .unwrap_or_default();
+            }
+        }
+    }
+
+    actions
+}
+
+/// Latest `metaData` and `protocol` actions after replaying the transaction log
+#[derive(Debug, Clone, Default)]
+struct TableState {
+    metadata: Option<Value>,
+    protocol: Option<Value>,
+}
+
+/// Directories listed in full before reading the log when data files come from a previous
+/// report or an inventory
+const METADATA_DIRS: [&str; 2] = ["_delta_log/", "_change_data/"];
+
+/// Features implied by legacy (pre table-features) writer protocol versions
+const LEGACY_WRITER_FEATURES: &[(u32, &str)] = &[
+    (2, "appendOnly"),
+    (2, "invariants"),
+    (3, "checkConstraints"),
+    (4, "changeDataFeed"),
+    (4, "generatedColumns"),
+    (5, "columnMapping"),
+    (6, "identityColumns"),
+];
+
+/// Reader features that engines without table-features support cannot read
+const READER_FEATURE_BLOCKERS: &[&str] = &[
+    "columnMapping",
+    "deletionVectors",
+    "timestampNtz",
+    "v2Checkpoint",
+    "vacuumProtocolCheck",
+    "typeWidening",
+    "variantType",
+];
+
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Build the table features report from a protocol action and the table configuration
+fn build_table_features(protocol: &Value, configuration: Option<&Value>) -> TableFeatures {
+    let min_reader_version = protocol
+        .get("minReaderVersion")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(1) as u32;
+    let min_writer_version = protocol
+        .get("minWriterVersion")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(2) as u32;
+    let reader_features = string_list(protocol.get("readerFeatures"));
+    let writer_features = string_list(protocol.get("writerFeatures"));
+    let uses_table_features = min_reader_version >= 3 || min_writer_version >= 7;
+
+    let mut enabled_features: Vec<String> = Vec::new();
+    let mut enable = |feature: &str| {
+        if !enabled_features.iter().any(|f| f == feature) {
+            enabled_features.push(feature.to_string());
+        }
+    };
+
+    if min_writer_version >= 7 {
+        for feature in reader_features.iter().chain(writer_features.iter()) {
+            enable(feature);
+        }
+    } else {
+        for (version, feature) in LEGACY_WRITER_FEATURES {
+            if min_writer_version >= *version {
+                enable(feature);
+            }
+        }
+    }
+
+    // Table properties switch on features, but only if the protocol supports them
+    let mut features_requiring_upgrade = Vec::new();
+    if let Some(config) = configuration {
+        let property = |key: &str| config.get(key).and_then(|v| v.as_str());
+        let supports = |feature: &str, legacy_writer_version: u32| {
+            if min_writer_version >= 7 {
+                writer_features.iter().any(|f| f == feature)
+            } else {
+                min_writer_version >= legacy_writer_version
+            }
+        };
+
+        // (feature, legacy writer version that implies it, requested by properties)
+        let requested = [
+            (
+                "deletionVectors",
+                u32::MAX,
+                property("delta.enableDeletionVectors") == Some("true"),
+            ),
+            (
+                "changeDataFeed",
+                4,
+                property("delta.enableChangeDataFeed") == Some("true"),
+            ),
+            (
+                "columnMapping",
+                5,
+                matches!(
+                    property("delta.columnMapping.mode"),
+                    Some("name") | Some("id")
+                ),
+            ),
+            (
+                "appendOnly",
+                2,
+                property("delta.appendOnly") == Some("true"),
+            ),
+        ];
+        for (feature, legacy_writer_version, is_requested) in requested {
+            if !is_requested {
+                continue;
+            }
+            if supports(feature, legacy_writer_version) {
+                enable(feature);
+            } else {
+                features_requiring_upgrade.push(feature.to_string());
+            }
+        }
+    }
+
+    let compatibility_blockers: Vec<String> = if min_reader_version >= 3 {
+        reader_features
+            .iter()
+            .filter(|f| READER_FEATURE_BLOCKERS.contains(&f.as_str()))
+            .cloned()
+            .collect()
+    } else if min_reader_version == 2 {
+        vec!["columnMapping".to_string()]
+    } else {
+        Vec::new()
+    };
+
+    TableFeatures {
+        min_reader_version,
+        min_writer_version,
+        reader_features,
+        writer_features,
+        enabled_features,
+        uses_table_features,
+        compatibility_blockers,
+        features_requiring_upgrade,
+    }
+}
+
+/// Entries of an `add` or `remove` action, written either as one object or as an array
+fn action_entries<'a>(json: &'a Value, action: &str) -> Vec<&'a Value> {
+    match json.get(action) {
+        Some(Value::Array(entries)) => entries.iter().collect(),
+        Some(entry) => vec![entry],
+        None => Vec::new(),
+    }
+}
+
+/// Commits that added and removed a data file, as recorded in the transaction log
+#[derive(Debug, Clone, Default, PartialEq)]
+struct FileLifecycle {
+    added_version: Option<u64>,
+    created: Option<chrono::DateTime<chrono::Utc>>,
+    removed_version: Option<u64>,
+    num_records: Option<u64>,
+    size: Option<u64>,                         // `size` of the add action
+    partition_values: HashMap<String, String>, // null values as `__HIVE_DEFAULT_PARTITION__`
+}
+
+/// Replay `add` and `remove` actions, keyed by table-relative path. A file added again
+/// after being removed is live again, so the later add clears the tombstone.
+fn file_lifecycles(commits: &[(u64, Vec<Value>)]) -> HashMap<String, FileLifecycle> {
+    let mut lifecycles: HashMap<String, FileLifecycle> = HashMap::new();
+    for (version, actions) in commits {
+        for json in actions {
+            for add in action_entries(json, "add") {
+                if let Some(path) = add.get("path").and_then(|p| p.as_str()) {
+                    let created = add
+                        .get("modificationTime")
+                        .and_then(|t| t.as_i64())
+                        .and_then(chrono::DateTime::from_timestamp_millis);
+                    // `stats` is a JSON document embedded as a string
+                    let num_records = add
+                        .get("stats")
+                        .and_then(|s| s.as_str())
+                        .and_then(|s| serde_json::from_str::<Value>(s).ok())
+                        .and_then(|stats| stats.get("numRecords").and_then(|n| n.as_u64()));
+                    let partition_values = add
+                        .get("partitionValues")
+                        .and_then(|v| v.as_object())
+                        .map(|values| {
+                            values
+                                .iter()
+                                .map(|(column, value)| {
+                                    let value = value
+                                        .as_str()
+                                        .unwrap_or(crate::types::HIVE_DEFAULT_PARTITION);
+                                    (column.clone(), value.to_string())
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    lifecycles.insert(
+                        path.to_string(),
+                        FileLifecycle {
+                            added_version: Some(*version),
+                            created,
+                            removed_version: None,
+                            num_records,
+                            size: add.get("size").and_then(|s| s.as_u64()),
+                            partition_values,
+                        },
+                    );
+                }
+            }
+            for remove in action_entries(json, "remove") {
+                if let Some(path) = remove.get("path").and_then(|p| p.as_str()) {
+                    lifecycles
+                        .entry(path.to_string())
+                        .or_default()
+                        .removed_version = Some(*version);
+                }
+            }
+        }
+    }
+    lifecycles
+}
+
+/// Column bounds from the `stats` of each live file's add action, keyed by table-relative path
+fn live_file_bounds(commits: &[(u64, Vec<Value>)]) -> HashMap<String, ColumnBounds> {
+    let mut bounds = HashMap::new();
+    for (_, actions) in commits {
+        for json in actions {
+            for add in action_entries(json, "add") {
+                if let Some(path) = add.get("path").and_then(|p| p.as_str()) {
+                    let stats = add
+                        .get("stats")
+                        .and_then(|s| s.as_str())
+                        .and_then(|s| serde_json::from_str::<Value>(s).ok());
+                    match stats {
+                        Some(stats) => bounds.insert(path.to_string(), delta_bounds(&stats)),
+                        None => bounds.remove(path),
+                    };
+                }
+            }
+            for remove in action_entries(json, "remove") {
+                if let Some(path) = remove.get("path").and_then(|p| p.as_str()) {
+                    bounds.remove(path);
+                }
+            }
+        }
+    }
+    bounds
+}
+
+/// Object key of an add action's path: relative paths are under the table, absolute ones
+/// must be in the table's bucket. Both are URL-encoded.
+fn referenced_key(path: &str, bucket: &str, table_prefix: &str) -> Option<String> {
+    let decode = |path: &str| {
+        percent_encoding::percent_decode_str(path)
+            .decode_utf8_lossy()
+            .into_owned()
+    };
+    if path.contains("://") {
+        return path.strip_prefix(&format!("s3://{}/", bucket)).map(decode);
+    }
+    Some(match table_prefix.trim_end_matches('/') {
+        "" => decode(path),
+        prefix => format!("{}/{}", prefix, decode(path)),
+    })
+}
+
+/// Compare each live file's size in the listing with its add action, and note whether
+/// partition values only exist in the log
+fn check_log_consistency(
+    data_files: &[&crate::s3_client::ObjectInfo],
+    lifecycles: &HashMap<String, FileLifecycle>,
+    table_prefix: &str,
+) -> LogConsistencyMetrics {
+    let mut metrics = LogConsistencyMetrics::new();
+    for file in data_files {
+        let relative = crate::s3_client::relative_key(table_prefix, &file.key);
+        let Some(lifecycle) = lifecycles.get(relative) else {
+            continue;
+        };
+        if lifecycle.removed_version.is_some() {
+            continue;
+        }
+        let (dirs, _) = relative.rsplit_once('/').unwrap_or(("", relative));
+        if !lifecycle.partition_values.is_empty() && !dirs.split('/').any(|d| d.contains('=')) {
+            metrics.partitions_from_log = true;
+        }
+        if let Some(size) = lifecycle.size {
+            metrics.check_size(&file.key, size, file.size as u64);
+        }
+    }
+    metrics
+}
+
+/// Top-level schema columns as (column mapping id, logical name); columns without an id are skipped
+fn mapped_columns(metadata: &Value) -> Vec<(u64, String)> {
+    metadata
+        .get("schemaString")
+        .and_then(|s| s.as_str())
+        .and_then(|s| serde_json::from_str::<Value>(s).ok())
+        .and_then(|schema| schema.get("fields").and_then(|f| f.as_array()).cloned())
+        .map(|fields| {
+            fields
+                .iter()
+                .filter_map(|field| {
+                    let id = field
+                        .get("metadata")?
+                        .get("delta.columnMapping.id")?
+                        .as_u64()?;
+                    let name = field.get("name")?.as_str()?;
+                    Some((id, name.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Replay commits, ordered by version, to report the column mapping mode along with
+/// renamed and dropped columns. Dropped columns stay in files written before the drop
+/// until they are rewritten, so their share of those files is counted as reclaimable.
+fn build_column_mapping_metrics(commits: &[(u64, Vec<Value>)]) -> Option<ColumnMappingMetrics> {
+    let mut latest_metadata: Option<&Value> = None;
+    let mut original_names: HashMap<u64, String> = HashMap::new();
+    let mut latest_names: HashMap<u64, String> = HashMap::new();
+    // Column ids of every schema seen, indexed by live files written under it
+    let mut schemas: Vec<Vec<u64>> = vec![Vec::new()];
+    let mut live_files: HashMap<String, (u64, usize)> = HashMap::new();
+
+    for (_, actions) in commits {
+        for json in actions {
+            if let Some(metadata) = json.get("metaData") {
+                latest_metadata = Some(metadata);
+                let columns = mapped_columns(metadata);
+                for (id, name) in &columns {
+                    original_names.entry(*id).or_insert_with(|| name.clone());
+                    latest_names.insert(*id, name.clone());
+                }
+                schemas.push(columns.into_iter().map(|(id, _)| id).collect());
+            }
+            for add in action_entries(json, "add") {
+                if let Some(path) = add.get("path").and_then(|p| p.as_str()) {
+                    let size = add.get("size").and_then(|s| s.as_u64()).unwrap_or(0);
+                    live_files.insert(path.to_string(), (size, schemas.len() - 1));
+                }
+            }
+            for remove in action_entries(json, "remove") {
+                if let Some(path) = remove.get("path").and_then(|p| p.as_str()) {
+                    live_files.remove(path);
+                }
+            }
+        }
+    }
+
+    let metadata = latest_metadata?;
+    let configuration = metadata.get("configuration");
+    let mode = configuration
+        .and_then(|c| c.get("delta.columnMapping.mode"))
+        .and_then(|m| m.as_str())
+        .unwrap_or("none")
+        .to_string();
+    let is_enabled = mode == "name" || mode == "id";
+
+    let current_ids: HashSet<u64> = schemas.last().into_iter().flatten().copied().collect();
+    let max_column_id = configuration
+        .and_then(|c| c.get("delta.columnMapping.maxColumnId"))
+        .and_then(|v| {
+            v.as_str()
+                .and_then(|s| s.parse().ok())
+                .or_else(|| v.as_u64())
+        })
+        .or_else(|| latest_names.keys().max().copied())
+        .unwrap_or(0);
+
+    let mut renamed: Vec<(u64, String)> = current_ids
+        .iter()
+        .filter_map(|id| {
+            let original = original_names.get(id)?;
+            let latest = latest_names.get(id)?;
+            (original != latest).then(|| (*id, format!("{} -> {}", original, latest)))
+        })
+        .collect();
+    renamed.sort();
+
+    let mut dropped: Vec<(u64, String)> = latest_names
+        .iter()
+        .filter(|(id, _)| !current_ids.contains(id))
+        .map(|(id, name)| (*id, name.clone()))
+        .collect();
+    dropped.sort();
+    let dropped_ids: HashSet<u64> = dropped.iter().map(|(id, _)| *id).collect();
+
+    let mut files_with_dropped_columns = 0;
+    let mut reclaimable_bytes = 0u64;
+    for (size, schema_index) in live_files.values() {
+        let columns = &schemas[*schema_index];
+        let stale = columns.iter().filter(|id| dropped_ids.contains(id)).count();
+        if stale > 0 {
+            files_with_dropped_columns += 1;
+            reclaimable_bytes += size * stale as u64 / columns.len() as u64;
+        }
+    }
+
+    Some(ColumnMappingMetrics {
+        mode,
+        is_enabled,
+        max_column_id,
+        renamed_columns: renamed.into_iter().map(|(_, r)| r).collect(),
+        dropped_columns: dropped.into_iter().map(|(_, name)| name).collect(),
+        files_with_dropped_columns,
+        reclaimable_bytes,
+    })
+}
+
+/// Column-level schema changes across commits, comparing each `metaData` schema with the previous one
+fn schema_change_log(commits: &[(u64, Vec<Value>)]) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+    let mut previous_fields: Option<Vec<Value>> = None;
+
+    for (version, actions) in commits {
+        let timestamp = actions
+            .iter()
+            .find_map(|json| json.get("commitInfo")?.get("timestamp")?.as_i64())
+            .and_then(chrono::DateTime::from_timestamp_millis);
+        for json in actions {
+            let fields = json
+                .get("metaData")
+                .and_then(|m| m.get("schemaString"))
+                .and_then(|s| s.as_str())
+                .and_then(|s| serde_json::from_str::<Value>(s).ok())
+                .and_then(|schema| schema.get("fields").and_then(|f| f.as_array()).cloned());
+            if let Some(fields) = fields {
+                if let Some(ref previous) = previous_fields {
+                    changes.extend(SchemaChange::between(
+                        *version as i64,
+                        timestamp,
+                        previous,
+                        &fields,
+                    ));
+                }
+                previous_fields = Some(fields);
+            }
+        }
+    }
+
+    changes
+}
+
+/// Parse a Delta interval property such as `interval 7 days` into days
+pub(crate) fn parse_interval_days(interval: &str) -> Option<f64> {
+    let parts: Vec<&str> = interval
+        .trim()
+        .trim_start_matches("interval")
+        .split_whitespace()
+        .collect();
+    if parts.len() != 2 {
+        return None;
+    }
+
+    let amount: f64 = parts[0].parse().ok()?;
+    let unit_days = match parts[1].to_lowercase().trim_end_matches('s') {
+        "week" => 7.0,
+        "day" => 1.0,
+        "hour" => 1.0 / 24.0,
+        "minute" => 1.0 / 1440.0,
+        "second" => 1.0 / 86400.0,
+        _ => return None,
+    };
+    Some(amount * unit_days)
+}
+
+/// Summarize `_change_data/` files against the retention window that VACUUM enforces
+fn build_change_data_feed_metrics(
+    change_files: &[&crate::s3_client::ObjectInfo],
+    configuration: Option<&Value>,
+    as_of: chrono::DateTime<chrono::Utc>,
+) -> Option<ChangeDataFeedMetrics> {
+    let property = |key: &str| {
+        configuration
+            .and_then(|c| c.get(key))
+            .and_then(|v| v.as_str())
+    };
+    let is_enabled = property("delta.enableChangeDataFeed") == Some("true");
+    if !is_enabled && change_files.is_empty() {
+        return None;
+    }
+
+    let retention_days = property("delta.deletedFileRetentionDuration")
+        .and_then(parse_interval_days)
+        .unwrap_or(7.0);
+
+    let mut oldest_change_file_age_days: f64 = 0.0;
+    let mut files_beyond_retention = 0;
+    let mut bytes_beyond_retention = 0u64;
+    for file in change_files {
+        let age_days = file
+            .last_modified
+            .map(|modified| (as_of - modified).num_seconds().max(0) as f64 / 86400.0)
+            .unwrap_or(0.0);
+        oldest_change_file_age_days = oldest_change_file_age_days.max(age_days);
+        if age_days > retention_days {
+            files_beyond_retention += 1;
+            bytes_beyond_retention += file.size as u64;
+        }
+    }
+
+    Some(ChangeDataFeedMetrics {
+        is_enabled,
+        change_file_count: change_files.len(),
+        change_size_bytes: change_files.iter().map(|f| f.size as u64).sum(),
+        oldest_change_file_age_days,
+        retention_days,
+        files_beyond_retention,
+        bytes_beyond_retention,
+        is_retention_unbounded: files_beyond_retention > 0,
+    })
+}
+
+/// `delta.checkpointInterval` and `delta.logRetentionDuration` when unset
+const DEFAULT_CHECKPOINT_INTERVAL: u64 = 10;
+const DEFAULT_LOG_RETENTION_DAYS: f64 = 30.0;
+/// `delta.deletedFileRetentionDuration` when unset
+const DEFAULT_DELETED_FILE_RETENTION_DAYS: f64 = 7.0;
+/// JSON a reader should replay on top of a checkpoint at most
+const TARGET_REPLAY_BYTES: f64 = 10.0 * 1024.0 * 1024.0;
+/// JSON commits the retained log should hold at most
+const TARGET_RETAINED_COMMITS: f64 = 10_000.0;
+
+/// Checkpoint cadence and JSON log growth, from the `_delta_log/` objects
+fn build_delta_log_metrics(
+    log_objects: &[&crate::s3_client::ObjectInfo],
+    configuration: Option<&Value>,
+    as_of: chrono::DateTime<chrono::Utc>,
+) -> Option<DeltaLogMetrics> {
+    let property = |key: &str| {
+        configuration
+            .and_then(|c| c.get(key))
+            .and_then(|v| v.as_str())
+    };
+    let checkpoint_interval = property("delta.checkpointInterval")
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_CHECKPOINT_INTERVAL)
+        .max(1);
+    let log_retention_days = property("delta.logRetentionDuration")
+        .and_then(parse_interval_days)
+        .unwrap_or(DEFAULT_LOG_RETENTION_DAYS);
+
+    let file_name = |key: &str| key.rsplit('/').next().unwrap_or_default().to_string();
+    // Commits are `<20-digit version>.json`; compacted logs and sidecars aren't
+    let commits: Vec<&crate::s3_client::ObjectInfo> = log_objects
+        .iter()
+        .copied()
+        .filter(|obj| {
+            file_name(&obj.key)
+                .strip_suffix(".json")
+                .is_some_and(|version| {
+                    version.len() == 20 && version.bytes().all(|b| b.is_ascii_digit())
+                })
+        })
+        .collect();
+    let mut checkpoints: Vec<u64> = log_objects
+        .iter()
+        .filter(|obj| file_name(&obj.key).contains(".checkpoint."))
+        .map(|obj| log_version(&obj.key))
+        .collect();
+    checkpoints.sort_unstable();
+    checkpoints.dedup();
+    if commits.is_empty() && checkpoints.is_empty() {
+        return None;
+    }
+
+    let latest_checkpoint_version = checkpoints.last().copied();
+    let latest_version = commits
+        .iter()
+        .map(|obj| log_version(&obj.key))
+        .chain(latest_checkpoint_version)
+        .max()
+        .unwrap_or(0);
+    let since_checkpoint: Vec<&crate::s3_client::ObjectInfo> = commits
+        .iter()
+        .copied()
+        .filter(|obj| latest_checkpoint_version.is_none_or(|cp| log_version(&obj.key) > cp))
+        .collect();
+    let commits_since_checkpoint = since_checkpoint.len() as u64;
+    let bytes_since_checkpoint: u64 = since_checkpoint.iter().map(|obj| obj.size as u64).sum();
+    let avg_commits_per_checkpoint = (checkpoints.len() > 1).then(|| {
+        (checkpoints[checkpoints.len() - 1] - checkpoints[0]) as f64
+            / (checkpoints.len() - 1) as f64
+    });
+
+    let json_log_files = commits.len();
+    let json_log_size_bytes: u64 = commits.iter().map(|obj| obj.size as u64).sum();
+    let times: Vec<chrono::DateTime<chrono::Utc>> =
+        commits.iter().filter_map(|obj| obj.last_modified).collect();
+    let days = |from: chrono::DateTime<chrono::Utc>, to: chrono::DateTime<chrono::Utc>| {
+        (to - from).num_seconds().max(0) as f64 / 86400.0
+    };
+    let oldest = times.iter().min().copied();
+    let oldest_json_log_age_days = oldest.map(|oldest| days(oldest, as_of));
+    let commits_per_day = match (oldest, times.iter().max()) {
+        (Some(oldest), Some(newest)) if days(oldest, *newest) > 0.0 => {
+            Some(times.len() as f64 / days(oldest, *newest))
+        }
+        _ => None,
+    };
+    // Log cleanup only deletes commits a checkpoint covers
+    let expired_json_log_files = commits
+        .iter()
+        .filter(|obj| {
+            latest_checkpoint_version.is_some_and(|cp| log_version(&obj.key) < cp)
+                && obj
+                    .last_modified
+                    .is_some_and(|modified| days(modified, as_of) > log_retention_days + 1.0)
+        })
+        .count();
+
+    // Keep the JSON replayed on top of a checkpoint small: large commits call for denser
+    // checkpoints
+    let recommended_checkpoint_interval = (json_log_files > 0)
+        .then(|| {
+            let avg_commit_bytes = (json_log_size_bytes as f64 / json_log_files as f64).max(1.0);
+            ((TARGET_REPLAY_BYTES / avg_commit_bytes).floor() as u64).clamp(1, 100)
+        })
+        .filter(|recommended| *recommended < checkpoint_interval);
+    // Keep the retained log to about TARGET_RETAINED_COMMITS commits, and a week of history
+    let recommended_log_retention_days = commits_per_day
+        .filter(|_| json_log_files as f64 > TARGET_RETAINED_COMMITS)
+        .map(|per_day| ((TARGET_RETAINED_COMMITS / per_day).floor() as u64).max(7))
+        .filter(|recommended| (*recommended as f64) < log_retention_days);
+
+    let is_pathological = commits_since_checkpoint > 10 * checkpoint_interval
+        || bytes_since_checkpoint > 100 * 1024 * 1024
+        || json_log_files as f64 > TARGET_RETAINED_COMMITS
+        || json_log_size_bytes > 1024 * 1024 * 1024;
+
+    Some(DeltaLogMetrics {
+        latest_version,
+        checkpoint_count: checkpoints.len(),
+        latest_checkpoint_version,
+        commits_since_checkpoint,
+        bytes_since_checkpoint,
+        avg_commits_per_checkpoint,
+        json_log_files,
+        json_log_size_bytes,
+        oldest_json_log_age_days,
+        commits_per_day,
+        expired_json_log_files,
+        checkpoint_interval,
+        log_retention_days,
+        recommended_checkpoint_interval,
+        recommended_log_retention_days,
+        is_pathological,
+    })
+}
+
+/// Extract the commit version from a `_delta_log/<version>.json` key
+/// When each commit was made: its `commitInfo.timestamp`, else the log file's modification time
+fn commit_timestamps(
+    commits: &[(u64, Vec<Value>)],
+    log_files: &[&crate::s3_client::ObjectInfo],
+) -> Vec<chrono::DateTime<chrono::Utc>> {
+    commits
+        .iter()
+        .filter_map(|(version, actions)| {
+            actions
+                .iter()
+                .find_map(|json| json.get("commitInfo")?.get("timestamp")?.as_i64())
+                .and_then(chrono::DateTime::from_timestamp_millis)
+                .or_else(|| {
+                    log_files
+                        .iter()
+                        .find(|file| log_version(&file.key) == *version)
+                        .and_then(|file| file.last_modified)
+                })
+        })
+        .collect()
+}
+
+const MAX_LISTED_VACUUMED_FILES: usize = 100;
+
+/// Versions in the log that read tombstoned files missing from storage, i.e. vacuumed while
+/// commits referencing them are still within log retention. Only files `partition_filter`
+/// lets the listing see are checked.
+fn check_time_travel_integrity(
+    commits: &[(u64, Vec<Value>)],
+    lifecycles: &HashMap<String, FileLifecycle>,
+    listed_keys: &HashSet<&str>,
+    bucket: &str,
+    table_prefix: &str,
+    partition_filter: &crate::config::PartitionFilter,
+    configuration: Option<&Value>,
+) -> Option<TimeTravelIntegrityMetrics> {
+    let oldest_log_version = commits.first()?.0;
+    let latest_version = commits.last()?.0;
+
+    // Versions [added, removed) of each vacuumed file, clipped to the log
+    let mut broken: Vec<(u64, u64)> = Vec::new();
+    let mut vacuumed_paths = Vec::new();
+    let mut vacuumed_size_bytes = 0;
+    for (path, lifecycle) in lifecycles {
+        let Some(removed) = lifecycle.removed_version else {
+            continue;
+        };
+        let added = lifecycle
+            .added_version
+            .unwrap_or(oldest_log_version)
+            .max(oldest_log_version);
+        if removed <= added {
+            continue;
+        }
+        let Some(key) = referenced_key(path, bucket, table_prefix) else {
+            continue;
+        };
+        let relative = crate::s3_client::relative_key(table_prefix, &key);
+        if !partition_filter.matches(relative) || listed_keys.contains(key.as_str()) {
+            continue;
+        }
+        broken.push((added, removed));
+        vacuumed_paths.push(path.clone());
+        vacuumed_size_bytes += lifecycle.size.unwrap_or_default();
+    }
+
+    broken.sort_unstable();
+    let mut broken_version_count = 0;
+    let mut covered_until = oldest_log_version;
+    for (from, to) in &broken {
+        let from = (*from).max(covered_until);
+        if *to > from {
+            broken_version_count += (to - from) as usize;
+            covered_until = *to;
+        }
+    }
+    let earliest_intact_version = broken
+        .iter()
+        .map(|(_, removed)| *removed)
+        .max()
+        .unwrap_or(oldest_log_version);
+
+    vacuumed_paths.sort();
+    let vacuumed_file_count = vacuumed_paths.len();
+    vacuumed_paths.truncate(MAX_LISTED_VACUUMED_FILES);
+    let property = |key: &str, default: f64| {
+        configuration
+            .and_then(|c| c.get(key))
+            .and_then(|v| v.as_str())
+            .and_then(parse_interval_days)
+            .unwrap_or(default)
+    };
+    Some(TimeTravelIntegrityMetrics {
+        oldest_log_version,
+        latest_version,
+        earliest_intact_version,
+        broken_version_count,
+        vacuumed_file_count,
+        vacuumed_size_bytes,
+        vacuumed_paths,
+        deleted_file_retention_days: property(
+            "delta.deletedFileRetentionDuration",
+            DEFAULT_DELETED_FILE_RETENTION_DAYS,
+        ),
+        log_retention_days: property("delta.logRetentionDuration", DEFAULT_LOG_RETENTION_DAYS),
+    })
+}
+
+fn log_version(key: &str) -> u64 {
+    key.split('/')
+        .next_back()
+        .and_then(|name| name.split('.').next())
+        .and_then(|version| version.parse::<u64>().ok())
+        .unwrap_or(0)
 }
 
 impl DeltaLakeAnalyzer {
-    pub fn new(s3_client: S3ClientWrapper) -> Self {
-        Self { s3_client }
+    pub fn new(s3_client: S3ClientWrapper, config: AnalysisConfig) -> Self {
+        Self {
+            s3_client: s3_client.with_cache(config.object_cache.as_ref()),
+            analysis_time: config.clock.now(),
+            config,
+            events: EventSink::default(),
+            catalog_schema: None,
+        }
+    }
+
+    /// Report drift between this catalog-registered schema and the table's `schemaString`
+    pub fn with_catalog_schema(mut self, catalog_schema: Option<TableSchema>) -> Self {
+        self.catalog_schema = catalog_schema;
+        self
+    }
+
+    /// Report progress, warnings and metrics to `events` as the analysis runs
+    pub fn with_events(mut self, events: EventSink) -> Self {
+        self.events = events;
+        self
     }
 
     pub async fn analyze(&self) -> Result<HealthReport> {
+        crate::otlp::traced(&self.config, &self.events, async {
+            // Failures name the stage that was running
+            let report = self.analyze_table().await.map_err(|e| {
+                let stage = self.events.current_stage();
+                anyhow::Error::from(DrainageError::from_anyhow(e, stage.as_deref()))
+            });
+            self.events.end_phase();
+            if let Ok(ref report) = report {
+                crate::history::record(&self.s3_client, &self.config, &self.events, report).await;
+                crate::observability::record(&self.s3_client, &self.config, &self.events, report)
+                    .await;
+                crate::notify::notify(&self.config, &self.events, report).await;
+                crate::cloud_metrics::publish(&self.s3_client, &self.config, &self.events, report)
+                    .await;
+            }
+            report.map(|mut report| {
+                report.analysis_log = Some(self.events.analysis_log(!self.config.deterministic));
+                report
+            })
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "analyze", skip_all, fields(table_type = "delta", bucket = self.s3_client.get_bucket(), prefix = self.s3_client.get_prefix()))]
+    async fn analyze_table(&self) -> Result<HealthReport> {
+        let started = std::time::Instant::now();
+        let deadline = crate::deadline::Deadline::after(self.config.max_duration_seconds);
+        let mut phases = crate::partial::Phases::new(self.config.allow_partial, &self.events);
         let mut report = HealthReport::new(
             format!(
                 "s3://{}/{}",
@@ -31,38 +891,144 @@ impl DeltaLakeAnalyzer {
             ),
             "delta".to_string(),
         );
+        report.analysis_timestamp = self.analysis_time;
+        if !self.config.partition_filter.is_empty() {
+            report.partition_filter = Some(self.config.partition_filter.to_map());
+        }
+        if !self.config.tags.is_empty() {
+            report.tags = Some(self.config.tags.clone());
+        }
 
-        // List all files in the Delta table directory
-        let all_objects = self
-            .s3_client
-            .list_objects(self.s3_client.get_prefix())
+        self.events.progress("listing");
+        // List all files in the Delta table directory. Warm starts list the log first and
+        // only the partitions it changed afterwards; with an inventory, data files come
+        // from the inventory report instead.
+        let previous = crate::warm_start::previous_report(&self.config, &report);
+        if previous.is_none() && self.config.previous_report.is_some() {
+            self.events.warning(
+                "The previous report is for another table or partition filter; listing the whole table",
+            );
+        }
+        let mut all_objects = if previous.is_some() || self.config.inventory.is_some() {
+            let mut objects = Vec::new();
+            for dir in METADATA_DIRS {
+                objects.extend(
+                    self.s3_client
+                        .list_objects(&self.s3_client.table_key(dir))
+                        .await?,
+                );
+            }
+            objects
+        } else {
+            self.s3_client
+                .list_table_objects(
+                    &["_delta_log/"],
+                    "",
+                    &self.config.partition_filter,
+                    &deadline,
+                )
+                .await?
+        };
+
+        self.events.progress("reading_log");
+
+        // Replay the full log once for per-file and history-based analyses
+        let commits = {
+            let (_, log_files) = self.categorize_files(&all_objects)?;
+            self.load_commits(&log_files).await?
+        };
+        let lifecycles = file_lifecycles(&commits);
+
+        let mut warm_start = crate::warm_start::partition_digests(
+            lifecycles
+                .iter()
+                .filter(|(_, lifecycle)| lifecycle.removed_version.is_none())
+                .map(|(path, _)| path.as_str()),
+        );
+        if let Some(ref location) = self.config.inventory {
+            self.events.progress("reading_inventory");
+            let (data_objects, source) = crate::inventory::data_objects(
+                &self.s3_client,
+                location,
+                &METADATA_DIRS,
+                &self.config,
+            )
             .await?;
+            all_objects.extend(data_objects);
+            report.inventory = Some(source);
+        } else if let Some(previous) = previous {
+            self.events.progress("listing_changed_partitions");
+            match crate::warm_start::list_data_objects(
+                &self.s3_client,
+                previous,
+                &mut warm_start,
+                &METADATA_DIRS,
+                &self.config,
+            )
+            .await?
+            {
+                Some(data_objects) => all_objects.extend(data_objects),
+                None => {
+                    self.events.warning(
+                        "The previous report's file paths are redacted; listing the whole table",
+                    );
+                    all_objects = self
+                        .s3_client
+                        .list_table_objects(
+                            &["_delta_log/"],
+                            "",
+                            &self.config.partition_filter,
+                            &deadline,
+                        )
+                        .await?;
+                }
+            }
+        }
+
+        // Deterministic runs see the objects in key order however they were gathered
+        if self.config.deterministic {
+            all_objects.sort_by(|a, b| a.key.cmp(&b.key));
+        }
 
         // Separate data files from metadata files
         let (data_files, metadata_files) = self.categorize_files(&all_objects)?;
 
-        // Analyze Delta log to find referenced files
-        let referenced_files = self.find_referenced_files(&metadata_files).await?;
-
         // Find clustering information
-        let clustering_columns = self.find_clustering_info(&metadata_files).await?;
+        let clustering_columns = phases
+            .check(
+                "clustering",
+                self.find_clustering_info(&metadata_files).await,
+            )?
+            .flatten();
+
+        // Load the latest table metadata (partition columns, schema, configuration) and protocol
+        let table_state = self.load_table_state(&metadata_files).await?;
 
         // Calculate metrics
         let mut metrics = HealthMetrics::new();
         metrics.total_files = data_files.len();
         metrics.total_size_bytes = data_files.iter().map(|f| f.size as u64).sum();
+        if self.config.adaptive_thresholds {
+            metrics.size_class = Some(crate::types::SizeClass::of(metrics.total_size_bytes));
+        }
+        self.events
+            .metric("total_files", metrics.total_files as f64);
+        self.events
+            .metric("total_size_bytes", metrics.total_size_bytes as f64);
 
         // Find unreferenced files
-        let referenced_set: HashSet<String> = referenced_files.into_iter().collect();
+        // Log paths are relative to the table root
         for file in &data_files {
             let file_path = format!("{}/{}", self.s3_client.get_prefix(), file.key);
-            if !referenced_set.contains(&file_path) {
-                metrics.unreferenced_files.push(FileInfo {
-                    path: file_path,
-                    size_bytes: file.size as u64,
-                    last_modified: file.last_modified.clone(),
-                    is_referenced: false,
-                });
+            let relative = crate::s3_client::relative_key(self.s3_client.get_prefix(), &file.key);
+            if !lifecycles.contains_key(relative) {
+                metrics.unreferenced_files.push(FileInfo::new(
+                    file_path,
+                    file.size as u64,
+                    file.last_modified,
+                    false,
+                    self.analysis_time,
+                ));
             }
         }
 
@@ -72,14 +1038,236 @@ impl DeltaLakeAnalyzer {
             .map(|f| f.size_bytes)
             .sum();
 
+        // Recent unreferenced files are likely uncommitted writes still in flight
+        metrics.calculate_in_flight_unreferenced(self.config.unreferenced_grace_period_hours);
+        self.events.metric(
+            "unreferenced_files",
+            metrics.unreferenced_files.len() as f64,
+        );
+
+        // Check listed sizes against the log before partitioning, which falls back to the
+        // log's partition values when paths don't carry them
+        let log_consistency =
+            check_log_consistency(&data_files, &lifecycles, self.s3_client.get_prefix());
+        let partitions_from_log = log_consistency.partitions_from_log;
+        metrics.log_consistency = Some(log_consistency);
+
+        // HEAD every live file, including those in partitions the listing skipped
+        if self.config.verify_referenced_files {
+            self.events.progress("checking_referenced_files");
+            let keys = lifecycles
+                .iter()
+                .filter(|(_, lifecycle)| lifecycle.removed_version.is_none())
+                .filter_map(|(path, _)| {
+                    referenced_key(
+                        path,
+                        self.s3_client.get_bucket(),
+                        self.s3_client.get_prefix(),
+                    )
+                })
+                .filter(|key| {
+                    self.config
+                        .partition_filter
+                        .matches(crate::s3_client::relative_key(
+                            self.s3_client.get_prefix(),
+                            key,
+                        ))
+                })
+                .collect();
+            metrics.existence_check = deadline
+                .run(
+                    "checking_referenced_files",
+                    crate::existence::check_keys(keys, |key| async move {
+                        self.s3_client.head_object(&key).await
+                    }),
+                )
+                .await;
+        }
+
+        // Incomplete multipart uploads under the table, which no listing shows
+        if self.config.check_multipart_uploads {
+            self.events.progress("listing_multipart_uploads");
+            metrics.incomplete_uploads = deadline
+                .run(
+                    "listing_multipart_uploads",
+                    crate::multipart::check_uploads(&self.s3_client, self.analysis_time),
+                )
+                .await
+                .map(|checked| phases.check("multipart_uploads", checked))
+                .transpose()?
+                .flatten();
+        }
+
         // Analyze partitioning
-        self.analyze_partitioning(&data_files, &mut metrics)?;
+        phases.check(
+            "partitioning",
+            self.analyze_partitioning(&data_files, &lifecycles, &mut metrics),
+        )?;
+        self.events
+            .metric("partition_count", metrics.partition_count as f64);
+        metrics.calculate_partition_anomalies(self.analysis_time);
+
+        // Expose the current schema alongside the health data
+        report.current_schema = table_state.metadata.as_ref().map(|table_metadata| {
+            let fields = table_metadata
+                .get("schemaString")
+                .and_then(|s| s.as_str())
+                .and_then(|s| serde_json::from_str::<Value>(s).ok())
+                .and_then(|schema| schema.get("fields").and_then(|f| f.as_array()).cloned())
+                .unwrap_or_default();
+            TableSchema::from_fields(None, &fields, self.partition_columns(table_metadata))
+        });
+
+        // Check path-derived partition depth against the declared partition columns
+        // (unless partition values only live in the log)
+        if let Some(table_metadata) = table_state
+            .metadata
+            .as_ref()
+            .filter(|_| !partitions_from_log)
+        {
+            let partition_columns = self.partition_columns(table_metadata);
+            metrics.calculate_partition_spec_mismatch(
+                &data_files,
+                self.s3_client.get_prefix(),
+                &partition_columns,
+            );
+        }
 
         // Analyze clustering if clustering columns are found
         if let Some(ref clustering_cols) = clustering_columns {
-            self.analyze_clustering(&data_files, clustering_cols, &mut metrics)?;
+            phases.check(
+                "clustering",
+                self.analyze_clustering(&data_files, clustering_cols, &mut metrics),
+            )?;
+        }
+
+        // Aggregate when files were added and which are tombstoned
+        metrics.calculate_file_attribution();
+
+        // Deep scan: inspect Parquet footers of a sample of data files, unless the previous
+        // report's sample still describes the table
+        let reusable_footers = crate::warm_start::reusable_footers(previous, &warm_start);
+        if self.config.deep_scan && reusable_footers.is_some() {
+            metrics.parquet_footers = reusable_footers;
+            warm_start.reused_footer_sample = true;
+        } else if self.config.deep_scan {
+            self.events.progress("scanning_footers");
+            let sampled = deadline
+                .run(
+                    "scanning_footers",
+                    crate::parquet_footer::sample_footers(
+                        &self.s3_client,
+                        &data_files,
+                        self.config.footer_sample_size,
+                    ),
+                )
+                .await;
+            if let Some((footers, failed_files)) = sampled {
+                if failed_files > 0 {
+                    self.events.warning(format!(
+                        "Could not read the Parquet footers of {} sampled files",
+                        failed_files
+                    ));
+                }
+                metrics.parquet_footers = crate::parquet_footer::summarize_footers(
+                    &footers,
+                    failed_files,
+                    metrics.total_size_bytes,
+                );
+            }
+        }
+
+        // Compare the catalog-registered schema with the log and sampled files
+        if let (Some(catalog_schema), Some(physical_schema)) =
+            (&self.catalog_schema, &report.current_schema)
+        {
+            metrics.calculate_schema_drift(catalog_schema, physical_schema);
+        }
+
+        // Bytes by storage class, and live files that were archived
+        metrics.storage_classes = Some(StorageClassMetrics::from_files(
+            &data_files,
+            |file| {
+                let relative =
+                    crate::s3_client::relative_key(self.s3_client.get_prefix(), &file.key);
+                lifecycles
+                    .get(relative)
+                    .is_some_and(|lifecycle| lifecycle.removed_version.is_none())
+            },
+            self.analysis_time,
+        ));
+
+        // Whether archived live files can be read now or wait on a restore
+        if let Some(ref mut storage_classes) = metrics.storage_classes {
+            if !storage_classes.archived_live_paths.is_empty() {
+                self.events.progress("checking_restore_status");
+                storage_classes.restore_status = deadline
+                    .run(
+                        "checking_restore_status",
+                        crate::existence::check_restore_status(
+                            storage_classes.archived_live_paths.clone(),
+                            |key| async move { self.s3_client.restore_header(&key).await },
+                        ),
+                    )
+                    .await;
+            }
         }
 
+        // Delta only reads Parquet; ORC and Avro files left by migrations are counted apart
+        let table_files: Vec<&crate::s3_client::ObjectInfo> = all_objects
+            .iter()
+            .filter(|obj| !obj.key.contains("_delta_log/") && !obj.key.contains("_change_data/"))
+            .collect();
+        metrics.file_formats =
+            FileFormatMetrics::from_files(&table_files, self.s3_client.get_prefix(), &["parquet"]);
+
+        // Checksums, temporary files and empty files among the table's data
+        metrics.file_naming =
+            FileNamingMetrics::from_files(&table_files, self.s3_client.get_prefix());
+
+        // Empty and truncated data files, split by whether the current snapshot references them
+        let is_live = |file: &crate::s3_client::ObjectInfo| {
+            let relative = crate::s3_client::relative_key(self.s3_client.get_prefix(), &file.key);
+            lifecycles
+                .get(relative)
+                .is_some_and(|lifecycle| lifecycle.removed_version.is_none())
+        };
+        metrics.file_integrity = FileIntegrityMetrics::from_files(&data_files, is_live);
+
+        // The same content (size and ETag) stored under several keys
+        metrics.duplicate_files = DuplicateFileMetrics::from_files(&data_files, is_live);
+
+        // Join S3 access logs against the data files to find cold and hot data
+        if let Some(ref location) = self.config.access_logs {
+            self.events.progress("reading_access_logs");
+            let loaded = deadline
+                .run(
+                    "reading_access_logs",
+                    crate::access_logs::load_access_records(&self.s3_client, location),
+                )
+                .await
+                .map(|loaded| phases.check("access_logs", loaded))
+                .transpose()?
+                .flatten();
+            if let Some((records, log_objects_read, truncated)) = loaded {
+                if truncated {
+                    self.events.warning(
+                        "Access logs were truncated to the newest log objects; older reads are not counted",
+                    );
+                }
+                metrics.access_patterns = Some(crate::access_logs::summarize_access(
+                    &records,
+                    &data_files,
+                    self.s3_client.get_bucket(),
+                    self.s3_client.get_prefix(),
+                    log_objects_read,
+                    truncated,
+                ));
+            }
+        }
+
+        self.events.progress("computing_metrics");
+
         // Calculate file size distribution
         self.calculate_file_size_distribution(&data_files, &mut metrics);
 
@@ -91,35 +1279,230 @@ impl DeltaLakeAnalyzer {
 
         // Calculate additional health metrics
         metrics.calculate_data_skew();
+        metrics.calculate_partitioning_advice();
         let metadata_files_owned: Vec<crate::s3_client::ObjectInfo> =
             metadata_files.iter().map(|f| (*f).clone()).collect();
         metrics.calculate_metadata_health(&metadata_files_owned);
         metrics.calculate_snapshot_health(metadata_files.len()); // Simplified: use metadata file count as snapshot count
 
+        // Monthly storage cost of live data, time-travel history, orphans and metadata
+        metrics.cost = Some(CostMetrics::from_files(
+            &data_files,
+            |file| {
+                let relative =
+                    crate::s3_client::relative_key(self.s3_client.get_prefix(), &file.key);
+                match lifecycles.get(relative) {
+                    Some(lifecycle) if lifecycle.removed_version.is_none() => CostCategory::Live,
+                    Some(_) => CostCategory::History,
+                    None => CostCategory::Orphan,
+                }
+            },
+            metrics.metadata_health.metadata_total_size_bytes,
+            &self.config.pricing,
+        ));
+
+        // Report protocol versions and enabled table features
+        metrics.table_features = table_state.protocol.as_ref().map(|protocol| {
+            build_table_features(
+                protocol,
+                table_state
+                    .metadata
+                    .as_ref()
+                    .and_then(|m| m.get("configuration")),
+            )
+        });
+
+        // Table properties against the best-practice ruleset
+        metrics.table_properties = Some(crate::table_properties::audit(
+            "delta",
+            table_state
+                .metadata
+                .as_ref()
+                .and_then(|m| m.get("configuration")),
+            &format!("delta.`{}`", report.table_path),
+        ));
+
+        // How recently the table was committed to and written
+        metrics.freshness = Some(FreshnessMetrics::from_commit_times(
+            commit_timestamps(&commits, &metadata_files),
+            data_files.iter().filter_map(|f| f.last_modified).max(),
+            self.analysis_time,
+            self.config.freshness_sla_hours,
+        ));
+
+        // Checkpoint cadence and JSON log growth
+        let log_objects: Vec<&crate::s3_client::ObjectInfo> = all_objects
+            .iter()
+            .filter(|obj| {
+                crate::s3_client::relative_key(self.s3_client.get_prefix(), &obj.key)
+                    .starts_with("_delta_log/")
+            })
+            .collect();
+        metrics.delta_log = build_delta_log_metrics(
+            &log_objects,
+            table_state
+                .metadata
+                .as_ref()
+                .and_then(|m| m.get("configuration")),
+            self.analysis_time,
+        );
+
+        // Versions in the log whose files were vacuumed; a cut-short listing would make
+        // every file it missed look vacuumed
+        if !deadline.was_cut("listing") {
+            let listed_keys: HashSet<&str> =
+                data_files.iter().map(|file| file.key.as_str()).collect();
+            metrics.time_travel_integrity = check_time_travel_integrity(
+                &commits,
+                &lifecycles,
+                &listed_keys,
+                self.s3_client.get_bucket(),
+                self.s3_client.get_prefix(),
+                &self.config.partition_filter,
+                table_state
+                    .metadata
+                    .as_ref()
+                    .and_then(|m| m.get("configuration")),
+            );
+        }
+
+        // Analyze Change Data Feed files
+        let change_files: Vec<&crate::s3_client::ObjectInfo> = all_objects
+            .iter()
+            .filter(|obj| obj.key.contains("_change_data/") && obj.key.ends_with(".parquet"))
+            .collect();
+        metrics.change_data_feed = build_change_data_feed_metrics(
+            &change_files,
+            table_state
+                .metadata
+                .as_ref()
+                .and_then(|m| m.get("configuration")),
+            self.analysis_time,
+        );
+
+        // Analyze column mapping, renames and data left behind by dropped columns
+        metrics.column_mapping = build_column_mapping_metrics(&commits);
+
         // Analyze deletion vectors
-        metrics.deletion_vector_metrics = self.analyze_deletion_vectors(&metadata_files).await?;
+        metrics.deletion_vector_metrics = phases
+            .check(
+                "deletion_vectors",
+                self.analyze_deletion_vectors(&metadata_files).await,
+            )?
+            .flatten();
 
         // Analyze schema evolution
-        metrics.schema_evolution = self.analyze_schema_evolution(&metadata_files).await?;
+        metrics.schema_evolution = phases
+            .check(
+                "schema_evolution",
+                self.analyze_schema_evolution(&metadata_files).await,
+            )?
+            .flatten();
+        if let Some(ref mut schema_evolution) = metrics.schema_evolution {
+            schema_evolution.schema_changes = schema_change_log(&commits);
+        }
 
         // Analyze time travel storage costs
-        metrics.time_travel_metrics = self.analyze_time_travel(&metadata_files).await?;
+        metrics.time_travel_metrics = phases
+            .check(
+                "time_travel",
+                self.analyze_time_travel(&metadata_files).await,
+            )?
+            .flatten();
+
+        // Assess readiness for Iceberg readers through Delta UniForm
+        if self.config.assess_iceberg_migration {
+            metrics.iceberg_migration = phases.check(
+                "iceberg_migration",
+                self.assess_iceberg_migration(
+                    &all_objects,
+                    &table_state,
+                    &commits,
+                    metrics.deletion_vector_metrics.as_ref(),
+                )
+                .await,
+            )?;
+        }
 
         // Analyze table constraints
-        metrics.table_constraints = self.analyze_table_constraints(&metadata_files).await?;
+        metrics.table_constraints = phases
+            .check(
+                "table_constraints",
+                self.analyze_table_constraints(&metadata_files).await,
+            )?
+            .flatten();
 
         // Analyze file compaction opportunities
-        metrics.file_compaction = self
-            .analyze_file_compaction(&data_files, &metadata_files)
-            .await?;
+        let buckets = self.config.file_size_buckets(metrics.size_class.as_ref());
+        let z_order_candidates = {
+            let bounds = live_file_bounds(&commits);
+            rank_candidates(data_files.iter().filter_map(|file| {
+                bounds.get(crate::s3_client::relative_key(
+                    self.s3_client.get_prefix(),
+                    &file.key,
+                ))
+            }))
+        };
+        metrics.file_compaction = phases
+            .check(
+                "file_compaction",
+                self.analyze_file_compaction(
+                    &data_files,
+                    &metadata_files,
+                    &buckets,
+                    z_order_candidates,
+                )
+                .await,
+            )?
+            .flatten();
+
+        self.events.progress("scoring");
+
+        // Check file counts against what the engine can plan
+        metrics.engine_limits =
+            crate::engine_limits::check_engine_limits(&metrics, &self.config.engine);
 
         // Generate recommendations
         self.generate_recommendations(&mut metrics);
+        metrics.remediation_estimates =
+            crate::remediation::estimate_remediations(&metrics, "delta", &self.config.engine);
 
         // Calculate health score
         metrics.health_score = metrics.calculate_health_score();
+        self.events.metric("health_score", metrics.health_score);
+        metrics.sub_scores = Some(metrics.calculate_sub_scores());
         report.metrics = metrics;
         report.health_score = report.metrics.health_score;
+        crate::custom_checks::run(
+            &self.events,
+            &mut report,
+            self.s3_client.get_prefix(),
+            &data_files,
+            || crate::custom_checks::delta_snapshots(&commits),
+        );
+        if let Some(ref policy) = self.config.policy {
+            report.compliance = Some(policy.evaluate(&report));
+        }
+        report.failed_phases = phases.into_failures();
+        // A partial listing can't seed a warm start
+        report.truncated = deadline.truncation();
+        match report.truncated {
+            Some(ref truncation) => self.events.warning(format!(
+                "Analysis hit max_duration_seconds={}; cut short: {}",
+                truncation.max_duration_seconds,
+                truncation.stages.join(", ")
+            )),
+            None => report.warm_start = Some(warm_start),
+        }
+        report.analysis_stats = Some(AnalysisStats::new(
+            &self.s3_client.requests,
+            started.elapsed(),
+            &self.config.pricing,
+        ));
+        report.apply_result_limits(&self.config.result_limits);
+        if self.config.deterministic {
+            report.make_deterministic();
+        }
 
         Ok(report)
     }
@@ -135,71 +1518,106 @@ impl DeltaLakeAnalyzer {
         let mut metadata_files = Vec::new();
 
         for obj in objects {
-            if obj.key.ends_with(".parquet") {
+            if obj.key.contains("_delta_log/") {
+                if obj.key.ends_with(".json") {
+                    metadata_files.push(obj);
+                }
+            } else if obj.key.contains("_change_data/") {
+                // Change Data Feed output isn't table data and is never referenced by add actions
+                continue;
+            } else if obj.key.ends_with(".parquet") {
                 data_files.push(obj);
-            } else if obj.key.contains("_delta_log/") && obj.key.ends_with(".json") {
-                metadata_files.push(obj);
             }
         }
 
         Ok((data_files, metadata_files))
     }
 
-    async fn find_referenced_files(
+    /// Readiness for UniForm Iceberg, reading the newest Iceberg metadata file if the table
+    /// already has one
+    async fn assess_iceberg_migration(
+        &self,
+        objects: &[crate::s3_client::ObjectInfo],
+        table_state: &TableState,
+        commits: &[(u64, Vec<Value>)],
+        deletion_vectors: Option<&DeletionVectorMetrics>,
+    ) -> Result<IcebergMigrationMetrics> {
+        let prefix = self.s3_client.get_prefix();
+        let newest = objects
+            .iter()
+            .filter(|obj| {
+                crate::s3_client::relative_key(prefix, &obj.key).starts_with("metadata/")
+                    && obj.key.ends_with("metadata.json")
+            })
+            .max_by(|a, b| (a.last_modified, &a.key).cmp(&(b.last_modified, &b.key)));
+        let iceberg_metadata = match newest {
+            Some(obj) => {
+                let content: Value =
+                    serde_json::from_slice(&self.s3_client.get_object(&obj.key).await?)?;
+                Some((crate::s3_client::relative_key(prefix, &obj.key), content))
+            }
+            None => None,
+        };
+        Ok(crate::migration::assess(
+            table_state.metadata.as_ref(),
+            table_state.protocol.as_ref(),
+            deletion_vectors,
+            iceberg_metadata
+                .as_ref()
+                .map(|(key, content)| (*key, content)),
+            commits.iter().map(|(version, _)| *version).max(),
+        ))
+    }
+
+    async fn load_table_state(
         &self,
         metadata_files: &[&crate::s3_client::ObjectInfo],
-    ) -> Result<Vec<String>> {
-        let mut referenced_files = Vec::new();
+    ) -> Result<TableState> {
+        let mut sorted_files = metadata_files.to_vec();
+        sorted_files.sort_by_key(|f| log_version(&f.key));
 
-        for metadata_file in metadata_files {
+        // Later commits override earlier metaData and protocol actions
+        let mut state = TableState::default();
+        for metadata_file in &sorted_files {
             let content = self.s3_client.get_object(&metadata_file.key).await?;
-
-            // Handle both single JSON objects and newline-delimited JSON (NDJSON)
-            let content_str = String::from_utf8_lossy(&content);
-
-            for line in content_str.lines() {
-                let line = line.trim();
-                if line.is_empty() {
-                    continue;
+            for json in parse_log_actions(&content) {
+                if let Some(metadata) = json.get("metaData") {
+                    state.metadata = Some(metadata.clone());
                 }
-
-                // Try to parse each line as a JSON object
-                match serde_json::from_str::<Value>(line) {
-                    Ok(json) => {
-                        if let Some(add_actions) = json.get("add") {
-                            if let Some(add_array) = add_actions.as_array() {
-                                for add_action in add_array {
-                                    if let Some(path) = add_action.get("path") {
-                                        if let Some(path_str) = path.as_str() {
-                                            referenced_files.push(path_str.to_string());
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    Err(_) => {
-                        // If individual line parsing fails, try parsing the entire content as a single JSON
-                        if let Ok(json) = serde_json::from_slice::<Value>(&content) {
-                            if let Some(add_actions) = json.get("add") {
-                                if let Some(add_array) = add_actions.as_array() {
-                                    for add_action in add_array {
-                                        if let Some(path) = add_action.get("path") {
-                                            if let Some(path_str) = path.as_str() {
-                                                referenced_files.push(path_str.to_string());
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        break; // Exit the line-by-line loop if we fall back to single JSON
-                    }
+                if let Some(protocol) = json.get("protocol") {
+                    state.protocol = Some(protocol.clone());
                 }
             }
         }
 
-        Ok(referenced_files)
+        Ok(state)
+    }
+
+    /// Load every commit's actions, ordered by version
+    async fn load_commits(
+        &self,
+        metadata_files: &[&crate::s3_client::ObjectInfo],
+    ) -> Result<Vec<(u64, Vec<Value>)>> {
+        let mut commits = Vec::with_capacity(metadata_files.len());
+        for metadata_file in metadata_files {
+            let content = self.s3_client.get_object(&metadata_file.key).await?;
+            commits.push((log_version(&metadata_file.key), parse_log_actions(&content)));
+        }
+        commits.sort_by_key(|(version, _)| *version);
+
+        Ok(commits)
+    }
+
+    fn partition_columns(&self, table_metadata: &Value) -> Vec<String> {
+        table_metadata
+            .get("partitionColumns")
+            .and_then(|cols| cols.as_array())
+            .map(|cols| {
+                cols.iter()
+                    .filter_map(|c| c.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 
     async fn find_clustering_info(
@@ -309,6 +1727,7 @@ impl DeltaLakeAnalyzer {
     fn analyze_partitioning(
         &self,
         data_files: &[&crate::s3_client::ObjectInfo],
+        lifecycles: &HashMap<String, FileLifecycle>,
         metrics: &mut HealthMetrics,
     ) -> Result<()> {
         let mut partition_map: HashMap<String, PartitionInfo> = HashMap::new();
@@ -331,6 +1750,18 @@ impl DeltaLakeAnalyzer {
                 }
             }
 
+            let lifecycle = lifecycles.get(crate::s3_client::relative_key(
+                self.s3_client.get_prefix(),
+                &file.key,
+            ));
+            // Writers may keep partition values out of the path (e.g. column mapping with
+            // random prefixes); the add action still records them
+            if partition_values.is_empty() {
+                if let Some(lifecycle) = lifecycle {
+                    partition_values = lifecycle.partition_values.clone();
+                }
+            }
+
             let partition_key = serde_json::to_string(&partition_values).unwrap_or_default();
 
             let partition_info =
@@ -346,12 +1777,21 @@ impl DeltaLakeAnalyzer {
 
             partition_info.file_count += 1;
             partition_info.total_size_bytes += file.size as u64;
-            partition_info.files.push(FileInfo {
-                path: format!("{}/{}", self.s3_client.get_prefix(), file.key),
-                size_bytes: file.size as u64,
-                last_modified: file.last_modified.clone(),
-                is_referenced: true, // We'll update this later
-            });
+            let mut file_info = FileInfo::new(
+                format!("{}/{}", self.s3_client.get_prefix(), file.key),
+                file.size as u64,
+                file.last_modified,
+                true, // We'll update this later
+                self.analysis_time,
+            );
+            file_info.storage_class = file.storage_class.clone();
+            if let Some(lifecycle) = lifecycle {
+                file_info.commit_id = lifecycle.added_version.map(|v| v as i64);
+                file_info.created = lifecycle.created;
+                file_info.removed_commit_id = lifecycle.removed_version.map(|v| v as i64);
+                file_info.record_count = lifecycle.num_records;
+            }
+            partition_info.files.push(file_info);
         }
 
         // Calculate averages for each partition
@@ -413,28 +1853,33 @@ impl DeltaLakeAnalyzer {
         data_files: &[&crate::s3_client::ObjectInfo],
         metrics: &mut HealthMetrics,
     ) {
+        let buckets = &self.config.file_size_buckets(metrics.size_class.as_ref());
         for file in data_files {
-            let size_mb = file.size as f64 / (1024.0 * 1024.0);
-
-            if size_mb < 16.0 {
-                metrics.file_size_distribution.small_files += 1;
-            } else if size_mb < 128.0 {
-                metrics.file_size_distribution.medium_files += 1;
-            } else if size_mb < 1024.0 {
-                metrics.file_size_distribution.large_files += 1;
-            } else {
-                metrics.file_size_distribution.very_large_files += 1;
-            }
+            metrics
+                .file_size_distribution
+                .add(file.size as u64, buckets);
         }
+        metrics.file_size_histogram = crate::types::FileSizeHistogram::from_sizes(
+            data_files.iter().map(|f| f.size as u64).collect(),
+            buckets,
+        );
     }
 
     fn generate_recommendations(&self, metrics: &mut HealthMetrics) {
-        // Check for unreferenced files
-        if !metrics.unreferenced_files.is_empty() {
+        // Check for unreferenced files, ignoring recent ones that may still be committed
+        let stale_unreferenced =
+            metrics.unreferenced_files.len() - metrics.in_flight_unreferenced_files;
+        if stale_unreferenced > 0 {
+            let stale_bytes =
+                metrics.unreferenced_size_bytes - metrics.in_flight_unreferenced_size_bytes;
+            let cost = metrics.cost.as_ref().map_or(String::new(), |cost| {
+                format!(", about ${:.2}/month", cost.orphan_cost(stale_bytes))
+            });
             metrics.recommendations.push(format!(
-                "Found {} unreferenced files ({} bytes). Consider cleaning up orphaned data files.",
-                metrics.unreferenced_files.len(),
-                metrics.unreferenced_size_bytes
+                "Found {} unreferenced files ({} bytes{}). Consider cleaning up orphaned data files.",
+                stale_unreferenced,
+                stale_bytes,
+                cost
             ));
         }
 
@@ -486,6 +1931,9 @@ impl DeltaLakeAnalyzer {
         }
 
         // Check data skew
+        if let Some(recommendation) = metrics.data_skew.row_skew_recommendation() {
+            metrics.recommendations.push(recommendation);
+        }
         if metrics.data_skew.partition_skew_score > 0.5 {
             metrics.recommendations.push(
                 "High partition skew detected. Consider repartitioning to balance data distribution.".to_string()
@@ -500,8 +1948,8 @@ impl DeltaLakeAnalyzer {
         }
 
         // Check metadata health
-        if metrics.metadata_health.metadata_total_size_bytes > 50 * 1024 * 1024 {
-            // > 50MB
+        if metrics.metadata_health.metadata_total_size_bytes > metrics.metadata_bloat_bytes() / 2 {
+            // Half the bloat limit of the table's size class, 50MB by default
             metrics.recommendations.push(
                 "Large metadata size detected. Consider running VACUUM to clean up old transaction logs.".to_string()
             );
@@ -515,6 +1963,205 @@ impl DeltaLakeAnalyzer {
             );
         }
 
+        // Check partition directory layout against the partition columns
+        if let Some(ref mismatch) = metrics.partition_spec_mismatch {
+            metrics.recommendations.push(format!(
+                "Found {} data files whose partition directories don't match the partition columns [{}]. These usually come from manual copies or misconfigured writers and can break path-based readers; rewrite or remove them.",
+                mismatch.mismatched_file_count,
+                mismatch.expected_partition_columns.join(", ")
+            ));
+        }
+
+        // Check for drift between the catalog and the transaction log's schema
+        if let Some(ref drift) = metrics.schema_drift {
+            metrics.recommendations.extend(drift.recommendations());
+        }
+
+        // Check protocol versions and table features
+        if let Some(ref features) = metrics.table_features {
+            if !features.compatibility_blockers.is_empty() {
+                metrics.recommendations.push(format!(
+                    "Table protocol (reader v{}, writer v{}) requires reader features [{}]. Engines without Delta table-features support (older Spark, Trino, Athena, Redshift Spectrum) cannot read this table; verify every consumer supports them.",
+                    features.min_reader_version,
+                    features.min_writer_version,
+                    features.compatibility_blockers.join(", ")
+                ));
+            }
+
+            if !features.features_requiring_upgrade.is_empty() {
+                metrics.recommendations.push(format!(
+                    "Table properties request [{}] but the protocol (reader v{}, writer v{}) doesn't support them, so they have no effect. Upgrade the protocol deliberately: upgrades cannot be reverted and drop support for older readers.",
+                    features.features_requiring_upgrade.join(", "),
+                    features.min_reader_version,
+                    features.min_writer_version
+                ));
+            }
+        }
+
+        // Check Change Data Feed retention
+        if let Some(ref cdf) = metrics.change_data_feed {
+            if cdf.is_retention_unbounded {
+                metrics.recommendations.push(format!(
+                    "{} Change Data Feed files ({:.2} MB) are older than the {:.0}-day retention window, so nothing is cleaning them up. Run VACUUM regularly, or disable CDF if no consumer reads the change feed.",
+                    cdf.files_beyond_retention,
+                    cdf.bytes_beyond_retention as f64 / (1024.0 * 1024.0),
+                    cdf.retention_days
+                ));
+            }
+        }
+
+        // Check for data left behind by dropped columns
+        if let Some(ref column_mapping) = metrics.column_mapping {
+            if column_mapping.files_with_dropped_columns > 0 {
+                metrics.recommendations.push(format!(
+                    "Dropped columns [{}] still occupy an estimated {:.2} MB across {} data files. Run REORG TABLE ... APPLY (PURGE) to rewrite those files and reclaim the space.",
+                    column_mapping.dropped_columns.join(", "),
+                    column_mapping.reclaimable_bytes as f64 / (1024.0 * 1024.0),
+                    column_mapping.files_with_dropped_columns
+                ));
+            }
+        }
+
+        // Check the partition layout against the data volume
+        if let Some(recommendation) = metrics
+            .partitioning_advice
+            .as_ref()
+            .and_then(|advice| advice.recommendation())
+        {
+            metrics.recommendations.push(recommendation);
+        }
+
+        // Check freshness against the SLA
+        if let Some(recommendation) = metrics
+            .freshness
+            .as_ref()
+            .and_then(|freshness| freshness.recommendation())
+        {
+            metrics.recommendations.push(recommendation);
+        }
+
+        // Check live files against their add actions
+        if let Some(recommendation) = metrics
+            .log_consistency
+            .as_ref()
+            .and_then(|log_consistency| log_consistency.recommendation())
+        {
+            metrics.recommendations.push(recommendation);
+        }
+
+        // Check for abandoned multipart uploads
+        if let Some(ref incomplete_uploads) = metrics.incomplete_uploads {
+            metrics
+                .recommendations
+                .extend(incomplete_uploads.recommendations());
+        }
+
+        // Check that referenced files exist
+        if let Some(ref existence_check) = metrics.existence_check {
+            metrics
+                .recommendations
+                .extend(existence_check.recommendations("delta"));
+        }
+
+        // Check what time-travel history costs
+        if let Some(recommendation) = metrics.cost.as_ref().and_then(|cost| cost.recommendation()) {
+            metrics.recommendations.push(recommendation);
+        }
+
+        // Check storage classes for archived live data and tiering candidates
+        if let Some(ref storage_classes) = metrics.storage_classes {
+            metrics
+                .recommendations
+                .extend(storage_classes.recommendations());
+        }
+
+        // Check for data files in more than one format
+        if let Some(ref file_formats) = metrics.file_formats {
+            metrics
+                .recommendations
+                .extend(file_formats.recommendations("delta"));
+        }
+
+        // Check for leftover, empty and misnamed files in data paths
+        if let Some(ref file_naming) = metrics.file_naming {
+            metrics
+                .recommendations
+                .extend(file_naming.recommendations());
+        }
+
+        // Check for data files committed by interrupted writes
+        if let Some(ref file_integrity) = metrics.file_integrity {
+            metrics
+                .recommendations
+                .extend(file_integrity.recommendations("delta"));
+        }
+
+        // Check for data files stored more than once
+        if let Some(ref duplicate_files) = metrics.duplicate_files {
+            metrics
+                .recommendations
+                .extend(duplicate_files.recommendations("delta"));
+        }
+
+        // Check checkpoint cadence and log retention
+        if let Some(ref delta_log) = metrics.delta_log {
+            metrics.recommendations.extend(delta_log.recommendations());
+        }
+
+        // Check that every version in the log can still be read
+        if let Some(ref time_travel_integrity) = metrics.time_travel_integrity {
+            metrics
+                .recommendations
+                .extend(time_travel_integrity.recommendations());
+        }
+
+        // Check table properties against best practices
+        if let Some(ref table_properties) = metrics.table_properties {
+            metrics
+                .recommendations
+                .extend(table_properties.recommendations());
+        }
+
+        // Check what blocks Iceberg readers through UniForm
+        if let Some(ref migration) = metrics.iceberg_migration {
+            metrics.recommendations.extend(migration.recommendations());
+        }
+
+        // Check file counts against the engine's per-query limit
+        if let Some(ref limits) = metrics.engine_limits {
+            let recommendations =
+                crate::engine_limits::engine_limit_recommendations(limits, "delta");
+            metrics.recommendations.extend(recommendations);
+        }
+
+        // Check access patterns for cold and hot data
+        if let Some(ref access_patterns) = metrics.access_patterns {
+            metrics
+                .recommendations
+                .extend(access_patterns.recommendations(metrics.total_size_bytes));
+        }
+
+        // Check date partitions for gaps, future dates and null partitions
+        if let Some(ref anomalies) = metrics.partition_anomalies {
+            let recommendations = anomalies.recommendations();
+            metrics.recommendations.extend(recommendations);
+        }
+
+        // Check Parquet footers from a deep scan
+        if let Some(ref footers) = metrics.parquet_footers {
+            let clustering_columns = metrics
+                .clustering
+                .as_ref()
+                .map(|c| c.clustering_columns.as_slice())
+                .unwrap_or(&[]);
+            let recommendations = crate::parquet_footer::footer_recommendations(
+                footers,
+                metrics.total_size_bytes,
+                clustering_columns,
+            );
+            metrics.recommendations.extend(recommendations);
+        }
+
         // Check clustering
         if let Some(ref clustering) = metrics.clustering {
             if clustering.avg_files_per_cluster > 50.0 {
@@ -698,7 +2345,7 @@ impl DeltaLakeAnalyzer {
                                 ) {
                                     let is_breaking =
                                         self.is_breaking_change(&schema_changes, &schema);
-                                    schema_changes.push(SchemaChange {
+                                    schema_changes.push(SchemaSnapshot {
                                         version: current_version,
                                         timestamp: json
                                             .get("timestamp")
@@ -716,7 +2363,7 @@ impl DeltaLakeAnalyzer {
                             if let Some(reader_version) = protocol.get("minReaderVersion") {
                                 let new_version = reader_version.as_u64().unwrap_or(0);
                                 if new_version > current_version {
-                                    schema_changes.push(SchemaChange {
+                                    schema_changes.push(SchemaSnapshot {
                                         version: current_version,
                                         timestamp: json
                                             .get("timestamp")
@@ -740,7 +2387,7 @@ impl DeltaLakeAnalyzer {
                                     ) {
                                         let is_breaking =
                                             self.is_breaking_change(&schema_changes, &schema);
-                                        schema_changes.push(SchemaChange {
+                                        schema_changes.push(SchemaSnapshot {
                                             version: current_version,
                                             timestamp: json
                                                 .get("timestamp")
@@ -767,7 +2414,7 @@ impl DeltaLakeAnalyzer {
         self.calculate_schema_metrics(schema_changes, current_version)
     }
 
-    fn is_breaking_change(&self, previous_changes: &[SchemaChange], new_schema: &Value) -> bool {
+    fn is_breaking_change(&self, previous_changes: &[SchemaSnapshot], new_schema: &Value) -> bool {
         if previous_changes.is_empty() {
             return false;
         }
@@ -852,7 +2499,7 @@ impl DeltaLakeAnalyzer {
 
     fn calculate_schema_metrics(
         &self,
-        changes: Vec<SchemaChange>,
+        changes: Vec<SchemaSnapshot>,
         current_version: u64,
     ) -> Result<Option<crate::types::SchemaEvolutionMetrics>> {
         let total_changes = changes.len();
@@ -860,9 +2507,8 @@ impl DeltaLakeAnalyzer {
         let non_breaking_changes = total_changes - breaking_changes;
 
         // Calculate time-based metrics
-        let now = chrono::Utc::now().timestamp() as u64;
         let days_since_last = if let Some(last_change) = changes.last() {
-            (now - last_change.timestamp / 1000) as f64 / 86400.0
+            crate::types::days_since_commit(self.analysis_time, last_change.timestamp)
         } else {
             365.0 // No changes in a year = very stable
         };
@@ -871,7 +2517,7 @@ impl DeltaLakeAnalyzer {
         let total_days = if changes.len() > 1 {
             let first_change = changes.first().unwrap().timestamp / 1000;
             let last_change = changes.last().unwrap().timestamp / 1000;
-            ((last_change - first_change) as f64 / 86400.0).max(1.0_f64)
+            (last_change.saturating_sub(first_change) as f64 / 86400.0).max(1.0_f64)
         } else {
             1.0
         };
@@ -894,6 +2540,7 @@ impl DeltaLakeAnalyzer {
             days_since_last_change: days_since_last,
             schema_change_frequency: change_frequency,
             current_schema_version: current_version,
+            schema_changes: Vec::new(),
         }))
     }
 
@@ -991,7 +2638,7 @@ impl DeltaLakeAnalyzer {
                                         if let Some(timestamp) = remove_action.get("timestamp") {
                                             let creation_time =
                                                 timestamp.as_u64().unwrap_or(0) as i64;
-                                            let age_days = (chrono::Utc::now().timestamp()
+                                            let age_days = (self.analysis_time.timestamp()
                                                 - creation_time / 1000)
                                                 as f64
                                                 / 86400.0;
@@ -1025,7 +2672,7 @@ impl DeltaLakeAnalyzer {
                                             {
                                                 let creation_time =
                                                     timestamp.as_u64().unwrap_or(0) as i64;
-                                                let age_days = (chrono::Utc::now().timestamp()
+                                                let age_days = (self.analysis_time.timestamp()
                                                     - creation_time / 1000)
                                                     as f64
                                                     / 86400.0;
@@ -1098,7 +2745,7 @@ impl DeltaLakeAnalyzer {
     ) -> Result<Option<crate::types::TimeTravelMetrics>> {
         let mut total_snapshots = 0;
         let mut total_historical_size = 0u64;
-        let mut oldest_timestamp = chrono::Utc::now().timestamp() as u64;
+        let mut oldest_timestamp = self.analysis_time.timestamp_millis() as u64;
         let mut newest_timestamp = 0u64;
 
         // Analyze all metadata files to understand time travel storage
@@ -1152,9 +2799,8 @@ impl DeltaLakeAnalyzer {
             return Ok(None);
         }
 
-        let now = chrono::Utc::now().timestamp() as u64;
-        let oldest_age_days = (now - oldest_timestamp / 1000) as f64 / 86400.0;
-        let newest_age_days = (now - newest_timestamp / 1000) as f64 / 86400.0;
+        let oldest_age_days = crate::types::days_since_commit(self.analysis_time, oldest_timestamp);
+        let newest_age_days = crate::types::days_since_commit(self.analysis_time, newest_timestamp);
         let avg_snapshot_size = total_historical_size as f64 / total_snapshots as f64;
 
         let storage_cost_impact = self.calculate_storage_cost_impact(
@@ -1176,6 +2822,12 @@ impl DeltaLakeAnalyzer {
             storage_cost_impact_score: storage_cost_impact,
             retention_efficiency_score: retention_efficiency,
             recommended_retention_days: recommended_retention,
+            oldest_snapshot_timestamp: chrono::DateTime::from_timestamp_millis(
+                oldest_timestamp as i64,
+            ),
+            newest_snapshot_timestamp: chrono::DateTime::from_timestamp_millis(
+                newest_timestamp as i64,
+            ),
         }))
     }
 
@@ -1475,6 +3127,8 @@ impl DeltaLakeAnalyzer {
         &self,
         data_files: &[&crate::s3_client::ObjectInfo],
         metadata_files: &[&crate::s3_client::ObjectInfo],
+        buckets: &crate::config::FileSizeBuckets,
+        z_order_candidates: Vec<crate::types::ZOrderCandidate>,
     ) -> Result<Option<crate::types::FileCompactionMetrics>> {
         let mut small_files_count = 0;
         let mut small_files_size = 0u64;
@@ -1484,8 +3138,7 @@ impl DeltaLakeAnalyzer {
         // Analyze file sizes for compaction opportunities
         for file in data_files {
             let file_size = file.size as u64;
-            if file_size < 16 * 1024 * 1024 {
-                // < 16MB
+            if file_size < buckets.small_bytes {
                 small_files_count += 1;
                 small_files_size += file_size;
                 potential_compaction_files += 1;
@@ -1494,7 +3147,8 @@ impl DeltaLakeAnalyzer {
 
         // Calculate potential savings
         if small_files_count > 1 {
-            let target_size = 128 * 1024 * 1024; // 128MB target
+            // Compacted files land in the medium bucket (128MB by default)
+            let target_size = buckets.medium_bytes;
             let files_per_target = (target_size as f64
                 / (small_files_size as f64 / small_files_count as f64))
                 .ceil() as usize;
@@ -1503,6 +3157,17 @@ impl DeltaLakeAnalyzer {
             estimated_savings = small_files_size.saturating_sub(estimated_target_size);
         }
 
+        let table_files: Vec<(&str, u64)> = data_files
+            .iter()
+            .map(|file| {
+                (
+                    crate::s3_client::relative_key(self.s3_client.get_prefix(), &file.key),
+                    file.size as u64,
+                )
+            })
+            .collect();
+        let compaction_plan = crate::types::CompactionTask::plan(&table_files, buckets);
+
         let compaction_opportunity = self.calculate_compaction_opportunity(
             small_files_count,
             small_files_size,
@@ -1511,8 +3176,17 @@ impl DeltaLakeAnalyzer {
         let recommended_target_size = self.calculate_recommended_target_size(data_files);
         let compaction_priority =
             self.calculate_compaction_priority(compaction_opportunity, small_files_count);
-        let (z_order_opportunity, z_order_columns) =
-            self.analyze_z_order_opportunity(metadata_files).await?;
+        // Columns the file statistics point to, else those the table is clustered by
+        let (z_order_opportunity, z_order_columns) = if z_order_candidates.is_empty() {
+            self.analyze_z_order_opportunity(metadata_files).await?
+        } else {
+            let columns = z_order_candidates
+                .iter()
+                .take(MAX_Z_ORDER_COLUMNS)
+                .map(|candidate| candidate.column.clone())
+                .collect();
+            (true, columns)
+        };
 
         Ok(Some(crate::types::FileCompactionMetrics {
             compaction_opportunity_score: compaction_opportunity,
@@ -1524,6 +3198,8 @@ impl DeltaLakeAnalyzer {
             compaction_priority,
             z_order_opportunity,
             z_order_columns,
+            z_order_candidates,
+            compaction_plan,
         }))
     }
 
@@ -1624,3 +3300,473 @@ impl DeltaLakeAnalyzer {
         Ok((false, Vec::new()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::s3_client::ObjectInfo;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_log_actions_ndjson() {
+        let content = b"{\"commitInfo\":{}}\n\n{\"add\":{\"path\":\"a.parquet\"}}\n";
+        let actions = parse_log_actions(content);
+        assert_eq!(actions.len(), 2);
+        assert!(actions[1].get("add").is_some());
+    }
+
+    #[test]
+    fn test_parse_log_actions_single_document() {
+        let content = b"{\"add\": [\n  {\"path\": \"a.parquet\"}\n]}";
+        let actions = parse_log_actions(content);
+        assert_eq!(actions.len(), 1);
+        assert!(actions[0].get("add").unwrap().is_array());
+    }
+
+    #[test]
+    fn test_log_version() {
+        assert_eq!(log_version("t/_delta_log/00000000000000000012.json"), 12);
+        assert_eq!(log_version("t/_delta_log/garbage.json"), 0);
+    }
+
+    #[test]
+    fn test_build_table_features_legacy_protocol() {
+        let protocol = json!({"minReaderVersion": 1, "minWriterVersion": 4});
+        let config = json!({"delta.enableChangeDataFeed": "true"});
+        let features = build_table_features(&protocol, Some(&config));
+
+        assert!(!features.uses_table_features);
+        assert!(features.compatibility_blockers.is_empty());
+        assert!(features.features_requiring_upgrade.is_empty());
+        assert_eq!(
+            features.enabled_features,
+            vec![
+                "appendOnly",
+                "invariants",
+                "checkConstraints",
+                "changeDataFeed",
+                "generatedColumns"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_table_features_table_features_protocol() {
+        let protocol = json!({
+            "minReaderVersion": 3,
+            "minWriterVersion": 7,
+            "readerFeatures": ["deletionVectors", "timestampNtz"],
+            "writerFeatures": ["deletionVectors", "timestampNtz", "domainMetadata"]
+        });
+        let features = build_table_features(&protocol, None);
+
+        assert!(features.uses_table_features);
+        assert_eq!(
+            features.compatibility_blockers,
+            vec!["deletionVectors", "timestampNtz"]
+        );
+        assert_eq!(
+            features.enabled_features,
+            vec!["deletionVectors", "timestampNtz", "domainMetadata"]
+        );
+    }
+
+    #[test]
+    fn test_build_table_features_property_without_protocol_support() {
+        let protocol = json!({"minReaderVersion": 1, "minWriterVersion": 2});
+        let config = json!({"delta.enableDeletionVectors": "true"});
+        let features = build_table_features(&protocol, Some(&config));
+
+        assert_eq!(features.features_requiring_upgrade, vec!["deletionVectors"]);
+        assert!(!features
+            .enabled_features
+            .contains(&"deletionVectors".to_string()));
+    }
+
+    fn mapped_metadata(columns: &[(u64, &str)]) -> Value {
+        let fields: Vec<Value> = columns
+            .iter()
+            .map(|(id, name)| {
+                json!({
+                    "name": name,
+                    "type": "string",
+                    "nullable": true,
+                    "metadata": {
+                        "delta.columnMapping.id": id,
+                        "delta.columnMapping.physicalName": format!("col-{}", id)
+                    }
+                })
+            })
+            .collect();
+        json!({"metaData": {
+            "schemaString": json!({"type": "struct", "fields": fields}).to_string(),
+            "partitionColumns": [],
+            "configuration": {"delta.columnMapping.mode": "name", "delta.columnMapping.maxColumnId": "3"}
+        }})
+    }
+
+    #[test]
+    fn test_build_column_mapping_metrics_renames_and_drops() {
+        let commits = vec![
+            (
+                0,
+                vec![
+                    mapped_metadata(&[(1, "a"), (2, "b"), (3, "c")]),
+                    json!({"add": {"path": "part-0.parquet", "size": 300}}),
+                ],
+            ),
+            (
+                1,
+                vec![
+                    mapped_metadata(&[(1, "a"), (2, "b2")]),
+                    json!({"add": {"path": "part-1.parquet", "size": 200}}),
+                ],
+            ),
+        ];
+        let metrics = build_column_mapping_metrics(&commits).unwrap();
+
+        assert_eq!(metrics.mode, "name");
+        assert!(metrics.is_enabled);
+        assert_eq!(metrics.max_column_id, 3);
+        assert_eq!(metrics.renamed_columns, vec!["b -> b2"]);
+        assert_eq!(metrics.dropped_columns, vec!["c"]);
+        assert_eq!(metrics.files_with_dropped_columns, 1);
+        assert_eq!(metrics.reclaimable_bytes, 100);
+    }
+
+    #[test]
+    fn test_build_column_mapping_metrics_rewritten_files_are_not_stale() {
+        let commits = vec![
+            (
+                0,
+                vec![
+                    mapped_metadata(&[(1, "a"), (2, "b")]),
+                    json!({"add": {"path": "part-0.parquet", "size": 100}}),
+                ],
+            ),
+            (1, vec![mapped_metadata(&[(1, "a")])]),
+            (
+                2,
+                vec![
+                    json!({"remove": {"path": "part-0.parquet"}}),
+                    json!({"add": {"path": "part-1.parquet", "size": 50}}),
+                ],
+            ),
+        ];
+        let metrics = build_column_mapping_metrics(&commits).unwrap();
+
+        assert_eq!(metrics.dropped_columns, vec!["b"]);
+        assert_eq!(metrics.files_with_dropped_columns, 0);
+        assert_eq!(metrics.reclaimable_bytes, 0);
+    }
+
+    #[test]
+    fn test_build_column_mapping_metrics_disabled() {
+        let commits = vec![(
+            0,
+            vec![json!({"metaData": {"schemaString": "{\"type\":\"struct\",\"fields\":[]}"}})],
+        )];
+        let metrics = build_column_mapping_metrics(&commits).unwrap();
+
+        assert_eq!(metrics.mode, "none");
+        assert!(!metrics.is_enabled);
+        assert!(metrics.dropped_columns.is_empty());
+        assert!(build_column_mapping_metrics(&[]).is_none());
+    }
+
+    #[test]
+    fn test_file_lifecycles_tracks_adds_and_removes() {
+        let commits = vec![
+            (
+                0,
+                vec![
+                    json!({"add": {"path": "a.parquet", "size": 1, "modificationTime": 1672531200000i64}}),
+                ],
+            ),
+            (
+                3,
+                vec![
+                    json!({"add": [{"path": "b.parquet", "size": 2, "stats": "{\"numRecords\":42}"}, {"path": "c.parquet", "size": 3}]}),
+                    json!({"remove": {"path": "a.parquet"}}),
+                ],
+            ),
+            (
+                5,
+                vec![
+                    json!({"remove": {"path": "c.parquet"}}),
+                    json!({"add": {"path": "c.parquet", "size": 3}}),
+                ],
+            ),
+        ];
+        let lifecycles = file_lifecycles(&commits);
+
+        assert_eq!(lifecycles.len(), 3);
+        assert_eq!(lifecycles["a.parquet"].added_version, Some(0));
+        assert_eq!(lifecycles["a.parquet"].removed_version, Some(3));
+        assert_eq!(
+            lifecycles["a.parquet"].created.unwrap().to_rfc3339(),
+            "2023-01-01T00:00:00+00:00"
+        );
+        assert_eq!(lifecycles["b.parquet"].added_version, Some(3));
+        assert_eq!(lifecycles["b.parquet"].removed_version, None);
+        assert_eq!(lifecycles["b.parquet"].num_records, Some(42));
+        assert_eq!(lifecycles["c.parquet"].num_records, None);
+        assert_eq!(lifecycles["c.parquet"].added_version, Some(5));
+        assert_eq!(lifecycles["c.parquet"].removed_version, None);
+    }
+
+    #[test]
+    fn test_referenced_key() {
+        assert_eq!(
+            referenced_key(
+                "date=2024-01-01%2012%3A00/part-0.parquet",
+                "lake",
+                "events/"
+            ),
+            Some("events/date=2024-01-01 12:00/part-0.parquet".to_string())
+        );
+        assert_eq!(
+            referenced_key("s3://lake/shared/part-1.parquet", "lake", "events"),
+            Some("shared/part-1.parquet".to_string())
+        );
+        assert_eq!(
+            referenced_key("s3://other/part-2.parquet", "lake", "events"),
+            None
+        );
+        assert_eq!(
+            referenced_key("part-3.parquet", "lake", ""),
+            Some("part-3.parquet".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_log_consistency() {
+        let commits = vec![(
+            0,
+            vec![
+                json!({"add": {"path": "a1/part-0.parquet", "size": 100, "partitionValues": {"date": "2024-01-01"}}}),
+                json!({"add": {"path": "b2/part-1.parquet", "size": 200, "partitionValues": {"date": null}}}),
+            ],
+        )];
+        let lifecycles = file_lifecycles(&commits);
+        assert_eq!(
+            lifecycles["b2/part-1.parquet"].partition_values["date"],
+            crate::types::HIVE_DEFAULT_PARTITION
+        );
+
+        let object = |key: &str, size: i64| ObjectInfo {
+            key: key.to_string(),
+            size,
+            last_modified: None,
+            etag: None,
+            storage_class: None,
+        };
+        let files = [
+            object("t/a1/part-0.parquet", 100),
+            object("t/b2/part-1.parquet", 150),
+            object("t/orphan.parquet", 10),
+        ];
+        let data_files: Vec<&ObjectInfo> = files.iter().collect();
+        let metrics = check_log_consistency(&data_files, &lifecycles, "t");
+
+        assert!(metrics.partitions_from_log);
+        assert_eq!(metrics.checked_files, 2);
+        assert_eq!(metrics.size_mismatched_files, 1);
+        assert_eq!(metrics.size_mismatch_paths, vec!["t/b2/part-1.parquet"]);
+        assert_eq!(metrics.truncated_files, 1);
+        assert_eq!(metrics.size_mismatches[0].recorded_size_bytes, 200);
+        assert_eq!(metrics.size_mismatches[0].actual_size_bytes, 150);
+        assert!(metrics.recommendation().is_some());
+    }
+
+    #[test]
+    fn test_parse_interval_days() {
+        assert_eq!(parse_interval_days("interval 7 days"), Some(7.0));
+        assert_eq!(parse_interval_days("interval 2 weeks"), Some(14.0));
+        assert_eq!(parse_interval_days("48 hours"), Some(2.0));
+        assert_eq!(parse_interval_days("forever"), None);
+    }
+
+    #[test]
+    fn test_build_change_data_feed_metrics() {
+        let as_of: chrono::DateTime<chrono::Utc> = "2023-01-31T00:00:00Z".parse().unwrap();
+        let old = ObjectInfo {
+            key: "table/_change_data/cdc-00000.c000.snappy.parquet".to_string(),
+            size: 1024,
+            last_modified: Some("2023-01-01T00:00:00Z".parse().unwrap()),
+            etag: None,
+            storage_class: None,
+        };
+        let recent = ObjectInfo {
+            key: "table/_change_data/cdc-00001.c000.snappy.parquet".to_string(),
+            size: 2048,
+            last_modified: Some("2023-01-30T00:00:00Z".parse().unwrap()),
+            etag: None,
+            storage_class: None,
+        };
+        let config = json!({"delta.enableChangeDataFeed": "true"});
+        let metrics =
+            build_change_data_feed_metrics(&[&old, &recent], Some(&config), as_of).unwrap();
+
+        assert!(metrics.is_enabled);
+        assert_eq!(metrics.change_file_count, 2);
+        assert_eq!(metrics.change_size_bytes, 3072);
+        assert_eq!(metrics.oldest_change_file_age_days, 30.0);
+        assert_eq!(metrics.retention_days, 7.0);
+        assert_eq!(metrics.files_beyond_retention, 1);
+        assert_eq!(metrics.bytes_beyond_retention, 1024);
+        assert!(metrics.is_retention_unbounded);
+
+        assert!(build_change_data_feed_metrics(&[], None, as_of).is_none());
+    }
+
+    #[test]
+    fn test_build_delta_log_metrics() {
+        let as_of: chrono::DateTime<chrono::Utc> = "2024-03-01T00:00:00Z".parse().unwrap();
+        let start: chrono::DateTime<chrono::Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+        // 60 daily commits of 2 MB, checkpointed at versions 10 and 20 only
+        let mut objects: Vec<ObjectInfo> = (0..60)
+            .map(|version| ObjectInfo {
+                key: format!("t/_delta_log/{:020}.json", version),
+                size: 2 * 1024 * 1024,
+                last_modified: Some(start + chrono::Duration::days(version)),
+                etag: None,
+                storage_class: None,
+            })
+            .collect();
+        for version in [10, 20] {
+            objects.push(ObjectInfo {
+                key: format!("t/_delta_log/{:020}.checkpoint.parquet", version),
+                size: 4096,
+                last_modified: None,
+                etag: None,
+                storage_class: None,
+            });
+        }
+        objects.push(ObjectInfo {
+            key: "t/_delta_log/_last_checkpoint".to_string(),
+            size: 100,
+            last_modified: None,
+            etag: None,
+            storage_class: None,
+        });
+        let log_objects: Vec<&ObjectInfo> = objects.iter().collect();
+        let config = json!({"delta.logRetentionDuration": "interval 14 days"});
+        let metrics = build_delta_log_metrics(&log_objects, Some(&config), as_of).unwrap();
+
+        assert_eq!(metrics.latest_version, 59);
+        assert_eq!(metrics.checkpoint_count, 2);
+        assert_eq!(metrics.latest_checkpoint_version, Some(20));
+        assert_eq!(metrics.commits_since_checkpoint, 39);
+        assert_eq!(metrics.avg_commits_per_checkpoint, Some(10.0));
+        assert_eq!(metrics.json_log_files, 60);
+        assert_eq!(metrics.checkpoint_interval, 10);
+        // 10 MB of replay at 2 MB per commit
+        assert_eq!(metrics.recommended_checkpoint_interval, Some(5));
+        assert!(metrics.recommended_log_retention_days.is_none());
+        // Commits 0-19 are covered by the checkpoint; those older than 15 days stay behind
+        assert_eq!(metrics.expired_json_log_files, 20);
+        assert!(!metrics.is_pathological);
+        assert_eq!(metrics.recommendations().len(), 3);
+
+        assert!(build_delta_log_metrics(&[], None, as_of).is_none());
+    }
+
+    #[test]
+    fn test_check_time_travel_integrity() {
+        let commits = vec![
+            (
+                0,
+                vec![
+                    json!({"add": {"path": "a.parquet", "size": 100}}),
+                    json!({"add": {"path": "b.parquet", "size": 200}}),
+                ],
+            ),
+            (2, vec![json!({"add": {"path": "c.parquet", "size": 300}})]),
+            (
+                4,
+                vec![
+                    json!({"remove": {"path": "a.parquet"}}),
+                    json!({"add": {"path": "d.parquet", "size": 400}}),
+                ],
+            ),
+            (6, vec![json!({"remove": {"path": "c.parquet"}})]),
+            (8, vec![json!({"remove": {"path": "b.parquet"}})]),
+        ];
+        let lifecycles = file_lifecycles(&commits);
+        let filter = crate::config::PartitionFilter::default();
+        let config = json!({"delta.deletedFileRetentionDuration": "interval 1 days"});
+
+        // a and c were vacuumed, b is tombstoned but still in storage
+        let listed = HashSet::from(["t/b.parquet", "t/d.parquet"]);
+        let metrics = check_time_travel_integrity(
+            &commits,
+            &lifecycles,
+            &listed,
+            "lake",
+            "t/",
+            &filter,
+            Some(&config),
+        )
+        .unwrap();
+        assert_eq!(metrics.vacuumed_file_count, 2);
+        assert_eq!(metrics.vacuumed_size_bytes, 400);
+        assert_eq!(metrics.vacuumed_paths, ["a.parquet", "c.parquet"]);
+        // Versions 0-5: a breaks 0-3, c breaks 2-5
+        assert_eq!(metrics.broken_version_count, 6);
+        assert_eq!(metrics.earliest_intact_version, 6);
+        assert_eq!(metrics.deleted_file_retention_days, 1.0);
+        assert_eq!(metrics.log_retention_days, 30.0);
+        assert!(metrics.recommendations()[0].starts_with("Time travel is broken before version 6"));
+
+        let listed = HashSet::from(["t/a.parquet", "t/b.parquet", "t/c.parquet", "t/d.parquet"]);
+        let intact = check_time_travel_integrity(
+            &commits,
+            &lifecycles,
+            &listed,
+            "lake",
+            "t/",
+            &filter,
+            None,
+        )
+        .unwrap();
+        assert_eq!(intact.broken_version_count, 0);
+        assert_eq!(intact.earliest_intact_version, 0);
+        assert!(intact.recommendations().is_empty());
+    }
+
+    #[test]
+    fn test_schema_change_log() {
+        let schema = |fields: Value| json!({"metaData": {"schemaString": json!({"type": "struct", "fields": fields}).to_string()}});
+        let commits = vec![
+            (
+                0,
+                vec![schema(
+                    json!([{"name": "id", "type": "long", "nullable": false}]),
+                )],
+            ),
+            (1, vec![json!({"add": {"path": "a.parquet", "size": 1}})]),
+            (
+                2,
+                vec![
+                    json!({"commitInfo": {"timestamp": 1672531200000i64, "operation": "ADD COLUMNS"}}),
+                    schema(json!([
+                        {"name": "id", "type": "long", "nullable": false},
+                        {"name": "country", "type": "string", "nullable": true}
+                    ])),
+                ],
+            ),
+        ];
+        let changes = schema_change_log(&commits);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].version, 2);
+        assert_eq!(changes[0].change_type, "added");
+        assert_eq!(changes[0].column, "country");
+        assert!(!changes[0].is_breaking);
+        assert_eq!(
+            changes[0].timestamp.unwrap().to_rfc3339(),
+            "2023-01-01T00:00:00+00:00"
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Duplicate tables across a namespace crawl: tables that look like copies of one another,
+//! left behind by backfills, migrations and "temporary" clones.
+//!
+//! Two tables are copies when their schemas fingerprint the same (column names and types in
+//! order, plus partition columns) and their live files nearly match by name and size, as
+//! they do after `aws s3 sync`, `DEEP CLONE` or a copied warehouse prefix. Of each group the
+//! largest table is kept as the original; the others are reported as redundant with their
+//! storage cost. Reports with hashed paths (`hash_paths`) or capped partitions
+//! (`max_partitions`) compare fewer files and may miss copies.
+
+use crate::types::{DuplicateTableGroup, HealthReport, TableAnalysis};
+use std::collections::HashMap;
+
+/// Share of the larger inventory two tables must have in common to count as copies
+pub const MIN_INVENTORY_SIMILARITY: f64 = 0.9;
+
+/// Live files by (file name, size)
+fn inventory(report: &HealthReport) -> HashMap<(&str, u64), usize> {
+    let mut files = HashMap::new();
+    for file in report
+        .metrics
+        .partitions
+        .iter()
+        .flat_map(|partition| &partition.files)
+        .filter(|file| file.removed_commit_id.is_none())
+    {
+        let name = file.path.rsplit('/').next().unwrap_or(&file.path);
+        *files.entry((name, file.size_bytes)).or_default() += 1;
+    }
+    files
+}
+
+/// Files two inventories share, as a share of the larger one
+fn similarity(a: &HashMap<(&str, u64), usize>, b: &HashMap<(&str, u64), usize>) -> f64 {
+    let total = |files: &HashMap<(&str, u64), usize>| files.values().sum::<usize>();
+    let larger = total(a).max(total(b));
+    if larger == 0 {
+        return 0.0;
+    }
+    let shared: usize = a
+        .iter()
+        .map(|(file, count)| (*count).min(b.get(file).copied().unwrap_or(0)))
+        .sum();
+    shared as f64 / larger as f64
+}
+
+/// Groups of analyzed tables that look like copies of one another
+pub fn find_duplicates(tables: &[TableAnalysis]) -> Vec<DuplicateTableGroup> {
+    let mut by_schema: HashMap<String, Vec<(&str, &HealthReport)>> = HashMap::new();
+    for table in tables {
+        let Some(ref report) = table.report else {
+            continue;
+        };
+        let Some(ref schema) = report.current_schema else {
+            continue;
+        };
+        if schema.columns.is_empty() {
+            continue;
+        }
+        by_schema
+            .entry(schema.fingerprint())
+            .or_default()
+            .push((table.table.as_str(), report));
+    }
+
+    let mut groups = Vec::new();
+    for (fingerprint, mut candidates) in by_schema {
+        if candidates.len() < 2 {
+            continue;
+        }
+        // Largest first, so the table most likely to be the original leads its group
+        candidates.sort_by(|a, b| {
+            b.1.metrics
+                .total_size_bytes
+                .cmp(&a.1.metrics.total_size_bytes)
+                .then(a.0.cmp(b.0))
+        });
+        let inventories: Vec<_> = candidates.iter().map(|(_, r)| inventory(r)).collect();
+        let mut assigned = vec![false; candidates.len()];
+        for original in 0..candidates.len() {
+            if assigned[original] || inventories[original].is_empty() {
+                continue;
+            }
+            let copies: Vec<(usize, f64)> = (original + 1..candidates.len())
+                .filter(|copy| !assigned[*copy])
+                .map(|copy| (copy, similarity(&inventories[original], &inventories[copy])))
+                .filter(|(_, similarity)| *similarity >= MIN_INVENTORY_SIMILARITY)
+                .collect();
+            if copies.is_empty() {
+                continue;
+            }
+            for (copy, _) in &copies {
+                assigned[*copy] = true;
+            }
+            let reports: Vec<&HealthReport> =
+                copies.iter().map(|(copy, _)| candidates[*copy].1).collect();
+            groups.push(DuplicateTableGroup {
+                schema_fingerprint: fingerprint.clone(),
+                original_table: candidates[original].0.to_string(),
+                duplicate_tables: copies
+                    .iter()
+                    .map(|(copy, _)| candidates[*copy].0.to_string())
+                    .collect(),
+                min_similarity: copies
+                    .iter()
+                    .map(|(_, similarity)| *similarity)
+                    .fold(1.0, f64::min),
+                redundant_size_bytes: reports.iter().map(|r| r.metrics.total_size_bytes).sum(),
+                redundant_monthly_cost: reports
+                    .iter()
+                    .filter_map(|r| r.metrics.cost.as_ref())
+                    .map(|cost| cost.total_monthly_cost)
+                    .sum(),
+            });
+        }
+    }
+    groups.sort_by(|a, b| {
+        b.redundant_size_bytes
+            .cmp(&a.redundant_size_bytes)
+            .then_with(|| a.original_table.cmp(&b.original_table))
+    });
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ColumnInfo, FileInfo, PartitionInfo, TableSchema};
+
+    fn table(name: &str, columns: &[&str], files: &[(&str, u64)]) -> TableAnalysis {
+        let mut report = HealthReport::new(format!("s3://lake/{}", name), "iceberg".to_string());
+        report.current_schema = Some(TableSchema {
+            schema_id: Some(0),
+            columns: columns
+                .iter()
+                .map(|column| ColumnInfo {
+                    name: column.to_string(),
+                    data_type: "long".to_string(),
+                    nullable: true,
+                    field_id: None,
+                })
+                .collect(),
+            partition_columns: Vec::new(),
+        });
+        report.metrics.total_size_bytes = files.iter().map(|(_, size)| size).sum();
+        report.metrics.partitions = vec![PartitionInfo {
+            partition_values: HashMap::new(),
+            file_count: files.len(),
+            total_size_bytes: report.metrics.total_size_bytes,
+            avg_file_size_bytes: 0.0,
+            files: files
+                .iter()
+                .map(|(file, size)| {
+                    let path = format!("{}/data/{}", name, file);
+                    FileInfo::new(path, *size, None, true, chrono::Utc::now())
+                })
+                .collect(),
+        }];
+        TableAnalysis {
+            table: name.to_string(),
+            report: Some(report),
+            error: None,
+            error_code: None,
+        }
+    }
+
+    #[test]
+    fn test_find_duplicates_groups_copies() {
+        let files: Vec<(String, u64)> = (0..10)
+            .map(|i| (format!("part-{}.parquet", i), 1000 + i))
+            .collect();
+        let files: Vec<(&str, u64)> = files.iter().map(|(f, s)| (f.as_str(), *s)).collect();
+        let tables = [
+            table("analytics.events", &["id", "ts"], &files),
+            // A copy missing its newest file still matches 9 of 10
+            table("scratch.events_backup", &["id", "ts"], &files[..9]),
+            // Same schema, different data
+            table(
+                "analytics.events_eu",
+                &["id", "ts"],
+                &[("part-0.parquet", 7)],
+            ),
+            // Same files, different schema
+            table("analytics.clicks", &["id", "url"], &files),
+        ];
+
+        let groups = find_duplicates(&tables);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].original_table, "analytics.events");
+        assert_eq!(groups[0].duplicate_tables, ["scratch.events_backup"]);
+        assert!((groups[0].min_similarity - 0.9).abs() < 1e-9);
+        assert_eq!(
+            groups[0].redundant_size_bytes,
+            (0..9).map(|i| 1000 + i).sum::<u64>()
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! File counts checked against what the configured engine can plan in one query, turning
+//! "too many small files" into "this breaks on engine X". Limits are the rules of thumb in
+//! `EngineProfile::named`, overridable with `max_files_per_query`.
+
+use crate::config::EngineProfile;
+use crate::types::{EngineLimitMetrics, HealthMetrics, PartitionInfo};
+
+const MAX_LISTED_PARTITIONS: usize = 100;
+/// Share of the limit at which a table is reported as approaching it
+const APPROACHING_LIMIT: f64 = 0.8;
+
+/// Compare the table's and each partition's file count with the engine's limit. None when
+/// the engine has no limit or the table has no data files.
+pub fn check_engine_limits(
+    metrics: &HealthMetrics,
+    engine: &EngineProfile,
+) -> Option<EngineLimitMetrics> {
+    let limit = engine.max_files_per_query?;
+    if metrics.total_files == 0 {
+        return None;
+    }
+
+    let partitioned: Vec<&PartitionInfo> = metrics
+        .partitions
+        .iter()
+        .filter(|partition| !partition.partition_values.is_empty())
+        .collect();
+    let mut over_limit: Vec<&PartitionInfo> = partitioned
+        .iter()
+        .copied()
+        .filter(|partition| partition.file_count > limit)
+        .collect();
+    over_limit.sort_by(|a, b| {
+        b.file_count
+            .cmp(&a.file_count)
+            .then_with(|| a.path().cmp(&b.path()))
+    });
+
+    Some(EngineLimitMetrics {
+        engine: engine.name.clone(),
+        max_files_per_query: limit,
+        table_files: metrics.total_files,
+        table_exceeds_limit: metrics.total_files > limit,
+        avg_files_per_partition: if partitioned.is_empty() {
+            metrics.total_files as f64
+        } else {
+            metrics.total_files as f64 / partitioned.len() as f64
+        },
+        largest_partition_files: partitioned
+            .iter()
+            .map(|partition| partition.file_count)
+            .max()
+            .unwrap_or(metrics.total_files),
+        partitions_over_limit: over_limit.len(),
+        partitions_over_limit_paths: over_limit
+            .iter()
+            .take(MAX_LISTED_PARTITIONS)
+            .map(|partition| partition.path())
+            .collect(),
+    })
+}
+
+/// Findings for file counts at or near the engine's limit. `table_type` ("delta" or
+/// "iceberg") picks the compaction command.
+pub fn engine_limit_recommendations(limits: &EngineLimitMetrics, table_type: &str) -> Vec<String> {
+    let compact = if table_type == "delta" {
+        "OPTIMIZE"
+    } else {
+        "rewrite_data_files"
+    };
+    let mut recommendations = Vec::new();
+    if limits.partitions_over_limit > 0 {
+        recommendations.push(format!(
+            "{} partitions hold more than the ~{} files one {} query can plan (largest: {} files, e.g. {}). Even queries pruned to one of these partitions are likely to fail; compact them with {} or write fewer, larger files.",
+            limits.partitions_over_limit,
+            limits.max_files_per_query,
+            limits.engine,
+            limits.largest_partition_files,
+            limits.partitions_over_limit_paths.join(", "),
+            compact
+        ));
+    }
+    if limits.table_exceeds_limit {
+        recommendations.push(format!(
+            "The table has {} data files, more than the ~{} one {} query can plan ({:.0} files per partition on average). Full scans are likely to fail or stall in planning; compact with {} or make sure queries filter on partition columns.",
+            limits.table_files,
+            limits.max_files_per_query,
+            limits.engine,
+            limits.avg_files_per_partition,
+            compact
+        ));
+    } else if limits.table_files as f64 >= limits.max_files_per_query as f64 * APPROACHING_LIMIT {
+        recommendations.push(format!(
+            "The table has {} data files, approaching the ~{} one {} query can plan. Compact with {} before full scans start failing.",
+            limits.table_files, limits.max_files_per_query, limits.engine, compact
+        ));
+    }
+    recommendations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn partition(day: &str, file_count: usize) -> PartitionInfo {
+        PartitionInfo {
+            partition_values: HashMap::from([("day".to_string(), day.to_string())]),
+            file_count,
+            total_size_bytes: 0,
+            avg_file_size_bytes: 0.0,
+            files: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_check_engine_limits_flags_partitions_and_table() {
+        let mut metrics = HealthMetrics::new();
+        metrics.total_files = 180_000;
+        metrics.partitions = vec![
+            partition("2024-01-01", 20_000),
+            partition("2024-01-02", 150_000),
+            partition("2024-01-03", 10_000),
+        ];
+
+        let athena = EngineProfile::named("athena").unwrap();
+        let limits = check_engine_limits(&metrics, &athena).unwrap();
+        assert!(limits.table_exceeds_limit);
+        assert_eq!(limits.partitions_over_limit, 1);
+        assert_eq!(limits.partitions_over_limit_paths, ["day=2024-01-02"]);
+        assert_eq!(limits.largest_partition_files, 150_000);
+        assert_eq!(limits.avg_files_per_partition, 60_000.0);
+        assert_eq!(engine_limit_recommendations(&limits, "iceberg").len(), 2);
+
+        // Within Spark's limit, and no limit at all once disabled
+        let mut spark = EngineProfile::default();
+        let limits = check_engine_limits(&metrics, &spark).unwrap();
+        assert!(!limits.table_exceeds_limit);
+        assert!(engine_limit_recommendations(&limits, "delta").is_empty());
+        spark.max_files_per_query = None;
+        assert!(check_engine_limits(&metrics, &spark).is_none());
+    }
+}
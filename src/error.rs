@@ -0,0 +1,310 @@
+//! Typed analysis errors: what failed, in which stage, on which object and whether trying
+//! again may help, raised in Python as `drainage.DrainageError` (a `RuntimeError`) with
+//! `code`, `phase`, `object_key` and `retriable` attributes.
+//!
+//! Storage and catalog requests classify their failures where they are made, from the HTTP
+//! status and service error code, and name the key they were reading. Analyzers add the
+//! stage that was running when they gave up; errors from elsewhere (parsing, invalid input)
+//! are classified by their type at that boundary.
+
+use aws_sdk_s3::error::{DisplayErrorContext, ProvideErrorMetadata, SdkError};
+use pyo3::prelude::*;
+use std::fmt;
+
+/// The Python exception type, a subclass of `RuntimeError`
+pub mod py {
+    pyo3::create_exception!(
+        drainage,
+        DrainageError,
+        pyo3::exceptions::PyRuntimeError,
+        "An analysis failure, with its `code`, `phase`, `object_key` and `retriable` flag"
+    );
+}
+
+/// Where an error happened, and its sanitized message
+#[derive(Debug, Clone, Default)]
+pub struct ErrorContext {
+    pub message: String,
+    pub phase: Option<String>, // analysis stage, e.g. "listing", or "client_setup"
+    pub object_key: Option<String>, // key or prefix of the failed request
+}
+
+impl ErrorContext {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: crate::sanitize::sanitize(&message.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_key(self, key: &str) -> Self {
+        Self {
+            object_key: Some(key.to_string()),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// A failed analysis, by cause
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum DrainageError {
+    /// Credentials missing, expired or not allowed to read the object
+    #[error("{0}")]
+    AccessDenied(ErrorContext),
+    /// The bucket, table or object does not exist
+    #[error("{0}")]
+    NotFound(ErrorContext),
+    /// Storage or the catalog asked to slow down
+    #[error("{0}")]
+    Throttled(ErrorContext),
+    /// A request timed out
+    #[error("{0}")]
+    Timeout(ErrorContext),
+    /// The connection failed
+    #[error("{0}")]
+    Network(ErrorContext),
+    /// Storage or the catalog failed on its side (5xx)
+    #[error("{0}")]
+    Unavailable(ErrorContext),
+    /// Table metadata that can't be parsed
+    #[error("{0}")]
+    InvalidMetadata(ErrorContext),
+    /// An invalid path, table type or option
+    #[error("{0}")]
+    InvalidInput(ErrorContext),
+    /// The catalog rejected a request
+    #[error("{0}")]
+    Catalog(ErrorContext),
+    /// Anything else
+    #[error("{0}")]
+    Internal(ErrorContext),
+}
+
+impl DrainageError {
+    /// Stable identifier of the cause, `DrainageError.code` in Python
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::AccessDenied(_) => "ACCESS_DENIED",
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::Throttled(_) => "THROTTLED",
+            Self::Timeout(_) => "TIMEOUT",
+            Self::Network(_) => "NETWORK",
+            Self::Unavailable(_) => "UNAVAILABLE",
+            Self::InvalidMetadata(_) => "INVALID_METADATA",
+            Self::InvalidInput(_) => "INVALID_INPUT",
+            Self::Catalog(_) => "CATALOG",
+            Self::Internal(_) => "INTERNAL",
+        }
+    }
+
+    /// Whether the same call may succeed when tried again later
+    pub fn retriable(&self) -> bool {
+        matches!(
+            self,
+            Self::Throttled(_) | Self::Timeout(_) | Self::Network(_) | Self::Unavailable(_)
+        )
+    }
+
+    pub fn context(&self) -> &ErrorContext {
+        match self {
+            Self::AccessDenied(context)
+            | Self::NotFound(context)
+            | Self::Throttled(context)
+            | Self::Timeout(context)
+            | Self::Network(context)
+            | Self::Unavailable(context)
+            | Self::InvalidMetadata(context)
+            | Self::InvalidInput(context)
+            | Self::Catalog(context)
+            | Self::Internal(context) => context,
+        }
+    }
+
+    fn context_mut(&mut self) -> &mut ErrorContext {
+        match self {
+            Self::AccessDenied(context)
+            | Self::NotFound(context)
+            | Self::Throttled(context)
+            | Self::Timeout(context)
+            | Self::Network(context)
+            | Self::Unavailable(context)
+            | Self::InvalidMetadata(context)
+            | Self::InvalidInput(context)
+            | Self::Catalog(context)
+            | Self::Internal(context) => context,
+        }
+    }
+
+    /// The error for an HTTP status, None for statuses without a more specific cause
+    pub fn from_status(status: u16, context: ErrorContext) -> Option<Self> {
+        Some(match status {
+            401 | 403 => Self::AccessDenied(context),
+            404 => Self::NotFound(context),
+            408 => Self::Timeout(context),
+            429 => Self::Throttled(context),
+            500..=599 => Self::Unavailable(context),
+            _ => return None,
+        })
+    }
+
+    /// A failed `operation` (e.g. "GetObject") on `key` of `bucket`
+    pub fn from_sdk<E>(operation: &str, bucket: &str, key: &str, error: SdkError<E>) -> Self
+    where
+        E: ProvideErrorMetadata + std::error::Error + 'static,
+    {
+        let context = ErrorContext::new(format!(
+            "{} s3://{}/{} failed: {}",
+            operation,
+            bucket,
+            key,
+            DisplayErrorContext(&error)
+        ))
+        .with_key(key);
+        let status = error.raw_response().map(|r| r.http().status().as_u16());
+        match (&error, error.code()) {
+            (SdkError::TimeoutError(_), _) => Self::Timeout(context),
+            (SdkError::DispatchFailure(failure), _) if failure.is_timeout() => {
+                Self::Timeout(context)
+            }
+            (SdkError::DispatchFailure(_), _) => Self::Network(context),
+            (_, Some("SlowDown" | "Throttling" | "RequestLimitExceeded")) => {
+                Self::Throttled(context)
+            }
+            (
+                _,
+                Some(
+                    "AccessDenied"
+                    | "ExpiredToken"
+                    | "InvalidAccessKeyId"
+                    | "InvalidToken"
+                    | "SignatureDoesNotMatch",
+                ),
+            ) => Self::AccessDenied(context),
+            (_, Some("NoSuchKey" | "NoSuchBucket" | "NoSuchUpload")) => Self::NotFound(context),
+            _ => match status {
+                Some(status) => {
+                    Self::from_status(status, context.clone()).unwrap_or(Self::Internal(context))
+                }
+                None => Self::Internal(context),
+            },
+        }
+    }
+
+    /// Classify `error`: the first `DrainageError` in its chain, with the outer context
+    /// added to its message, or else by the type of its root cause. `phase` is used when the
+    /// error doesn't name one already.
+    pub fn from_anyhow(error: anyhow::Error, phase: Option<&str>) -> Self {
+        let context = ErrorContext::new(format!("{:#}", error));
+        let mut classified = match error.chain().find_map(|e| e.downcast_ref::<Self>()) {
+            Some(inner) => {
+                let mut classified = inner.clone();
+                classified.context_mut().message = context.message;
+                classified
+            }
+            None => Self::classify_root(error.root_cause(), context),
+        };
+        let context = classified.context_mut();
+        if context.phase.is_none() {
+            context.phase = phase.map(str::to_string);
+        }
+        classified
+    }
+
+    fn classify_root(root: &(dyn std::error::Error + 'static), context: ErrorContext) -> Self {
+        if root.is::<serde_json::Error>()
+            || root.is::<parquet::errors::ParquetError>()
+            || root.is::<std::string::FromUtf8Error>()
+        {
+            Self::InvalidMetadata(context)
+        } else if root.is::<url::ParseError>() {
+            Self::InvalidInput(context)
+        } else if let Some(e) = root.downcast_ref::<reqwest::Error>() {
+            if e.is_timeout() {
+                Self::Timeout(context)
+            } else if e.is_connect() || e.is_request() {
+                Self::Network(context)
+            } else if e.is_decode() {
+                Self::Catalog(context)
+            } else {
+                e.status()
+                    .and_then(|status| Self::from_status(status.as_u16(), context.clone()))
+                    .unwrap_or(Self::Internal(context))
+            }
+        } else if let Some(e) = root.downcast_ref::<std::io::Error>() {
+            match e.kind() {
+                std::io::ErrorKind::NotFound => Self::NotFound(context),
+                std::io::ErrorKind::PermissionDenied => Self::AccessDenied(context),
+                std::io::ErrorKind::TimedOut => Self::Timeout(context),
+                _ => Self::Internal(context),
+            }
+        } else {
+            Self::Internal(context)
+        }
+    }
+
+    /// The Python exception, its message prefixed with `summary`
+    pub fn into_py_err(self, summary: &str) -> PyErr {
+        let context = self.context();
+        let err = py::DrainageError::new_err(format!("{}: {}", summary, context.message));
+        Python::with_gil(|py| {
+            let value = err.value(py);
+            let attributes = [
+                ("code", self.code().into_py(py)),
+                ("phase", context.phase.clone().into_py(py)),
+                ("object_key", context.object_key.clone().into_py(py)),
+                ("retriable", self.retriable().into_py(py)),
+            ];
+            for (name, attribute) in attributes {
+                // Setting attributes on a fresh exception instance can't fail
+                let _ = value.setattr(name, attribute);
+            }
+        });
+        err
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_are_classified_through_context() {
+        let throttled = DrainageError::Throttled(
+            ErrorContext::new("GetObject s3://lake/t/_delta_log/0.json failed: SlowDown")
+                .with_key("t/_delta_log/0.json"),
+        );
+        let error = anyhow::Error::new(throttled).context("Failed to read commit 0");
+        let classified = DrainageError::from_anyhow(error, Some("reading_log"));
+        assert_eq!(classified.code(), "THROTTLED");
+        assert!(classified.retriable());
+        assert_eq!(classified.context().phase.as_deref(), Some("reading_log"));
+        assert_eq!(
+            classified.context().object_key.as_deref(),
+            Some("t/_delta_log/0.json")
+        );
+        assert!(classified
+            .to_string()
+            .starts_with("Failed to read commit 0: GetObject"));
+
+        let parse = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let classified = DrainageError::from_anyhow(anyhow::Error::new(parse), None);
+        assert_eq!(classified.code(), "INVALID_METADATA");
+        assert!(!classified.retriable());
+        assert_eq!(classified.context().phase, None);
+
+        let missing =
+            DrainageError::from_status(404, ErrorContext::new("GET /v1/tables/t")).unwrap();
+        assert_eq!(missing.code(), "NOT_FOUND");
+        assert!(DrainageError::from_status(400, ErrorContext::default()).is_none());
+        assert_eq!(
+            DrainageError::from_anyhow(anyhow::anyhow!("unexpected"), Some("listing")).code(),
+            "INTERNAL"
+        );
+    }
+}
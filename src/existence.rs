@@ -0,0 +1,151 @@
+//! HEAD-based existence checks for the files a table's metadata references.
+//!
+//! Listing a huge table to find out whether every referenced file is still there can cost
+//! more than the rest of the analysis. Checking each live file with a HEAD request scales with
+//! the number of live files instead, and works on partitions the analysis never listed.
+//! Requests go out in concurrent batches paced like TCP congestion control: the batch grows
+//! while S3 keeps up and halves, with a growing pause, whenever a request is throttled
+//! (503 SlowDown). Throttled keys are retried in later batches.
+//!
+//! Archived live files get a HEAD request of their own for their restore status, which the
+//! listing doesn't carry.
+
+use crate::s3_client::HeadOutcome;
+use crate::types::{ExistenceCheckMetrics, RestoreStatusMetrics};
+use anyhow::Result;
+use futures::StreamExt;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::time::Duration;
+
+const INITIAL_CONCURRENCY: usize = 16;
+const MAX_CONCURRENCY: usize = 256;
+// Batch growth per batch without throttling
+const CONCURRENCY_STEP: usize = 8;
+const MIN_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+// HEAD attempts per key before it is reported as unverified
+const MAX_ATTEMPTS: u32 = 5;
+const MAX_LISTED_MISSING_FILES: usize = 100;
+// Restore checks cover at most the 100 archived live files a report lists
+const RESTORE_CHECK_CONCURRENCY: usize = 16;
+
+/// Check that each key exists, calling `head` for every attempt
+pub async fn check_keys<F, Fut>(keys: Vec<String>, head: F) -> ExistenceCheckMetrics
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<HeadOutcome>>,
+{
+    let mut metrics = ExistenceCheckMetrics {
+        checked_files: 0,
+        missing_files: 0,
+        missing_paths: Vec::new(),
+        unverified_files: 0,
+        throttled_requests: 0,
+        final_concurrency: INITIAL_CONCURRENCY,
+    };
+    let mut pending: VecDeque<(String, u32)> = keys.into_iter().map(|key| (key, 1)).collect();
+    let mut concurrency = INITIAL_CONCURRENCY;
+    let mut backoff = Duration::ZERO;
+
+    while !pending.is_empty() {
+        let batch: Vec<(String, u32)> = pending.drain(..concurrency.min(pending.len())).collect();
+        let outcomes = futures::future::join_all(batch.into_iter().map(|(key, attempt)| {
+            let request = head(key.clone());
+            async move { (key, attempt, request.await) }
+        }))
+        .await;
+
+        let mut throttled = false;
+        for (key, attempt, outcome) in outcomes {
+            match outcome {
+                Ok(HeadOutcome::Present) => metrics.checked_files += 1,
+                Ok(HeadOutcome::Missing) => {
+                    metrics.checked_files += 1;
+                    metrics.missing_files += 1;
+                    if metrics.missing_paths.len() < MAX_LISTED_MISSING_FILES {
+                        metrics.missing_paths.push(key);
+                    }
+                }
+                Ok(HeadOutcome::Throttled) => {
+                    throttled = true;
+                    metrics.throttled_requests += 1;
+                    if attempt < MAX_ATTEMPTS {
+                        pending.push_back((key, attempt + 1));
+                    } else {
+                        metrics.unverified_files += 1;
+                    }
+                }
+                // Access denied and the like won't change on a retry
+                Err(_) => metrics.unverified_files += 1,
+            }
+        }
+
+        if throttled {
+            concurrency = (concurrency / 2).max(1);
+            backoff = (backoff * 2).clamp(MIN_BACKOFF, MAX_BACKOFF);
+            tokio::time::sleep(backoff).await;
+        } else {
+            concurrency = (concurrency + CONCURRENCY_STEP).min(MAX_CONCURRENCY);
+            backoff = Duration::ZERO;
+        }
+    }
+
+    metrics.missing_paths.sort();
+    metrics.final_concurrency = concurrency;
+    metrics
+}
+
+/// Restore status of archived keys, from the `x-amz-restore` header `head` returns
+pub async fn check_restore_status<F, Fut>(keys: Vec<String>, head: F) -> RestoreStatusMetrics
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Option<String>>>,
+{
+    let headers = futures::stream::iter(keys)
+        .map(head)
+        .buffer_unordered(RESTORE_CHECK_CONCURRENCY)
+        .collect()
+        .await;
+    RestoreStatusMetrics::from_headers(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_check_keys_backs_off_and_retries_throttled_keys() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let calls = AtomicUsize::new(0);
+        let keys: Vec<String> = (0..40)
+            .map(|i| format!("t/part-{:02}.parquet", i))
+            .collect();
+
+        let metrics = runtime.block_on(check_keys(keys, |key| {
+            // The first 20 requests are throttled; "part-07" is gone and "part-09" is denied
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if call < 20 {
+                    Ok(HeadOutcome::Throttled)
+                } else if key.ends_with("part-07.parquet") {
+                    Ok(HeadOutcome::Missing)
+                } else if key.ends_with("part-09.parquet") {
+                    Err(anyhow::anyhow!("Access Denied"))
+                } else {
+                    Ok(HeadOutcome::Present)
+                }
+            }
+        }));
+
+        assert_eq!(metrics.throttled_requests, 20);
+        assert_eq!(metrics.checked_files, 39);
+        assert_eq!(metrics.missing_files, 1);
+        assert_eq!(metrics.missing_paths, vec!["t/part-07.parquet"]);
+        assert_eq!(metrics.unverified_files, 1);
+        // Halved by the two throttled batches (16 -> 8 -> 4), then grown by a step per batch
+        assert_eq!(metrics.final_concurrency, 28);
+        assert_eq!(calls.load(Ordering::SeqCst), 60);
+    }
+}
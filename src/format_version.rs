@@ -0,0 +1,159 @@
+//! Iceberg format-version audit: which format version the table is on, what v1 leftovers its
+//! metadata still carries, and what upgrading to v2 would change.
+//!
+//! Format version 1 has no delete files, so every row-level delete, update or merge rewrites
+//! the data files it touches (copy-on-write). Version 2 adds position and equality deletes,
+//! letting those operations write small delete files instead (merge-on-read). The overwrite
+//! commits in the snapshot history show how much rewriting that would have saved.
+
+use crate::types::FormatVersionMetrics;
+use serde_json::Value;
+
+/// Row-level operations and their write mode properties
+const WRITE_MODES: [(&str, &str); 3] = [
+    ("delete", "write.delete.mode"),
+    ("update", "write.update.mode"),
+    ("merge", "write.merge.mode"),
+];
+/// v1 metadata fields and the v2 fields replacing them
+const LEGACY_FIELDS: [(&str, &str); 2] =
+    [("schema", "schemas"), ("partition-spec", "partition-specs")];
+
+/// A snapshot summary count, which Iceberg writes as a string
+fn summary_count(summary: Option<&Value>, key: &str) -> u64 {
+    summary
+        .and_then(|s| s.get(key))
+        .and_then(|v| {
+            v.as_str()
+                .and_then(|s| s.parse().ok())
+                .or_else(|| v.as_u64())
+        })
+        .unwrap_or(0)
+}
+
+/// Audit the format version of the table described by `metadata` (a `metadata.json`)
+pub fn audit(metadata: &Value) -> FormatVersionMetrics {
+    let format_version = metadata
+        .get("format-version")
+        .and_then(Value::as_u64)
+        .unwrap_or(1);
+    let snapshots: &[Value] = metadata
+        .get("snapshots")
+        .and_then(Value::as_array)
+        .map_or(&[], Vec::as_slice);
+
+    // v1 snapshots may list their manifests inline instead of writing a manifest list
+    let legacy_manifest_snapshots = snapshots
+        .iter()
+        .filter(|s| s.get("manifest-list").is_none() && s.get("manifests").is_some())
+        .count();
+    let legacy_metadata_fields = LEGACY_FIELDS
+        .iter()
+        .filter(|(legacy, current)| {
+            metadata.get(*legacy).is_some() && metadata.get(*current).is_none()
+        })
+        .map(|(legacy, _)| legacy.to_string())
+        .collect();
+
+    let current_summary = metadata
+        .get("current-snapshot-id")
+        .and_then(Value::as_i64)
+        .and_then(|id| {
+            snapshots
+                .iter()
+                .find(|s| s.get("snapshot-id").and_then(Value::as_i64) == Some(id))
+        })
+        .and_then(|s| s.get("summary"));
+    let delete_files = summary_count(current_summary, "total-delete-files");
+
+    // Overwrites that removed data files rewrote them to change rows
+    let rewrites: Vec<Option<&Value>> = snapshots
+        .iter()
+        .map(|s| s.get("summary"))
+        .filter(|summary| {
+            summary
+                .and_then(|s| s.get("operation"))
+                .and_then(Value::as_str)
+                == Some("overwrite")
+                && summary_count(*summary, "deleted-data-files") > 0
+        })
+        .collect();
+    let copy_on_write_rewritten_bytes = rewrites
+        .iter()
+        .map(|summary| summary_count(*summary, "removed-files-size"))
+        .sum();
+
+    let properties = metadata.get("properties");
+    let merge_on_read_operations = WRITE_MODES
+        .iter()
+        .filter(|(_, property)| {
+            properties
+                .and_then(|p| p.get(*property))
+                .and_then(Value::as_str)
+                == Some("merge-on-read")
+        })
+        .map(|(operation, _)| operation.to_string())
+        .collect();
+
+    FormatVersionMetrics {
+        format_version,
+        legacy_manifest_snapshots,
+        legacy_metadata_fields,
+        row_level_deletes_supported: format_version >= 2,
+        delete_files,
+        copy_on_write_rewrites: rewrites.len(),
+        copy_on_write_rewritten_bytes,
+        merge_on_read_operations,
+        upgrade_recommended: format_version < 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_audit_v1_table_with_rewrites() {
+        let v1 = json!({
+            "format-version": 1,
+            "schema": {"type": "struct", "fields": []},
+            "partition-spec": [],
+            "current-snapshot-id": 2,
+            "snapshots": [
+                {"snapshot-id": 1, "manifests": ["s3://lake/t/metadata/m0.avro"],
+                 "summary": {"operation": "append", "added-data-files": "4"}},
+                {"snapshot-id": 2, "manifest-list": "s3://lake/t/metadata/snap-2.avro",
+                 "summary": {"operation": "overwrite", "deleted-data-files": "2",
+                             "removed-files-size": "1048576", "added-data-files": "2"}},
+            ],
+        });
+        let audit_v1 = audit(&v1);
+        assert_eq!(audit_v1.format_version, 1);
+        assert!(audit_v1.upgrade_recommended);
+        assert!(!audit_v1.row_level_deletes_supported);
+        assert_eq!(audit_v1.legacy_manifest_snapshots, 1);
+        assert_eq!(
+            audit_v1.legacy_metadata_fields,
+            ["schema", "partition-spec"]
+        );
+        assert_eq!(audit_v1.copy_on_write_rewrites, 1);
+        assert_eq!(audit_v1.copy_on_write_rewritten_bytes, 1048576);
+        assert!(audit_v1.recommendations()[0].contains("format-version"));
+
+        let v2 = json!({
+            "format-version": 2,
+            "schemas": [],
+            "properties": {"write.delete.mode": "merge-on-read"},
+            "current-snapshot-id": 7,
+            "snapshots": [{"snapshot-id": 7, "manifest-list": "snap-7.avro",
+                           "summary": {"operation": "delete", "total-delete-files": "3"}}],
+        });
+        let audit_v2 = audit(&v2);
+        assert!(!audit_v2.upgrade_recommended);
+        assert_eq!(audit_v2.delete_files, 3);
+        assert_eq!(audit_v2.merge_on_read_operations, ["delete"]);
+        assert!(audit_v2.legacy_metadata_fields.is_empty());
+        assert!(audit_v2.recommendations().is_empty());
+    }
+}
@@ -1,12 +1,25 @@
+use crate::catalog::{find_tables, CatalogConfig, LoadedTable};
+use crate::config::{AnalysisConfig, DetectionRules};
 use crate::delta_lake::DeltaLakeAnalyzer;
+use crate::error::{DrainageError, ErrorContext};
 use crate::iceberg::IcebergAnalyzer;
-use crate::s3_client::S3ClientWrapper;
-use crate::types::HealthReport;
+use crate::network::NetworkSettings;
+use crate::s3_client::{relative_key, AwsCredentials, S3ClientWrapper};
+use crate::stream::EventSink;
+use crate::types::{HealthReport, NamespaceReport, TableAnalysis, TableDetection, TableSchema};
+use anyhow::Result;
+use aws_sdk_s3::Client as S3Client;
+use futures::{FutureExt, StreamExt};
 use pyo3::prelude::*;
+use serde::Deserialize;
+use std::sync::Arc;
 
 #[pyclass]
 pub struct HealthAnalyzer {
     s3_client: S3ClientWrapper,
+    config: AnalysisConfig,
+    catalog_schema: Option<TableSchema>,
+    catalog_table_type: Option<&'static str>, // format registered in the catalog
 }
 
 #[pymethods]
@@ -24,49 +37,468 @@ impl HealthAnalyzer {
     /// Create a new HealthAnalyzer asynchronously (internal use)
     pub async fn create_async(
         s3_path: String,
-        aws_access_key_id: Option<String>,
-        aws_secret_access_key: Option<String>,
+        credentials: AwsCredentials,
         aws_region: Option<String>,
+        config: AnalysisConfig,
     ) -> PyResult<Self> {
-        let s3_client = S3ClientWrapper::new(
-            &s3_path,
-            aws_access_key_id,
-            aws_secret_access_key,
+        let s3_client = S3ClientWrapper::new(&s3_path, credentials, aws_region, &config.network)
+            .await
+            .map_err(|e| {
+                DrainageError::from_anyhow(e, Some("client_setup"))
+                    .into_py_err("Failed to create S3 client")
+            })?;
+
+        Ok(Self::with_client(s3_client, config))
+    }
+
+    /// An analyzer for a table reached through an existing client (internal use)
+    pub fn with_client(s3_client: S3ClientWrapper, config: AnalysisConfig) -> Self {
+        Self {
+            s3_client: s3_client.with_listing(config.listing.as_deref()),
+            config,
+            catalog_schema: None,
+            catalog_table_type: None,
+        }
+    }
+
+    /// Resolve a table through a catalog, using the storage credentials it vends
+    /// (falling back to the default AWS chain) (internal use)
+    pub async fn create_from_catalog(
+        catalog: &CatalogConfig,
+        table: &str,
+        aws_region: Option<String>,
+        config: AnalysisConfig,
+    ) -> PyResult<Self> {
+        let runtime_error = |e: anyhow::Error| {
+            DrainageError::from_anyhow(e, Some("catalog")).into_py_err("Catalog lookup failed")
+        };
+        let client = catalog
+            .connect(aws_region.clone(), &config.network)
+            .await
+            .map_err(runtime_error)?;
+        let mut loaded = client.load_table(table).await.map_err(runtime_error)?;
+        let catalog_schema = loaded.schema.take();
+        let catalog_table_type = loaded.table_type;
+        let s3_client = catalog_table_client(
+            loaded,
             aws_region,
+            &config.network,
+            &tokio::sync::OnceCell::new(),
         )
         .await
         .map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to create S3 client: {}", e))
+            DrainageError::from_anyhow(e, Some("client_setup"))
+                .into_py_err("Failed to create S3 client")
         })?;
 
-        Ok(Self { s3_client })
+        Ok(Self {
+            s3_client: s3_client.with_listing(config.listing.as_deref()),
+            config,
+            catalog_schema,
+            catalog_table_type,
+        })
+    }
+
+    /// Analyze a catalog table as the format the catalog registered it with, detecting it
+    /// when the catalog doesn't say (internal use)
+    pub async fn analyze_catalog_table(&self) -> PyResult<HealthReport> {
+        self.analyze(self.catalog_table_type).await
     }
 
     /// Analyze Delta Lake table health (internal use)
     pub async fn analyze_delta_lake(&self) -> PyResult<HealthReport> {
-        let analyzer = DeltaLakeAnalyzer::new(self.s3_client.clone());
+        let analyzer = DeltaLakeAnalyzer::new(self.s3_client.clone(), self.config.clone())
+            .with_catalog_schema(self.catalog_schema.clone());
         analyzer.analyze().await.map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!("Delta Lake analysis failed: {}", e))
+            DrainageError::from_anyhow(e, None).into_py_err("Delta Lake analysis failed")
         })
     }
 
     /// Analyze Apache Iceberg table health (internal use)
     pub async fn analyze_iceberg(&self) -> PyResult<HealthReport> {
-        let analyzer = IcebergAnalyzer::new(self.s3_client.clone());
-        analyzer.analyze().await.map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!("Iceberg analysis failed: {}", e))
-        })
+        let analyzer = IcebergAnalyzer::new(self.s3_client.clone(), self.config.clone())
+            .with_catalog_schema(self.catalog_schema.clone());
+        analyzer
+            .analyze()
+            .await
+            .map_err(|e| DrainageError::from_anyhow(e, None).into_py_err("Iceberg analysis failed"))
+    }
+
+    /// Analyze the table as `table_type`, detecting its format when None (internal use)
+    pub async fn analyze(&self, table_type: Option<&str>) -> PyResult<HealthReport> {
+        // If table type is specified, use it directly
+        if let Some(ttype) = table_type {
+            match ttype.to_lowercase().as_str() {
+                "delta" | "delta_lake" => self.analyze_delta_lake().await,
+                "iceberg" | "apache_iceberg" => self.analyze_iceberg().await,
+                _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown table type: {}. Supported types: 'delta', 'iceberg'",
+                    ttype
+                ))),
+            }
+        } else {
+            // Auto-detect table type by checking for characteristic files
+            match self.detect_table_type().await? {
+                "delta" => self.analyze_delta_lake().await,
+                _ => self.analyze_iceberg().await,
+            }
+        }
     }
 
-    /// List objects for table type detection (internal use)
-    pub async fn list_objects_for_detection(&self) -> PyResult<Vec<crate::s3_client::ObjectInfo>> {
-        self.s3_client
-            .list_objects(self.s3_client.get_prefix())
+    /// Detect the table format from the objects under the path, with its evidence (internal use)
+    pub async fn detect(&self) -> PyResult<TableDetection> {
+        detect_table(&self.s3_client, &self.config.detection)
             .await
             .map_err(|e| {
-                pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to list objects: {}", e))
+                DrainageError::from_anyhow(e, Some("detection"))
+                    .into_py_err("Table type detection failed")
             })
     }
+
+    /// Copy the table's metadata files, without data, to `output_dir` (internal use)
+    pub async fn capture_metadata(
+        &self,
+        output_dir: &std::path::Path,
+    ) -> PyResult<crate::capture::MetadataCapture> {
+        crate::capture::capture_metadata(&self.s3_client, &self.config, output_dir)
+            .await
+            .map_err(|e| {
+                DrainageError::from_anyhow(e, Some("capture"))
+                    .into_py_err("Metadata capture failed")
+            })
+    }
+
+    /// Detect the table format to analyze the path as (internal use)
+    pub async fn detect_table_type(&self) -> PyResult<&'static str> {
+        self.detect()
+            .await?
+            .analyzable()
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+}
+
+/// Classify the table from characteristic files: Delta Lake, Iceberg, both ("ambiguous"),
+/// Hudi, plain Parquet or "unknown". User signatures are checked first; the built-in checks
+/// only decide when they match neither or both formats.
+pub fn classify_table(
+    objects: &[crate::s3_client::ObjectInfo],
+    table_prefix: &str,
+    rules: &DetectionRules,
+) -> TableDetection {
+    let keys = || {
+        objects
+            .iter()
+            .map(|obj| relative_key(table_prefix, &obj.key))
+    };
+    let signature_match = |format: &str| {
+        rules
+            .signatures
+            .iter()
+            .filter(|(f, _)| *f == format)
+            .find_map(|(_, pattern)| {
+                keys()
+                    .find(|key| pattern.is_match(key))
+                    .map(|key| format!("{}: matches detection signature {}", key, pattern))
+            })
+    };
+    let (mut delta, mut iceberg) = (signature_match("delta"), signature_match("iceberg"));
+    if delta.is_some() == iceberg.is_some() {
+        // Check for Delta Lake characteristic files
+        delta = keys()
+            .find(|key| key.contains("_delta_log/") && key.ends_with(".json"))
+            .map(|key| format!("{}: Delta Lake transaction log", key));
+        // Check for Iceberg characteristic files
+        iceberg = keys()
+            .find(|key| key.ends_with("metadata.json"))
+            .map(|key| format!("{}: Iceberg table metadata", key));
+    }
+
+    let mut evidence = Vec::new();
+    let table_type = match (&delta, &iceberg, rules.prefer) {
+        (Some(_), None, _) => "delta",
+        (None, Some(_), _) => "iceberg",
+        (Some(_), Some(_), Some(prefer)) => {
+            evidence.push(format!("prefer_table_type is {}", prefer));
+            prefer
+        }
+        (Some(_), Some(_), None) => "ambiguous",
+        (None, None, _) => {
+            if let Some(key) = keys().find(|key| key.starts_with(".hoodie/")) {
+                evidence.push(format!("{}: Apache Hudi timeline", key));
+                "hudi"
+            } else if let Some(key) = keys().find(|key| key.ends_with(".parquet")) {
+                evidence.push(format!("{}: Parquet data file without table metadata", key));
+                "parquet"
+            } else {
+                "unknown"
+            }
+        }
+    };
+    evidence.extend(delta);
+    evidence.extend(iceberg);
+    TableDetection {
+        table_type: table_type.to_string(),
+        evidence,
+        uniform: false,
+    }
+}
+
+/// Evidence that the newest Iceberg metadata file was written by Delta UniForm, which
+/// records the Delta version it converted in the table properties
+fn uniform_evidence(metadata_key: &str, metadata: &[u8]) -> Option<String> {
+    let metadata: serde_json::Value = serde_json::from_slice(metadata).ok()?;
+    let version = crate::migration::converted_delta_version(&metadata)?;
+    Some(format!(
+        "{}: written by Delta UniForm from Delta version {}",
+        metadata_key, version
+    ))
+}
+
+/// List the table and classify it. When both formats are found, a Delta UniForm table
+/// (Iceberg metadata generated from the Delta log) is detected as Delta, its source of truth.
+pub async fn detect_table(
+    s3_client: &S3ClientWrapper,
+    rules: &DetectionRules,
+) -> Result<TableDetection> {
+    let table_prefix = s3_client.get_prefix();
+    let objects = s3_client.list_objects(table_prefix).await?;
+    let mut detection = classify_table(&objects, table_prefix, rules);
+    if detection.table_type != "ambiguous" {
+        return Ok(detection);
+    }
+    let newest_metadata = objects
+        .iter()
+        .filter(|obj| {
+            relative_key(table_prefix, &obj.key).starts_with("metadata/")
+                && obj.key.ends_with("metadata.json")
+        })
+        .max_by(|a, b| (a.last_modified, &a.key).cmp(&(b.last_modified, &b.key)));
+    if let Some(metadata) = newest_metadata {
+        let content = s3_client.get_object(&metadata.key).await?;
+        let key = relative_key(table_prefix, &metadata.key);
+        if let Some(evidence) = uniform_evidence(key, &content) {
+            detection.table_type = "delta".to_string();
+            detection.uniform = true;
+            detection.evidence.insert(0, evidence);
+        }
+    }
+    Ok(detection)
+}
+
+/// A table to analyze outside the Python API (C ABI, JSON-RPC server)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TableTarget {
+    pub s3_path: String,
+    #[serde(default)]
+    pub table_type: Option<String>,
+    #[serde(flatten)]
+    pub credentials: AwsCredentials,
+    #[serde(default)]
+    pub aws_region: Option<String>,
+}
+
+impl TableTarget {
+    async fn client(&self, config: &AnalysisConfig) -> Result<S3ClientWrapper> {
+        let s3_client = S3ClientWrapper::new(
+            &self.s3_path,
+            self.credentials.clone(),
+            self.aws_region.clone(),
+            &config.network,
+        )
+        .await?;
+        Ok(s3_client.with_listing(config.listing.as_deref()))
+    }
+
+    async fn detect_with(
+        &self,
+        s3_client: &S3ClientWrapper,
+        rules: &DetectionRules,
+    ) -> Result<String> {
+        Ok(detect_table(s3_client, rules)
+            .await?
+            .analyzable()
+            .map_err(|message| anyhow::anyhow!(message))?
+            .to_string())
+    }
+
+    /// Detect the table format from the objects under the path
+    pub async fn detect(&self, config: &AnalysisConfig) -> Result<String> {
+        let s3_client = self.client(config).await?;
+        self.detect_with(&s3_client, &config.detection).await
+    }
+
+    /// Analyze the table, detecting its format unless `table_type` is set. `progress`
+    /// is called with the name of each stage as it starts.
+    pub async fn analyze(
+        &self,
+        config: AnalysisConfig,
+        progress: impl FnMut(&'static str),
+    ) -> Result<HealthReport> {
+        self.analyze_with_events(config, progress, EventSink::default())
+            .await
+    }
+
+    /// Like `analyze`, also reporting the analyzer's own stages, warnings and metrics to `events`
+    pub async fn analyze_with_events(
+        &self,
+        config: AnalysisConfig,
+        mut progress: impl FnMut(&'static str),
+        events: EventSink,
+    ) -> Result<HealthReport> {
+        let s3_client = self.client(&config).await?;
+        let table_type = match self.table_type {
+            Some(ref table_type) => table_type.to_lowercase(),
+            None => {
+                progress("detecting");
+                self.detect_with(&s3_client, &config.detection).await?
+            }
+        };
+
+        progress("analyzing");
+        match table_type.as_str() {
+            "delta" | "delta_lake" => {
+                DeltaLakeAnalyzer::new(s3_client, config)
+                    .with_events(events)
+                    .analyze()
+                    .await
+            }
+            "iceberg" | "apache_iceberg" => {
+                IcebergAnalyzer::new(s3_client, config)
+                    .with_events(events)
+                    .analyze()
+                    .await
+            }
+            other => Err(anyhow::anyhow!(
+                "Unknown table type: {}. Supported types: 'delta', 'iceberg'",
+                other
+            )),
+        }
+    }
+}
+
+/// S3 client for a catalog table, using the storage credentials it vends. Tables without
+/// vended credentials use the default AWS chain through `default_client`, shared so they
+/// reuse one connection pool.
+async fn catalog_table_client(
+    loaded: LoadedTable,
+    aws_region: Option<String>,
+    network: &NetworkSettings,
+    default_client: &tokio::sync::OnceCell<S3Client>,
+) -> Result<S3ClientWrapper> {
+    let Some(credentials) = loaded.credentials else {
+        let client = default_client
+            .get_or_try_init(|| {
+                S3ClientWrapper::connect(AwsCredentials::default(), aws_region, network)
+            })
+            .await?;
+        return S3ClientWrapper::with_client(client.clone(), &loaded.location);
+    };
+    let non_empty = |value: String| (!value.is_empty()).then_some(value);
+    S3ClientWrapper::new(
+        &loaded.location,
+        AwsCredentials {
+            aws_access_key_id: non_empty(credentials.access_key_id),
+            aws_secret_access_key: non_empty(credentials.secret_access_key),
+            aws_session_token: credentials.session_token,
+            ..AwsCredentials::default()
+        },
+        credentials.region.or(aws_region),
+        network,
+    )
+    .await
+}
+
+/// Analyze every catalog table matching `pattern` (e.g. `analytics.*`), up to
+/// `max_concurrency` at a time, as the format the catalog registered it with (detected from
+/// storage when it doesn't say). A failing table is recorded with its error and doesn't
+/// stop the crawl; only catalog connection and listing errors fail the whole call. The
+/// tables share one metadata cache, so manifests common to cloned tables are read once.
+pub async fn analyze_namespace(
+    catalog: &CatalogConfig,
+    pattern: &str,
+    aws_region: Option<String>,
+    mut config: AnalysisConfig,
+    max_concurrency: usize,
+    cancelled: impl std::future::Future<Output = ()>,
+) -> Result<NamespaceReport> {
+    let cancelled = cancelled.shared();
+    config.object_cache.get_or_insert_with(Arc::default);
+    let default_client = tokio::sync::OnceCell::new();
+    let listed = async {
+        let client = catalog.connect(aws_region.clone(), &config.network).await?;
+        let tables = find_tables(client.as_ref(), pattern).await?;
+        anyhow::Ok((client, tables))
+    };
+    let (client, tables) = tokio::select! {
+        listed = listed => listed?,
+        _ = cancelled.clone() => {
+            let mut report = NamespaceReport::new(pattern.to_string(), Vec::new());
+            report.interrupted = true;
+            return Ok(report);
+        }
+    };
+
+    let (client, default_client) = (client.as_ref(), &default_client);
+    let table_count = tables.len();
+    let results = futures::stream::iter(tables)
+        .map(|table| {
+            let aws_region = aws_region.clone();
+            let config = config.clone();
+            async move {
+                let outcome = async {
+                    let mut loaded = client.load_table(&table).await?;
+                    let catalog_schema = loaded.schema.take();
+                    let table_type = loaded.table_type;
+                    let s3_client =
+                        catalog_table_client(loaded, aws_region, &config.network, default_client)
+                            .await?;
+                    let table_type = match table_type {
+                        Some(table_type) => table_type,
+                        None => detect_table(&s3_client, &config.detection)
+                            .await?
+                            .analyzable()
+                            .map_err(|e| DrainageError::InvalidInput(ErrorContext::new(e)))?,
+                    };
+                    if table_type == "delta" {
+                        DeltaLakeAnalyzer::new(s3_client, config)
+                            .with_catalog_schema(catalog_schema)
+                            .analyze()
+                            .await
+                    } else {
+                        IcebergAnalyzer::new(s3_client, config)
+                            .with_catalog_schema(catalog_schema)
+                            .analyze()
+                            .await
+                    }
+                }
+                .await;
+                match outcome {
+                    Ok(report) => TableAnalysis {
+                        table,
+                        report: Some(report),
+                        error: None,
+                        error_code: None,
+                    },
+                    Err(e) => {
+                        let error = DrainageError::from_anyhow(e, None);
+                        TableAnalysis {
+                            table,
+                            report: None,
+                            error: Some(error.to_string()),
+                            error_code: Some(error.code().to_string()),
+                        }
+                    }
+                }
+            }
+        })
+        .buffered(max_concurrency.max(1))
+        .take_until(cancelled)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut report = NamespaceReport::new(pattern.to_string(), results);
+    report.interrupted = report.tables.len() < table_count;
+    Ok(report)
 }
 
 // We need to implement Clone for S3ClientWrapper to use it in the analyzer methods
@@ -78,6 +510,9 @@ impl Clone for S3ClientWrapper {
             client: self.client.clone(),
             bucket: self.bucket.clone(),
             prefix: self.prefix.clone(),
+            requests: self.requests.clone(),
+            listing: self.listing.clone(),
+            cache: self.cache.clone(),
         }
     }
 }
@@ -133,24 +568,27 @@ mod tests {
 
     #[test]
     fn test_health_analyzer_table_type_detection_delta() {
-        let objects = vec![
+        let objects = [
             crate::s3_client::ObjectInfo {
                 key: "part-00000.parquet".to_string(),
                 size: 1024,
                 last_modified: None,
                 etag: None,
+                storage_class: None,
             },
             crate::s3_client::ObjectInfo {
                 key: "_delta_log/00000000000000000000.json".to_string(),
                 size: 2048,
                 last_modified: None,
                 etag: None,
+                storage_class: None,
             },
             crate::s3_client::ObjectInfo {
                 key: "_delta_log/00000000000000000001.json".to_string(),
                 size: 1024,
                 last_modified: None,
                 etag: None,
+                storage_class: None,
             },
         ];
 
@@ -162,28 +600,35 @@ mod tests {
 
         assert!(has_delta_log, "Should detect Delta Lake files");
         assert!(!has_iceberg_metadata, "Should not detect Iceberg files");
+        assert_eq!(
+            super::classify_table(&objects, "", &Default::default()).analyzable(),
+            Ok("delta")
+        );
     }
 
     #[test]
     fn test_health_analyzer_table_type_detection_iceberg() {
-        let objects = vec![
+        let objects = [
             crate::s3_client::ObjectInfo {
                 key: "data/00000-0-00000000000000000000-00000000000000000000.parquet".to_string(),
                 size: 1024,
                 last_modified: None,
                 etag: None,
+                storage_class: None,
             },
             crate::s3_client::ObjectInfo {
                 key: "metadata/00000-00000000000000000000.metadata.json".to_string(),
                 size: 2048,
                 last_modified: None,
                 etag: None,
+                storage_class: None,
             },
             crate::s3_client::ObjectInfo {
                 key: "metadata/snap-00000000000000000000-1-00000000000000000000.avro".to_string(),
                 size: 1024,
                 last_modified: None,
                 etag: None,
+                storage_class: None,
             },
         ];
 
@@ -194,29 +639,36 @@ mod tests {
         let has_iceberg_metadata = objects.iter().any(|obj| obj.key.ends_with("metadata.json"));
 
         assert!(!has_delta_log, "Should not detect Delta Lake files");
+        assert_eq!(
+            super::classify_table(&objects, "", &Default::default()).analyzable(),
+            Ok("iceberg")
+        );
         assert!(has_iceberg_metadata, "Should detect Iceberg files");
     }
 
     #[test]
     fn test_health_analyzer_table_type_detection_ambiguous() {
-        let objects = vec![
+        let objects = [
             crate::s3_client::ObjectInfo {
                 key: "part-00000.parquet".to_string(),
                 size: 1024,
                 last_modified: None,
                 etag: None,
+                storage_class: None,
             },
             crate::s3_client::ObjectInfo {
                 key: "_delta_log/00000000000000000000.json".to_string(),
                 size: 2048,
                 last_modified: None,
                 etag: None,
+                storage_class: None,
             },
             crate::s3_client::ObjectInfo {
                 key: "metadata/00000-00000000000000000000.metadata.json".to_string(),
                 size: 1024,
                 last_modified: None,
                 etag: None,
+                storage_class: None,
             },
         ];
 
@@ -229,6 +681,97 @@ mod tests {
         assert!(has_delta_log, "Should detect Delta Lake files");
         assert!(has_iceberg_metadata, "Should detect Iceberg files");
         // This should be ambiguous
+        assert!(super::classify_table(&objects, "", &Default::default())
+            .analyzable()
+            .is_err());
+    }
+
+    #[test]
+    fn test_detect_table_type_with_user_rules() {
+        let object = |key: &str| crate::s3_client::ObjectInfo {
+            key: key.to_string(),
+            size: 1024,
+            last_modified: None,
+            etag: None,
+            storage_class: None,
+        };
+        let uniform = [
+            object("lake/events/_delta_log/00000000000000000000.json"),
+            object("lake/events/metadata/v1.metadata.json"),
+        ];
+        let options = serde_json::json!({ "prefer_table_type": "delta_lake" });
+        let config =
+            crate::config::AnalysisConfig::from_json(options.as_object().unwrap()).unwrap();
+        assert_eq!(
+            super::classify_table(&uniform, "lake/events", &config.detection).analyzable(),
+            Ok("delta")
+        );
+
+        // A signature on relative keys decides alone when it matches one format
+        let converted = [
+            object("lake/events/_delta_log/00000000000000000000.json"),
+            object("lake/events/metadata/v1.metadata.json"),
+            object("lake/events/metadata/version-hint.text"),
+        ];
+        let options = serde_json::json!({
+            "detection_signatures": { "iceberg": ["^metadata/version-hint\\.text$"] }
+        });
+        let config =
+            crate::config::AnalysisConfig::from_json(options.as_object().unwrap()).unwrap();
+        assert_eq!(
+            super::classify_table(&converted, "lake/events", &config.detection).analyzable(),
+            Ok("iceberg")
+        );
+        assert!(
+            super::classify_table(&converted, "lake/events", &Default::default())
+                .analyzable()
+                .is_err()
+        );
+
+        let options = serde_json::json!({ "detection_signatures": { "hudi": [".hoodie/"] } });
+        assert!(crate::config::AnalysisConfig::from_json(options.as_object().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_classify_table_with_evidence() {
+        let object = |key: &str| crate::s3_client::ObjectInfo {
+            key: key.to_string(),
+            size: 1024,
+            last_modified: None,
+            etag: None,
+            storage_class: None,
+        };
+        let rules = Default::default();
+        let classify = |keys: &[&str]| {
+            let objects: Vec<_> = keys.iter().map(|key| object(key)).collect();
+            super::classify_table(&objects, "lake/t", &rules)
+        };
+
+        let delta = classify(&["lake/t/_delta_log/00000000000000000000.json"]);
+        assert_eq!(delta.table_type, "delta");
+        assert_eq!(
+            delta.evidence,
+            ["_delta_log/00000000000000000000.json: Delta Lake transaction log"]
+        );
+        let both = classify(&[
+            "lake/t/_delta_log/00000000000000000000.json",
+            "lake/t/metadata/v1.metadata.json",
+        ]);
+        assert_eq!(both.table_type, "ambiguous");
+        assert_eq!(both.evidence.len(), 2);
+        let hudi = classify(&["lake/t/.hoodie/hoodie.properties", "lake/t/a.parquet"]);
+        assert_eq!(hudi.table_type, "hudi");
+        assert!(hudi.analyzable().unwrap_err().contains("Hudi"));
+        assert_eq!(classify(&["lake/t/a.parquet"]).table_type, "parquet");
+        assert_eq!(classify(&["lake/t/_SUCCESS"]).table_type, "unknown");
+
+        let uniform = br#"{"format-version": 2, "properties": {"delta-version": "12"}}"#;
+        assert_eq!(
+            super::uniform_evidence("metadata/v3.metadata.json", uniform).as_deref(),
+            Some("metadata/v3.metadata.json: written by Delta UniForm from Delta version 12")
+        );
+        let converted = br#"{"format-version": 2, "properties": {"owner": "etl"}}"#;
+        assert!(super::uniform_evidence("metadata/v3.metadata.json", converted).is_none());
     }
 
     #[test]
@@ -0,0 +1,423 @@
+//! Report history (`history_path=`): every analysis saved as JSON under a local directory or
+//! an `s3://` prefix, and `drainage.history(path)` reading it back as a time series for
+//! trend dashboards.
+//!
+//! Reports are stored as `<table id>/<analysis time>.json`, where the table id is a hash of
+//! the table path, so one location can hold the history of many tables. Trends are read
+//! from the JSON rather than deserialized into reports, so reports written by older
+//! versions stay readable.
+//!
+//! `detect_regressions` compares a report with that history. A metric is flagged when it
+//! worsened by more than the sensitivity's threshold since the analysis about a week
+//! earlier and, given at least 3 earlier analyses that vary, lies that many standard
+//! deviations from their mean on the bad side, so ordinary noise doesn't raise alerts.
+
+use crate::s3_client::{relative_key, S3ClientWrapper};
+use crate::types::{HealthReport, HistoryRegression};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use pyo3::prelude::*;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// One analysis of a table, as recorded in the history
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass]
+pub struct HistoryPoint {
+    #[pyo3(get)]
+    pub table_path: String,
+    #[pyo3(get)]
+    pub table_type: String,
+    #[pyo3(get)]
+    pub analysis_timestamp: DateTime<Utc>,
+    #[pyo3(get)]
+    pub health_score: f64,
+    #[pyo3(get)]
+    pub total_size_bytes: u64,
+    #[pyo3(get)]
+    pub orphan_bytes: u64, // unreferenced files past the grace period
+    #[pyo3(get)]
+    pub small_file_ratio: f64, // share of data files under the small bucket bound
+    #[pyo3(get)]
+    pub file_count: usize,
+}
+
+/// Where reports are kept
+pub enum HistoryStore {
+    Local(PathBuf),
+    S3(S3ClientWrapper),
+}
+
+impl HistoryStore {
+    /// The store at `location`, reaching `s3://` locations through `s3_client`'s credentials
+    pub fn at(location: &str, s3_client: &S3ClientWrapper) -> Result<Self> {
+        Ok(match location.starts_with("s3://") {
+            true => Self::S3(s3_client.with_location(location)?),
+            false => Self::Local(PathBuf::from(location)),
+        })
+    }
+
+    /// Save `report`, returning the key or path written
+    pub async fn record(&self, report: &HealthReport) -> Result<String> {
+        let relative = report_key(report);
+        let body = serde_json::to_vec(report)?;
+        match self {
+            Self::Local(root) => {
+                let path = root.join(&relative);
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)
+                        .with_context(|| format!("Cannot create {}", parent.display()))?;
+                }
+                std::fs::write(&path, body)
+                    .with_context(|| format!("Cannot write {}", path.display()))?;
+                Ok(path.display().to_string())
+            }
+            Self::S3(s3_client) => {
+                let key = s3_client.table_key(&relative);
+                s3_client.put_object(&key, body).await?;
+                Ok(format!("s3://{}/{}", s3_client.get_bucket(), key))
+            }
+        }
+    }
+
+    /// Every recorded analysis, of `table_path` only if given, ordered by table and time
+    pub async fn points(&self, table_path: Option<&str>) -> Result<Vec<HistoryPoint>> {
+        let subdir = table_path.map(table_id);
+        let mut points = Vec::new();
+        match self {
+            Self::Local(root) => {
+                let dir = subdir.map_or(root.clone(), |id| root.join(id));
+                for path in json_files(&dir)? {
+                    let content = std::fs::read(&path)
+                        .with_context(|| format!("Cannot read {}", path.display()))?;
+                    points.extend(point(&content));
+                }
+            }
+            Self::S3(s3_client) => {
+                let prefix = s3_client.table_key(&subdir.map_or(String::new(), |id| id + "/"));
+                for object in s3_client.list_objects(&prefix).await? {
+                    if relative_key(s3_client.get_prefix(), &object.key).ends_with(".json") {
+                        points.extend(point(&s3_client.get_object(&object.key).await?));
+                    }
+                }
+            }
+        }
+        points.retain(|p| table_path.is_none_or(|path| p.table_path == path));
+        points.sort_by(|a, b| {
+            (&a.table_path, a.analysis_timestamp).cmp(&(&b.table_path, b.analysis_timestamp))
+        });
+        Ok(points)
+    }
+}
+
+/// Save `report` to the configured `history_path`, if any. A failed write is a warning: the
+/// analysis itself succeeded.
+pub async fn record(
+    s3_client: &S3ClientWrapper,
+    config: &crate::config::AnalysisConfig,
+    events: &crate::stream::EventSink,
+    report: &HealthReport,
+) {
+    let Some(ref location) = config.history_path else {
+        return;
+    };
+    let recorded = match HistoryStore::at(location, s3_client) {
+        Ok(store) => store.record(report).await,
+        Err(e) => Err(e),
+    };
+    match recorded {
+        Ok(path) => tracing::debug!("Report saved to {}", path),
+        Err(e) => events.warning(format!(
+            "Failed to save the report to history {}: {:#}",
+            location, e
+        )),
+    }
+}
+
+/// Earlier analyses needed for a z-score
+const MIN_BASELINE_POINTS: usize = 3;
+/// Orphan bytes growing by less than this are never flagged, however large the factor
+const MIN_ORPHAN_GROWTH_BYTES: f64 = 64.0 * 1024.0 * 1024.0;
+
+/// How large a deterioration `detect_regressions` flags
+#[derive(Debug, Clone, Copy)]
+pub struct Sensitivity {
+    z_score: f64,       // standard deviations from the historical mean
+    growth_factor: f64, // for orphan bytes
+    score_change: f64,  // for the health score and small-file ratio, absolute
+}
+
+impl Sensitivity {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let (z_score, growth_factor, score_change) = match name {
+            "low" => (4.0, 3.0, 0.2),
+            "medium" => (3.0, 2.0, 0.1),
+            "high" => (2.0, 1.5, 0.05),
+            other => {
+                return Err(format!(
+                    "Unknown sensitivity: {} (expected low, medium or high)",
+                    other
+                ))
+            }
+        };
+        Ok(Self {
+            z_score,
+            growth_factor,
+            score_change,
+        })
+    }
+}
+
+/// Whether a larger value of a metric is worse
+type TrendMetric = (&'static str, bool, fn(&HistoryPoint) -> f64);
+
+const TREND_METRICS: [TrendMetric; 3] = [
+    ("health_score", false, |p| p.health_score),
+    ("orphan_bytes", true, |p| p.orphan_bytes as f64),
+    ("small_file_ratio", true, |p| p.small_file_ratio),
+];
+
+fn show(metric: &str, value: f64) -> String {
+    match metric {
+        "orphan_bytes" => format!("{:.2} GB", value / (1024.0 * 1024.0 * 1024.0)),
+        _ => format!("{:.1}%", value * 100.0),
+    }
+}
+
+/// Metrics of `report` that deteriorated significantly against the earlier analyses of its
+/// table in `history`
+pub fn detect_regressions(
+    report: &HealthReport,
+    history: &[HistoryPoint],
+    sensitivity: Sensitivity,
+) -> Result<Vec<HistoryRegression>> {
+    let current = point(&serde_json::to_vec(report)?).context("Report has no metrics")?;
+    let earlier: Vec<&HistoryPoint> = history
+        .iter()
+        .filter(|p| {
+            p.table_path.trim_end_matches('/') == current.table_path.trim_end_matches('/')
+                && p.analysis_timestamp < current.analysis_timestamp
+        })
+        .collect();
+    // The latest analysis at least a week old, or the oldest one when history is shorter
+    let week_ago = current.analysis_timestamp - Duration::days(7);
+    let Some(previous) = earlier
+        .iter()
+        .filter(|p| p.analysis_timestamp <= week_ago)
+        .max_by_key(|p| p.analysis_timestamp)
+        .or_else(|| earlier.iter().min_by_key(|p| p.analysis_timestamp))
+    else {
+        return Ok(Vec::new());
+    };
+
+    Ok(TREND_METRICS
+        .iter()
+        .filter_map(|(metric, higher_is_worse, value)| {
+            let (now, before) = (value(&current), value(previous));
+            let worsening = if *higher_is_worse {
+                now - before
+            } else {
+                before - now
+            };
+            let significant_change = match *metric {
+                "orphan_bytes" => {
+                    now >= before * sensitivity.growth_factor
+                        && worsening >= MIN_ORPHAN_GROWTH_BYTES
+                }
+                _ => worsening >= sensitivity.score_change,
+            };
+            if !significant_change {
+                return None;
+            }
+
+            let values: Vec<f64> = earlier.iter().map(|p| value(p)).collect();
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            let variance =
+                values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+            let z_score = (values.len() >= MIN_BASELINE_POINTS && variance > 0.0).then(|| {
+                let z = (now - mean) / variance.sqrt();
+                if *higher_is_worse {
+                    z
+                } else {
+                    -z
+                }
+            });
+            if z_score.is_some_and(|z| z < sensitivity.z_score) {
+                return None;
+            }
+
+            let mut message = format!(
+                "{} worsened from {} to {} since {}",
+                metric,
+                show(metric, before),
+                show(metric, now),
+                previous.analysis_timestamp.format("%Y-%m-%d")
+            );
+            if let Some(z) = z_score {
+                message.push_str(&format!(
+                    " ({:.1} standard deviations from the mean of {} analyses)",
+                    z,
+                    values.len()
+                ));
+            }
+            Some(HistoryRegression {
+                metric: metric.to_string(),
+                severity: "high".to_string(),
+                current: now,
+                previous: before,
+                previous_timestamp: previous.analysis_timestamp,
+                baseline_mean: mean,
+                z_score,
+                message,
+            })
+        })
+        .collect())
+}
+
+/// Stable directory name for a table's reports
+fn table_id(table_path: &str) -> String {
+    let digest = Sha256::digest(table_path.trim_end_matches('/').as_bytes());
+    hex::encode(&digest[..8])
+}
+
+fn report_key(report: &HealthReport) -> String {
+    format!(
+        "{}/{}.json",
+        table_id(&report.table_path),
+        report.analysis_timestamp.format("%Y%m%dT%H%M%S%.3fZ")
+    )
+}
+
+/// `.json` files under `dir`, recursively; none if it doesn't exist yet
+fn json_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Cannot read {}", dir.display())),
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(json_files(&path)?);
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// The trend values of a stored report; None for files that aren't reports
+fn point(content: &[u8]) -> Option<HistoryPoint> {
+    let report: Value = serde_json::from_slice(content).ok()?;
+    let metrics = report.get("metrics")?;
+    let count = |value: Option<&Value>| value.and_then(Value::as_u64).unwrap_or(0);
+    let file_count = count(metrics.get("total_files")) as usize;
+    let small_files = count(metrics.pointer("/file_size_distribution/small_files"));
+    Some(HistoryPoint {
+        table_path: report.get("table_path")?.as_str()?.to_string(),
+        table_type: report.get("table_type")?.as_str()?.to_string(),
+        analysis_timestamp: report.get("analysis_timestamp")?.as_str()?.parse().ok()?,
+        health_score: report.get("health_score")?.as_f64()?,
+        total_size_bytes: count(metrics.get("total_size_bytes")),
+        orphan_bytes: count(metrics.get("unreferenced_size_bytes")),
+        small_file_ratio: match file_count {
+            0 => 0.0,
+            n => small_files as f64 / n as f64,
+        },
+        file_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_history_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::Local(dir.path().to_path_buf());
+        let report = |table: &str, time: &str, score: f64| {
+            let mut report = HealthReport::new(table.to_string(), "delta".to_string());
+            report.analysis_timestamp = time.parse().unwrap();
+            report.health_score = score;
+            report.metrics.total_files = 4;
+            report.metrics.file_size_distribution.small_files = 1;
+            report.metrics.unreferenced_size_bytes = 2048;
+            report
+        };
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            for (table, time, score) in [
+                ("s3://lake/events", "2024-06-02T00:00:00Z", 0.7),
+                ("s3://lake/events", "2024-06-01T00:00:00Z", 0.9),
+                ("s3://lake/orders", "2024-06-01T00:00:00Z", 0.5),
+            ] {
+                store.record(&report(table, time, score)).await.unwrap();
+            }
+            std::fs::write(dir.path().join("notes.json"), "[]").unwrap();
+
+            let events = store.points(Some("s3://lake/events")).await.unwrap();
+            let scores: Vec<f64> = events.iter().map(|p| p.health_score).collect();
+            assert_eq!(scores, [0.9, 0.7]);
+            assert_eq!(events[0].orphan_bytes, 2048);
+            assert_eq!(events[0].small_file_ratio, 0.25);
+            assert_eq!(store.points(None).await.unwrap().len(), 3);
+            assert!(HistoryStore::Local(dir.path().join("missing"))
+                .points(None)
+                .await
+                .unwrap()
+                .is_empty());
+        });
+    }
+
+    #[test]
+    fn test_detect_regressions_flags_significant_changes() {
+        const GB: u64 = 1024 * 1024 * 1024;
+        let history_point = |day: u32, score: f64, orphan_bytes: u64| HistoryPoint {
+            table_path: "s3://lake/events".to_string(),
+            table_type: "delta".to_string(),
+            analysis_timestamp: format!("2024-06-{:02}T00:00:00Z", day).parse().unwrap(),
+            health_score: score,
+            total_size_bytes: 100 * GB,
+            orphan_bytes,
+            small_file_ratio: 0.1,
+            file_count: 1000,
+        };
+        let history = [
+            history_point(1, 0.80, 2 * GB),
+            history_point(3, 0.82, 2 * GB + GB / 10),
+            history_point(8, 0.81, 2 * GB),
+            history_point(12, 0.80, 2 * GB + GB / 5),
+        ];
+        let mut report = HealthReport::new("s3://lake/events/".to_string(), "delta".to_string());
+        report.analysis_timestamp = "2024-06-15T00:00:00Z".parse().unwrap();
+        report.health_score = 0.78;
+        report.metrics.total_files = 1000;
+        report.metrics.file_size_distribution.small_files = 100;
+        report.metrics.unreferenced_size_bytes = 5 * GB;
+
+        let medium = Sensitivity::parse("medium").unwrap();
+        let found = detect_regressions(&report, &history, medium).unwrap();
+        // Orphan bytes more than doubled since June 8th; the score dip is within noise
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].metric, "orphan_bytes");
+        assert_eq!(found[0].previous, (2 * GB) as f64);
+        assert!(found[0].z_score.unwrap() > 3.0);
+        assert!(found[0].message.contains("since 2024-06-08"));
+
+        // A small rise is not
+        report.metrics.unreferenced_size_bytes = 2 * GB + GB / 10;
+        assert!(detect_regressions(&report, &history, medium)
+            .unwrap()
+            .is_empty());
+        // Too few earlier analyses for a z-score: the change alone decides
+        report.health_score = 0.5;
+        let found =
+            detect_regressions(&report, &history[..2], Sensitivity::parse("low").unwrap()).unwrap();
+        assert_eq!(found[0].metric, "health_score");
+        assert_eq!(found[0].z_score, None);
+        assert!(Sensitivity::parse("extreme").is_err());
+    }
+}
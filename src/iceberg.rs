@@ -1,11 +1,15 @@
+use crate::clustering::{iceberg_bounds, rank_candidates, ColumnBounds, MAX_Z_ORDER_COLUMNS};
+use crate::config::AnalysisConfig;
+use crate::error::DrainageError;
 use crate::s3_client::S3ClientWrapper;
+use crate::stream::EventSink;
 use crate::types::*;
 use anyhow::Result;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone)]
-struct SchemaChange {
+struct SchemaSnapshot {
     #[allow(dead_code)]
     version: u64,
     timestamp: u64,
@@ -13,16 +17,198 @@ struct SchemaChange {
     is_breaking: bool,
 }
 
+/// Snapshots that added and deleted a data file, as recorded in manifest entries
+#[derive(Debug, Clone, Default)]
+struct ManifestEntryHistory {
+    added_snapshot_id: Option<i64>,
+    deleted_snapshot_id: Option<i64>,
+    record_count: Option<u64>,
+    file_size_in_bytes: Option<u64>,
+    bounds: ColumnBounds, // keyed by field id
+}
+
 pub struct IcebergAnalyzer {
     s3_client: S3ClientWrapper,
+    config: AnalysisConfig,
+    // Single clock reading shared by every age calculation in one analysis
+    analysis_time: chrono::DateTime<chrono::Utc>,
+    events: EventSink,
+    // Schema registered in the catalog the table was resolved through, if any
+    catalog_schema: Option<TableSchema>,
+}
+
+/// Column-level changes between consecutive schemas in table metadata. Each change is
+/// timestamped with the first snapshot written under the new schema.
+fn schema_change_log(metadata: &Value) -> Vec<SchemaChange> {
+    let mut schemas: Vec<&Value> = metadata
+        .get("schemas")
+        .and_then(|s| s.as_array())
+        .map(|schemas| schemas.iter().collect())
+        .unwrap_or_default();
+    schemas.sort_by_key(|schema| schema.get("schema-id").and_then(|id| id.as_i64()));
+
+    let first_used = |schema_id: i64| {
+        metadata
+            .get("snapshots")
+            .and_then(|s| s.as_array())?
+            .iter()
+            .filter(|snapshot| {
+                snapshot.get("schema-id").and_then(|id| id.as_i64()) == Some(schema_id)
+            })
+            .filter_map(|snapshot| snapshot.get("timestamp-ms").and_then(|t| t.as_i64()))
+            .min()
+            .and_then(chrono::DateTime::from_timestamp_millis)
+    };
+    let fields = |schema: &Value| {
+        schema
+            .get("fields")
+            .and_then(|f| f.as_array())
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    let mut changes = Vec::new();
+    for pair in schemas.windows(2) {
+        let schema_id = pair[1]
+            .get("schema-id")
+            .and_then(|id| id.as_i64())
+            .unwrap_or(0);
+        changes.extend(SchemaChange::between(
+            schema_id,
+            first_used(schema_id),
+            &fields(pair[0]),
+            &fields(pair[1]),
+        ));
+    }
+
+    changes
+}
+
+/// Current schema of Iceberg table metadata, as found in metadata.json or a catalog's
+/// LoadTable response
+pub(crate) fn current_schema(metadata: &Value) -> TableSchema {
+    // Format v2 keeps every schema in `schemas`; v1 only has `schema`
+    let schema_id = metadata.get("current-schema-id").and_then(|id| id.as_i64());
+    let schema = metadata
+        .get("schemas")
+        .and_then(|schemas| schemas.as_array())
+        .and_then(|schemas| {
+            schemas
+                .iter()
+                .find(|s| s.get("schema-id").and_then(|id| id.as_i64()) == schema_id)
+        })
+        .or_else(|| metadata.get("schema"));
+    let fields = schema
+        .and_then(|s| s.get("fields"))
+        .and_then(|f| f.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let schema_id = schema_id.or_else(|| schema?.get("schema-id")?.as_i64());
+
+    TableSchema::from_fields(schema_id, &fields, current_partition_fields(metadata))
+}
+
+/// Compare each live file's size in the listing with `file_size_in_bytes` in its manifest
+/// entry; manifests key files by their absolute `bucket_uri` + key
+fn check_manifest_sizes(
+    data_files: &[&crate::s3_client::ObjectInfo],
+    referenced_files: &HashMap<String, ManifestEntryHistory>,
+    bucket_uri: &str,
+) -> LogConsistencyMetrics {
+    let mut metrics = LogConsistencyMetrics::new();
+    for file in data_files {
+        let Some(history) = referenced_files.get(&format!("{}{}", bucket_uri, file.key)) else {
+            continue;
+        };
+        if history.deleted_snapshot_id.is_some() {
+            continue;
+        }
+        if let Some(size) = history.file_size_in_bytes {
+            metrics.check_size(&file.key, size, file.size as u64);
+        }
+    }
+    metrics
+}
+
+fn current_partition_fields(metadata: &Value) -> Vec<String> {
+    // Format v2 keeps every spec in `partition-specs`; v1 only has `partition-spec`
+    let default_spec_id = metadata.get("default-spec-id").and_then(|id| id.as_i64());
+    let fields = metadata
+        .get("partition-specs")
+        .and_then(|specs| specs.as_array())
+        .and_then(|specs| {
+            specs
+                .iter()
+                .find(|spec| spec.get("spec-id").and_then(|id| id.as_i64()) == default_spec_id)
+                .or_else(|| specs.last())
+        })
+        .and_then(|spec| spec.get("fields"))
+        .or_else(|| metadata.get("partition-spec"))
+        .and_then(|fields| fields.as_array());
+
+    fields
+        .map(|fields| {
+            fields
+                .iter()
+                .filter_map(|f| f.get("name").and_then(|n| n.as_str()))
+                .map(|s| s.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 impl IcebergAnalyzer {
-    pub fn new(s3_client: S3ClientWrapper) -> Self {
-        Self { s3_client }
+    pub fn new(s3_client: S3ClientWrapper, config: AnalysisConfig) -> Self {
+        Self {
+            s3_client: s3_client.with_cache(config.object_cache.as_ref()),
+            analysis_time: config.clock.now(),
+            config,
+            events: EventSink::default(),
+            catalog_schema: None,
+        }
+    }
+
+    /// Report drift between this catalog-registered schema and the table metadata
+    pub fn with_catalog_schema(mut self, catalog_schema: Option<TableSchema>) -> Self {
+        self.catalog_schema = catalog_schema;
+        self
+    }
+
+    /// Report progress, warnings and metrics to `events` as the analysis runs
+    pub fn with_events(mut self, events: EventSink) -> Self {
+        self.events = events;
+        self
     }
 
     pub async fn analyze(&self) -> Result<HealthReport> {
+        crate::otlp::traced(&self.config, &self.events, async {
+            // Failures name the stage that was running
+            let report = self.analyze_table().await.map_err(|e| {
+                let stage = self.events.current_stage();
+                anyhow::Error::from(DrainageError::from_anyhow(e, stage.as_deref()))
+            });
+            self.events.end_phase();
+            if let Ok(ref report) = report {
+                crate::history::record(&self.s3_client, &self.config, &self.events, report).await;
+                crate::observability::record(&self.s3_client, &self.config, &self.events, report)
+                    .await;
+                crate::notify::notify(&self.config, &self.events, report).await;
+                crate::cloud_metrics::publish(&self.s3_client, &self.config, &self.events, report)
+                    .await;
+            }
+            report.map(|mut report| {
+                report.analysis_log = Some(self.events.analysis_log(!self.config.deterministic));
+                report
+            })
+        })
+        .await
+    }
+
+    #[tracing::instrument(name = "analyze", skip_all, fields(table_type = "iceberg", bucket = self.s3_client.get_bucket(), prefix = self.s3_client.get_prefix()))]
+    async fn analyze_table(&self) -> Result<HealthReport> {
+        let started = std::time::Instant::now();
+        let deadline = crate::deadline::Deadline::after(self.config.max_duration_seconds);
+        let mut phases = crate::partial::Phases::new(self.config.allow_partial, &self.events);
         let mut report = HealthReport::new(
             format!(
                 "s3://{}/{}",
@@ -31,12 +217,45 @@ impl IcebergAnalyzer {
             ),
             "iceberg".to_string(),
         );
+        report.analysis_timestamp = self.analysis_time;
+        if !self.config.partition_filter.is_empty() {
+            report.partition_filter = Some(self.config.partition_filter.to_map());
+        }
+        if !self.config.tags.is_empty() {
+            report.tags = Some(self.config.tags.clone());
+        }
+        if self.config.assess_iceberg_migration {
+            self.events.warning(
+                "assess_iceberg_migration only applies to Delta Lake tables; skipping the assessment",
+            );
+        }
 
-        // List all files in the Iceberg table directory
-        let all_objects = self
-            .s3_client
-            .list_objects(self.s3_client.get_prefix())
-            .await?;
+        self.events.progress("listing");
+        // List all files in the Iceberg table directory. Warm starts list the metadata first
+        // and only the partitions it changed afterwards; with an inventory, data files come
+        // from the inventory report instead.
+        let previous = crate::warm_start::previous_report(&self.config, &report);
+        if previous.is_none() && self.config.previous_report.is_some() {
+            self.events.warning(
+                "The previous report is for another table or partition filter; listing the whole table",
+            );
+        }
+        let mut all_objects = if previous.is_some() || self.config.inventory.is_some() {
+            self.s3_client
+                .list_objects(&self.s3_client.table_key("metadata/"))
+                .await?
+        } else {
+            self.s3_client
+                .list_table_objects(
+                    &["metadata/"],
+                    "data/",
+                    &self.config.partition_filter,
+                    &deadline,
+                )
+                .await?
+        };
+
+        self.events.progress("reading_metadata");
 
         // Find the current metadata.json file
         let metadata_file = self.find_current_metadata(&all_objects)?;
@@ -48,6 +267,60 @@ impl IcebergAnalyzer {
         // Analyze manifests to find referenced files
         let referenced_files = self.find_referenced_files(&manifest_list).await?;
 
+        // Manifests record absolute file URIs
+        let bucket_uri = format!("s3://{}/", self.s3_client.get_bucket());
+        let mut warm_start = crate::warm_start::partition_digests(
+            referenced_files
+                .iter()
+                .filter(|(_, history)| history.deleted_snapshot_id.is_none())
+                .filter_map(|(uri, _)| uri.strip_prefix(&bucket_uri))
+                .map(|key| crate::s3_client::relative_key(self.s3_client.get_prefix(), key)),
+        );
+        if let Some(ref location) = self.config.inventory {
+            self.events.progress("reading_inventory");
+            let (data_objects, source) = crate::inventory::data_objects(
+                &self.s3_client,
+                location,
+                &["metadata/"],
+                &self.config,
+            )
+            .await?;
+            all_objects.extend(data_objects);
+            report.inventory = Some(source);
+        } else if let Some(previous) = previous {
+            self.events.progress("listing_changed_partitions");
+            match crate::warm_start::list_data_objects(
+                &self.s3_client,
+                previous,
+                &mut warm_start,
+                &["metadata/"],
+                &self.config,
+            )
+            .await?
+            {
+                Some(data_objects) => all_objects.extend(data_objects),
+                None => {
+                    self.events.warning(
+                        "The previous report's file paths are redacted; listing the whole table",
+                    );
+                    all_objects = self
+                        .s3_client
+                        .list_table_objects(
+                            &["metadata/"],
+                            "data/",
+                            &self.config.partition_filter,
+                            &deadline,
+                        )
+                        .await?;
+                }
+            }
+        }
+
+        // Deterministic runs see the objects in key order however they were gathered
+        if self.config.deterministic {
+            all_objects.sort_by(|a, b| a.key.cmp(&b.key));
+        }
+
         // Separate data files from metadata files
         let (data_files, metadata_files) = self.categorize_files(&all_objects)?;
 
@@ -55,18 +328,27 @@ impl IcebergAnalyzer {
         let mut metrics = HealthMetrics::new();
         metrics.total_files = data_files.len();
         metrics.total_size_bytes = data_files.iter().map(|f| f.size as u64).sum();
+        if self.config.adaptive_thresholds {
+            metrics.size_class = Some(crate::types::SizeClass::of(metrics.total_size_bytes));
+        }
+        self.events
+            .metric("total_files", metrics.total_files as f64);
+        self.events
+            .metric("total_size_bytes", metrics.total_size_bytes as f64);
 
         // Find unreferenced files
-        let referenced_set: HashSet<String> = referenced_files.into_iter().collect();
+        // Manifests record absolute file URIs
         for file in &data_files {
             let file_path = format!("{}/{}", self.s3_client.get_prefix(), file.key);
-            if !referenced_set.contains(&file_path) {
-                metrics.unreferenced_files.push(FileInfo {
-                    path: file_path,
-                    size_bytes: file.size as u64,
-                    last_modified: file.last_modified.clone(),
-                    is_referenced: false,
-                });
+            let file_uri = format!("s3://{}/{}", self.s3_client.get_bucket(), file.key);
+            if !referenced_files.contains_key(&file_uri) {
+                metrics.unreferenced_files.push(FileInfo::new(
+                    file_path,
+                    file.size as u64,
+                    file.last_modified,
+                    false,
+                    self.analysis_time,
+                ));
             }
         }
 
@@ -76,8 +358,261 @@ impl IcebergAnalyzer {
             .map(|f| f.size_bytes)
             .sum();
 
+        // Recent unreferenced files are likely uncommitted writes still in flight
+        metrics.calculate_in_flight_unreferenced(self.config.unreferenced_grace_period_hours);
+        self.events.metric(
+            "unreferenced_files",
+            metrics.unreferenced_files.len() as f64,
+        );
+
+        // Live files whose size disagrees with their manifest entries
+        metrics.log_consistency = Some(check_manifest_sizes(
+            &data_files,
+            &referenced_files,
+            &bucket_uri,
+        ));
+
         // Analyze partitioning and clustering
-        self.analyze_partitioning_and_clustering(&data_files, &metadata, &mut metrics)?;
+        phases.check(
+            "partitioning",
+            self.analyze_partitioning_and_clustering(
+                &data_files,
+                &metadata,
+                &referenced_files,
+                &mut metrics,
+            ),
+        )?;
+        self.events
+            .metric("partition_count", metrics.partition_count as f64);
+        metrics.calculate_partition_anomalies(self.analysis_time);
+
+        // Expose the current schema alongside the health data
+        report.current_schema = Some(current_schema(&metadata));
+
+        // Format version, v1 leftovers and the impact of upgrading to v2
+        metrics.format_version = Some(crate::format_version::audit(&metadata));
+
+        // Table properties against the best-practice ruleset
+        metrics.table_properties = Some(crate::table_properties::audit(
+            "iceberg",
+            metadata.get("properties"),
+            "...",
+        ));
+
+        // Check path-derived partition depth against the current partition spec
+        // (object-storage layouts hash file locations, so paths carry no partition info)
+        let object_storage_layout = metadata
+            .get("properties")
+            .and_then(|props| props.get("write.object-storage.enabled"))
+            .and_then(|v| v.as_str())
+            == Some("true");
+        if !object_storage_layout {
+            let partition_fields = current_partition_fields(&metadata);
+            metrics.calculate_partition_spec_mismatch(
+                &data_files,
+                self.s3_client.get_prefix(),
+                &partition_fields,
+            );
+        }
+
+        // Aggregate when files were added and which are tombstoned
+        metrics.calculate_file_attribution();
+
+        // Deep scan: inspect Parquet footers of a sample of data files, unless the previous
+        // report's sample still describes the table
+        let reusable_footers = crate::warm_start::reusable_footers(previous, &warm_start);
+        if self.config.deep_scan && reusable_footers.is_some() {
+            metrics.parquet_footers = reusable_footers;
+            warm_start.reused_footer_sample = true;
+        } else if self.config.deep_scan {
+            self.events.progress("scanning_footers");
+            let sampled = deadline
+                .run(
+                    "scanning_footers",
+                    crate::parquet_footer::sample_footers(
+                        &self.s3_client,
+                        &data_files,
+                        self.config.footer_sample_size,
+                    ),
+                )
+                .await;
+            if let Some((footers, failed_files)) = sampled {
+                if failed_files > 0 {
+                    self.events.warning(format!(
+                        "Could not read the Parquet footers of {} sampled files",
+                        failed_files
+                    ));
+                }
+                metrics.parquet_footers = crate::parquet_footer::summarize_footers(
+                    &footers,
+                    failed_files,
+                    metrics.total_size_bytes,
+                );
+            }
+        }
+
+        // Bytes by storage class, and live files that were archived
+        metrics.storage_classes = Some(StorageClassMetrics::from_files(
+            &data_files,
+            |file| {
+                referenced_files
+                    .get(&format!("{}{}", bucket_uri, file.key))
+                    .is_some_and(|history| history.deleted_snapshot_id.is_none())
+            },
+            self.analysis_time,
+        ));
+
+        // Whether archived live files can be read now or wait on a restore
+        if let Some(ref mut storage_classes) = metrics.storage_classes {
+            if !storage_classes.archived_live_paths.is_empty() {
+                self.events.progress("checking_restore_status");
+                storage_classes.restore_status = deadline
+                    .run(
+                        "checking_restore_status",
+                        crate::existence::check_restore_status(
+                            storage_classes.archived_live_paths.clone(),
+                            |key| async move { self.s3_client.restore_header(&key).await },
+                        ),
+                    )
+                    .await;
+            }
+        }
+
+        // Parquet, ORC and Avro data files, per table and partition
+        metrics.file_formats = FileFormatMetrics::from_files(
+            &data_files,
+            self.s3_client.get_prefix(),
+            &["parquet", "orc", "avro"],
+        );
+
+        // Checksums, temporary files and empty files among the table's data
+        let table_files: Vec<&crate::s3_client::ObjectInfo> = all_objects
+            .iter()
+            .filter(|obj| {
+                !crate::s3_client::relative_key(self.s3_client.get_prefix(), &obj.key)
+                    .starts_with("metadata/")
+            })
+            .collect();
+        metrics.file_naming =
+            FileNamingMetrics::from_files(&table_files, self.s3_client.get_prefix());
+
+        // Empty and truncated data files, split by whether the current snapshot references them
+        let is_live = |file: &crate::s3_client::ObjectInfo| {
+            referenced_files
+                .get(&format!("{}{}", bucket_uri, file.key))
+                .is_some_and(|history| history.deleted_snapshot_id.is_none())
+        };
+        metrics.file_integrity = FileIntegrityMetrics::from_files(&data_files, is_live);
+
+        // The same content (size and ETag) stored under several keys
+        metrics.duplicate_files = DuplicateFileMetrics::from_files(&data_files, is_live);
+
+        // Deep scan: stripe layout and statistics of a sample of ORC data files
+        let has_orc_files = metrics
+            .file_formats
+            .as_ref()
+            .is_some_and(|formats| formats.files_by_format.contains_key("orc"));
+        if self.config.deep_scan && has_orc_files {
+            self.events.progress("scanning_footers");
+            let sampled = deadline
+                .run(
+                    "scanning_footers",
+                    crate::orc_footer::sample_footers(
+                        &self.s3_client,
+                        &data_files,
+                        self.config.footer_sample_size,
+                    ),
+                )
+                .await;
+            if let Some((footers, failed_files)) = sampled {
+                if failed_files > 0 {
+                    self.events.warning(format!(
+                        "Could not read the ORC footers of {} sampled files",
+                        failed_files
+                    ));
+                }
+                metrics.orc_footers = crate::orc_footer::summarize_footers(&footers, failed_files);
+            }
+        }
+
+        // HEAD every live file, including those in partitions the listing skipped
+        if self.config.verify_referenced_files {
+            self.events.progress("checking_referenced_files");
+            let keys = referenced_files
+                .iter()
+                .filter(|(_, history)| history.deleted_snapshot_id.is_none())
+                .filter_map(|(uri, _)| uri.strip_prefix(&bucket_uri))
+                .filter(|key| {
+                    self.config
+                        .partition_filter
+                        .matches(crate::s3_client::relative_key(
+                            self.s3_client.get_prefix(),
+                            key,
+                        ))
+                })
+                .map(str::to_string)
+                .collect();
+            metrics.existence_check = deadline
+                .run(
+                    "checking_referenced_files",
+                    crate::existence::check_keys(keys, |key| async move {
+                        self.s3_client.head_object(&key).await
+                    }),
+                )
+                .await;
+        }
+
+        // Incomplete multipart uploads under the table, which no listing shows
+        if self.config.check_multipart_uploads {
+            self.events.progress("listing_multipart_uploads");
+            metrics.incomplete_uploads = deadline
+                .run(
+                    "listing_multipart_uploads",
+                    crate::multipart::check_uploads(&self.s3_client, self.analysis_time),
+                )
+                .await
+                .map(|checked| phases.check("multipart_uploads", checked))
+                .transpose()?
+                .flatten();
+        }
+
+        // Join S3 access logs against the data files to find cold and hot data
+        if let Some(ref location) = self.config.access_logs {
+            self.events.progress("reading_access_logs");
+            let loaded = deadline
+                .run(
+                    "reading_access_logs",
+                    crate::access_logs::load_access_records(&self.s3_client, location),
+                )
+                .await
+                .map(|loaded| phases.check("access_logs", loaded))
+                .transpose()?
+                .flatten();
+            if let Some((records, log_objects_read, truncated)) = loaded {
+                if truncated {
+                    self.events.warning(
+                        "Access logs were truncated to the newest log objects; older reads are not counted",
+                    );
+                }
+                metrics.access_patterns = Some(crate::access_logs::summarize_access(
+                    &records,
+                    &data_files,
+                    self.s3_client.get_bucket(),
+                    self.s3_client.get_prefix(),
+                    log_objects_read,
+                    truncated,
+                ));
+            }
+        }
+
+        // Compare the catalog-registered schema with the metadata and sampled files
+        if let (Some(catalog_schema), Some(physical_schema)) =
+            (&self.catalog_schema, &report.current_schema)
+        {
+            metrics.calculate_schema_drift(catalog_schema, physical_schema);
+        }
+
+        self.events.progress("computing_metrics");
 
         // Calculate file size distribution
         self.calculate_file_size_distribution(&data_files, &mut metrics);
@@ -90,37 +625,190 @@ impl IcebergAnalyzer {
 
         // Calculate additional health metrics
         metrics.calculate_data_skew();
+
+        // Partition statistics: row counts per partition straight from the table when they
+        // describe the current snapshot, and how stale they are otherwise
+        if let Some((snapshot_id, path)) = crate::partition_stats::latest_statistics_file(&metadata)
+        {
+            self.events.progress("reading_partition_stats");
+            let key = path.strip_prefix(&bucket_uri).unwrap_or(&path);
+            match deadline
+                .run("reading_partition_stats", self.s3_client.get_object(key))
+                .await
+                .map(|content| content.and_then(crate::partition_stats::parse))
+            {
+                None => {}
+                Some(Ok(stats)) => {
+                    let summary =
+                        crate::partition_stats::summarize(&stats, snapshot_id, path, &metadata);
+                    if summary.snapshots_behind == 0 {
+                        let partition_rows: Vec<u64> =
+                            stats.iter().map(|p| p.data_record_count).collect();
+                        metrics.apply_partition_row_counts(&partition_rows);
+                    }
+                    metrics.partition_statistics = Some(summary);
+                }
+                Some(Err(e)) => self.events.warning(format!(
+                    "Could not read the partition statistics file {}: {}",
+                    path, e
+                )),
+            }
+        }
+        metrics.calculate_partitioning_advice();
         let metadata_files_owned: Vec<crate::s3_client::ObjectInfo> =
             metadata_files.iter().map(|f| (*f).clone()).collect();
         metrics.calculate_metadata_health(&metadata_files_owned);
         metrics.calculate_snapshot_health(metadata_files.len()); // Simplified: use metadata file count as snapshot count
 
+        // Monthly storage cost of live data, time-travel history, orphans and metadata
+        metrics.cost = Some(CostMetrics::from_files(
+            &data_files,
+            |file| match referenced_files.get(&format!("{}{}", bucket_uri, file.key)) {
+                Some(history) if history.deleted_snapshot_id.is_none() => CostCategory::Live,
+                Some(_) => CostCategory::History,
+                None => CostCategory::Orphan,
+            },
+            metrics.metadata_health.metadata_total_size_bytes,
+            &self.config.pricing,
+        ));
+
+        // How recently the table was committed to and written
+        let snapshot_times = metadata
+            .get("snapshots")
+            .and_then(|s| s.as_array())
+            .map(|snapshots| {
+                snapshots
+                    .iter()
+                    .filter_map(|snapshot| snapshot.get("timestamp-ms")?.as_i64())
+                    .filter_map(chrono::DateTime::from_timestamp_millis)
+                    .collect()
+            })
+            .unwrap_or_default();
+        metrics.freshness = Some(FreshnessMetrics::from_commit_times(
+            snapshot_times,
+            data_files.iter().filter_map(|f| f.last_modified).max(),
+            self.analysis_time,
+            self.config.freshness_sla_hours,
+        ));
+
         // Analyze deletion vectors (Iceberg v3+)
-        metrics.deletion_vector_metrics = self
-            .analyze_deletion_vectors(&manifest_list, &metadata)
-            .await?;
+        metrics.deletion_vector_metrics = phases
+            .check(
+                "deletion_vectors",
+                self.analyze_deletion_vectors(&manifest_list, &metadata)
+                    .await,
+            )?
+            .flatten();
 
         // Analyze schema evolution
-        metrics.schema_evolution = self.analyze_schema_evolution(&metadata_files).await?;
+        metrics.schema_evolution = phases
+            .check(
+                "schema_evolution",
+                self.analyze_schema_evolution(&metadata_files).await,
+            )?
+            .flatten();
+        if let Some(ref mut schema_evolution) = metrics.schema_evolution {
+            schema_evolution.schema_changes = schema_change_log(&metadata);
+        }
 
         // Analyze time travel storage costs
-        metrics.time_travel_metrics = self.analyze_time_travel(&metadata_files).await?;
+        metrics.time_travel_metrics = phases
+            .check(
+                "time_travel",
+                self.analyze_time_travel(&metadata_files).await,
+            )?
+            .flatten();
 
         // Analyze table constraints
-        metrics.table_constraints = self.analyze_table_constraints(&metadata_files).await?;
+        metrics.table_constraints = phases
+            .check(
+                "table_constraints",
+                self.analyze_table_constraints(&metadata_files).await,
+            )?
+            .flatten();
 
         // Analyze file compaction opportunities
-        metrics.file_compaction = self
-            .analyze_file_compaction(&data_files, &metadata_files)
-            .await?;
+        let buckets = self.config.file_size_buckets(metrics.size_class.as_ref());
+        let z_order_candidates = {
+            let names: HashMap<String, String> = current_schema(&metadata)
+                .columns
+                .into_iter()
+                .filter_map(|column| Some((column.field_id?.to_string(), column.name)))
+                .collect();
+            let bounds: Vec<ColumnBounds> = data_files
+                .iter()
+                .filter_map(|file| referenced_files.get(&format!("{}{}", bucket_uri, file.key)))
+                .filter(|history| history.deleted_snapshot_id.is_none())
+                .map(|history| {
+                    history
+                        .bounds
+                        .iter()
+                        .map(|(id, range)| (names.get(id).unwrap_or(id).clone(), range.clone()))
+                        .collect()
+                })
+                .collect();
+            rank_candidates(&bounds)
+        };
+        metrics.file_compaction = phases
+            .check(
+                "file_compaction",
+                self.analyze_file_compaction(
+                    &data_files,
+                    &metadata_files,
+                    &buckets,
+                    z_order_candidates,
+                )
+                .await,
+            )?
+            .flatten();
+
+        self.events.progress("scoring");
+
+        // Check file counts against what the engine can plan
+        metrics.engine_limits =
+            crate::engine_limits::check_engine_limits(&metrics, &self.config.engine);
 
         // Generate recommendations
         self.generate_recommendations(&mut metrics);
+        metrics.remediation_estimates =
+            crate::remediation::estimate_remediations(&metrics, "iceberg", &self.config.engine);
 
         // Calculate health score
         metrics.health_score = metrics.calculate_health_score();
+        self.events.metric("health_score", metrics.health_score);
+        metrics.sub_scores = Some(metrics.calculate_sub_scores());
         report.metrics = metrics;
         report.health_score = report.metrics.health_score;
+        crate::custom_checks::run(
+            &self.events,
+            &mut report,
+            self.s3_client.get_prefix(),
+            &data_files,
+            || crate::custom_checks::iceberg_snapshots(&metadata),
+        );
+        if let Some(ref policy) = self.config.policy {
+            report.compliance = Some(policy.evaluate(&report));
+        }
+        report.failed_phases = phases.into_failures();
+        // A partial listing can't seed a warm start
+        report.truncated = deadline.truncation();
+        match report.truncated {
+            Some(ref truncation) => self.events.warning(format!(
+                "Analysis hit max_duration_seconds={}; cut short: {}",
+                truncation.max_duration_seconds,
+                truncation.stages.join(", ")
+            )),
+            None => report.warm_start = Some(warm_start),
+        }
+        report.analysis_stats = Some(AnalysisStats::new(
+            &self.s3_client.requests,
+            started.elapsed(),
+            &self.config.pricing,
+        ));
+        report.apply_result_limits(&self.config.result_limits);
+        if self.config.deterministic {
+            report.make_deterministic();
+        }
 
         Ok(report)
     }
@@ -136,17 +824,16 @@ impl IcebergAnalyzer {
             .collect();
 
         if metadata_files.is_empty() {
-            return Err(anyhow::anyhow!("No metadata.json file found"));
+            return Err(DrainageError::NotFound(
+                crate::error::ErrorContext::new("No metadata.json file found")
+                    .with_key(&self.s3_client.table_key("metadata/")),
+            )
+            .into());
         }
 
         // Sort by last modified time and take the most recent
         let mut sorted_files = metadata_files;
-        sorted_files.sort_by(|a, b| {
-            b.last_modified
-                .as_ref()
-                .unwrap_or(&"".to_string())
-                .cmp(a.last_modified.as_ref().unwrap_or(&"".to_string()))
-        });
+        sorted_files.sort_by_key(|f| std::cmp::Reverse(f.last_modified));
 
         Ok(sorted_files[0])
     }
@@ -182,8 +869,12 @@ impl IcebergAnalyzer {
         Ok(manifest_list)
     }
 
-    async fn find_referenced_files(&self, manifest_list: &[String]) -> Result<Vec<String>> {
-        let mut referenced_files = Vec::new();
+    /// Data file paths referenced by the manifests, with the snapshots that added and deleted them
+    async fn find_referenced_files(
+        &self,
+        manifest_list: &[String],
+    ) -> Result<HashMap<String, ManifestEntryHistory>> {
+        let mut referenced_files: HashMap<String, ManifestEntryHistory> = HashMap::new();
 
         for manifest_path in manifest_list {
             let content = self.s3_client.get_object(manifest_path).await?;
@@ -195,7 +886,36 @@ impl IcebergAnalyzer {
                         if let Some(data_file) = entry.get("data-file") {
                             if let Some(file_path) = data_file.get("file-path") {
                                 if let Some(path_str) = file_path.as_str() {
-                                    referenced_files.push(path_str.to_string());
+                                    let snapshot_id = entry
+                                        .get("snapshot-id")
+                                        .or_else(|| entry.get("snapshot_id"))
+                                        .and_then(|id| id.as_i64());
+                                    let history =
+                                        referenced_files.entry(path_str.to_string()).or_default();
+                                    if let Some(rows) = data_file
+                                        .get("record-count")
+                                        .or_else(|| data_file.get("record_count"))
+                                        .and_then(|r| r.as_u64())
+                                    {
+                                        history.record_count = Some(rows);
+                                    }
+                                    if let Some(size) = data_file
+                                        .get("file-size-in-bytes")
+                                        .or_else(|| data_file.get("file_size_in_bytes"))
+                                        .and_then(|s| s.as_u64())
+                                    {
+                                        history.file_size_in_bytes = Some(size);
+                                    }
+                                    let bounds = iceberg_bounds(data_file);
+                                    if !bounds.is_empty() {
+                                        history.bounds = bounds;
+                                    }
+                                    // Status 2 marks the entry as deleted by that snapshot
+                                    if entry.get("status").and_then(|s| s.as_i64()) == Some(2) {
+                                        history.deleted_snapshot_id = snapshot_id;
+                                    } else {
+                                        history.added_snapshot_id = snapshot_id;
+                                    }
                                 }
                             }
                         }
@@ -218,7 +938,10 @@ impl IcebergAnalyzer {
         let mut metadata_files = Vec::new();
 
         for obj in objects {
-            if obj.key.ends_with(".parquet") {
+            // Manifests and manifest lists are Avro files too
+            let in_metadata = crate::s3_client::relative_key(self.s3_client.get_prefix(), &obj.key)
+                .starts_with("metadata/");
+            if !in_metadata && FileFormatMetrics::format_of(&obj.key).is_some() {
                 data_files.push(obj);
             } else if obj.key.contains("metadata.json") || obj.key.contains("manifest") {
                 metadata_files.push(obj);
@@ -232,8 +955,25 @@ impl IcebergAnalyzer {
         &self,
         data_files: &[&crate::s3_client::ObjectInfo],
         metadata: &Value,
+        referenced_files: &HashMap<String, ManifestEntryHistory>,
         metrics: &mut HealthMetrics,
     ) -> Result<()> {
+        // Commit time of each snapshot, used as the write time of the files it added
+        let snapshot_times: HashMap<i64, chrono::DateTime<chrono::Utc>> = metadata
+            .get("snapshots")
+            .and_then(|s| s.as_array())
+            .map(|snapshots| {
+                snapshots
+                    .iter()
+                    .filter_map(|snapshot| {
+                        let id = snapshot.get("snapshot-id")?.as_i64()?;
+                        let timestamp = snapshot.get("timestamp-ms")?.as_i64()?;
+                        Some((id, chrono::DateTime::from_timestamp_millis(timestamp)?))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
         // Extract partition spec from metadata
         let _partition_spec = metadata
             .get("partition-spec")
@@ -280,12 +1020,25 @@ impl IcebergAnalyzer {
 
             partition_info.file_count += 1;
             partition_info.total_size_bytes += file.size as u64;
-            partition_info.files.push(FileInfo {
-                path: format!("{}/{}", self.s3_client.get_prefix(), file.key),
-                size_bytes: file.size as u64,
-                last_modified: file.last_modified.clone(),
-                is_referenced: true, // We'll update this later
-            });
+            let mut file_info = FileInfo::new(
+                format!("{}/{}", self.s3_client.get_prefix(), file.key),
+                file.size as u64,
+                file.last_modified,
+                true, // We'll update this later
+                self.analysis_time,
+            );
+            file_info.storage_class = file.storage_class.clone();
+            // Manifests record absolute file URIs
+            let file_uri = format!("s3://{}/{}", self.s3_client.get_bucket(), file.key);
+            if let Some(history) = referenced_files.get(&file_uri) {
+                file_info.commit_id = history.added_snapshot_id;
+                file_info.created = history
+                    .added_snapshot_id
+                    .and_then(|id| snapshot_times.get(&id).copied());
+                file_info.removed_commit_id = history.deleted_snapshot_id;
+                file_info.record_count = history.record_count;
+            }
+            partition_info.files.push(file_info);
         }
 
         // Calculate averages for each partition
@@ -347,19 +1100,16 @@ impl IcebergAnalyzer {
         data_files: &[&crate::s3_client::ObjectInfo],
         metrics: &mut HealthMetrics,
     ) {
+        let buckets = &self.config.file_size_buckets(metrics.size_class.as_ref());
         for file in data_files {
-            let size_mb = file.size as f64 / (1024.0 * 1024.0);
-
-            if size_mb < 16.0 {
-                metrics.file_size_distribution.small_files += 1;
-            } else if size_mb < 128.0 {
-                metrics.file_size_distribution.medium_files += 1;
-            } else if size_mb < 1024.0 {
-                metrics.file_size_distribution.large_files += 1;
-            } else {
-                metrics.file_size_distribution.very_large_files += 1;
-            }
+            metrics
+                .file_size_distribution
+                .add(file.size as u64, buckets);
         }
+        metrics.file_size_histogram = crate::types::FileSizeHistogram::from_sizes(
+            data_files.iter().map(|f| f.size as u64).collect(),
+            buckets,
+        );
     }
 
     fn generate_recommendations(&self, metrics: &mut HealthMetrics) {
@@ -380,18 +1130,30 @@ impl IcebergAnalyzer {
         }
 
         if !incomplete_sections.is_empty() {
+            self.events.warning(format!(
+                "Could not analyze {}: metadata files missing or inaccessible",
+                incomplete_sections.join(", ")
+            ));
             metrics.recommendations.push(format!(
                 "⚠️  Analysis incomplete: {} sections could not be analyzed due to missing/inaccessible metadata files (common in actively updated tables). Basic metrics are still accurate.",
                 incomplete_sections.join(", ")
             ));
         }
 
-        // Check for unreferenced files
-        if !metrics.unreferenced_files.is_empty() {
+        // Check for unreferenced files, ignoring recent ones that may still be committed
+        let stale_unreferenced =
+            metrics.unreferenced_files.len() - metrics.in_flight_unreferenced_files;
+        if stale_unreferenced > 0 {
+            let stale_bytes =
+                metrics.unreferenced_size_bytes - metrics.in_flight_unreferenced_size_bytes;
+            let cost = metrics.cost.as_ref().map_or(String::new(), |cost| {
+                format!(", about ${:.2}/month", cost.orphan_cost(stale_bytes))
+            });
             metrics.recommendations.push(format!(
-                "Found {} unreferenced files ({} bytes). Consider running VACUUM to clean up orphaned data files.",
-                metrics.unreferenced_files.len(),
-                metrics.unreferenced_size_bytes
+                "Found {} unreferenced files ({} bytes{}). Consider running VACUUM to clean up orphaned data files.",
+                stale_unreferenced,
+                stale_bytes,
+                cost
             ));
         }
 
@@ -429,6 +1191,163 @@ impl IcebergAnalyzer {
             }
         }
 
+        // Check partition directory layout against the partition spec
+        if let Some(ref mismatch) = metrics.partition_spec_mismatch {
+            metrics.recommendations.push(format!(
+                "Found {} data files whose partition directories don't match the current partition spec [{}]. These usually come from manual copies or misconfigured writers and can break path-based readers; rewrite or remove them.",
+                mismatch.mismatched_file_count,
+                mismatch.expected_partition_columns.join(", ")
+            ));
+        }
+
+        // Check for drift between the catalog and the physical schema
+        if let Some(ref drift) = metrics.schema_drift {
+            metrics.recommendations.extend(drift.recommendations());
+        }
+
+        // Check the partition layout against the data volume
+        if let Some(recommendation) = metrics
+            .partitioning_advice
+            .as_ref()
+            .and_then(|advice| advice.recommendation())
+        {
+            metrics.recommendations.push(recommendation);
+        }
+
+        // Check freshness against the SLA
+        if let Some(recommendation) = metrics
+            .freshness
+            .as_ref()
+            .and_then(|freshness| freshness.recommendation())
+        {
+            metrics.recommendations.push(recommendation);
+        }
+
+        // Check what time-travel history costs
+        if let Some(recommendation) = metrics.cost.as_ref().and_then(|cost| cost.recommendation()) {
+            metrics.recommendations.push(recommendation);
+        }
+
+        // Check storage classes for archived live data and tiering candidates
+        if let Some(ref storage_classes) = metrics.storage_classes {
+            metrics
+                .recommendations
+                .extend(storage_classes.recommendations());
+        }
+
+        // Check for data files in more than one format
+        if let Some(ref file_formats) = metrics.file_formats {
+            metrics
+                .recommendations
+                .extend(file_formats.recommendations("iceberg"));
+        }
+
+        // Check for leftover, empty and misnamed files in data paths
+        if let Some(ref file_naming) = metrics.file_naming {
+            metrics
+                .recommendations
+                .extend(file_naming.recommendations());
+        }
+
+        // Check for data files committed by interrupted writes
+        if let Some(ref file_integrity) = metrics.file_integrity {
+            metrics
+                .recommendations
+                .extend(file_integrity.recommendations("iceberg"));
+        }
+
+        // Check for data files stored more than once
+        if let Some(ref duplicate_files) = metrics.duplicate_files {
+            metrics
+                .recommendations
+                .extend(duplicate_files.recommendations("iceberg"));
+        }
+
+        // Check file counts against the engine's per-query limit
+        if let Some(ref limits) = metrics.engine_limits {
+            let recommendations =
+                crate::engine_limits::engine_limit_recommendations(limits, "iceberg");
+            metrics.recommendations.extend(recommendations);
+        }
+
+        // Check live files against their manifest entries
+        if let Some(recommendation) = metrics
+            .log_consistency
+            .as_ref()
+            .and_then(|log_consistency| log_consistency.recommendation())
+        {
+            metrics.recommendations.push(recommendation);
+        }
+
+        // Check for abandoned multipart uploads
+        if let Some(ref incomplete_uploads) = metrics.incomplete_uploads {
+            metrics
+                .recommendations
+                .extend(incomplete_uploads.recommendations());
+        }
+
+        // Check that referenced files exist
+        if let Some(ref existence_check) = metrics.existence_check {
+            metrics
+                .recommendations
+                .extend(existence_check.recommendations("iceberg"));
+        }
+
+        // Check table properties against best practices
+        if let Some(ref table_properties) = metrics.table_properties {
+            metrics
+                .recommendations
+                .extend(table_properties.recommendations());
+        }
+
+        // Check the format version and copy-on-write rewrites
+        if let Some(ref format_version) = metrics.format_version {
+            metrics
+                .recommendations
+                .extend(format_version.recommendations());
+        }
+
+        // Check that partition statistics are current
+        if let Some(ref partition_statistics) = metrics.partition_statistics {
+            if let Some(recommendation) = partition_statistics.recommendation() {
+                metrics.recommendations.push(recommendation);
+            }
+        }
+
+        // Check access patterns for cold and hot data
+        if let Some(ref access_patterns) = metrics.access_patterns {
+            metrics
+                .recommendations
+                .extend(access_patterns.recommendations(metrics.total_size_bytes));
+        }
+
+        // Check date partitions for gaps, future dates and null partitions
+        if let Some(ref anomalies) = metrics.partition_anomalies {
+            let recommendations = anomalies.recommendations();
+            metrics.recommendations.extend(recommendations);
+        }
+
+        // Check Parquet footers from a deep scan
+        if let Some(ref footers) = metrics.parquet_footers {
+            let clustering_columns = metrics
+                .clustering
+                .as_ref()
+                .map(|c| c.clustering_columns.as_slice())
+                .unwrap_or(&[]);
+            let recommendations = crate::parquet_footer::footer_recommendations(
+                footers,
+                metrics.total_size_bytes,
+                clustering_columns,
+            );
+            metrics.recommendations.extend(recommendations);
+        }
+
+        // Check ORC footers from a deep scan
+        if let Some(ref footers) = metrics.orc_footers {
+            let recommendations = crate::orc_footer::footer_recommendations(footers);
+            metrics.recommendations.extend(recommendations);
+        }
+
         // Check clustering
         if let Some(ref clustering) = metrics.clustering {
             if clustering.avg_files_per_cluster > 50.0 {
@@ -453,6 +1372,9 @@ impl IcebergAnalyzer {
         }
 
         // Check data skew
+        if let Some(recommendation) = metrics.data_skew.row_skew_recommendation() {
+            metrics.recommendations.push(recommendation);
+        }
         if metrics.data_skew.partition_skew_score > 0.5 {
             metrics.recommendations.push(
                 "High partition skew detected. Consider repartitioning to balance data distribution.".to_string()
@@ -466,8 +1388,8 @@ impl IcebergAnalyzer {
         }
 
         // Check metadata health
-        if metrics.metadata_health.metadata_total_size_bytes > 50 * 1024 * 1024 {
-            // > 50MB
+        if metrics.metadata_health.metadata_total_size_bytes > metrics.metadata_bloat_bytes() / 2 {
+            // Half the bloat limit of the table's size class, 50MB by default
             metrics.recommendations.push(
                 "Large metadata size detected. Consider running expire_snapshots to clean up old metadata.".to_string()
             );
@@ -640,7 +1562,7 @@ impl IcebergAnalyzer {
                                 // Parse creation time for age calculation
                                 if let Some(timestamp) = deletion_file.get("file_sequence_number") {
                                     let creation_time = timestamp.as_u64().unwrap_or(0) as i64;
-                                    let age_days = (chrono::Utc::now().timestamp() - creation_time)
+                                    let age_days = (self.analysis_time.timestamp() - creation_time)
                                         as f64
                                         / 86400.0;
                                     oldest_dv_age = oldest_dv_age.max(age_days);
@@ -735,7 +1657,7 @@ impl IcebergAnalyzer {
             // Check for schema changes in metadata
             if let Some(schema) = metadata.get("schema") {
                 let is_breaking = self.is_breaking_change(&schema_changes, schema);
-                schema_changes.push(SchemaChange {
+                schema_changes.push(SchemaSnapshot {
                     version: current_version,
                     timestamp: metadata
                         .get("timestamp_ms")
@@ -750,7 +1672,7 @@ impl IcebergAnalyzer {
             if let Some(schema_id) = metadata.get("schema-id") {
                 let new_schema_id = schema_id.as_u64().unwrap_or(0);
                 if new_schema_id > current_version {
-                    schema_changes.push(SchemaChange {
+                    schema_changes.push(SchemaSnapshot {
                         version: current_version,
                         timestamp: metadata
                             .get("timestamp_ms")
@@ -772,7 +1694,7 @@ impl IcebergAnalyzer {
         self.calculate_schema_metrics(schema_changes, current_version)
     }
 
-    fn is_breaking_change(&self, previous_changes: &[SchemaChange], new_schema: &Value) -> bool {
+    fn is_breaking_change(&self, previous_changes: &[SchemaSnapshot], new_schema: &Value) -> bool {
         if previous_changes.is_empty() {
             return false;
         }
@@ -856,7 +1778,7 @@ impl IcebergAnalyzer {
 
     fn calculate_schema_metrics(
         &self,
-        changes: Vec<SchemaChange>,
+        changes: Vec<SchemaSnapshot>,
         current_version: u64,
     ) -> Result<Option<crate::types::SchemaEvolutionMetrics>> {
         let total_changes = changes.len();
@@ -864,9 +1786,8 @@ impl IcebergAnalyzer {
         let non_breaking_changes = total_changes - breaking_changes;
 
         // Calculate time-based metrics
-        let now = chrono::Utc::now().timestamp() as u64;
         let days_since_last = if let Some(last_change) = changes.last() {
-            (now - last_change.timestamp / 1000) as f64 / 86400.0
+            crate::types::days_since_commit(self.analysis_time, last_change.timestamp)
         } else {
             365.0 // No changes in a year = very stable
         };
@@ -875,7 +1796,7 @@ impl IcebergAnalyzer {
         let total_days = if changes.len() > 1 {
             let first_change = changes.first().unwrap().timestamp / 1000;
             let last_change = changes.last().unwrap().timestamp / 1000;
-            ((last_change - first_change) as f64 / 86400.0).max(1.0_f64)
+            (last_change.saturating_sub(first_change) as f64 / 86400.0).max(1.0_f64)
         } else {
             1.0
         };
@@ -898,6 +1819,7 @@ impl IcebergAnalyzer {
             days_since_last_change: days_since_last,
             schema_change_frequency: change_frequency,
             current_schema_version: current_version,
+            schema_changes: Vec::new(),
         }))
     }
 
@@ -956,7 +1878,7 @@ impl IcebergAnalyzer {
     ) -> Result<Option<crate::types::TimeTravelMetrics>> {
         let mut total_snapshots = 0;
         let mut total_historical_size = 0u64;
-        let mut oldest_timestamp = chrono::Utc::now().timestamp() as u64;
+        let mut oldest_timestamp = self.analysis_time.timestamp_millis() as u64;
         let mut newest_timestamp = 0u64;
 
         // Analyze metadata files for time travel storage
@@ -990,9 +1912,8 @@ impl IcebergAnalyzer {
             return Ok(None);
         }
 
-        let now = chrono::Utc::now().timestamp() as u64;
-        let oldest_age_days = (now - oldest_timestamp / 1000) as f64 / 86400.0;
-        let newest_age_days = (now - newest_timestamp / 1000) as f64 / 86400.0;
+        let oldest_age_days = crate::types::days_since_commit(self.analysis_time, oldest_timestamp);
+        let newest_age_days = crate::types::days_since_commit(self.analysis_time, newest_timestamp);
         let avg_snapshot_size = total_historical_size as f64 / total_snapshots as f64;
 
         let storage_cost_impact = self.calculate_storage_cost_impact(
@@ -1014,6 +1935,12 @@ impl IcebergAnalyzer {
             storage_cost_impact_score: storage_cost_impact,
             retention_efficiency_score: retention_efficiency,
             recommended_retention_days: recommended_retention,
+            oldest_snapshot_timestamp: chrono::DateTime::from_timestamp_millis(
+                oldest_timestamp as i64,
+            ),
+            newest_snapshot_timestamp: chrono::DateTime::from_timestamp_millis(
+                newest_timestamp as i64,
+            ),
         }))
     }
 
@@ -1280,6 +2207,8 @@ impl IcebergAnalyzer {
         &self,
         data_files: &[&crate::s3_client::ObjectInfo],
         metadata_files: &[&crate::s3_client::ObjectInfo],
+        buckets: &crate::config::FileSizeBuckets,
+        z_order_candidates: Vec<crate::types::ZOrderCandidate>,
     ) -> Result<Option<crate::types::FileCompactionMetrics>> {
         let mut small_files_count = 0;
         let mut small_files_size = 0u64;
@@ -1289,8 +2218,7 @@ impl IcebergAnalyzer {
         // Analyze file sizes for compaction opportunities
         for file in data_files {
             let file_size = file.size as u64;
-            if file_size < 16 * 1024 * 1024 {
-                // < 16MB
+            if file_size < buckets.small_bytes {
                 small_files_count += 1;
                 small_files_size += file_size;
                 potential_compaction_files += 1;
@@ -1299,7 +2227,8 @@ impl IcebergAnalyzer {
 
         // Calculate potential savings
         if small_files_count > 1 {
-            let target_size = 128 * 1024 * 1024; // 128MB target
+            // Compacted files land in the medium bucket (128MB by default)
+            let target_size = buckets.medium_bytes;
             let files_per_target = (target_size as f64
                 / (small_files_size as f64 / small_files_count as f64))
                 .ceil() as usize;
@@ -1308,6 +2237,17 @@ impl IcebergAnalyzer {
             estimated_savings = small_files_size.saturating_sub(estimated_target_size);
         }
 
+        let table_files: Vec<(&str, u64)> = data_files
+            .iter()
+            .map(|file| {
+                (
+                    crate::s3_client::relative_key(self.s3_client.get_prefix(), &file.key),
+                    file.size as u64,
+                )
+            })
+            .collect();
+        let compaction_plan = crate::types::CompactionTask::plan(&table_files, buckets);
+
         let compaction_opportunity = self.calculate_compaction_opportunity(
             small_files_count,
             small_files_size,
@@ -1316,9 +2256,18 @@ impl IcebergAnalyzer {
         let recommended_target_size = self.calculate_recommended_target_size(data_files);
         let compaction_priority =
             self.calculate_compaction_priority(compaction_opportunity, small_files_count);
-        let (z_order_opportunity, z_order_columns) = self
-            .analyze_iceberg_z_order_opportunity(metadata_files)
-            .await?;
+        // Columns the file statistics point to, else those the table is sorted by
+        let (z_order_opportunity, z_order_columns) = if z_order_candidates.is_empty() {
+            self.analyze_iceberg_z_order_opportunity(metadata_files)
+                .await?
+        } else {
+            let columns = z_order_candidates
+                .iter()
+                .take(MAX_Z_ORDER_COLUMNS)
+                .map(|candidate| candidate.column.clone())
+                .collect();
+            (true, columns)
+        };
 
         Ok(Some(crate::types::FileCompactionMetrics {
             compaction_opportunity_score: compaction_opportunity,
@@ -1330,6 +2279,8 @@ impl IcebergAnalyzer {
             compaction_priority,
             z_order_opportunity,
             z_order_columns,
+            z_order_candidates,
+            compaction_plan,
         }))
     }
 
@@ -1445,3 +2396,82 @@ impl IcebergAnalyzer {
         Ok((false, Vec::new()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_manifest_sizes() {
+        let history = |size: u64, deleted: Option<i64>| ManifestEntryHistory {
+            added_snapshot_id: Some(1),
+            deleted_snapshot_id: deleted,
+            record_count: None,
+            file_size_in_bytes: Some(size),
+            bounds: ColumnBounds::new(),
+        };
+        let referenced_files = HashMap::from([
+            ("s3://lake/t/data/a.parquet".to_string(), history(100, None)),
+            ("s3://lake/t/data/b.parquet".to_string(), history(100, None)),
+            ("s3://lake/t/data/c.parquet".to_string(), history(100, None)),
+            (
+                "s3://lake/t/data/d.parquet".to_string(),
+                history(100, Some(2)),
+            ),
+        ]);
+        let object = |key: &str, size: i64| crate::s3_client::ObjectInfo {
+            key: key.to_string(),
+            size,
+            last_modified: None,
+            etag: None,
+            storage_class: None,
+        };
+        let files = [
+            object("t/data/a.parquet", 100),
+            object("t/data/b.parquet", 40),
+            object("t/data/c.parquet", 180),
+            object("t/data/d.parquet", 10),
+            object("t/data/orphan.parquet", 10),
+        ];
+        let data_files: Vec<&crate::s3_client::ObjectInfo> = files.iter().collect();
+        let metrics = check_manifest_sizes(&data_files, &referenced_files, "s3://lake/");
+
+        assert_eq!(metrics.checked_files, 3);
+        assert_eq!(metrics.size_mismatched_files, 2);
+        assert_eq!(metrics.truncated_files, 1);
+        assert_eq!(metrics.overwritten_files, 1);
+        assert!(!metrics.partitions_from_log);
+        assert!(metrics.recommendation().unwrap().contains("1 smaller"));
+    }
+
+    #[test]
+    fn test_schema_change_log() {
+        let metadata = json!({
+            "schemas": [
+                {"schema-id": 1, "fields": [
+                    {"id": 1, "name": "id", "required": true, "type": "long"},
+                    {"id": 2, "name": "amount", "required": false, "type": "int"}
+                ]},
+                {"schema-id": 0, "fields": [
+                    {"id": 1, "name": "id", "required": true, "type": "long"}
+                ]}
+            ],
+            "snapshots": [
+                {"snapshot-id": 10, "schema-id": 0, "timestamp-ms": 1672531200000i64},
+                {"snapshot-id": 12, "schema-id": 1, "timestamp-ms": 1672704000000i64},
+                {"snapshot-id": 11, "schema-id": 1, "timestamp-ms": 1672617600000i64}
+            ]
+        });
+        let changes = schema_change_log(&metadata);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].version, 1);
+        assert_eq!(changes[0].change_type, "added");
+        assert_eq!(changes[0].column, "amount");
+        assert_eq!(
+            changes[0].timestamp.unwrap().to_rfc3339(),
+            "2023-01-02T00:00:00+00:00"
+        );
+    }
+}
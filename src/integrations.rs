@@ -0,0 +1,189 @@
+//! Orchestrator helpers (`drainage.integrations`): a table's health checked against
+//! thresholds, returned in the shapes Airflow and Dagster gate downstream work on.
+//!
+//! Thresholds are the rules of `notify_rules` (`min_health_score`, `max_orphan_gb`,
+//! `max_small_file_ratio`). `airflow_check` returns a small JSON-serializable dict for XCom;
+//! `dagster_check` returns the keyword arguments of a Dagster `AssetCheckResult`. Both take a
+//! table path, analyzed with `analyze_table` and any of its keyword arguments, or a report.
+
+use crate::notify::{breaches, Breach, NotifyRules};
+use crate::types::HealthReport;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::{json, Value};
+
+/// Recommendations kept in a summary, so it stays small enough for XCom
+const SUMMARY_RECOMMENDATIONS: usize = 3;
+
+fn rules(thresholds: &PyDict) -> PyResult<NotifyRules> {
+    let mut rules = NotifyRules::default();
+    for (rule, threshold) in thresholds.iter() {
+        rules
+            .set(rule.extract()?, threshold.extract()?)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    }
+    Ok(rules)
+}
+
+/// The report of `table`: a `HealthReport`, or a path analyzed with `analyze_table`
+fn report(py: Python<'_>, table: &PyAny, kwargs: Option<&PyDict>) -> PyResult<HealthReport> {
+    if let Ok(report) = table.extract::<HealthReport>() {
+        return Ok(report);
+    }
+    py.import("drainage")?
+        .getattr("analyze_table")?
+        .call((table,), kwargs)?
+        .extract()
+}
+
+fn small_file_ratio(report: &HealthReport) -> f64 {
+    match report.metrics.total_files {
+        0 => 0.0,
+        files => report.metrics.file_size_distribution.small_files as f64 / files as f64,
+    }
+}
+
+/// The XCom summary of `report` against the rules it broke
+fn summary(report: &HealthReport, failed: &[Breach]) -> Value {
+    let failed_checks: Vec<Value> = failed
+        .iter()
+        .map(|b| json!({ "rule": b.rule, "threshold": b.threshold, "value": b.value, "message": b.message }))
+        .collect();
+    let recommendations: Vec<&String> = report
+        .metrics
+        .recommendations
+        .iter()
+        .take(SUMMARY_RECOMMENDATIONS)
+        .collect();
+    json!({
+        "passed": failed.is_empty(),
+        "table_path": report.table_path,
+        "table_type": report.table_type,
+        "analysis_timestamp": report.analysis_timestamp.to_rfc3339(),
+        "health_score": report.health_score,
+        "total_files": report.metrics.total_files,
+        "total_size_bytes": report.metrics.total_size_bytes,
+        "unreferenced_size_bytes": report.metrics.unreferenced_size_bytes,
+        "small_file_ratio": small_file_ratio(report),
+        "failed_checks": failed_checks,
+        "top_recommendations": recommendations,
+    })
+}
+
+/// `AssetCheckResult` keyword arguments for `report` against the rules it broke
+fn asset_check(report: &HealthReport, failed: &[Breach]) -> Value {
+    let description = match failed {
+        [] => format!(
+            "{} is healthy: score {:.1}%",
+            report.table_path,
+            report.health_score * 100.0
+        ),
+        failed => failed
+            .iter()
+            .map(|b| b.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; "),
+    };
+    let mut metadata = json!({
+        "table_path": report.table_path,
+        "table_type": report.table_type,
+        "health_score": report.health_score,
+        "total_files": report.metrics.total_files,
+        "total_size_bytes": report.metrics.total_size_bytes,
+        "unreferenced_size_bytes": report.metrics.unreferenced_size_bytes,
+        "small_file_ratio": small_file_ratio(report),
+        "recommendation_count": report.metrics.recommendations.len(),
+    });
+    for breach in failed {
+        metadata[format!("{}_threshold", breach.rule)] = json!(breach.threshold);
+    }
+    json!({ "passed": failed.is_empty(), "description": description, "metadata": metadata })
+}
+
+fn to_python(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    Ok(py
+        .import("json")?
+        .call_method1("loads", (value.to_string(),))?
+        .into())
+}
+
+/// Check `table` (a path or a `HealthReport`) against `thresholds`, returning a
+/// JSON-serializable dict with `passed`, the key metrics, the failed checks and the top
+/// recommendations. Other keyword arguments go to `analyze_table`.
+#[pyfunction]
+#[pyo3(signature = (table, thresholds, **kwargs))]
+fn airflow_check(
+    py: Python<'_>,
+    table: &PyAny,
+    thresholds: &PyDict,
+    kwargs: Option<&PyDict>,
+) -> PyResult<PyObject> {
+    let rules = rules(thresholds)?;
+    let report = report(py, table, kwargs)?;
+    to_python(py, &summary(&report, &breaches(&report, &rules)))
+}
+
+/// Check `table` (a path or a `HealthReport`) against `thresholds`, returning the `passed`,
+/// `description` and `metadata` keyword arguments of a Dagster `AssetCheckResult`. Other
+/// keyword arguments go to `analyze_table`.
+#[pyfunction]
+#[pyo3(signature = (table, thresholds, **kwargs))]
+fn dagster_check(
+    py: Python<'_>,
+    table: &PyAny,
+    thresholds: &PyDict,
+    kwargs: Option<&PyDict>,
+) -> PyResult<PyObject> {
+    let rules = rules(thresholds)?;
+    let report = report(py, table, kwargs)?;
+    to_python(py, &asset_check(&report, &breaches(&report, &rules)))
+}
+
+/// Add the `drainage.integrations` submodule to `parent`
+pub fn register(py: Python<'_>, parent: &PyModule) -> PyResult<()> {
+    let module = PyModule::new(py, "integrations")?;
+    module.add_function(wrap_pyfunction!(airflow_check, module)?)?;
+    module.add_function(wrap_pyfunction!(dagster_check, module)?)?;
+    parent.add_submodule(module)?;
+    // So `import drainage.integrations` finds it
+    py.import("sys")?
+        .getattr("modules")?
+        .set_item("drainage.integrations", module)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checks_summarize_failed_thresholds() {
+        let mut report = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        report.health_score = 0.6;
+        report.metrics.total_files = 4;
+        report.metrics.recommendations = (1..=5).map(|i| format!("Fix {}", i)).collect();
+        let mut rules = NotifyRules::default();
+        rules.set("min_health_score", 0.7).unwrap();
+        rules.set("max_orphan_gb", 10.0).unwrap();
+
+        let failed = breaches(&report, &rules);
+        let xcom = summary(&report, &failed);
+        assert_eq!(xcom["passed"], false);
+        assert_eq!(xcom["failed_checks"][0]["rule"], "min_health_score");
+        assert_eq!(xcom["failed_checks"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            xcom["top_recommendations"],
+            json!(["Fix 1", "Fix 2", "Fix 3"])
+        );
+
+        let check = asset_check(&report, &failed);
+        assert_eq!(check["description"], "Health score 60.0% is below 70.0%");
+        assert_eq!(check["metadata"]["min_health_score_threshold"], 0.7);
+        report.health_score = 0.9;
+        let check = asset_check(&report, &breaches(&report, &rules));
+        assert_eq!(check["passed"], true);
+        assert_eq!(
+            check["description"],
+            "s3://lake/events is healthy: score 90.0%"
+        );
+    }
+}
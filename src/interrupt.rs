@@ -0,0 +1,102 @@
+//! Ctrl-C and termination handling for long analyses called from Python.
+//!
+//! Python only runs signal handlers when the interpreter gets control back, so blocking on
+//! the analysis while holding the GIL ignores Ctrl-C until it finishes, or forever if a
+//! request hangs. Analyses therefore run with the GIL released while a watcher re-acquires
+//! it every few milliseconds to run pending handlers. When a handler raises (SIGINT's
+//! `KeyboardInterrupt`, or whatever a SIGTERM handler installed with `signal.signal` raises),
+//! the analysis future is dropped, cancelling its in-flight requests, and the runtime is
+//! shut down without waiting for them.
+
+use pyo3::prelude::*;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+const SIGNAL_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Runs Python's pending signal handlers while an analysis is in flight
+#[derive(Default)]
+pub struct SignalWatch {
+    error: Mutex<Option<PyErr>>,
+}
+
+impl SignalWatch {
+    /// Resolves once a signal handler raised; the exception is kept for `take_error`.
+    /// Handlers only run on the main thread, so this must be polled from the calling thread.
+    pub async fn interrupted(&self) {
+        loop {
+            tokio::time::sleep(SIGNAL_CHECK_INTERVAL).await;
+            if let Err(e) = Python::with_gil(|py| py.check_signals()) {
+                *self.error.lock().unwrap_or_else(|e| e.into_inner()) = Some(e);
+                return;
+            }
+        }
+    }
+
+    /// The exception raised by the signal handler, if the analysis was interrupted
+    pub fn take_error(&self) -> Option<PyErr> {
+        self.error.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+}
+
+/// Drive `future` to completion with the GIL released; `cancel` resolving stops it early
+/// (returning None). Tasks still running afterwards are abandoned, not awaited.
+pub fn block_on_until<F, C>(py: Python<'_>, future: F, cancel: C) -> PyResult<Option<F::Output>>
+where
+    F: Future + Send,
+    F::Output: Send,
+    C: Future<Output = ()> + Send,
+{
+    let runtime = tokio::runtime::Runtime::new()?;
+    let outcome = run_until(py, &runtime, future, cancel);
+    runtime.shutdown_background();
+    Ok(outcome)
+}
+
+/// Like `block_on_until` on a runtime that outlives the call (and keeps its tasks)
+fn run_until<F, C>(py: Python<'_>, runtime: &Runtime, future: F, cancel: C) -> Option<F::Output>
+where
+    F: Future + Send,
+    F::Output: Send,
+    C: Future<Output = ()> + Send,
+{
+    py.allow_threads(|| {
+        runtime.block_on(async {
+            tokio::select! {
+                biased;
+                output = future => Some(output),
+                _ = cancel => None,
+            }
+        })
+    })
+}
+
+/// Run an analysis that can be interrupted with Ctrl-C
+pub fn block_on<F, T>(py: Python<'_>, future: F) -> PyResult<T>
+where
+    F: Future<Output = PyResult<T>> + Send,
+    T: Send,
+{
+    let runtime = tokio::runtime::Runtime::new()?;
+    let result = block_on_in(py, &runtime, future);
+    runtime.shutdown_background();
+    result
+}
+
+/// Run an analysis that can be interrupted with Ctrl-C on a long-lived `runtime`, whose
+/// pooled connections later calls reuse
+pub fn block_on_in<F, T>(py: Python<'_>, runtime: &Runtime, future: F) -> PyResult<T>
+where
+    F: Future<Output = PyResult<T>> + Send,
+    T: Send,
+{
+    let watch = SignalWatch::default();
+    match run_until(py, runtime, future, watch.interrupted()) {
+        Some(result) => result.map_err(|e| crate::sanitize::py_err(py, e)),
+        None => Err(watch
+            .take_error()
+            .unwrap_or_else(|| pyo3::exceptions::PyKeyboardInterrupt::new_err(()))),
+    }
+}
@@ -0,0 +1,369 @@
+//! S3 Inventory reports as the source of a table's data files.
+//!
+//! ListObjectsV2 returns at most 1,000 keys per request, so a table with millions of objects
+//! takes thousands of sequential requests to list. An S3 Inventory report holds the same
+//! listing in a handful of CSV or Parquet files, described by a `manifest.json`. Data files
+//! are taken from the report; the transaction log or metadata directory is still listed
+//! live, since a report can be a day or more old.
+
+use crate::config::AnalysisConfig;
+use crate::s3_client::{relative_key, ObjectInfo, S3ClientWrapper};
+use crate::types::InventorySource;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::Field;
+use serde_json::Value;
+
+/// What a `manifest.json` says about one inventory delivery
+#[derive(Debug)]
+struct Manifest {
+    source_bucket: String,
+    destination_bucket: String,
+    file_format: String,      // "CSV", "Parquet" or "ORC"
+    file_schema: Vec<String>, // CSV column names, e.g. "Key", "Size"
+    files: Vec<String>,
+    created_at: Option<DateTime<Utc>>,
+}
+
+fn parse_manifest(content: &[u8]) -> Result<Manifest> {
+    let json: Value = serde_json::from_slice(content).context("Invalid inventory manifest")?;
+    let string = |field: &str| {
+        json.get(field)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .with_context(|| format!("Inventory manifest has no {}", field))
+    };
+    Ok(Manifest {
+        source_bucket: string("sourceBucket")?,
+        // "arn:aws:s3:::bucket"
+        destination_bucket: string("destinationBucket")?
+            .rsplit(':')
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        file_format: string("fileFormat")?,
+        file_schema: string("fileSchema")
+            .unwrap_or_default()
+            .split(',')
+            .map(|column| column.trim().to_string())
+            .collect(),
+        files: json
+            .get("files")
+            .and_then(|f| f.as_array())
+            .map(|files| {
+                files
+                    .iter()
+                    .filter_map(|file| file.get("key").and_then(|k| k.as_str()))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        created_at: json
+            .get("creationTimestamp")
+            .and_then(|t| t.as_str())
+            .and_then(|t| t.parse::<i64>().ok())
+            .and_then(DateTime::from_timestamp_millis),
+    })
+}
+
+/// Fields of one CSV line; every field is quoted and quotes inside are doubled
+pub fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Current objects in a CSV inventory file. Keys are URL-encoded, with spaces as `+`.
+fn parse_csv(content: &str, schema: &[String]) -> Vec<ObjectInfo> {
+    let column = |name: &str| schema.iter().position(|c| c.eq_ignore_ascii_case(name));
+    let (Some(key), size, modified, etag, class) = (
+        column("Key"),
+        column("Size"),
+        column("LastModifiedDate"),
+        column("ETag"),
+        column("StorageClass"),
+    ) else {
+        return Vec::new();
+    };
+    let (is_latest, is_delete_marker) = (column("IsLatest"), column("IsDeleteMarker"));
+
+    content
+        .lines()
+        .map(csv_fields)
+        .filter(|fields| {
+            let flag =
+                |index: Option<usize>| index.and_then(|i| fields.get(i)).map(|v| v == "true");
+            flag(is_latest) != Some(false) && flag(is_delete_marker) != Some(true)
+        })
+        .filter_map(|fields| {
+            let value = |index: Option<usize>| {
+                index
+                    .and_then(|i| fields.get(i))
+                    .filter(|v| !v.is_empty())
+                    .cloned()
+            };
+            let encoded = value(Some(key))?.replace('+', " ");
+            Some(ObjectInfo {
+                key: percent_encoding::percent_decode_str(&encoded)
+                    .decode_utf8_lossy()
+                    .into_owned(),
+                size: value(size).and_then(|s| s.parse().ok()).unwrap_or_default(),
+                last_modified: value(modified).and_then(|d| d.parse().ok()),
+                etag: value(etag),
+                storage_class: value(class),
+            })
+        })
+        .collect()
+}
+
+/// Current objects in a Parquet inventory file. Keys are not encoded.
+fn parse_parquet(content: Vec<u8>) -> Result<Vec<ObjectInfo>> {
+    let reader = SerializedFileReader::new(bytes::Bytes::from(content))?;
+    let mut objects = Vec::new();
+    for row in reader.get_row_iter(None)? {
+        let row = row?;
+        let mut object = ObjectInfo {
+            key: String::new(),
+            size: 0,
+            last_modified: None,
+            etag: None,
+            storage_class: None,
+        };
+        let mut current = true;
+        for (name, field) in row.get_column_iter() {
+            match (name.as_str(), field) {
+                ("key", Field::Str(key)) => object.key = key.clone(),
+                ("size", Field::Long(size)) => object.size = *size,
+                ("last_modified_date", Field::TimestampMillis(millis)) => {
+                    object.last_modified = DateTime::from_timestamp_millis(*millis);
+                }
+                ("e_tag", Field::Str(etag)) => object.etag = Some(etag.clone()),
+                ("storage_class", Field::Str(class)) => {
+                    object.storage_class = Some(class.clone());
+                }
+                ("is_latest", Field::Bool(latest)) => current &= *latest,
+                ("is_delete_marker", Field::Bool(marker)) => current &= !*marker,
+                _ => {}
+            }
+        }
+        if current && !object.key.is_empty() {
+            objects.push(object);
+        }
+    }
+    Ok(objects)
+}
+
+/// Key of the manifest at `location`: the manifest itself, or the newest one under an
+/// inventory configuration's prefix (deliveries sit in dated folders that sort by time)
+async fn find_manifest(inventory: &S3ClientWrapper) -> Result<String> {
+    let prefix = inventory.get_prefix();
+    if prefix.ends_with("manifest.json") {
+        return Ok(prefix.to_string());
+    }
+    inventory
+        .list_objects(prefix)
+        .await?
+        .into_iter()
+        .map(|object| object.key)
+        .filter(|key| key.ends_with("/manifest.json") && !key.contains("/hive/"))
+        .max()
+        .with_context(|| format!("No inventory manifest under {}", prefix))
+}
+
+/// Data objects of the table from the inventory report at `location`, a `manifest.json` or
+/// the prefix its deliveries are written to. Objects under `metadata_dirs` or outside the
+/// partition filter are dropped; the caller lists those directories live.
+pub async fn data_objects(
+    s3_client: &S3ClientWrapper,
+    location: &str,
+    metadata_dirs: &[&str],
+    config: &AnalysisConfig,
+) -> Result<(Vec<ObjectInfo>, InventorySource)> {
+    let inventory = s3_client
+        .with_location(location)
+        .context("Invalid inventory location")?;
+    let manifest_key = find_manifest(&inventory).await?;
+    let manifest = parse_manifest(&inventory.get_object(&manifest_key).await?)?;
+    anyhow::ensure!(
+        manifest.source_bucket == s3_client.get_bucket(),
+        "The inventory is for bucket {}, not {}",
+        manifest.source_bucket,
+        s3_client.get_bucket()
+    );
+
+    let files = inventory.with_location(&format!("s3://{}/", manifest.destination_bucket))?;
+    let table_prefix = format!("{}/", s3_client.get_prefix().trim_end_matches('/'));
+    let mut objects = Vec::new();
+    for file in &manifest.files {
+        let content = files.get_object(file).await?;
+        let rows = match manifest.file_format.to_ascii_uppercase().as_str() {
+            "CSV" => {
+                let content = match file.ends_with(".gz") {
+                    true => crate::access_logs::gunzip(&content)?,
+                    false => content,
+                };
+                parse_csv(&String::from_utf8_lossy(&content), &manifest.file_schema)
+            }
+            "PARQUET" => parse_parquet(content)
+                .with_context(|| format!("Failed to read inventory file {}", file))?,
+            other => anyhow::bail!("Unsupported inventory format {}; use CSV or Parquet", other),
+        };
+        objects.extend(rows.into_iter().filter(|object| {
+            let relative = relative_key(s3_client.get_prefix(), &object.key);
+            (table_prefix == "/" || object.key.starts_with(&table_prefix))
+                && !metadata_dirs.iter().any(|dir| relative.starts_with(dir))
+                && config.partition_filter.matches(relative)
+        }));
+    }
+
+    let source = InventorySource {
+        manifest_path: format!("s3://{}/{}", inventory.get_bucket(), manifest_key),
+        file_format: manifest.file_format,
+        created_at: manifest.created_at,
+        data_objects: objects.len(),
+    };
+    Ok((objects, source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = parse_manifest(
+            br#"{
+                "sourceBucket": "lake",
+                "destinationBucket": "arn:aws:s3:::lake-inventory",
+                "version": "2016-11-30",
+                "creationTimestamp": "1704070800000",
+                "fileFormat": "CSV",
+                "fileSchema": "Bucket, Key, Size, LastModifiedDate, ETag, StorageClass",
+                "files": [{"key": "lake/daily/data/a.csv.gz", "size": 100, "MD5checksum": "x"}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.source_bucket, "lake");
+        assert_eq!(manifest.destination_bucket, "lake-inventory");
+        assert_eq!(manifest.file_schema[1], "Key");
+        assert_eq!(manifest.files, vec!["lake/daily/data/a.csv.gz"]);
+        assert_eq!(
+            manifest.created_at.map(|t| t.to_rfc3339()),
+            Some("2024-01-01T01:00:00+00:00".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_skips_old_versions_and_decodes_keys() {
+        let schema: Vec<String> = [
+            "Bucket",
+            "Key",
+            "VersionId",
+            "IsLatest",
+            "IsDeleteMarker",
+            "Size",
+            "LastModifiedDate",
+            "ETag",
+            "StorageClass",
+        ]
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+        let content = concat!(
+            "\"lake\",\"events/date%3D2024-01-01/part+0.parquet\",\"v2\",\"true\",\"false\",\"1024\",\"2024-01-01T00:00:00.000Z\",\"abc\",\"STANDARD\"\n",
+            "\"lake\",\"events/old.parquet\",\"v1\",\"false\",\"false\",\"10\",\"2023-01-01T00:00:00.000Z\",\"def\",\"STANDARD\"\n",
+            "\"lake\",\"events/deleted.parquet\",\"v3\",\"true\",\"true\",\"\",\"2024-01-02T00:00:00.000Z\",\"\",\"\"\n",
+        );
+
+        let objects = parse_csv(content, &schema);
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].key, "events/date=2024-01-01/part 0.parquet");
+        assert_eq!(objects[0].size, 1024);
+        assert_eq!(objects[0].storage_class.as_deref(), Some("STANDARD"));
+        assert!(objects[0].last_modified.is_some());
+    }
+
+    #[test]
+    fn test_parse_parquet() {
+        use parquet::data_type::{BoolType, ByteArray, ByteArrayType, Int64Type};
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+        use std::sync::Arc;
+
+        let schema = Arc::new(
+            parse_message_type(
+                "message s3.inventory { required binary key (UTF8); required int64 size; required int64 last_modified_date (TIMESTAMP_MILLIS); required boolean is_delete_marker; }",
+            )
+            .unwrap(),
+        );
+        let mut buffer = Vec::new();
+        let mut writer =
+            SerializedFileWriter::new(&mut buffer, schema, Arc::new(WriterProperties::default()))
+                .unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+        let mut key = row_group.next_column().unwrap().unwrap();
+        key.typed::<ByteArrayType>()
+            .write_batch(
+                &[
+                    ByteArray::from("events/a.parquet"),
+                    ByteArray::from("events/b.parquet"),
+                ],
+                None,
+                None,
+            )
+            .unwrap();
+        key.close().unwrap();
+        let mut size = row_group.next_column().unwrap().unwrap();
+        size.typed::<Int64Type>()
+            .write_batch(&[100, 0], None, None)
+            .unwrap();
+        size.close().unwrap();
+        let mut modified = row_group.next_column().unwrap().unwrap();
+        modified
+            .typed::<Int64Type>()
+            .write_batch(&[1704067200000, 1704067200000], None, None)
+            .unwrap();
+        modified.close().unwrap();
+        let mut marker = row_group.next_column().unwrap().unwrap();
+        marker
+            .typed::<BoolType>()
+            .write_batch(&[false, true], None, None)
+            .unwrap();
+        marker.close().unwrap();
+        row_group.close().unwrap();
+        writer.close().unwrap();
+
+        let objects = parse_parquet(buffer).unwrap();
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].key, "events/a.parquet");
+        assert_eq!(objects[0].size, 100);
+        assert_eq!(
+            objects[0].last_modified.map(|t| t.to_rfc3339()),
+            Some("2024-01-01T00:00:00+00:00".to_string())
+        );
+    }
+
+    #[test]
+    fn test_csv_fields_unescape_quotes() {
+        assert_eq!(
+            csv_fields(r#""a","say ""hi"", then go","""#),
+            vec!["a", r#"say "hi", then go"#, ""]
+        );
+    }
+}
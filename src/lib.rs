@@ -1,111 +1,542 @@
+// pyo3 0.20's `#[new]` expansion trips this lint on newer compilers
+#![allow(non_local_definitions)]
+// A `capi` build is a plain C library without the Python module, so the Python entry
+// points, and what only they use, go unused
+#![cfg_attr(feature = "capi", allow(dead_code))]
+
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 
+mod access_logs;
+mod avro;
+mod badge;
+mod bundle;
+#[cfg(feature = "capi")]
+mod capi;
+mod capture;
+mod catalog;
+mod cloud_metrics;
+mod clustering;
+mod config;
+mod custom_checks;
+mod data_quality;
+mod deadline;
 mod delta_lake;
+mod duplicates;
+mod engine_limits;
+mod error;
+mod existence;
+mod format_version;
+mod glue_catalog;
 mod health_analyzer;
+#[cfg(test)]
+mod health_analyzer_tests;
+mod history;
+mod hive_metastore;
 mod iceberg;
+mod integrations;
+mod interrupt;
+mod inventory;
+mod listing;
+mod logging;
+mod migration;
+mod multipart;
+mod network;
+mod notify;
+mod observability;
+mod options;
+mod orc_footer;
+mod otlp;
+mod parquet_footer;
+mod partial;
+mod partition_stats;
+mod policy;
+mod provider;
+mod redaction;
+mod regression;
+mod remediation;
+mod rpc;
 mod s3_client;
+#[cfg(test)]
+mod s3_client_tests;
+mod sanitize;
+mod sarif;
+mod server;
+mod session;
+mod settings;
+mod simulation;
+mod stream;
+mod table_properties;
 mod types;
+#[cfg(test)]
+mod types_tests;
+mod unity_catalog;
+mod warm_start;
 
 use health_analyzer::HealthAnalyzer;
+use s3_client::AwsCredentials;
 
-/// A Python module implemented in Rust for analyzing data lake health
+/// A Python module implemented in Rust for analyzing data lake health. Left out of `capi`
+/// builds, whose library C hosts load without Python.
+#[cfg(not(feature = "capi"))]
 #[pymodule]
-fn drainage(_py: Python, m: &PyModule) -> PyResult<()> {
+fn drainage(py: Python, m: &PyModule) -> PyResult<()> {
+    logging::install();
     m.add_function(wrap_pyfunction!(analyze_delta_lake, m)?)?;
     m.add_function(wrap_pyfunction!(analyze_iceberg, m)?)?;
     m.add_function(wrap_pyfunction!(analyze_table, m)?)?;
+    m.add_function(wrap_pyfunction!(analyze_catalog_table, m)?)?;
+    m.add_function(wrap_pyfunction!(analyze_namespace, m)?)?;
+    m.add_function(wrap_pyfunction!(analyze_stream, m)?)?;
+    m.add_function(wrap_pyfunction!(detect_table_type, m)?)?;
+    m.add_function(wrap_pyfunction!(capture_metadata, m)?)?;
+    m.add_function(wrap_pyfunction!(compare_schemas, m)?)?;
+    m.add_function(wrap_pyfunction!(report_history, m)?)?;
+    m.add_function(wrap_pyfunction!(detect_regressions, m)?)?;
     m.add_function(wrap_pyfunction!(print_health_report, m)?)?;
+    m.add_function(wrap_pyfunction!(cli, m)?)?;
+    m.add_function(wrap_pyfunction!(set_log_level, m)?)?;
+    m.add_function(wrap_pyfunction!(custom_checks::register_check, m)?)?;
+    m.add_function(wrap_pyfunction!(custom_checks::unregister_check, m)?)?;
+    m.add("DrainageError", py.get_type::<error::py::DrainageError>())?;
+    m.add_class::<redaction::RedactionPolicy>()?;
+    m.add_class::<catalog::RestCatalogConfig>()?;
+    m.add_class::<glue_catalog::GlueCatalogConfig>()?;
+    m.add_class::<unity_catalog::UnityCatalogConfig>()?;
+    m.add_class::<hive_metastore::HiveMetastoreConfig>()?;
+    m.add_class::<options::AnalysisOptions>()?;
+    m.add_class::<options::StorageOptions>()?;
+    m.add_class::<session::DrainageSession>()?;
+    integrations::register(py, m)?;
     Ok(())
 }
 
 /// Analyze Delta Lake table health
 #[pyfunction]
+#[pyo3(signature = (s3_path, aws_access_key_id=None, aws_secret_access_key=None, aws_region=None, aws_session_token=None, aws_profile=None, role_arn=None, external_id=None, **options))]
+#[allow(clippy::too_many_arguments)]
 fn analyze_delta_lake(
+    py: Python<'_>,
     s3_path: String,
     aws_access_key_id: Option<String>,
     aws_secret_access_key: Option<String>,
     aws_region: Option<String>,
+    aws_session_token: Option<String>,
+    aws_profile: Option<String>,
+    role_arn: Option<String>,
+    external_id: Option<String>,
+    options: Option<&PyDict>,
 ) -> PyResult<types::HealthReport> {
-    let rt = tokio::runtime::Runtime::new()?;
-    rt.block_on(async {
-        let analyzer = HealthAnalyzer::create_async(
-            s3_path,
+    let (credentials, aws_region, config) = options::resolve_with_credentials(
+        options,
+        AwsCredentials {
             aws_access_key_id,
             aws_secret_access_key,
-            aws_region,
-        )
-        .await?;
+            aws_session_token,
+            aws_profile,
+            role_arn,
+            external_id,
+        },
+        aws_region,
+    )?;
+    interrupt::block_on(py, async {
+        let analyzer =
+            HealthAnalyzer::create_async(s3_path, credentials, aws_region, config).await?;
         analyzer.analyze_delta_lake().await
     })
 }
 
 /// Analyze Apache Iceberg table health
 #[pyfunction]
+#[pyo3(signature = (s3_path, aws_access_key_id=None, aws_secret_access_key=None, aws_region=None, aws_session_token=None, aws_profile=None, role_arn=None, external_id=None, **options))]
+#[allow(clippy::too_many_arguments)]
 fn analyze_iceberg(
+    py: Python<'_>,
     s3_path: String,
     aws_access_key_id: Option<String>,
     aws_secret_access_key: Option<String>,
     aws_region: Option<String>,
+    aws_session_token: Option<String>,
+    aws_profile: Option<String>,
+    role_arn: Option<String>,
+    external_id: Option<String>,
+    options: Option<&PyDict>,
 ) -> PyResult<types::HealthReport> {
-    let rt = tokio::runtime::Runtime::new()?;
-    rt.block_on(async {
-        let analyzer = HealthAnalyzer::create_async(
-            s3_path,
+    let (credentials, aws_region, config) = options::resolve_with_credentials(
+        options,
+        AwsCredentials {
             aws_access_key_id,
             aws_secret_access_key,
-            aws_region,
-        )
-        .await?;
+            aws_session_token,
+            aws_profile,
+            role_arn,
+            external_id,
+        },
+        aws_region,
+    )?;
+    interrupt::block_on(py, async {
+        let analyzer =
+            HealthAnalyzer::create_async(s3_path, credentials, aws_region, config).await?;
         analyzer.analyze_iceberg().await
     })
 }
 
 /// Analyze table health with automatic table type detection
 #[pyfunction]
+#[pyo3(signature = (s3_path, table_type=None, aws_access_key_id=None, aws_secret_access_key=None, aws_region=None, aws_session_token=None, aws_profile=None, role_arn=None, external_id=None, **options))]
+#[allow(clippy::too_many_arguments)]
 fn analyze_table(
+    py: Python<'_>,
     s3_path: String,
     table_type: Option<String>,
     aws_access_key_id: Option<String>,
     aws_secret_access_key: Option<String>,
     aws_region: Option<String>,
+    aws_session_token: Option<String>,
+    aws_profile: Option<String>,
+    role_arn: Option<String>,
+    external_id: Option<String>,
+    options: Option<&PyDict>,
 ) -> PyResult<types::HealthReport> {
-    let rt = tokio::runtime::Runtime::new()?;
-    rt.block_on(async {
-        let analyzer = HealthAnalyzer::create_async(s3_path.clone(), aws_access_key_id, aws_secret_access_key, aws_region).await?;
-        // If table type is specified, use it directly
-        if let Some(ref ttype) = table_type {
-            match ttype.to_lowercase().as_str() {
-                "delta" | "delta_lake" => analyzer.analyze_delta_lake().await,
-                "iceberg" | "apache_iceberg" => analyzer.analyze_iceberg().await,
-                _ => Err(pyo3::exceptions::PyValueError::new_err(
-                    format!("Unknown table type: {}. Supported types: 'delta', 'iceberg'", ttype)
-                )),
-            }
-        } else {
-            // Auto-detect table type by checking for characteristic files
-            let objects = analyzer.list_objects_for_detection().await
-                .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to list objects: {}", e)))?;
-            // Check for Delta Lake characteristic files
-            let has_delta_log = objects.iter().any(|obj| obj.key.contains("_delta_log/") && obj.key.ends_with(".json"));
-            // Check for Iceberg characteristic files
-            let has_iceberg_metadata = objects.iter().any(|obj| obj.key.ends_with("metadata.json"));
-            if has_delta_log && !has_iceberg_metadata {
-                analyzer.analyze_delta_lake().await
-            } else if has_iceberg_metadata && !has_delta_log {
-                analyzer.analyze_iceberg().await
-            } else if has_delta_log && has_iceberg_metadata {
-                Err(pyo3::exceptions::PyValueError::new_err(
-                    "Ambiguous table type: both Delta Lake and Iceberg files detected. Please specify table_type explicitly."
-                ))
-            } else {
-                Err(pyo3::exceptions::PyValueError::new_err(
-                    "Could not determine table type. No Delta Lake (_delta_log) or Iceberg (metadata.json) files found. Please specify table_type explicitly."
-                ))
-            }
+    let (credentials, aws_region, config) = options::resolve_with_credentials(
+        options,
+        AwsCredentials {
+            aws_access_key_id,
+            aws_secret_access_key,
+            aws_session_token,
+            aws_profile,
+            role_arn,
+            external_id,
+        },
+        aws_region,
+    )?;
+    interrupt::block_on(py, async {
+        let analyzer =
+            HealthAnalyzer::create_async(s3_path, credentials, aws_region, config).await?;
+        analyzer.analyze(table_type.as_deref()).await
+    })
+}
+
+/// Detect the format of the table at `s3_path` without analyzing it: "delta", "iceberg",
+/// "hudi", "parquet", "ambiguous" or "unknown", with the files that decided it
+#[pyfunction]
+#[pyo3(signature = (s3_path, aws_access_key_id=None, aws_secret_access_key=None, aws_region=None, aws_session_token=None, aws_profile=None, role_arn=None, external_id=None, **options))]
+#[allow(clippy::too_many_arguments)]
+fn detect_table_type(
+    py: Python<'_>,
+    s3_path: String,
+    aws_access_key_id: Option<String>,
+    aws_secret_access_key: Option<String>,
+    aws_region: Option<String>,
+    aws_session_token: Option<String>,
+    aws_profile: Option<String>,
+    role_arn: Option<String>,
+    external_id: Option<String>,
+    options: Option<&PyDict>,
+) -> PyResult<types::TableDetection> {
+    let (credentials, aws_region, config) = options::resolve_with_credentials(
+        options,
+        AwsCredentials {
+            aws_access_key_id,
+            aws_secret_access_key,
+            aws_session_token,
+            aws_profile,
+            role_arn,
+            external_id,
+        },
+        aws_region,
+    )?;
+    interrupt::block_on(py, async {
+        let analyzer =
+            HealthAnalyzer::create_async(s3_path, credentials, aws_region, config).await?;
+        analyzer.detect().await
+    })
+}
+
+/// Copy the table's transaction log or metadata directory (no data files) and a listing of
+/// its objects to `output_dir`, as a reproducer to share without data or bucket access
+#[pyfunction]
+#[pyo3(signature = (s3_path, output_dir, aws_access_key_id=None, aws_secret_access_key=None, aws_region=None, aws_session_token=None, aws_profile=None, role_arn=None, external_id=None, **options))]
+#[allow(clippy::too_many_arguments)]
+fn capture_metadata(
+    py: Python<'_>,
+    s3_path: String,
+    output_dir: std::path::PathBuf,
+    aws_access_key_id: Option<String>,
+    aws_secret_access_key: Option<String>,
+    aws_region: Option<String>,
+    aws_session_token: Option<String>,
+    aws_profile: Option<String>,
+    role_arn: Option<String>,
+    external_id: Option<String>,
+    options: Option<&PyDict>,
+) -> PyResult<capture::MetadataCapture> {
+    let (credentials, aws_region, config) = options::resolve_with_credentials(
+        options,
+        AwsCredentials {
+            aws_access_key_id,
+            aws_secret_access_key,
+            aws_session_token,
+            aws_profile,
+            role_arn,
+            external_id,
+        },
+        aws_region,
+    )?;
+    interrupt::block_on(py, async {
+        let analyzer =
+            HealthAnalyzer::create_async(s3_path, credentials, aws_region, config).await?;
+        analyzer.capture_metadata(&output_dir).await
+    })
+}
+
+/// Stream an analysis as an async iterator of `AnalysisEvent`s (progress, warnings and
+/// metrics as they become known), ending with a `report` event
+#[pyfunction]
+#[pyo3(signature = (s3_path, table_type=None, aws_access_key_id=None, aws_secret_access_key=None, aws_region=None, aws_session_token=None, aws_profile=None, role_arn=None, external_id=None, **options))]
+#[allow(clippy::too_many_arguments)]
+fn analyze_stream(
+    s3_path: String,
+    table_type: Option<String>,
+    aws_access_key_id: Option<String>,
+    aws_secret_access_key: Option<String>,
+    aws_region: Option<String>,
+    aws_session_token: Option<String>,
+    aws_profile: Option<String>,
+    role_arn: Option<String>,
+    external_id: Option<String>,
+    options: Option<&PyDict>,
+) -> PyResult<stream::AnalysisStream> {
+    let (credentials, aws_region, config) = options::resolve_with_credentials(
+        options,
+        AwsCredentials {
+            aws_access_key_id,
+            aws_secret_access_key,
+            aws_session_token,
+            aws_profile,
+            role_arn,
+            external_id,
+        },
+        aws_region,
+    )?;
+    let target = health_analyzer::TableTarget {
+        s3_path,
+        table_type,
+        credentials,
+        aws_region,
+    };
+    Ok(stream::AnalysisStream::start(target, config))
+}
+
+/// Analyze a table resolved by name (`namespace.table`) through a REST, Glue, Unity Catalog
+/// or Hive Metastore catalog, as the format the catalog registered it with
+#[pyfunction]
+#[pyo3(signature = (catalog, table, aws_region=None, **options))]
+fn analyze_catalog_table(
+    py: Python<'_>,
+    catalog: catalog::CatalogConfig,
+    table: String,
+    aws_region: Option<String>,
+    options: Option<&PyDict>,
+) -> PyResult<types::HealthReport> {
+    let options = options::resolve(options)?;
+    let aws_region = aws_region.or(options.storage.aws_region);
+    let config = options.config;
+    interrupt::block_on(py, async {
+        let analyzer =
+            HealthAnalyzer::create_from_catalog(&catalog, &table, aws_region, config).await?;
+        analyzer.analyze_catalog_table().await
+    })
+}
+
+/// Analyze every catalog table matching `pattern` (e.g. `analytics.*`), isolating
+/// per-table failures, and summarize the results across tables
+#[pyfunction]
+#[pyo3(signature = (catalog, pattern, aws_region=None, max_concurrency=None, **options))]
+fn analyze_namespace(
+    py: Python<'_>,
+    catalog: catalog::CatalogConfig,
+    pattern: String,
+    aws_region: Option<String>,
+    max_concurrency: Option<usize>,
+    options: Option<&PyDict>,
+) -> PyResult<types::NamespaceReport> {
+    let options = options::resolve(options)?;
+    let aws_region = aws_region.or(options.storage.aws_region);
+    let max_concurrency = max_concurrency.or(options.max_concurrency).unwrap_or(4);
+    let config = options.config;
+    let watch = interrupt::SignalWatch::default();
+    let report = interrupt::block_on_until(
+        py,
+        health_analyzer::analyze_namespace(
+            &catalog,
+            &pattern,
+            aws_region,
+            config,
+            max_concurrency,
+            watch.interrupted(),
+        ),
+        std::future::pending(),
+    )?
+    .expect("analysis is never cancelled from outside")
+    .map_err(|e| {
+        error::DrainageError::from_anyhow(e, Some("catalog"))
+            .into_py_err("Namespace analysis failed")
+    })?;
+
+    // Hand the tables finished before Ctrl-C to the caller on the exception
+    if let Some(error) = watch.take_error() {
+        error
+            .value(py)
+            .setattr("partial_report", report.into_py(py))?;
+        return Err(error);
+    }
+    Ok(report)
+}
+
+/// Command-line entry point installed as the `drainage` script.
+/// `drainage serve --stdio` speaks JSON-RPC over stdin/stdout until stdin closes;
+/// `drainage serve --config fleet.yaml` runs the scheduled health service over HTTP.
+#[pyfunction]
+fn cli(py: Python) -> PyResult<()> {
+    let argv: Vec<String> = py.import("sys")?.getattr("argv")?.extract()?;
+    let args: Vec<&str> = argv.iter().skip(1).map(String::as_str).collect();
+    match args.as_slice() {
+        ["serve", "--stdio"] => py
+            .allow_threads(|| rpc::serve(std::io::stdin().lock(), &mut std::io::stdout().lock()))
+            .map_err(|e| {
+                pyo3::exceptions::PyRuntimeError::new_err(format!("Server failed: {}", e))
+            }),
+        ["serve", "--config", path] => py
+            .allow_threads(|| server::serve(std::path::Path::new(path)))
+            .map_err(|e| {
+                pyo3::exceptions::PyRuntimeError::new_err(format!("Server failed: {:#}", e))
+            }),
+        _ => {
+            eprintln!("usage: drainage serve --stdio | drainage serve --config FLEET_FILE");
+            Err(pyo3::exceptions::PySystemExit::new_err(2))
+        }
+    }
+}
+
+/// Forward drainage's log records at or above `level` (a `logging` level number or name
+/// such as "DEBUG"; "OFF" silences them) to Python's `logging`. The default is WARNING.
+#[pyfunction]
+fn set_log_level(level: &PyAny) -> PyResult<()> {
+    let level = match level.extract::<u8>() {
+        Ok(level) => level,
+        Err(_) => {
+            let name: String = level.extract()?;
+            logging::parse_level(&name).ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!("Unknown log level: {}", name))
+            })?
         }
+    };
+    logging::set_level(level);
+    Ok(())
+}
+
+/// Compare the current schemas of two reports, e.g. of a table and its mirror
+#[pyfunction]
+fn compare_schemas(
+    report_a: &types::HealthReport,
+    report_b: &types::HealthReport,
+) -> PyResult<types::SchemaComparison> {
+    let schema = |report: &types::HealthReport| {
+        report.current_schema.clone().ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "The report of {} has no schema",
+                report.table_path
+            ))
+        })
+    };
+    Ok(types::SchemaComparison::between(
+        &schema(report_a)?,
+        &schema(report_b)?,
+    ))
+}
+
+/// A copy of `report` whose `history_regressions` lists the metrics that deteriorated
+/// significantly against `history` (points from `drainage.history`), as high-severity findings
+#[pyfunction]
+#[pyo3(signature = (report, history, sensitivity="medium"))]
+fn detect_regressions(
+    report: &types::HealthReport,
+    history: Vec<history::HistoryPoint>,
+    sensitivity: &str,
+) -> PyResult<types::HealthReport> {
+    let sensitivity = history::Sensitivity::parse(sensitivity)
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let regressions = history::detect_regressions(report, &history, sensitivity)
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("{:#}", e)))?;
+    let mut report = report.clone();
+    report.history_regressions = Some(regressions);
+    Ok(report)
+}
+
+/// Health score, size, orphan bytes and small-file ratio of every report saved under `path`
+/// with `history_path=`, of `table_path` only if given, ordered by table and time
+#[pyfunction]
+#[pyo3(name = "history", signature = (path, table_path=None, aws_access_key_id=None, aws_secret_access_key=None, aws_region=None, aws_session_token=None, aws_profile=None, role_arn=None, external_id=None, **options))]
+#[allow(clippy::too_many_arguments)]
+fn report_history(
+    py: Python<'_>,
+    path: String,
+    table_path: Option<String>,
+    aws_access_key_id: Option<String>,
+    aws_secret_access_key: Option<String>,
+    aws_region: Option<String>,
+    aws_session_token: Option<String>,
+    aws_profile: Option<String>,
+    role_arn: Option<String>,
+    external_id: Option<String>,
+    options: Option<&PyDict>,
+) -> PyResult<Vec<history::HistoryPoint>> {
+    let (credentials, aws_region, config) = options::resolve_with_credentials(
+        options,
+        AwsCredentials {
+            aws_access_key_id,
+            aws_secret_access_key,
+            aws_session_token,
+            aws_profile,
+            role_arn,
+            external_id,
+        },
+        aws_region,
+    )?;
+    let network = config.network;
+    interrupt::block_on(py, async {
+        let points = async {
+            let store = match path.starts_with("s3://") {
+                true => history::HistoryStore::S3(
+                    s3_client::S3ClientWrapper::new(&path, credentials, aws_region, &network)
+                        .await?,
+                ),
+                false => history::HistoryStore::Local(path.clone().into()),
+            };
+            store.points(table_path.as_deref()).await
+        };
+        points.await.map_err(|e| {
+            pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to read history: {:#}", e))
+        })
     })
 }
 
+/// A byte count in the largest whole-ish unit, e.g. "16MB" or "1.5GB"
+pub(crate) fn size_label(bytes: u64) -> String {
+    let mb = bytes as f64 / (1024.0 * 1024.0);
+    let (value, unit) = match mb {
+        mb if mb >= 1024.0 => (mb / 1024.0, "GB"),
+        mb if mb >= 1.0 => (mb, "MB"),
+        mb => (mb * 1024.0, "KB"),
+    };
+    if value.fract() == 0.0 {
+        format!("{:.0}{}", value, unit)
+    } else {
+        format!("{:.1}{}", value, unit)
+    }
+}
+
 /// Print a comprehensive health report with nice formatting
 #[pyfunction]
 fn print_health_report(report: &types::HealthReport) -> PyResult<()> {
@@ -113,13 +544,93 @@ fn print_health_report(report: &types::HealthReport) -> PyResult<()> {
     println!("\n{}", "=".repeat(60));
     println!("Table Health Report: {}", report.table_path);
     println!("Type: {}", report.table_type);
-    println!("Analysis Time: {}", report.analysis_timestamp);
+    println!("Analysis Time: {}", report.analysis_timestamp.to_rfc3339());
+    if let Some(ref filter) = report.partition_filter {
+        let mut partitions: Vec<String> =
+            filter.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        partitions.sort();
+        println!("Partition Filter: {}", partitions.join(", "));
+    }
+    if let Some(ref tags) = report.tags {
+        let mut tags: Vec<String> = tags.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        tags.sort();
+        println!("Tags: {}", tags.join(", "));
+    }
+    if let Some(ref inventory) = report.inventory {
+        println!(
+            "Inventory: {} ({}, created {})",
+            inventory.manifest_path,
+            inventory.file_format,
+            inventory
+                .created_at
+                .map_or("at an unknown time".to_string(), |t| t.to_rfc3339())
+        );
+    }
+    if let Some(ref truncation) = report.truncated {
+        println!(
+            "⚠️  Truncated: stopped at {}s; cut short: {}",
+            truncation.max_duration_seconds,
+            truncation.stages.join(", ")
+        );
+    }
+    if let Some(truncation) = report.result_truncation.as_ref().filter(|t| t.truncated) {
+        println!(
+            "⚠️  Limited: showing {} of {} unreferenced files, {} of {} partitions, {} of {} recommendations",
+            report.metrics.unreferenced_files.len(),
+            truncation.unreferenced_files_total,
+            report.metrics.partitions.len(),
+            truncation.partitions_total,
+            report.metrics.recommendations.len(),
+            truncation.recommendations_total
+        );
+        if truncation.partition_files_truncated {
+            println!(
+                "⚠️  Limited: {} of {} partition file entries omitted",
+                truncation.partition_files_omitted, truncation.partition_files_total
+            );
+        }
+    }
+    for failure in report.failed_phases.iter().flatten() {
+        println!("⚠️  Partial: {} failed ({})", failure.phase, failure.error);
+    }
+    for regression in report.history_regressions.iter().flatten() {
+        println!(
+            "🚨 Regression ({}): {}",
+            regression.severity, regression.message
+        );
+    }
+    if let Some(ref warm_start) = report.warm_start {
+        if warm_start.used_previous_report {
+            println!(
+                "Warm Start: {} partitions reused, {} listed again{}",
+                warm_start.reused_partitions,
+                warm_start.relisted_partitions,
+                if warm_start.reused_footer_sample {
+                    ", footer sample reused"
+                } else {
+                    ""
+                }
+            );
+        }
+    }
+    if let Some(ref stats) = report.analysis_stats {
+        println!(
+            "S3 Requests: {} LIST, {} GET (+{} cached), {} HEAD, {:.2} MB downloaded in {:.1}s (~{})",
+            stats.list_requests,
+            stats.get_requests,
+            stats.cached_gets,
+            stats.head_requests,
+            stats.bytes_downloaded as f64 / (1024.0 * 1024.0),
+            stats.duration_seconds,
+            config::format_amount(stats.estimated_request_cost, &stats.currency, 4)
+        );
+    }
     println!("{}\n", "=".repeat(60));
 
     // Overall health score
-    let health_emoji = if report.health_score > 0.8 {
+    let health_emoji = if report.health_score > badge::HEALTHY_SCORE {
         "🟢"
-    } else if report.health_score > 0.6 {
+    } else if report.health_score > badge::FAIR_SCORE {
         "🟡"
     } else {
         "🔴"
@@ -129,6 +640,14 @@ fn print_health_report(report: &types::HealthReport) -> PyResult<()> {
         health_emoji,
         report.health_score * 100.0
     );
+    if let Some(ref sub_scores) = report.metrics.sub_scores {
+        println!(
+            "   Storage Cost: {:.1}% | Query Performance: {:.1}% | Reliability: {:.1}%",
+            sub_scores.storage_cost_score * 100.0,
+            sub_scores.query_performance_score * 100.0,
+            sub_scores.reliability_score * 100.0
+        );
+    }
 
     // Key metrics
     println!("\n📊 Key Metrics:");
@@ -148,6 +667,15 @@ fn print_health_report(report: &types::HealthReport) -> PyResult<()> {
     let avg_mb = report.metrics.avg_file_size_bytes / (1024.0 * 1024.0);
     println!("  Average File Size:   {:.2} MB", avg_mb);
     println!("  Partition Count:     {}", report.metrics.partition_count);
+    if let Some(ref size_class) = report.metrics.size_class {
+        println!(
+            "  Size Class:          {} (small files <{}, {} snapshots, {} metadata)",
+            size_class.size_class,
+            size_label(size_class.small_file_bytes),
+            size_class.snapshot_count_limit,
+            size_label(size_class.metadata_bloat_bytes)
+        );
+    }
 
     // File size distribution
     println!("\n📦 File Size Distribution:");
@@ -157,25 +685,44 @@ fn print_health_report(report: &types::HealthReport) -> PyResult<()> {
         (dist.small_files + dist.medium_files + dist.large_files + dist.very_large_files) as f64;
 
     if total_files > 0.0 {
-        println!(
-            "  Small (<16MB):       {:>6} files ({:>5.1}%)",
-            dist.small_files,
-            dist.small_files as f64 / total_files * 100.0
-        );
-        println!(
-            "  Medium (16-128MB):   {:>6} files ({:>5.1}%)",
-            dist.medium_files,
-            dist.medium_files as f64 / total_files * 100.0
-        );
-        println!(
-            "  Large (128MB-1GB):   {:>6} files ({:>5.1}%)",
-            dist.large_files,
-            dist.large_files as f64 / total_files * 100.0
+        let buckets = report.metrics.file_size_buckets();
+        let (small, medium, large) = (
+            buckets.small_bytes,
+            buckets.medium_bytes,
+            buckets.large_bytes,
         );
+        for (label, count) in [
+            (format!("Small (<{}):", size_label(small)), dist.small_files),
+            (
+                format!("Medium ({}-{}):", size_label(small), size_label(medium)),
+                dist.medium_files,
+            ),
+            (
+                format!("Large ({}-{}):", size_label(medium), size_label(large)),
+                dist.large_files,
+            ),
+            (
+                format!("Very Large (>{}):", size_label(large)),
+                dist.very_large_files,
+            ),
+        ] {
+            println!(
+                "  {:<20} {:>6} files ({:>5.1}%)",
+                label,
+                count,
+                count as f64 / total_files * 100.0
+            );
+        }
+    }
+    if let Some(ref histogram) = report.metrics.file_size_histogram {
         println!(
-            "  Very Large (>1GB):   {:>6} files ({:>5.1}%)",
-            dist.very_large_files,
-            dist.very_large_files as f64 / total_files * 100.0
+            "  Percentiles:         p5 {} | p25 {} | p50 {} | p75 {} | p95 {} (max {})",
+            size_label(histogram.p5_bytes),
+            size_label(histogram.p25_bytes),
+            size_label(histogram.p50_bytes),
+            size_label(histogram.p75_bytes),
+            size_label(histogram.p95_bytes),
+            size_label(histogram.max_bytes)
         );
     }
 
@@ -216,6 +763,26 @@ fn print_health_report(report: &types::HealthReport) -> PyResult<()> {
         println!("  Smallest Partition:  {:.2} MB", smallest_mb);
         println!("  Avg Partition Size:  {:.2} MB", avg_mb);
     }
+    if skew.files_with_row_counts > 0 {
+        println!(
+            "  Partition Row Skew:   {:.2} (0=perfect, 1=highly skewed)",
+            skew.partition_row_skew_score
+        );
+        println!(
+            "  File Row Skew:        {:.2} (0=perfect, 1=highly skewed)",
+            skew.file_row_skew_score
+        );
+        if skew.avg_partition_rows > 0 {
+            println!(
+                "  Largest Partition:   {} rows",
+                skew.largest_partition_rows
+            );
+            println!(
+                "  Smallest Partition:  {} rows",
+                skew.smallest_partition_rows
+            );
+        }
+    }
 
     // Metadata health
     println!("\n📋 Metadata Health:");
@@ -262,7 +829,13 @@ fn print_health_report(report: &types::HealthReport) -> PyResult<()> {
     if !report.metrics.unreferenced_files.is_empty() {
         println!("\n⚠️  Unreferenced Files:");
         println!("{}", "─".repeat(60));
-        println!("  Count:  {}", report.metrics.unreferenced_files.len());
+        println!("  Count:  {}", report.unreferenced_file_count());
+        if report.metrics.in_flight_unreferenced_files > 0 {
+            println!(
+                "  Recent: {} (within the grace period, likely in-flight writes)",
+                report.metrics.in_flight_unreferenced_files
+            );
+        }
         let wasted_gb = report.metrics.unreferenced_size_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
         if wasted_gb >= 1.0 {
             println!("  Wasted: {:.2} GB", wasted_gb);
@@ -338,6 +911,25 @@ fn print_health_report(report: &types::HealthReport) -> PyResult<()> {
             "  Current Version:       {}",
             schema_metrics.current_schema_version
         );
+        for change in schema_metrics.schema_changes.iter().rev().take(5) {
+            let detail = match (&change.previous, &change.current) {
+                (Some(previous), Some(current)) => format!(" ({} -> {})", previous, current),
+                (None, Some(current)) => format!(" ({})", current),
+                _ => String::new(),
+            };
+            println!(
+                "    - v{}: {} {}{}{}",
+                change.version,
+                change.change_type,
+                change.column,
+                detail,
+                if change.is_breaking {
+                    " [breaking]"
+                } else {
+                    ""
+                }
+            );
+        }
     }
 
     // Time travel analysis
@@ -373,6 +965,12 @@ fn print_health_report(report: &types::HealthReport) -> PyResult<()> {
             "  Recommended Retention: {} days",
             tt_metrics.recommended_retention_days
         );
+        if let Some(ref oldest) = tt_metrics.oldest_snapshot_timestamp {
+            println!("  Oldest Commit:         {}", oldest.to_rfc3339());
+        }
+        if let Some(ref newest) = tt_metrics.newest_snapshot_timestamp {
+            println!("  Newest Commit:         {}", newest.to_rfc3339());
+        }
     }
 
     // Table constraints analysis
@@ -460,17 +1058,1035 @@ fn print_health_report(report: &types::HealthReport) -> PyResult<()> {
                 compaction_metrics.z_order_columns.join(", ")
             );
         }
+        for candidate in compaction_metrics.z_order_candidates.iter().take(5) {
+            println!(
+                "    • {}: files overlap {:.0}%, {} distinct bounds in {} files",
+                candidate.column,
+                candidate.file_overlap_ratio * 100.0,
+                candidate.distinct_bound_values,
+                candidate.files_with_stats
+            );
+        }
+        let plan = &compaction_metrics.compaction_plan;
+        if !plan.is_empty() {
+            println!(
+                "  Compaction Plan:       {} tasks, {} files → {} jobs",
+                plan.len(),
+                plan.iter().map(|t| t.input_file_count).sum::<usize>(),
+                plan.iter().map(|t| t.estimated_job_count).sum::<usize>()
+            );
+            for task in plan.iter().take(5) {
+                println!(
+                    "    • {}: {} files, {} in {} jobs",
+                    if task.partition.is_empty() {
+                        "(unpartitioned)"
+                    } else {
+                        &task.partition
+                    },
+                    task.input_file_count,
+                    size_label(task.input_size_bytes),
+                    task.estimated_job_count
+                );
+            }
+        }
     }
 
-    // Recommendations
-    if !report.metrics.recommendations.is_empty() {
-        println!("\n💡 Recommendations:");
+    // Protocol and table features (Delta Lake only)
+    if let Some(ref features) = report.metrics.table_features {
+        println!("\n🧩 Table Features:");
         println!("{}", "─".repeat(60));
-        for (i, rec) in report.metrics.recommendations.iter().enumerate() {
-            println!("  {}. {}", i + 1, rec);
+        println!(
+            "  Protocol:              reader v{}, writer v{}",
+            features.min_reader_version, features.min_writer_version
+        );
+        if !features.enabled_features.is_empty() {
+            println!(
+                "  Enabled Features:      {}",
+                features.enabled_features.join(", ")
+            );
+        }
+        if !features.compatibility_blockers.is_empty() {
+            println!(
+                "  Compatibility Risks:   {}",
+                features.compatibility_blockers.join(", ")
+            );
+        }
+    }
+
+    // File attribution to commits/snapshots
+    if let Some(ref attribution) = report.metrics.file_attribution {
+        println!("\n🧾 File Attribution:");
+        println!("{}", "─".repeat(60));
+        println!(
+            "  Attributed Files:      {}",
+            attribution.attributed_file_count
+        );
+        println!(
+            "  Unattributed Files:    {}",
+            attribution.unattributed_file_count
+        );
+        if attribution.tombstoned_file_count > 0 {
+            println!(
+                "  Tombstoned Files:      {} ({:.2} MB)",
+                attribution.tombstoned_file_count,
+                attribution.tombstoned_size_bytes as f64 / (1024.0 * 1024.0)
+            );
+        }
+        for (day, bytes) in attribution.bytes_added_per_day.iter().rev().take(7) {
+            println!(
+                "    {}: {:.2} MB added",
+                day,
+                *bytes as f64 / (1024.0 * 1024.0)
+            );
+        }
+    }
+
+    // Parquet footer sample (deep scan only)
+    if let Some(ref footers) = report.metrics.parquet_footers {
+        println!("\n🔬 Parquet Footers (sampled):");
+        println!("{}", "─".repeat(60));
+        println!(
+            "  Sampled Files:         {} ({} unreadable)",
+            footers.sampled_files, footers.failed_files
+        );
+        println!(
+            "  Row Groups per File:   {:.1}",
+            footers.avg_row_groups_per_file
+        );
+        println!(
+            "  Row Group Size:        {:.2} MB avg ({:.2} - {:.2} MB)",
+            footers.avg_row_group_size_bytes / (1024.0 * 1024.0),
+            footers.min_row_group_size_bytes as f64 / (1024.0 * 1024.0),
+            footers.max_row_group_size_bytes as f64 / (1024.0 * 1024.0)
+        );
+        println!(
+            "  Rows per Row Group:    {:.0}",
+            footers.avg_rows_per_row_group
+        );
+        let mut codecs: Vec<_> = footers.codecs.iter().collect();
+        codecs.sort();
+        for (codec, count) in codecs {
+            println!("    {}: {} column chunks", codec, count);
+        }
+        println!("  Compression Ratio:     {:.2}x", footers.compression_ratio);
+        for column in footers.columns.iter().take(5) {
+            println!(
+                "    {}: {} {:.2}x{}",
+                column.column,
+                column.codec,
+                column.compression_ratio,
+                if column.dictionary_encoded {
+                    ", dictionary"
+                } else {
+                    ""
+                }
+            );
+        }
+        if !footers.high_cardinality_columns.is_empty() {
+            println!(
+                "  High-Cardinality:      {}",
+                footers.high_cardinality_columns.join(", ")
+            );
+            println!(
+                "  Bloom Filters:         {:.1}% of files",
+                footers.bloom_filter_coverage * 100.0
+            );
+            println!(
+                "  Page Indexes:          {:.1}% of files",
+                footers.page_index_coverage * 100.0
+            );
+        }
+        if footers.projected_codec_savings_bytes > 0 {
+            println!(
+                "  zstd Savings (est.):   {:.2} MB",
+                footers.projected_codec_savings_bytes as f64 / (1024.0 * 1024.0)
+            );
+        }
+        if footers.projected_dictionary_savings_bytes > 0 {
+            println!(
+                "  Dictionary Savings:    {:.2} MB (est.)",
+                footers.projected_dictionary_savings_bytes as f64 / (1024.0 * 1024.0)
+            );
+        }
+        println!(
+            "  Statistics Coverage:   {:.1}%",
+            footers.stats_completeness * 100.0
+        );
+        if !footers.columns_missing_stats.is_empty() {
+            println!(
+                "  Missing Statistics:    {}",
+                footers.columns_missing_stats.join(", ")
+            );
+        }
+    }
+
+    // ORC footer sample (deep scan of tables with ORC data files)
+    if let Some(ref footers) = report.metrics.orc_footers {
+        println!("\n🔬 ORC Footers (sampled):");
+        println!("{}", "─".repeat(60));
+        println!(
+            "  Sampled Files:         {} ({} undecoded, {} unreadable)",
+            footers.sampled_files, footers.undecoded_files, footers.failed_files
+        );
+        println!(
+            "  Stripes per File:      {:.1}",
+            footers.avg_stripes_per_file
+        );
+        println!(
+            "  Stripe Size:           {:.2} MB avg ({:.2} - {:.2} MB)",
+            footers.avg_stripe_size_bytes / (1024.0 * 1024.0),
+            footers.min_stripe_size_bytes as f64 / (1024.0 * 1024.0),
+            footers.max_stripe_size_bytes as f64 / (1024.0 * 1024.0)
+        );
+        println!(
+            "  Rows per Stripe:       {:.0}",
+            footers.avg_rows_per_stripe
+        );
+        let mut codecs: Vec<_> = footers.codecs.iter().collect();
+        codecs.sort();
+        for (codec, count) in codecs {
+            println!("    {}: {} files", codec, count);
+        }
+        println!(
+            "  Statistics Coverage:   {:.1}%",
+            footers.stats_completeness * 100.0
+        );
+        if !footers.columns_missing_stats.is_empty() {
+            println!(
+                "  Missing Statistics:    {}",
+                footers.columns_missing_stats.join(", ")
+            );
+        }
+    }
+
+    // Catalog vs physical schema (tables resolved through a catalog only)
+    if let Some(ref drift) = report.metrics.schema_drift {
+        println!("\n🔀 Catalog Schema Drift:");
+        println!("{}", "─".repeat(60));
+        let schema_id = |id: Option<i64>| id.map_or("-".to_string(), |id| id.to_string());
+        println!(
+            "  Schema IDs:            catalog {}, metadata {}",
+            schema_id(drift.catalog_schema_id),
+            schema_id(drift.physical_schema_id)
+        );
+        println!(
+            "  Drift Detected:        {}",
+            if drift.has_drift { "Yes" } else { "No" }
+        );
+        if !drift.columns_missing_from_catalog.is_empty() {
+            println!(
+                "  Not in Catalog:        {}",
+                drift.columns_missing_from_catalog.join(", ")
+            );
+        }
+        if !drift.columns_missing_from_metadata.is_empty() {
+            println!(
+                "  Not in Metadata:       {}",
+                drift.columns_missing_from_metadata.join(", ")
+            );
+        }
+        if !drift.file_columns_missing_from_catalog.is_empty() {
+            println!(
+                "  In Files Only:         {}",
+                drift.file_columns_missing_from_catalog.join(", ")
+            );
+        }
+        for mismatch in &drift.type_mismatches {
+            println!(
+                "    {}: catalog {}, metadata {}",
+                mismatch.column, mismatch.catalog_type, mismatch.physical_type
+            );
+        }
+    }
+
+    // Change Data Feed storage (Delta Lake only)
+    if let Some(ref cdf) = report.metrics.change_data_feed {
+        println!("\n🔁 Change Data Feed:");
+        println!("{}", "─".repeat(60));
+        println!(
+            "  Enabled:               {}",
+            if cdf.is_enabled { "Yes" } else { "No" }
+        );
+        println!("  Change Files:          {}", cdf.change_file_count);
+        let change_mb = cdf.change_size_bytes as f64 / (1024.0 * 1024.0);
+        println!("  Change Data Size:      {:.2} MB", change_mb);
+        println!(
+            "  Oldest Change File:    {:.1} days",
+            cdf.oldest_change_file_age_days
+        );
+        println!("  Retention Window:      {:.1} days", cdf.retention_days);
+        if cdf.is_retention_unbounded {
+            println!(
+                "  Beyond Retention:      {} files ({:.2} MB)",
+                cdf.files_beyond_retention,
+                cdf.bytes_beyond_retention as f64 / (1024.0 * 1024.0)
+            );
+        }
+    }
+
+    // Column mapping (Delta Lake only)
+    if let Some(ref column_mapping) = report.metrics.column_mapping {
+        println!("\n🏷️  Column Mapping:");
+        println!("{}", "─".repeat(60));
+        println!("  Mode:                  {}", column_mapping.mode);
+        if !column_mapping.renamed_columns.is_empty() {
+            println!(
+                "  Renamed Columns:       {}",
+                column_mapping.renamed_columns.join(", ")
+            );
+        }
+        if !column_mapping.dropped_columns.is_empty() {
+            println!(
+                "  Dropped Columns:       {}",
+                column_mapping.dropped_columns.join(", ")
+            );
+            println!(
+                "  Files With Stale Data: {}",
+                column_mapping.files_with_dropped_columns
+            );
+            let reclaimable_mb = column_mapping.reclaimable_bytes as f64 / (1024.0 * 1024.0);
+            println!("  Reclaimable Size:      {:.2} MB", reclaimable_mb);
+        }
+    }
+
+    // Partition layout vs spec
+    if let Some(ref mismatch) = report.metrics.partition_spec_mismatch {
+        println!("\n🧭 Partition Layout Mismatch:");
+        println!("{}", "─".repeat(60));
+        println!(
+            "  Expected Columns:      {}",
+            if mismatch.expected_partition_columns.is_empty() {
+                "(unpartitioned)".to_string()
+            } else {
+                mismatch.expected_partition_columns.join("/")
+            }
+        );
+        println!(
+            "  Mismatched Files:      {}",
+            mismatch.mismatched_file_count
+        );
+        let mismatched_mb = mismatch.mismatched_size_bytes as f64 / (1024.0 * 1024.0);
+        println!("  Mismatched Size:       {:.2} MB", mismatched_mb);
+        for path in mismatch.offending_paths.iter().take(5) {
+            println!("    - {}", path);
+        }
+        if mismatch.offending_paths.len() > 5 {
+            println!("    ... and {} more", mismatch.offending_paths.len() - 5);
+        }
+    }
+
+    // Freshness
+    if let Some(ref freshness) = report.metrics.freshness {
+        println!("\n🕒 Freshness:");
+        println!("{}", "─".repeat(60));
+        let hours = |value: Option<f64>| value.map_or("-".to_string(), |h| format!("{:.1} h", h));
+        println!(
+            "  Since Last Commit:     {}",
+            hours(freshness.hours_since_last_commit)
+        );
+        println!(
+            "  Since Last Write:      {}",
+            hours(freshness.hours_since_last_data_write)
+        );
+        println!(
+            "  Commit Interval:       p50 {} | p90 {} | p99 {}",
+            hours(freshness.commit_interval_p50_hours),
+            hours(freshness.commit_interval_p90_hours),
+            hours(freshness.commit_interval_p99_hours)
+        );
+        if let Some(sla) = freshness.sla_hours {
+            println!(
+                "  SLA:                   {:.1} h ({})",
+                sla,
+                if freshness.is_stale { "STALE" } else { "met" }
+            );
+        }
+    }
+
+    // Table metadata vs storage
+    if let Some(ref log_consistency) = report.metrics.log_consistency {
+        if log_consistency.partitions_from_log || log_consistency.size_mismatched_files > 0 {
+            println!("\n🔎 Log Consistency:");
+            println!("{}", "─".repeat(60));
+            if log_consistency.partitions_from_log {
+                println!("  Partition values come from the transaction log (not in file paths)");
+            }
+            println!(
+                "  Size Mismatches:       {} of {} live files ({} smaller, {} larger)",
+                log_consistency.size_mismatched_files,
+                log_consistency.checked_files,
+                log_consistency.truncated_files,
+                log_consistency.overwritten_files
+            );
+            for mismatch in log_consistency.size_mismatches.iter().take(5) {
+                println!(
+                    "    - {} ({} bytes recorded, {} in storage)",
+                    mismatch.path, mismatch.recorded_size_bytes, mismatch.actual_size_bytes
+                );
+            }
+        }
+    }
+
+    // Multipart uploads never completed or aborted
+    if let Some(ref incomplete_uploads) = report.metrics.incomplete_uploads {
+        if incomplete_uploads.incomplete_uploads > 0 {
+            println!("\n📤 Incomplete Multipart Uploads:");
+            println!("{}", "─".repeat(60));
+            println!(
+                "  Uploads:               {} ({} stale)",
+                incomplete_uploads.incomplete_uploads, incomplete_uploads.stale_uploads
+            );
+            if let Some(age) = incomplete_uploads.oldest_upload_age_days {
+                println!("  Oldest:                {:.1} days", age);
+            }
+            println!(
+                "  Uploaded Parts:        {:.1} MB in {} sized uploads",
+                incomplete_uploads.uploaded_part_bytes as f64 / (1024.0 * 1024.0),
+                incomplete_uploads.sized_uploads
+            );
+            for key in incomplete_uploads.upload_keys.iter().take(5) {
+                println!("    - {}", key);
+            }
+        }
+    }
+
+    // HEAD checks of referenced files
+    if let Some(ref existence_check) = report.metrics.existence_check {
+        println!("\n🩺 Referenced File Check:");
+        println!("{}", "─".repeat(60));
+        println!(
+            "  Missing Files:         {} of {} checked",
+            existence_check.missing_files, existence_check.checked_files
+        );
+        for path in existence_check.missing_paths.iter().take(5) {
+            println!("    - {}", path);
+        }
+        if existence_check.unverified_files > 0 {
+            println!(
+                "  Unverified:            {}",
+                existence_check.unverified_files
+            );
+        }
+        println!(
+            "  Throttled Requests:    {} (settled at {} concurrent)",
+            existence_check.throttled_requests, existence_check.final_concurrency
+        );
+    }
+
+    // Iceberg partition statistics
+    if let Some(ref stats) = report.metrics.partition_statistics {
+        println!("\n📑 Partition Statistics (Iceberg):");
+        println!("{}", "─".repeat(60));
+        println!("  Snapshot:              {}", stats.snapshot_id);
+        if stats.snapshots_behind > 0 {
+            println!(
+                "  Staleness:             {} snapshot(s), {:.1} hours behind",
+                stats.snapshots_behind, stats.staleness_hours
+            );
+        } else {
+            println!("  Staleness:             current");
+        }
+        println!(
+            "  Partitions:            {} ({} with delete files)",
+            stats.partition_count, stats.partitions_with_deletes
+        );
+        println!(
+            "  Records:               {} in {} data files ({:.2} MB)",
+            stats.data_record_count,
+            stats.data_file_count,
+            stats.total_data_file_size_bytes as f64 / (1024.0 * 1024.0)
+        );
+    }
+
+    // Monthly storage cost
+    if let Some(ref cost) = report.metrics.cost {
+        println!("\n💵 Monthly Storage Cost ({}):", cost.pricing);
+        println!("{}", "─".repeat(60));
+        let gb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+        let amount = |amount: f64| config::format_amount(amount, &cost.currency, 2);
+        println!(
+            "  Live Data:             {} ({:.2} GB)",
+            amount(cost.live_data_monthly_cost),
+            gb(cost.live_data_bytes)
+        );
+        println!(
+            "  Time-Travel History:   {} ({:.2} GB)",
+            amount(cost.history_monthly_cost),
+            gb(cost.history_bytes)
+        );
+        println!(
+            "  Orphaned Files:        {} ({:.2} GB)",
+            amount(cost.orphan_monthly_cost),
+            gb(cost.orphan_bytes)
+        );
+        println!(
+            "  Metadata:              {} ({:.2} GB)",
+            amount(cost.metadata_monthly_cost),
+            gb(cost.metadata_bytes)
+        );
+        println!(
+            "  Total:                 {}",
+            amount(cost.total_monthly_cost)
+        );
+    }
+
+    // Storage classes
+    if let Some(ref storage_classes) = report.metrics.storage_classes {
+        println!("\n🧊 Storage Classes:");
+        println!("{}", "─".repeat(60));
+        let mut classes: Vec<(&String, &u64)> = storage_classes.bytes_by_class.iter().collect();
+        classes.sort_by_key(|(_, bytes)| std::cmp::Reverse(**bytes));
+        for (class, bytes) in classes {
+            println!(
+                "  {:<22} {} files ({:.2} MB)",
+                format!("{}:", class),
+                storage_classes.files_by_class.get(class).unwrap_or(&0),
+                *bytes as f64 / (1024.0 * 1024.0)
+            );
+        }
+        if storage_classes.archived_live_files > 0 {
+            println!(
+                "  ⚠️  Archived Live Files: {} ({:.2} MB)",
+                storage_classes.archived_live_files,
+                storage_classes.archived_live_size_bytes as f64 / (1024.0 * 1024.0)
+            );
+            if let Some(ref restore) = storage_classes.restore_status {
+                println!(
+                    "      Restore Status:    {} not restored, {} restoring, {} restored of {} checked",
+                    restore.not_restored_files,
+                    restore.restoring_files,
+                    restore.restored_files,
+                    restore.checked_files
+                );
+                if let Some(expiry) = restore.earliest_expiry {
+                    println!("      Restored Until:    {}", expiry.to_rfc3339());
+                }
+            }
+        }
+        println!(
+            "  IA Candidates:         {} files ({:.2} MB)",
+            storage_classes.ia_eligible_files,
+            storage_classes.ia_eligible_size_bytes as f64 / (1024.0 * 1024.0)
+        );
+    }
+
+    // Data file formats
+    if let Some(ref formats) = report.metrics.file_formats {
+        if formats.is_mixed() {
+            println!("\n🗂️  File Formats:");
+            println!("{}", "─".repeat(60));
+            let mut by_format: Vec<(&String, &u64)> = formats.bytes_by_format.iter().collect();
+            by_format.sort_by_key(|(_, bytes)| std::cmp::Reverse(**bytes));
+            for (format, bytes) in by_format {
+                println!(
+                    "  {:<22} {} files ({:.2} MB)",
+                    format!("{}:", format),
+                    formats.files_by_format.get(format).unwrap_or(&0),
+                    *bytes as f64 / (1024.0 * 1024.0)
+                );
+            }
+            println!("  Mixed Partitions:      {}", formats.mixed_partitions);
+            for path in formats.mixed_partition_paths.iter().take(5) {
+                println!("    - {}", path);
+            }
+            if formats.unsupported_files > 0 {
+                println!(
+                    "  ⚠️  Unreadable by Delta: {} files ({:.2} MB)",
+                    formats.unsupported_files,
+                    formats.unsupported_size_bytes as f64 / (1024.0 * 1024.0)
+                );
+            }
+        }
+    }
+
+    // File naming anomalies
+    if let Some(ref naming) = report.metrics.file_naming {
+        if naming.anomalous_files() > 0 {
+            println!("\n🏷️  File Naming Anomalies:");
+            println!("{}", "─".repeat(60));
+            for (label, count, kind) in [
+                ("Zero-Byte Data Files:", naming.zero_byte_files, "zero_byte"),
+                ("Checksum Files:", naming.checksum_files, "checksum"),
+                ("Temporary Files:", naming.temporary_files, "temporary"),
+                (
+                    "Double Extensions:",
+                    naming.double_extension_files,
+                    "double_extension",
+                ),
+                (
+                    "Unexpected Extensions:",
+                    naming.unexpected_extension_files,
+                    "unexpected_extension",
+                ),
+            ] {
+                if count == 0 {
+                    continue;
+                }
+                println!("  {:<22} {}", label, count);
+                for path in naming.example_paths.get(kind).into_iter().flatten() {
+                    println!("    - {}", path);
+                }
+            }
+        }
+    }
+
+    // Delta UniForm readiness (Delta Lake only, with assess_iceberg_migration)
+    if let Some(ref migration) = report.metrics.iceberg_migration {
+        println!("\n🔁 Iceberg Migration Readiness:");
+        println!("{}", "─".repeat(60));
+        println!(
+            "  Readiness Score:       {:.1}%{}",
+            migration.readiness_score * 100.0,
+            if migration.is_ready { " (ready)" } else { "" }
+        );
+        println!(
+            "  UniForm Enabled:       {}",
+            if migration.uniform_enabled {
+                "yes"
+            } else {
+                "no"
+            }
+        );
+        println!("  Column Mapping:        {}", migration.column_mapping_mode);
+        if let Some(ref path) = migration.iceberg_metadata_path {
+            println!("  Iceberg Metadata:      {}", path);
+        }
+        if let Some(ref version) = migration.converted_delta_version {
+            println!(
+                "  Converted Version:     {} ({} behind)",
+                version,
+                migration.versions_behind.unwrap_or_default()
+            );
+        }
+        for blocker in &migration.blockers {
+            println!("  ✗ {}", blocker);
+        }
+        for warning in &migration.warnings {
+            println!("  ! {}", warning);
+        }
+    }
+
+    // Delta checkpoint cadence and log growth
+    if let Some(ref delta_log) = report.metrics.delta_log {
+        println!("\n📜 Delta Log:");
+        println!("{}", "─".repeat(60));
+        println!(
+            "  Latest Version:        {}{}",
+            delta_log.latest_version,
+            if delta_log.is_pathological {
+                " (log has grown pathologically)"
+            } else {
+                ""
+            }
+        );
+        println!(
+            "  Latest Checkpoint:     {}",
+            delta_log
+                .latest_checkpoint_version
+                .map_or("none".to_string(), |v| v.to_string())
+        );
+        println!(
+            "  Since Checkpoint:      {} commits, {:.1} MB",
+            delta_log.commits_since_checkpoint,
+            delta_log.bytes_since_checkpoint as f64 / (1024.0 * 1024.0)
+        );
+        if let Some(gap) = delta_log.avg_commits_per_checkpoint {
+            println!(
+                "  Commits/Checkpoint:    {:.1} (interval {})",
+                gap, delta_log.checkpoint_interval
+            );
+        }
+        println!(
+            "  JSON Log:              {} files, {:.1} MB",
+            delta_log.json_log_files,
+            delta_log.json_log_size_bytes as f64 / (1024.0 * 1024.0)
+        );
+        if let Some(age) = delta_log.oldest_json_log_age_days {
+            println!(
+                "  Oldest JSON Commit:    {:.1} days (retention {:.0} days)",
+                age, delta_log.log_retention_days
+            );
+        }
+        if let Some(interval) = delta_log.recommended_checkpoint_interval {
+            println!("  Recommended Interval:  {}", interval);
+        }
+        if let Some(days) = delta_log.recommended_log_retention_days {
+            println!("  Recommended Retention: {} days", days);
+        }
+    }
+
+    // Log versions whose data files were already vacuumed
+    if let Some(ref integrity) = report.metrics.time_travel_integrity {
+        if integrity.broken_version_count > 0 {
+            println!("\n⏪ Time Travel:");
+            println!("{}", "─".repeat(60));
+            println!(
+                "  Broken Before Version: {} ({} of versions {}-{})",
+                integrity.earliest_intact_version,
+                integrity.broken_version_count,
+                integrity.oldest_log_version,
+                integrity.latest_version
+            );
+            println!(
+                "  Vacuumed Files:        {}, {:.1} MB",
+                integrity.vacuumed_file_count,
+                integrity.vacuumed_size_bytes as f64 / (1024.0 * 1024.0)
+            );
+            println!(
+                "  Retention:             files {:.0} days, log {:.0} days",
+                integrity.deleted_file_retention_days, integrity.log_retention_days
+            );
+        }
+    }
+
+    // Table properties that differ from best practices
+    if let Some(ref table_properties) = report.metrics.table_properties {
+        if !table_properties.findings.is_empty() {
+            println!("\n🛠️  Table Properties:");
+            println!("{}", "─".repeat(60));
+            for finding in &table_properties.findings {
+                println!(
+                    "  {}: {} → {}",
+                    finding.property,
+                    finding.current.as_deref().unwrap_or("unset"),
+                    finding.recommended
+                );
+            }
+            if let Some(ref statement) = table_properties.statement {
+                println!("  {}", statement);
+            }
+        }
+    }
+
+    // Iceberg format version
+    if let Some(ref format_version) = report.metrics.format_version {
+        println!("\n🧬 Format Version:");
+        println!("{}", "─".repeat(60));
+        println!(
+            "  Format Version:        v{}{}",
+            format_version.format_version,
+            if format_version.upgrade_recommended {
+                " (upgrade to v2 recommended)"
+            } else {
+                ""
+            }
+        );
+        println!(
+            "  Row-Level Deletes:     {}",
+            if format_version.row_level_deletes_supported {
+                "supported"
+            } else {
+                "not supported"
+            }
+        );
+        if format_version.delete_files > 0 {
+            println!("  Delete Files:          {}", format_version.delete_files);
+        }
+        if !format_version.merge_on_read_operations.is_empty() {
+            println!(
+                "  Merge-on-Read:         {}",
+                format_version.merge_on_read_operations.join(", ")
+            );
+        }
+        if format_version.copy_on_write_rewrites > 0 {
+            println!(
+                "  Copy-on-Write Rewrites: {} ({:.2} GB rewritten)",
+                format_version.copy_on_write_rewrites,
+                format_version.copy_on_write_rewritten_bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+            );
+        }
+        if format_version.legacy_manifest_snapshots > 0 {
+            println!(
+                "  Legacy Snapshots:      {} without a manifest list",
+                format_version.legacy_manifest_snapshots
+            );
+        }
+        if !format_version.legacy_metadata_fields.is_empty() {
+            println!(
+                "  Legacy Fields:         {}",
+                format_version.legacy_metadata_fields.join(", ")
+            );
+        }
+    }
+
+    // Empty and truncated data files
+    if let Some(ref integrity) = report.metrics.file_integrity {
+        if integrity.referenced_files() + integrity.unreferenced_files() > 0 {
+            println!("\n🩹 File Integrity:");
+            println!("{}", "─".repeat(60));
+            for (label, zero_byte, truncated, paths) in [
+                (
+                    "Referenced:",
+                    integrity.referenced_zero_byte_files,
+                    integrity.referenced_truncated_files,
+                    &integrity.referenced_paths,
+                ),
+                (
+                    "Unreferenced:",
+                    integrity.unreferenced_zero_byte_files,
+                    integrity.unreferenced_truncated_files,
+                    &integrity.unreferenced_paths,
+                ),
+            ] {
+                if zero_byte + truncated == 0 {
+                    continue;
+                }
+                println!(
+                    "  {:<22} {} zero-byte, {} truncated",
+                    label, zero_byte, truncated
+                );
+                for path in paths.iter().take(5) {
+                    println!("    - {}", path);
+                }
+            }
+        }
+    }
+
+    // The same content stored under several keys
+    if let Some(ref duplicates) = report.metrics.duplicate_files {
+        println!("\n👯 Duplicate Data Files:");
+        println!("{}", "─".repeat(60));
+        println!(
+            "  Duplicated Contents:   {} ({} extra copies, {} committed twice)",
+            duplicates.duplicate_groups,
+            duplicates.duplicate_files,
+            duplicates.live_duplicate_groups
+        );
+        println!(
+            "  Reclaimable:           {:.2} MB",
+            duplicates.reclaimable_bytes as f64 / (1024.0 * 1024.0)
+        );
+        for group in duplicates.groups.iter().take(5) {
+            println!(
+                "    - {} copies of {} bytes ({} live): {}",
+                group.paths.len(),
+                group.size_bytes,
+                group.live_copies,
+                group.paths.join(", ")
+            );
+        }
+    }
+
+    // Access patterns from S3 access logs
+    if let Some(ref access) = report.metrics.access_patterns {
+        println!("\n❄️  Access Patterns:");
+        println!("{}", "─".repeat(60));
+        println!(
+            "  Log Window:            {:.1} days ({} log objects{})",
+            access.window_days(),
+            access.log_objects_read,
+            if access.truncated { ", truncated" } else { "" }
+        );
+        println!("  Table Reads:           {}", access.table_reads);
+        println!(
+            "  Never Read:            {} files ({:.2} MB)",
+            access.never_read_file_count,
+            access.never_read_size_bytes as f64 / (1024.0 * 1024.0)
+        );
+        println!(
+            "  Cold Partitions:       {} ({:.2} MB)",
+            access.cold_partitions.len(),
+            access.cold_partition_size_bytes as f64 / (1024.0 * 1024.0)
+        );
+        for file in access.hot_files.iter().take(5) {
+            println!("    🔥 {} ({} reads)", file.path, file.read_count);
+        }
+    }
+
+    // Partition layout advice
+    if let Some(ref advice) = report.metrics.partitioning_advice {
+        if advice.verdict != "balanced" {
+            println!("\n🗂️  Partitioning Advice:");
+            println!("{}", "─".repeat(60));
+            println!(
+                "  Verdict:               {}",
+                advice.verdict.replace('_', "-")
+            );
+            let mut cardinality: Vec<_> = advice.column_cardinality.iter().collect();
+            cardinality.sort();
+            for (column, distinct) in cardinality {
+                println!("    {}: {} distinct values", column, distinct);
+            }
+            if !advice.columns_to_drop.is_empty() {
+                println!(
+                    "  Drop Columns:          {}",
+                    advice.columns_to_drop.join(", ")
+                );
+            }
+            if !advice.candidate_columns_to_add.is_empty() {
+                println!(
+                    "  Candidate Columns:     {}",
+                    advice.candidate_columns_to_add.join(", ")
+                );
+            }
+            println!(
+                "  Projected Partitions:  {} ({:.2} GB avg)",
+                advice.projected_partition_count,
+                advice.projected_avg_partition_size_bytes / (1024.0 * 1024.0 * 1024.0)
+            );
+        }
+    }
+
+    // Date partition continuity and null partitions
+    if let Some(ref anomalies) = report.metrics.partition_anomalies {
+        println!("\n📅 Partition Anomalies:");
+        println!("{}", "─".repeat(60));
+        if let Some(ref column) = anomalies.date_column {
+            println!(
+                "  Date Column:           {} (by {})",
+                column,
+                anomalies.granularity.as_deref().unwrap_or("day")
+            );
+            println!(
+                "  Date Range:            {} to {}",
+                anomalies.first_partition_date.as_deref().unwrap_or("-"),
+                anomalies.last_partition_date.as_deref().unwrap_or("-")
+            );
+            println!(
+                "  Missing Partitions:    {} (longest gap {})",
+                anomalies.missing_partition_count, anomalies.longest_gap
+            );
+            for missing in anomalies.missing_partitions.iter().take(5) {
+                println!("    - {}", missing);
+            }
+            if anomalies.missing_partition_count > 5 {
+                println!("    ... and {} more", anomalies.missing_partition_count - 5);
+            }
+            if !anomalies.future_partitions.is_empty() {
+                println!(
+                    "  Future-Dated:          {}",
+                    anomalies.future_partitions.join(", ")
+                );
+            }
+        }
+        if !anomalies.null_partitions.is_empty() {
+            println!(
+                "  Null Partitions:       {} ({} files, {:.2} MB)",
+                anomalies.null_partitions.join(", "),
+                anomalies.null_partition_file_count,
+                anomalies.null_partition_size_bytes as f64 / (1024.0 * 1024.0)
+            );
+        }
+    }
+
+    // Current schema
+    if let Some(ref schema) = report.current_schema {
+        println!("\n📐 Current Schema:");
+        println!("{}", "─".repeat(60));
+        for column in &schema.columns {
+            let partition_marker = if schema.partition_columns.contains(&column.name) {
+                " [partition]"
+            } else {
+                ""
+            };
+            println!(
+                "  {:<22} {}{}{}",
+                column.name,
+                column.data_type,
+                if column.nullable { "" } else { " NOT NULL" },
+                partition_marker
+            );
+        }
+    }
+
+    // Policy compliance
+    if let Some(ref compliance) = report.compliance {
+        println!(
+            "\n📏 Policy Compliance{}:",
+            compliance
+                .policy
+                .as_ref()
+                .map(|name| format!(" ({})", name))
+                .unwrap_or_default()
+        );
+        println!("{}", "─".repeat(60));
+        println!(
+            "  {} {} error(s), {} warning(s)",
+            if compliance.compliant {
+                "✅ Compliant:"
+            } else {
+                "❌ Not compliant:"
+            },
+            compliance.errors,
+            compliance.warnings
+        );
+        for rule in &compliance.rules {
+            let status = match rule.passed {
+                Some(true) => "✅",
+                Some(false) if rule.severity == "error" => "❌",
+                Some(false) => "⚠️ ",
+                None => "❔",
+            };
+            let value = rule
+                .value
+                .map(|value| format!("{:.4}", value))
+                .unwrap_or_else(|| "n/a".to_string());
+            println!(
+                "  {} {} ({}; value {})",
+                status, rule.name, rule.rule, value
+            );
+        }
+    }
+
+    // Recommendations
+    if !report.metrics.recommendations.is_empty() {
+        println!("\n💡 Recommendations:");
+        println!("{}", "─".repeat(60));
+        for (i, rec) in report.metrics.recommendations.iter().enumerate() {
+            println!("  {}. {}", i + 1, rec);
+        }
+    } else {
+        println!("\n✅ No recommendations - table is in excellent health!");
+    }
+
+    // Estimated cost of the remediations
+    if !report.metrics.remediation_estimates.is_empty() {
+        println!("\n⏱️  Remediation Estimates:");
+        println!("{}", "─".repeat(60));
+        for estimate in &report.metrics.remediation_estimates {
+            println!(
+                "  {} ({}): {:.1} min on {}, {:.2} core-hours, {} effort",
+                estimate.action,
+                estimate.operation,
+                estimate.estimated_runtime_seconds / 60.0,
+                estimate.engine,
+                estimate.estimated_core_hours,
+                estimate.effort
+            );
+            println!(
+                "    Rewrite {} files ({:.2} MB), delete {} files, saves {:.2} MB",
+                estimate.files_to_rewrite,
+                estimate.bytes_to_rewrite as f64 / (1024.0 * 1024.0),
+                estimate.files_to_delete,
+                estimate.bytes_saved as f64 / (1024.0 * 1024.0)
+            );
+        }
+    }
+
+    // Engine file limits
+    if let Some(ref limits) = report.metrics.engine_limits {
+        if limits.table_exceeds_limit || limits.partitions_over_limit > 0 {
+            println!("\n🚧 Engine File Limits ({}):", limits.engine);
+            println!("{}", "─".repeat(60));
+            println!("  Files per Query Limit: ~{}", limits.max_files_per_query);
+            println!(
+                "  Table Files:           {}{}",
+                limits.table_files,
+                if limits.table_exceeds_limit {
+                    " (over the limit)"
+                } else {
+                    ""
+                }
+            );
+            println!(
+                "  Avg Files/Partition:   {:.0}",
+                limits.avg_files_per_partition
+            );
+            println!("  Partitions Over Limit: {}", limits.partitions_over_limit);
+            for path in limits.partitions_over_limit_paths.iter().take(5) {
+                println!("    - {}", path);
+            }
         }
-    } else {
-        println!("\n✅ No recommendations - table is in excellent health!");
     }
 
     println!("\n{}\n", "=".repeat(60));
@@ -0,0 +1,220 @@
+//! A provided file listing (`listing=`) used instead of listing the table's storage.
+//!
+//! The listing can be a CSV file with a header row, a Parquet file, or (from Python) any
+//! iterable of `(key, size, last_modified)` tuples or dicts with those keys. It allows
+//! analyzing from a pre-captured inventory or a metadata dump from production, and scoring
+//! real-world layouts in tests. Only the listing is replaced: the transaction log, manifests
+//! and footers are still read from storage.
+//!
+//! Keys may be `s3://` URLs, keys in the bucket, or paths relative to the table root.
+
+use crate::s3_client::ObjectInfo;
+use chrono::{DateTime, Utc};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::Field;
+use serde_json::Value;
+use std::path::Path;
+
+/// Entry fields accepted for the modification time
+const MODIFIED_FIELDS: [&str; 2] = ["last_modified", "mtime"];
+/// File columns accepted for the key and the modification time
+const KEY_COLUMNS: [&str; 2] = ["key", "path"];
+const MODIFIED_COLUMNS: [&str; 4] = ["last_modified", "last_modified_date", "mtime", "modified"];
+
+/// Whether file column `column` is one of `names`, ignoring case and underscores
+/// (`LastModified` is `last_modified`)
+fn is_column(column: &str, names: &[&str]) -> bool {
+    let column = column.trim().replace('_', "").to_lowercase();
+    names.iter().any(|name| name.replace('_', "") == column)
+}
+
+fn parse_time(text: &str) -> Result<DateTime<Utc>, String> {
+    text.parse::<DateTime<Utc>>()
+        .map_err(|e| format!("Invalid last_modified {}: {}", text, e))
+}
+
+fn object(key: String, size: i64, last_modified: Option<DateTime<Utc>>) -> ObjectInfo {
+    ObjectInfo {
+        key,
+        size,
+        last_modified,
+        etag: None,
+        storage_class: None,
+    }
+}
+
+/// Objects in a CSV listing with `key`, `size` and optionally `last_modified` columns
+fn parse_csv(content: &str) -> Result<Vec<ObjectInfo>, String> {
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+    let header = crate::inventory::csv_fields(lines.next().unwrap_or_default());
+    let column = |names: &[&str]| header.iter().position(|c| is_column(c, names));
+    let (Some(key), Some(size)) = (column(&KEY_COLUMNS), column(&["size"])) else {
+        return Err("A CSV listing needs key and size columns".to_string());
+    };
+    let modified = column(&MODIFIED_COLUMNS);
+
+    lines
+        .map(|line| {
+            let fields = crate::inventory::csv_fields(line);
+            let field = |index: usize| fields.get(index).map(|f| f.trim()).unwrap_or_default();
+            let size = field(size)
+                .parse()
+                .map_err(|_| format!("Invalid size in listing line: {}", line))?;
+            let last_modified = match modified.map(field) {
+                Some(text) if !text.is_empty() => Some(parse_time(text)?),
+                _ => None,
+            };
+            Ok(object(field(key).to_string(), size, last_modified))
+        })
+        .collect()
+}
+
+/// Objects in a Parquet listing with `key`, `size` and optionally `last_modified` columns
+fn parse_parquet(content: Vec<u8>) -> Result<Vec<ObjectInfo>, String> {
+    let reader =
+        SerializedFileReader::new(bytes::Bytes::from(content)).map_err(|e| e.to_string())?;
+    let mut objects = Vec::new();
+    for row in reader.get_row_iter(None).map_err(|e| e.to_string())? {
+        let row = row.map_err(|e| e.to_string())?;
+        let mut listed = object(String::new(), 0, None);
+        for (name, field) in row.get_column_iter() {
+            match (name, field) {
+                (name, Field::Str(key)) if is_column(name, &KEY_COLUMNS) => {
+                    listed.key = key.clone();
+                }
+                (name, Field::Long(size)) if is_column(name, &["size"]) => listed.size = *size,
+                (name, Field::Int(size)) if is_column(name, &["size"]) => {
+                    listed.size = i64::from(*size);
+                }
+                (name, field) if is_column(name, &MODIFIED_COLUMNS) => {
+                    listed.last_modified = match field {
+                        Field::TimestampMillis(millis) => DateTime::from_timestamp_millis(*millis),
+                        Field::TimestampMicros(micros) => DateTime::from_timestamp_micros(*micros),
+                        Field::Str(text) => Some(parse_time(text)?),
+                        _ => None,
+                    };
+                }
+                _ => {}
+            }
+        }
+        if !listed.key.is_empty() {
+            objects.push(listed);
+        }
+    }
+    Ok(objects)
+}
+
+/// Objects in the listing file at `path`, CSV or (by its `.parquet` extension) Parquet
+pub fn read_file(path: &Path) -> Result<Vec<ObjectInfo>, String> {
+    let invalid = |e: String| format!("Invalid listing {}: {}", path.display(), e);
+    let content = std::fs::read(path).map_err(|e| invalid(e.to_string()))?;
+    let is_parquet = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("parquet"));
+    match is_parquet {
+        true => parse_parquet(content),
+        false => parse_csv(&String::from_utf8_lossy(&content)),
+    }
+    .map_err(invalid)
+}
+
+/// A listing given as JSON: a file path, or an array of `{"key", "size", "last_modified"}`
+/// objects or `[key, size, last_modified]` arrays
+pub fn from_json(value: &Value) -> Result<Vec<ObjectInfo>, String> {
+    if let Some(path) = value.as_str() {
+        return read_file(Path::new(path));
+    }
+    let entries = value
+        .as_array()
+        .ok_or_else(|| format!("Invalid value for listing: {}", value))?;
+    entries
+        .iter()
+        .map(|entry| {
+            let (key, size, modified) = match entry {
+                Value::Object(fields) => (
+                    fields.get("key"),
+                    fields.get("size"),
+                    MODIFIED_FIELDS.iter().find_map(|name| fields.get(*name)),
+                ),
+                Value::Array(fields) => (fields.first(), fields.get(1), fields.get(2)),
+                _ => (None, None, None),
+            };
+            let invalid = || format!("Invalid listing entry: {}", entry);
+            let last_modified = match modified {
+                Some(Value::String(text)) => Some(parse_time(text)?),
+                None | Some(Value::Null) => None,
+                Some(_) => return Err(invalid()),
+            };
+            Ok(object(
+                key.and_then(Value::as_str).ok_or_else(invalid)?.to_string(),
+                size.and_then(Value::as_i64).ok_or_else(invalid)?,
+                last_modified,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::s3_client::S3ClientWrapper;
+    use aws_sdk_s3::config::Region;
+
+    #[test]
+    fn test_listing_replaces_storage_listing() {
+        let csv = concat!(
+            "Key,Size,LastModified\n",
+            "s3://lake/events/_delta_log/00000000000000000000.json,812,2024-01-01T00:00:00Z\n",
+            "events/date=2024-01-01/part-0.parquet,1048576,2024-01-01T00:00:00Z\n",
+            "date=2024-01-02/part-1.parquet,2048,\n",
+        );
+        let listing = parse_csv(csv).unwrap();
+        assert_eq!(listing[1].size, 1048576);
+        assert!(listing[2].last_modified.is_none());
+        assert!(parse_csv("path,bytes\na,1\n").is_err());
+
+        let json = serde_json::json!([
+            { "key": "date=2024-01-03/part-2.parquet", "size": 10, "mtime": "2024-01-03T00:00:00Z" },
+            ["date=2024-01-04/part-3.parquet", 20],
+        ]);
+        let from_json = from_json(&json).unwrap();
+        assert_eq!(
+            from_json[0].last_modified,
+            listing[0]
+                .last_modified
+                .map(|t| t + chrono::Duration::days(2))
+        );
+        assert!(super::from_json(&serde_json::json!([["a.parquet"]])).is_err());
+
+        let config = aws_sdk_s3::Config::builder()
+            .region(Region::new("us-east-1"))
+            .build();
+        let client =
+            S3ClientWrapper::with_client(aws_sdk_s3::Client::from_conf(config), "s3://lake/events")
+                .unwrap()
+                .with_listing(Some(&listing.into_iter().chain(from_json).collect()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let keys = |prefix: &str| -> Vec<String> {
+            runtime
+                .block_on(client.list_objects(prefix))
+                .unwrap()
+                .into_iter()
+                .map(|object| object.key)
+                .collect()
+        };
+        assert_eq!(
+            keys("events/_delta_log/"),
+            ["events/_delta_log/00000000000000000000.json"]
+        );
+        assert_eq!(
+            keys("events/date=2024-01-0"),
+            [
+                "events/date=2024-01-01/part-0.parquet",
+                "events/date=2024-01-02/part-1.parquet",
+                "events/date=2024-01-03/part-2.parquet",
+                "events/date=2024-01-04/part-3.parquet",
+            ]
+        );
+        assert_eq!(client.requests.snapshot(), (0, 0, 0, 0));
+    }
+}
@@ -0,0 +1,256 @@
+//! `tracing` records forwarded to Python's `logging` module.
+//!
+//! Analyses open a span per analysis and per phase, and storage requests open a debug-level
+//! span with the key and bytes read; closing a span logs its duration. Each record goes to
+//! the Python logger named after its target (`drainage.iceberg`, `aws_smithy_client`, ...),
+//! so the usual handlers and per-logger levels apply. Records below `set_log_level` (WARNING
+//! by default) are dropped before the GIL is taken, so quiet analyses pay nothing for them.
+
+use pyo3::prelude::*;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+use std::time::Instant;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Filter, Layer};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+
+const WARNING: u8 = 30;
+/// Python level at or above which records are forwarded
+static LEVEL: AtomicU8 = AtomicU8::new(WARNING);
+
+/// Python `logging` level of a tracing level (TRACE below DEBUG)
+fn python_level(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 40,
+        Level::WARN => 30,
+        Level::INFO => 20,
+        Level::DEBUG => 10,
+        Level::TRACE => 5,
+    }
+}
+
+/// Parse a level name ("DEBUG", "info", "OFF", ...) as Python `logging` levels
+pub fn parse_level(name: &str) -> Option<u8> {
+    match name.to_uppercase().as_str() {
+        "TRACE" => Some(5),
+        "DEBUG" => Some(10),
+        "INFO" => Some(20),
+        "WARN" | "WARNING" => Some(30),
+        "ERROR" => Some(40),
+        "CRITICAL" => Some(50),
+        "OFF" => Some(u8::MAX),
+        _ => None,
+    }
+}
+
+pub fn set_level(level: u8) {
+    LEVEL.store(level, Ordering::Relaxed);
+}
+
+fn enabled(level: &Level) -> bool {
+    python_level(level) >= LEVEL.load(Ordering::Relaxed)
+}
+
+/// Where records go once `install` ran (only the Python module does)
+static EMIT: OnceLock<fn(&str, u8, String)> = OnceLock::new();
+
+/// Route `tracing` records to Python `logging`; later calls do nothing
+pub fn install() {
+    if EMIT.set(to_python).is_ok() {
+        // Another global subscriber (e.g. set up by an embedding application) takes precedence
+        let _ = tracing::subscriber::set_global_default(
+            tracing_subscriber::registry().with(python_layer()),
+        );
+    }
+}
+
+/// The layer forwarding records at or above `set_log_level` to Python `logging`, for
+/// subscribers that combine it with other layers; None outside the Python module
+pub fn python_layer<S>() -> Option<impl Layer<S>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    EMIT.get()
+        .map(|emit| LogLayer { emit: *emit }.with_filter(PythonLevel))
+}
+
+/// Enables records at or above `set_log_level`
+struct PythonLevel;
+
+impl<S> Filter<S> for PythonLevel {
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: &Context<'_, S>) -> bool {
+        enabled(metadata.level())
+    }
+
+    // The level can change at runtime, so no callsite is cached as always or never enabled
+    fn callsite_enabled(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        Interest::sometimes()
+    }
+}
+
+/// `message` and `key=value` pairs of an event or span
+#[derive(Default)]
+struct Fields {
+    message: String,
+    pairs: String,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            let _ = write!(self.pairs, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            let _ = write!(self.pairs, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// Fields and start time of an open span
+struct SpanData {
+    fields: Fields,
+    started: Instant,
+}
+
+/// Log `message` at Python `level` on the logger named after `target`
+fn to_python(target: &str, level: u8, message: String) {
+    let logger = target.replace("::", ".");
+    // Logging must never fail an analysis
+    let _ = Python::with_gil(|py| -> PyResult<()> {
+        py.import("logging")?
+            .call_method1("getLogger", (logger,))?
+            .call_method1("log", (level, message))?;
+        Ok(())
+    });
+}
+
+/// Formats records and passes them to `emit` (target, Python level, message)
+struct LogLayer {
+    emit: fn(&str, u8, String),
+}
+
+impl<S> Layer<S> for LogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanData {
+                fields,
+                started: Instant::now(),
+            });
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(data) = span.extensions_mut().get_mut::<SpanData>() {
+                values.record(&mut data.fields);
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let mut message = String::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                let extensions = span.extensions();
+                let span_fields = extensions
+                    .get::<SpanData>()
+                    .map(|data| data.fields.pairs.trim_start())
+                    .unwrap_or_default();
+                let _ = write!(message, "{}{{{}}}: ", span.name(), span_fields);
+            }
+        }
+        message.push_str(&fields.message);
+        message.push_str(&fields.pairs);
+        let metadata = event.metadata();
+        (self.emit)(
+            metadata.target(),
+            python_level(metadata.level()),
+            crate::sanitize::sanitize(&message),
+        );
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let extensions = span.extensions();
+        let Some(data) = extensions.get::<SpanData>() else {
+            return;
+        };
+        let metadata = span.metadata();
+        let message = format!(
+            "{} finished in {:.1} ms{}",
+            span.name(),
+            data.started.elapsed().as_secs_f64() * 1000.0,
+            data.fields.pairs
+        );
+        (self.emit)(
+            metadata.target(),
+            python_level(metadata.level()),
+            crate::sanitize::sanitize(&message),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    static RECORDS: Mutex<Vec<(String, u8, String)>> = Mutex::new(Vec::new());
+
+    fn collect(target: &str, level: u8, message: String) {
+        RECORDS
+            .lock()
+            .unwrap()
+            .push((target.to_string(), level, message));
+    }
+
+    #[test]
+    fn test_log_layer_formats_events_and_span_durations() {
+        assert_eq!(parse_level("warning"), Some(30));
+        assert_eq!(parse_level("verbose"), None);
+
+        let subscriber = tracing_subscriber::registry()
+            .with(LogLayer { emit: collect }.with_filter(PythonLevel));
+        tracing::subscriber::with_default(subscriber, || {
+            set_level(10);
+            let span = tracing::info_span!("phase", phase = "listing");
+            let _entered = span.enter();
+            tracing::debug!(key = "a/b.parquet", bytes = 42, "fetched object");
+            tracing::trace!("below the level, dropped");
+        });
+        set_level(WARNING);
+
+        let records = RECORDS.lock().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].0, "drainage::logging::tests");
+        assert_eq!(records[0].1, 10);
+        assert_eq!(
+            records[0].2,
+            "phase{phase=listing}: fetched object key=a/b.parquet bytes=42"
+        );
+        assert_eq!(records[1].1, 20);
+        assert!(records[1].2.starts_with("phase finished in "));
+        assert!(records[1].2.ends_with(" ms phase=listing"));
+    }
+}
@@ -0,0 +1,274 @@
+//! Delta Lake to Iceberg readiness (`assess_iceberg_migration`): whether Delta UniForm can
+//! generate Iceberg metadata for the table, and what blocks it.
+//!
+//! UniForm (IcebergCompatV2) needs column mapping and rejects deletion vectors, and Iceberg
+//! has no equivalent for some Delta types. Blockers must be fixed before UniForm can be
+//! enabled; warnings are conversions that work but change what Iceberg readers see.
+
+use crate::types::{DeletionVectorMetrics, IcebergMigrationMetrics};
+use serde_json::Value;
+
+/// Properties Delta UniForm writes into the Iceberg metadata it generates, holding the Delta
+/// version converted
+const CONVERTED_VERSION_PROPERTIES: [&str; 2] = ["delta-version", "converted_delta_version"];
+
+const BLOCKER_PENALTY: f64 = 0.3;
+const WARNING_PENALTY: f64 = 0.05;
+
+/// The Delta version an Iceberg `metadata.json` was generated from, if UniForm wrote it
+pub fn converted_delta_version(metadata: &Value) -> Option<String> {
+    let properties = metadata.get("properties")?;
+    let version = CONVERTED_VERSION_PROPERTIES
+        .iter()
+        .find_map(|name| properties.get(*name))?;
+    Some(version.as_str().map_or(version.to_string(), str::to_string))
+}
+
+/// Columns whose types Iceberg can't represent (`unsupported`) or represents with a wider type
+/// (`widened`), as "path (type)", walking nested types
+fn check_type(
+    path: &str,
+    data_type: &Value,
+    unsupported: &mut Vec<String>,
+    widened: &mut Vec<String>,
+) {
+    match data_type {
+        Value::String(name) => match name.as_str() {
+            // Iceberg has no void type, and format version 2 no variant
+            "void" | "variant" => unsupported.push(format!("{} ({})", path, name)),
+            "byte" | "short" => widened.push(format!("{} ({})", path, name)),
+            _ => {}
+        },
+        Value::Object(nested) => match nested.get("type").and_then(Value::as_str) {
+            Some("struct") => {
+                for field in nested
+                    .get("fields")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    let name = field.get("name").and_then(Value::as_str).unwrap_or("?");
+                    let field_path = match path {
+                        "" => name.to_string(),
+                        _ => format!("{}.{}", path, name),
+                    };
+                    if let Some(field_type) = field.get("type") {
+                        check_type(&field_path, field_type, unsupported, widened);
+                    }
+                }
+            }
+            Some("array") => {
+                if let Some(element) = nested.get("elementType") {
+                    check_type(&format!("{}.element", path), element, unsupported, widened);
+                }
+            }
+            Some("map") => {
+                for (part, key) in [("key", "keyType"), ("value", "valueType")] {
+                    if let Some(part_type) = nested.get(key) {
+                        check_type(
+                            &format!("{}.{}", path, part),
+                            part_type,
+                            unsupported,
+                            widened,
+                        );
+                    }
+                }
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+/// Readiness of a Delta table for UniForm Iceberg, from its latest `metaData` and protocol
+/// actions, its deletion vectors and, when the table already has Iceberg metadata, the newest
+/// `metadata.json` (key and content)
+pub fn assess(
+    metadata: Option<&Value>,
+    protocol: Option<&Value>,
+    deletion_vectors: Option<&DeletionVectorMetrics>,
+    iceberg_metadata: Option<(&str, &Value)>,
+    current_version: Option<u64>,
+) -> IcebergMigrationMetrics {
+    let configuration = metadata.and_then(|m| m.get("configuration"));
+    let property = |key: &str| {
+        configuration
+            .and_then(|c| c.get(key))
+            .and_then(Value::as_str)
+            .map(str::to_lowercase)
+    };
+    let has_feature = |feature: &str| {
+        ["readerFeatures", "writerFeatures"].iter().any(|list| {
+            protocol
+                .and_then(|p| p.get(*list))
+                .and_then(Value::as_array)
+                .is_some_and(|features| features.iter().any(|f| f.as_str() == Some(feature)))
+        })
+    };
+
+    let mut blockers = Vec::new();
+    let mut warnings = Vec::new();
+
+    let deletion_vector_count = deletion_vectors.map_or(0, |dv| dv.deletion_vector_count);
+    let deletion_vectors_enabled = property("delta.enableDeletionVectors").as_deref()
+        == Some("true")
+        || has_feature("deletionVectors")
+        || deletion_vector_count > 0;
+    if deletion_vectors_enabled {
+        blockers.push(match deletion_vector_count {
+            0 => "Deletion vectors are enabled; UniForm Iceberg requires them disabled".to_string(),
+            count => format!(
+                "{} deletion vectors in the log; UniForm Iceberg requires them disabled and purged",
+                count
+            ),
+        });
+    }
+
+    let column_mapping_mode =
+        property("delta.columnMapping.mode").unwrap_or_else(|| "none".to_string());
+    if column_mapping_mode == "none" {
+        blockers.push(
+            "Column mapping is off; UniForm Iceberg requires column mapping mode name or id"
+                .to_string(),
+        );
+    }
+
+    let schema: Option<Value> = metadata
+        .and_then(|m| m.get("schemaString"))
+        .and_then(Value::as_str)
+        .and_then(|schema| serde_json::from_str(schema).ok());
+    let (mut unsupported_columns, mut widened_columns) = (Vec::new(), Vec::new());
+    if let Some(ref schema) = schema {
+        check_type("", schema, &mut unsupported_columns, &mut widened_columns);
+    }
+    if !unsupported_columns.is_empty() {
+        blockers.push(format!(
+            "Iceberg can't represent columns {}",
+            unsupported_columns.join(", ")
+        ));
+    }
+    if !widened_columns.is_empty() {
+        warnings.push(format!(
+            "Columns {} are widened to int in Iceberg",
+            widened_columns.join(", ")
+        ));
+    }
+
+    if has_feature("typeWidening") || has_feature("typeWidening-preview") {
+        warnings.push(
+            "Type widening is enabled; Iceberg readers only see widened types once affected files are rewritten"
+                .to_string(),
+        );
+    }
+
+    let uniform_enabled = property("delta.universalFormat.enabledFormats")
+        .is_some_and(|formats| formats.split(',').any(|f| f.trim() == "iceberg"));
+    let converted_version =
+        iceberg_metadata.and_then(|(_, content)| converted_delta_version(content));
+    let versions_behind = match (converted_version.as_deref(), current_version) {
+        (Some(converted), Some(current)) => converted
+            .parse::<u64>()
+            .ok()
+            .map(|converted| current.saturating_sub(converted)),
+        _ => None,
+    };
+    if uniform_enabled && iceberg_metadata.is_none() {
+        warnings.push(
+            "UniForm is enabled but no Iceberg metadata was found; the conversion may be failing"
+                .to_string(),
+        );
+    }
+    if versions_behind.is_some_and(|behind| behind > 0) {
+        warnings.push(format!(
+            "The Iceberg metadata is {} Delta versions behind",
+            versions_behind.unwrap_or_default()
+        ));
+    }
+
+    let readiness_score =
+        (1.0 - BLOCKER_PENALTY * blockers.len() as f64 - WARNING_PENALTY * warnings.len() as f64)
+            .max(0.0);
+    IcebergMigrationMetrics {
+        readiness_score,
+        is_ready: blockers.is_empty(),
+        uniform_enabled,
+        deletion_vectors_enabled,
+        column_mapping_mode,
+        unsupported_columns,
+        iceberg_metadata_path: iceberg_metadata.map(|(key, _)| key.to_string()),
+        converted_delta_version: converted_version,
+        versions_behind,
+        blockers,
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn metadata(configuration: Value, fields: Value) -> Value {
+        json!({
+            "schemaString": json!({"type": "struct", "fields": fields}).to_string(),
+            "configuration": configuration,
+        })
+    }
+
+    #[test]
+    fn test_assess_blockers_and_existing_uniform_metadata() {
+        let fields = json!([
+            {"name": "id", "type": "long", "nullable": false, "metadata": {}},
+            {"name": "flags", "type": "byte", "nullable": true, "metadata": {}},
+            {"name": "attrs", "type": {"type": "map", "keyType": "string", "valueType": "void", "valueContainsNull": true}, "nullable": true, "metadata": {}},
+        ]);
+        let protocol = json!({
+            "minReaderVersion": 3,
+            "minWriterVersion": 7,
+            "readerFeatures": ["deletionVectors"],
+            "writerFeatures": ["deletionVectors"],
+        });
+        let blocked = assess(
+            Some(&metadata(json!({}), fields.clone())),
+            Some(&protocol),
+            None,
+            None,
+            Some(4),
+        );
+        assert!(!blocked.is_ready);
+        assert_eq!(blocked.blockers.len(), 3);
+        assert!(blocked.deletion_vectors_enabled);
+        assert!(blocked.blockers[1].starts_with("Column mapping is off"));
+        assert_eq!(blocked.unsupported_columns, ["attrs.value (void)"]);
+        assert_eq!(
+            blocked.warnings,
+            ["Columns flags (byte) are widened to int in Iceberg"]
+        );
+        assert!((blocked.readiness_score - 0.05).abs() < 1e-9);
+
+        let configuration = json!({
+            "delta.columnMapping.mode": "name",
+            "delta.enableIcebergCompatV2": "true",
+            "delta.universalFormat.enabledFormats": "iceberg",
+        });
+        let fields = json!([{"name": "id", "type": "long", "nullable": false, "metadata": {}}]);
+        let iceberg = json!({"format-version": 2, "properties": {"delta-version": "10"}});
+        let ready = assess(
+            Some(&metadata(configuration, fields)),
+            Some(
+                &json!({"minReaderVersion": 2, "minWriterVersion": 7, "writerFeatures": ["icebergCompatV2", "columnMapping"]}),
+            ),
+            None,
+            Some(("metadata/v3.metadata.json", &iceberg)),
+            Some(12),
+        );
+        assert!(ready.is_ready);
+        assert!(ready.uniform_enabled);
+        assert_eq!(ready.converted_delta_version.as_deref(), Some("10"));
+        assert_eq!(ready.versions_behind, Some(2));
+        assert_eq!(
+            ready.warnings,
+            ["The Iceberg metadata is 2 Delta versions behind"]
+        );
+    }
+}
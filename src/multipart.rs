@@ -0,0 +1,148 @@
+//! Incomplete multipart uploads (`check_multipart_uploads=True`): uploads under the table
+//! prefix that a writer started but never completed or aborted.
+//!
+//! Their parts are billed as storage but appear in no listing, so the table's own metrics
+//! never count them. Uploads younger than a week may still be running; older ones are
+//! abandoned, and only an `AbortIncompleteMultipartUpload` lifecycle rule (or an explicit
+//! abort) removes them. Sizing an upload takes a ListParts call per upload, so only the
+//! oldest 100 are sized.
+
+use crate::s3_client::{relative_key, S3ClientWrapper, UploadInfo};
+use crate::types::IncompleteUploadMetrics;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Uploads initiated longer ago than this are treated as abandoned
+pub const STALE_UPLOAD_DAYS: u32 = 7;
+const MAX_SIZED_UPLOADS: usize = 100;
+const SIZE_CONCURRENCY: usize = 16;
+const MAX_LISTED_UPLOADS: usize = 100;
+
+/// S3 lifecycle configuration aborting uploads under `prefix` left incomplete for `days`
+pub fn lifecycle_rule(prefix: &str, days: u32) -> String {
+    let prefix = match prefix.trim_end_matches('/') {
+        "" => String::new(),
+        prefix => format!("{}/", prefix),
+    };
+    json!({
+        "Rules": [{
+            "ID": "abort-incomplete-multipart-uploads",
+            "Status": "Enabled",
+            "Filter": {"Prefix": prefix},
+            "AbortIncompleteMultipartUpload": {"DaysAfterInitiation": days},
+        }]
+    })
+    .to_string()
+}
+
+/// Summarize `uploads`; `sizes` holds the part bytes of the uploads that were sized, by
+/// upload id
+fn summarize(
+    uploads: &[UploadInfo],
+    sizes: &HashMap<String, u64>,
+    table_prefix: &str,
+    now: DateTime<Utc>,
+) -> IncompleteUploadMetrics {
+    let ages: Vec<f64> = uploads
+        .iter()
+        .filter_map(|upload| upload.initiated)
+        .map(|initiated| (now - initiated).num_seconds().max(0) as f64 / 86_400.0)
+        .collect();
+    let stale_uploads = ages
+        .iter()
+        .filter(|age| **age > STALE_UPLOAD_DAYS as f64)
+        .count();
+
+    IncompleteUploadMetrics {
+        incomplete_uploads: uploads.len(),
+        stale_uploads,
+        oldest_upload_age_days: ages.iter().copied().reduce(f64::max),
+        sized_uploads: sizes.len(),
+        uploaded_part_bytes: sizes.values().sum(),
+        upload_keys: uploads
+            .iter()
+            .take(MAX_LISTED_UPLOADS)
+            .map(|upload| relative_key(table_prefix, &upload.key).to_string())
+            .collect(),
+        lifecycle_rule: (stale_uploads > 0)
+            .then(|| lifecycle_rule(table_prefix, STALE_UPLOAD_DAYS)),
+    }
+}
+
+/// List the incomplete multipart uploads under the table and size the oldest of them
+pub async fn check_uploads(
+    s3_client: &S3ClientWrapper,
+    now: DateTime<Utc>,
+) -> Result<IncompleteUploadMetrics> {
+    let table_prefix = s3_client.get_prefix();
+    let mut uploads = s3_client
+        .list_multipart_uploads(&format!("{}/", table_prefix.trim_end_matches('/')))
+        .await?;
+    uploads.sort_by(|a, b| a.initiated.cmp(&b.initiated).then(a.key.cmp(&b.key)));
+
+    let sizes: HashMap<String, u64> =
+        futures::stream::iter(uploads.iter().take(MAX_SIZED_UPLOADS).cloned().map(
+            |upload| async move {
+                let size = s3_client
+                    .multipart_upload_size(&upload.key, &upload.upload_id)
+                    .await?;
+                anyhow::Ok((upload.upload_id, size))
+            },
+        ))
+        .buffer_unordered(SIZE_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<_>>()?;
+
+    Ok(summarize(&uploads, &sizes, table_prefix, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_flags_stale_uploads() {
+        let now: DateTime<Utc> = "2024-03-01T00:00:00Z".parse().unwrap();
+        let upload = |key: &str, id: &str, initiated: &str| UploadInfo {
+            key: key.to_string(),
+            upload_id: id.to_string(),
+            initiated: Some(initiated.parse().unwrap()),
+        };
+        let uploads = [
+            upload(
+                "lake/t/date=2024-01-01/part-0.parquet",
+                "a",
+                "2024-01-01T00:00:00Z",
+            ),
+            upload(
+                "lake/t/date=2024-02-29/part-1.parquet",
+                "b",
+                "2024-02-29T12:00:00Z",
+            ),
+        ];
+        let sizes = HashMap::from([("a".to_string(), 5 << 20), ("b".to_string(), 1 << 20)]);
+        let metrics = summarize(&uploads, &sizes, "lake/t", now);
+
+        assert_eq!(metrics.incomplete_uploads, 2);
+        assert_eq!(metrics.stale_uploads, 1);
+        assert_eq!(metrics.oldest_upload_age_days, Some(60.0));
+        assert_eq!(metrics.uploaded_part_bytes, 6 << 20);
+        assert_eq!(metrics.upload_keys[0], "date=2024-01-01/part-0.parquet");
+        assert_eq!(
+            metrics.lifecycle_rule.as_deref(),
+            Some(
+                r#"{"Rules":[{"AbortIncompleteMultipartUpload":{"DaysAfterInitiation":7},"Filter":{"Prefix":"lake/t/"},"ID":"abort-incomplete-multipart-uploads","Status":"Enabled"}]}"#
+            )
+        );
+        assert_eq!(metrics.recommendations().len(), 1);
+
+        let running = summarize(&uploads[1..], &HashMap::new(), "lake/t", now);
+        assert!(running.lifecycle_rule.is_none());
+        assert!(running.recommendations().is_empty());
+    }
+}
@@ -0,0 +1,302 @@
+//! Webhook notifications (`webhooks=`, `notify_rules=`): when a finished report breaches a
+//! rule (score below a minimum, orphaned data or small files above a maximum), a summary is
+//! posted to each webhook, rendered for Slack, Microsoft Teams or as plain JSON.
+//!
+//! Notifications go out from the analyzers, so one-off analyses, batches and the scheduled
+//! runs of `drainage serve` all send them. A failed post is a warning on the report.
+
+use crate::config::AnalysisConfig;
+use crate::stream::EventSink;
+use crate::types::HealthReport;
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// How a webhook's payload is rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookFormat {
+    Slack,
+    Teams,
+    Generic,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Webhook {
+    pub url: String,
+    pub format: WebhookFormat,
+}
+
+impl Webhook {
+    /// A webhook posting to `url`; without a `format`, Slack and Teams are recognized by
+    /// their webhook hosts and anything else gets the generic JSON payload
+    pub fn new(url: &str, format: Option<&str>) -> Result<Self, String> {
+        let parsed = url::Url::parse(url).map_err(|e| format!("Invalid webhook {}: {}", url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!("Invalid webhook {}: not an HTTP URL", url));
+        }
+        let host = parsed.host_str().unwrap_or_default();
+        let format = match format.map(str::to_lowercase).as_deref() {
+            Some("slack") => WebhookFormat::Slack,
+            Some("teams") => WebhookFormat::Teams,
+            Some("generic") => WebhookFormat::Generic,
+            Some(other) => {
+                return Err(format!(
+                    "Unknown webhook format: {} (expected 'slack', 'teams' or 'generic')",
+                    other
+                ))
+            }
+            None if host == "hooks.slack.com" => WebhookFormat::Slack,
+            None if host.ends_with(".webhook.office.com") || host.ends_with(".logic.azure.com") => {
+                WebhookFormat::Teams
+            }
+            None => WebhookFormat::Generic,
+        };
+        Ok(Self {
+            url: url.to_string(),
+            format,
+        })
+    }
+}
+
+/// Thresholds that trigger a notification; unset rules never fire
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NotifyRules {
+    pub min_health_score: Option<f64>,
+    pub max_orphan_gb: Option<f64>,
+    pub max_small_file_ratio: Option<f64>,
+}
+
+impl NotifyRules {
+    pub fn set(&mut self, rule: &str, threshold: f64) -> Result<(), String> {
+        let field = match rule {
+            "min_health_score" => &mut self.min_health_score,
+            "max_orphan_gb" => &mut self.max_orphan_gb,
+            "max_small_file_ratio" => &mut self.max_small_file_ratio,
+            other => return Err(format!("Unknown notify rule: {}", other)),
+        };
+        *field = Some(threshold);
+        Ok(())
+    }
+}
+
+/// A rule the report breaks
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breach {
+    pub rule: &'static str,
+    pub threshold: f64,
+    pub value: f64,
+    pub message: String,
+}
+
+/// The rules `report` breaks, in the order of `NotifyRules`' fields
+pub fn breaches(report: &HealthReport, rules: &NotifyRules) -> Vec<Breach> {
+    let metrics = &report.metrics;
+    let orphan_gb = metrics.unreferenced_size_bytes as f64 / GB;
+    let small_file_ratio = match metrics.total_files {
+        0 => 0.0,
+        files => metrics.file_size_distribution.small_files as f64 / files as f64,
+    };
+    let checks = [
+        (
+            "min_health_score",
+            rules.min_health_score,
+            report.health_score,
+            true,
+        ),
+        ("max_orphan_gb", rules.max_orphan_gb, orphan_gb, false),
+        (
+            "max_small_file_ratio",
+            rules.max_small_file_ratio,
+            small_file_ratio,
+            false,
+        ),
+    ];
+    checks
+        .into_iter()
+        .filter_map(|(rule, threshold, value, minimum)| {
+            let threshold = threshold?;
+            let breached = if minimum {
+                value < threshold
+            } else {
+                value > threshold
+            };
+            breached.then(|| Breach {
+                rule,
+                threshold,
+                value,
+                message: match rule {
+                    "min_health_score" => format!(
+                        "Health score {:.1}% is below {:.1}%",
+                        value * 100.0,
+                        threshold * 100.0
+                    ),
+                    "max_orphan_gb" => format!(
+                        "{:.2} GB of orphaned files, above {:.2} GB",
+                        value, threshold
+                    ),
+                    _ => format!(
+                        "{:.1}% of files are small, above {:.1}%",
+                        value * 100.0,
+                        threshold * 100.0
+                    ),
+                },
+            })
+        })
+        .collect()
+}
+
+/// The payload posted to a webhook of `format`
+fn payload(format: WebhookFormat, report: &HealthReport, breaches: &[Breach]) -> Value {
+    let title = format!(
+        "drainage: {} breached {} health threshold{}",
+        report.table_path,
+        breaches.len(),
+        if breaches.len() == 1 { "" } else { "s" }
+    );
+    let lines: Vec<String> = breaches
+        .iter()
+        .map(|b| format!("• {}", b.message))
+        .collect();
+    let top_recommendation = report.metrics.recommendations.first();
+    match format {
+        WebhookFormat::Slack => {
+            let mut text = format!("*{}*\n{}", title, lines.join("\n"));
+            if let Some(recommendation) = top_recommendation {
+                text.push_str(&format!("\n_Top recommendation:_ {}", recommendation));
+            }
+            json!({ "text": text })
+        }
+        WebhookFormat::Teams => {
+            let facts: Vec<Value> = [
+                ("Table", report.table_path.clone()),
+                ("Format", report.table_type.clone()),
+                (
+                    "Health score",
+                    format!("{:.1}%", report.health_score * 100.0),
+                ),
+                ("Analyzed", report.analysis_timestamp.to_rfc3339()),
+            ]
+            .into_iter()
+            .chain(top_recommendation.map(|r| ("Top recommendation", r.clone())))
+            .map(|(name, value)| json!({ "name": name, "value": value }))
+            .collect();
+            json!({
+                "@type": "MessageCard",
+                "@context": "https://schema.org/extensions",
+                "summary": title,
+                "themeColor": "D70000",
+                "title": title,
+                "text": lines.join("<br>"),
+                "sections": [{ "facts": facts }],
+            })
+        }
+        WebhookFormat::Generic => {
+            let breaches: Vec<Value> = breaches
+                .iter()
+                .map(|b| {
+                    json!({
+                        "rule": b.rule,
+                        "threshold": b.threshold,
+                        "value": b.value,
+                        "message": b.message,
+                    })
+                })
+                .collect();
+            json!({
+                "event": "threshold_breach",
+                "summary": title,
+                "table_path": report.table_path,
+                "table_type": report.table_type,
+                "analysis_timestamp": report.analysis_timestamp,
+                "health_score": report.health_score,
+                "tags": report.tags,
+                "breaches": breaches,
+            })
+        }
+    }
+}
+
+async fn post(config: &AnalysisConfig, webhook: &Webhook, payload: &Value) -> Result<()> {
+    config
+        .network
+        .http_client()?
+        .post(&webhook.url)
+        .json(payload)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        // The URL usually embeds a secret, so errors only name its host
+        .with_context(|| {
+            let host = url::Url::parse(&webhook.url)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string));
+            format!("Webhook to {} failed", host.unwrap_or_default())
+        })?;
+    Ok(())
+}
+
+/// Post to the configured `webhooks` when `report` breaches a `notify_rules` threshold
+pub async fn notify(config: &AnalysisConfig, events: &EventSink, report: &HealthReport) {
+    if config.webhooks.is_empty() {
+        return;
+    }
+    let breaches = breaches(report, &config.notify_rules);
+    if breaches.is_empty() {
+        return;
+    }
+    for webhook in &config.webhooks {
+        let payload = payload(webhook.format, report, &breaches);
+        if let Err(e) = post(config, webhook, &payload).await {
+            events.warning(crate::sanitize::sanitize(&format!("{:#}", e)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breaches_render_per_webhook_format() {
+        let mut report = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        report.health_score = 0.55;
+        report.metrics.total_files = 10;
+        report.metrics.file_size_distribution.small_files = 2;
+        report.metrics.unreferenced_size_bytes = 80 * GB as u64;
+        let mut rules = NotifyRules::default();
+        rules.set("min_health_score", 0.7).unwrap();
+        rules.set("max_orphan_gb", 50.0).unwrap();
+        rules.set("max_small_file_ratio", 0.5).unwrap();
+        assert!(rules.set("max_score", 1.0).is_err());
+
+        let found = breaches(&report, &rules);
+        let messages: Vec<&str> = found.iter().map(|b| b.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Health score 55.0% is below 70.0%",
+                "80.00 GB of orphaned files, above 50.00 GB"
+            ]
+        );
+
+        let slack = Webhook::new("https://hooks.slack.com/services/T0/B0/x", None).unwrap();
+        assert_eq!(slack.format, WebhookFormat::Slack);
+        let text = payload(slack.format, &report, &found)["text"].clone();
+        assert!(text
+            .as_str()
+            .unwrap()
+            .starts_with("*drainage: s3://lake/events breached 2 health thresholds*\n• Health"));
+        let teams = Webhook::new("https://acme.webhook.office.com/webhookb2/x", None).unwrap();
+        assert_eq!(
+            payload(teams.format, &report, &found)["@type"],
+            "MessageCard"
+        );
+        let generic = Webhook::new("https://ops.example.com/hook", None).unwrap();
+        let body = payload(generic.format, &report, &found);
+        assert_eq!(body["breaches"][1]["rule"], "max_orphan_gb");
+        assert_eq!(body["health_score"], 0.55);
+        assert!(Webhook::new("https://ops.example.com/hook", Some("email")).is_err());
+        assert!(breaches(&report, &NotifyRules::default()).is_empty());
+    }
+}
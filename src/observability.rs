@@ -0,0 +1,839 @@
+//! Observability tables (`observability_table=`): every report appended as one flattened row
+//! to a Delta Lake or Iceberg table, so the health history of the whole lake can be queried
+//! with the engines that already read it.
+//!
+//! The table is created by the first append, under a local directory or an `s3://` prefix.
+//! Each append writes one Parquet file and commits it with a conditional write of the next
+//! log entry (`_delta_log/<version>.json`) or metadata file (`metadata/v<n>.metadata.json`),
+//! trying the following version when another writer got there first, so concurrent analyses
+//! can share a table. Iceberg tables are format version 2, unpartitioned and tracked through
+//! `version-hint.text` rather than a catalog; register them with the catalog's
+//! `register_table` to query them by name. Appending to a table created by something else
+//! fails unless its columns match.
+
+use crate::avro;
+use crate::otlp::random_u64;
+use crate::s3_client::{relative_key, S3ClientWrapper};
+use crate::types::HealthReport;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Versions tried before giving up on a contended table
+const MAX_COMMIT_ATTEMPTS: usize = 20;
+
+/// Format of the observability table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TableFormat {
+    #[default]
+    Delta,
+    Iceberg,
+}
+
+impl TableFormat {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "delta" | "delta_lake" => Ok(Self::Delta),
+            "iceberg" | "apache_iceberg" => Ok(Self::Iceberg),
+            other => Err(format!(
+                "Unknown observability_format: {} (expected 'delta' or 'iceberg')",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    String,
+    Timestamp, // microseconds since the epoch, UTC
+    Long,
+    Double,
+}
+
+/// (name, type, required, value): a column of the table and its value in a report
+type Column = (&'static str, Kind, bool, fn(&HealthReport) -> Value);
+
+const COLUMNS: [Column; 17] = [
+    ("table_path", Kind::String, true, |r| json!(r.table_path)),
+    ("table_type", Kind::String, true, |r| json!(r.table_type)),
+    ("analysis_timestamp", Kind::Timestamp, true, |r| {
+        json!(r.analysis_timestamp.timestamp_micros())
+    }),
+    ("health_score", Kind::Double, true, |r| {
+        json!(r.health_score)
+    }),
+    ("storage_cost_score", Kind::Double, false, |r| {
+        json!(r.metrics.sub_scores.as_ref().map(|s| s.storage_cost_score))
+    }),
+    ("query_performance_score", Kind::Double, false, |r| {
+        json!(r
+            .metrics
+            .sub_scores
+            .as_ref()
+            .map(|s| s.query_performance_score))
+    }),
+    ("reliability_score", Kind::Double, false, |r| {
+        json!(r.metrics.sub_scores.as_ref().map(|s| s.reliability_score))
+    }),
+    ("total_files", Kind::Long, true, |r| {
+        json!(r.metrics.total_files)
+    }),
+    ("total_size_bytes", Kind::Long, true, |r| {
+        json!(r.metrics.total_size_bytes)
+    }),
+    ("unreferenced_files", Kind::Long, true, |r| {
+        json!(r.unreferenced_file_count())
+    }),
+    ("unreferenced_size_bytes", Kind::Long, true, |r| {
+        json!(r.metrics.unreferenced_size_bytes)
+    }),
+    ("small_files", Kind::Long, true, |r| {
+        json!(r.metrics.file_size_distribution.small_files)
+    }),
+    ("avg_file_size_bytes", Kind::Double, true, |r| {
+        json!(r.metrics.avg_file_size_bytes)
+    }),
+    ("partition_count", Kind::Long, true, |r| {
+        json!(r.metrics.partition_count)
+    }),
+    ("recommendation_count", Kind::Long, true, |r| {
+        json!(r.metrics.recommendations.len())
+    }),
+    ("tags", Kind::String, false, |r| {
+        json!(r.tags.as_ref().map(|tags| json!(tags).to_string()))
+    }),
+    ("drainage_version", Kind::String, true, |_| {
+        json!(env!("CARGO_PKG_VERSION"))
+    }),
+];
+
+/// Parquet schema of the rows, with Iceberg field ids (the column's position from 1)
+fn parquet_schema() -> String {
+    let fields: Vec<String> = COLUMNS
+        .iter()
+        .enumerate()
+        .map(|(i, (name, kind, required, _))| {
+            let physical = match kind {
+                Kind::String => "binary",
+                Kind::Timestamp | Kind::Long => "int64",
+                Kind::Double => "double",
+            };
+            let logical = match kind {
+                Kind::String => " (UTF8)",
+                Kind::Timestamp => " (TIMESTAMP(MICROS,true))",
+                Kind::Long | Kind::Double => "",
+            };
+            let repetition = if *required { "required" } else { "optional" };
+            format!(
+                "{} {} {}{} = {};",
+                repetition,
+                physical,
+                name,
+                logical,
+                i + 1
+            )
+        })
+        .collect();
+    format!("message drainage_health {{ {} }}", fields.join(" "))
+}
+
+/// One Parquet file with a row per report
+fn rows_parquet(reports: &[&HealthReport]) -> Result<Vec<u8>> {
+    let schema = Arc::new(parse_message_type(&parquet_schema())?);
+    let mut buffer = Vec::new();
+    let mut writer = SerializedFileWriter::new(
+        &mut buffer,
+        schema,
+        Arc::new(WriterProperties::builder().build()),
+    )?;
+    let mut row_group = writer.next_row_group()?;
+    for (name, kind, required, value) in COLUMNS {
+        let values: Vec<Value> = reports.iter().map(|report| value(report)).collect();
+        let levels: Vec<i16> = values.iter().map(|v| i16::from(!v.is_null())).collect();
+        let levels = (!required).then_some(levels.as_slice());
+        let present = values.iter().filter(|v| !v.is_null());
+        let mut column = row_group
+            .next_column()?
+            .with_context(|| format!("{} column", name))?;
+        match kind {
+            Kind::String => {
+                let strings: Vec<ByteArray> = present
+                    .map(|v| ByteArray::from(v.as_str().unwrap_or_default()))
+                    .collect();
+                column
+                    .typed::<ByteArrayType>()
+                    .write_batch(&strings, levels, None)?
+            }
+            Kind::Timestamp | Kind::Long => {
+                let longs: Vec<i64> = present.map(|v| v.as_i64().unwrap_or_default()).collect();
+                column
+                    .typed::<Int64Type>()
+                    .write_batch(&longs, levels, None)?
+            }
+            Kind::Double => {
+                let doubles: Vec<f64> = present.map(|v| v.as_f64().unwrap_or_default()).collect();
+                column
+                    .typed::<DoubleType>()
+                    .write_batch(&doubles, levels, None)?
+            }
+        };
+        column.close()?;
+    }
+    row_group.close()?;
+    writer.close()?;
+    Ok(buffer)
+}
+
+/// Delta `schemaString` of the rows
+fn delta_schema() -> String {
+    let fields: Vec<Value> = COLUMNS
+        .iter()
+        .map(|(name, kind, required, _)| {
+            let data_type = match kind {
+                Kind::String => "string",
+                Kind::Timestamp => "timestamp",
+                Kind::Long => "long",
+                Kind::Double => "double",
+            };
+            json!({ "name": name, "type": data_type, "nullable": !required, "metadata": {} })
+        })
+        .collect();
+    json!({ "type": "struct", "fields": fields }).to_string()
+}
+
+/// Iceberg schema of the rows
+fn iceberg_schema() -> Value {
+    let fields: Vec<Value> = COLUMNS
+        .iter()
+        .enumerate()
+        .map(|(i, (name, kind, required, _))| {
+            let data_type = match kind {
+                Kind::String => "string",
+                Kind::Timestamp => "timestamptz",
+                Kind::Long => "long",
+                Kind::Double => "double",
+            };
+            json!({ "id": i + 1, "name": name, "required": required, "type": data_type })
+        })
+        .collect();
+    json!({ "type": "struct", "schema-id": 0, "fields": fields })
+}
+
+/// Fail unless the table's columns (as a Delta or Iceberg schema) are the rows' columns
+fn check_columns(location: &str, schema: &Value) -> Result<()> {
+    let columns: Vec<&str> = schema["fields"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|field| field["name"].as_str())
+        .collect();
+    let expected: Vec<&str> = COLUMNS.iter().map(|(name, ..)| *name).collect();
+    anyhow::ensure!(
+        columns == expected,
+        "{} is not a drainage observability table: it has columns {}",
+        location,
+        columns.join(", ")
+    );
+    Ok(())
+}
+
+fn uuid() -> String {
+    let (a, b) = (random_u64(), random_u64());
+    format!(
+        "{:08x}-{:04x}-4{:03x}-{:04x}-{:012x}",
+        a >> 32,
+        (a >> 16) & 0xffff,
+        a & 0xfff,
+        (b >> 48) & 0x3fff | 0x8000,
+        b & 0xffff_ffff_ffff
+    )
+}
+
+/// Where the observability table lives
+enum TableStore {
+    Local(PathBuf),
+    S3(S3ClientWrapper),
+}
+
+impl TableStore {
+    fn at(location: &str, s3_client: &S3ClientWrapper) -> Result<Self> {
+        Ok(match location.starts_with("s3://") {
+            true => Self::S3(s3_client.with_location(location)?),
+            false => Self::Local(PathBuf::from(location)),
+        })
+    }
+
+    /// Absolute location of a table-relative path, as Iceberg metadata refers to files
+    fn uri(&self, relative: &str) -> String {
+        match self {
+            Self::Local(root) => root.join(relative).display().to_string(),
+            Self::S3(s3_client) => format!(
+                "s3://{}/{}",
+                s3_client.get_bucket(),
+                s3_client.table_key(relative)
+            ),
+        }
+    }
+
+    /// The table-relative path of a location inside the table
+    fn relative<'a>(&self, uri: &'a str) -> Result<&'a str> {
+        let root = self.uri("");
+        uri.strip_prefix(root.as_str())
+            .map(|path| path.trim_start_matches('/'))
+            .with_context(|| format!("{} is outside the table at {}", uri, root))
+    }
+
+    /// Table-relative paths of the files in directory `dir` (e.g. "_delta_log/")
+    async fn list(&self, dir: &str) -> Result<Vec<String>> {
+        match self {
+            Self::Local(root) => {
+                let entries = match std::fs::read_dir(root.join(dir)) {
+                    Ok(entries) => entries,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+                    Err(e) => return Err(e.into()),
+                };
+                let mut files = Vec::new();
+                for entry in entries {
+                    files.push(format!("{}{}", dir, entry?.file_name().to_string_lossy()));
+                }
+                Ok(files)
+            }
+            Self::S3(s3_client) => Ok(s3_client
+                .list_objects(&s3_client.table_key(dir))
+                .await?
+                .iter()
+                .map(|object| relative_key(s3_client.get_prefix(), &object.key).to_string())
+                .collect()),
+        }
+    }
+
+    async fn get(&self, relative: &str) -> Result<Vec<u8>> {
+        match self {
+            Self::Local(root) => {
+                let path = root.join(relative);
+                std::fs::read(&path).with_context(|| format!("Cannot read {}", path.display()))
+            }
+            Self::S3(s3_client) => s3_client.get_object(&s3_client.table_key(relative)).await,
+        }
+    }
+
+    async fn put(&self, relative: &str, body: Vec<u8>) -> Result<()> {
+        match self {
+            Self::Local(root) => {
+                let path = root.join(relative);
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&path, body)
+                    .with_context(|| format!("Cannot write {}", path.display()))
+            }
+            Self::S3(s3_client) => {
+                s3_client
+                    .put_object(&s3_client.table_key(relative), body)
+                    .await
+            }
+        }
+    }
+
+    /// Write a file unless it exists, returning false when it does
+    async fn put_if_absent(&self, relative: &str, body: Vec<u8>) -> Result<bool> {
+        match self {
+            Self::Local(root) => {
+                use std::io::Write;
+                let path = root.join(relative);
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let mut file = match std::fs::OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&path)
+                {
+                    Ok(file) => file,
+                    Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return Ok(false),
+                    Err(e) => {
+                        return Err(e).with_context(|| format!("Cannot write {}", path.display()))
+                    }
+                };
+                file.write_all(&body)?;
+                Ok(true)
+            }
+            Self::S3(s3_client) => {
+                s3_client
+                    .put_object_if_absent(&s3_client.table_key(relative), body)
+                    .await
+            }
+        }
+    }
+}
+
+/// Version of a Delta log file (`00000000000000000003.json`, `...checkpoint.parquet`)
+fn delta_version(path: &str) -> Option<i64> {
+    let name = path.rsplit('/').next()?;
+    let digits = name.split('.').next()?;
+    (digits.len() == 20).then(|| digits.parse().ok())?
+}
+
+/// Append a Parquet file of `rows` rows to the Delta table, returning the committed version
+async fn append_delta(store: &TableStore, parquet: Vec<u8>, rows: usize) -> Result<i64> {
+    let data_file = format!("part-00000-{}-c000.parquet", uuid());
+    let size = parquet.len();
+    store.put(&data_file, parquet).await?;
+
+    for _ in 0..MAX_COMMIT_ATTEMPTS {
+        let log = store.list("_delta_log/").await?;
+        let version = log.iter().filter_map(|path| delta_version(path)).max();
+        let first_commit = format!("_delta_log/{:020}.json", 0);
+        if version.is_some() && log.contains(&first_commit) {
+            let commit = String::from_utf8(store.get(&first_commit).await?)?;
+            for action in commit
+                .lines()
+                .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            {
+                if let Some(schema) = action
+                    .pointer("/metaData/schemaString")
+                    .and_then(Value::as_str)
+                {
+                    check_columns(&store.uri(""), &serde_json::from_str(schema)?)?;
+                }
+            }
+        }
+
+        let version = version.map_or(0, |v| v + 1);
+        let now = Utc::now().timestamp_millis();
+        let mut actions = vec![json!({ "commitInfo": {
+            "timestamp": now,
+            "operation": "WRITE",
+            "operationParameters": { "mode": "Append" },
+            "isBlindAppend": true,
+            "engineInfo": format!("drainage/{}", env!("CARGO_PKG_VERSION")),
+        }})];
+        if version == 0 {
+            actions.push(json!({ "protocol": { "minReaderVersion": 1, "minWriterVersion": 2 } }));
+            actions.push(json!({ "metaData": {
+                "id": uuid(),
+                "name": "drainage_health",
+                "format": { "provider": "parquet", "options": {} },
+                "schemaString": delta_schema(),
+                "partitionColumns": [],
+                "configuration": {},
+                "createdTime": now,
+            }}));
+        }
+        actions.push(json!({ "add": {
+            "path": data_file,
+            "partitionValues": {},
+            "size": size,
+            "modificationTime": now,
+            "dataChange": true,
+            "stats": json!({ "numRecords": rows }).to_string(),
+        }}));
+        let commit: String = actions
+            .iter()
+            .map(|action| format!("{}\n", action))
+            .collect();
+        if store
+            .put_if_absent(
+                &format!("_delta_log/{:020}.json", version),
+                commit.into_bytes(),
+            )
+            .await?
+        {
+            return Ok(version);
+        }
+    }
+    anyhow::bail!(
+        "Gave up committing to {} after {} conflicting writes",
+        store.uri(""),
+        MAX_COMMIT_ATTEMPTS
+    )
+}
+
+/// Avro schema of Iceberg v2 manifest entries, with the data file fields drainage writes
+fn manifest_entry_schema() -> Value {
+    json!({
+        "type": "record",
+        "name": "manifest_entry",
+        "fields": [
+            { "name": "status", "type": "int", "field-id": 0 },
+            { "name": "snapshot_id", "type": ["null", "long"], "default": null, "field-id": 1 },
+            { "name": "sequence_number", "type": ["null", "long"], "default": null, "field-id": 3 },
+            { "name": "file_sequence_number", "type": ["null", "long"], "default": null, "field-id": 4 },
+            { "name": "data_file", "field-id": 2, "type": {
+                "type": "record",
+                "name": "r2",
+                "fields": [
+                    { "name": "content", "type": "int", "field-id": 134 },
+                    { "name": "file_path", "type": "string", "field-id": 100 },
+                    { "name": "file_format", "type": "string", "field-id": 101 },
+                    { "name": "partition", "field-id": 102,
+                      "type": { "type": "record", "name": "r102", "fields": [] } },
+                    { "name": "record_count", "type": "long", "field-id": 103 },
+                    { "name": "file_size_in_bytes", "type": "long", "field-id": 104 },
+                ],
+            }},
+        ],
+    })
+}
+
+/// Avro schema of Iceberg v2 manifest lists
+fn manifest_file_schema() -> Value {
+    let field = |name: &str, data_type: &str, id: u32| json!({ "name": name, "type": data_type, "field-id": id });
+    json!({
+        "type": "record",
+        "name": "manifest_file",
+        "fields": [
+            field("manifest_path", "string", 500),
+            field("manifest_length", "long", 501),
+            field("partition_spec_id", "int", 502),
+            field("content", "int", 517),
+            field("sequence_number", "long", 515),
+            field("min_sequence_number", "long", 516),
+            field("added_snapshot_id", "long", 503),
+            field("added_files_count", "int", 504),
+            field("existing_files_count", "int", 505),
+            field("deleted_files_count", "int", 506),
+            field("added_rows_count", "long", 512),
+            field("existing_rows_count", "long", 513),
+            field("deleted_rows_count", "long", 514),
+        ],
+    })
+}
+
+/// A snapshot summary count, which Iceberg writes as a string
+fn summary_total(snapshot: Option<&Value>, key: &str) -> u64 {
+    snapshot
+        .and_then(|s| s.pointer(&format!("/summary/{}", key)))
+        .and_then(Value::as_str)
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Append a Parquet file of `rows` rows to the Iceberg table, returning the new metadata
+/// version
+async fn append_iceberg(store: &TableStore, parquet: Vec<u8>, rows: usize) -> Result<u64> {
+    let data_file = format!("data/{}.parquet", uuid());
+    let size = parquet.len();
+    store.put(&data_file, parquet).await?;
+    // Positive, as some engines assume
+    let snapshot_id = (random_u64() >> 1) as i64;
+    let manifest = avro::write(
+        &manifest_entry_schema(),
+        &[
+            ("schema", iceberg_schema().to_string()),
+            ("schema-id", "0".to_string()),
+            ("partition-spec", "[]".to_string()),
+            ("partition-spec-id", "0".to_string()),
+            ("format-version", "2".to_string()),
+            ("content", "data".to_string()),
+        ],
+        &[json!({
+            "status": 1,
+            "snapshot_id": snapshot_id,
+            "data_file": {
+                "content": 0,
+                "file_path": store.uri(&data_file),
+                "file_format": "PARQUET",
+                "partition": {},
+                "record_count": rows,
+                "file_size_in_bytes": size,
+            },
+        })],
+    )?;
+    let manifest_file = format!("metadata/{}-m0.avro", uuid());
+    let manifest_length = manifest.len();
+    store.put(&manifest_file, manifest).await?;
+
+    for attempt in 0..MAX_COMMIT_ATTEMPTS {
+        let version = store
+            .list("metadata/")
+            .await?
+            .iter()
+            .filter_map(|path| {
+                let name = path.rsplit('/').next()?;
+                name.strip_prefix('v')?
+                    .strip_suffix(".metadata.json")?
+                    .parse::<u64>()
+                    .ok()
+            })
+            .max();
+        let previous: Option<Value> = match version {
+            Some(version) => {
+                let content = store
+                    .get(&format!("metadata/v{}.metadata.json", version))
+                    .await?;
+                Some(serde_json::from_slice(&content)?)
+            }
+            None => None,
+        };
+        let now = Utc::now().timestamp_millis();
+
+        let mut metadata = match previous {
+            Some(ref previous) => {
+                let current_schema = previous["schemas"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .find(|schema| schema["schema-id"] == previous["current-schema-id"]);
+                check_columns(&store.uri(""), current_schema.unwrap_or(&Value::Null))?;
+                previous.clone()
+            }
+            None => json!({
+                "format-version": 2,
+                "table-uuid": uuid(),
+                "location": store.uri("").trim_end_matches('/'),
+                "last-sequence-number": 0,
+                "last-column-id": COLUMNS.len(),
+                "current-schema-id": 0,
+                "schemas": [iceberg_schema()],
+                "default-spec-id": 0,
+                "partition-specs": [{ "spec-id": 0, "fields": [] }],
+                "last-partition-id": 999,
+                "default-sort-order-id": 0,
+                "sort-orders": [{ "order-id": 0, "fields": [] }],
+                "properties": {},
+                "current-snapshot-id": -1,
+                "snapshots": [],
+                "snapshot-log": [],
+                "metadata-log": [],
+            }),
+        };
+        let sequence_number = metadata["last-sequence-number"].as_i64().unwrap_or(0) + 1;
+        let parent_id = metadata["current-snapshot-id"]
+            .as_i64()
+            .filter(|id| *id >= 0);
+        let parent = parent_id.and_then(|id| {
+            metadata["snapshots"]
+                .as_array()?
+                .iter()
+                .find(|s| s["snapshot-id"] == id)
+                .cloned()
+        });
+
+        // The parent's manifests are carried over, after the new one
+        let mut manifests = vec![json!({
+            "manifest_path": store.uri(&manifest_file),
+            "manifest_length": manifest_length,
+            "partition_spec_id": 0,
+            "content": 0,
+            "sequence_number": sequence_number,
+            "min_sequence_number": sequence_number,
+            "added_snapshot_id": snapshot_id,
+            "added_files_count": 1,
+            "existing_files_count": 0,
+            "deleted_files_count": 0,
+            "added_rows_count": rows,
+            "existing_rows_count": 0,
+            "deleted_rows_count": 0,
+        })];
+        if let Some(list) = parent.as_ref().and_then(|p| p["manifest-list"].as_str()) {
+            let (_, carried) = avro::read(&store.get(store.relative(list)?).await?)
+                .with_context(|| format!("Cannot read manifest list {}", list))?;
+            manifests.extend(carried);
+        }
+        let manifest_list = format!("metadata/snap-{}-{}-{}.avro", snapshot_id, attempt, uuid());
+        let mut list_metadata = vec![
+            ("snapshot-id", snapshot_id.to_string()),
+            ("sequence-number", sequence_number.to_string()),
+            ("format-version", "2".to_string()),
+        ];
+        if let Some(parent_id) = parent_id {
+            list_metadata.push(("parent-snapshot-id", parent_id.to_string()));
+        }
+        store
+            .put(
+                &manifest_list,
+                avro::write(&manifest_file_schema(), &list_metadata, &manifests)?,
+            )
+            .await?;
+
+        let parent = parent.as_ref();
+        let total =
+            |key: &str, added: usize| (summary_total(parent, key) + added as u64).to_string();
+        let mut snapshot = json!({
+            "snapshot-id": snapshot_id,
+            "sequence-number": sequence_number,
+            "timestamp-ms": now,
+            "manifest-list": store.uri(&manifest_list),
+            "schema-id": 0,
+            "summary": {
+                "operation": "append",
+                "added-data-files": "1",
+                "added-records": rows.to_string(),
+                "added-files-size": size.to_string(),
+                "total-data-files": total("total-data-files", 1),
+                "total-records": total("total-records", rows),
+                "total-files-size": total("total-files-size", size),
+                "total-delete-files": "0",
+                "total-position-deletes": "0",
+                "total-equality-deletes": "0",
+            },
+        });
+        if let Some(parent_id) = parent_id {
+            snapshot["parent-snapshot-id"] = json!(parent_id);
+        }
+        if let Some(version) = version {
+            let previous_file = store.uri(&format!("metadata/v{}.metadata.json", version));
+            let updated = metadata["last-updated-ms"].clone();
+            push(
+                &mut metadata["metadata-log"],
+                json!({ "timestamp-ms": updated, "metadata-file": previous_file }),
+            );
+        }
+        push(&mut metadata["snapshots"], snapshot);
+        push(
+            &mut metadata["snapshot-log"],
+            json!({ "timestamp-ms": now, "snapshot-id": snapshot_id }),
+        );
+        metadata["last-sequence-number"] = json!(sequence_number);
+        metadata["last-updated-ms"] = json!(now);
+        metadata["current-snapshot-id"] = json!(snapshot_id);
+        metadata["refs"]["main"] = json!({ "snapshot-id": snapshot_id, "type": "branch" });
+
+        let next = version.map_or(1, |v| v + 1);
+        if store
+            .put_if_absent(
+                &format!("metadata/v{}.metadata.json", next),
+                serde_json::to_vec_pretty(&metadata)?,
+            )
+            .await?
+        {
+            store
+                .put("metadata/version-hint.text", next.to_string().into_bytes())
+                .await?;
+            return Ok(next);
+        }
+    }
+    anyhow::bail!(
+        "Gave up committing to {} after {} conflicting writes",
+        store.uri(""),
+        MAX_COMMIT_ATTEMPTS
+    )
+}
+
+/// Add `item` to a JSON array, creating it if missing
+fn push(array: &mut Value, item: Value) {
+    match array.as_array_mut() {
+        Some(items) => items.push(item),
+        None => *array = json!([item]),
+    }
+}
+
+/// Append one row per report to the `format` table in `store`, returning the committed
+/// version
+async fn append(
+    store: &TableStore,
+    format: TableFormat,
+    reports: &[&HealthReport],
+) -> Result<String> {
+    let parquet = rows_parquet(reports)?;
+    Ok(match format {
+        TableFormat::Delta => format!(
+            "version {}",
+            append_delta(store, parquet, reports.len()).await?
+        ),
+        TableFormat::Iceberg => format!(
+            "metadata v{}",
+            append_iceberg(store, parquet, reports.len()).await?
+        ),
+    })
+}
+
+/// Append `report` to the configured `observability_table`, if any. A failed append is a
+/// warning: the analysis itself succeeded.
+pub async fn record(
+    s3_client: &S3ClientWrapper,
+    config: &crate::config::AnalysisConfig,
+    events: &crate::stream::EventSink,
+    report: &HealthReport,
+) {
+    let Some(ref location) = config.observability_table else {
+        return;
+    };
+    let appended = match TableStore::at(location, s3_client) {
+        Ok(store) => append(&store, config.observability_format, &[report]).await,
+        Err(e) => Err(e),
+    };
+    match appended {
+        Ok(version) => tracing::debug!("Report appended to {} ({})", location, version),
+        Err(e) => events.warning(format!(
+            "Failed to append the report to observability table {}: {:#}",
+            location, e
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    #[test]
+    fn test_append_creates_and_extends_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut report = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        report.health_score = 0.8;
+        report.metrics.total_files = 4;
+        report.tags = Some([("team".to_string(), "ads".to_string())].into());
+        let delta = TableStore::Local(dir.path().join("delta"));
+        let iceberg = TableStore::Local(dir.path().join("iceberg"));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            for expected in ["version 0", "version 1"] {
+                let version = append(&delta, TableFormat::Delta, &[&report]).await;
+                assert_eq!(version.unwrap(), expected);
+            }
+            let first = delta.get("_delta_log/00000000000000000000.json").await;
+            let first = String::from_utf8(first.unwrap()).unwrap();
+            assert!(first.contains("\"protocol\"") && first.contains("\"metaData\""));
+            let second = delta.get("_delta_log/00000000000000000001.json").await;
+            let second = String::from_utf8(second.unwrap()).unwrap();
+            assert!(!second.contains("\"metaData\""));
+            let add: Value = serde_json::from_str(second.lines().last().unwrap()).unwrap();
+            let data = delta.get(add["add"]["path"].as_str().unwrap()).await;
+            let reader = SerializedFileReader::new(bytes::Bytes::from(data.unwrap())).unwrap();
+            let file_metadata = reader.metadata().file_metadata();
+            assert_eq!(file_metadata.num_rows(), 1);
+            assert_eq!(file_metadata.schema_descr().num_columns(), COLUMNS.len());
+
+            for _ in 0..2 {
+                append(&iceberg, TableFormat::Iceberg, &[&report, &report])
+                    .await
+                    .unwrap();
+            }
+            let metadata = iceberg.get("metadata/v2.metadata.json").await.unwrap();
+            let metadata: Value = serde_json::from_slice(&metadata).unwrap();
+            assert_eq!(metadata["snapshots"].as_array().unwrap().len(), 2);
+            assert_eq!(metadata["last-sequence-number"], 2);
+            assert_eq!(metadata["snapshots"][1]["summary"]["total-records"], "4");
+            let list = metadata["snapshots"][1]["manifest-list"].as_str().unwrap();
+            let list = iceberg.get(iceberg.relative(list).unwrap()).await.unwrap();
+            let (_, manifests) = avro::read(&list).unwrap();
+            assert_eq!(manifests.len(), 2);
+            assert_eq!(manifests[1]["sequence_number"], 1);
+            let hint = iceberg.get("metadata/version-hint.text").await.unwrap();
+            assert_eq!(hint, b"2");
+
+            // A table with other columns is left alone
+            let other = TableStore::Local(dir.path().join("other"));
+            let schema = json!({ "type": "struct", "fields": [{ "name": "id" }] });
+            let commit = json!({ "metaData": { "schemaString": schema.to_string() } });
+            other
+                .put(
+                    "_delta_log/00000000000000000000.json",
+                    commit.to_string().into_bytes(),
+                )
+                .await
+                .unwrap();
+            let error = append(&other, TableFormat::Delta, &[&report]).await;
+            assert!(error.unwrap_err().to_string().contains("columns id"));
+        });
+    }
+}
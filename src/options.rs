@@ -0,0 +1,493 @@
+//! `AnalysisOptions` and `StorageOptions`: the analysis settings as reusable objects, as an
+//! alternative to passing credentials and keyword options to every `analyze_*` call.
+//!
+//! Both are immutable; each `with_*` method returns an updated copy, so a base set of options
+//! can be shared and specialized per table. Keyword arguments and explicit credentials given
+//! to an entry point take precedence over `options=` and `storage=`, and those over the
+//! `drainage.toml` and `DRAINAGE_*` defaults (see `settings`).
+
+use crate::config::AnalysisConfig;
+use crate::s3_client::AwsCredentials;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::Value;
+
+/// Credentials and region for the table's storage
+#[derive(Debug, Clone, Default)]
+#[pyclass]
+pub struct StorageOptions {
+    pub aws_access_key_id: Option<String>,
+    pub aws_secret_access_key: Option<String>,
+    pub aws_session_token: Option<String>,
+    #[pyo3(get)]
+    pub aws_region: Option<String>,
+    #[pyo3(get)]
+    pub aws_profile: Option<String>,
+    #[pyo3(get)]
+    pub role_arn: Option<String>,
+    #[pyo3(get)]
+    pub external_id: Option<String>,
+}
+
+#[pymethods]
+impl StorageOptions {
+    #[new]
+    #[pyo3(signature = (aws_access_key_id=None, aws_secret_access_key=None, aws_region=None, aws_session_token=None, aws_profile=None, role_arn=None, external_id=None))]
+    pub fn py_new(
+        aws_access_key_id: Option<String>,
+        aws_secret_access_key: Option<String>,
+        aws_region: Option<String>,
+        aws_session_token: Option<String>,
+        aws_profile: Option<String>,
+        role_arn: Option<String>,
+        external_id: Option<String>,
+    ) -> Self {
+        Self {
+            aws_access_key_id,
+            aws_secret_access_key,
+            aws_session_token,
+            aws_region,
+            aws_profile,
+            role_arn,
+            external_id,
+        }
+    }
+
+    /// Static access keys, with a session token for temporary credentials
+    #[pyo3(signature = (aws_access_key_id, aws_secret_access_key, aws_session_token=None))]
+    fn with_keys(
+        &self,
+        aws_access_key_id: String,
+        aws_secret_access_key: String,
+        aws_session_token: Option<String>,
+    ) -> Self {
+        Self {
+            aws_access_key_id: Some(aws_access_key_id),
+            aws_secret_access_key: Some(aws_secret_access_key),
+            aws_session_token,
+            ..self.clone()
+        }
+    }
+
+    /// A profile from the shared AWS config files
+    fn with_profile(&self, aws_profile: String) -> Self {
+        Self {
+            aws_profile: Some(aws_profile),
+            ..self.clone()
+        }
+    }
+
+    /// A role to assume with the other credentials
+    #[pyo3(signature = (role_arn, external_id=None))]
+    fn with_role(&self, role_arn: String, external_id: Option<String>) -> Self {
+        Self {
+            role_arn: Some(role_arn),
+            external_id,
+            ..self.clone()
+        }
+    }
+
+    fn with_region(&self, aws_region: String) -> Self {
+        Self {
+            aws_region: Some(aws_region),
+            ..self.clone()
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "StorageOptions(keys={}, aws_region={:?}, aws_profile={:?}, role_arn={:?})",
+            if self.aws_access_key_id.is_some() {
+                "set"
+            } else {
+                "unset"
+            },
+            self.aws_region,
+            self.aws_profile,
+            self.role_arn
+        )
+    }
+}
+
+impl StorageOptions {
+    /// These options, with fields left unset taken from `defaults`
+    pub fn or(&self, defaults: &StorageOptions) -> Self {
+        let (credentials, aws_region) = defaults.merge(
+            AwsCredentials {
+                aws_access_key_id: self.aws_access_key_id.clone(),
+                aws_secret_access_key: self.aws_secret_access_key.clone(),
+                aws_session_token: self.aws_session_token.clone(),
+                aws_profile: self.aws_profile.clone(),
+                role_arn: self.role_arn.clone(),
+                external_id: self.external_id.clone(),
+            },
+            self.aws_region.clone(),
+        );
+        Self {
+            aws_access_key_id: credentials.aws_access_key_id,
+            aws_secret_access_key: credentials.aws_secret_access_key,
+            aws_session_token: credentials.aws_session_token,
+            aws_region,
+            aws_profile: credentials.aws_profile,
+            role_arn: credentials.role_arn,
+            external_id: credentials.external_id,
+        }
+    }
+
+    /// `credentials` and `aws_region` given explicitly, completed from these options
+    pub fn merge(
+        &self,
+        credentials: AwsCredentials,
+        aws_region: Option<String>,
+    ) -> (AwsCredentials, Option<String>) {
+        let merged = AwsCredentials {
+            aws_access_key_id: credentials
+                .aws_access_key_id
+                .or_else(|| self.aws_access_key_id.clone()),
+            aws_secret_access_key: credentials
+                .aws_secret_access_key
+                .or_else(|| self.aws_secret_access_key.clone()),
+            aws_session_token: credentials
+                .aws_session_token
+                .or_else(|| self.aws_session_token.clone()),
+            aws_profile: credentials.aws_profile.or_else(|| self.aws_profile.clone()),
+            role_arn: credentials.role_arn.or_else(|| self.role_arn.clone()),
+            external_id: credentials.external_id.or_else(|| self.external_id.clone()),
+        };
+        (merged, aws_region.or_else(|| self.aws_region.clone()))
+    }
+}
+
+/// Keyword options of the `analyze_*` calls, storage credentials and namespace concurrency
+#[pyclass]
+pub struct AnalysisOptions {
+    options: Py<PyDict>,
+    #[pyo3(get)]
+    storage: Option<StorageOptions>,
+    #[pyo3(get)]
+    max_concurrency: Option<usize>,
+}
+
+impl AnalysisOptions {
+    /// A copy with `values` set (None values skipped), checked like keyword options
+    fn with_values(&self, py: Python<'_>, values: &[(&str, Option<PyObject>)]) -> PyResult<Self> {
+        let options = self.options.as_ref(py).copy()?;
+        for (key, value) in values {
+            if let Some(value) = value {
+                options.set_item(key, value)?;
+            }
+        }
+        AnalysisConfig::from_options(Some(options))?;
+        Ok(Self {
+            options: options.into(),
+            storage: self.storage.clone(),
+            max_concurrency: self.max_concurrency,
+        })
+    }
+}
+
+#[pymethods]
+impl AnalysisOptions {
+    /// Takes the same keyword options as `analyze_table` (`deep_scan=True`, `engine="trino"`, ...)
+    #[new]
+    #[pyo3(signature = (storage=None, max_concurrency=None, **options))]
+    fn py_new(
+        py: Python<'_>,
+        storage: Option<StorageOptions>,
+        max_concurrency: Option<usize>,
+        options: Option<&PyDict>,
+    ) -> PyResult<Self> {
+        let options = match options {
+            Some(options) => options.copy()?,
+            None => PyDict::new(py),
+        };
+        AnalysisConfig::from_options(Some(options))?;
+        Ok(Self {
+            options: options.into(),
+            storage,
+            max_concurrency,
+        })
+    }
+
+    /// The keyword options set so far
+    #[getter]
+    fn options(&self, py: Python<'_>) -> PyResult<Py<PyDict>> {
+        Ok(self.options.as_ref(py).copy()?.into())
+    }
+
+    fn with_storage(&self, py: Python<'_>, storage: StorageOptions) -> Self {
+        Self {
+            options: self.options.clone_ref(py),
+            storage: Some(storage),
+            max_concurrency: self.max_concurrency,
+        }
+    }
+
+    /// Tables analyzed at once by `analyze_namespace`
+    fn with_concurrency(&self, py: Python<'_>, max_concurrency: usize) -> Self {
+        Self {
+            options: self.options.clone_ref(py),
+            storage: self.storage.clone(),
+            max_concurrency: Some(max_concurrency.max(1)),
+        }
+    }
+
+    #[pyo3(signature = (connect_timeout_seconds=None, read_timeout_seconds=None, max_duration_seconds=None))]
+    fn with_timeouts(
+        &self,
+        py: Python<'_>,
+        connect_timeout_seconds: Option<f64>,
+        read_timeout_seconds: Option<f64>,
+        max_duration_seconds: Option<f64>,
+    ) -> PyResult<Self> {
+        self.with_values(
+            py,
+            &[
+                (
+                    "connect_timeout_seconds",
+                    connect_timeout_seconds.map(|s| s.into_py(py)),
+                ),
+                (
+                    "read_timeout_seconds",
+                    read_timeout_seconds.map(|s| s.into_py(py)),
+                ),
+                (
+                    "max_duration_seconds",
+                    max_duration_seconds.map(|s| s.into_py(py)),
+                ),
+            ],
+        )
+    }
+
+    /// HTTP connection pool settings for S3 and catalog requests
+    #[pyo3(signature = (pool_max_idle_per_host=None, pool_idle_timeout_seconds=None, http2=None))]
+    fn with_connection_pool(
+        &self,
+        py: Python<'_>,
+        pool_max_idle_per_host: Option<usize>,
+        pool_idle_timeout_seconds: Option<f64>,
+        http2: Option<bool>,
+    ) -> PyResult<Self> {
+        self.with_values(
+            py,
+            &[
+                (
+                    "pool_max_idle_per_host",
+                    pool_max_idle_per_host.map(|n| n.into_py(py)),
+                ),
+                (
+                    "pool_idle_timeout_seconds",
+                    pool_idle_timeout_seconds.map(|s| s.into_py(py)),
+                ),
+                ("http2", http2.map(|b| b.into_py(py))),
+            ],
+        )
+    }
+
+    /// Caps on the unreferenced files, partitions, file lists and recommendations a report
+    /// returns
+    #[pyo3(signature = (max_unreferenced_files=None, max_partitions=None, max_recommendations=None, max_files_in_report=None, include_file_lists=None))]
+    fn with_result_limits(
+        &self,
+        py: Python<'_>,
+        max_unreferenced_files: Option<usize>,
+        max_partitions: Option<usize>,
+        max_recommendations: Option<usize>,
+        max_files_in_report: Option<usize>,
+        include_file_lists: Option<bool>,
+    ) -> PyResult<Self> {
+        self.with_values(
+            py,
+            &[
+                (
+                    "max_unreferenced_files",
+                    max_unreferenced_files.map(|n| n.into_py(py)),
+                ),
+                ("max_partitions", max_partitions.map(|n| n.into_py(py))),
+                (
+                    "max_recommendations",
+                    max_recommendations.map(|n| n.into_py(py)),
+                ),
+                (
+                    "max_files_in_report",
+                    max_files_in_report.map(|n| n.into_py(py)),
+                ),
+                (
+                    "include_file_lists",
+                    include_file_lists.map(|b| b.into_py(py)),
+                ),
+            ],
+        )
+    }
+
+    /// Only analyze the partitions matching `partition_filter`, e.g. `{"date": "2024-06-01"}`
+    fn with_partition_filter(&self, py: Python<'_>, partition_filter: PyObject) -> PyResult<Self> {
+        self.with_values(py, &[("partition_filter", Some(partition_filter))])
+    }
+
+    /// What scores, estimates and costs assume
+    #[pyo3(signature = (engine=None, engine_cores=None, pricing=None, freshness_sla_hours=None, unreferenced_grace_period_hours=None))]
+    fn with_scoring(
+        &self,
+        py: Python<'_>,
+        engine: Option<String>,
+        engine_cores: Option<usize>,
+        pricing: Option<String>,
+        freshness_sla_hours: Option<f64>,
+        unreferenced_grace_period_hours: Option<f64>,
+    ) -> PyResult<Self> {
+        self.with_values(
+            py,
+            &[
+                ("engine", engine.map(|v| v.into_py(py))),
+                ("engine_cores", engine_cores.map(|v| v.into_py(py))),
+                ("pricing", pricing.map(|v| v.into_py(py))),
+                (
+                    "freshness_sla_hours",
+                    freshness_sla_hours.map(|v| v.into_py(py)),
+                ),
+                (
+                    "unreferenced_grace_period_hours",
+                    unreferenced_grace_period_hours.map(|v| v.into_py(py)),
+                ),
+            ],
+        )
+    }
+
+    /// How thoroughly and how reproducibly to analyze
+    #[pyo3(signature = (deep_scan=None, verify_referenced_files=None, allow_partial=None, deterministic=None, now=None, assess_iceberg_migration=None, check_multipart_uploads=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn with_mode(
+        &self,
+        py: Python<'_>,
+        deep_scan: Option<bool>,
+        verify_referenced_files: Option<bool>,
+        allow_partial: Option<bool>,
+        deterministic: Option<bool>,
+        now: Option<PyObject>,
+        assess_iceberg_migration: Option<bool>,
+        check_multipart_uploads: Option<bool>,
+    ) -> PyResult<Self> {
+        self.with_values(
+            py,
+            &[
+                ("deep_scan", deep_scan.map(|v| v.into_py(py))),
+                (
+                    "verify_referenced_files",
+                    verify_referenced_files.map(|v| v.into_py(py)),
+                ),
+                ("allow_partial", allow_partial.map(|v| v.into_py(py))),
+                ("deterministic", deterministic.map(|v| v.into_py(py))),
+                ("now", now),
+                (
+                    "assess_iceberg_migration",
+                    assess_iceberg_migration.map(|v| v.into_py(py)),
+                ),
+                (
+                    "check_multipart_uploads",
+                    check_multipart_uploads.map(|v| v.into_py(py)),
+                ),
+            ],
+        )
+    }
+
+    /// Any other keyword option
+    fn with_option(&self, py: Python<'_>, key: &str, value: PyObject) -> PyResult<Self> {
+        self.with_values(py, &[(key, Some(value))])
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "AnalysisOptions({}, storage={}, max_concurrency={:?})",
+            self.options.as_ref(py).repr()?,
+            self.storage
+                .as_ref()
+                .map_or("None".to_string(), StorageOptions::__repr__),
+            self.max_concurrency
+        ))
+    }
+}
+
+/// What an entry point's `**options` resolve to
+pub struct ResolvedOptions {
+    pub config: AnalysisConfig,
+    pub storage: StorageOptions,
+    pub max_concurrency: Option<usize>,
+}
+
+/// Resolve `options=AnalysisOptions(...)` and `storage=StorageOptions(...)` among keyword
+/// options, on top of the `drainage.toml` and `DRAINAGE_*` defaults; other keywords override
+/// what `options=` sets
+pub fn resolve(kwargs: Option<&PyDict>) -> PyResult<ResolvedOptions> {
+    let defaults = crate::settings::load().map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Python::with_gil(|py| {
+        let merged: &PyDict = py
+            .import("json")?
+            .call_method1("loads", (Value::Object(defaults.options).to_string(),))?
+            .downcast()?;
+        let mut storage = StorageOptions::default();
+        let mut max_concurrency = None;
+        if let Some(kwargs) = kwargs {
+            if let Some(options) = kwargs.get_item("options")? {
+                let options: PyRef<AnalysisOptions> = options.extract()?;
+                merged.update(options.options.as_ref(py).as_mapping())?;
+                storage = options.storage.clone().unwrap_or_default();
+                max_concurrency = options.max_concurrency;
+            }
+            if let Some(explicit) = kwargs.get_item("storage")? {
+                storage = explicit.extract()?;
+            }
+            for (key, value) in kwargs.iter() {
+                let key: &str = key.extract()?;
+                if key != "options" && key != "storage" {
+                    merged.set_item(key, value)?;
+                }
+            }
+        }
+        Ok(ResolvedOptions {
+            config: AnalysisConfig::from_options(Some(merged))?,
+            storage: storage.or(&defaults.storage),
+            max_concurrency: max_concurrency.or(defaults.max_concurrency),
+        })
+    })
+}
+
+/// Resolve keyword options like `resolve`, completing the `credentials` and `aws_region`
+/// given explicitly to an entry point from `storage=` and the defaults
+pub fn resolve_with_credentials(
+    kwargs: Option<&PyDict>,
+    credentials: AwsCredentials,
+    aws_region: Option<String>,
+) -> PyResult<(AwsCredentials, Option<String>, AnalysisConfig)> {
+    let options = resolve(kwargs)?;
+    let (credentials, aws_region) = options.storage.merge(credentials, aws_region);
+    Ok((credentials, aws_region, options.config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_options_fill_missing_credentials() {
+        let storage = StorageOptions {
+            aws_profile: Some("analytics".to_string()),
+            aws_region: Some("eu-west-1".to_string()),
+            role_arn: Some("arn:aws:iam::123456789012:role/reader".to_string()),
+            ..StorageOptions::default()
+        };
+        let explicit = AwsCredentials {
+            role_arn: Some("arn:aws:iam::123456789012:role/admin".to_string()),
+            ..AwsCredentials::default()
+        };
+        let (credentials, region) = storage.merge(explicit, None);
+        assert_eq!(credentials.aws_profile.as_deref(), Some("analytics"));
+        assert_eq!(
+            credentials.role_arn.as_deref(),
+            Some("arn:aws:iam::123456789012:role/admin")
+        );
+        assert_eq!(region.as_deref(), Some("eu-west-1"));
+        let (_, region) = storage.merge(AwsCredentials::default(), Some("us-east-1".to_string()));
+        assert_eq!(region.as_deref(), Some("us-east-1"));
+    }
+}
@@ -0,0 +1,514 @@
+//! ORC file tails for deep scans, the counterpart of `parquet_footer` for tables with ORC
+//! data files.
+//!
+//! An ORC file ends with a protobuf PostScript whose length is the file's last byte. It
+//! names the compression codec and the length of the Footer just before it, which lists the
+//! stripes (ORC's row groups) and per-column statistics. Uncompressed and zlib footers are
+//! decoded; with the other codecs only the codec is known. The few fields needed are read
+//! straight off the protobuf wire format.
+
+use crate::s3_client::{ObjectInfo, S3ClientWrapper};
+use crate::types::{FileFormatMetrics, OrcFooterMetrics};
+use anyhow::Result;
+use std::collections::{BTreeSet, HashMap};
+
+/// Bytes fetched from the end of a file on the first attempt; most tails fit
+const FOOTER_FETCH_BYTES: u64 = 64 * 1024;
+
+/// Stripes below this size (when files hold several) make readers seek more than they scan
+const SMALL_STRIPE_BYTES: f64 = 16.0 * 1024.0 * 1024.0;
+
+/// A protobuf field value
+enum Wire<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed, // 32- and 64-bit values, which none of the fields read here use
+}
+
+fn varint(buf: &[u8], pos: &mut usize) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *buf
+            .get(*pos)
+            .ok_or_else(|| anyhow::anyhow!("Truncated ORC varint"))?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(anyhow::anyhow!("ORC varint is too long"))
+}
+
+/// (field number, value) of every field of a protobuf message, in order
+fn fields(buf: &[u8]) -> Result<Vec<(u64, Wire<'_>)>> {
+    let mut pos = 0;
+    let mut fields = Vec::new();
+    while pos < buf.len() {
+        let key = varint(buf, &mut pos)?;
+        let value = match key & 7 {
+            0 => Wire::Varint(varint(buf, &mut pos)?),
+            1 => {
+                pos += 8;
+                Wire::Fixed
+            }
+            2 => {
+                let len = varint(buf, &mut pos)? as usize;
+                let end = pos
+                    .checked_add(len)
+                    .filter(|end| *end <= buf.len())
+                    .ok_or_else(|| anyhow::anyhow!("Truncated ORC message"))?;
+                let bytes = &buf[pos..end];
+                pos = end;
+                Wire::Bytes(bytes)
+            }
+            5 => {
+                pos += 4;
+                Wire::Fixed
+            }
+            wire_type => anyhow::bail!("Unsupported protobuf wire type {}", wire_type),
+        };
+        fields.push((key >> 3, value));
+    }
+    if pos > buf.len() {
+        anyhow::bail!("Truncated ORC message");
+    }
+    Ok(fields)
+}
+
+/// Last value of an integer field, 0 when absent
+fn uint(fields: &[(u64, Wire)], number: u64) -> u64 {
+    fields
+        .iter()
+        .rev()
+        .find_map(|(n, value)| match value {
+            Wire::Varint(value) if *n == number => Some(*value),
+            _ => None,
+        })
+        .unwrap_or(0)
+}
+
+/// Every value of a length-delimited field (messages, strings, packed integers)
+fn bytes<'a>(fields: &[(u64, Wire<'a>)], number: u64) -> Vec<&'a [u8]> {
+    fields
+        .iter()
+        .filter_map(|(n, value)| match value {
+            Wire::Bytes(bytes) if *n == number => Some(*bytes),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Every value of a repeated integer field, packed or not
+fn uints(fields: &[(u64, Wire)], number: u64) -> Result<Vec<u64>> {
+    let mut values = Vec::new();
+    for (n, value) in fields {
+        match value {
+            Wire::Varint(value) if *n == number => values.push(*value),
+            Wire::Bytes(packed) if *n == number => {
+                let mut pos = 0;
+                while pos < packed.len() {
+                    values.push(varint(packed, &mut pos)?);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(values)
+}
+
+fn codec_name(compression: u64) -> &'static str {
+    match compression {
+        0 => "none",
+        1 => "zlib",
+        2 => "snappy",
+        3 => "lzo",
+        4 => "lz4",
+        5 => "zstd",
+        _ => "unknown",
+    }
+}
+
+/// Contents of a compressed footer, None for codecs that can't be decoded here.
+/// Compressed sections are split into chunks behind a 3-byte header: the chunk length
+/// shifted left by one, with the low bit set when the chunk was stored uncompressed.
+fn decompress(codec: &str, data: &[u8]) -> Result<Option<Vec<u8>>> {
+    match codec {
+        "none" => Ok(Some(data.to_vec())),
+        "zlib" => {
+            let mut output = Vec::new();
+            let mut pos = 0;
+            while pos < data.len() {
+                let header = data
+                    .get(pos..pos + 3)
+                    .ok_or_else(|| anyhow::anyhow!("Truncated ORC compression chunk"))?;
+                let header =
+                    header[0] as usize | (header[1] as usize) << 8 | (header[2] as usize) << 16;
+                let start = pos + 3;
+                let end = start + (header >> 1);
+                let chunk = data
+                    .get(start..end)
+                    .ok_or_else(|| anyhow::anyhow!("Truncated ORC compression chunk"))?;
+                if header & 1 == 1 {
+                    output.extend_from_slice(chunk);
+                } else {
+                    output.extend(miniz_oxide::inflate::decompress_to_vec(chunk).map_err(|e| {
+                        anyhow::anyhow!("Invalid zlib data in ORC footer: {:?}", e)
+                    })?);
+                }
+                pos = end;
+            }
+            Ok(Some(output))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// One stripe, as listed in the footer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stripe {
+    pub size_bytes: u64, // index, data and stripe footer
+    pub rows: u64,
+}
+
+/// What the tail of an ORC file says about its layout
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrcFooter {
+    pub codec: &'static str,
+    pub decoded: bool, // false when the footer's codec can't be decoded here
+    pub stripes: Vec<Stripe>,
+    pub columns: Vec<(String, bool)>, // top-level column and whether it has statistics
+}
+
+fn parse_footer(codec: &'static str, footer: &[u8]) -> Result<OrcFooter> {
+    let footer = fields(footer)?;
+    let stripes = bytes(&footer, 3)
+        .into_iter()
+        .map(|stripe| {
+            let stripe = fields(stripe)?;
+            Ok(Stripe {
+                size_bytes: uint(&stripe, 2) + uint(&stripe, 3) + uint(&stripe, 4),
+                rows: uint(&stripe, 5),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // Type 0 is the root struct; its fields are the table's columns
+    let types = bytes(&footer, 4);
+    let statistics = bytes(&footer, 7);
+    let mut columns = Vec::new();
+    if let Some(root) = types.first() {
+        let root = fields(root)?;
+        let names = bytes(&root, 3);
+        for (name, type_id) in names.iter().zip(uints(&root, 2)?) {
+            // Typed statistics (int, double, string, bucket, decimal, date, binary,
+            // timestamp) are fields 2 to 9; a bare value count doesn't help skipping
+            let has_statistics = match statistics.get(type_id as usize) {
+                Some(stats) => fields(stats)?.iter().any(|(n, _)| (2..=9).contains(n)),
+                None => false,
+            };
+            columns.push((String::from_utf8_lossy(name).into_owned(), has_statistics));
+        }
+    }
+
+    Ok(OrcFooter {
+        codec,
+        decoded: true,
+        stripes,
+        columns,
+    })
+}
+
+/// Result of decoding the tail of an ORC file
+enum FooterTail {
+    Complete(OrcFooter),
+    /// The footer is longer than the tail; this many trailing bytes are needed
+    NeedBytes(usize),
+}
+
+fn decode_tail(tail: &[u8]) -> Result<FooterTail> {
+    let postscript_len = *tail
+        .last()
+        .ok_or_else(|| anyhow::anyhow!("Empty ORC file"))? as usize;
+    if postscript_len + 1 > tail.len() {
+        anyhow::bail!("File too small to be ORC");
+    }
+    let postscript = fields(&tail[tail.len() - 1 - postscript_len..tail.len() - 1])?;
+    if bytes(&postscript, 8000).first() != Some(&b"ORC".as_slice()) {
+        anyhow::bail!("Not an ORC file");
+    }
+    let footer_len = uint(&postscript, 1) as usize;
+    let codec = codec_name(uint(&postscript, 2));
+    let needed = footer_len + postscript_len + 1;
+    if needed > tail.len() {
+        return Ok(FooterTail::NeedBytes(needed));
+    }
+
+    let footer = &tail[tail.len() - needed..tail.len() - 1 - postscript_len];
+    match decompress(codec, footer)? {
+        Some(footer) => Ok(FooterTail::Complete(parse_footer(codec, &footer)?)),
+        None => Ok(FooterTail::Complete(OrcFooter {
+            codec,
+            decoded: false,
+            stripes: Vec::new(),
+            columns: Vec::new(),
+        })),
+    }
+}
+
+/// Read a data file's tail with one range GET, or two when the footer is large
+pub async fn fetch_footer(s3_client: &S3ClientWrapper, file: &ObjectInfo) -> Result<OrcFooter> {
+    let tail = s3_client
+        .get_object_tail(&file.key, FOOTER_FETCH_BYTES.min(file.size.max(0) as u64))
+        .await?;
+    match decode_tail(&tail)? {
+        FooterTail::Complete(footer) => Ok(footer),
+        FooterTail::NeedBytes(needed) => {
+            let tail = s3_client.get_object_tail(&file.key, needed as u64).await?;
+            match decode_tail(&tail)? {
+                FooterTail::Complete(footer) => Ok(footer),
+                FooterTail::NeedBytes(_) => Err(anyhow::anyhow!("Truncated ORC footer")),
+            }
+        }
+    }
+}
+
+/// Fetch the tails of a sample of the ORC data files. Files whose tails can't be read are
+/// counted rather than failing the analysis.
+pub async fn sample_footers(
+    s3_client: &S3ClientWrapper,
+    data_files: &[&ObjectInfo],
+    sample_size: usize,
+) -> (Vec<OrcFooter>, usize) {
+    let orc_files: Vec<&ObjectInfo> = data_files
+        .iter()
+        .copied()
+        .filter(|file| FileFormatMetrics::format_of(&file.key) == Some("orc"))
+        .collect();
+    let mut footers = Vec::new();
+    let mut failed_files = 0;
+    for file in crate::parquet_footer::sample_files(&orc_files, sample_size) {
+        match fetch_footer(s3_client, file).await {
+            Ok(footer) => footers.push(footer),
+            Err(_) => failed_files += 1,
+        }
+    }
+    (footers, failed_files)
+}
+
+/// Summarize stripe layout, codecs and statistics coverage of sampled tails
+pub fn summarize_footers(footers: &[OrcFooter], failed_files: usize) -> Option<OrcFooterMetrics> {
+    if footers.is_empty() && failed_files == 0 {
+        return None;
+    }
+
+    let mut codecs: HashMap<String, usize> = HashMap::new();
+    let mut stripe_sizes: Vec<u64> = Vec::new();
+    let mut total_rows = 0u64;
+    let mut columns = 0usize;
+    let mut columns_with_stats = 0usize;
+    let mut columns_missing_stats: BTreeSet<String> = BTreeSet::new();
+    for footer in footers {
+        *codecs.entry(footer.codec.to_string()).or_insert(0) += 1;
+        for stripe in &footer.stripes {
+            stripe_sizes.push(stripe.size_bytes);
+            total_rows += stripe.rows;
+        }
+        for (column, has_statistics) in &footer.columns {
+            columns += 1;
+            if *has_statistics {
+                columns_with_stats += 1;
+            } else {
+                columns_missing_stats.insert(column.clone());
+            }
+        }
+    }
+
+    let decoded_files = footers.iter().filter(|f| f.decoded).count();
+    let total_stripes = stripe_sizes.len();
+    let avg = |total: f64, count: usize| {
+        if count > 0 {
+            total / count as f64
+        } else {
+            0.0
+        }
+    };
+
+    Some(OrcFooterMetrics {
+        sampled_files: decoded_files,
+        undecoded_files: footers.len() - decoded_files,
+        failed_files,
+        total_stripes,
+        avg_stripes_per_file: avg(total_stripes as f64, decoded_files),
+        avg_stripe_size_bytes: avg(stripe_sizes.iter().sum::<u64>() as f64, total_stripes),
+        min_stripe_size_bytes: stripe_sizes.iter().copied().min().unwrap_or(0),
+        max_stripe_size_bytes: stripe_sizes.iter().copied().max().unwrap_or(0),
+        avg_rows_per_stripe: avg(total_rows as f64, total_stripes),
+        codecs,
+        stats_completeness: if columns > 0 {
+            columns_with_stats as f64 / columns as f64
+        } else {
+            1.0
+        },
+        columns_missing_stats: columns_missing_stats.into_iter().collect(),
+    })
+}
+
+/// Recommendations drawn from sampled ORC tails
+pub fn footer_recommendations(footers: &OrcFooterMetrics) -> Vec<String> {
+    let mut recommendations = Vec::new();
+
+    if footers.avg_stripes_per_file > 1.0 && footers.avg_stripe_size_bytes < SMALL_STRIPE_BYTES {
+        recommendations.push(format!(
+            "Sampled ORC files hold {:.1} stripes of {:.2} MB on average. Raise the writer's stripe size (orc.stripe.size) so each file has fewer, larger stripes.",
+            footers.avg_stripes_per_file,
+            footers.avg_stripe_size_bytes / (1024.0 * 1024.0)
+        ));
+    }
+
+    let uncompressed = footers.codecs.get("none").copied().unwrap_or(0);
+    if uncompressed > 0 {
+        recommendations.push(format!(
+            "{} of {} sampled ORC files are uncompressed. Set orc.compress (e.g. ZSTD) in the writer and rewrite them to cut storage and scan time.",
+            uncompressed,
+            footers.sampled_files + footers.undecoded_files
+        ));
+    }
+
+    if footers.stats_completeness < 0.9 {
+        recommendations.push(format!(
+            "Only {:.0}% of sampled ORC columns carry statistics (missing for [{}]), so engines can't skip stripes on those columns. Enable column statistics in the writer.",
+            footers.stats_completeness * 100.0,
+            footers.columns_missing_stats.join(", ")
+        ));
+    }
+
+    recommendations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_varint(mut value: u64, out: &mut Vec<u8>) {
+        while value >= 0x80 {
+            out.push((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn int_field(number: u64, value: u64, out: &mut Vec<u8>) {
+        encode_varint(number << 3, out);
+        encode_varint(value, out);
+    }
+
+    fn bytes_field(number: u64, value: &[u8], out: &mut Vec<u8>) {
+        encode_varint(number << 3 | 2, out);
+        encode_varint(value.len() as u64, out);
+        out.extend_from_slice(value);
+    }
+
+    /// Tail of an ORC file with two 1 MB stripes and columns `id` (with integer statistics)
+    /// and `name` (value count only)
+    fn orc_tail(codec: u64) -> Vec<u8> {
+        let mut footer = Vec::new();
+        for _ in 0..2 {
+            let mut stripe = Vec::new();
+            int_field(1, 3, &mut stripe);
+            int_field(2, 1024, &mut stripe);
+            int_field(3, 1024 * 1024 - 2048, &mut stripe);
+            int_field(4, 1024, &mut stripe);
+            int_field(5, 5000, &mut stripe);
+            bytes_field(3, &stripe, &mut footer);
+        }
+        let mut root = Vec::new();
+        int_field(1, 12, &mut root); // STRUCT
+        bytes_field(2, &[1, 2], &mut root); // packed subtypes
+        bytes_field(3, b"id", &mut root);
+        bytes_field(3, b"name", &mut root);
+        bytes_field(4, &root, &mut footer);
+        let mut column_type = Vec::new();
+        int_field(1, 4, &mut column_type);
+        bytes_field(4, &column_type, &mut footer);
+        bytes_field(4, &column_type, &mut footer);
+        let mut root_stats = Vec::new();
+        int_field(1, 10000, &mut root_stats);
+        bytes_field(7, &root_stats, &mut footer);
+        let mut id_stats = Vec::new();
+        int_field(1, 10000, &mut id_stats);
+        let mut int_stats = Vec::new();
+        int_field(1, 0, &mut int_stats);
+        bytes_field(2, &int_stats, &mut id_stats);
+        bytes_field(7, &id_stats, &mut footer);
+        bytes_field(7, &root_stats, &mut footer);
+
+        let footer = if codec == 1 {
+            let compressed = miniz_oxide::deflate::compress_to_vec(&footer, 6);
+            let header = compressed.len() << 1;
+            let mut chunk = vec![header as u8, (header >> 8) as u8, (header >> 16) as u8];
+            chunk.extend(compressed);
+            chunk
+        } else {
+            footer
+        };
+        let mut postscript = Vec::new();
+        int_field(1, footer.len() as u64, &mut postscript);
+        int_field(2, codec, &mut postscript);
+        bytes_field(8000, b"ORC", &mut postscript);
+
+        let mut tail = b"stripe data".to_vec();
+        tail.extend(&footer);
+        tail.extend(&postscript);
+        tail.push(postscript.len() as u8);
+        tail
+    }
+
+    fn decode_complete(tail: &[u8]) -> OrcFooter {
+        match decode_tail(tail).unwrap() {
+            FooterTail::Complete(footer) => footer,
+            FooterTail::NeedBytes(_) => panic!("expected a complete footer"),
+        }
+    }
+
+    #[test]
+    fn test_decode_tail_and_summarize() {
+        let tail = orc_tail(1);
+        let footer = decode_complete(&tail);
+        let uncompressed = decode_complete(&orc_tail(0));
+        assert_eq!(footer.codec, "zlib");
+        assert_eq!(footer.stripes, uncompressed.stripes);
+        assert_eq!(footer.stripes.len(), 2);
+        assert_eq!(footer.stripes[0].size_bytes, 1024 * 1024);
+        assert_eq!(
+            footer.columns,
+            [("id".to_string(), true), ("name".to_string(), false)]
+        );
+        match decode_tail(&tail[tail.len() - 40..]).unwrap() {
+            FooterTail::NeedBytes(needed) => assert!(needed > 40),
+            FooterTail::Complete(_) => panic!("40 bytes can't hold the footer"),
+        }
+        assert!(decode_tail(b"PAR1").is_err());
+
+        let snappy = OrcFooter {
+            codec: "snappy",
+            decoded: false,
+            stripes: Vec::new(),
+            columns: Vec::new(),
+        };
+        let metrics = summarize_footers(&[footer, uncompressed, snappy], 1).unwrap();
+        assert_eq!(metrics.sampled_files, 2);
+        assert_eq!(metrics.undecoded_files, 1);
+        assert_eq!(metrics.failed_files, 1);
+        assert_eq!(metrics.avg_stripes_per_file, 2.0);
+        assert_eq!(metrics.avg_rows_per_stripe, 5000.0);
+        assert_eq!(metrics.codecs["none"], 1);
+        assert_eq!(metrics.stats_completeness, 0.5);
+        assert_eq!(metrics.columns_missing_stats, ["name"]);
+
+        let recommendations = footer_recommendations(&metrics);
+        assert_eq!(recommendations.len(), 3);
+        assert!(recommendations[0].contains("2.0 stripes of 1.00 MB"));
+        assert!(summarize_footers(&[], 0).is_none());
+    }
+}
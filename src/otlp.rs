@@ -0,0 +1,279 @@
+//! OpenTelemetry export of an analysis's spans (`otlp_endpoint`).
+//!
+//! The analysis runs under its own tracing subscriber that keeps every span at INFO and
+//! above (the analysis and its phases: listing, reading the log or manifests, scoring) as
+//! it closes. When the analysis ends, the spans are sent as one trace to an OTLP/HTTP
+//! collector in the JSON encoding (`POST {otlp_endpoint}/v1/traces`). Per-request storage
+//! spans are DEBUG and not exported; a 20-minute run would produce far too many of them.
+
+use crate::config::AnalysisConfig;
+use crate::stream::EventSink;
+use anyhow::{Context as _, Result};
+use serde_json::{json, Value};
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::instrument::WithSubscriber;
+use tracing::span::{Attributes, Id, Record};
+use tracing::Subscriber;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Span attributes, as tracing field names and values
+#[derive(Debug, Clone, Default)]
+struct SpanAttributes(Vec<(String, String)>);
+
+impl Visit for SpanAttributes {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name().to_string(), value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .push((field.name().to_string(), format!("{:?}", value)));
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FinishedSpan {
+    span_id: u64,
+    parent_span_id: Option<u64>,
+    name: String,
+    start: SystemTime,
+    end: SystemTime,
+    attributes: Vec<(String, String)>,
+}
+
+/// An open span: its OTLP id, start time and attributes so far
+struct OpenSpan {
+    span_id: u64,
+    start: SystemTime,
+    attributes: SpanAttributes,
+}
+
+/// Collects the spans of one analysis as they close
+#[derive(Clone)]
+struct SpanCollector {
+    id_base: u64,
+    next_id: Arc<Mutex<u64>>,
+    finished: Arc<Mutex<Vec<FinishedSpan>>>,
+}
+
+pub fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+impl SpanCollector {
+    fn new() -> Self {
+        Self {
+            id_base: random_u64(),
+            next_id: Arc::default(),
+            finished: Arc::default(),
+        }
+    }
+
+    fn next_span_id(&self) -> u64 {
+        let mut next = self.next_id.lock().unwrap_or_else(|e| e.into_inner());
+        *next += 1;
+        // Span ids must not be zero
+        self.id_base.wrapping_add(*next).max(1)
+    }
+
+    fn take(&self) -> Vec<FinishedSpan> {
+        std::mem::take(&mut *self.finished.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl<S> Layer<S> for SpanCollector
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut attributes = SpanAttributes::default();
+        attrs.record(&mut attributes);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(OpenSpan {
+                span_id: self.next_span_id(),
+                start: SystemTime::now(),
+                attributes,
+            });
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(open) = span.extensions_mut().get_mut::<OpenSpan>() {
+                values.record(&mut open.attributes);
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let parent_span_id = span.parent().and_then(|parent| {
+            parent
+                .extensions()
+                .get::<OpenSpan>()
+                .map(|open| open.span_id)
+        });
+        let extensions = span.extensions();
+        let Some(open) = extensions.get::<OpenSpan>() else {
+            return;
+        };
+        // Phase spans are named after their phase, e.g. "listing"
+        let name = open
+            .attributes
+            .0
+            .iter()
+            .find(|(key, _)| key == "phase")
+            .map_or(span.name(), |(_, phase)| phase.as_str())
+            .to_string();
+        self.finished
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(FinishedSpan {
+                span_id: open.span_id,
+                parent_span_id,
+                name,
+                start: open.start,
+                end: SystemTime::now(),
+                attributes: open.attributes.0.clone(),
+            });
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+/// OTLP/JSON `ExportTraceServiceRequest` for `spans` of one trace. Root spans get an error
+/// status when the analysis failed.
+fn export_request(trace_id: u128, spans: &[FinishedSpan], error: Option<&str>) -> Value {
+    let spans: Vec<Value> = spans
+        .iter()
+        .map(|span| {
+            let mut otlp_span = json!({
+                "traceId": format!("{:032x}", trace_id),
+                "spanId": format!("{:016x}", span.span_id),
+                "name": span.name,
+                "kind": 1, // SPAN_KIND_INTERNAL
+                "startTimeUnixNano": unix_nanos(span.start),
+                "endTimeUnixNano": unix_nanos(span.end),
+                "attributes": span
+                    .attributes
+                    .iter()
+                    .map(|(key, value)| json!({ "key": key, "value": { "stringValue": value } }))
+                    .collect::<Vec<_>>(),
+            });
+            if let Some(parent_span_id) = span.parent_span_id {
+                otlp_span["parentSpanId"] = json!(format!("{:016x}", parent_span_id));
+            }
+            if let (None, Some(message)) = (span.parent_span_id, error) {
+                otlp_span["status"] = json!({ "code": 2, "message": message }); // STATUS_CODE_ERROR
+            }
+            otlp_span
+        })
+        .collect();
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [{ "key": "service.name", "value": { "stringValue": "drainage" } }],
+            },
+            "scopeSpans": [{
+                "scope": { "name": "drainage", "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans,
+            }],
+        }],
+    })
+}
+
+async fn export(config: &AnalysisConfig, endpoint: &str, request: &Value) -> Result<()> {
+    let endpoint = endpoint.trim_end_matches('/');
+    let url = if endpoint.ends_with("/v1/traces") {
+        endpoint.to_string()
+    } else {
+        format!("{}/v1/traces", endpoint)
+    };
+    let mut post = config.network.http_client()?.post(&url).json(request);
+    for (name, value) in &config.otlp_headers {
+        post = post.header(name, value);
+    }
+    post.send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("OTLP export to {} failed", url))?;
+    Ok(())
+}
+
+/// Run `analysis`, exporting its spans when `otlp_endpoint` is set. A failed export is a
+/// warning, never an analysis failure.
+pub async fn traced<T, F>(config: &AnalysisConfig, events: &EventSink, analysis: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    let Some(ref endpoint) = config.otlp_endpoint else {
+        return analysis.await;
+    };
+    let collector = SpanCollector::new();
+    // Keep forwarding log records to Python while this subscriber is the default
+    let subscriber = tracing_subscriber::registry()
+        .with(crate::logging::python_layer())
+        .with(collector.clone().with_filter(LevelFilter::INFO));
+    let result = analysis.with_subscriber(subscriber).await;
+
+    let trace_id = (u128::from(random_u64()) << 64) | u128::from(random_u64());
+    let error = result
+        .as_ref()
+        .err()
+        .map(|e| crate::sanitize::sanitize(&e.to_string()));
+    let request = export_request(trace_id, &collector.take(), error.as_deref());
+    if let Err(e) = export(config, endpoint, &request).await {
+        events.warning(format!("{:#}", e));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collected_spans_export_as_one_trace() {
+        let collector = SpanCollector::new();
+        let subscriber =
+            tracing_subscriber::registry().with(collector.clone().with_filter(LevelFilter::INFO));
+        tracing::subscriber::with_default(subscriber, || {
+            let analyze = tracing::info_span!("analyze", table_type = "delta");
+            let _entered = analyze.enter();
+            tracing::info_span!("phase", phase = "listing").in_scope(|| {
+                // Storage request spans are DEBUG and not collected
+                tracing::debug_span!("get_object", key = "t/_delta_log/0.json").in_scope(|| {});
+            });
+        });
+
+        let spans = collector.take();
+        assert_eq!(spans.len(), 2);
+        let request = export_request(7, &spans, Some("listing failed"));
+        let exported = &request["resourceSpans"][0]["scopeSpans"][0]["spans"];
+        assert_eq!(exported[0]["name"], "listing");
+        assert_eq!(exported[0]["traceId"], format!("{:032x}", 7));
+        assert_eq!(exported[0]["parentSpanId"], exported[1]["spanId"]);
+        assert!(exported[0].get("status").is_none());
+        assert_eq!(exported[1]["name"], "analyze");
+        assert_eq!(
+            exported[1]["attributes"][0],
+            json!({ "key": "table_type", "value": { "stringValue": "delta" } })
+        );
+        assert_eq!(exported[1]["status"]["code"], 2);
+    }
+}
@@ -0,0 +1,628 @@
+use crate::s3_client::{ObjectInfo, S3ClientWrapper};
+use crate::types::{ParquetColumnInfo, ParquetFooterMetrics};
+use anyhow::Result;
+use parquet::basic::{Compression, Type as PhysicalType};
+use parquet::file::metadata::{ParquetMetaData, ParquetMetaDataReader};
+use std::collections::{BTreeSet, HashMap};
+
+/// Bytes fetched from the end of a file on the first attempt; most footers fit
+const FOOTER_FETCH_BYTES: u64 = 64 * 1024;
+
+/// Parquet files end with the metadata length (4 bytes) and the `PAR1` magic
+const FOOTER_TRAILER_BYTES: usize = 8;
+
+/// Result of decoding the tail of a Parquet file
+enum FooterTail {
+    Complete(Box<ParquetMetaData>),
+    /// The metadata is longer than the tail; this many trailing bytes are needed
+    NeedBytes(usize),
+}
+
+fn decode_tail(tail: &[u8]) -> Result<FooterTail> {
+    if tail.len() < FOOTER_TRAILER_BYTES {
+        return Err(anyhow::anyhow!("File too small to be Parquet"));
+    }
+    let trailer: &[u8; FOOTER_TRAILER_BYTES] =
+        tail[tail.len() - FOOTER_TRAILER_BYTES..].try_into()?;
+    let metadata_len = ParquetMetaDataReader::decode_footer(trailer)?;
+    let needed = metadata_len + FOOTER_TRAILER_BYTES;
+    if needed > tail.len() {
+        return Ok(FooterTail::NeedBytes(needed));
+    }
+
+    let metadata_start = tail.len() - needed;
+    let metadata = ParquetMetaDataReader::decode_metadata(
+        &tail[metadata_start..tail.len() - FOOTER_TRAILER_BYTES],
+    )?;
+    Ok(FooterTail::Complete(Box::new(metadata)))
+}
+
+/// Read a data file's footer with one range GET, or two when the metadata is large
+pub async fn fetch_footer(
+    s3_client: &S3ClientWrapper,
+    file: &ObjectInfo,
+) -> Result<ParquetMetaData> {
+    let tail = s3_client
+        .get_object_tail(&file.key, FOOTER_FETCH_BYTES.min(file.size.max(0) as u64))
+        .await?;
+    match decode_tail(&tail)? {
+        FooterTail::Complete(metadata) => Ok(*metadata),
+        FooterTail::NeedBytes(needed) => {
+            let tail = s3_client.get_object_tail(&file.key, needed as u64).await?;
+            match decode_tail(&tail)? {
+                FooterTail::Complete(metadata) => Ok(*metadata),
+                FooterTail::NeedBytes(_) => Err(anyhow::anyhow!("Truncated Parquet footer")),
+            }
+        }
+    }
+}
+
+/// Pick up to `sample_size` files spread evenly across the listing
+pub fn sample_files<'a>(files: &[&'a ObjectInfo], sample_size: usize) -> Vec<&'a ObjectInfo> {
+    if sample_size == 0 || files.is_empty() {
+        return Vec::new();
+    }
+    if files.len() <= sample_size {
+        return files.to_vec();
+    }
+
+    let step = files.len() as f64 / sample_size as f64;
+    (0..sample_size)
+        .map(|i| files[(i as f64 * step) as usize])
+        .collect()
+}
+
+/// Fetch footers for a sample of data files. Files whose footers can't be read are
+/// counted rather than failing the analysis.
+pub async fn sample_footers(
+    s3_client: &S3ClientWrapper,
+    data_files: &[&ObjectInfo],
+    sample_size: usize,
+) -> (Vec<ParquetMetaData>, usize) {
+    let mut footers = Vec::new();
+    let mut failed_files = 0;
+    // ORC and Avro data files have no Parquet footer
+    let parquet_files: Vec<&ObjectInfo> = data_files
+        .iter()
+        .copied()
+        .filter(|file| file.key.ends_with(".parquet"))
+        .collect();
+    for file in sample_files(&parquet_files, sample_size) {
+        match fetch_footer(s3_client, file).await {
+            Ok(metadata) => footers.push(metadata),
+            Err(_) => failed_files += 1,
+        }
+    }
+    (footers, failed_files)
+}
+
+pub fn codec_name(compression: Compression) -> &'static str {
+    match compression {
+        Compression::UNCOMPRESSED => "uncompressed",
+        Compression::SNAPPY => "snappy",
+        Compression::GZIP(_) => "gzip",
+        Compression::LZO => "lzo",
+        Compression::BROTLI(_) => "brotli",
+        Compression::LZ4 => "lz4",
+        Compression::ZSTD(_) => "zstd",
+        Compression::LZ4_RAW => "lz4_raw",
+    }
+}
+
+/// Expected size after re-compressing a chunk with zstd, relative to its current size.
+/// Rough figures for typical analytic data; `None` for codecs zstd rarely beats.
+fn zstd_size_factor(codec: &str) -> Option<f64> {
+    match codec {
+        "uncompressed" => Some(0.35),
+        "snappy" | "lz4" | "lz4_raw" | "lzo" => Some(0.75),
+        _ => None,
+    }
+}
+
+/// Expected size of a poorly compressed string chunk once dictionary encoded
+const DICTIONARY_SIZE_FACTOR: f64 = 0.7;
+
+/// String chunks compressing worse than this without a dictionary are dictionary candidates
+const DICTIONARY_CANDIDATE_MAX_RATIO: f64 = 2.0;
+
+/// Columns whose sampled chunks report at least this share of distinct values are high-cardinality
+const HIGH_CARDINALITY_DISTINCT_RATIO: f64 = 0.5;
+
+#[derive(Default)]
+struct ColumnAccumulator {
+    compressed: u64,
+    uncompressed: u64,
+    codecs: HashMap<&'static str, usize>,
+    chunks: usize,
+    dictionary_chunks: usize,
+    bloom_filter_chunks: usize,
+    page_index_chunks: usize,
+    values: i64,
+    distinct_values: Option<u64>,
+    is_boolean: bool,
+}
+
+impl ColumnAccumulator {
+    /// Writers rarely record distinct counts, so fall back to dictionary usage: writers
+    /// drop the dictionary once a chunk has too many distinct values.
+    fn is_high_cardinality(&self) -> bool {
+        if self.is_boolean {
+            return false;
+        }
+        match self.distinct_values {
+            Some(distinct) if self.values > 0 => {
+                distinct as f64 / self.values as f64 >= HIGH_CARDINALITY_DISTINCT_RATIO
+            }
+            _ => self.dictionary_chunks * 2 < self.chunks,
+        }
+    }
+}
+
+/// Whether a chunk has both the column index and the offset index (the page index)
+fn has_page_index(column: &parquet::file::metadata::ColumnChunkMetaData) -> bool {
+    column.column_index_offset().is_some() && column.offset_index_offset().is_some()
+}
+
+fn ratio(uncompressed: u64, compressed: u64) -> f64 {
+    if compressed > 0 {
+        uncompressed as f64 / compressed as f64
+    } else {
+        1.0
+    }
+}
+
+/// Summarize row group layout, codecs, encodings and statistics coverage of sampled footers.
+/// Projected savings are scaled from the sample up to `table_size_bytes`.
+pub fn summarize_footers(
+    footers: &[ParquetMetaData],
+    failed_files: usize,
+    table_size_bytes: u64,
+) -> Option<ParquetFooterMetrics> {
+    if footers.is_empty() && failed_files == 0 {
+        return None;
+    }
+
+    let mut row_group_sizes: Vec<u64> = Vec::new();
+    let mut total_rows = 0i64;
+    let mut codecs: HashMap<String, usize> = HashMap::new();
+    let mut encodings: HashMap<String, usize> = HashMap::new();
+    let mut created_by: HashMap<String, usize> = HashMap::new();
+    let mut column_chunks = 0usize;
+    let mut chunks_with_stats = 0usize;
+    let mut columns_missing_stats: BTreeSet<String> = BTreeSet::new();
+    let mut columns: HashMap<String, ColumnAccumulator> = HashMap::new();
+    let mut codec_savings = 0.0;
+    let mut dictionary_savings = 0.0;
+
+    for footer in footers {
+        let writer = footer
+            .file_metadata()
+            .created_by()
+            .unwrap_or("unknown")
+            .to_string();
+        *created_by.entry(writer).or_insert(0) += 1;
+
+        for row_group in footer.row_groups() {
+            row_group_sizes.push(row_group.compressed_size().max(0) as u64);
+            total_rows += row_group.num_rows();
+
+            for column in row_group.columns() {
+                column_chunks += 1;
+                let codec = codec_name(column.compression());
+                *codecs.entry(codec.to_string()).or_insert(0) += 1;
+
+                let compressed = column.compressed_size().max(0) as u64;
+                let uncompressed = column.uncompressed_size().max(0) as u64;
+                let has_dictionary = column.dictionary_page_offset().is_some();
+                if let Some(factor) = zstd_size_factor(codec) {
+                    codec_savings += compressed as f64 * (1.0 - factor);
+                }
+                if !has_dictionary
+                    && column.column_type() == PhysicalType::BYTE_ARRAY
+                    && ratio(uncompressed, compressed) < DICTIONARY_CANDIDATE_MAX_RATIO
+                {
+                    dictionary_savings += compressed as f64 * (1.0 - DICTIONARY_SIZE_FACTOR);
+                }
+
+                let entry = columns.entry(column.column_path().string()).or_default();
+                entry.compressed += compressed;
+                entry.uncompressed += uncompressed;
+                *entry.codecs.entry(codec).or_insert(0) += 1;
+                entry.chunks += 1;
+                if has_dictionary {
+                    entry.dictionary_chunks += 1;
+                }
+                if column.bloom_filter_offset().is_some() {
+                    entry.bloom_filter_chunks += 1;
+                }
+                if has_page_index(column) {
+                    entry.page_index_chunks += 1;
+                }
+                entry.values += column.num_values();
+                entry.is_boolean = column.column_type() == PhysicalType::BOOLEAN;
+                let distinct = column.statistics().and_then(|s| s.distinct_count_opt());
+                entry.distinct_values = match (entry.chunks, entry.distinct_values, distinct) {
+                    (1, _, Some(distinct)) => Some(distinct),
+                    (_, Some(total), Some(distinct)) => Some(total + distinct),
+                    _ => None,
+                };
+
+                for encoding in column.encodings() {
+                    *encodings.entry(encoding.to_string()).or_insert(0) += 1;
+                }
+                if column
+                    .statistics()
+                    .is_some_and(|s| s.min_bytes_opt().is_some() && s.max_bytes_opt().is_some())
+                {
+                    chunks_with_stats += 1;
+                } else {
+                    columns_missing_stats.insert(column.column_path().string());
+                }
+            }
+        }
+    }
+
+    let sampled_compressed: u64 = columns.values().map(|c| c.compressed).sum();
+    let sampled_uncompressed: u64 = columns.values().map(|c| c.uncompressed).sum();
+    let scale = if sampled_compressed > 0 {
+        table_size_bytes as f64 / sampled_compressed as f64
+    } else {
+        0.0
+    };
+
+    let high_cardinality: BTreeSet<String> = columns
+        .iter()
+        .filter(|(_, acc)| acc.is_high_cardinality())
+        .map(|(column, _)| column.clone())
+        .collect();
+
+    // A file is covered when every chunk of a high-cardinality column in it is indexed
+    let mut files_with_high_cardinality = 0usize;
+    let mut files_with_bloom_filters = 0usize;
+    let mut files_with_page_index = 0usize;
+    for footer in footers {
+        let chunks: Vec<_> = footer
+            .row_groups()
+            .iter()
+            .flat_map(|row_group| row_group.columns())
+            .filter(|column| high_cardinality.contains(&column.column_path().string()))
+            .collect();
+        if chunks.is_empty() {
+            continue;
+        }
+        files_with_high_cardinality += 1;
+        if chunks.iter().all(|c| c.bloom_filter_offset().is_some()) {
+            files_with_bloom_filters += 1;
+        }
+        if chunks.iter().all(|c| has_page_index(c)) {
+            files_with_page_index += 1;
+        }
+    }
+    let file_coverage = |covered: usize| {
+        if files_with_high_cardinality > 0 {
+            covered as f64 / files_with_high_cardinality as f64
+        } else {
+            1.0
+        }
+    };
+
+    let mut columns: Vec<ParquetColumnInfo> = columns
+        .into_iter()
+        .map(|(column, acc)| ParquetColumnInfo {
+            codec: acc
+                .codecs
+                .iter()
+                .max_by_key(|(codec, count)| (**count, std::cmp::Reverse(**codec)))
+                .map(|(codec, _)| codec.to_string())
+                .unwrap_or_default(),
+            compressed_size_bytes: acc.compressed,
+            uncompressed_size_bytes: acc.uncompressed,
+            compression_ratio: ratio(acc.uncompressed, acc.compressed),
+            dictionary_encoded: acc.dictionary_chunks == acc.chunks,
+            high_cardinality: high_cardinality.contains(&column),
+            bloom_filter_coverage: acc.bloom_filter_chunks as f64 / acc.chunks as f64,
+            page_index_coverage: acc.page_index_chunks as f64 / acc.chunks as f64,
+            column,
+        })
+        .collect();
+    columns.sort_by(|a, b| {
+        b.compressed_size_bytes
+            .cmp(&a.compressed_size_bytes)
+            .then_with(|| a.column.cmp(&b.column))
+    });
+
+    let total_row_groups = row_group_sizes.len();
+    let avg = |total: f64, count: usize| {
+        if count > 0 {
+            total / count as f64
+        } else {
+            0.0
+        }
+    };
+
+    Some(ParquetFooterMetrics {
+        sampled_files: footers.len(),
+        failed_files,
+        total_row_groups,
+        avg_row_groups_per_file: avg(total_row_groups as f64, footers.len()),
+        avg_row_group_size_bytes: avg(row_group_sizes.iter().sum::<u64>() as f64, total_row_groups),
+        min_row_group_size_bytes: row_group_sizes.iter().copied().min().unwrap_or(0),
+        max_row_group_size_bytes: row_group_sizes.iter().copied().max().unwrap_or(0),
+        avg_rows_per_row_group: avg(total_rows as f64, total_row_groups),
+        codecs,
+        encodings,
+        created_by,
+        stats_completeness: if column_chunks > 0 {
+            chunks_with_stats as f64 / column_chunks as f64
+        } else {
+            1.0
+        },
+        columns_missing_stats: columns_missing_stats.into_iter().collect(),
+        compression_ratio: ratio(sampled_uncompressed, sampled_compressed),
+        columns,
+        projected_codec_savings_bytes: (codec_savings * scale) as u64,
+        projected_dictionary_savings_bytes: (dictionary_savings * scale) as u64,
+        high_cardinality_columns: high_cardinality.into_iter().collect(),
+        bloom_filter_coverage: file_coverage(files_with_bloom_filters),
+        page_index_coverage: file_coverage(files_with_page_index),
+    })
+}
+
+/// Row groups below this size (when files hold several) make readers seek more than they scan
+const SMALL_ROW_GROUP_BYTES: f64 = 16.0 * 1024.0 * 1024.0;
+
+/// Share of the table a zstd rewrite must save before it is recommended
+pub(crate) const CODEC_REWRITE_MIN_SAVINGS: f64 = 0.1;
+
+/// Recommendations drawn from sampled footers; shared by the Delta and Iceberg analyzers
+pub fn footer_recommendations(
+    footers: &ParquetFooterMetrics,
+    table_size_bytes: u64,
+    clustering_columns: &[String],
+) -> Vec<String> {
+    let mut recommendations = Vec::new();
+    let table_size = table_size_bytes as f64;
+
+    if footers.avg_row_groups_per_file > 1.0
+        && footers.avg_row_group_size_bytes < SMALL_ROW_GROUP_BYTES
+    {
+        recommendations.push(format!(
+            "Sampled files hold {:.1} row groups of {:.2} MB on average. Raise the writer's row group size (e.g. parquet.block.size) so each file has fewer, larger row groups.",
+            footers.avg_row_groups_per_file,
+            footers.avg_row_group_size_bytes / (1024.0 * 1024.0)
+        ));
+    }
+
+    if footers.stats_completeness < 0.9 {
+        recommendations.push(format!(
+            "Only {:.0}% of sampled column chunks carry min/max statistics (missing for [{}]), so engines can't skip row groups on those columns. Enable statistics in the writer.",
+            footers.stats_completeness * 100.0,
+            footers.columns_missing_stats.join(", ")
+        ));
+    }
+
+    // Only worth a rewrite when the projected savings are a meaningful share of the table
+    if table_size > 0.0
+        && footers.projected_codec_savings_bytes as f64 >= table_size * CODEC_REWRITE_MIN_SAVINGS
+    {
+        let mut codecs: Vec<&str> = footers
+            .columns
+            .iter()
+            .filter(|c| zstd_size_factor(&c.codec).is_some())
+            .map(|c| c.codec.as_str())
+            .collect();
+        codecs.sort();
+        codecs.dedup();
+        recommendations.push(format!(
+            "Most data is written with {} compression. Switching the writer to zstd could save an estimated {:.2} MB ({:.0}% of the table) once files are rewritten.",
+            codecs.join("/"),
+            footers.projected_codec_savings_bytes as f64 / (1024.0 * 1024.0),
+            footers.projected_codec_savings_bytes as f64 / table_size * 100.0
+        ));
+    }
+
+    if table_size > 0.0 && footers.projected_dictionary_savings_bytes as f64 >= table_size * 0.05 {
+        let candidates: Vec<&str> = footers
+            .columns
+            .iter()
+            .filter(|c| {
+                !c.dictionary_encoded && c.compression_ratio < DICTIONARY_CANDIDATE_MAX_RATIO
+            })
+            .map(|c| c.column.as_str())
+            .collect();
+        recommendations.push(format!(
+            "String columns [{}] are written without dictionary encoding and compress poorly. Enabling dictionary encoding could save an estimated {:.2} MB.",
+            candidates.join(", "),
+            footers.projected_dictionary_savings_bytes as f64 / (1024.0 * 1024.0)
+        ));
+    }
+
+    // Clustering on a high-cardinality column suggests point lookups, which bloom filters
+    // and page indexes serve far better than row group min/max statistics
+    let unindexed: Vec<&ParquetColumnInfo> = footers
+        .columns
+        .iter()
+        .filter(|c| c.high_cardinality && clustering_columns.contains(&c.column))
+        .filter(|c| c.bloom_filter_coverage < 1.0 || c.page_index_coverage < 1.0)
+        .collect();
+    if !unindexed.is_empty() {
+        let names: Vec<&str> = unindexed.iter().map(|c| c.column.as_str()).collect();
+        recommendations.push(format!(
+            "Clustering columns [{}] have high cardinality, but only {:.0}% of sampled files carry bloom filters and {:.0}% carry page indexes on them. Enable bloom filters and page indexes for these columns in the writer to speed up point lookups.",
+            names.join(", "),
+            footers.bloom_filter_coverage * 100.0,
+            footers.page_index_coverage * 100.0
+        ));
+    }
+
+    recommendations
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+    use parquet::file::properties::{EnabledStatistics, WriterProperties};
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use parquet::schema::types::ColumnPath;
+    use std::sync::Arc;
+
+    /// Write a small uncompressed Parquet file with two row groups; `name` has no statistics
+    /// and no dictionary, and optionally a bloom filter
+    pub(crate) fn write_test_parquet(name_bloom_filter: bool) -> Vec<u8> {
+        let schema = Arc::new(
+            parse_message_type("message test { required int64 id; required binary name (UTF8); }")
+                .unwrap(),
+        );
+        let props = Arc::new(
+            WriterProperties::builder()
+                .set_column_statistics_enabled(ColumnPath::from("name"), EnabledStatistics::None)
+                .set_column_dictionary_enabled(ColumnPath::from("name"), false)
+                .set_column_bloom_filter_enabled(ColumnPath::from("name"), name_bloom_filter)
+                .build(),
+        );
+        let mut buffer = Vec::new();
+        let mut writer = SerializedFileWriter::new(&mut buffer, schema, props).unwrap();
+        for _ in 0..2 {
+            let mut row_group = writer.next_row_group().unwrap();
+            let mut id = row_group.next_column().unwrap().unwrap();
+            id.typed::<Int64Type>()
+                .write_batch(&[1, 2, 3], None, None)
+                .unwrap();
+            id.close().unwrap();
+            let mut name = row_group.next_column().unwrap().unwrap();
+            name.typed::<ByteArrayType>()
+                .write_batch(
+                    &[
+                        ByteArray::from("a"),
+                        ByteArray::from("b"),
+                        ByteArray::from("c"),
+                    ],
+                    None,
+                    None,
+                )
+                .unwrap();
+            name.close().unwrap();
+            row_group.close().unwrap();
+        }
+        writer.close().unwrap();
+        buffer
+    }
+
+    fn decode_complete(tail: &[u8]) -> ParquetMetaData {
+        match decode_tail(tail).unwrap() {
+            FooterTail::Complete(metadata) => *metadata,
+            FooterTail::NeedBytes(_) => panic!("expected a complete footer"),
+        }
+    }
+
+    #[test]
+    fn test_decode_tail_requests_more_bytes_for_large_footers() {
+        let file = write_test_parquet(false);
+
+        let short_tail = &file[file.len() - 16..];
+        match decode_tail(short_tail).unwrap() {
+            FooterTail::NeedBytes(needed) => {
+                assert!(needed > 16);
+                let metadata = decode_complete(&file[file.len() - needed..]);
+                assert_eq!(metadata.num_row_groups(), 2);
+            }
+            FooterTail::Complete(_) => panic!("16 bytes can't hold the footer"),
+        }
+        assert!(decode_tail(b"PAR1").is_err());
+    }
+
+    #[test]
+    fn test_summarize_footers() {
+        let file = write_test_parquet(false);
+        let metadata = decode_complete(&file);
+        let metrics = summarize_footers(&[metadata], 1, file.len() as u64).unwrap();
+
+        assert_eq!(metrics.sampled_files, 1);
+        assert_eq!(metrics.failed_files, 1);
+        assert_eq!(metrics.total_row_groups, 2);
+        assert_eq!(metrics.avg_row_groups_per_file, 2.0);
+        assert_eq!(metrics.avg_rows_per_row_group, 3.0);
+        assert_eq!(metrics.codecs["uncompressed"], 4);
+        assert_eq!(metrics.stats_completeness, 0.5);
+        assert_eq!(metrics.columns_missing_stats, vec!["name"]);
+        assert!(metrics.min_row_group_size_bytes > 0);
+        assert!(metrics.compression_ratio > 0.0);
+        assert_eq!(metrics.columns.len(), 2);
+        let name = metrics.columns.iter().find(|c| c.column == "name").unwrap();
+        assert_eq!(name.codec, "uncompressed");
+        assert!(!name.dictionary_encoded);
+        let id = metrics.columns.iter().find(|c| c.column == "id").unwrap();
+        assert!(id.dictionary_encoded);
+        // Uncompressed chunks are projected to shrink to 35% under zstd
+        assert!(metrics.projected_codec_savings_bytes > 0);
+        assert!(metrics.projected_dictionary_savings_bytes > 0);
+        assert!(summarize_footers(&[], 0, 0).is_none());
+    }
+
+    #[test]
+    fn test_footer_recommendations() {
+        let file = write_test_parquet(false);
+        let table_size = file.len() as u64;
+        let metrics = summarize_footers(&[decode_complete(&file)], 0, table_size).unwrap();
+
+        let recommendations = footer_recommendations(&metrics, table_size, &[]);
+        assert_eq!(recommendations.len(), 4);
+        assert!(recommendations[0].contains("2.0 row groups"));
+        assert!(recommendations[1].contains("[name]"));
+        assert!(recommendations[2].contains("uncompressed compression"));
+        assert!(recommendations[3].contains("String columns [name]"));
+
+        let clustered = footer_recommendations(&metrics, table_size, &["name".to_string()]);
+        assert_eq!(clustered.len(), 5);
+        assert!(clustered[4].contains("Clustering columns [name]"));
+    }
+
+    #[test]
+    fn test_summarize_footers_index_coverage() {
+        let without = write_test_parquet(false);
+        let with = write_test_parquet(true);
+        let footers = [decode_complete(&without), decode_complete(&with)];
+        let metrics = summarize_footers(&footers, 0, 0).unwrap();
+
+        // `name` has no dictionary, so it is treated as high-cardinality
+        assert_eq!(metrics.high_cardinality_columns, vec!["name"]);
+        assert_eq!(metrics.bloom_filter_coverage, 0.5);
+        let name = metrics.columns.iter().find(|c| c.column == "name").unwrap();
+        assert!(name.high_cardinality);
+        assert_eq!(name.bloom_filter_coverage, 0.5);
+        // Without statistics the writer emits no column index for `name`
+        assert_eq!(name.page_index_coverage, 0.0);
+        assert_eq!(metrics.page_index_coverage, 0.0);
+
+        let id = metrics.columns.iter().find(|c| c.column == "id").unwrap();
+        assert!(!id.high_cardinality);
+        assert_eq!(id.bloom_filter_coverage, 0.0);
+    }
+
+    #[test]
+    fn test_sample_files_spreads_across_listing() {
+        let files: Vec<ObjectInfo> = (0..10)
+            .map(|i| ObjectInfo {
+                key: format!("part-{}.parquet", i),
+                size: 1,
+                last_modified: None,
+                etag: None,
+                storage_class: None,
+            })
+            .collect();
+        let refs: Vec<&ObjectInfo> = files.iter().collect();
+
+        let sample: Vec<&str> = sample_files(&refs, 3)
+            .iter()
+            .map(|f| f.key.as_str())
+            .collect();
+        assert_eq!(
+            sample,
+            vec!["part-0.parquet", "part-3.parquet", "part-6.parquet"]
+        );
+        assert_eq!(sample_files(&refs, 20).len(), 10);
+        assert!(sample_files(&refs, 0).is_empty());
+    }
+}
@@ -0,0 +1,75 @@
+//! Partial results (`allow_partial=True`): a failing optional phase of an analysis
+//! (clustering, deletion vectors, schema evolution, ...) is recorded on the report and
+//! reported as a warning instead of failing the whole `analyze_*` call.
+//!
+//! Listing, metadata and the data file inventory are never optional: without them there is
+//! nothing to report on.
+
+use crate::stream::EventSink;
+use crate::types::PhaseFailure;
+use anyhow::Result;
+
+/// Failures of the optional phases of one analysis
+pub struct Phases<'a> {
+    allow_partial: bool,
+    events: &'a EventSink,
+    failures: Vec<PhaseFailure>,
+}
+
+impl<'a> Phases<'a> {
+    pub fn new(allow_partial: bool, events: &'a EventSink) -> Self {
+        Self {
+            allow_partial,
+            events,
+            failures: Vec::new(),
+        }
+    }
+
+    /// The outcome of optional `phase`. With partial results allowed, a failure is recorded
+    /// and None returned; otherwise it fails the analysis as before.
+    pub fn check<T>(&mut self, phase: &str, result: Result<T>) -> Result<Option<T>> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if self.allow_partial => {
+                self.events
+                    .warning(format!("Skipped {} after an error: {}", phase, e));
+                self.failures.push(PhaseFailure {
+                    phase: phase.to_string(),
+                    error: crate::sanitize::sanitize(&e.to_string()),
+                });
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The recorded failures, None when every phase succeeded
+    pub fn into_failures(self) -> Option<Vec<PhaseFailure>> {
+        (!self.failures.is_empty()).then_some(self.failures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases_downgrade_failures_when_partial() {
+        let events = EventSink::default();
+        let mut strict = Phases::new(false, &events);
+        assert_eq!(strict.check("clustering", Ok(1)).unwrap(), Some(1));
+        assert!(strict
+            .check::<()>("clustering", Err(anyhow::anyhow!("bad log entry")))
+            .is_err());
+        assert!(strict.into_failures().is_none());
+
+        let mut partial = Phases::new(true, &events);
+        let skipped = partial
+            .check::<u32>("schema_evolution", Err(anyhow::anyhow!("truncated commit")))
+            .unwrap();
+        assert_eq!(skipped, None);
+        let failures = partial.into_failures().unwrap();
+        assert_eq!(failures[0].phase, "schema_evolution");
+        assert_eq!(failures[0].error, "truncated commit");
+    }
+}
@@ -0,0 +1,206 @@
+//! Iceberg partition statistics files.
+//!
+//! Table metadata can list, under `partition-statistics`, a Parquet file per snapshot with one
+//! row per partition: record, file and delete counts and the total data file size. When the
+//! file describes the current snapshot, partition row counts come from it rather than from
+//! summing per-file counts. Statistics left behind by later commits are reported as stale,
+//! since engines that plan with them see old numbers.
+
+use crate::types::PartitionStatisticsMetrics;
+use anyhow::Result;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::{Field, Row};
+use serde_json::Value;
+
+/// One row of a partition statistics file
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionStats {
+    pub partition: String, // "field=value/..." in partition spec order
+    pub data_record_count: u64,
+    pub data_file_count: u64,
+    pub total_data_file_size_bytes: u64,
+    pub delete_file_count: u64, // position and equality delete files
+}
+
+/// (snapshot id, statistics path) of the statistics file for the current snapshot, or of
+/// the newest snapshot that has one
+pub fn latest_statistics_file(metadata: &Value) -> Option<(i64, String)> {
+    let entries = metadata.get("partition-statistics")?.as_array()?;
+    let snapshot_time = |id: i64| snapshot_timestamp_ms(metadata, id).unwrap_or(i64::MIN);
+    entries
+        .iter()
+        .filter_map(|entry| {
+            Some((
+                entry.get("snapshot-id")?.as_i64()?,
+                entry.get("statistics-path")?.as_str()?.to_string(),
+            ))
+        })
+        .max_by_key(|(id, _)| {
+            (
+                Some(*id) == current_snapshot_id(metadata),
+                snapshot_time(*id),
+            )
+        })
+}
+
+fn current_snapshot_id(metadata: &Value) -> Option<i64> {
+    metadata.get("current-snapshot-id")?.as_i64()
+}
+
+fn snapshot_timestamp_ms(metadata: &Value, snapshot_id: i64) -> Option<i64> {
+    metadata
+        .get("snapshots")?
+        .as_array()?
+        .iter()
+        .find(|snapshot| {
+            snapshot.get("snapshot-id").and_then(|id| id.as_i64()) == Some(snapshot_id)
+        })?
+        .get("timestamp-ms")?
+        .as_i64()
+}
+
+fn as_u64(field: &Field) -> u64 {
+    match field {
+        Field::Int(value) => (*value).max(0) as u64,
+        Field::Long(value) => (*value).max(0) as u64,
+        _ => 0,
+    }
+}
+
+fn partition_path(partition: &Row) -> String {
+    partition
+        .get_column_iter()
+        .map(|(name, value)| match value {
+            Field::Str(value) => format!("{}={}", name, value),
+            Field::Null => format!("{}=null", name),
+            value => format!("{}={}", name, value),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Rows of a partition statistics file
+pub fn parse(content: Vec<u8>) -> Result<Vec<PartitionStats>> {
+    let reader = SerializedFileReader::new(bytes::Bytes::from(content))?;
+    let mut stats = Vec::new();
+    for row in reader.get_row_iter(None)? {
+        let row = row?;
+        let mut partition = PartitionStats {
+            partition: String::new(),
+            data_record_count: 0,
+            data_file_count: 0,
+            total_data_file_size_bytes: 0,
+            delete_file_count: 0,
+        };
+        for (name, field) in row.get_column_iter() {
+            match (name.as_str(), field) {
+                ("partition", Field::Group(values)) => partition.partition = partition_path(values),
+                ("data_record_count", field) => partition.data_record_count = as_u64(field),
+                ("data_file_count", field) => partition.data_file_count = as_u64(field),
+                ("total_data_file_size_in_bytes", field) => {
+                    partition.total_data_file_size_bytes = as_u64(field);
+                }
+                ("position_delete_file_count" | "equality_delete_file_count", field) => {
+                    partition.delete_file_count += as_u64(field);
+                }
+                _ => {}
+            }
+        }
+        stats.push(partition);
+    }
+    Ok(stats)
+}
+
+/// Summary of the statistics file `path` written for `snapshot_id`, and how far the table
+/// has moved on since
+pub fn summarize(
+    stats: &[PartitionStats],
+    snapshot_id: i64,
+    path: String,
+    metadata: &Value,
+) -> PartitionStatisticsMetrics {
+    let stats_time = snapshot_timestamp_ms(metadata, snapshot_id);
+    let current_time =
+        current_snapshot_id(metadata).and_then(|id| snapshot_timestamp_ms(metadata, id));
+    let snapshots_behind = match stats_time {
+        Some(stats_time) => {
+            metadata
+                .get("snapshots")
+                .and_then(|s| s.as_array())
+                .map_or(0, |snapshots| {
+                    snapshots
+                        .iter()
+                        .filter_map(|snapshot| snapshot.get("timestamp-ms")?.as_i64())
+                        .filter(|time| *time > stats_time)
+                        .count()
+                })
+        }
+        // Written for a snapshot that has since expired
+        None => current_snapshot_id(metadata).map_or(0, |_| 1),
+    };
+    let staleness_hours = match (stats_time, current_time) {
+        (Some(stats_time), Some(current_time)) => {
+            (current_time - stats_time).max(0) as f64 / 3_600_000.0
+        }
+        _ => 0.0,
+    };
+
+    PartitionStatisticsMetrics {
+        statistics_path: path,
+        snapshot_id,
+        snapshots_behind,
+        staleness_hours,
+        partition_count: stats.len(),
+        data_record_count: stats.iter().map(|p| p.data_record_count).sum(),
+        data_file_count: stats.iter().map(|p| p.data_file_count).sum(),
+        total_data_file_size_bytes: stats.iter().map(|p| p.total_data_file_size_bytes).sum(),
+        partitions_with_deletes: stats.iter().filter(|p| p.delete_file_count > 0).count(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_statistics_file_staleness() {
+        let metadata = json!({
+            "current-snapshot-id": 3,
+            "snapshots": [
+                { "snapshot-id": 1, "timestamp-ms": 1_700_000_000_000i64 },
+                { "snapshot-id": 2, "timestamp-ms": 1_700_003_600_000i64 },
+                { "snapshot-id": 3, "timestamp-ms": 1_700_007_200_000i64 },
+            ],
+            "partition-statistics": [
+                { "snapshot-id": 2, "statistics-path": "s3://lake/t/metadata/stats-2.parquet", "file-size-in-bytes": 1 },
+                { "snapshot-id": 1, "statistics-path": "s3://lake/t/metadata/stats-1.parquet", "file-size-in-bytes": 1 },
+            ],
+        });
+
+        let (snapshot_id, path) = latest_statistics_file(&metadata).unwrap();
+        assert_eq!(snapshot_id, 2);
+        let stats = vec![
+            PartitionStats {
+                partition: "date=2024-01-01".to_string(),
+                data_record_count: 10,
+                data_file_count: 2,
+                total_data_file_size_bytes: 100,
+                delete_file_count: 1,
+            },
+            PartitionStats {
+                partition: "date=2024-01-02".to_string(),
+                data_record_count: 30,
+                data_file_count: 1,
+                total_data_file_size_bytes: 300,
+                delete_file_count: 0,
+            },
+        ];
+        let summary = summarize(&stats, snapshot_id, path, &metadata);
+        assert_eq!(summary.snapshots_behind, 1);
+        assert_eq!(summary.staleness_hours, 1.0);
+        assert_eq!(summary.data_record_count, 40);
+        assert_eq!(summary.partitions_with_deletes, 1);
+        assert!(latest_statistics_file(&json!({})).is_none());
+    }
+}
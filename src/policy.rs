@@ -0,0 +1,338 @@
+//! Policies (`policy=`): declarative rules on report metrics, read from a YAML file, whose
+//! outcome is the report's `compliance` section, for org-wide lakehouse standards.
+//!
+//! ```yaml
+//! name: lakehouse-standards
+//! rules:
+//!   - name: few-small-files
+//!     rule: small_file_ratio < 0.2
+//!   - rule: snapshot_count < 100
+//!     severity: warning
+//!   - partition depth <= 2
+//! ```
+//!
+//! A rule compares a metric with a number (`<`, `<=`, `>`, `>=`, `==` or `!=`); metric names
+//! ignore case, and spaces or dashes stand for underscores. Rules are `error`s unless given
+//! another severity, and a table is compliant when no `error` rule fails. A rule on a metric
+//! the analysis didn't produce (e.g. freshness without commit times) is neither passed nor
+//! failed.
+
+use crate::types::{ComplianceReport, HealthReport, PolicyRuleResult};
+use serde::Deserialize;
+use std::path::Path;
+
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+const MB: f64 = 1024.0 * 1024.0;
+
+type Metric = fn(&HealthReport) -> Option<f64>;
+
+/// Every metric a rule can name
+const METRICS: [(&str, Metric); 23] = [
+    ("health_score", |r| Some(r.health_score)),
+    ("storage_cost_score", |r| {
+        r.metrics.sub_scores.as_ref().map(|s| s.storage_cost_score)
+    }),
+    ("query_performance_score", |r| {
+        r.metrics
+            .sub_scores
+            .as_ref()
+            .map(|s| s.query_performance_score)
+    }),
+    ("reliability_score", |r| {
+        r.metrics.sub_scores.as_ref().map(|s| s.reliability_score)
+    }),
+    ("total_files", |r| Some(r.metrics.total_files as f64)),
+    ("total_size_bytes", |r| {
+        Some(r.metrics.total_size_bytes as f64)
+    }),
+    ("total_size_gb", |r| {
+        Some(r.metrics.total_size_bytes as f64 / GB)
+    }),
+    ("avg_file_size_mb", |r| {
+        Some(r.metrics.avg_file_size_bytes / MB)
+    }),
+    ("small_files", |r| {
+        Some(r.metrics.file_size_distribution.small_files as f64)
+    }),
+    ("small_file_ratio", |r| {
+        Some(match r.metrics.total_files {
+            0 => 0.0,
+            files => r.metrics.file_size_distribution.small_files as f64 / files as f64,
+        })
+    }),
+    ("partition_count", |r| {
+        Some(r.metrics.partition_count as f64)
+    }),
+    ("partition_depth", |r| {
+        // The declared partition columns, else the deepest partition seen
+        let depth = match r.current_schema {
+            Some(ref schema) => schema.partition_columns.len(),
+            None => r
+                .metrics
+                .partitions
+                .iter()
+                .map(|p| p.partition_values.len())
+                .max()
+                .unwrap_or_default(),
+        };
+        Some(depth as f64)
+    }),
+    ("partition_skew_score", |r| {
+        Some(r.metrics.data_skew.partition_skew_score)
+    }),
+    ("file_size_skew_score", |r| {
+        Some(r.metrics.data_skew.file_size_skew_score)
+    }),
+    ("snapshot_count", |r| {
+        Some(r.metrics.snapshot_health.snapshot_count as f64)
+    }),
+    ("metadata_file_count", |r| {
+        Some(r.metrics.metadata_health.metadata_file_count as f64)
+    }),
+    ("unreferenced_files", |r| {
+        Some(r.unreferenced_file_count() as f64)
+    }),
+    ("unreferenced_size_bytes", |r| {
+        Some(r.metrics.unreferenced_size_bytes as f64)
+    }),
+    ("orphan_gb", |r| {
+        Some(r.metrics.unreferenced_size_bytes as f64 / GB)
+    }),
+    ("column_count", |r| {
+        r.current_schema.as_ref().map(|s| s.columns.len() as f64)
+    }),
+    ("recommendation_count", |r| {
+        Some(r.metrics.recommendations.len() as f64)
+    }),
+    ("hours_since_last_commit", |r| {
+        r.metrics.freshness.as_ref()?.hours_since_last_commit
+    }),
+    ("snapshot_retention_risk", |r| {
+        Some(r.metrics.snapshot_health.snapshot_retention_risk)
+    }),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Comparison {
+    fn holds(self, value: f64, limit: f64) -> bool {
+        match self {
+            Self::Lt => value < limit,
+            Self::Le => value <= limit,
+            Self::Gt => value > limit,
+            Self::Ge => value >= limit,
+            Self::Eq => value == limit,
+            Self::Ne => value != limit,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    name: String,
+    expression: String,
+    metric: Metric,
+    comparison: Comparison,
+    limit: f64,
+    severity: String, // "error" or "warning"
+    description: Option<String>,
+}
+
+impl Rule {
+    /// Parse `metric <op> number`
+    fn parse(expression: &str) -> Result<(Metric, Comparison, f64), String> {
+        let invalid = |why: &str| format!("Invalid policy rule '{}': {}", expression, why);
+        let at = expression
+            .find(['<', '>', '=', '!'])
+            .ok_or_else(|| invalid("no comparison"))?;
+        let (metric, rest) = expression.split_at(at);
+        let (comparison, limit) = [
+            ("<=", Comparison::Le),
+            (">=", Comparison::Ge),
+            ("==", Comparison::Eq),
+            ("!=", Comparison::Ne),
+            ("<", Comparison::Lt),
+            (">", Comparison::Gt),
+        ]
+        .into_iter()
+        .find_map(|(op, comparison)| Some((comparison, rest.strip_prefix(op)?)))
+        .ok_or_else(|| invalid("unknown comparison"))?;
+        let name = metric.trim().to_lowercase().replace([' ', '-'], "_");
+        let metric = METRICS
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|(_, metric)| *metric)
+            .ok_or_else(|| invalid(&format!("unknown metric '{}'", name)))?;
+        let limit = limit
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| invalid("the limit is not a number"))?;
+        Ok((metric, comparison, limit))
+    }
+}
+
+/// A rule as written: the expression alone, or with a name, severity and description
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RuleSpec {
+    Expression(String),
+    Full {
+        rule: String,
+        name: Option<String>,
+        severity: Option<String>,
+        description: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicySpec {
+    name: Option<String>,
+    rules: Vec<RuleSpec>,
+}
+
+/// Rules a report is checked against
+#[derive(Debug, Clone)]
+pub struct Policy {
+    name: Option<String>,
+    rules: Vec<Rule>,
+}
+
+impl Policy {
+    pub fn from_yaml(text: &str) -> Result<Self, String> {
+        let spec: PolicySpec =
+            serde_yaml::from_str(text).map_err(|e| format!("Invalid policy: {}", e))?;
+        let rules = spec
+            .rules
+            .into_iter()
+            .map(|spec| {
+                let (expression, name, severity, description) = match spec {
+                    RuleSpec::Expression(rule) => (rule, None, None, None),
+                    RuleSpec::Full {
+                        rule,
+                        name,
+                        severity,
+                        description,
+                    } => (rule, name, severity, description),
+                };
+                let severity = match severity.as_deref().map(str::to_lowercase).as_deref() {
+                    None | Some("error") => "error",
+                    Some("warning") | Some("warn") => "warning",
+                    Some(other) => {
+                        return Err(format!(
+                            "Unknown policy severity: {} (expected 'error' or 'warning')",
+                            other
+                        ))
+                    }
+                };
+                let (metric, comparison, limit) = Rule::parse(&expression)?;
+                Ok(Rule {
+                    name: name.unwrap_or_else(|| expression.clone()),
+                    expression,
+                    metric,
+                    comparison,
+                    limit,
+                    severity: severity.to_string(),
+                    description,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self {
+            name: spec.name,
+            rules,
+        })
+    }
+
+    pub fn read_file(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Invalid policy {}: {}", path.display(), e))?;
+        Self::from_yaml(&text)
+    }
+
+    /// The compliance of `report` with the policy
+    pub fn evaluate(&self, report: &HealthReport) -> ComplianceReport {
+        let results: Vec<PolicyRuleResult> = self
+            .rules
+            .iter()
+            .map(|rule| {
+                let value = (rule.metric)(report);
+                PolicyRuleResult {
+                    name: rule.name.clone(),
+                    rule: rule.expression.clone(),
+                    severity: rule.severity.clone(),
+                    value,
+                    passed: value.map(|value| rule.comparison.holds(value, rule.limit)),
+                    description: rule.description.clone(),
+                }
+            })
+            .collect();
+        let failed = |severity: &str| {
+            results
+                .iter()
+                .filter(|r| r.passed == Some(false) && r.severity == severity)
+                .count()
+        };
+        ComplianceReport {
+            policy: self.name.clone(),
+            compliant: failed("error") == 0,
+            errors: failed("error"),
+            warnings: failed("warning"),
+            rules: results,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_rules_evaluate_against_report() {
+        let policy = Policy::from_yaml(
+            "name: standards\n\
+             rules:\n\
+             \x20 - name: few-small-files\n\
+             \x20   rule: small_file_ratio < 0.2\n\
+             \x20 - rule: snapshot_count < 100\n\
+             \x20   severity: warning\n\
+             \x20 - partition depth <= 2\n\
+             \x20 - hours_since_last_commit < 24\n",
+        )
+        .unwrap();
+        let mut report = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        report.metrics.total_files = 10;
+        report.metrics.file_size_distribution.small_files = 5;
+        report.metrics.snapshot_health.snapshot_count = 150;
+
+        let compliance = policy.evaluate(&report);
+        assert_eq!(compliance.policy.as_deref(), Some("standards"));
+        let outcomes: Vec<(&str, Option<bool>)> = compliance
+            .rules
+            .iter()
+            .map(|r| (r.name.as_str(), r.passed))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                ("few-small-files", Some(false)),
+                ("snapshot_count < 100", Some(false)),
+                ("partition depth <= 2", Some(true)),
+                ("hours_since_last_commit < 24", None),
+            ]
+        );
+        assert!(!compliance.compliant);
+        assert_eq!((compliance.errors, compliance.warnings), (1, 1));
+
+        assert!(Policy::from_yaml("rules: [row_count < 10]").is_err());
+        assert!(Policy::from_yaml("rules: [health_score ~ 0.5]").is_err());
+        assert!(Policy::from_yaml("rules: [{rule: health_score > 0.5, severity: fatal}]").is_err());
+    }
+}
@@ -0,0 +1,200 @@
+//! Presets for S3-compatible object stores (Cloudflare R2, DigitalOcean Spaces, Wasabi).
+//!
+//! Each provider needs its own endpoint, signing region and addressing style. The provider is
+//! named with `provider=` or inferred from the `endpoint_url` hostname; any other endpoint is
+//! treated as a generic S3-compatible store (MinIO, Ceph) and addressed path-style.
+//! Drainage only lists, reads and HEADs objects, so the request checksums some of these
+//! stores reject on uploads don't come into play.
+
+use anyhow::Result;
+use url::Url;
+
+/// Where S3 requests go
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StorageProvider {
+    Aws,
+    CloudflareR2,
+    DigitalOceanSpaces,
+    Wasabi,
+    S3Compatible, // any other endpoint
+}
+
+/// Endpoint and signing settings resolved for a provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedEndpoint {
+    pub endpoint_url: Option<String>, // None for AWS's own endpoints
+    pub region: Option<String>,       // overrides the configured region when set
+    pub force_path_style: bool,
+}
+
+impl StorageProvider {
+    /// Provider for a `provider=` name
+    pub fn named(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "aws" | "s3" => Some(Self::Aws),
+            "r2" | "cloudflare" | "cloudflare_r2" => Some(Self::CloudflareR2),
+            "spaces" | "digitalocean" | "digitalocean_spaces" => Some(Self::DigitalOceanSpaces),
+            "wasabi" => Some(Self::Wasabi),
+            "s3_compatible" | "minio" => Some(Self::S3Compatible),
+            _ => None,
+        }
+    }
+
+    /// Provider serving an endpoint, by its hostname
+    pub fn infer(endpoint_host: &str) -> Self {
+        let host = endpoint_host.to_lowercase();
+        if host.ends_with(".r2.cloudflarestorage.com") {
+            Self::CloudflareR2
+        } else if host.ends_with(".digitaloceanspaces.com") {
+            Self::DigitalOceanSpaces
+        } else if host == "wasabisys.com" || host.ends_with(".wasabisys.com") {
+            Self::Wasabi
+        } else if host == "amazonaws.com" || host.ends_with(".amazonaws.com") {
+            Self::Aws
+        } else {
+            Self::S3Compatible
+        }
+    }
+
+    /// Settings for `provider` (or the provider inferred from `endpoint_url`), given the
+    /// configured region. None when neither is set and the SDK defaults apply.
+    pub fn resolve(
+        provider: Option<Self>,
+        endpoint_url: Option<&str>,
+        region: Option<&str>,
+    ) -> Result<Option<ResolvedEndpoint>> {
+        let host = match endpoint_url {
+            Some(endpoint) => {
+                let url = Url::parse(endpoint)
+                    .map_err(|e| anyhow::anyhow!("Invalid endpoint_url {}: {}", endpoint, e))?;
+                Some(
+                    url.host_str()
+                        .ok_or_else(|| anyhow::anyhow!("endpoint_url has no host: {}", endpoint))?
+                        .to_lowercase(),
+                )
+            }
+            None => None,
+        };
+        let Some(provider) = provider.or_else(|| host.as_deref().map(Self::infer)) else {
+            return Ok(None);
+        };
+        let endpoint_url = endpoint_url.map(str::to_string);
+
+        let resolved = match provider {
+            Self::Aws => ResolvedEndpoint {
+                endpoint_url,
+                region: None,
+                force_path_style: false,
+            },
+            // R2 signs every request for the "auto" region; endpoints are per account
+            Self::CloudflareR2 => ResolvedEndpoint {
+                endpoint_url: Some(endpoint_url.ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Cloudflare R2 needs endpoint_url=\"https://<account_id>.r2.cloudflarestorage.com\""
+                    )
+                })?),
+                region: Some("auto".to_string()),
+                force_path_style: true,
+            },
+            // <region>.digitaloceanspaces.com, signed for that region
+            Self::DigitalOceanSpaces => {
+                let region = host
+                    .as_deref()
+                    .and_then(|h| h.strip_suffix(".digitaloceanspaces.com"))
+                    .map(|h| h.rsplit('.').next().unwrap_or(h).to_string())
+                    .or_else(|| region.map(str::to_string))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("DigitalOcean Spaces needs aws_region (e.g. nyc3) or endpoint_url")
+                    })?;
+                ResolvedEndpoint {
+                    endpoint_url: Some(endpoint_url.unwrap_or_else(|| {
+                        format!("https://{}.digitaloceanspaces.com", region)
+                    })),
+                    region: Some(region),
+                    force_path_style: false,
+                }
+            }
+            // s3.<region>.wasabisys.com, or s3.wasabisys.com for us-east-1
+            Self::Wasabi => {
+                let region = host
+                    .as_deref()
+                    .and_then(|h| h.strip_suffix(".wasabisys.com"))
+                    .map(|h| match h.strip_prefix("s3.") {
+                        Some(region) => region.to_string(),
+                        None => "us-east-1".to_string(),
+                    })
+                    .or_else(|| region.map(str::to_string))
+                    .unwrap_or_else(|| "us-east-1".to_string());
+                ResolvedEndpoint {
+                    endpoint_url: Some(endpoint_url.unwrap_or_else(|| {
+                        format!("https://s3.{}.wasabisys.com", region)
+                    })),
+                    region: Some(region),
+                    force_path_style: false,
+                }
+            }
+            Self::S3Compatible => ResolvedEndpoint {
+                endpoint_url: Some(endpoint_url.ok_or_else(|| {
+                    anyhow::anyhow!("An S3-compatible provider needs endpoint_url")
+                })?),
+                region: None,
+                force_path_style: true,
+            },
+        };
+        Ok(Some(resolved))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_provider_presets() {
+        assert_eq!(
+            StorageProvider::resolve(None, None, Some("us-west-2")).unwrap(),
+            None
+        );
+
+        let r2 = StorageProvider::resolve(
+            None,
+            Some("https://0123abcd.r2.cloudflarestorage.com"),
+            Some("us-east-1"),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(r2.region.as_deref(), Some("auto"));
+        assert!(r2.force_path_style);
+        assert!(StorageProvider::resolve(StorageProvider::named("r2"), None, None).is_err());
+
+        let spaces = StorageProvider::resolve(StorageProvider::named("spaces"), None, Some("fra1"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            spaces.endpoint_url.as_deref(),
+            Some("https://fra1.digitaloceanspaces.com")
+        );
+        let spaces =
+            StorageProvider::resolve(None, Some("https://nyc3.digitaloceanspaces.com"), None)
+                .unwrap()
+                .unwrap();
+        assert_eq!(spaces.region.as_deref(), Some("nyc3"));
+
+        let wasabi =
+            StorageProvider::resolve(None, Some("https://s3.eu-central-1.wasabisys.com"), None)
+                .unwrap()
+                .unwrap();
+        assert_eq!(wasabi.region.as_deref(), Some("eu-central-1"));
+        let wasabi = StorageProvider::resolve(None, Some("https://s3.wasabisys.com"), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(wasabi.region.as_deref(), Some("us-east-1"));
+
+        let minio =
+            StorageProvider::resolve(None, Some("http://localhost:9000"), Some("us-east-1"))
+                .unwrap()
+                .unwrap();
+        assert!(minio.force_path_style);
+        assert_eq!(minio.region, None);
+    }
+}
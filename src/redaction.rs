@@ -0,0 +1,195 @@
+use crate::types::{FileInfo, HealthReport};
+use pyo3::prelude::*;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+/// Hook applied to a report before it is exported, so it can be shared without
+/// leaking sensitive naming
+pub trait ReportRedactor {
+    fn redact(&self, report: &mut HealthReport);
+}
+
+/// Built-in redaction: hash file and table paths, drop partition values matching patterns
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct RedactionPolicy {
+    #[pyo3(get)]
+    pub hash_paths: bool,
+    #[pyo3(get)]
+    pub drop_partition_values: Vec<String>, // regex patterns matched against partition values
+    salt: String,
+    patterns: Vec<Regex>,
+}
+
+#[pymethods]
+impl RedactionPolicy {
+    #[new]
+    #[pyo3(signature = (hash_paths=true, drop_partition_values=None, salt=None))]
+    pub fn py_new(
+        hash_paths: bool,
+        drop_partition_values: Option<Vec<String>>,
+        salt: Option<String>,
+    ) -> PyResult<Self> {
+        Self::new(
+            hash_paths,
+            drop_partition_values.unwrap_or_default(),
+            salt.unwrap_or_default(),
+        )
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid pattern: {}", e)))
+    }
+}
+
+impl RedactionPolicy {
+    pub fn new(
+        hash_paths: bool,
+        drop_partition_values: Vec<String>,
+        salt: String,
+    ) -> Result<Self, regex::Error> {
+        let patterns = drop_partition_values
+            .iter()
+            .map(|pattern| Regex::new(pattern))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            hash_paths,
+            drop_partition_values,
+            salt,
+            patterns,
+        })
+    }
+
+    /// Replace a path with a salted hash, keeping the file extension so file kinds stay visible
+    fn hash_path(&self, path: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        hasher.update(path.as_bytes());
+        let digest = hex::encode(hasher.finalize());
+
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+        match file_name.split_once('.') {
+            Some((_, extension)) => format!("redacted/{}.{}", &digest[..16], extension),
+            None => format!("redacted/{}", &digest[..16]),
+        }
+    }
+
+    fn redact_file(&self, file: &mut FileInfo) {
+        if self.hash_paths {
+            file.path = self.hash_path(&file.path);
+        }
+    }
+}
+
+impl ReportRedactor for RedactionPolicy {
+    fn redact(&self, report: &mut HealthReport) {
+        if self.hash_paths {
+            report.table_path = self.hash_path(&report.table_path);
+            // Directory names carry partition values, and a redacted report can't seed a
+            // warm start anyway
+            report.warm_start = None;
+            // Warnings quote object keys
+            report.analysis_log = None;
+        }
+
+        let metrics = &mut report.metrics;
+        for file in metrics.unreferenced_files.iter_mut() {
+            self.redact_file(file);
+        }
+        for partition in metrics.partitions.iter_mut() {
+            partition
+                .partition_values
+                .retain(|_, value| !self.patterns.iter().any(|p| p.is_match(value)));
+            for file in partition.files.iter_mut() {
+                self.redact_file(file);
+            }
+        }
+        if self.hash_paths {
+            if let Some(ref mut mismatch) = metrics.partition_spec_mismatch {
+                for path in mismatch.offending_paths.iter_mut() {
+                    *path = self.hash_path(path);
+                }
+            }
+            if let Some(ref mut storage_classes) = metrics.storage_classes {
+                for path in storage_classes.archived_live_paths.iter_mut() {
+                    *path = self.hash_path(path);
+                }
+            }
+            if let Some(ref mut log_consistency) = metrics.log_consistency {
+                for path in log_consistency.size_mismatch_paths.iter_mut() {
+                    *path = self.hash_path(path);
+                }
+                for mismatch in log_consistency.size_mismatches.iter_mut() {
+                    mismatch.path = self.hash_path(&mismatch.path);
+                }
+            }
+            if let Some(ref mut incomplete_uploads) = metrics.incomplete_uploads {
+                for key in incomplete_uploads.upload_keys.iter_mut() {
+                    *key = self.hash_path(key);
+                }
+            }
+            if let Some(ref mut duplicate_files) = metrics.duplicate_files {
+                for group in duplicate_files.groups.iter_mut() {
+                    for path in group.paths.iter_mut() {
+                        *path = self.hash_path(path);
+                    }
+                }
+            }
+            if let Some(ref mut existence_check) = metrics.existence_check {
+                for path in existence_check.missing_paths.iter_mut() {
+                    *path = self.hash_path(path);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PartitionInfo;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_redaction_policy_hashes_paths_and_drops_values() {
+        let mut report = HealthReport::new(
+            "s3://acme-payroll/tables/salaries".to_string(),
+            "delta".to_string(),
+        );
+        let file = FileInfo::new(
+            "tables/salaries/customer=acme/part-0.snappy.parquet".to_string(),
+            1024,
+            None,
+            true,
+            chrono::Utc::now(),
+        );
+        report.metrics.partitions = vec![PartitionInfo {
+            partition_values: HashMap::from([
+                ("customer".to_string(), "acme".to_string()),
+                ("year".to_string(), "2024".to_string()),
+            ]),
+            file_count: 1,
+            total_size_bytes: 1024,
+            avg_file_size_bytes: 1024.0,
+            files: vec![file.clone()],
+        }];
+        report.metrics.unreferenced_files = vec![file];
+
+        let policy =
+            RedactionPolicy::new(true, vec!["^acme".to_string()], "pepper".to_string()).unwrap();
+        policy.redact(&mut report);
+
+        assert!(!report.table_path.contains("payroll"));
+        let partition = &report.metrics.partitions[0];
+        assert!(!partition.partition_values.contains_key("customer"));
+        assert_eq!(partition.partition_values["year"], "2024");
+        let redacted_path = &partition.files[0].path;
+        assert!(redacted_path.starts_with("redacted/"));
+        assert!(redacted_path.ends_with(".snappy.parquet"));
+        assert!(!redacted_path.contains("acme"));
+        // The same path always hashes to the same value within a policy
+        assert_eq!(&report.metrics.unreferenced_files[0].path, redacted_path);
+    }
+
+    #[test]
+    fn test_redaction_policy_rejects_invalid_pattern() {
+        assert!(RedactionPolicy::new(false, vec!["(".to_string()], String::new()).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! Health regression checks against a stored baseline report, for pipeline tests in CI.
+//!
+//! Each checked metric has a direction (scores should not drop, ratios of problem files
+//! should not grow) and a tolerance: how much it may worsen, in the metric's own unit,
+//! before it counts as a regression. Metrics missing from either report are skipped.
+
+use crate::types::HealthReport;
+use std::collections::HashMap;
+
+/// A metric that got worse than its tolerance allows
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    pub metric: &'static str,
+    pub baseline: f64,
+    pub current: f64,
+    pub tolerance: f64,
+}
+
+/// Whether a larger value of a metric is better
+#[derive(Clone, Copy)]
+enum Direction {
+    HigherIsBetter,
+    LowerIsBetter,
+}
+
+fn ratio(count: usize, total: usize) -> Option<f64> {
+    (total > 0).then(|| count as f64 / total as f64)
+}
+
+type Metric = (&'static str, Direction, fn(&HealthReport) -> Option<f64>);
+
+/// Every metric compared with the baseline
+const METRICS: [Metric; 10] = [
+    ("health_score", Direction::HigherIsBetter, |r| {
+        Some(r.health_score)
+    }),
+    ("storage_cost_score", Direction::HigherIsBetter, |r| {
+        r.metrics.sub_scores.as_ref().map(|s| s.storage_cost_score)
+    }),
+    ("query_performance_score", Direction::HigherIsBetter, |r| {
+        r.metrics
+            .sub_scores
+            .as_ref()
+            .map(|s| s.query_performance_score)
+    }),
+    ("reliability_score", Direction::HigherIsBetter, |r| {
+        r.metrics.sub_scores.as_ref().map(|s| s.reliability_score)
+    }),
+    ("small_file_ratio", Direction::LowerIsBetter, |r| {
+        ratio(
+            r.metrics.file_size_distribution.small_files,
+            r.metrics.total_files,
+        )
+    }),
+    ("unreferenced_file_ratio", Direction::LowerIsBetter, |r| {
+        ratio(r.unreferenced_file_count(), r.metrics.total_files)
+    }),
+    ("partition_skew_score", Direction::LowerIsBetter, |r| {
+        Some(r.metrics.data_skew.partition_skew_score)
+    }),
+    ("file_size_skew_score", Direction::LowerIsBetter, |r| {
+        Some(r.metrics.data_skew.file_size_skew_score)
+    }),
+    ("snapshot_retention_risk", Direction::LowerIsBetter, |r| {
+        Some(r.metrics.snapshot_health.snapshot_retention_risk)
+    }),
+    ("missing_files", Direction::LowerIsBetter, |r| {
+        r.metrics
+            .existence_check
+            .as_ref()
+            .map(|e| e.missing_files as f64)
+    }),
+];
+
+/// Metrics of `current` that worsened against `baseline` by more than their tolerance
+/// (0 unless given in `tolerances`)
+pub fn regressions(
+    current: &HealthReport,
+    baseline: &HealthReport,
+    tolerances: &HashMap<String, f64>,
+) -> Result<Vec<Regression>, String> {
+    if let Some(unknown) = tolerances
+        .keys()
+        .find(|name| !METRICS.iter().any(|(metric, _, _)| metric == name))
+    {
+        let known: Vec<&str> = METRICS.iter().map(|(metric, _, _)| *metric).collect();
+        return Err(format!(
+            "Unknown metric in tolerances: {} (expected one of {})",
+            unknown,
+            known.join(", ")
+        ));
+    }
+
+    Ok(METRICS
+        .iter()
+        .filter_map(|(metric, direction, value)| {
+            let (baseline, current) = (value(baseline)?, value(current)?);
+            let tolerance = tolerances.get(*metric).copied().unwrap_or(0.0);
+            let worsening = match direction {
+                Direction::HigherIsBetter => baseline - current,
+                Direction::LowerIsBetter => current - baseline,
+            };
+            // Allow for float noise when the tolerance is exact
+            (worsening > tolerance + 1e-9).then_some(Regression {
+                metric,
+                baseline,
+                current,
+                tolerance,
+            })
+        })
+        .collect())
+}
+
+/// Readable diff of the regressed metrics
+pub fn describe(regressions: &[Regression], baseline: &HealthReport) -> String {
+    let mut message = format!(
+        "{} metric(s) regressed against the baseline report of {} from {}:",
+        regressions.len(),
+        baseline.table_path,
+        baseline.analysis_timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    for regression in regressions {
+        message.push_str(&format!(
+            "\n  {:<24} {:>10.4} -> {:<10.4} ({:+.4}, tolerance {})",
+            regression.metric,
+            regression.baseline,
+            regression.current,
+            regression.current - regression.baseline,
+            regression.tolerance
+        ));
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regressions_respect_direction_and_tolerance() {
+        let mut baseline = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        baseline.health_score = 0.9;
+        baseline.metrics.total_files = 100;
+        baseline.metrics.file_size_distribution.small_files = 10;
+        baseline.metrics.data_skew.partition_skew_score = 0.4;
+
+        let mut current = baseline.clone();
+        current.health_score = 0.86;
+        current.metrics.file_size_distribution.small_files = 30;
+        current.metrics.data_skew.partition_skew_score = 0.1; // improved
+
+        let tolerances = HashMap::from([("health_score".to_string(), 0.05)]);
+        let found = regressions(&current, &baseline, &tolerances).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].metric, "small_file_ratio");
+        assert!((found[0].current - 0.3).abs() < 1e-9);
+
+        let found = regressions(&current, &baseline, &HashMap::new()).unwrap();
+        let metrics: Vec<&str> = found.iter().map(|r| r.metric).collect();
+        assert_eq!(metrics, ["health_score", "small_file_ratio"]);
+        assert!(describe(&found, &baseline).contains("health_score"));
+
+        let typo = HashMap::from([("health".to_string(), 0.1)]);
+        assert!(regressions(&current, &baseline, &typo).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+//! Effort estimates for the remediations behind recommendations, so the cost of a fix
+//! can be weighed against what it saves. Estimates assume the throughput of the
+//! configured engine and are meant as orders of magnitude.
+
+use crate::config::EngineProfile;
+use crate::parquet_footer::CODEC_REWRITE_MIN_SAVINGS;
+use crate::types::{HealthMetrics, RemediationEstimate};
+
+const MB: f64 = 1024.0 * 1024.0;
+
+struct Work {
+    action: &'static str,
+    operation: &'static str,
+    files_to_rewrite: usize,
+    bytes_to_rewrite: u64,
+    files_to_delete: usize,
+    bytes_saved: u64,
+}
+
+fn estimate(work: Work, engine: &EngineProfile) -> RemediationEstimate {
+    // Rewrites parallelize across files, up to the engine's cores
+    let rewrite_core_seconds =
+        work.bytes_to_rewrite as f64 / MB / engine.rewrite_mb_per_core_second.max(f64::EPSILON);
+    let parallelism = engine.cores.min(work.files_to_rewrite).max(1) as f64;
+    // Deletes are issued from a single driver
+    let delete_seconds = work.files_to_delete as f64 / engine.deletes_per_second.max(f64::EPSILON);
+
+    let estimated_runtime_seconds = rewrite_core_seconds / parallelism + delete_seconds;
+    let effort = if estimated_runtime_seconds < 10.0 * 60.0 {
+        "low"
+    } else if estimated_runtime_seconds < 2.0 * 3600.0 {
+        "medium"
+    } else {
+        "high"
+    };
+
+    RemediationEstimate {
+        action: work.action.to_string(),
+        operation: work.operation.to_string(),
+        engine: engine.name.clone(),
+        files_to_rewrite: work.files_to_rewrite,
+        bytes_to_rewrite: work.bytes_to_rewrite,
+        files_to_delete: work.files_to_delete,
+        bytes_saved: work.bytes_saved,
+        estimated_runtime_seconds,
+        estimated_core_hours: (rewrite_core_seconds + delete_seconds) / 3600.0,
+        effort: effort.to_string(),
+    }
+}
+
+/// Estimate each remediation the metrics call for. `table_type` ("delta" or "iceberg")
+/// picks the operation names.
+pub fn estimate_remediations(
+    metrics: &HealthMetrics,
+    table_type: &str,
+    engine: &EngineProfile,
+) -> Vec<RemediationEstimate> {
+    let delta = table_type == "delta";
+    let avg_file_size = metrics.avg_file_size_bytes.max(0.0) as u64;
+    let mut work = Vec::new();
+
+    // Orphaned files past the in-flight grace period
+    let stale_files = metrics
+        .unreferenced_files
+        .len()
+        .saturating_sub(metrics.in_flight_unreferenced_files);
+    if stale_files > 0 {
+        let stale_bytes = metrics
+            .unreferenced_size_bytes
+            .saturating_sub(metrics.in_flight_unreferenced_size_bytes);
+        work.push(Work {
+            action: "Delete unreferenced files",
+            operation: if delta {
+                "VACUUM"
+            } else {
+                "remove_orphan_files"
+            },
+            files_to_rewrite: 0,
+            bytes_to_rewrite: 0,
+            files_to_delete: stale_files,
+            bytes_saved: stale_bytes,
+        });
+    }
+
+    // Files removed from the table that are still in storage
+    if let Some(ref attribution) = metrics.file_attribution {
+        if attribution.tombstoned_file_count > 0 {
+            work.push(Work {
+                action: "Delete tombstoned files",
+                operation: if delta { "VACUUM" } else { "expire_snapshots" },
+                files_to_rewrite: 0,
+                bytes_to_rewrite: 0,
+                files_to_delete: attribution.tombstoned_file_count,
+                bytes_saved: attribution.tombstoned_size_bytes,
+            });
+        }
+    }
+
+    // Compaction rewrites the small files; the originals are deleted afterwards
+    if let Some(ref compaction) = metrics.file_compaction {
+        if compaction.potential_compaction_files > 0 {
+            work.push(Work {
+                action: "Compact small files",
+                operation: if delta {
+                    "OPTIMIZE"
+                } else {
+                    "rewrite_data_files"
+                },
+                files_to_rewrite: compaction.small_files_count,
+                bytes_to_rewrite: compaction.small_files_size_bytes,
+                files_to_delete: compaction.small_files_count,
+                bytes_saved: compaction.estimated_compaction_savings_bytes,
+            });
+        }
+    }
+
+    // Data left behind by dropped columns (Delta column mapping)
+    if let Some(ref column_mapping) = metrics.column_mapping {
+        if column_mapping.files_with_dropped_columns > 0 && column_mapping.reclaimable_bytes > 0 {
+            work.push(Work {
+                action: "Purge dropped column data",
+                operation: "REORG TABLE ... APPLY (PURGE)",
+                files_to_rewrite: column_mapping.files_with_dropped_columns,
+                bytes_to_rewrite: column_mapping.files_with_dropped_columns as u64 * avg_file_size,
+                files_to_delete: column_mapping.files_with_dropped_columns,
+                bytes_saved: column_mapping.reclaimable_bytes,
+            });
+        }
+    }
+
+    // Rows masked by deletion vectors / delete files are only dropped by rewriting
+    if let Some(ref dv) = metrics.deletion_vector_metrics {
+        if dv.deletion_vector_count > 0 {
+            let files = dv.deletion_vector_count.min(metrics.total_files.max(1));
+            work.push(Work {
+                action: "Apply deletion vectors",
+                operation: if delta {
+                    "REORG TABLE ... APPLY (PURGE)"
+                } else {
+                    "rewrite_data_files"
+                },
+                files_to_rewrite: files,
+                bytes_to_rewrite: files as u64 * avg_file_size,
+                files_to_delete: files,
+                bytes_saved: dv.total_deletion_vector_size_bytes,
+            });
+        }
+    }
+
+    // Recompressing with zstd rewrites the whole table
+    if let Some(ref footers) = metrics.parquet_footers {
+        let table_size = metrics.total_size_bytes as f64;
+        if table_size > 0.0
+            && footers.projected_codec_savings_bytes as f64
+                >= table_size * CODEC_REWRITE_MIN_SAVINGS
+        {
+            work.push(Work {
+                action: "Recompress data files with zstd",
+                operation: if delta {
+                    "OPTIMIZE (compression codec zstd)"
+                } else {
+                    "rewrite_data_files (write.parquet.compression-codec=zstd)"
+                },
+                files_to_rewrite: metrics.total_files,
+                bytes_to_rewrite: metrics.total_size_bytes,
+                files_to_delete: metrics.total_files,
+                bytes_saved: footers.projected_codec_savings_bytes,
+            });
+        }
+    }
+
+    work.into_iter()
+        .map(|work| estimate(work, engine))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FileInfo;
+
+    #[test]
+    fn test_estimate_remediations_scales_with_engine() {
+        let mut metrics = HealthMetrics::new();
+        metrics.unreferenced_files = (0..2000)
+            .map(|i| FileInfo::new(format!("f{}", i), 1024, None, false, chrono::Utc::now()))
+            .collect();
+        metrics.unreferenced_size_bytes = 2000 * 1024;
+        metrics.file_compaction = Some(crate::types::FileCompactionMetrics {
+            compaction_opportunity_score: 0.9,
+            small_files_count: 1000,
+            small_files_size_bytes: 64 * 1024 * 1024 * 1024,
+            potential_compaction_files: 1000,
+            estimated_compaction_savings_bytes: 0,
+            recommended_target_file_size_bytes: 128 * 1024 * 1024,
+            compaction_priority: "high".to_string(),
+            z_order_opportunity: false,
+            z_order_columns: Vec::new(),
+            z_order_candidates: Vec::new(),
+            compaction_plan: Vec::new(),
+        });
+
+        let spark = EngineProfile::default();
+        let estimates = estimate_remediations(&metrics, "delta", &spark);
+        assert_eq!(estimates.len(), 2);
+        assert_eq!(estimates[0].operation, "VACUUM");
+        assert_eq!(estimates[0].files_to_delete, 2000);
+        assert!((estimates[0].estimated_runtime_seconds - 2.0).abs() < 1e-9);
+        assert_eq!(estimates[0].effort, "low");
+
+        // 64 GiB at 16 MB/s per core on 64 cores, plus 1000 deletes
+        assert_eq!(estimates[1].operation, "OPTIMIZE");
+        assert!((estimates[1].estimated_runtime_seconds - 65.0).abs() < 1e-9);
+        assert!((estimates[1].estimated_core_hours - 4097.0 / 3600.0).abs() < 1e-9);
+
+        let duckdb = EngineProfile::named("duckdb").unwrap();
+        let estimates = estimate_remediations(&metrics, "iceberg", &duckdb);
+        assert_eq!(estimates[1].operation, "rewrite_data_files");
+        assert!(estimates[1].estimated_runtime_seconds > 65.0);
+    }
+}
@@ -0,0 +1,237 @@
+//! JSON-RPC 2.0 over stdio, so orchestrators outside Python (e.g. on the JVM) can drive
+//! analyses through a long-lived `drainage serve --stdio` subprocess.
+//!
+//! Each request and response is a single line of JSON. While an analysis runs, the server
+//! writes `progress` notifications carrying the request id and the stage that started.
+
+use crate::config::AnalysisConfig;
+use crate::health_analyzer::TableTarget;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::io::{self, BufRead, Write};
+use tokio::runtime::Runtime;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const ANALYSIS_FAILED: i64 = -32000;
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: crate::sanitize::sanitize(&message.into()),
+        }
+    }
+}
+
+/// Params of `analyze`, `score` and `detect`, also the body of the health service's
+/// `POST /analyze`
+#[derive(Deserialize)]
+pub struct AnalyzeParams {
+    #[serde(flatten)]
+    pub target: TableTarget,
+    #[serde(default)]
+    pub options: Map<String, Value>,
+}
+
+fn write_message<W: Write>(output: &mut W, message: &Value) -> io::Result<()> {
+    writeln!(output, "{}", message)?;
+    output.flush()
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+/// Run an `analyze` request, reporting each stage through `progress`
+fn analyze<F: FnMut(&'static str)>(
+    runtime: &Runtime,
+    params: Value,
+    mut progress: F,
+) -> Result<crate::types::HealthReport, RpcError> {
+    let params: AnalyzeParams = parse_params(params)?;
+    let config = AnalysisConfig::from_json(&params.options)
+        .map_err(|message| RpcError::new(INVALID_PARAMS, message))?;
+
+    progress("started");
+    let report = runtime
+        .block_on(params.target.analyze(config, &mut progress))
+        .map_err(|e| RpcError::new(ANALYSIS_FAILED, e.to_string()))?;
+    progress("completed");
+    Ok(report)
+}
+
+fn dispatch<W: Write>(
+    runtime: &Runtime,
+    output: &mut W,
+    id: &Value,
+    method: &str,
+    params: Value,
+) -> Result<Value, RpcError> {
+    let mut progress = |stage: &'static str| {
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "progress",
+            "params": { "id": id, "stage": stage },
+        });
+        // Progress is best effort; a broken pipe surfaces when the response is written
+        let _ = write_message(output, &notification);
+    };
+
+    match method {
+        "detect" => {
+            let params: AnalyzeParams = parse_params(params)?;
+            let config = AnalysisConfig::from_json(&params.options)
+                .map_err(|message| RpcError::new(INVALID_PARAMS, message))?;
+            let table_type = runtime
+                .block_on(params.target.detect(&config))
+                .map_err(|e| RpcError::new(ANALYSIS_FAILED, e.to_string()))?;
+            Ok(json!({ "table_type": table_type }))
+        }
+        "analyze" => {
+            let report = analyze(runtime, params, &mut progress)?;
+            serde_json::to_value(&report).map_err(|e| RpcError::new(ANALYSIS_FAILED, e.to_string()))
+        }
+        "score" => {
+            let report = analyze(runtime, params, &mut progress)?;
+            Ok(json!({
+                "table_type": report.table_type,
+                "health_score": report.health_score,
+                "sub_scores": report.metrics.sub_scores,
+            }))
+        }
+        "shutdown" => Ok(Value::Null),
+        other => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method: {}", other),
+        )),
+    }
+}
+
+/// Serve requests from `input` until it closes or a `shutdown` request arrives.
+/// Requests without an `id` are notifications and get no response.
+pub fn serve<R: BufRead, W: Write>(input: R, output: &mut W) -> io::Result<()> {
+    let runtime = Runtime::new()?;
+
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (id, outcome, shutdown) = match serde_json::from_str::<Value>(&line) {
+            Err(e) => (
+                Some(Value::Null),
+                Err(RpcError::new(PARSE_ERROR, e.to_string())),
+                false,
+            ),
+            Ok(request) => {
+                let id = request.get("id").cloned();
+                let response_id = id.clone().unwrap_or(Value::Null);
+                match request.get("method").and_then(|m| m.as_str()) {
+                    None => (
+                        Some(response_id),
+                        Err(RpcError::new(INVALID_REQUEST, "Missing method")),
+                        false,
+                    ),
+                    Some(method) => {
+                        let params = request.get("params").cloned().unwrap_or(json!({}));
+                        let outcome = dispatch(&runtime, output, &response_id, method, params);
+                        (id, outcome, method == "shutdown")
+                    }
+                }
+            }
+        };
+
+        if let Some(id) = id {
+            let response = match outcome {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                Err(error) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": error.code, "message": error.message },
+                }),
+            };
+            write_message(output, &response)?;
+        }
+        if shutdown {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(requests: &[&str]) -> Vec<Value> {
+        let input = requests.join("\n");
+        let mut output = Vec::new();
+        serve(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_serve_reports_protocol_errors() {
+        let messages = run(&[
+            "not json",
+            r#"{"jsonrpc":"2.0","id":1,"method":"nope"}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"analyze","params":{}}"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":"score","params":{"s3_path":"s3://b/t","options":{"bogus":1}}}"#,
+            r#"{"jsonrpc":"2.0","method":"nope"}"#,
+        ]);
+
+        let codes: Vec<(Value, i64)> = messages
+            .iter()
+            .map(|m| (m["id"].clone(), m["error"]["code"].as_i64().unwrap()))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                (Value::Null, PARSE_ERROR),
+                (json!(1), METHOD_NOT_FOUND),
+                (json!(2), INVALID_PARAMS),
+                (json!(3), INVALID_PARAMS),
+            ]
+        );
+        assert_eq!(
+            messages[3]["error"]["message"],
+            "Unknown analysis option: bogus"
+        );
+    }
+
+    #[test]
+    fn test_serve_streams_progress_and_stops_on_shutdown() {
+        let messages = run(&[
+            r#"{"jsonrpc":"2.0","id":"a","method":"analyze","params":{"s3_path":"not a url","table_type":"delta"}}"#,
+            r#"{"jsonrpc":"2.0","id":"b","method":"shutdown"}"#,
+            r#"{"jsonrpc":"2.0","id":"c","method":"nope"}"#,
+        ]);
+
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0]["method"], "progress");
+        assert_eq!(
+            messages[0]["params"],
+            json!({ "id": "a", "stage": "started" })
+        );
+        assert_eq!(messages[1]["id"], "a");
+        assert_eq!(messages[1]["error"]["code"], ANALYSIS_FAILED);
+        assert_eq!(
+            messages[2],
+            json!({ "jsonrpc": "2.0", "id": "b", "result": null })
+        );
+    }
+}
@@ -1,60 +1,379 @@
-use anyhow::Result;
-use aws_config::meta::region::RegionProviderChain;
+use crate::config::PartitionFilter;
+use crate::deadline::Deadline;
+use crate::error::{DrainageError, ErrorContext};
+use crate::network::NetworkSettings;
+use crate::provider::StorageProvider;
+use anyhow::{Context, Result};
+use aws_config::meta::region::{ProvideRegion, RegionProviderChain};
+use aws_config::profile::ProfileFileRegionProvider;
+use aws_config::sts::AssumeRoleProvider;
 use aws_sdk_s3::{config::Credentials, config::Region, Client as S3Client};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use url::Url;
 
+/// S3 requests made through a client and the wrappers sharing it
+#[derive(Debug, Default)]
+pub struct RequestCounts {
+    list: AtomicU64,
+    get: AtomicU64,
+    head: AtomicU64,
+    bytes_downloaded: AtomicU64,
+    cached_get: AtomicU64,
+}
+
+impl RequestCounts {
+    pub fn record_list(&self) {
+        self.list.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_get(&self, bytes: usize) {
+        self.get.fetch_add(1, Ordering::Relaxed);
+        self.bytes_downloaded
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_head(&self) {
+        self.head.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_cached_get(&self) {
+        self.cached_get.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// GET requests answered from the analysis's object cache instead of storage
+    pub fn cached_gets(&self) -> u64 {
+        self.cached_get.load(Ordering::Relaxed)
+    }
+
+    /// (LIST, GET, HEAD, bytes downloaded) so far
+    pub fn snapshot(&self) -> (u64, u64, u64, u64) {
+        (
+            self.list.load(Ordering::Relaxed),
+            self.get.load(Ordering::Relaxed),
+            self.head.load(Ordering::Relaxed),
+            self.bytes_downloaded.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// Metadata objects kept by the object cache, as key fragments
+const CACHED_DIRS: [&str; 2] = ["_delta_log/", "metadata/"];
+/// Bodies beyond this are downloaded again rather than kept
+const MAX_CACHED_BYTES: u64 = 256 * 1024 * 1024;
+
+/// A cached body, set once the first read of the object completes
+type CachedBody = Arc<tokio::sync::OnceCell<Arc<Vec<u8>>>>;
+
+/// Metadata object bodies read during one analysis, so phases reading the same log,
+/// checkpoint or manifest files (schema evolution, snapshots, deletion vectors, ...)
+/// download each once. Concurrent reads of one key share a single request.
+#[derive(Debug, Default)]
+pub struct ObjectCache {
+    entries: Mutex<HashMap<(String, String), CachedBody>>, // by bucket and key
+    bytes: AtomicU64,
+}
+
+impl ObjectCache {
+    pub fn is_cacheable(key: &str) -> bool {
+        CACHED_DIRS.iter().any(|dir| key.contains(dir))
+    }
+
+    /// The body of `key` in `bucket`: cached, or read with `fetch` and then cached if it
+    /// fits. Returns whether it came from the cache.
+    pub async fn get_or_fetch<F>(
+        &self,
+        bucket: &str,
+        key: &str,
+        fetch: F,
+    ) -> Result<(Vec<u8>, bool)>
+    where
+        F: std::future::Future<Output = Result<Vec<u8>>>,
+    {
+        let cell = {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            let entry = (bucket.to_string(), key.to_string());
+            match entries.get(&entry) {
+                Some(cell) => Some(cell.clone()),
+                None if self.bytes.load(Ordering::Relaxed) >= MAX_CACHED_BYTES => None,
+                None => Some(entries.entry(entry).or_default().clone()),
+            }
+        };
+        let Some(cell) = cell else {
+            return Ok((fetch.await?, false));
+        };
+        let mut fetched = false;
+        let body = cell
+            .get_or_try_init(|| async {
+                fetched = true;
+                let body = fetch.await?;
+                self.bytes.fetch_add(body.len() as u64, Ordering::Relaxed);
+                anyhow::Ok(Arc::new(body))
+            })
+            .await?;
+        Ok((body.as_ref().clone(), !fetched))
+    }
+}
+
+/// A multipart upload that was initiated but neither completed nor aborted
+#[derive(Debug, Clone)]
+pub struct UploadInfo {
+    pub key: String,
+    pub upload_id: String,
+    pub initiated: Option<DateTime<Utc>>,
+}
+
+/// Answer to a HEAD request for one object
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeadOutcome {
+    Present,
+    Missing,
+    Throttled, // 503 SlowDown even after the SDK's own retries
+}
+
 pub struct S3ClientWrapper {
     pub client: S3Client,
     pub bucket: String,
     pub prefix: String,
+    pub requests: Arc<RequestCounts>,
+    /// Objects listed from a provided listing instead of storage, with keys in the bucket
+    pub listing: Option<Arc<Vec<ObjectInfo>>>,
+    pub cache: Arc<ObjectCache>,
+}
+
+/// Where S3 credentials come from when not the default AWS chain. Static keys take
+/// precedence over a named profile (which may use SSO); `role_arn` is assumed with
+/// whichever of them applies.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AwsCredentials {
+    #[serde(default)]
+    pub aws_access_key_id: Option<String>,
+    #[serde(default)]
+    pub aws_secret_access_key: Option<String>,
+    #[serde(default)]
+    pub aws_session_token: Option<String>,
+    #[serde(default)]
+    pub aws_profile: Option<String>,
+    #[serde(default)]
+    pub role_arn: Option<String>,
+    #[serde(default)]
+    pub external_id: Option<String>, // required by some cross-account roles
 }
 
 impl S3ClientWrapper {
     pub async fn new(
         s3_path: &str,
-        aws_access_key_id: Option<String>,
-        aws_secret_access_key: Option<String>,
+        credentials: AwsCredentials,
         aws_region: Option<String>,
+        network: &NetworkSettings,
     ) -> Result<Self> {
-        let url = Url::parse(s3_path)?;
-        let bucket = url
-            .host_str()
-            .ok_or_else(|| anyhow::anyhow!("Invalid S3 URL: missing bucket"))?
-            .to_string();
-        let prefix = url.path().trim_start_matches('/').to_string();
+        // A malformed URL fails before any credentials are resolved
+        Url::parse(s3_path)?;
+        let client = Self::connect(credentials, aws_region, network).await?;
+        Self::with_client(client, s3_path)
+    }
+
+    /// An S3 client with resolved credentials and region, for reuse across tables
+    pub async fn connect(
+        credentials: AwsCredentials,
+        aws_region: Option<String>,
+        network: &NetworkSettings,
+    ) -> Result<S3Client> {
+        let endpoint = StorageProvider::resolve(
+            network.provider,
+            network.endpoint_url.as_deref(),
+            aws_region.as_deref(),
+        )?;
 
         let region = if let Some(region_str) = aws_region {
             Region::new(region_str)
         } else {
-            RegionProviderChain::default_provider()
+            // A named profile's region comes before the environment's
+            let profile_region = match credentials.aws_profile {
+                Some(ref profile) => {
+                    ProfileFileRegionProvider::builder()
+                        .profile_name(profile)
+                        .build()
+                        .region()
+                        .await
+                }
+                None => None,
+            };
+            RegionProviderChain::first_try(profile_region)
+                .or_default_provider()
                 .region()
                 .await
                 .unwrap_or_else(|| Region::new("us-east-1"))
         };
 
-        let config = if let (Some(access_key), Some(secret_key)) =
-            (aws_access_key_id, aws_secret_access_key)
-        {
-            let creds = Credentials::new(access_key, secret_key, None, None, "drainage");
-            aws_config::from_env()
+        let connector = network.aws_connector()?;
+        let mut loader = aws_config::from_env().region(region.clone());
+        if let Some(ref connector) = connector {
+            loader = loader.http_connector(connector.clone());
+        }
+        if let Some(timeouts) = network.aws_timeouts() {
+            loader = loader.timeout_config(timeouts);
+        }
+        if let Some(ref profile) = credentials.aws_profile {
+            loader = loader.profile_name(profile);
+        }
+        if let (Some(access_key), Some(secret_key)) = (
+            credentials.aws_access_key_id,
+            credentials.aws_secret_access_key,
+        ) {
+            for secret in [Some(&secret_key), credentials.aws_session_token.as_ref()]
+                .into_iter()
+                .flatten()
+            {
+                crate::sanitize::register(secret);
+            }
+            loader = loader.credentials_provider(Credentials::new(
+                access_key,
+                secret_key,
+                credentials.aws_session_token,
+                None,
+                "drainage",
+            ));
+        }
+        let mut config = loader.load().await;
+
+        if let Some(role_arn) = credentials.role_arn {
+            let source = config
+                .credentials_provider()
+                .cloned()
+                .context("No credentials to assume the role with")?;
+            let mut role = AssumeRoleProvider::builder(role_arn)
+                .session_name("drainage")
+                .region(region.clone());
+            if let Some(external_id) = credentials.external_id {
+                role = role.external_id(external_id);
+            }
+            if let Some(sts_connector) = connector
+                .as_ref()
+                .and_then(|c| c.connector(&Default::default(), None))
+            {
+                role = role.connection(sts_connector);
+            }
+            let mut loader = aws_config::from_env()
                 .region(region)
-                .credentials_provider(creds)
-                .load()
-                .await
-        } else {
-            aws_config::from_env().region(region).load().await
-        };
+                .credentials_provider(role.build(source));
+            if let Some(connector) = connector {
+                loader = loader.http_connector(connector);
+            }
+            if let Some(timeouts) = network.aws_timeouts() {
+                loader = loader.timeout_config(timeouts);
+            }
+            config = loader.load().await;
+        }
 
-        let client = S3Client::new(&config);
+        // Only S3 goes to the provider's endpoint; STS stays with AWS
+        let mut s3_config = aws_sdk_s3::config::Builder::from(&config);
+        if let Some(endpoint) = endpoint {
+            if let Some(endpoint_url) = endpoint.endpoint_url {
+                s3_config = s3_config.endpoint_url(endpoint_url);
+            }
+            if let Some(region) = endpoint.region {
+                s3_config = s3_config.region(Region::new(region));
+            }
+            s3_config = s3_config.force_path_style(endpoint.force_path_style);
+        }
+        Ok(S3Client::from_conf(s3_config.build()))
+    }
 
+    /// A wrapper for the table at `s3_path` using `client`, with its own request counts
+    pub fn with_client(client: S3Client, s3_path: &str) -> Result<Self> {
+        let url = Url::parse(s3_path)?;
+        let bucket = url
+            .host_str()
+            .ok_or_else(|| {
+                DrainageError::InvalidInput(ErrorContext::new(format!(
+                    "Invalid S3 URL {}: missing bucket",
+                    s3_path
+                )))
+            })?
+            .to_string();
         Ok(Self {
             client,
             bucket,
-            prefix,
+            prefix: url.path().trim_start_matches('/').to_string(),
+            requests: Arc::default(),
+            listing: None,
+            cache: Arc::default(),
+        })
+    }
+
+    /// A wrapper for another `s3://bucket/prefix` location sharing this client's credentials,
+    /// request counts and object cache
+    pub fn with_location(&self, s3_path: &str) -> Result<Self> {
+        Ok(Self {
+            requests: self.requests.clone(),
+            cache: self.cache.clone(),
+            ..Self::with_client(self.client.clone(), s3_path)?
         })
     }
 
+    /// This wrapper using `cache`, shared across a batch of analyses, or an empty object
+    /// cache, so one analysis never reads objects cached by an earlier one
+    pub fn with_cache(self, cache: Option<&Arc<ObjectCache>>) -> Self {
+        Self {
+            cache: cache.cloned().unwrap_or_default(),
+            ..self
+        }
+    }
+
+    /// This wrapper listing from `listing` (`s3://` URLs, keys in the bucket or paths
+    /// relative to the table) instead of storage, when one is given
+    pub fn with_listing(self, listing: Option<&Vec<ObjectInfo>>) -> Self {
+        let Some(listing) = listing else {
+            return self;
+        };
+        let table_prefix = format!("{}/", self.prefix.trim_end_matches('/'));
+        let objects = listing
+            .iter()
+            .map(|object| {
+                let key = match object.key.split_once("://") {
+                    Some((_, location)) => location
+                        .split_once('/')
+                        .map_or("", |(_, key)| key)
+                        .to_string(),
+                    None if table_prefix == "/" || object.key.starts_with(&table_prefix) => {
+                        object.key.clone()
+                    }
+                    None => self.table_key(object.key.trim_start_matches('/')),
+                };
+                ObjectInfo {
+                    key,
+                    ..object.clone()
+                }
+            })
+            .collect();
+        Self {
+            listing: Some(Arc::new(objects)),
+            ..self
+        }
+    }
+
     pub async fn list_objects(&self, prefix: &str) -> Result<Vec<ObjectInfo>> {
+        self.list_objects_until(prefix, &Deadline::default()).await
+    }
+
+    /// List objects under `prefix`, stopping at the first page boundary past `deadline`
+    #[tracing::instrument(level = "debug", skip(self, deadline), fields(objects))]
+    pub async fn list_objects_until(
+        &self,
+        prefix: &str,
+        deadline: &Deadline,
+    ) -> Result<Vec<ObjectInfo>> {
+        if let Some(ref listing) = self.listing {
+            return Ok(listing
+                .iter()
+                .filter(|object| object.key.starts_with(prefix))
+                .cloned()
+                .collect());
+        }
         let mut objects = Vec::new();
         let mut continuation_token: Option<String> = None;
 
@@ -69,42 +388,316 @@ impl S3ClientWrapper {
                 request = request.continuation_token(token);
             }
 
-            let response = request.send().await?;
+            let response = request
+                .send()
+                .await
+                .map_err(|e| DrainageError::from_sdk("ListObjectsV2", &self.bucket, prefix, e))?;
+            self.requests.record_list();
 
             if let Some(contents) = response.contents {
                 for obj in contents {
                     objects.push(ObjectInfo {
                         key: obj.key.unwrap_or_default(),
                         size: obj.size,
-                        last_modified: obj.last_modified.map(|dt| format!("{:?}", dt)),
+                        last_modified: obj
+                            .last_modified
+                            .and_then(|dt| DateTime::from_timestamp(dt.secs(), dt.subsec_nanos())),
                         etag: obj.e_tag,
+                        storage_class: obj.storage_class.map(|class| class.as_str().to_string()),
                     });
                 }
             }
 
-            if response.is_truncated {
-                continuation_token = response.next_continuation_token;
-            } else {
+            if !response.is_truncated {
+                break;
+            }
+            if deadline.expired() {
+                deadline.cut("listing");
                 break;
             }
+            continuation_token = response.next_continuation_token;
+        }
+
+        tracing::Span::current().record("objects", objects.len());
+        Ok(objects)
+    }
+
+    /// List a table's objects, scoped to `filter` when one is set. Objects under
+    /// `metadata_dirs` are always listed in full; data files only until `deadline`. Data
+    /// files are first looked up under `data_dir` + the filter's partition directory; if
+    /// nothing is there (the filter keys aren't the leading partition columns), the whole
+    /// table is listed and filtered by path.
+    pub async fn list_table_objects(
+        &self,
+        metadata_dirs: &[&str],
+        data_dir: &str,
+        filter: &PartitionFilter,
+        deadline: &Deadline,
+    ) -> Result<Vec<ObjectInfo>> {
+        let table_prefix = self.get_prefix();
+        if filter.is_empty() && !deadline.is_set() {
+            return self.list_objects(table_prefix).await;
         }
 
+        let is_metadata = |key: &str| {
+            let relative = relative_key(table_prefix, key);
+            metadata_dirs.iter().any(|dir| relative.starts_with(dir))
+        };
+
+        let mut objects = Vec::new();
+        for dir in metadata_dirs {
+            objects.extend(self.list_objects(&self.table_key(dir)).await?);
+        }
+        // Without a filter the whole table is listed, as data may live outside `data_dir`
+        let mut data_objects = if filter.is_empty() {
+            Vec::new()
+        } else {
+            self.list_objects_until(
+                &self.table_key(&format!("{}{}", data_dir, filter.directory())),
+                deadline,
+            )
+            .await?
+        };
+        if data_objects.is_empty() {
+            data_objects = self
+                .list_objects_until(table_prefix, deadline)
+                .await?
+                .into_iter()
+                .filter(|obj| !is_metadata(&obj.key))
+                .collect();
+        }
+        objects.extend(
+            data_objects
+                .into_iter()
+                .filter(|obj| filter.matches(relative_key(table_prefix, &obj.key))),
+        );
         Ok(objects)
     }
 
+    /// Full key of a table-relative path
+    pub fn table_key(&self, relative: &str) -> String {
+        match self.prefix.trim_end_matches('/') {
+            "" => relative.to_string(),
+            prefix => format!("{}/{}", prefix, relative),
+        }
+    }
+
+    /// Read an object, from the analysis's object cache for metadata files read before
     pub async fn get_object(&self, key: &str) -> Result<Vec<u8>> {
+        if !ObjectCache::is_cacheable(key) {
+            return self.fetch_object(key).await;
+        }
+        let (body, cached) = self
+            .cache
+            .get_or_fetch(&self.bucket, key, self.fetch_object(key))
+            .await?;
+        if cached {
+            self.requests.record_cached_get();
+        }
+        Ok(body)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self), fields(bytes))]
+    async fn fetch_object(&self, key: &str) -> Result<Vec<u8>> {
         let response = self
             .client
             .get_object()
             .bucket(&self.bucket)
             .key(key)
             .send()
-            .await?;
+            .await
+            .map_err(|e| DrainageError::from_sdk("GetObject", &self.bucket, key, e))?;
+
+        let body = response.body.collect().await?.into_bytes().to_vec();
+        self.requests.record_get(body.len());
+        tracing::Span::current().record("bytes", body.len());
+        Ok(body)
+    }
+
+    /// Write an object, replacing any object at `key`
+    #[tracing::instrument(level = "debug", skip(self, body), fields(bytes = body.len()))]
+    pub async fn put_object(&self, key: &str, body: Vec<u8>) -> Result<()> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .body(body.into())
+            .send()
+            .await
+            .map_err(|e| DrainageError::from_sdk("PutObject", &self.bucket, key, e))?;
+        Ok(())
+    }
+
+    /// Write an object unless `key` exists (an `If-None-Match: *` conditional write),
+    /// returning false when it does
+    #[tracing::instrument(level = "debug", skip(self, body), fields(bytes = body.len()))]
+    pub async fn put_object_if_absent(&self, key: &str, body: Vec<u8>) -> Result<bool> {
+        let request = self
+            .client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .body(body.into())
+            .customize()
+            .await
+            .map_err(|e| DrainageError::from_sdk("PutObject", &self.bucket, key, e))?
+            .mutate_request(|request| {
+                request.headers_mut().insert(
+                    hyper::header::IF_NONE_MATCH,
+                    hyper::header::HeaderValue::from_static("*"),
+                );
+            });
+        match request.send().await {
+            Ok(_) => Ok(true),
+            // 409 when a concurrent conditional write to the key is still in progress
+            Err(e)
+                if matches!(
+                    e.raw_response().map(|r| r.http().status().as_u16()),
+                    Some(409 | 412)
+                ) =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(DrainageError::from_sdk("PutObject", &self.bucket, key, e).into()),
+        }
+    }
+
+    /// Fetch the last `length` bytes of an object (an HTTP suffix range)
+    #[tracing::instrument(level = "debug", skip(self), fields(bytes))]
+    pub async fn get_object_tail(&self, key: &str, length: u64) -> Result<Vec<u8>> {
+        let response = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .range(format!("bytes=-{}", length))
+            .send()
+            .await
+            .map_err(|e| DrainageError::from_sdk("GetObject", &self.bucket, key, e))?;
 
         let body = response.body.collect().await?.into_bytes().to_vec();
+        self.requests.record_get(body.len());
+        tracing::Span::current().record("bytes", body.len());
         Ok(body)
     }
 
+    /// Whether an object exists. Throttling is returned rather than raised so callers can
+    /// slow down; other failures, such as access denied, are errors.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn head_object(&self, key: &str) -> Result<HeadOutcome> {
+        let result = self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await;
+        self.requests.record_head();
+
+        match result {
+            Ok(_) => Ok(HeadOutcome::Present),
+            // HEAD responses have no body, so the status code is all there is to go on
+            Err(e) => match e.raw_response().map(|r| r.http().status().as_u16()) {
+                Some(404) => Ok(HeadOutcome::Missing),
+                Some(429 | 503) => Ok(HeadOutcome::Throttled),
+                _ => Err(DrainageError::from_sdk("HeadObject", &self.bucket, key, e).into()),
+            },
+        }
+    }
+
+    /// The `x-amz-restore` header of an archived object, None when no restore was requested
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn restore_header(&self, key: &str) -> Result<Option<String>> {
+        let result = self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await;
+        self.requests.record_head();
+        let head =
+            result.map_err(|e| DrainageError::from_sdk("HeadObject", &self.bucket, key, e))?;
+        Ok(head.restore().map(str::to_string))
+    }
+
+    /// Incomplete multipart uploads of keys under `prefix`
+    #[tracing::instrument(level = "debug", skip(self), fields(uploads))]
+    pub async fn list_multipart_uploads(&self, prefix: &str) -> Result<Vec<UploadInfo>> {
+        let mut uploads = Vec::new();
+        let mut key_marker: Option<String> = None;
+        let mut upload_id_marker: Option<String> = None;
+
+        loop {
+            let response = self
+                .client
+                .list_multipart_uploads()
+                .bucket(&self.bucket)
+                .prefix(prefix)
+                .set_key_marker(key_marker)
+                .set_upload_id_marker(upload_id_marker)
+                .send()
+                .await
+                .map_err(|e| {
+                    DrainageError::from_sdk("ListMultipartUploads", &self.bucket, prefix, e)
+                })?;
+            self.requests.record_list();
+
+            for upload in response.uploads.unwrap_or_default() {
+                uploads.push(UploadInfo {
+                    key: upload.key.unwrap_or_default(),
+                    upload_id: upload.upload_id.unwrap_or_default(),
+                    initiated: upload
+                        .initiated
+                        .and_then(|dt| DateTime::from_timestamp(dt.secs(), dt.subsec_nanos())),
+                });
+            }
+
+            if !response.is_truncated {
+                break;
+            }
+            key_marker = response.next_key_marker;
+            upload_id_marker = response.next_upload_id_marker;
+        }
+
+        tracing::Span::current().record("uploads", uploads.len());
+        Ok(uploads)
+    }
+
+    /// Bytes of the parts uploaded so far to an incomplete multipart upload
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn multipart_upload_size(&self, key: &str, upload_id: &str) -> Result<u64> {
+        let mut size = 0;
+        let mut part_number_marker: Option<String> = None;
+
+        loop {
+            let response = self
+                .client
+                .list_parts()
+                .bucket(&self.bucket)
+                .key(key)
+                .upload_id(upload_id)
+                .set_part_number_marker(part_number_marker)
+                .send()
+                .await
+                .map_err(|e| DrainageError::from_sdk("ListParts", &self.bucket, key, e))?;
+            self.requests.record_list();
+
+            size += response
+                .parts
+                .unwrap_or_default()
+                .iter()
+                .map(|part| part.size.max(0) as u64)
+                .sum::<u64>();
+
+            if !response.is_truncated {
+                break;
+            }
+            part_number_marker = response.next_part_number_marker;
+        }
+        Ok(size)
+    }
+
     pub fn get_bucket(&self) -> &str {
         &self.bucket
     }
@@ -114,12 +707,24 @@ impl S3ClientWrapper {
     }
 }
 
+/// Strip the table prefix from an object key, leaving the table-relative path
+pub fn relative_key<'a>(table_prefix: &str, key: &'a str) -> &'a str {
+    let prefix = table_prefix.trim_end_matches('/');
+    if prefix.is_empty() {
+        return key;
+    }
+    key.strip_prefix(prefix)
+        .map(|rest| rest.trim_start_matches('/'))
+        .unwrap_or(key)
+}
+
 #[derive(Debug, Clone)]
 pub struct ObjectInfo {
     pub key: String,
     pub size: i64,
-    pub last_modified: Option<String>,
+    pub last_modified: Option<DateTime<Utc>>,
     pub etag: Option<String>,
+    pub storage_class: Option<String>, // e.g. "STANDARD", "GLACIER"; None when unknown
 }
 
 #[cfg(test)]
@@ -132,15 +737,16 @@ mod tests {
         let object_info = ObjectInfo {
             key: "test/file.parquet".to_string(),
             size: 1024,
-            last_modified: Some("2023-01-01T00:00:00Z".to_string()),
+            last_modified: Some("2023-01-01T00:00:00Z".parse().unwrap()),
             etag: Some("etag123".to_string()),
+            storage_class: None,
         };
 
         assert_eq!(object_info.key, "test/file.parquet");
         assert_eq!(object_info.size, 1024);
         assert_eq!(
             object_info.last_modified,
-            Some("2023-01-01T00:00:00Z".to_string())
+            Some("2023-01-01T00:00:00Z".parse().unwrap())
         );
         assert_eq!(object_info.etag, Some("etag123".to_string()));
     }
@@ -150,8 +756,9 @@ mod tests {
         let object_info = ObjectInfo {
             key: "test/file.parquet".to_string(),
             size: 1024,
-            last_modified: Some("2023-01-01T00:00:00Z".to_string()),
+            last_modified: Some("2023-01-01T00:00:00Z".parse().unwrap()),
             etag: Some("etag123".to_string()),
+            storage_class: None,
         };
 
         let cloned = object_info.clone();
@@ -254,13 +861,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_relative_key() {
+        assert_eq!(
+            relative_key("tables/events", "tables/events/year=2024/part-0.parquet"),
+            "year=2024/part-0.parquet"
+        );
+        assert_eq!(
+            relative_key("tables/events/", "tables/events/part-0.parquet"),
+            "part-0.parquet"
+        );
+        assert_eq!(relative_key("", "part-0.parquet"), "part-0.parquet");
+        assert_eq!(
+            relative_key("other", "tables/events/part-0.parquet"),
+            "tables/events/part-0.parquet"
+        );
+    }
+
     #[test]
     fn test_object_info_optional_fields() {
         let object_info_with_all = ObjectInfo {
             key: "test/file.parquet".to_string(),
             size: 1024,
-            last_modified: Some("2023-01-01T00:00:00Z".to_string()),
+            last_modified: Some("2023-01-01T00:00:00Z".parse().unwrap()),
             etag: Some("etag123".to_string()),
+            storage_class: None,
         };
 
         let object_info_minimal = ObjectInfo {
@@ -268,6 +893,7 @@ mod tests {
             size: 1024,
             last_modified: None,
             etag: None,
+            storage_class: None,
         };
 
         assert!(object_info_with_all.last_modified.is_some());
@@ -275,4 +901,71 @@ mod tests {
         assert!(object_info_minimal.last_modified.is_none());
         assert!(object_info_minimal.etag.is_none());
     }
+
+    #[test]
+    fn test_session_client_counts_requests_per_table() {
+        let config = aws_sdk_s3::Config::builder()
+            .region(Region::new("us-east-1"))
+            .build();
+        let client = S3Client::from_conf(config);
+        let orders = S3ClientWrapper::with_client(client.clone(), "s3://lake/orders").unwrap();
+        let users = S3ClientWrapper::with_client(client, "s3://warehouse/users/").unwrap();
+        let inventory = orders.with_location("s3://inventory/lake/").unwrap();
+        assert_eq!(
+            (users.get_bucket(), users.get_prefix()),
+            ("warehouse", "users/")
+        );
+
+        orders.requests.record_list();
+        inventory.requests.record_get(10);
+        assert_eq!(orders.requests.snapshot(), (1, 1, 0, 10));
+        assert_eq!(users.requests.snapshot(), (0, 0, 0, 0));
+        assert!(S3ClientWrapper::with_client(orders.client.clone(), "lake/orders").is_err());
+    }
+
+    #[test]
+    fn test_object_cache_fetches_each_key_once() {
+        let cache = ObjectCache::default();
+        let fetches = AtomicU64::new(0);
+        let fetch = |body: &'static [u8]| {
+            let fetches = &fetches;
+            async move {
+                fetches.fetch_add(1, Ordering::Relaxed);
+                tokio::task::yield_now().await;
+                Ok(body.to_vec())
+            }
+        };
+        let key = "t/_delta_log/00000000000000000000.json";
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let (a, b) = tokio::join!(
+                cache.get_or_fetch("lake", key, fetch(b"{}")),
+                cache.get_or_fetch("lake", key, fetch(b"{}")),
+            );
+            assert_eq!(a.unwrap().0, b"{}");
+            assert!(b.unwrap().1);
+            assert_eq!(fetches.load(Ordering::Relaxed), 1);
+
+            // Same key in another bucket, and a failed read that is retried
+            cache
+                .get_or_fetch("other", key, fetch(b"[]"))
+                .await
+                .unwrap();
+            let failed = cache
+                .get_or_fetch("lake", "t/metadata/v1.metadata.json", async {
+                    anyhow::bail!("SlowDown")
+                })
+                .await;
+            assert!(failed.is_err());
+            let (body, cached) = cache
+                .get_or_fetch("lake", "t/metadata/v1.metadata.json", fetch(b"v1"))
+                .await
+                .unwrap();
+            assert_eq!((body.as_slice(), cached), (&b"v1"[..], false));
+            assert_eq!(fetches.load(Ordering::Relaxed), 3);
+        });
+        assert!(!ObjectCache::is_cacheable(
+            "t/date=2024-01-01/part-0.parquet"
+        ));
+    }
 }
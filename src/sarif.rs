@@ -0,0 +1,207 @@
+//! SARIF 2.1.0 output, so table-health findings show up in code-scanning dashboards
+//! (GitHub code scanning, Azure DevOps) when drainage runs in CI.
+//!
+//! Low health, storage cost, query performance and reliability scores are results of their
+//! own rule, colored by the same thresholds as the printed report; every recommendation is a
+//! result of the `recommendation` rule, and every regression `detect_regressions` found an
+//! error of the `history-regression` rule. Results point at the table, or at the file that
+//! defines it (e.g. a Terraform module) when one is given, since code scanning only shows
+//! results located in the repository.
+
+use crate::badge::{FAIR_SCORE, HEALTHY_SCORE};
+use crate::types::HealthReport;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const INFORMATION_URI: &str = "https://github.com/danielbeach/drainage";
+
+/// (id, short description) of every rule a report can produce
+const RULES: [(&str, &str); 6] = [
+    ("health-score", "Table health score is low"),
+    ("storage-cost", "Storage cost score is low"),
+    ("query-performance", "Query performance score is low"),
+    ("reliability", "Reliability score is low"),
+    ("recommendation", "Table maintenance recommendation"),
+    (
+        "history-regression",
+        "Table health regressed against its history",
+    ),
+];
+
+/// Result level of a 0.0-1.0 score, None when it is healthy
+fn score_level(score: f64) -> Option<&'static str> {
+    if score > HEALTHY_SCORE {
+        None
+    } else if score > FAIR_SCORE {
+        Some("warning")
+    } else {
+        Some("error")
+    }
+}
+
+/// A finding of a report: a low score, a recommendation or a regression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub rule_id: &'static str,
+    pub level: &'static str, // "warning" or "error"
+    pub message: String,
+    pub key: Option<String>, // what tells findings of the same rule apart
+}
+
+/// The report's findings, by rule
+pub fn findings(report: &HealthReport) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if let Some(level) = score_level(report.health_score) {
+        findings.push(Finding {
+            rule_id: "health-score",
+            level,
+            message: format!(
+                "{} table {} has a health score of {:.1}%",
+                report.table_type,
+                report.table_path,
+                report.health_score * 100.0
+            ),
+            key: None,
+        });
+    }
+    if let Some(ref sub_scores) = report.metrics.sub_scores {
+        for (rule_id, name, score) in [
+            (
+                "storage-cost",
+                "storage cost",
+                sub_scores.storage_cost_score,
+            ),
+            (
+                "query-performance",
+                "query performance",
+                sub_scores.query_performance_score,
+            ),
+            ("reliability", "reliability", sub_scores.reliability_score),
+        ] {
+            if let Some(level) = score_level(score) {
+                findings.push(Finding {
+                    rule_id,
+                    level,
+                    message: format!("The {} score is {:.1}%", name, score * 100.0),
+                    key: None,
+                });
+            }
+        }
+    }
+    for recommendation in &report.metrics.recommendations {
+        findings.push(Finding {
+            rule_id: "recommendation",
+            level: "warning",
+            message: recommendation.clone(),
+            key: Some(recommendation.clone()),
+        });
+    }
+    for regression in report.history_regressions.iter().flatten() {
+        findings.push(Finding {
+            rule_id: "history-regression",
+            level: "error",
+            message: regression.message.clone(),
+            key: Some(regression.metric.clone()),
+        });
+    }
+    findings
+}
+
+/// SARIF log of the report's findings, located at `artifact_uri` or else the table path
+pub fn to_sarif(report: &HealthReport, artifact_uri: Option<&str>) -> Value {
+    let uri = artifact_uri.unwrap_or(&report.table_path);
+    // Score findings keep their fingerprint as the score moves; recommendations are
+    // identified by their text and regressions by their metric
+    let results: Vec<Value> = findings(report)
+        .into_iter()
+        .map(|finding| {
+            let mut identity = format!("{}\n{}", report.table_path, finding.rule_id);
+            if let Some(ref key) = finding.key {
+                identity = format!("{}\n{}", identity, key);
+            }
+            let fingerprint = hex::encode(Sha256::digest(identity.as_bytes()));
+            json!({
+                "ruleId": finding.rule_id,
+                "ruleIndex": RULES.iter().position(|(id, _)| *id == finding.rule_id),
+                "level": finding.level,
+                "message": { "text": finding.message },
+                "locations": [{
+                    "physicalLocation": { "artifactLocation": { "uri": uri } },
+                    "logicalLocations": [{ "fullyQualifiedName": report.table_path, "kind": "table" }],
+                }],
+                "partialFingerprints": { "drainageFinding/v1": fingerprint },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "drainage",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": INFORMATION_URI,
+                    "rules": RULES
+                        .iter()
+                        .map(|(id, description)| json!({
+                            "id": id,
+                            "shortDescription": { "text": description },
+                        }))
+                        .collect::<Vec<_>>(),
+                }
+            },
+            "automationDetails": { "id": format!("drainage/{}/", report.table_path) },
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_sarif() {
+        let mut report = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        report.health_score = 0.7;
+        report.metrics.sub_scores = Some(crate::types::HealthSubScores {
+            storage_cost_score: 0.95,
+            query_performance_score: 0.5,
+            reliability_score: 1.0,
+            weakest_dimension: "query_performance".to_string(),
+        });
+        report
+            .metrics
+            .recommendations
+            .push("Run OPTIMIZE to compact small files".to_string());
+
+        let sarif = to_sarif(&report, Some("infra/events.tf"));
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        let rules: Vec<&str> = results
+            .iter()
+            .map(|r| r["ruleId"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            rules,
+            ["health-score", "query-performance", "recommendation"]
+        );
+        assert_eq!(results[0]["level"], "warning");
+        assert_eq!(results[1]["level"], "error");
+        assert_eq!(results[1]["ruleIndex"], 2);
+        assert_eq!(
+            results[2]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "infra/events.tf"
+        );
+
+        // Score fingerprints don't move with the score
+        report.health_score = 0.65;
+        let rerun = to_sarif(&report, None);
+        assert_eq!(
+            rerun["runs"][0]["results"][0]["partialFingerprints"],
+            results[0]["partialFingerprints"]
+        );
+    }
+}
@@ -0,0 +1,622 @@
+//! Health service (`drainage serve --config fleet.yaml`): re-analyzes a fleet of tables on a
+//! schedule, keeps each table's latest report in memory and serves them over HTTP.
+//!
+//! The fleet file is YAML (TOML when it ends in `.toml`) with the address to listen on, the
+//! default interval between analyses, analysis `options` for every table and the `tables`
+//! themselves, each an `s3_path` with the fields of an RPC `analyze` request plus an
+//! optional `name`, `interval_minutes` and `options` of its own. Tables are analyzed at
+//! startup, at most `max_concurrency` at a time, then again `interval_minutes` after each
+//! analysis finished. A failed analysis keeps the previous report and records the error.
+//!
+//! Endpoints: `GET /health`, `GET /tables` (a summary per table), `GET /tables/<name>` (the
+//! latest report and schedule), `POST /tables/<name>/refresh` (analyze now) and
+//! `GET /metrics` (Prometheus text format). Other services analyze any table on demand with
+//! `POST /analyze`, which takes the params of an RPC `analyze` request and answers with the
+//! report once done, sharing the `max_concurrency` slots with the schedule.
+//! `GET /reports/<table>` returns the latest report of a fleet table (by name or path) or
+//! of a path analyzed on demand.
+
+use crate::config::AnalysisConfig;
+use crate::error::DrainageError;
+use crate::health_analyzer::TableTarget;
+use crate::types::HealthReport;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{Notify, Semaphore};
+
+fn default_listen() -> String {
+    "127.0.0.1:8080".to_string()
+}
+
+fn default_interval_minutes() -> f64 {
+    60.0
+}
+
+fn default_max_concurrency() -> usize {
+    4
+}
+
+/// The fleet file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FleetConfig {
+    #[serde(default = "default_listen")]
+    pub listen: String,
+    #[serde(default = "default_interval_minutes")]
+    pub interval_minutes: f64,
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+    /// Analysis options of every table, under each table's own
+    #[serde(default)]
+    pub options: Map<String, Value>,
+    pub tables: Vec<FleetTable>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FleetTable {
+    /// Name in URLs and metrics, the `s3_path` if unset
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub target: TableTarget,
+    #[serde(default)]
+    pub interval_minutes: Option<f64>,
+    #[serde(default)]
+    pub options: Map<String, Value>,
+}
+
+impl FleetConfig {
+    /// Parse a fleet file, TOML if `path` ends in `.toml` and YAML otherwise
+    pub fn parse(path: &Path, text: &str) -> Result<Self, String> {
+        let config: Self = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(text).map_err(|e| e.to_string())?,
+            _ => serde_yaml::from_str(text).map_err(|e| e.to_string())?,
+        };
+        if config.tables.is_empty() {
+            return Err("No tables to analyze".to_string());
+        }
+        Ok(config)
+    }
+}
+
+/// What the service knows about a table, as served by `/tables`
+#[derive(Debug, Default, Serialize)]
+struct TableState {
+    name: String,
+    s3_path: String,
+    running: bool,
+    runs: u64,
+    failures: u64,
+    last_started: Option<DateTime<Utc>>,
+    last_finished: Option<DateTime<Utc>>,
+    last_duration_seconds: Option<f64>,
+    next_run: Option<DateTime<Utc>>,
+    last_error: Option<Value>, // {"code", "message"} of the latest analysis, if it failed
+    health_score: Option<f64>,
+    analysis_timestamp: Option<DateTime<Utc>>,
+    #[serde(skip)]
+    report: Option<Arc<HealthReport>>,
+}
+
+/// A table on the schedule
+struct Scheduled {
+    target: TableTarget,
+    config: AnalysisConfig,
+    interval: Duration,
+    state: Mutex<TableState>,
+    refresh: Notify,
+}
+
+impl Scheduled {
+    fn state(&self) -> std::sync::MutexGuard<'_, TableState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Analyze the table once, recording the outcome
+    async fn run(&self) {
+        let started = Instant::now();
+        {
+            let mut state = self.state();
+            state.running = true;
+            state.last_started = Some(Utc::now());
+        }
+        let outcome = self.target.analyze(self.config.clone(), |_| {}).await;
+
+        let mut state = self.state();
+        state.running = false;
+        state.runs += 1;
+        state.last_finished = Some(Utc::now());
+        state.last_duration_seconds = Some(started.elapsed().as_secs_f64());
+        match outcome {
+            Ok(report) => {
+                state.last_error = None;
+                state.health_score = Some(report.health_score);
+                state.analysis_timestamp = Some(report.analysis_timestamp);
+                state.report = Some(Arc::new(report));
+            }
+            Err(e) => {
+                let error = DrainageError::from_anyhow(e, None);
+                tracing::warn!("Analysis of {} failed: {}", state.name, error);
+                state.failures += 1;
+                state.last_error =
+                    Some(json!({ "code": error.code(), "message": error.to_string() }));
+            }
+        }
+    }
+
+    /// Analyze the table now and then every interval, or sooner when a refresh is asked for
+    async fn schedule(self: Arc<Self>, permits: Arc<Semaphore>) {
+        loop {
+            match permits.acquire().await {
+                Ok(_permit) => self.run().await,
+                Err(_) => return, // closed: shutting down
+            }
+            let next = chrono::Duration::from_std(self.interval).unwrap_or(chrono::Duration::MAX);
+            self.state().next_run = Utc::now().checked_add_signed(next);
+            tokio::select! {
+                _ = tokio::time::sleep(self.interval) => {}
+                _ = self.refresh.notified() => {}
+            }
+        }
+    }
+}
+
+/// The tables of a fleet and their latest results
+pub struct Service {
+    tables: Vec<Arc<Scheduled>>,
+    permits: Arc<Semaphore>, // analyses allowed to run at once
+    /// Latest report of each path analyzed through `POST /analyze`
+    analyzed: Mutex<HashMap<String, Arc<HealthReport>>>,
+}
+
+impl Service {
+    pub fn new(fleet: FleetConfig) -> Result<Self, String> {
+        let mut tables: Vec<Arc<Scheduled>> = Vec::new();
+        for table in fleet.tables {
+            let name = table.name.unwrap_or_else(|| table.target.s3_path.clone());
+            if tables.iter().any(|t| t.state().name == name) {
+                return Err(format!("Duplicate table name: {}", name));
+            }
+            let minutes = table.interval_minutes.unwrap_or(fleet.interval_minutes);
+            if !(minutes.is_finite() && minutes > 0.0) {
+                return Err(format!(
+                    "Invalid interval_minutes for {}: {}",
+                    name, minutes
+                ));
+            }
+            let mut options = fleet.options.clone();
+            options.extend(table.options);
+            let config =
+                AnalysisConfig::from_json(&options).map_err(|e| format!("{}: {}", name, e))?;
+            tables.push(Arc::new(Scheduled {
+                state: Mutex::new(TableState {
+                    name,
+                    s3_path: table.target.s3_path.clone(),
+                    ..TableState::default()
+                }),
+                target: table.target,
+                config,
+                interval: Duration::from_secs_f64(minutes * 60.0),
+                refresh: Notify::new(),
+            }));
+        }
+        Ok(Self {
+            tables,
+            permits: Arc::new(Semaphore::new(fleet.max_concurrency.max(1))),
+            analyzed: Mutex::default(),
+        })
+    }
+
+    fn table(&self, name: &str) -> Option<&Arc<Scheduled>> {
+        self.tables.iter().find(|t| t.state().name == name)
+    }
+
+    /// The newest report of `table`, a fleet table's name or any analyzed path
+    fn latest_report(&self, table: &str) -> Option<Arc<HealthReport>> {
+        let scheduled = self.tables.iter().filter_map(|t| {
+            let state = t.state();
+            (state.name == table || state.s3_path == table)
+                .then(|| state.report.clone())
+                .flatten()
+        });
+        let analyzed = self.analyzed.lock().unwrap_or_else(|e| e.into_inner());
+        scheduled
+            .chain(analyzed.get(table).cloned())
+            .max_by_key(|report| report.analysis_timestamp)
+    }
+
+    /// Answer a request
+    pub async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        let (parts, body) = request.into_parts();
+        if parts.method != Method::POST || parts.uri.path().trim_matches('/') != "analyze" {
+            return self.respond(&parts.method, parts.uri.path());
+        }
+        match hyper::body::to_bytes(body).await {
+            Ok(body) => self.analyze(&body).await,
+            Err(e) => json_response(
+                StatusCode::BAD_REQUEST,
+                json!({ "error": format!("Cannot read the request: {}", e) }),
+            ),
+        }
+    }
+
+    /// `POST /analyze`: analyze the table in `body` once a slot is free
+    async fn analyze(&self, body: &[u8]) -> Response<Body> {
+        let params = serde_json::from_slice::<crate::rpc::AnalyzeParams>(body)
+            .map_err(|e| format!("Invalid request: {}", e))
+            .and_then(|params| {
+                let config = AnalysisConfig::from_json(&params.options)?;
+                Ok((params.target, config))
+            });
+        let (target, config) = match params {
+            Ok(params) => params,
+            Err(message) => {
+                return json_response(StatusCode::BAD_REQUEST, json!({ "error": message }))
+            }
+        };
+        let Ok(_permit) = self.permits.acquire().await else {
+            return json_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                json!({ "error": "Shutting down" }),
+            );
+        };
+        match target.analyze(config, |_| {}).await {
+            Ok(report) => {
+                let body = json!(report);
+                self.analyzed
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(target.s3_path, Arc::new(report));
+                json_response(StatusCode::OK, body)
+            }
+            Err(e) => {
+                let error = DrainageError::from_anyhow(e, None);
+                let status = match error.code() {
+                    "INVALID_INPUT" => StatusCode::BAD_REQUEST,
+                    "NOT_FOUND" => StatusCode::NOT_FOUND,
+                    _ if error.retriable() => StatusCode::SERVICE_UNAVAILABLE,
+                    _ => StatusCode::INTERNAL_SERVER_ERROR,
+                };
+                let context = error.context();
+                let error = json!({
+                    "code": error.code(),
+                    "message": error.to_string(),
+                    "phase": context.phase,
+                    "object_key": context.object_key,
+                    "retriable": error.retriable(),
+                });
+                json_response(status, json!({ "error": error }))
+            }
+        }
+    }
+
+    fn respond(&self, method: &Method, path: &str) -> Response<Body> {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        // Table names may be `s3://` paths, which arrive percent-encoded
+        let name = segments
+            .get(1)
+            .map(|name| percent_encoding::percent_decode_str(name).decode_utf8_lossy());
+        let table = name.as_deref().and_then(|name| self.table(name));
+        match (method, segments.as_slice(), table) {
+            (&Method::GET, ["health"], _) => {
+                json_response(StatusCode::OK, json!({ "status": "ok" }))
+            }
+            (&Method::GET, ["tables"], _) => {
+                let tables: Vec<Value> = self.tables.iter().map(|t| json!(*t.state())).collect();
+                json_response(StatusCode::OK, json!({ "tables": tables }))
+            }
+            (&Method::GET, ["tables", _], Some(table)) => {
+                let state = table.state();
+                let mut body = json!(*state);
+                body["report"] = json!(state.report.as_deref());
+                json_response(StatusCode::OK, body)
+            }
+            (&Method::POST, ["tables", _, "refresh"], Some(table)) => {
+                table.refresh.notify_one();
+                json_response(StatusCode::ACCEPTED, json!({ "refresh": "scheduled" }))
+            }
+            (&Method::GET, ["reports", _], _) => {
+                let name = name.unwrap_or_default();
+                match self.latest_report(&name) {
+                    Some(report) => json_response(StatusCode::OK, json!(*report)),
+                    None => json_response(
+                        StatusCode::NOT_FOUND,
+                        json!({ "error": format!("No report of {}", name) }),
+                    ),
+                }
+            }
+            (&Method::GET, ["metrics"], _) => Response::builder()
+                .header(hyper::header::CONTENT_TYPE, "text/plain; version=0.0.4")
+                .body(Body::from(self.metrics()))
+                .unwrap_or_default(),
+            (_, ["tables", ..], None) => json_response(
+                StatusCode::NOT_FOUND,
+                json!({ "error": format!("Unknown table: {}", name.unwrap_or_default()) }),
+            ),
+            _ => json_response(StatusCode::NOT_FOUND, json!({ "error": "Not found" })),
+        }
+    }
+
+    /// Latest results in the Prometheus text format
+    fn metrics(&self) -> String {
+        type Metric = (
+            &'static str,
+            &'static str,
+            &'static str,
+            fn(&TableState) -> Option<f64>,
+        );
+        const METRICS: [Metric; 7] = [
+            (
+                "drainage_health_score",
+                "gauge",
+                "Health score of the latest report",
+                |s| s.health_score,
+            ),
+            (
+                "drainage_total_size_bytes",
+                "gauge",
+                "Size of the table's data files",
+                |s| s.report.as_ref().map(|r| r.metrics.total_size_bytes as f64),
+            ),
+            (
+                "drainage_unreferenced_size_bytes",
+                "gauge",
+                "Size of files no snapshot references",
+                |s| {
+                    s.report
+                        .as_ref()
+                        .map(|r| r.metrics.unreferenced_size_bytes as f64)
+                },
+            ),
+            (
+                "drainage_total_files",
+                "gauge",
+                "Number of data files",
+                |s| s.report.as_ref().map(|r| r.metrics.total_files as f64),
+            ),
+            (
+                "drainage_analysis_timestamp_seconds",
+                "gauge",
+                "When the latest report was taken",
+                |s| s.analysis_timestamp.map(|t| t.timestamp() as f64),
+            ),
+            (
+                "drainage_analysis_runs_total",
+                "counter",
+                "Analyses run",
+                |s| Some(s.runs as f64),
+            ),
+            (
+                "drainage_analysis_failures_total",
+                "counter",
+                "Analyses that failed",
+                |s| Some(s.failures as f64),
+            ),
+        ];
+        // Tags are labels too, bar those clashing with `table`, `path` or reserved names
+        let labels: Vec<String> = self
+            .tables
+            .iter()
+            .map(|table| {
+                let state = table.state();
+                let mut labels = format!(
+                    "table=\"{}\",path=\"{}\"",
+                    label(&state.name),
+                    label(&state.s3_path)
+                );
+                let tags: BTreeMap<&String, &String> = table.config.tags.iter().collect();
+                for (key, value) in tags {
+                    if !matches!(key.as_str(), "table" | "path") && !key.starts_with("__") {
+                        let _ = write!(labels, ",{}=\"{}\"", key, label(value));
+                    }
+                }
+                labels
+            })
+            .collect();
+        let mut text = String::new();
+        for (name, kind, help, value) in METRICS {
+            let _ = writeln!(text, "# HELP {} {}\n# TYPE {} {}", name, help, name, kind);
+            for (table, labels) in self.tables.iter().zip(&labels) {
+                if let Some(value) = value(&table.state()) {
+                    let _ = writeln!(text, "{}{{{}}} {}", name, labels, value);
+                }
+            }
+        }
+        text
+    }
+}
+
+/// A Prometheus label value, escaped
+fn label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap_or_default()
+}
+
+/// Run the service for the fleet file at `path` until interrupted
+pub fn serve(path: &Path) -> Result<()> {
+    let text =
+        std::fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let fleet = FleetConfig::parse(path, &text)
+        .and_then(|fleet| Ok((fleet.listen.clone(), Service::new(fleet)?)))
+        .map_err(|e| anyhow::anyhow!("Invalid fleet file {}: {}", path.display(), e));
+    let (listen, service) = fleet?;
+    let address: std::net::SocketAddr = listen
+        .parse()
+        .with_context(|| format!("Invalid listen address: {}", listen))?;
+
+    tokio::runtime::Runtime::new()?.block_on(async move {
+        let service = Arc::new(service);
+        let permits = service.permits.clone();
+        for table in &service.tables {
+            tokio::spawn(table.clone().schedule(permits.clone()));
+        }
+
+        let make_service = make_service_fn(move |_| {
+            let service = service.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let service = service.clone();
+                    async move { Ok::<_, Infallible>(service.handle(request).await) }
+                }))
+            }
+        });
+        let server = hyper::Server::try_bind(&address)?.serve(make_service);
+        tracing::info!(
+            "Serving {} on http://{}",
+            path.display(),
+            server.local_addr()
+        );
+        server
+            .with_graceful_shutdown(async {
+                let _ = tokio::signal::ctrl_c().await;
+            })
+            .await?;
+        permits.close();
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(response: Response<Body>) -> Value {
+        let bytes = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(hyper::body::to_bytes(response.into_body()))
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_service_serves_latest_results() {
+        let yaml = r#"
+            interval_minutes: 30
+            options:
+              deep_scan: true
+            tables:
+              - name: events
+                s3_path: s3://lake/events
+                table_type: delta
+                options:
+                  tags: { team: growth, path: shadowed }
+              - s3_path: not a url
+                interval_minutes: 5
+        "#;
+        let fleet = FleetConfig::parse(Path::new("fleet.yaml"), yaml).unwrap();
+        let service = Service::new(fleet).unwrap();
+        assert!(service.tables[0].config.deep_scan);
+        assert_eq!(service.tables[1].interval, Duration::from_secs(300));
+
+        let broken = service.table("not a url").unwrap();
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(broken.run());
+        let mut report = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        report.health_score = 0.75;
+        {
+            let mut state = service.tables[0].state();
+            state.health_score = Some(report.health_score);
+            state.report = Some(Arc::new(report));
+        }
+
+        let tables = body(service.respond(&Method::GET, "/tables"));
+        assert_eq!(tables["tables"][0]["health_score"], 0.75);
+        assert_eq!(tables["tables"][1]["failures"], 1);
+        assert_eq!(tables["tables"][1]["last_error"]["code"], "INVALID_INPUT");
+        let events = body(service.respond(&Method::GET, "/tables/events"));
+        assert_eq!(events["report"]["table_path"], "s3://lake/events");
+        let refresh = service.respond(&Method::POST, "/tables/not%20a%20url/refresh");
+        assert_eq!(refresh.status(), StatusCode::ACCEPTED);
+        let missing = service.respond(&Method::GET, "/tables/orders");
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+
+        let metrics = service.metrics();
+        assert!(metrics
+            .contains("drainage_health_score{table=\"events\",path=\"s3://lake/events\",team=\"growth\"} 0.75"));
+        assert!(metrics.contains(
+            "drainage_analysis_failures_total{table=\"not a url\",path=\"not a url\"} 1"
+        ));
+
+        let duplicate = "tables:\n  - s3_path: s3://a/t\n  - s3_path: s3://a/t\n";
+        let fleet = FleetConfig::parse(Path::new("fleet.yml"), duplicate).unwrap();
+        assert_eq!(
+            Service::new(fleet).err().unwrap(),
+            "Duplicate table name: s3://a/t"
+        );
+    }
+
+    #[test]
+    fn test_analyze_on_demand_and_read_reports() {
+        let yaml = "tables:\n  - name: events\n    s3_path: s3://lake/events\n";
+        let service = Service::new(FleetConfig::parse(Path::new("f.yaml"), yaml).unwrap()).unwrap();
+        let post = |body: &str| {
+            let request = Request::post("/analyze")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(service.handle(request))
+        };
+
+        let invalid = post(r#"{"table_type": "delta"}"#);
+        assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+        assert!(body(invalid)["error"]
+            .as_str()
+            .unwrap()
+            .contains("missing field `s3_path`"));
+        let unknown = body(post(
+            r#"{"s3_path": "s3://lake/t", "options": {"bogus": 1}}"#,
+        ));
+        assert_eq!(unknown["error"], "Unknown analysis option: bogus");
+        let failed = post(r#"{"s3_path": "not a url", "table_type": "delta"}"#);
+        assert_eq!(failed.status(), StatusCode::BAD_REQUEST);
+        let failed = body(failed);
+        assert_eq!(failed["error"]["code"], "INVALID_INPUT");
+        assert_eq!(failed["error"]["retriable"], false);
+
+        let report = |path: &str, time: &str| {
+            let mut report = HealthReport::new(path.to_string(), "delta".to_string());
+            report.analysis_timestamp = time.parse().unwrap();
+            Arc::new(report)
+        };
+        service.tables[0].state().report = Some(report("s3://lake/events", "2024-06-01T00:00:00Z"));
+        service.analyzed.lock().unwrap().extend([
+            (
+                "s3://lake/events".to_string(),
+                report("s3://lake/events", "2024-06-02T00:00:00Z"),
+            ),
+            (
+                "s3://lake/orders".to_string(),
+                report("s3://lake/orders", "2024-06-01T00:00:00Z"),
+            ),
+        ]);
+        let events = body(service.respond(&Method::GET, "/reports/events"));
+        assert_eq!(events["analysis_timestamp"], "2024-06-01T00:00:00Z");
+        let latest = body(service.respond(&Method::GET, "/reports/s3%3A%2F%2Flake%2Fevents"));
+        assert_eq!(latest["analysis_timestamp"], "2024-06-02T00:00:00Z");
+        let orders = body(service.respond(&Method::GET, "/reports/s3%3A%2F%2Flake%2Forders"));
+        assert_eq!(orders["table_path"], "s3://lake/orders");
+        let missing = service.respond(&Method::GET, "/reports/s3%3A%2F%2Flake%2Fusers");
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,108 @@
+//! `DrainageSession`: one runtime and S3 client shared by many analyses.
+//!
+//! Each `analyze_*` call starts its own tokio runtime and resolves credentials from scratch
+//! (reading profiles, an SSO token or assuming a role), and the connections it opened close
+//! with that runtime. A fleet scan pays this for every table. A session does it once: its
+//! runtime keeps the connection pool alive between calls, and its client keeps the credential
+//! provider, which caches credentials and refreshes them before they expire.
+
+use crate::health_analyzer::HealthAnalyzer;
+use crate::interrupt;
+use crate::s3_client::{AwsCredentials, S3ClientWrapper};
+use crate::types::HealthReport;
+use aws_sdk_s3::Client as S3Client;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use tokio::runtime::Runtime;
+
+fn client_error(e: anyhow::Error) -> PyErr {
+    crate::error::DrainageError::from_anyhow(e, Some("client_setup"))
+        .into_py_err("Failed to create S3 client")
+}
+
+/// Credentials, options and connections reused by every `analyze` call
+#[pyclass]
+pub struct DrainageSession {
+    runtime: Runtime,
+    client: S3Client,
+    options: Py<PyDict>, // keyword options under each call's own
+}
+
+#[pymethods]
+impl DrainageSession {
+    /// Takes the credentials and keyword options of `analyze_table`. Storage and network
+    /// settings (`storage=`, `endpoint_url`, `provider`, proxies and timeouts) are fixed when
+    /// the session is created.
+    #[new]
+    #[pyo3(signature = (aws_access_key_id=None, aws_secret_access_key=None, aws_region=None, aws_session_token=None, aws_profile=None, role_arn=None, external_id=None, **options))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        py: Python<'_>,
+        aws_access_key_id: Option<String>,
+        aws_secret_access_key: Option<String>,
+        aws_region: Option<String>,
+        aws_session_token: Option<String>,
+        aws_profile: Option<String>,
+        role_arn: Option<String>,
+        external_id: Option<String>,
+        options: Option<&PyDict>,
+    ) -> PyResult<Self> {
+        let options = match options {
+            Some(options) => options.copy()?,
+            None => PyDict::new(py),
+        };
+        let (credentials, aws_region, config) = crate::options::resolve_with_credentials(
+            Some(options),
+            AwsCredentials {
+                aws_access_key_id,
+                aws_secret_access_key,
+                aws_session_token,
+                aws_profile,
+                role_arn,
+                external_id,
+            },
+            aws_region,
+        )?;
+        let network = config.network;
+        let runtime = Runtime::new()?;
+        let client = interrupt::block_on_in(py, &runtime, async {
+            S3ClientWrapper::connect(credentials, aws_region, &network)
+                .await
+                .map_err(client_error)
+        })?;
+        Ok(Self {
+            runtime,
+            client,
+            options: options.into(),
+        })
+    }
+
+    /// Analyze the table at `s3_path` like `analyze_table`, with keyword options applied on
+    /// top of the session's
+    #[pyo3(signature = (s3_path, table_type=None, **options))]
+    fn analyze(
+        &self,
+        py: Python<'_>,
+        s3_path: String,
+        table_type: Option<String>,
+        options: Option<&PyDict>,
+    ) -> PyResult<HealthReport> {
+        let merged = self.options.as_ref(py).copy()?;
+        if let Some(options) = options {
+            if options.contains("storage")? {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "storage= is set when the session is created",
+                ));
+            }
+            merged.update(options.as_mapping())?;
+        }
+        let config = crate::options::resolve(Some(merged))?.config;
+        let s3_client =
+            S3ClientWrapper::with_client(self.client.clone(), &s3_path).map_err(client_error)?;
+        interrupt::block_on_in(py, &self.runtime, async {
+            HealthAnalyzer::with_client(s3_client, config)
+                .analyze(table_type.as_deref())
+                .await
+        })
+    }
+}
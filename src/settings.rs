@@ -0,0 +1,208 @@
+//! Defaults from a `drainage.toml` file and `DRAINAGE_*` environment variables, so fleet
+//! jobs can configure every analysis once instead of passing keyword options through
+//! orchestration code.
+//!
+//! The file is the one `DRAINAGE_CONFIG` names, or `drainage.toml` in the working directory.
+//! Its top-level keys are the keyword options of `analyze_table`, plus `max_concurrency` and a
+//! `[storage]` table with the `StorageOptions` fields. Each `DRAINAGE_<KEY>` variable sets
+//! the option `<key>`; values that parse as JSON (`true`, `8`, `{"team": "growth"}`) take
+//! that type, anything else is a string. Variables naming no option are logged and ignored,
+//! since the environment is shared with other tools. Variables take precedence over the
+//! file, and `options=`, `storage=` and keyword arguments over both.
+
+use crate::config::{AnalysisConfig, ANALYSIS_OPTIONS};
+use crate::options::StorageOptions;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+const ENV_PREFIX: &str = "DRAINAGE_";
+const CONFIG_FILE: &str = "drainage.toml";
+
+/// Settings applied under every call's own options
+#[derive(Debug, Clone, Default)]
+pub struct Defaults {
+    pub options: Map<String, Value>,
+    pub storage: StorageOptions,
+    pub max_concurrency: Option<usize>,
+}
+
+/// The `StorageOptions` field named `key`
+fn storage_field<'a>(storage: &'a mut StorageOptions, key: &str) -> Option<&'a mut Option<String>> {
+    match key {
+        "aws_access_key_id" => Some(&mut storage.aws_access_key_id),
+        "aws_secret_access_key" => Some(&mut storage.aws_secret_access_key),
+        "aws_session_token" => Some(&mut storage.aws_session_token),
+        "aws_region" => Some(&mut storage.aws_region),
+        "aws_profile" => Some(&mut storage.aws_profile),
+        "role_arn" => Some(&mut storage.role_arn),
+        "external_id" => Some(&mut storage.external_id),
+        _ => None,
+    }
+}
+
+impl Defaults {
+    /// Set `key` from a file or variable, routing storage and concurrency keys apart
+    fn set(&mut self, key: &str, value: Value) -> Result<(), String> {
+        if let Some(field) = storage_field(&mut self.storage, key) {
+            *field = match value {
+                Value::String(text) => Some(text),
+                Value::Null => None,
+                other => return Err(format!("Invalid value for {}: {}", key, other)),
+            };
+        } else if key == "max_concurrency" {
+            let concurrency = value
+                .as_u64()
+                .ok_or_else(|| format!("Invalid value for max_concurrency: {}", value))?;
+            self.max_concurrency = Some((concurrency as usize).max(1));
+        } else {
+            self.options.insert(key.to_string(), value);
+        }
+        Ok(())
+    }
+
+    /// Parse `toml` (the contents of `drainage.toml`) and `vars` (the environment), with
+    /// variables overriding the file
+    fn from_sources(
+        toml: Option<&str>,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, String> {
+        let mut defaults = Self::default();
+        if let Some(toml) = toml {
+            let table: Map<String, Value> = toml::from_str(toml).map_err(|e| e.to_string())?;
+            for (key, value) in table {
+                if key == "storage" {
+                    let Value::Object(storage) = value else {
+                        return Err(format!("Invalid value for storage: {}", value));
+                    };
+                    for (key, value) in storage {
+                        if storage_field(&mut defaults.storage, &key).is_none() {
+                            return Err(format!("Unknown storage option: {}", key));
+                        }
+                        defaults.set(&key, value)?;
+                    }
+                } else {
+                    defaults.set(&key, value)?;
+                }
+            }
+        }
+
+        let mut vars: Vec<(String, String)> = vars
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let key = name.strip_prefix(ENV_PREFIX)?.to_lowercase();
+                if key == "config" {
+                    return None;
+                }
+                let known = ANALYSIS_OPTIONS.contains(&key.as_str())
+                    || key == "max_concurrency"
+                    || storage_field(&mut defaults.storage, &key).is_some();
+                if !known {
+                    tracing::warn!("Ignoring {}: not a drainage option", name);
+                    return None;
+                }
+                Some((key, value))
+            })
+            .collect();
+        vars.sort();
+        for (key, value) in vars {
+            let value = serde_json::from_str(&value).unwrap_or(Value::String(value));
+            defaults
+                .set(&key, value)
+                .map_err(|e| format!("{}{}: {}", ENV_PREFIX, key.to_uppercase(), e))?;
+        }
+
+        // Checked here so a bad default fails with its source, not as a keyword option
+        AnalysisConfig::from_json(&defaults.options)?;
+        Ok(defaults)
+    }
+}
+
+/// The configuration file: `DRAINAGE_CONFIG`, which must exist, or `./drainage.toml` if present
+fn config_file() -> Result<Option<PathBuf>, String> {
+    match std::env::var_os(format!("{}CONFIG", ENV_PREFIX)) {
+        Some(path) => {
+            let path = PathBuf::from(path);
+            if !path.is_file() {
+                return Err(format!("DRAINAGE_CONFIG: {} not found", path.display()));
+            }
+            Ok(Some(path))
+        }
+        None => Ok(Path::new(CONFIG_FILE)
+            .is_file()
+            .then(|| PathBuf::from(CONFIG_FILE))),
+    }
+}
+
+/// Read the defaults from the configuration file and environment
+pub fn load() -> Result<Defaults, String> {
+    let vars = std::env::vars().filter(|(name, _)| name.starts_with(ENV_PREFIX));
+    let Some(path) = config_file()? else {
+        return Defaults::from_sources(None, vars)
+            .map_err(|e| format!("Invalid settings in the environment: {}", e));
+    };
+    let toml = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    Defaults::from_sources(Some(&toml), vars).map_err(|e| {
+        format!(
+            "Invalid settings in {} or the environment: {}",
+            path.display(),
+            e
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_environment_overrides_config_file() {
+        let toml = r#"
+            engine = "trino"
+            deep_scan = true
+            max_concurrency = 8
+            tags = { team = "growth" }
+
+            [storage]
+            aws_region = "eu-west-1"
+            aws_profile = "analytics"
+        "#;
+        let vars = [
+            ("DRAINAGE_MAX_CONCURRENCY", "16"),
+            ("DRAINAGE_ENDPOINT_URL", "http://localhost:9000"),
+            ("DRAINAGE_DEEP_SCAN", "false"),
+            ("DRAINAGE_AWS_REGION", "us-east-1"),
+            ("DRAINAGE_CONFIG", "/etc/drainage.toml"),
+            ("HOME", "/root"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+
+        let defaults = Defaults::from_sources(Some(toml), vars).unwrap();
+        assert_eq!(defaults.max_concurrency, Some(16));
+        assert_eq!(defaults.storage.aws_region.as_deref(), Some("us-east-1"));
+        assert_eq!(defaults.storage.aws_profile.as_deref(), Some("analytics"));
+        let config = AnalysisConfig::from_json(&defaults.options).unwrap();
+        assert_eq!(config.engine.name, "trino");
+        assert!(!config.deep_scan);
+        assert_eq!(
+            config.network.endpoint_url.as_deref(),
+            Some("http://localhost:9000")
+        );
+        assert_eq!(config.tags["team"], "growth");
+
+        // Variables naming no option are ignored, unknown file keys are errors
+        let stray = [("DRAINAGE_DEEP_SCNA".to_string(), "true".to_string())];
+        assert!(Defaults::from_sources(None, stray)
+            .unwrap()
+            .options
+            .is_empty());
+        assert_eq!(
+            Defaults::from_sources(Some("deep_scna = true"), []).unwrap_err(),
+            "Unknown analysis option: deep_scna"
+        );
+        let bad = [("DRAINAGE_MAX_CONCURRENCY".to_string(), "lots".to_string())];
+        assert!(Defaults::from_sources(None, bad)
+            .unwrap_err()
+            .starts_with("DRAINAGE_MAX_CONCURRENCY: "));
+    }
+}
@@ -0,0 +1,322 @@
+//! What-if simulation (`report.simulate`): the report's metrics and scores recomputed as if
+//! remediations had been applied, next to the remediation estimates of what they cost, so
+//! fixes can be ranked by score gained per core hour.
+//!
+//! `compaction` runs the compaction plan: each task's small files become its jobs' output
+//! files. `vacuum` deletes the unreferenced files past the in-flight grace period, and on
+//! Delta tables the tombstoned files too. `expire_snapshots` keeps a fifth of the size
+//! class's snapshot limit, retiring time travel storage, and on Iceberg tables deletes the
+//! tombstoned files. Scores are the built-in ones, without custom checks; recommendations
+//! are left as analyzed.
+
+use crate::types::{HealthMetrics, HealthReport, RemediationEstimate, Simulation, SizeClass};
+
+const ACTIONS: [&str; 3] = ["compaction", "vacuum", "expire_snapshots"];
+
+/// Whether `estimate` is the cost of `action` on a table of `table_type`
+fn covers(action: &str, estimate: &RemediationEstimate, table_type: &str) -> bool {
+    let delta = table_type == "delta";
+    match (action, estimate.action.as_str()) {
+        ("compaction", "Compact small files") => true,
+        ("vacuum", "Delete unreferenced files") => true,
+        ("vacuum", "Delete tombstoned files") => delta,
+        ("expire_snapshots", "Delete tombstoned files") => !delta,
+        _ => false,
+    }
+}
+
+/// Replace each compaction task's input files with its output files
+fn compact(metrics: &mut HealthMetrics) {
+    let buckets = metrics.file_size_buckets();
+    let Some(ref mut compaction) = metrics.file_compaction else {
+        return;
+    };
+    for task in std::mem::take(&mut compaction.compaction_plan) {
+        let inputs = task.input_file_count;
+        let jobs = task.estimated_job_count.max(1);
+        metrics.total_files = metrics.total_files + jobs - inputs;
+        metrics.file_size_distribution.small_files = metrics
+            .file_size_distribution
+            .small_files
+            .saturating_sub(inputs);
+        compaction.small_files_count = compaction.small_files_count.saturating_sub(inputs);
+        compaction.small_files_size_bytes = compaction
+            .small_files_size_bytes
+            .saturating_sub(task.input_size_bytes);
+        let output_size = task.expected_output_size_bytes / jobs as u64;
+        for _ in 0..jobs {
+            metrics.file_size_distribution.add(output_size, &buckets);
+            if output_size < buckets.small_bytes {
+                compaction.small_files_count += 1;
+                compaction.small_files_size_bytes += output_size;
+            }
+        }
+    }
+    compaction.potential_compaction_files = compaction.small_files_count;
+    compaction.estimated_compaction_savings_bytes = 0;
+    if metrics.total_files > 0 {
+        metrics.avg_file_size_bytes = metrics.total_size_bytes as f64 / metrics.total_files as f64;
+    }
+}
+
+/// Delete the unreferenced files, keeping those that may be in-flight writes
+fn delete_unreferenced(report: &mut HealthReport) {
+    let metrics = &mut report.metrics;
+    // The in-flight files are the most recent ones; files of unknown age never are
+    metrics
+        .unreferenced_files
+        .sort_by(|a, b| match (a.age_days, b.age_days) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+    metrics
+        .unreferenced_files
+        .truncate(metrics.in_flight_unreferenced_files);
+    metrics.unreferenced_size_bytes = metrics.in_flight_unreferenced_size_bytes;
+    if let Some(ref mut truncation) = report.result_truncation {
+        truncation.unreferenced_files_total = metrics.in_flight_unreferenced_files;
+        truncation.unreferenced_files_truncated = false;
+    }
+}
+
+fn delete_tombstoned(metrics: &mut HealthMetrics) {
+    if let Some(ref mut attribution) = metrics.file_attribution {
+        attribution.tombstoned_file_count = 0;
+        attribution.tombstoned_size_bytes = 0;
+    }
+}
+
+fn expire_snapshots(metrics: &mut HealthMetrics) {
+    let limit = metrics
+        .size_class
+        .as_ref()
+        .map_or(SizeClass::STANDARD.1, |class| class.snapshot_count_limit);
+    let kept = metrics.snapshot_health.snapshot_count.min(limit / 5);
+    metrics.calculate_snapshot_health(kept);
+    if let Some(ref mut time_travel) = metrics.time_travel_metrics {
+        let kept = time_travel.total_snapshots.min(limit / 5).max(1);
+        time_travel.total_historical_size_bytes =
+            (time_travel.avg_snapshot_size_bytes * kept as f64) as u64;
+        time_travel.total_snapshots = kept;
+        time_travel.oldest_snapshot_age_days = time_travel
+            .oldest_snapshot_age_days
+            .min(time_travel.recommended_retention_days as f64);
+        time_travel.storage_cost_impact_score = 0.0;
+        time_travel.retention_efficiency_score = 1.0;
+    }
+}
+
+/// Simulate applying `actions` (all of them when empty) to `report`
+pub fn simulate(report: &HealthReport, actions: &[String]) -> Result<Simulation, String> {
+    let actions: Vec<String> = match actions {
+        [] => ACTIONS.iter().map(|a| a.to_string()).collect(),
+        actions => actions.iter().map(|a| a.to_lowercase()).collect(),
+    };
+    if let Some(unknown) = actions.iter().find(|a| !ACTIONS.contains(&a.as_str())) {
+        return Err(format!(
+            "Unknown remediation: {} (expected 'compaction', 'vacuum' or 'expire_snapshots')",
+            unknown
+        ));
+    }
+    let delta = report.table_type == "delta";
+    let applies = |action: &str| actions.iter().any(|a| a == action);
+
+    let mut simulated = report.clone();
+    if applies("compaction") {
+        compact(&mut simulated.metrics);
+    }
+    if applies("vacuum") {
+        delete_unreferenced(&mut simulated);
+    }
+    if (applies("vacuum") && delta) || (applies("expire_snapshots") && !delta) {
+        delete_tombstoned(&mut simulated.metrics);
+    }
+    if applies("expire_snapshots") {
+        expire_snapshots(&mut simulated.metrics);
+    }
+
+    let (remediations, remaining): (Vec<RemediationEstimate>, Vec<RemediationEstimate>) =
+        std::mem::take(&mut simulated.metrics.remediation_estimates)
+            .into_iter()
+            .partition(|estimate| {
+                actions
+                    .iter()
+                    .any(|action| covers(action, estimate, &report.table_type))
+            });
+    simulated.metrics.remediation_estimates = remaining;
+    // The report's score may blend in custom checks, so move it by the change in the
+    // rescored metrics rather than replacing it
+    let baseline_health_score = report.health_score;
+    let rescored_improvement = simulated.calculate_health_score() - report.calculate_health_score();
+    simulated.health_score = (baseline_health_score + rescored_improvement).clamp(0.0, 1.0);
+    let sub_scores = simulated.calculate_sub_scores();
+    let metrics = &mut simulated.metrics;
+    metrics.health_score = simulated.health_score;
+    metrics.sub_scores = Some(sub_scores.clone());
+
+    Ok(Simulation {
+        actions,
+        baseline_health_score,
+        health_score: simulated.health_score,
+        score_improvement: simulated.health_score - baseline_health_score,
+        sub_scores,
+        estimated_core_hours: remediations.iter().map(|r| r.estimated_core_hours).sum(),
+        bytes_saved: remediations.iter().map(|r| r.bytes_saved).sum(),
+        remediations,
+        report: simulated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FileSizeBuckets;
+    use crate::types::{CompactionTask, FileCompactionMetrics, FileInfo};
+
+    const MB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_simulation_scores_each_remediation() {
+        let mut report = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        let metrics = &mut report.metrics;
+        metrics.total_files = 10;
+        metrics.total_size_bytes = 400 * MB;
+        metrics.file_size_distribution.small_files = 8;
+        metrics.file_size_distribution.medium_files = 2;
+        metrics.unreferenced_files = vec![FileInfo::new(
+            "old.parquet".to_string(),
+            MB,
+            None,
+            false,
+            chrono::Utc::now(),
+        )];
+        metrics.unreferenced_size_bytes = MB;
+        metrics.file_compaction = Some(FileCompactionMetrics {
+            compaction_opportunity_score: 1.0,
+            small_files_count: 8,
+            small_files_size_bytes: 80 * MB,
+            potential_compaction_files: 8,
+            estimated_compaction_savings_bytes: 0,
+            recommended_target_file_size_bytes: 128 * MB,
+            compaction_priority: "high".to_string(),
+            z_order_opportunity: false,
+            z_order_columns: Vec::new(),
+            z_order_candidates: Vec::new(),
+            compaction_plan: vec![CompactionTask {
+                partition: String::new(),
+                input_files: (0..8).map(|i| format!("part-{}.parquet", i)).collect(),
+                input_file_count: 8,
+                input_size_bytes: 80 * MB,
+                expected_output_size_bytes: 80 * MB,
+                estimated_job_count: 1,
+            }],
+        });
+        metrics.calculate_snapshot_health(150);
+        metrics.remediation_estimates = crate::remediation::estimate_remediations(
+            metrics,
+            "delta",
+            &crate::config::EngineProfile::default(),
+        );
+        report.health_score = report.calculate_health_score();
+
+        let compaction = simulate(&report, &["compaction".to_string()]).unwrap();
+        assert_eq!(compaction.baseline_health_score, report.health_score);
+        let simulated = &compaction.report.metrics;
+        assert_eq!(simulated.total_files, 3);
+        assert_eq!(simulated.file_size_distribution.small_files, 0);
+        assert_eq!(simulated.file_size_distribution.medium_files, 3);
+        assert!(compaction.score_improvement > 0.0);
+        assert_eq!(compaction.remediations[0].action, "Compact small files");
+        assert_eq!(simulated.remediation_estimates.len(), 1);
+
+        let vacuum = simulate(&report, &["vacuum".to_string()]).unwrap();
+        assert!(vacuum.report.metrics.unreferenced_files.is_empty());
+        assert_eq!(vacuum.bytes_saved, MB);
+
+        let expire = simulate(&report, &["expire_snapshots".to_string()]).unwrap();
+        let snapshots = &expire.report.metrics.snapshot_health;
+        assert_eq!(snapshots.snapshot_count, 20);
+        assert_eq!(snapshots.snapshot_retention_risk, 0.0);
+        assert!(expire.remediations.is_empty());
+
+        let all = simulate(&report, &[]).unwrap();
+        assert_eq!(all.actions, ACTIONS);
+        assert!(all.health_score > compaction.health_score);
+        assert!(simulate(&report, &["optimize".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_simulation_of_truncated_report() {
+        let mut report = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        report.metrics.total_files = 20;
+        report.metrics.unreferenced_files = (0..10)
+            .map(|i| {
+                let path = format!("old-{}.parquet", i);
+                FileInfo::new(path, MB, None, false, chrono::Utc::now())
+            })
+            .collect();
+        report.metrics.unreferenced_size_bytes = 10 * MB;
+        report.health_score = report.calculate_health_score();
+        let full = simulate(&report, &["vacuum".to_string()]).unwrap();
+
+        let mut limited = report.clone();
+        limited.apply_result_limits(&crate::config::ResultLimits {
+            max_unreferenced_files: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(limited.calculate_health_score(), report.health_score);
+        let vacuum = simulate(&limited, &["vacuum".to_string()]).unwrap();
+        assert_eq!(vacuum.baseline_health_score, report.health_score);
+        assert!(vacuum.score_improvement > 0.0);
+        assert!((vacuum.score_improvement - full.score_improvement).abs() < 1e-9);
+        assert_eq!(vacuum.health_score, full.health_score);
+    }
+
+    #[test]
+    fn test_simulated_compaction_counts_cut_file_lists() {
+        let mut report = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        let files: Vec<(String, u64)> = (0..8)
+            .map(|i| (format!("part-{}.parquet", i), 10 * MB))
+            .collect();
+        let paths: Vec<(&str, u64)> = files.iter().map(|(p, s)| (p.as_str(), *s)).collect();
+        report.metrics.total_files = 8;
+        report.metrics.file_size_distribution.small_files = 8;
+        report.metrics.file_compaction = Some(FileCompactionMetrics {
+            compaction_opportunity_score: 1.0,
+            small_files_count: 8,
+            small_files_size_bytes: 80 * MB,
+            potential_compaction_files: 8,
+            estimated_compaction_savings_bytes: 0,
+            recommended_target_file_size_bytes: 128 * MB,
+            compaction_priority: "high".to_string(),
+            z_order_opportunity: false,
+            z_order_columns: Vec::new(),
+            z_order_candidates: Vec::new(),
+            compaction_plan: CompactionTask::plan(&paths, &FileSizeBuckets::default()),
+        });
+        report.apply_result_limits(&crate::config::ResultLimits {
+            max_files_in_report: Some(2),
+            ..Default::default()
+        });
+        assert!(
+            report
+                .result_truncation
+                .as_ref()
+                .unwrap()
+                .compaction_files_truncated
+        );
+        let task = &report
+            .metrics
+            .file_compaction
+            .as_ref()
+            .unwrap()
+            .compaction_plan[0];
+        assert_eq!(task.input_files.len(), 2);
+        assert_eq!(task.input_file_count, 8);
+
+        let compaction = simulate(&report, &["compaction".to_string()]).unwrap();
+        let simulated = &compaction.report.metrics;
+        assert_eq!(simulated.total_files, 1);
+        assert_eq!(simulated.file_size_distribution.small_files, 0);
+    }
+}
@@ -0,0 +1,278 @@
+//! `analyze_stream`: analysis progress as an async iterator for asyncio applications.
+//!
+//! The analysis runs on its own thread and sends events through a channel. Each
+//! `__anext__` waits for the next event in the running event loop's default executor,
+//! so the loop stays free while the analysis works.
+
+use crate::config::AnalysisConfig;
+use crate::error::{DrainageError, ErrorContext};
+use crate::health_analyzer::TableTarget;
+use crate::types::{HealthReport, LogEntry};
+use pyo3::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct AnalysisEvent {
+    #[pyo3(get)]
+    pub kind: String, // "progress", "warning", "metric" or "report" (always last)
+    #[pyo3(get)]
+    pub stage: Option<String>, // progress events
+    #[pyo3(get)]
+    pub message: Option<String>, // warning events
+    #[pyo3(get)]
+    pub metric: Option<String>, // metric events, e.g. "total_files"
+    #[pyo3(get)]
+    pub value: Option<f64>,
+    #[pyo3(get)]
+    pub report: Option<HealthReport>, // report event
+}
+
+impl AnalysisEvent {
+    fn new(kind: &str) -> Self {
+        Self {
+            kind: kind.to_string(),
+            stage: None,
+            message: None,
+            metric: None,
+            value: None,
+            report: None,
+        }
+    }
+}
+
+#[pymethods]
+impl AnalysisEvent {
+    fn __repr__(&self) -> String {
+        match self.kind.as_str() {
+            "progress" => format!(
+                "AnalysisEvent(progress, {})",
+                self.stage.as_deref().unwrap_or("")
+            ),
+            "warning" => format!(
+                "AnalysisEvent(warning, {:?})",
+                self.message.as_deref().unwrap_or("")
+            ),
+            "metric" => format!(
+                "AnalysisEvent(metric, {}={})",
+                self.metric.as_deref().unwrap_or(""),
+                self.value.unwrap_or_default()
+            ),
+            kind => format!("AnalysisEvent({})", kind),
+        }
+    }
+}
+
+type StreamItem = Result<AnalysisEvent, DrainageError>;
+
+fn internal(message: &str) -> DrainageError {
+    DrainageError::Internal(ErrorContext::new(message))
+}
+/// When each stage started or warning was raised, its level and message
+type LogRecords = Arc<Mutex<Vec<(Instant, &'static str, String)>>>;
+
+/// Where analyzers report progress, warnings and metrics as soon as they are known.
+/// The default sink discards everything but the log records and the analysis log.
+#[derive(Debug, Clone, Default)]
+pub struct EventSink {
+    sender: Option<Sender<StreamItem>>,
+    phase: Arc<Mutex<Option<tracing::Span>>>, // span of the current stage
+    log: LogRecords,                          // stages and warnings, in order
+}
+
+impl EventSink {
+    // A closed stream (the consumer stopped iterating) doesn't stop the analysis
+    fn send(&self, event: AnalysisEvent) {
+        if let Some(ref sender) = self.sender {
+            let _ = sender.send(Ok(event));
+        }
+    }
+
+    fn record(&self, level: &'static str, message: &str) {
+        self.log.lock().unwrap_or_else(|e| e.into_inner()).push((
+            Instant::now(),
+            level,
+            message.to_string(),
+        ));
+    }
+
+    /// The stages and warnings so far, timed from the first; without timings for
+    /// deterministic reports
+    pub fn analysis_log(&self, timed: bool) -> Vec<LogEntry> {
+        let log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        let Some((started, _, _)) = log.first() else {
+            return Vec::new();
+        };
+        log.iter()
+            .map(|(time, level, message)| LogEntry {
+                elapsed_seconds: timed.then(|| time.duration_since(*started).as_secs_f64()),
+                level: level.to_string(),
+                message: message.clone(),
+            })
+            .collect()
+    }
+
+    pub fn progress(&self, stage: &str) {
+        // Replacing the previous stage's span closes it, which logs how long it took
+        let span = tracing::info_span!("phase", phase = stage);
+        let previous = self
+            .phase
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .replace(span);
+        drop(previous);
+        self.record("INFO", stage);
+        let mut event = AnalysisEvent::new("progress");
+        event.stage = Some(stage.to_string());
+        self.send(event);
+    }
+
+    /// The stage running now, the last one started
+    pub fn current_stage(&self) -> Option<String> {
+        let log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        log.iter()
+            .rev()
+            .find(|(_, level, _)| *level == "INFO")
+            .map(|(_, _, stage)| stage.clone())
+    }
+
+    /// Close the current stage's span, once the analysis is done
+    pub fn end_phase(&self) {
+        let current = self.phase.lock().unwrap_or_else(|e| e.into_inner()).take();
+        drop(current);
+    }
+
+    pub fn warning(&self, message: impl Into<String>) {
+        let message = crate::sanitize::sanitize(&message.into());
+        tracing::warn!("{}", message);
+        self.record("WARNING", &message);
+        let mut event = AnalysisEvent::new("warning");
+        event.message = Some(message);
+        self.send(event);
+    }
+
+    pub fn metric(&self, metric: &str, value: f64) {
+        tracing::debug!(metric, value);
+        let mut event = AnalysisEvent::new("metric");
+        event.metric = Some(metric.to_string());
+        event.value = Some(value);
+        self.send(event);
+    }
+}
+
+#[pyclass]
+pub struct AnalysisStream {
+    receiver: Mutex<Receiver<StreamItem>>,
+    finished: AtomicBool,
+}
+
+impl AnalysisStream {
+    /// Start analyzing `target` on a background thread
+    pub fn start(target: TableTarget, config: AnalysisConfig) -> Self {
+        let (sender, receiver) = channel();
+        std::thread::spawn(move || {
+            let events = EventSink {
+                sender: Some(sender.clone()),
+                ..EventSink::default()
+            };
+            let outcome = tokio::runtime::Runtime::new()
+                .map_err(anyhow::Error::from)
+                .and_then(|runtime| {
+                    events.progress("started");
+                    runtime.block_on(target.analyze_with_events(
+                        config,
+                        |stage| events.progress(stage),
+                        events.clone(),
+                    ))
+                });
+            let item = outcome
+                .map(|report| {
+                    let mut event = AnalysisEvent::new("report");
+                    event.report = Some(report);
+                    event
+                })
+                .map_err(|e| DrainageError::from_anyhow(e, None));
+            let _ = sender.send(item);
+        });
+
+        Self {
+            receiver: Mutex::new(receiver),
+            finished: AtomicBool::new(false),
+        }
+    }
+}
+
+#[pymethods]
+impl AnalysisStream {
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __anext__(slf: PyRef<'_, Self>, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+        let next_event = slf.into_py(py).getattr(py, "next_event")?;
+        let future = event_loop.call_method1("run_in_executor", (py.None(), next_event))?;
+        Ok(Some(future.into()))
+    }
+
+    /// Block until the next event; raises StopAsyncIteration after the report
+    fn next_event(&self, py: Python<'_>) -> PyResult<AnalysisEvent> {
+        if self.finished.load(Ordering::SeqCst) {
+            return Err(pyo3::exceptions::PyStopAsyncIteration::new_err(()));
+        }
+        let item = py.allow_threads(|| {
+            self.receiver
+                .lock()
+                .map_err(|_| internal("Analysis stream is poisoned"))
+                .and_then(|receiver| {
+                    receiver
+                        .recv()
+                        .unwrap_or_else(|_| Err(internal("Analysis thread exited")))
+                })
+        });
+        match item {
+            Ok(event) => {
+                if event.kind == "report" {
+                    self.finished.store(true, Ordering::SeqCst);
+                }
+                Ok(event)
+            }
+            Err(error) => {
+                self.finished.store(true, Ordering::SeqCst);
+                Err(error.into_py_err("Analysis failed"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_sink_sends_typed_events_in_order() {
+        let (sender, receiver) = channel();
+        let events = EventSink {
+            sender: Some(sender),
+            ..EventSink::default()
+        };
+        events.progress("listing");
+        events.metric("total_files", 42.0);
+        events.warning("footer unreadable");
+        drop(events);
+
+        let received: Vec<AnalysisEvent> = receiver.iter().map(Result::unwrap).collect();
+        let kinds: Vec<&str> = received.iter().map(|e| e.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["progress", "metric", "warning"]);
+        assert_eq!(received[0].stage.as_deref(), Some("listing"));
+        assert_eq!(received[1].metric.as_deref(), Some("total_files"));
+        assert_eq!(received[1].value, Some(42.0));
+        assert_eq!(received[2].message.as_deref(), Some("footer unreadable"));
+
+        // The default sink has nowhere to send and discards events
+        EventSink::default().progress("listing");
+    }
+}
@@ -0,0 +1,254 @@
+//! Table properties audit: the Delta `configuration` or Iceberg `properties` of the table
+//! compared with a built-in best-practice ruleset, with the statement fixing each finding.
+//!
+//! Rules only fire on values known to hurt (retention too short to be safe, sparse
+//! checkpoints, file size targets far from the usual range, too few commit retries), so a
+//! table left on the engine defaults gets no findings, with one exception: Iceberg keeps
+//! every old `metadata.json` unless told otherwise.
+
+use crate::types::{PropertyFinding, TablePropertiesMetrics};
+use serde_json::Value;
+use std::collections::HashMap;
+
+const MB: f64 = 1024.0 * 1024.0;
+const DAY_MS: f64 = 86_400_000.0;
+
+/// A best practice: `needs_change` is given the current value (None when unset)
+struct Rule {
+    property: &'static str,
+    recommended: &'static str,
+    needs_change: fn(Option<&str>) -> bool,
+    reason: &'static str,
+}
+
+/// Parse a size such as `128mb`, `1 GB` or `134217728` into bytes
+fn parse_size(text: &str) -> Option<f64> {
+    let text = text.trim().to_lowercase();
+    let digits = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let amount: f64 = text[..digits].parse().ok()?;
+    let unit = match text[digits..].trim() {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1024.0,
+        "m" | "mb" => MB,
+        "g" | "gb" => 1024.0 * MB,
+        _ => return None,
+    };
+    Some(amount * unit)
+}
+
+fn number(value: Option<&str>) -> Option<f64> {
+    value.and_then(|v| v.trim().parse().ok())
+}
+
+const DELTA_RULES: [Rule; 6] = [
+    Rule {
+        property: "delta.logRetentionDuration",
+        recommended: "interval 30 days",
+        needs_change: |v| {
+            v.and_then(crate::delta_lake::parse_interval_days)
+                .is_some_and(|days| days < 7.0)
+        },
+        reason: "Log retention under 7 days limits time travel and can break streaming readers that fall behind",
+    },
+    Rule {
+        property: "delta.deletedFileRetentionDuration",
+        recommended: "interval 7 days",
+        needs_change: |v| {
+            v.and_then(crate::delta_lake::parse_interval_days)
+                .is_some_and(|days| days < 7.0)
+        },
+        reason: "VACUUM can delete files that long-running queries and concurrent writers still read",
+    },
+    Rule {
+        property: "delta.checkpointInterval",
+        recommended: "10",
+        needs_change: |v| number(v).is_some_and(|n| n > 100.0),
+        reason: "Sparse checkpoints make every reader replay many JSON commits",
+    },
+    Rule {
+        property: "delta.targetFileSize",
+        recommended: "128mb",
+        needs_change: |v| {
+            v.and_then(parse_size)
+                .is_some_and(|bytes| !(16.0 * MB..=1024.0 * MB).contains(&bytes))
+        },
+        reason: "Targets far from 128 MB - 1 GB write many small files or files too large to split",
+    },
+    Rule {
+        property: "delta.checkpoint.writeStatsAsStruct",
+        recommended: "true",
+        needs_change: |v| v.is_some_and(|v| v.eq_ignore_ascii_case("false")),
+        reason: "Without struct stats in checkpoints, data skipping has to parse JSON stats",
+    },
+    Rule {
+        property: "delta.dataSkippingNumIndexedCols",
+        recommended: "32",
+        needs_change: |v| number(v).is_some_and(|n| n == 0.0 || n > 64.0),
+        reason: "Collecting stats on no columns disables data skipping, and on very many slows every write",
+    },
+];
+
+const ICEBERG_RULES: [Rule; 6] = [
+    Rule {
+        property: "write.target-file-size-bytes",
+        recommended: "536870912",
+        needs_change: |v| {
+            number(v).is_some_and(|bytes| !(16.0 * MB..=2048.0 * MB).contains(&bytes))
+        },
+        reason: "Targets far from 128 MB - 1 GB write many small files or files too large to split",
+    },
+    Rule {
+        property: "commit.retry.num-retries",
+        recommended: "4",
+        needs_change: |v| number(v).is_some_and(|n| n < 4.0),
+        reason: "Concurrent writers fail their commits after too few retries",
+    },
+    Rule {
+        property: "history.expire.max-snapshot-age-ms",
+        recommended: "432000000",
+        needs_change: |v| number(v).is_some_and(|ms| !(DAY_MS / 24.0..=30.0 * DAY_MS).contains(&ms)),
+        reason: "Snapshot expiration keeps more than 30 days of data files, or too little history for time travel and concurrent readers",
+    },
+    Rule {
+        property: "write.metadata.delete-after-commit.enabled",
+        recommended: "true",
+        needs_change: |v| !v.is_some_and(|v| v.eq_ignore_ascii_case("true")),
+        reason: "Every commit leaves its old metadata.json behind until this is enabled (with write.metadata.previous-versions-max, 100 by default)",
+    },
+    Rule {
+        property: "write.metadata.metrics.default",
+        recommended: "truncate(16)",
+        needs_change: |v| v.is_some_and(|v| v.eq_ignore_ascii_case("none")),
+        reason: "Without column metrics, queries can't skip files",
+    },
+    Rule {
+        property: "write.parquet.compression-codec",
+        recommended: "zstd",
+        needs_change: |v| {
+            v.is_some_and(|v| v.eq_ignore_ascii_case("uncompressed") || v.eq_ignore_ascii_case("none"))
+        },
+        reason: "Uncompressed Parquet stores several times the bytes",
+    },
+];
+
+fn set_statement(table: &str, properties: &[(&str, &str)]) -> String {
+    let assignments: Vec<String> = properties
+        .iter()
+        .map(|(property, value)| format!("'{}' = '{}'", property, value))
+        .collect();
+    format!(
+        "ALTER TABLE {} SET TBLPROPERTIES ({})",
+        table,
+        assignments.join(", ")
+    )
+}
+
+/// Audit `properties` (a Delta `configuration` or Iceberg `properties` object) of a table of
+/// `table_type`; statements name the table as `table`
+pub fn audit(table_type: &str, properties: Option<&Value>, table: &str) -> TablePropertiesMetrics {
+    let properties: HashMap<String, String> = properties
+        .and_then(Value::as_object)
+        .map(|object| {
+            object
+                .iter()
+                .map(|(key, value)| {
+                    let value = value.as_str().map_or(value.to_string(), str::to_string);
+                    (key.clone(), value)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let rules: &[Rule] = match table_type {
+        "delta" => &DELTA_RULES,
+        _ => &ICEBERG_RULES,
+    };
+
+    let findings: Vec<PropertyFinding> = rules
+        .iter()
+        .filter_map(|rule| {
+            let current = properties.get(rule.property).map(String::as_str);
+            (rule.needs_change)(current).then(|| PropertyFinding {
+                property: rule.property.to_string(),
+                current: current.map(str::to_string),
+                recommended: rule.recommended.to_string(),
+                reason: rule.reason.to_string(),
+                statement: set_statement(table, &[(rule.property, rule.recommended)]),
+            })
+        })
+        .collect();
+    let statement = (!findings.is_empty()).then(|| {
+        let all: Vec<(&str, &str)> = findings
+            .iter()
+            .map(|f| (f.property.as_str(), f.recommended.as_str()))
+            .collect();
+        set_statement(table, &all)
+    });
+
+    TablePropertiesMetrics {
+        properties,
+        findings,
+        statement,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_audit_flags_risky_properties() {
+        let delta = audit(
+            "delta",
+            Some(&json!({
+                "delta.logRetentionDuration": "interval 2 days",
+                "delta.deletedFileRetentionDuration": "interval 7 days",
+                "delta.targetFileSize": "4mb",
+                "delta.checkpointInterval": "10",
+            })),
+            "delta.`s3://lake/events`",
+        );
+        let flagged: Vec<&str> = delta.findings.iter().map(|f| f.property.as_str()).collect();
+        assert_eq!(
+            flagged,
+            ["delta.logRetentionDuration", "delta.targetFileSize"]
+        );
+        assert_eq!(delta.findings[1].current.as_deref(), Some("4mb"));
+        assert_eq!(
+            delta.statement.as_deref(),
+            Some("ALTER TABLE delta.`s3://lake/events` SET TBLPROPERTIES ('delta.logRetentionDuration' = 'interval 30 days', 'delta.targetFileSize' = '128mb')")
+        );
+        assert!(audit("delta", None, "t").findings.is_empty());
+
+        let iceberg = audit(
+            "iceberg",
+            Some(&json!({
+                "commit.retry.num-retries": "1",
+                "history.expire.max-snapshot-age-ms": "7776000000",
+                "write.metadata.delete-after-commit.enabled": "true",
+            })),
+            "...",
+        );
+        let flagged: Vec<&str> = iceberg
+            .findings
+            .iter()
+            .map(|f| f.property.as_str())
+            .collect();
+        assert_eq!(
+            flagged,
+            [
+                "commit.retry.num-retries",
+                "history.expire.max-snapshot-age-ms"
+            ]
+        );
+        // Defaults only miss metadata cleanup
+        let defaults = audit("iceberg", Some(&json!({})), "...");
+        assert_eq!(
+            defaults.findings[0].statement,
+            "ALTER TABLE ... SET TBLPROPERTIES ('write.metadata.delete-after-commit.enabled' = 'true')"
+        );
+        assert_eq!(parse_size("1 GB"), Some(1024.0 * MB));
+    }
+}
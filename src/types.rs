@@ -1,6 +1,7 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[pyclass]
@@ -10,9 +11,52 @@ pub struct FileInfo {
     #[pyo3(get)]
     pub size_bytes: u64,
     #[pyo3(get)]
-    pub last_modified: Option<String>,
+    pub last_modified: Option<DateTime<Utc>>,
     #[pyo3(get)]
     pub is_referenced: bool,
+    #[pyo3(get)]
+    pub created: Option<DateTime<Utc>>, // write time recorded in the table log, when available
+    #[pyo3(get)]
+    pub age_days: Option<f64>, // days from last_modified to the analysis time
+    #[pyo3(get)]
+    pub commit_id: Option<i64>, // Delta version / Iceberg snapshot id that added the file
+    #[pyo3(get)]
+    pub removed_commit_id: Option<i64>, // commit/snapshot that tombstoned the file, if any
+    #[pyo3(get)]
+    pub record_count: Option<u64>, // rows, from Delta stats / Iceberg manifest entries
+    #[pyo3(get)]
+    pub storage_class: Option<String>, // S3 storage class from the listing
+}
+
+impl FileInfo {
+    pub fn new(
+        path: String,
+        size_bytes: u64,
+        last_modified: Option<DateTime<Utc>>,
+        is_referenced: bool,
+        as_of: DateTime<Utc>,
+    ) -> Self {
+        let age_days =
+            last_modified.map(|modified| (as_of - modified).num_seconds().max(0) as f64 / 86400.0);
+        Self {
+            path,
+            size_bytes,
+            last_modified,
+            is_referenced,
+            created: None,
+            age_days,
+            commit_id: None,
+            removed_commit_id: None,
+            record_count: None,
+            storage_class: None,
+        }
+    }
+}
+
+/// Days from a commit at `timestamp_ms` (epoch milliseconds) to `as_of`. A commit after
+/// `as_of`, as with a fixed analysis clock, is zero days old.
+pub fn days_since_commit(as_of: DateTime<Utc>, timestamp_ms: u64) -> f64 {
+    (as_of.timestamp() - (timestamp_ms / 1000) as i64).max(0) as f64 / 86400.0
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +74,19 @@ pub struct PartitionInfo {
     pub files: Vec<FileInfo>,
 }
 
+impl PartitionInfo {
+    /// Hive-style path of the partition values, keys sorted: `day=2024-01-01/region=eu`
+    pub fn path(&self) -> String {
+        let mut values: Vec<_> = self.partition_values.iter().collect();
+        values.sort();
+        values
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[pyclass]
 pub struct ClusteringInfo {
@@ -55,6 +112,10 @@ pub struct HealthMetrics {
     #[pyo3(get)]
     pub unreferenced_size_bytes: u64,
     #[pyo3(get)]
+    pub in_flight_unreferenced_files: usize, // within the grace period, not penalized
+    #[pyo3(get)]
+    pub in_flight_unreferenced_size_bytes: u64,
+    #[pyo3(get)]
     pub partition_count: usize,
     #[pyo3(get)]
     pub partitions: Vec<PartitionInfo>,
@@ -84,19 +145,221 @@ pub struct HealthMetrics {
     pub table_constraints: Option<TableConstraintsMetrics>,
     #[pyo3(get)]
     pub file_compaction: Option<FileCompactionMetrics>,
+    #[pyo3(get)]
+    pub partition_spec_mismatch: Option<PartitionSpecMismatchMetrics>,
+    #[pyo3(get)]
+    pub table_features: Option<TableFeatures>,
+    #[pyo3(get)]
+    pub column_mapping: Option<ColumnMappingMetrics>,
+    #[pyo3(get)]
+    pub change_data_feed: Option<ChangeDataFeedMetrics>,
+    #[pyo3(get)]
+    pub file_attribution: Option<FileAttributionMetrics>,
+    #[pyo3(get)]
+    pub parquet_footers: Option<ParquetFooterMetrics>,
+    #[pyo3(get)]
+    pub orc_footers: Option<OrcFooterMetrics>,
+    #[pyo3(get)]
+    pub schema_drift: Option<SchemaDriftMetrics>,
+    #[pyo3(get)]
+    pub partition_anomalies: Option<PartitionAnomalies>,
+    #[pyo3(get)]
+    pub remediation_estimates: Vec<RemediationEstimate>,
+    #[pyo3(get)]
+    pub sub_scores: Option<HealthSubScores>,
+    #[pyo3(get)]
+    pub partitioning_advice: Option<PartitioningAdvice>,
+    #[pyo3(get)]
+    pub freshness: Option<FreshnessMetrics>,
+    #[pyo3(get)]
+    pub access_patterns: Option<AccessPatternMetrics>,
+    #[pyo3(get)]
+    pub storage_classes: Option<StorageClassMetrics>,
+    #[pyo3(get)]
+    pub log_consistency: Option<LogConsistencyMetrics>,
+    #[pyo3(get)]
+    pub cost: Option<CostMetrics>,
+    #[pyo3(get)]
+    pub existence_check: Option<ExistenceCheckMetrics>,
+    #[pyo3(get)]
+    pub incomplete_uploads: Option<IncompleteUploadMetrics>,
+    #[pyo3(get)]
+    pub partition_statistics: Option<PartitionStatisticsMetrics>,
+    #[pyo3(get)]
+    pub file_formats: Option<FileFormatMetrics>,
+    #[pyo3(get)]
+    pub engine_limits: Option<EngineLimitMetrics>,
+    #[pyo3(get)]
+    pub file_naming: Option<FileNamingMetrics>,
+    #[pyo3(get)]
+    pub file_integrity: Option<FileIntegrityMetrics>,
+    #[pyo3(get)]
+    pub duplicate_files: Option<DuplicateFileMetrics>,
+    #[pyo3(get)]
+    pub iceberg_migration: Option<IcebergMigrationMetrics>,
+    #[pyo3(get)]
+    pub format_version: Option<FormatVersionMetrics>,
+    #[pyo3(get)]
+    pub table_properties: Option<TablePropertiesMetrics>,
+    #[pyo3(get)]
+    pub delta_log: Option<DeltaLogMetrics>,
+    #[pyo3(get)]
+    pub time_travel_integrity: Option<TimeTravelIntegrityMetrics>, // Delta only
+    #[pyo3(get)]
+    pub file_size_histogram: Option<FileSizeHistogram>,
+    #[pyo3(get)]
+    pub size_class: Option<SizeClass>, // None with `adaptive_thresholds=False`
+}
+
+/// Dimension of table health a score deduction belongs to
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScoreDimension {
+    StorageCost,
+    QueryPerformance,
+    Reliability,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct HealthSubScores {
+    #[pyo3(get)]
+    pub storage_cost_score: f64, // orphaned data, snapshot/time travel retention, metadata size
+    #[pyo3(get)]
+    pub query_performance_score: f64, // file sizes, partitioning, skew, deletion vectors, compaction
+    #[pyo3(get)]
+    pub reliability_score: f64, // schema stability, constraints and data quality
+    #[pyo3(get)]
+    pub weakest_dimension: String, // "storage_cost", "query_performance" or "reliability"
 }
 
+/// Data files counted by the configured `FileSizeBuckets` bounds (`size_class` or
+/// `file_size_buckets_mb`; 16MB, 128MB and 1GB by default)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[pyclass]
 pub struct FileSizeDistribution {
     #[pyo3(get)]
-    pub small_files: usize, // < 16MB
+    pub small_files: usize, // below the small bound
+    #[pyo3(get)]
+    pub medium_files: usize, // from the small bound to the medium bound
+    #[pyo3(get)]
+    pub large_files: usize, // from the medium bound to the large bound
+    #[pyo3(get)]
+    pub very_large_files: usize, // at or above the large bound
+}
+
+impl FileSizeDistribution {
+    /// Count a file of `size` bytes in its bucket
+    pub fn add(&mut self, size: u64, buckets: &crate::config::FileSizeBuckets) {
+        if size < buckets.small_bytes {
+            self.small_files += 1;
+        } else if size < buckets.medium_bytes {
+            self.medium_files += 1;
+        } else if size < buckets.large_bytes {
+            self.large_files += 1;
+        } else {
+            self.very_large_files += 1;
+        }
+    }
+}
+
+const MIB: u64 = 1024 * 1024;
+const GIB: u64 = 1024 * MIB;
+const TIB: u64 = 1024 * GIB;
+
+/// The table's size class by live data size, and the thresholds it is judged by
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct SizeClass {
+    #[pyo3(get)]
+    pub size_class: String, // "tiny", "small", "medium", "large" or "huge"
+    #[pyo3(get)]
+    pub small_file_bytes: u64, // files below are small, unless `file_size_buckets_mb` is given
+    #[pyo3(get)]
+    pub snapshot_count_limit: usize, // high retention risk above; moderate above half, low above a fifth
+    #[pyo3(get)]
+    pub metadata_bloat_bytes: u64, // metadata above is penalized as bloat
+}
+
+impl SizeClass {
+    /// Thresholds of tables without a size class, those of medium tables
+    pub const STANDARD: (u64, usize, u64) = (16 * MIB, 100, 100 * MIB);
+
+    /// The class of a table holding `total_size_bytes` of live data. Small tables can't
+    /// avoid small files or modest metadata; huge ones commit often and carry large logs.
+    pub fn of(total_size_bytes: u64) -> Self {
+        let (size_class, small_file_bytes, snapshot_count_limit, metadata_bloat_bytes) =
+            match total_size_bytes {
+                size if size < GIB => ("tiny", MIB, 50, 10 * MIB),
+                size if size < 100 * GIB => ("small", 8 * MIB, 100, 50 * MIB),
+                size if size < 10 * TIB => (
+                    "medium",
+                    Self::STANDARD.0,
+                    Self::STANDARD.1,
+                    Self::STANDARD.2,
+                ),
+                size if size < 100 * TIB => ("large", 32 * MIB, 200, GIB),
+                _ => ("huge", 64 * MIB, 500, 10 * GIB),
+            };
+        Self {
+            size_class: size_class.to_string(),
+            small_file_bytes,
+            snapshot_count_limit,
+            metadata_bloat_bytes,
+        }
+    }
+}
+
+/// Data file size percentiles, with the bucket bounds `FileSizeDistribution` used
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct FileSizeHistogram {
+    #[pyo3(get)]
+    pub small_file_bytes: u64, // upper bound of the small bucket
+    #[pyo3(get)]
+    pub medium_file_bytes: u64,
+    #[pyo3(get)]
+    pub large_file_bytes: u64, // larger files are very large
+    #[pyo3(get)]
+    pub min_bytes: u64,
     #[pyo3(get)]
-    pub medium_files: usize, // 16MB - 128MB
+    pub p5_bytes: u64,
     #[pyo3(get)]
-    pub large_files: usize, // 128MB - 1GB
+    pub p25_bytes: u64,
     #[pyo3(get)]
-    pub very_large_files: usize, // > 1GB
+    pub p50_bytes: u64,
+    #[pyo3(get)]
+    pub p75_bytes: u64,
+    #[pyo3(get)]
+    pub p95_bytes: u64,
+    #[pyo3(get)]
+    pub max_bytes: u64,
+}
+
+impl FileSizeHistogram {
+    /// Percentiles of `sizes` in any order; None without files
+    pub fn from_sizes(
+        mut sizes: Vec<u64>,
+        buckets: &crate::config::FileSizeBuckets,
+    ) -> Option<Self> {
+        sizes.sort_unstable();
+        // Nearest-rank percentile
+        let percentile = |p: f64| {
+            let rank = ((p * sizes.len() as f64).ceil() as usize).max(1);
+            sizes[rank - 1]
+        };
+        Some(Self {
+            small_file_bytes: buckets.small_bytes,
+            medium_file_bytes: buckets.medium_bytes,
+            large_file_bytes: buckets.large_bytes,
+            min_bytes: *sizes.first()?,
+            p5_bytes: percentile(0.05),
+            p25_bytes: percentile(0.25),
+            p50_bytes: percentile(0.5),
+            p75_bytes: percentile(0.75),
+            p95_bytes: percentile(0.95),
+            max_bytes: *sizes.last()?,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -114,6 +377,58 @@ pub struct DataSkewMetrics {
     pub avg_partition_size: u64,
     #[pyo3(get)]
     pub partition_size_std_dev: f64,
+    #[pyo3(get)]
+    pub partition_row_skew_score: f64, // 0.0 (perfect) to 1.0 (highly skewed), by row count
+    #[pyo3(get)]
+    pub file_row_skew_score: f64, // 0.0 (perfect) to 1.0 (highly skewed), by row count
+    #[pyo3(get)]
+    pub largest_partition_rows: u64,
+    #[pyo3(get)]
+    pub smallest_partition_rows: u64,
+    #[pyo3(get)]
+    pub avg_partition_rows: u64,
+    #[pyo3(get)]
+    pub files_with_row_counts: usize, // row skew only covers files with known counts
+}
+
+impl DataSkewMetrics {
+    /// Flags row-count skew, calling out tables that look balanced by bytes alone
+    pub fn row_skew_recommendation(&self) -> Option<String> {
+        if self.partition_row_skew_score <= 0.5 {
+            return None;
+        }
+        let ratio = if self.smallest_partition_rows > 0 {
+            format!(
+                "{:.0}x",
+                self.largest_partition_rows as f64 / self.smallest_partition_rows as f64
+            )
+        } else {
+            "vastly".to_string()
+        };
+        Some(if self.partition_skew_score <= 0.5 {
+            format!(
+                "Partitions are balanced by size but the largest holds {} more rows than the smallest ({} vs {}), usually from compression differences. Size-based compaction won't fix this; rebalance tasks or repartition by row count.",
+                ratio, self.largest_partition_rows, self.smallest_partition_rows
+            )
+        } else {
+            format!(
+                "High partition row-count skew detected: the largest partition holds {} more rows than the smallest ({} vs {}). Consider repartitioning to balance data distribution.",
+                ratio, self.largest_partition_rows, self.smallest_partition_rows
+            )
+        })
+    }
+}
+
+/// Mean, standard deviation and coefficient of variation (capped at 1.0) of `values`
+fn spread(values: &[f64]) -> (f64, f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0, 0.0);
+    }
+    let avg = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - avg).powi(2)).sum::<f64>() / values.len() as f64;
+    let std_dev = variance.sqrt();
+    let coefficient_of_variation = if avg > 0.0 { std_dev / avg } else { 0.0 };
+    (avg, std_dev, coefficient_of_variation.min(1.0))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -154,144 +469,708 @@ pub struct HealthReport {
     #[pyo3(get)]
     pub table_type: String, // "delta" or "iceberg"
     #[pyo3(get)]
-    pub analysis_timestamp: String,
+    pub analysis_timestamp: DateTime<Utc>,
     #[pyo3(get)]
     pub metrics: HealthMetrics,
     #[pyo3(get)]
     pub health_score: f64, // 0.0 to 1.0
+    #[pyo3(get)]
+    pub current_schema: Option<TableSchema>,
+    #[pyo3(get)]
+    pub partition_filter: Option<HashMap<String, String>>, // set when the analysis was scoped
+    #[pyo3(get)]
+    pub tags: Option<HashMap<String, String>>, // user labels given with `tags=`
+    #[pyo3(get)]
+    pub warm_start: Option<WarmStart>,
+    #[pyo3(get)]
+    pub analysis_stats: Option<AnalysisStats>,
+    #[pyo3(get)]
+    pub inventory: Option<InventorySource>, // set when data files came from an S3 Inventory report
+    #[pyo3(get)]
+    pub truncated: Option<Truncation>, // set when `max_duration_seconds` cut the analysis short
+    #[pyo3(get)]
+    pub result_truncation: Option<ResultTruncation>, // set when result limits were given
+    #[pyo3(get)]
+    pub failed_phases: Option<Vec<PhaseFailure>>, // optional phases skipped under `allow_partial=True`
+    #[pyo3(get)]
+    pub analysis_log: Option<Vec<LogEntry>>, // stages and warnings of the analysis, in order
+    #[pyo3(get)]
+    pub history_regressions: Option<Vec<HistoryRegression>>, // set by `detect_regressions`
+    #[pyo3(get)]
+    pub custom_checks: Option<Vec<CustomCheckResult>>, // set when checks are registered
+    #[pyo3(get)]
+    pub compliance: Option<ComplianceReport>, // set when a `policy` was given
 }
 
-impl Default for HealthMetrics {
-    fn default() -> Self {
-        Self::new()
-    }
+/// How the table measures up to the rules of a `policy`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ComplianceReport {
+    #[pyo3(get)]
+    pub policy: Option<String>, // the policy's name
+    #[pyo3(get)]
+    pub compliant: bool, // no `error` rule failed
+    #[pyo3(get)]
+    pub errors: usize, // failed `error` rules
+    #[pyo3(get)]
+    pub warnings: usize, // failed `warning` rules
+    #[pyo3(get)]
+    pub rules: Vec<PolicyRuleResult>,
 }
 
-impl HealthMetrics {
-    pub fn new() -> Self {
-        Self {
-            total_files: 0,
-            total_size_bytes: 0,
-            unreferenced_files: Vec::new(),
-            unreferenced_size_bytes: 0,
-            partition_count: 0,
-            partitions: Vec::new(),
-            clustering: None,
-            avg_file_size_bytes: 0.0,
-            file_size_distribution: FileSizeDistribution {
-                small_files: 0,
-                medium_files: 0,
-                large_files: 0,
-                very_large_files: 0,
-            },
-            recommendations: Vec::new(),
-            health_score: 0.0,
-            data_skew: DataSkewMetrics {
-                partition_skew_score: 0.0,
-                file_size_skew_score: 0.0,
-                largest_partition_size: 0,
-                smallest_partition_size: 0,
-                avg_partition_size: 0,
-                partition_size_std_dev: 0.0,
-            },
-            metadata_health: MetadataHealth {
-                metadata_file_count: 0,
-                metadata_total_size_bytes: 0,
-                avg_metadata_file_size: 0.0,
-                metadata_growth_rate: 0.0,
-                manifest_file_count: 0,
-            },
-            snapshot_health: SnapshotHealth {
-                snapshot_count: 0,
-                oldest_snapshot_age_days: 0.0,
-                newest_snapshot_age_days: 0.0,
-                avg_snapshot_age_days: 0.0,
-                snapshot_retention_risk: 0.0,
-            },
-            deletion_vector_metrics: None,
-            schema_evolution: None,
-            time_travel_metrics: None,
-            table_constraints: None,
-            file_compaction: None,
-        }
-    }
-
-    pub fn calculate_health_score(&self) -> f64 {
-        let mut score = 1.0;
-
-        // Penalize unreferenced files
-        if self.total_files > 0 {
-            let unreferenced_ratio = self.unreferenced_files.len() as f64 / self.total_files as f64;
-            score -= unreferenced_ratio * 0.3;
-        }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct PolicyRuleResult {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub rule: String, // e.g. "small_file_ratio < 0.2"
+    #[pyo3(get)]
+    pub severity: String, // "error" or "warning"
+    #[pyo3(get)]
+    pub value: Option<f64>, // None when the analysis didn't produce the metric
+    #[pyo3(get)]
+    pub passed: Option<bool>,
+    #[pyo3(get)]
+    pub description: Option<String>,
+}
 
-        // Penalize small files (inefficient)
-        if self.total_files > 0 {
-            let small_file_ratio =
-                self.file_size_distribution.small_files as f64 / self.total_files as f64;
-            score -= small_file_ratio * 0.2;
-        }
+/// What a check registered with `register_check` found
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct CustomCheckResult {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub weight: f64, // in the health score, against 1.0 for the built-in score
+    #[pyo3(get)]
+    pub score: f64, // 1.0 without findings
+    #[pyo3(get)]
+    pub findings: Vec<String>,
+}
 
-        // Penalize very large files (potential performance issues)
-        if self.total_files > 0 {
-            let very_large_ratio =
-                self.file_size_distribution.very_large_files as f64 / self.total_files as f64;
-            score -= very_large_ratio * 0.1;
-        }
+/// A metric that deteriorated significantly against the table's recorded history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct HistoryRegression {
+    #[pyo3(get)]
+    pub metric: String, // "health_score", "orphan_bytes" or "small_file_ratio"
+    #[pyo3(get)]
+    pub severity: String, // "high"
+    #[pyo3(get)]
+    pub current: f64,
+    #[pyo3(get)]
+    pub previous: f64, // at the analysis compared with, about a week earlier
+    #[pyo3(get)]
+    pub previous_timestamp: DateTime<Utc>,
+    #[pyo3(get)]
+    pub baseline_mean: f64, // over every earlier analysis
+    #[pyo3(get)]
+    pub z_score: Option<f64>, // None with fewer than 3 earlier analyses or no variation
+    #[pyo3(get)]
+    pub message: String,
+}
 
-        // Reward good partitioning
-        if self.partition_count > 0 && self.total_files > 0 {
-            let avg_files_per_partition = self.total_files as f64 / self.partition_count as f64;
-            if avg_files_per_partition > 100.0 {
-                score -= 0.1; // Too many files per partition
-            } else if avg_files_per_partition < 5.0 {
-                score -= 0.05; // Too few files per partition
-            }
-        }
+/// A stage the analysis started or a warning it raised
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct LogEntry {
+    #[pyo3(get)]
+    pub elapsed_seconds: Option<f64>, // since the analysis started; None in deterministic reports
+    #[pyo3(get)]
+    pub level: String, // "INFO" for stages, "WARNING"
+    #[pyo3(get)]
+    pub message: String, // the stage name or warning
+}
 
-        // Penalize data skew
-        score -= self.data_skew.partition_skew_score * 0.15;
-        score -= self.data_skew.file_size_skew_score * 0.1;
+/// An optional analysis phase that failed and was skipped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct PhaseFailure {
+    #[pyo3(get)]
+    pub phase: String, // e.g. "schema_evolution"
+    #[pyo3(get)]
+    pub error: String,
+}
 
-        // Penalize metadata bloat
-        if self.metadata_health.metadata_total_size_bytes > 100 * 1024 * 1024 {
-            // > 100MB
-            score -= 0.05;
-        }
+/// Stages of an analysis that the `max_duration_seconds` deadline cut short or skipped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct Truncation {
+    #[pyo3(get)]
+    pub max_duration_seconds: f64,
+    #[pyo3(get)]
+    pub stages: Vec<String>, // progress stage names, e.g. "listing", "scanning_footers"
+}
 
-        // Penalize snapshot retention issues
-        score -= self.snapshot_health.snapshot_retention_risk * 0.1;
+/// Lists of a report cut to the result limits (`max_unreferenced_files`, `max_partitions`,
+/// `max_recommendations`, `max_files_in_report`, `include_file_lists`), with their full
+/// lengths
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ResultTruncation {
+    #[pyo3(get)]
+    pub truncated: bool, // any list was cut
+    #[pyo3(get)]
+    pub unreferenced_files_total: usize,
+    #[pyo3(get)]
+    pub unreferenced_files_truncated: bool, // the largest files are kept
+    #[pyo3(get)]
+    pub partitions_total: usize,
+    #[pyo3(get)]
+    pub partitions_truncated: bool, // the largest partitions are kept
+    #[pyo3(get)]
+    pub recommendations_total: usize,
+    #[pyo3(get)]
+    pub recommendations_truncated: bool, // the first recommendations are kept
+    #[pyo3(get)]
+    pub partition_files_total: usize, // in `partitions[].files`, over every partition
+    #[pyo3(get)]
+    pub partition_files_truncated: bool, // the largest files of each partition are kept
+    #[pyo3(get)]
+    pub unreferenced_files_omitted: usize,
+    #[pyo3(get)]
+    pub partition_files_omitted: usize, // including the files of partitions left out
+    #[pyo3(get)]
+    pub compaction_files_truncated: bool, // some compaction task's `input_files` were cut
+}
+
+/// The S3 Inventory delivery an analysis took its data files from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct InventorySource {
+    #[pyo3(get)]
+    pub manifest_path: String,
+    #[pyo3(get)]
+    pub file_format: String, // "CSV" or "Parquet"
+    #[pyo3(get)]
+    pub created_at: Option<DateTime<Utc>>, // files written since then are missing from the analysis
+    #[pyo3(get)]
+    pub data_objects: usize,
+}
+
+/// The format of the table under a path (`detect_table_type`), with the files it was told by
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct TableDetection {
+    #[pyo3(get)]
+    pub table_type: String, // "delta", "iceberg", "hudi", "parquet", "ambiguous" or "unknown"
+    #[pyo3(get)]
+    pub evidence: Vec<String>, // e.g. "_delta_log/00000000000000000000.json: Delta Lake transaction log"
+    #[pyo3(get)]
+    pub uniform: bool, // a Delta table whose Iceberg metadata Delta UniForm writes
+}
+
+impl TableDetection {
+    /// The analyzer to use, "delta" or "iceberg", or why the table can't be analyzed
+    pub fn analyzable(&self) -> Result<&'static str, String> {
+        match self.table_type.as_str() {
+            "delta" => Ok("delta"),
+            "iceberg" => Ok("iceberg"),
+            "ambiguous" => Err("Ambiguous table type: both Delta Lake and Iceberg files detected (e.g. a Delta UniForm or converted table). Please specify table_type or prefer_table_type explicitly.".to_string()),
+            "hudi" => Err("Apache Hudi tables (.hoodie/) are not supported. Supported types: 'delta', 'iceberg'".to_string()),
+            "parquet" => Err("Found Parquet files but no Delta Lake (_delta_log) or Iceberg (metadata.json) metadata; plain Parquet directories are not supported. Please specify table_type explicitly if the metadata lives elsewhere, or add detection_signatures.".to_string()),
+            _ => Err("Could not determine table type. No Delta Lake (_delta_log) or Iceberg (metadata.json) files found. Please specify table_type explicitly, or add detection_signatures.".to_string()),
+        }
+    }
+}
+
+/// What the analysis itself cost: the S3 requests it made, including format detection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct AnalysisStats {
+    #[pyo3(get)]
+    pub list_requests: u64,
+    #[pyo3(get)]
+    pub get_requests: u64,
+    #[pyo3(get)]
+    pub head_requests: u64,
+    #[pyo3(get)]
+    pub bytes_downloaded: u64, // object bodies read by GET requests
+    #[pyo3(get)]
+    pub cached_gets: u64, // metadata reads served by the analysis's cache, not counted above
+    #[pyo3(get)]
+    pub duration_seconds: f64,
+    #[pyo3(get)]
+    pub estimated_request_cost: f64, // at the configured pricing's request prices
+    #[pyo3(get)]
+    pub currency: String, // of the estimate, e.g. "USD"
+}
+
+impl AnalysisStats {
+    pub fn new(
+        requests: &crate::s3_client::RequestCounts,
+        duration: std::time::Duration,
+        pricing: &crate::config::PricingProfile,
+    ) -> Self {
+        let (list_requests, get_requests, head_requests, bytes_downloaded) = requests.snapshot();
+        Self {
+            list_requests,
+            get_requests,
+            head_requests,
+            bytes_downloaded,
+            cached_gets: requests.cached_gets(),
+            duration_seconds: duration.as_secs_f64(),
+            estimated_request_cost: list_requests as f64 / 1000.0 * pricing.list_per_1000
+                + (get_requests + head_requests) as f64 / 1000.0 * pricing.get_per_1000,
+            currency: pricing.currency.clone(),
+        }
+    }
+}
+
+/// Listing state a later analysis of the same table can start from, and how much of the
+/// previous report this analysis reused
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct WarmStart {
+    #[pyo3(get)]
+    pub partition_digests: HashMap<String, String>, // directory -> digest of its live files per the table metadata
+    #[pyo3(get)]
+    pub used_previous_report: bool,
+    #[pyo3(get)]
+    pub reused_partitions: usize, // unchanged directories taken from the previous report
+    #[pyo3(get)]
+    pub relisted_partitions: usize,
+    #[pyo3(get)]
+    pub reused_footer_sample: bool,
+}
+
+/// Outcome of one table in a namespace crawl: a report, or the error that stopped it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct TableAnalysis {
+    #[pyo3(get)]
+    pub table: String, // namespace.table
+    #[pyo3(get)]
+    pub report: Option<HealthReport>,
+    #[pyo3(get)]
+    pub error: Option<String>,
+    #[pyo3(get)]
+    pub error_code: Option<String>, // `DrainageError.code` of the failure
+}
+
+/// Totals across the tables of a namespace crawl
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct FleetSummary {
+    #[pyo3(get)]
+    pub table_count: usize,
+    #[pyo3(get)]
+    pub analyzed_count: usize,
+    #[pyo3(get)]
+    pub failed_count: usize,
+    #[pyo3(get)]
+    pub avg_health_score: f64, // over analyzed tables
+    #[pyo3(get)]
+    pub min_health_score: f64,
+    #[pyo3(get)]
+    pub total_files: usize,
+    #[pyo3(get)]
+    pub total_size_bytes: u64,
+    #[pyo3(get)]
+    pub total_unreferenced_size_bytes: u64,
+    #[pyo3(get)]
+    pub lowest_scoring_tables: Vec<String>, // up to 5, worst first
+    #[pyo3(get)]
+    pub redundant_size_bytes: u64, // in tables that look like copies of another
+    #[pyo3(get)]
+    pub redundant_monthly_cost: f64, // USD
+    #[pyo3(get)]
+    pub cached_gets: u64, // metadata reads served by the crawl's shared cache
+    #[pyo3(get)]
+    pub cache_hit_ratio: f64, // of all metadata and data GETs
+}
+
+/// Tables of a namespace crawl that look like copies of one table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct DuplicateTableGroup {
+    #[pyo3(get)]
+    pub schema_fingerprint: String,
+    #[pyo3(get)]
+    pub original_table: String, // the largest, kept
+    #[pyo3(get)]
+    pub duplicate_tables: Vec<String>,
+    #[pyo3(get)]
+    pub min_similarity: f64, // lowest share of the original's live files a copy has
+    #[pyo3(get)]
+    pub redundant_size_bytes: u64, // in the duplicates
+    #[pyo3(get)]
+    pub redundant_monthly_cost: f64, // USD, of the duplicates
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct NamespaceReport {
+    #[pyo3(get)]
+    pub pattern: String,
+    #[pyo3(get)]
+    pub tables: Vec<TableAnalysis>,
+    #[pyo3(get)]
+    pub summary: FleetSummary,
+    #[pyo3(get)]
+    pub duplicates: Vec<DuplicateTableGroup>, // most redundant bytes first
+    #[pyo3(get)]
+    pub interrupted: bool, // cancelled early; only the tables finished by then are included
+}
+
+impl NamespaceReport {
+    pub fn new(pattern: String, tables: Vec<TableAnalysis>) -> Self {
+        let mut scored: Vec<(&str, &HealthReport)> = tables
+            .iter()
+            .filter_map(|t| t.report.as_ref().map(|r| (t.table.as_str(), r)))
+            .collect();
+        scored.sort_by(|a, b| a.1.health_score.total_cmp(&b.1.health_score));
+
+        let analyzed_count = scored.len();
+        let duplicates = crate::duplicates::find_duplicates(&tables);
+        let stats = || scored.iter().filter_map(|(_, r)| r.analysis_stats.as_ref());
+        let cached_gets = stats().map(|s| s.cached_gets).sum::<u64>();
+        let get_requests = stats().map(|s| s.get_requests).sum::<u64>();
+        let summary = FleetSummary {
+            table_count: tables.len(),
+            analyzed_count,
+            failed_count: tables.len() - analyzed_count,
+            avg_health_score: if analyzed_count > 0 {
+                scored.iter().map(|(_, r)| r.health_score).sum::<f64>() / analyzed_count as f64
+            } else {
+                0.0
+            },
+            min_health_score: scored.first().map(|(_, r)| r.health_score).unwrap_or(0.0),
+            total_files: scored.iter().map(|(_, r)| r.metrics.total_files).sum(),
+            total_size_bytes: scored.iter().map(|(_, r)| r.metrics.total_size_bytes).sum(),
+            total_unreferenced_size_bytes: scored
+                .iter()
+                .map(|(_, r)| r.metrics.unreferenced_size_bytes)
+                .sum(),
+            lowest_scoring_tables: scored
+                .iter()
+                .take(5)
+                .map(|(table, _)| table.to_string())
+                .collect(),
+            redundant_size_bytes: duplicates.iter().map(|d| d.redundant_size_bytes).sum(),
+            redundant_monthly_cost: duplicates.iter().map(|d| d.redundant_monthly_cost).sum(),
+            cached_gets,
+            cache_hit_ratio: match cached_gets + get_requests {
+                0 => 0.0,
+                total => cached_gets as f64 / total as f64,
+            },
+        };
+
+        Self {
+            pattern,
+            tables,
+            summary,
+            duplicates,
+            interrupted: false,
+        }
+    }
+}
+
+impl Default for HealthMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthMetrics {
+    pub fn new() -> Self {
+        Self {
+            total_files: 0,
+            total_size_bytes: 0,
+            unreferenced_files: Vec::new(),
+            unreferenced_size_bytes: 0,
+            in_flight_unreferenced_files: 0,
+            in_flight_unreferenced_size_bytes: 0,
+            partition_count: 0,
+            partitions: Vec::new(),
+            clustering: None,
+            avg_file_size_bytes: 0.0,
+            file_size_distribution: FileSizeDistribution {
+                small_files: 0,
+                medium_files: 0,
+                large_files: 0,
+                very_large_files: 0,
+            },
+            recommendations: Vec::new(),
+            health_score: 0.0,
+            data_skew: DataSkewMetrics {
+                partition_skew_score: 0.0,
+                file_size_skew_score: 0.0,
+                largest_partition_size: 0,
+                smallest_partition_size: 0,
+                avg_partition_size: 0,
+                partition_size_std_dev: 0.0,
+                partition_row_skew_score: 0.0,
+                file_row_skew_score: 0.0,
+                largest_partition_rows: 0,
+                smallest_partition_rows: 0,
+                avg_partition_rows: 0,
+                files_with_row_counts: 0,
+            },
+            metadata_health: MetadataHealth {
+                metadata_file_count: 0,
+                metadata_total_size_bytes: 0,
+                avg_metadata_file_size: 0.0,
+                metadata_growth_rate: 0.0,
+                manifest_file_count: 0,
+            },
+            snapshot_health: SnapshotHealth {
+                snapshot_count: 0,
+                oldest_snapshot_age_days: 0.0,
+                newest_snapshot_age_days: 0.0,
+                avg_snapshot_age_days: 0.0,
+                snapshot_retention_risk: 0.0,
+            },
+            deletion_vector_metrics: None,
+            schema_evolution: None,
+            time_travel_metrics: None,
+            table_constraints: None,
+            file_compaction: None,
+            partition_spec_mismatch: None,
+            table_features: None,
+            column_mapping: None,
+            change_data_feed: None,
+            file_attribution: None,
+            parquet_footers: None,
+            orc_footers: None,
+            schema_drift: None,
+            partition_anomalies: None,
+            remediation_estimates: Vec::new(),
+            sub_scores: None,
+            partitioning_advice: None,
+            freshness: None,
+            access_patterns: None,
+            storage_classes: None,
+            log_consistency: None,
+            cost: None,
+            existence_check: None,
+            incomplete_uploads: None,
+            partition_statistics: None,
+            file_formats: None,
+            engine_limits: None,
+            file_naming: None,
+            file_integrity: None,
+            duplicate_files: None,
+            iceberg_migration: None,
+            format_version: None,
+            table_properties: None,
+            delta_log: None,
+            time_travel_integrity: None,
+            file_size_histogram: None,
+            size_class: None,
+        }
+    }
+
+    /// Score deductions, each tagged with the dimension of table health it affects, for a
+    /// table with `unreferenced_files` unreferenced files
+    fn score_penalties(&self, unreferenced_files: usize) -> Vec<(ScoreDimension, f64)> {
+        use ScoreDimension::*;
+        let mut penalties = Vec::new();
+
+        // Penalize unreferenced files, except recent ones that may be in-flight writes
+        if self.total_files > 0 {
+            let stale_unreferenced =
+                unreferenced_files.saturating_sub(self.in_flight_unreferenced_files);
+            let unreferenced_ratio = stale_unreferenced as f64 / self.total_files as f64;
+            penalties.push((StorageCost, unreferenced_ratio * 0.3));
+        }
+
+        // Penalize small files (inefficient)
+        if self.total_files > 0 {
+            let small_file_ratio =
+                self.file_size_distribution.small_files as f64 / self.total_files as f64;
+            penalties.push((QueryPerformance, small_file_ratio * 0.2));
+        }
+
+        // Penalize very large files (potential performance issues)
+        if self.total_files > 0 {
+            let very_large_ratio =
+                self.file_size_distribution.very_large_files as f64 / self.total_files as f64;
+            penalties.push((QueryPerformance, very_large_ratio * 0.1));
+        }
+
+        // Reward good partitioning
+        if self.partition_count > 0 && self.total_files > 0 {
+            let avg_files_per_partition = self.total_files as f64 / self.partition_count as f64;
+            if avg_files_per_partition > 100.0 {
+                penalties.push((QueryPerformance, 0.1)); // Too many files per partition
+            } else if avg_files_per_partition < 5.0 {
+                penalties.push((QueryPerformance, 0.05)); // Too few files per partition
+            }
+        }
+
+        // Penalize data skew
+        penalties.push((QueryPerformance, self.data_skew.partition_skew_score * 0.15));
+        penalties.push((QueryPerformance, self.data_skew.file_size_skew_score * 0.1));
+
+        // Penalize metadata bloat
+        if self.metadata_health.metadata_total_size_bytes > self.metadata_bloat_bytes() {
+            penalties.push((StorageCost, 0.05));
+        }
+
+        // Penalize a Delta log grown so far that every reader spends long replaying it
+        if self
+            .delta_log
+            .as_ref()
+            .is_some_and(|log| log.is_pathological)
+        {
+            penalties.push((QueryPerformance, 0.1));
+        }
+
+        // Penalize snapshot retention issues
+        penalties.push((
+            StorageCost,
+            self.snapshot_health.snapshot_retention_risk * 0.1,
+        ));
 
         // Penalize deletion vector impact
         if let Some(ref dv_metrics) = self.deletion_vector_metrics {
-            score -= dv_metrics.deletion_vector_impact_score * 0.15;
+            penalties.push((
+                QueryPerformance,
+                dv_metrics.deletion_vector_impact_score * 0.15,
+            ));
         }
 
         // Factor in schema stability
         if let Some(ref schema_metrics) = self.schema_evolution {
-            score -= (1.0 - schema_metrics.schema_stability_score) * 0.2;
+            penalties.push((
+                Reliability,
+                (1.0 - schema_metrics.schema_stability_score) * 0.2,
+            ));
         }
 
         // Factor in time travel storage costs
         if let Some(ref tt_metrics) = self.time_travel_metrics {
-            score -= tt_metrics.storage_cost_impact_score * 0.1;
-            score -= (1.0 - tt_metrics.retention_efficiency_score) * 0.05;
+            penalties.push((StorageCost, tt_metrics.storage_cost_impact_score * 0.1));
+            penalties.push((
+                StorageCost,
+                (1.0 - tt_metrics.retention_efficiency_score) * 0.05,
+            ));
         }
 
         // Factor in data quality from constraints
         if let Some(ref constraint_metrics) = self.table_constraints {
-            score -= (1.0 - constraint_metrics.data_quality_score) * 0.15;
-            score -= constraint_metrics.constraint_violation_risk * 0.1;
+            penalties.push((
+                Reliability,
+                (1.0 - constraint_metrics.data_quality_score) * 0.15,
+            ));
+            penalties.push((
+                Reliability,
+                constraint_metrics.constraint_violation_risk * 0.1,
+            ));
+        }
+
+        // Live files whose size disagrees with the log point to corrupted commits
+        if let Some(ref log_consistency) = self.log_consistency {
+            if log_consistency.size_mismatched_files > 0 {
+                penalties.push((Reliability, 0.2));
+            }
+        }
+
+        // Referenced files that are gone break every query that reads them
+        if let Some(ref existence_check) = self.existence_check {
+            if existence_check.missing_files > 0 {
+                penalties.push((Reliability, 0.3));
+            }
+        }
+
+        // Committed files too small to hold a footer fail every query that reads them
+        if let Some(ref file_integrity) = self.file_integrity {
+            if file_integrity.referenced_files() > 0 {
+                penalties.push((Reliability, 0.2));
+            }
+        }
+
+        // Content committed twice is read twice
+        if let Some(ref duplicate_files) = self.duplicate_files {
+            if duplicate_files.live_duplicate_groups > 0 {
+                penalties.push((Reliability, 0.15));
+            }
+        }
+
+        // Live files in an archive class can't be read until restored
+        if let Some(ref storage_classes) = self.storage_classes {
+            if storage_classes.archived_live_files > 0 && !storage_classes.all_archived_readable() {
+                penalties.push((Reliability, 0.2));
+            }
         }
 
         // Factor in file compaction opportunities
         if let Some(ref compaction_metrics) = self.file_compaction {
-            score -= (1.0 - compaction_metrics.compaction_opportunity_score) * 0.1;
+            penalties.push((
+                QueryPerformance,
+                (1.0 - compaction_metrics.compaction_opportunity_score) * 0.1,
+            ));
         }
 
+        penalties
+    }
+
+    /// The file size buckets the analysis used, the defaults for reports without a histogram
+    pub fn file_size_buckets(&self) -> crate::config::FileSizeBuckets {
+        self.file_size_histogram.as_ref().map_or_else(
+            crate::config::FileSizeBuckets::default,
+            |histogram| crate::config::FileSizeBuckets {
+                small_bytes: histogram.small_file_bytes,
+                medium_bytes: histogram.medium_file_bytes,
+                large_bytes: histogram.large_file_bytes,
+            },
+        )
+    }
+
+    pub fn calculate_health_score(&self) -> f64 {
+        self.health_score_with(self.unreferenced_files.len())
+    }
+
+    /// The health score counting `unreferenced_files` unreferenced files rather than the
+    /// length of `unreferenced_files`, which the result limits may have cut
+    pub fn health_score_with(&self, unreferenced_files: usize) -> f64 {
+        let score = self
+            .score_penalties(unreferenced_files)
+            .iter()
+            .fold(1.0, |score, (_, penalty)| score - penalty);
         score.clamp(0.0, 1.0)
     }
 
+    /// Storage cost, query performance and reliability scores, each made of the
+    /// deductions in `calculate_health_score` that concern that dimension
+    pub fn calculate_sub_scores(&self) -> HealthSubScores {
+        self.sub_scores_with(self.unreferenced_files.len())
+    }
+
+    /// The sub-scores counting `unreferenced_files` unreferenced files
+    pub fn sub_scores_with(&self, unreferenced_files: usize) -> HealthSubScores {
+        let penalties = self.score_penalties(unreferenced_files);
+        let score = |dimension: ScoreDimension| {
+            penalties
+                .iter()
+                .filter(|(d, _)| *d == dimension)
+                .fold(1.0, |score, (_, penalty)| score - penalty)
+                .clamp(0.0, 1.0)
+        };
+        let storage_cost_score = score(ScoreDimension::StorageCost);
+        let query_performance_score = score(ScoreDimension::QueryPerformance);
+        let reliability_score = score(ScoreDimension::Reliability);
+
+        let weakest_dimension = [
+            ("storage_cost", storage_cost_score),
+            ("query_performance", query_performance_score),
+            ("reliability", reliability_score),
+        ]
+        .into_iter()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_default();
+
+        HealthSubScores {
+            storage_cost_score,
+            query_performance_score,
+            reliability_score,
+            weakest_dimension,
+        }
+    }
+
     pub fn calculate_data_skew(&mut self) {
         if self.partitions.is_empty() {
             return;
@@ -346,6 +1225,46 @@ impl HealthMetrics {
 
             self.data_skew.file_size_skew_score = coefficient_of_variation.min(1.0);
         }
+
+        self.calculate_row_skew();
+    }
+
+    /// Row-count skew from per-file record counts. Tables can be balanced by bytes yet
+    /// heavily skewed by rows when compression differs between partitions.
+    fn calculate_row_skew(&mut self) {
+        let file_rows: Vec<f64> = self
+            .partitions
+            .iter()
+            .flat_map(|p| p.files.iter().filter_map(|f| f.record_count))
+            .map(|rows| rows as f64)
+            .collect();
+        self.data_skew.files_with_row_counts = file_rows.len();
+        if file_rows.is_empty() {
+            return;
+        }
+        self.data_skew.file_row_skew_score = spread(&file_rows).2;
+
+        // Only partitions whose files all have counts, so partial sums don't look like skew
+        let partition_rows: Vec<u64> = self
+            .partitions
+            .iter()
+            .filter_map(|p| p.files.iter().map(|f| f.record_count).sum::<Option<u64>>())
+            .collect();
+        self.apply_partition_row_counts(&partition_rows);
+    }
+
+    /// Partition row skew from authoritative per-partition row counts (current Iceberg
+    /// partition statistics) instead of summed per-file counts
+    pub fn apply_partition_row_counts(&mut self, partition_rows: &[u64]) {
+        if partition_rows.is_empty() {
+            return;
+        }
+        let values: Vec<f64> = partition_rows.iter().map(|&rows| rows as f64).collect();
+        let (avg_rows, _, skew) = spread(&values);
+        self.data_skew.partition_row_skew_score = skew;
+        self.data_skew.largest_partition_rows = partition_rows.iter().copied().max().unwrap_or(0);
+        self.data_skew.smallest_partition_rows = partition_rows.iter().copied().min().unwrap_or(0);
+        self.data_skew.avg_partition_rows = avg_rows as u64;
     }
 
     pub fn calculate_metadata_health(&mut self, metadata_files: &[crate::s3_client::ObjectInfo]) {
@@ -362,6 +1281,13 @@ impl HealthMetrics {
         self.metadata_health.metadata_growth_rate = 0.0; // Placeholder
     }
 
+    /// Metadata size above which the table's metadata counts as bloated
+    pub fn metadata_bloat_bytes(&self) -> u64 {
+        self.size_class
+            .as_ref()
+            .map_or(SizeClass::STANDARD.2, |class| class.metadata_bloat_bytes)
+    }
+
     pub fn calculate_snapshot_health(&mut self, snapshot_count: usize) {
         self.snapshot_health.snapshot_count = snapshot_count;
 
@@ -370,23 +1296,404 @@ impl HealthMetrics {
         self.snapshot_health.newest_snapshot_age_days = 0.0;
         self.snapshot_health.avg_snapshot_age_days = 0.0;
 
-        // Calculate retention risk based on snapshot count
-        if snapshot_count > 100 {
+        // Calculate retention risk based on snapshot count, against the size class's limit
+        let limit = self
+            .size_class
+            .as_ref()
+            .map_or(SizeClass::STANDARD.1, |class| class.snapshot_count_limit);
+        if snapshot_count > limit {
             self.snapshot_health.snapshot_retention_risk = 0.8;
-        } else if snapshot_count > 50 {
+        } else if snapshot_count > limit / 2 {
             self.snapshot_health.snapshot_retention_risk = 0.5;
-        } else if snapshot_count > 20 {
+        } else if snapshot_count > limit / 5 {
             self.snapshot_health.snapshot_retention_risk = 0.2;
         } else {
             self.snapshot_health.snapshot_retention_risk = 0.0;
         }
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[pyclass]
-pub struct DeletionVectorMetrics {
-    #[pyo3(get)]
+    pub fn calculate_in_flight_unreferenced(&mut self, grace_period_hours: f64) {
+        // Files of unknown age can't be shown to be recent, so they stay penalized
+        let in_flight: Vec<&FileInfo> = self
+            .unreferenced_files
+            .iter()
+            .filter(|f| {
+                f.age_days
+                    .is_some_and(|age| age * 24.0 < grace_period_hours)
+            })
+            .collect();
+        self.in_flight_unreferenced_files = in_flight.len();
+        self.in_flight_unreferenced_size_bytes = in_flight.iter().map(|f| f.size_bytes).sum();
+    }
+
+    pub fn calculate_file_attribution(&mut self) {
+        let files: Vec<&FileInfo> = self
+            .partitions
+            .iter()
+            .flat_map(|partition| partition.files.iter())
+            .collect();
+        if files.is_empty() {
+            self.file_attribution = None;
+            return;
+        }
+
+        let mut attribution = FileAttributionMetrics {
+            bytes_added_per_day: BTreeMap::new(),
+            files_added_per_day: BTreeMap::new(),
+            attributed_file_count: 0,
+            unattributed_file_count: 0,
+            tombstoned_file_count: 0,
+            tombstoned_size_bytes: 0,
+        };
+
+        for file in files {
+            if file.commit_id.is_some() {
+                attribution.attributed_file_count += 1;
+            } else {
+                attribution.unattributed_file_count += 1;
+            }
+            if let Some(created) = file.created {
+                let day = created.format("%Y-%m-%d").to_string();
+                *attribution
+                    .bytes_added_per_day
+                    .entry(day.clone())
+                    .or_insert(0) += file.size_bytes;
+                *attribution.files_added_per_day.entry(day).or_insert(0) += 1;
+            }
+            if file.removed_commit_id.is_some() {
+                attribution.tombstoned_file_count += 1;
+                attribution.tombstoned_size_bytes += file.size_bytes;
+            }
+        }
+
+        self.file_attribution = Some(attribution);
+    }
+
+    /// Compare the schema registered in a catalog with the table's physical schema and the
+    /// columns of the sampled Parquet footers
+    pub fn calculate_schema_drift(&mut self, catalog: &TableSchema, physical: &TableSchema) {
+        let file_columns: Vec<String> = self
+            .parquet_footers
+            .iter()
+            .flat_map(|footers| footers.columns.iter().map(|c| c.column.clone()))
+            .collect();
+        self.schema_drift = Some(SchemaDriftMetrics::between(
+            catalog,
+            physical,
+            &file_columns,
+        ));
+    }
+
+    pub fn calculate_partition_spec_mismatch(
+        &mut self,
+        data_files: &[&crate::s3_client::ObjectInfo],
+        table_prefix: &str,
+        partition_columns: &[String],
+    ) {
+        let mut offending_paths = Vec::new();
+        let mut mismatched_size_bytes = 0u64;
+
+        for file in data_files {
+            let relative = crate::s3_client::relative_key(table_prefix, &file.key);
+
+            // Only directory segments count towards the partition depth
+            let mut segments: Vec<&str> = relative.split('/').collect();
+            segments.pop();
+            let path_columns: Vec<&str> = segments
+                .iter()
+                .filter_map(|segment| segment.split_once('=').map(|(k, _)| k))
+                .collect();
+
+            let matches_spec = path_columns.len() == partition_columns.len()
+                && path_columns
+                    .iter()
+                    .zip(partition_columns)
+                    .all(|(path_col, spec_col)| *path_col == spec_col.as_str());
+
+            if !matches_spec {
+                offending_paths.push(file.key.clone());
+                mismatched_size_bytes += file.size as u64;
+            }
+        }
+
+        if offending_paths.is_empty() {
+            self.partition_spec_mismatch = None;
+            return;
+        }
+
+        self.partition_spec_mismatch = Some(PartitionSpecMismatchMetrics {
+            expected_partition_columns: partition_columns.to_vec(),
+            expected_depth: partition_columns.len(),
+            mismatched_file_count: offending_paths.len(),
+            mismatched_size_bytes,
+            offending_paths,
+        });
+    }
+
+    /// Check date-style partitions for gaps and future dates, and find null partitions.
+    /// Left unset for tables with neither a date partition column nor null partitions.
+    pub fn calculate_partition_anomalies(&mut self, analysis_time: DateTime<Utc>) {
+        let is_null = |value: &str| value == HIVE_DEFAULT_PARTITION || value == "null";
+
+        let mut null_partitions = BTreeSet::new();
+        let mut null_partition_file_count = 0;
+        let mut null_partition_size_bytes = 0;
+        for partition in &self.partitions {
+            let nulls: Vec<String> = partition
+                .partition_values
+                .iter()
+                .filter(|(_, value)| is_null(value))
+                .map(|(column, value)| format!("{}={}", column, value))
+                .collect();
+            if !nulls.is_empty() {
+                null_partitions.extend(nulls);
+                null_partition_file_count += partition.file_count;
+                null_partition_size_bytes += partition.total_size_bytes;
+            }
+        }
+
+        // A date column holds only date values (besides nulls); otherwise fall back to
+        // Hive-style year/month[/day] columns
+        let mut columns: Vec<&String> = self
+            .partitions
+            .iter()
+            .flat_map(|p| p.partition_values.keys())
+            .collect();
+        columns.sort();
+        columns.dedup();
+        let parse_column = |column: &str| -> Option<(DateGranularity, BTreeSet<NaiveDate>)> {
+            let mut granularity = None;
+            let mut dates = BTreeSet::new();
+            for partition in &self.partitions {
+                let Some(value) = partition.partition_values.get(column) else {
+                    continue;
+                };
+                if is_null(value) {
+                    continue;
+                }
+                let (date, value_granularity) = parse_partition_date(value)?;
+                if *granularity.get_or_insert(value_granularity) != value_granularity {
+                    return None;
+                }
+                dates.insert(date);
+            }
+            Some((granularity?, dates))
+        };
+        let date_partitions = columns
+            .iter()
+            .find_map(|column| parse_column(column).map(|parsed| (column.to_string(), parsed)))
+            .or_else(|| self.hierarchical_partition_dates());
+
+        if date_partitions.is_none() && null_partitions.is_empty() {
+            self.partition_anomalies = None;
+            return;
+        }
+
+        let mut anomalies = PartitionAnomalies {
+            date_column: None,
+            granularity: None,
+            first_partition_date: None,
+            last_partition_date: None,
+            missing_partition_count: 0,
+            missing_partitions: Vec::new(),
+            longest_gap: 0,
+            future_partitions: Vec::new(),
+            null_partitions: null_partitions.into_iter().collect(),
+            null_partition_file_count,
+            null_partition_size_bytes,
+        };
+
+        if let Some((column, (granularity, dates))) = date_partitions {
+            let today = analysis_time.date_naive();
+            let current_period = granularity.period_start(today);
+            let (past, future): (Vec<NaiveDate>, Vec<NaiveDate>) =
+                dates.into_iter().partition(|date| *date <= current_period);
+            anomalies.future_partitions = future.iter().map(|d| granularity.format(*d)).collect();
+
+            // Future dates are reported on their own rather than stretching the range
+            if let (Some(first), Some(last)) = (past.first(), past.last()) {
+                let present: BTreeSet<&NaiveDate> = past.iter().collect();
+                let mut gap = 0;
+                let mut period = *first;
+                while period < *last {
+                    if present.contains(&period) {
+                        gap = 0;
+                    } else {
+                        anomalies.missing_partition_count += 1;
+                        if anomalies.missing_partitions.len() < MAX_LISTED_MISSING_PARTITIONS {
+                            anomalies
+                                .missing_partitions
+                                .push(granularity.format(period));
+                        }
+                        gap += 1;
+                        anomalies.longest_gap = anomalies.longest_gap.max(gap);
+                    }
+                    period = granularity.next(period);
+                }
+                anomalies.first_partition_date = Some(granularity.format(*first));
+                anomalies.last_partition_date = Some(granularity.format(*last));
+            }
+            anomalies.date_column = Some(column);
+            anomalies.granularity = Some(granularity.name().to_string());
+        }
+
+        self.partition_anomalies = Some(anomalies);
+    }
+
+    /// Judge whether the partition layout is too fine or too coarse for the data volume,
+    /// and which partition columns to drop (or candidates to add) to fix it
+    pub fn calculate_partitioning_advice(&mut self) {
+        if self.total_files == 0 || self.partitions.is_empty() {
+            self.partitioning_advice = None;
+            return;
+        }
+
+        let mut column_cardinality: HashMap<String, usize> = HashMap::new();
+        for partition in &self.partitions {
+            for column in partition.partition_values.keys() {
+                column_cardinality.entry(column.clone()).or_insert(0);
+            }
+        }
+        for (column, cardinality) in column_cardinality.iter_mut() {
+            let values: std::collections::HashSet<Option<&String>> = self
+                .partitions
+                .iter()
+                .map(|p| p.partition_values.get(column))
+                .collect();
+            *cardinality = values.len();
+        }
+        let mut partition_columns: Vec<String> = column_cardinality.keys().cloned().collect();
+        partition_columns.sort();
+
+        // Partitions left if the table were partitioned by `columns` only
+        let partitions_by = |columns: &[String]| -> usize {
+            let keys: std::collections::HashSet<Vec<Option<&String>>> = self
+                .partitions
+                .iter()
+                .map(|p| columns.iter().map(|c| p.partition_values.get(c)).collect())
+                .collect();
+            keys.len()
+        };
+
+        let partition_count = self.partitions.len();
+        let avg_partition_size_bytes = self.total_size_bytes as f64 / partition_count as f64;
+        let avg_files_per_partition = self.total_files as f64 / partition_count as f64;
+
+        let mut advice = PartitioningAdvice {
+            verdict: "balanced".to_string(),
+            partition_count,
+            avg_files_per_partition,
+            avg_partition_size_bytes,
+            column_cardinality,
+            columns_to_drop: Vec::new(),
+            suggested_partition_columns: partition_columns.clone(),
+            projected_partition_count: partition_count,
+            projected_avg_partition_size_bytes: avg_partition_size_bytes,
+            candidate_columns_to_add: Vec::new(),
+        };
+
+        if !partition_columns.is_empty()
+            && partition_count > 1
+            && avg_partition_size_bytes < OVER_PARTITIONED_MAX_AVG_BYTES
+        {
+            advice.verdict = "over_partitioned".to_string();
+            // Drop the highest-cardinality columns until partitions reach the target size
+            let mut remaining = partition_columns;
+            while !remaining.is_empty() {
+                let projected = partitions_by(&remaining).max(1);
+                if self.total_size_bytes as f64 / projected as f64 >= TARGET_PARTITION_BYTES {
+                    break;
+                }
+                let (index, _) = remaining
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(_, column)| advice.column_cardinality[*column])
+                    .expect("remaining columns are not empty");
+                advice.columns_to_drop.push(remaining.remove(index));
+            }
+            advice.projected_partition_count = partitions_by(&remaining).max(1);
+            advice.suggested_partition_columns = remaining;
+        } else if avg_partition_size_bytes > UNDER_PARTITIONED_MIN_AVG_BYTES
+            && avg_files_per_partition > UNDER_PARTITIONED_MIN_FILES
+        {
+            advice.verdict = "under_partitioned".to_string();
+            // Clustering columns are what queries filter on; low-cardinality ones partition well
+            advice.candidate_columns_to_add = self
+                .clustering
+                .as_ref()
+                .map(|c| c.clustering_columns.clone())
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|column| !advice.column_cardinality.contains_key(column))
+                .collect();
+            let target_count = (self.total_size_bytes as f64 / TARGET_PARTITION_BYTES).ceil();
+            advice.projected_partition_count = target_count as usize;
+        }
+        advice.projected_avg_partition_size_bytes =
+            self.total_size_bytes as f64 / advice.projected_partition_count.max(1) as f64;
+
+        self.partitioning_advice = Some(advice);
+    }
+
+    /// Dates from separate `year`, `month` and optional `day` partition columns
+    fn hierarchical_partition_dates(
+        &self,
+    ) -> Option<(String, (DateGranularity, BTreeSet<NaiveDate>))> {
+        let value = |partition: &PartitionInfo, name: &str| -> Option<u32> {
+            partition
+                .partition_values
+                .iter()
+                .find(|(column, _)| column.eq_ignore_ascii_case(name))
+                .and_then(|(_, value)| value.parse().ok())
+        };
+        let has_day = self.partitions.iter().any(|p| value(p, "day").is_some());
+        let mut dates = BTreeSet::new();
+        for partition in &self.partitions {
+            let year = value(partition, "year")?;
+            let month = value(partition, "month")?;
+            let day = if has_day { value(partition, "day")? } else { 1 };
+            dates.insert(NaiveDate::from_ymd_opt(year as i32, month, day)?);
+        }
+        if dates.is_empty() {
+            return None;
+        }
+        let (column, granularity) = if has_day {
+            ("year/month/day", DateGranularity::Day)
+        } else {
+            ("year/month", DateGranularity::Month)
+        };
+        Some((column.to_string(), (granularity, dates)))
+    }
+}
+
+#[pymethods]
+impl HealthMetrics {
+    /// Files whose recorded write time falls within `[start, end)`; either bound may be omitted
+    #[pyo3(signature = (start=None, end=None))]
+    pub fn files_added_between(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Vec<FileInfo> {
+        self.partitions
+            .iter()
+            .flat_map(|partition| partition.files.iter())
+            .filter(|file| match file.created {
+                Some(created) => {
+                    start.is_none_or(|start| created >= start)
+                        && end.is_none_or(|end| created < end)
+                }
+                None => false,
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct DeletionVectorMetrics {
+    #[pyo3(get)]
     pub deletion_vector_count: usize,
     #[pyo3(get)]
     pub total_deletion_vector_size_bytes: u64,
@@ -417,6 +1724,208 @@ pub struct SchemaEvolutionMetrics {
     pub schema_change_frequency: f64, // changes per day
     #[pyo3(get)]
     pub current_schema_version: u64,
+    #[pyo3(get)]
+    pub schema_changes: Vec<SchemaChange>, // column-level change log, oldest first
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct SchemaChange {
+    #[pyo3(get)]
+    pub version: i64, // Delta commit version / Iceberg schema id
+    #[pyo3(get)]
+    pub timestamp: Option<DateTime<Utc>>,
+    #[pyo3(get)]
+    pub change_type: String, // "added", "dropped", "renamed", "retyped" or "nullability"
+    #[pyo3(get)]
+    pub column: String,
+    #[pyo3(get)]
+    pub previous: Option<String>, // old name, type or nullability
+    #[pyo3(get)]
+    pub current: Option<String>,
+    #[pyo3(get)]
+    pub is_breaking: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ColumnInfo {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub data_type: String, // primitive name, or the JSON type definition for nested types
+    #[pyo3(get)]
+    pub nullable: bool,
+    #[pyo3(get)]
+    pub field_id: Option<i64>, // Iceberg field id / Delta column mapping id
+}
+
+impl ColumnInfo {
+    /// Read a Delta (`nullable`, column mapping id) or Iceberg (`required`, `id`) field
+    pub fn from_field(field: &serde_json::Value) -> Option<Self> {
+        let name = field.get("name")?.as_str()?.to_string();
+        let field_id = field.get("id").and_then(|id| id.as_i64()).or_else(|| {
+            field
+                .get("metadata")
+                .and_then(|m| m.get("delta.columnMapping.id"))
+                .and_then(|id| id.as_i64())
+        });
+        let data_type = match field.get("type") {
+            Some(serde_json::Value::String(t)) => t.clone(),
+            Some(other) => other.to_string(),
+            None => "unknown".to_string(),
+        };
+        let nullable = match field.get("required").and_then(|r| r.as_bool()) {
+            Some(required) => !required,
+            None => field
+                .get("nullable")
+                .and_then(|n| n.as_bool())
+                .unwrap_or(true),
+        };
+        Some(Self {
+            name,
+            data_type,
+            nullable,
+            field_id,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct TableSchema {
+    #[pyo3(get)]
+    pub schema_id: Option<i64>, // Iceberg current-schema-id; Delta has none
+    #[pyo3(get)]
+    pub columns: Vec<ColumnInfo>,
+    #[pyo3(get)]
+    pub partition_columns: Vec<String>,
+}
+
+impl TableSchema {
+    pub fn from_fields(
+        schema_id: Option<i64>,
+        fields: &[serde_json::Value],
+        partition_columns: Vec<String>,
+    ) -> Self {
+        Self {
+            schema_id,
+            columns: fields.iter().filter_map(ColumnInfo::from_field).collect(),
+            partition_columns,
+        }
+    }
+}
+
+#[pymethods]
+impl TableSchema {
+    /// Stable hash of the column names and types, in order, and the partition columns.
+    /// Field ids and nullability are left out, so copies made with CTAS fingerprint the same.
+    pub fn fingerprint(&self) -> String {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        for column in &self.columns {
+            hasher.update(column.name.to_lowercase().as_bytes());
+            hasher.update(b":");
+            hasher.update(column.data_type.as_bytes());
+            hasher.update(b"\n");
+        }
+        hasher.update(b"partitioned by\n");
+        for column in &self.partition_columns {
+            hasher.update(column.to_lowercase().as_bytes());
+            hasher.update(b"\n");
+        }
+        hex::encode(hasher.finalize())[..16].to_string()
+    }
+}
+
+impl SchemaChange {
+    /// Column-level differences between two schemas' `fields` arrays. Columns are matched
+    /// by field id when both schemas carry one, so renames aren't reported as drop + add.
+    pub fn between(
+        version: i64,
+        timestamp: Option<DateTime<Utc>>,
+        old_fields: &[serde_json::Value],
+        new_fields: &[serde_json::Value],
+    ) -> Vec<SchemaChange> {
+        let old_columns: Vec<ColumnInfo> = old_fields
+            .iter()
+            .filter_map(ColumnInfo::from_field)
+            .collect();
+        let new_columns: Vec<ColumnInfo> = new_fields
+            .iter()
+            .filter_map(ColumnInfo::from_field)
+            .collect();
+        let same_column = |a: &ColumnInfo, b: &ColumnInfo| match (a.field_id, b.field_id) {
+            (Some(a_id), Some(b_id)) => a_id == b_id,
+            _ => a.name == b.name,
+        };
+        let change = |change_type: &str,
+                      column: &str,
+                      previous: Option<String>,
+                      current: Option<String>,
+                      is_breaking: bool| SchemaChange {
+            version,
+            timestamp,
+            change_type: change_type.to_string(),
+            column: column.to_string(),
+            previous,
+            current,
+            is_breaking,
+        };
+
+        let mut changes = Vec::new();
+        for old in &old_columns {
+            match new_columns.iter().find(|new| same_column(old, new)) {
+                None => changes.push(change("dropped", &old.name, None, None, true)),
+                Some(new) => {
+                    if old.name != new.name {
+                        changes.push(change(
+                            "renamed",
+                            &new.name,
+                            Some(old.name.clone()),
+                            Some(new.name.clone()),
+                            true,
+                        ));
+                    }
+                    if old.data_type != new.data_type {
+                        changes.push(change(
+                            "retyped",
+                            &new.name,
+                            Some(old.data_type.clone()),
+                            Some(new.data_type.clone()),
+                            true,
+                        ));
+                    }
+                    if old.nullable != new.nullable {
+                        let label = |nullable: bool| {
+                            if nullable { "nullable" } else { "required" }.to_string()
+                        };
+                        // Relaxing a required column breaks readers that assume non-null values
+                        changes.push(change(
+                            "nullability",
+                            &new.name,
+                            Some(label(old.nullable)),
+                            Some(label(new.nullable)),
+                            new.nullable,
+                        ));
+                    }
+                }
+            }
+        }
+        for new in &new_columns {
+            if !old_columns.iter().any(|old| same_column(old, new)) {
+                changes.push(change(
+                    "added",
+                    &new.name,
+                    None,
+                    Some(new.data_type.clone()),
+                    false,
+                ));
+            }
+        }
+
+        changes
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -438,6 +1947,10 @@ pub struct TimeTravelMetrics {
     pub retention_efficiency_score: f64, // 0.0 = inefficient, 1.0 = very efficient
     #[pyo3(get)]
     pub recommended_retention_days: u64,
+    #[pyo3(get)]
+    pub oldest_snapshot_timestamp: Option<DateTime<Utc>>,
+    #[pyo3(get)]
+    pub newest_snapshot_timestamp: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -481,17 +1994,2656 @@ pub struct FileCompactionMetrics {
     #[pyo3(get)]
     pub z_order_opportunity: bool,
     #[pyo3(get)]
-    pub z_order_columns: Vec<String>,
+    pub z_order_columns: Vec<String>, // best candidates, else the declared clustering columns
+    #[pyo3(get)]
+    pub z_order_candidates: Vec<ZOrderCandidate>, // best first, from file statistics
+    #[pyo3(get)]
+    pub compaction_plan: Vec<CompactionTask>, // largest tasks first
+}
+
+/// A column Z-ordering or clustering would help, with the file statistics behind it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ZOrderCandidate {
+    #[pyo3(get)]
+    pub column: String, // nested Delta columns as `parent.child`
+    #[pyo3(get)]
+    pub files_with_stats: usize,
+    #[pyo3(get)]
+    pub distinct_bound_values: usize, // distinct file mins and maxes, a cardinality estimate
+    #[pyo3(get)]
+    pub file_overlap_ratio: f64, // 0.0 = file ranges are disjoint, 1.0 = every file overlaps every other
+    #[pyo3(get)]
+    pub score: f64, // overlap weighted by cardinality
+}
+
+/// A rewrite of one partition's small files into files of the target size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct CompactionTask {
+    #[pyo3(get)]
+    pub partition: String, // "k=v/..." directories, "" for an unpartitioned table
+    #[pyo3(get)]
+    pub input_files: Vec<String>, // table-relative paths
+    #[pyo3(get)]
+    pub input_file_count: usize, // before result limits cut `input_files`
+    #[pyo3(get)]
+    pub input_size_bytes: u64,
+    #[pyo3(get)]
+    pub expected_output_size_bytes: u64,
+    #[pyo3(get)]
+    pub estimated_job_count: usize, // target-size bins, each rewritten into one file
+}
+
+impl CompactionTask {
+    /// Plan the compaction of the small files among `files` (table-relative path, size):
+    /// per partition, first-fit decreasing into bins of the medium bucket bound. Bins of a
+    /// single file would only rewrite it, so they are left out, as are partitions without
+    /// any other bin.
+    pub fn plan(files: &[(&str, u64)], buckets: &crate::config::FileSizeBuckets) -> Vec<Self> {
+        let mut partitions: BTreeMap<String, Vec<(&str, u64)>> = BTreeMap::new();
+        for &(path, size) in files {
+            if size >= buckets.small_bytes {
+                continue;
+            }
+            let directories = path.rsplit_once('/').map_or("", |(dirs, _)| dirs);
+            let partition: Vec<&str> = directories
+                .split('/')
+                .filter(|segment| segment.contains('='))
+                .collect();
+            partitions
+                .entry(partition.join("/"))
+                .or_default()
+                .push((path, size));
+        }
+
+        let mut tasks: Vec<Self> = partitions
+            .into_iter()
+            .filter_map(|(partition, mut files)| {
+                files.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+                let mut bins: Vec<(u64, Vec<&str>)> = Vec::new();
+                for (path, size) in files {
+                    match bins
+                        .iter_mut()
+                        .find(|(used, _)| used + size <= buckets.medium_bytes)
+                    {
+                        Some((used, paths)) => {
+                            *used += size;
+                            paths.push(path);
+                        }
+                        None => bins.push((size, vec![path])),
+                    }
+                }
+                bins.retain(|(_, paths)| paths.len() > 1);
+                if bins.is_empty() {
+                    return None;
+                }
+                let input_size_bytes = bins.iter().map(|(used, _)| used).sum();
+                let input_files: Vec<String> = bins
+                    .iter()
+                    .flat_map(|(_, paths)| paths)
+                    .map(|path| path.to_string())
+                    .collect();
+                Some(Self {
+                    partition,
+                    estimated_job_count: bins.len(),
+                    input_file_count: input_files.len(),
+                    input_files,
+                    input_size_bytes,
+                    // Compaction rewrites the same rows
+                    expected_output_size_bytes: input_size_bytes,
+                })
+            })
+            .collect();
+        tasks.sort_by(|a, b| {
+            b.input_size_bytes
+                .cmp(&a.input_size_bytes)
+                .then_with(|| a.partition.cmp(&b.partition))
+        });
+        tasks
+    }
+}
+
+/// Partitions should hold about this much data; much less means too many tiny partitions
+const TARGET_PARTITION_BYTES: f64 = 1024.0 * 1024.0 * 1024.0;
+/// Average partition size below which a partitioned table counts as over-partitioned
+const OVER_PARTITIONED_MAX_AVG_BYTES: f64 = 256.0 * 1024.0 * 1024.0;
+/// Average partition size and file count above which a table counts as under-partitioned
+const UNDER_PARTITIONED_MIN_AVG_BYTES: f64 = 1024.0 * 1024.0 * 1024.0 * 1024.0;
+const UNDER_PARTITIONED_MIN_FILES: f64 = 1000.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct PartitioningAdvice {
+    #[pyo3(get)]
+    pub verdict: String, // "over_partitioned", "under_partitioned" or "balanced"
+    #[pyo3(get)]
+    pub partition_count: usize,
+    #[pyo3(get)]
+    pub avg_files_per_partition: f64,
+    #[pyo3(get)]
+    pub avg_partition_size_bytes: f64,
+    #[pyo3(get)]
+    pub column_cardinality: HashMap<String, usize>, // distinct values per partition column
+    #[pyo3(get)]
+    pub columns_to_drop: Vec<String>, // highest cardinality first
+    #[pyo3(get)]
+    pub suggested_partition_columns: Vec<String>,
+    #[pyo3(get)]
+    pub projected_partition_count: usize,
+    #[pyo3(get)]
+    pub projected_avg_partition_size_bytes: f64,
+    #[pyo3(get)]
+    pub candidate_columns_to_add: Vec<String>, // clustering columns, when under-partitioned
+}
+
+impl PartitioningAdvice {
+    /// Repartitioning recommendation shared by the Delta Lake and Iceberg analyzers
+    pub fn recommendation(&self) -> Option<String> {
+        let gb = |bytes: f64| bytes / (1024.0 * 1024.0 * 1024.0);
+        match self.verdict.as_str() {
+            "over_partitioned" => {
+                let target = if self.suggested_partition_columns.is_empty() {
+                    "leaving the table unpartitioned (use clustering or Z-ordering instead)"
+                        .to_string()
+                } else {
+                    format!(
+                        "partitioning by [{}]",
+                        self.suggested_partition_columns.join(", ")
+                    )
+                };
+                Some(format!(
+                    "Table is over-partitioned: {} partitions average {:.3} GB and {:.1} files. Drop [{}] from the partition spec, {}, for about {} partitions of {:.2} GB.",
+                    self.partition_count,
+                    gb(self.avg_partition_size_bytes),
+                    self.avg_files_per_partition,
+                    self.columns_to_drop.join(", "),
+                    target,
+                    self.projected_partition_count,
+                    gb(self.projected_avg_partition_size_bytes)
+                ))
+            }
+            "under_partitioned" => {
+                let candidates = if self.candidate_columns_to_add.is_empty() {
+                    "a low-cardinality column that queries commonly filter on".to_string()
+                } else {
+                    format!(
+                        "a low-cardinality column queries filter on, e.g. one of [{}]",
+                        self.candidate_columns_to_add.join(", ")
+                    )
+                };
+                Some(format!(
+                    "Table is under-partitioned: {} partitions average {:.0} GB and {:.0} files. Partition more finely by {}, aiming for about {} partitions.",
+                    self.partition_count,
+                    gb(self.avg_partition_size_bytes),
+                    self.avg_files_per_partition,
+                    candidates,
+                    self.projected_partition_count
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Partition value Hive and Spark write for a null partition column
+pub(crate) const HIVE_DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+const MAX_LISTED_MISSING_PARTITIONS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DateGranularity {
+    Day,
+    Month,
+}
+
+impl DateGranularity {
+    fn name(self) -> &'static str {
+        match self {
+            DateGranularity::Day => "day",
+            DateGranularity::Month => "month",
+        }
+    }
+
+    fn format(self, date: NaiveDate) -> String {
+        match self {
+            DateGranularity::Day => date.format("%Y-%m-%d").to_string(),
+            DateGranularity::Month => date.format("%Y-%m").to_string(),
+        }
+    }
+
+    fn period_start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            DateGranularity::Day => date,
+            DateGranularity::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    fn next(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            DateGranularity::Day => date + chrono::Duration::days(1),
+            DateGranularity::Month => date + chrono::Months::new(1),
+        }
+    }
+}
+
+/// Parse a date-style partition value: `2024-01-15`, `20240115`, values that start with a
+/// date (Iceberg hour transforms `2024-01-15-10`, escaped timestamps), or months (`2024-01`)
+fn parse_partition_date(value: &str) -> Option<(NaiveDate, DateGranularity)> {
+    let day = |text: &str| NaiveDate::parse_from_str(text, "%Y-%m-%d").ok();
+    if let Some(date) = value.get(..10).and_then(day) {
+        return Some((date, DateGranularity::Day));
+    }
+    if value.len() == 8 && value.bytes().all(|b| b.is_ascii_digit()) {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .map(|date| (date, DateGranularity::Day));
+    }
+    if value.len() == 7 {
+        return day(&format!("{}-01", value)).map(|date| (date, DateGranularity::Month));
+    }
+    None
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct PartitionAnomalies {
+    #[pyo3(get)]
+    pub date_column: Option<String>, // e.g. "event_date", or "year/month/day" for split columns
+    #[pyo3(get)]
+    pub granularity: Option<String>, // "day" or "month"
+    #[pyo3(get)]
+    pub first_partition_date: Option<String>,
+    #[pyo3(get)]
+    pub last_partition_date: Option<String>, // latest date not in the future
+    #[pyo3(get)]
+    pub missing_partition_count: usize, // days/months between the first and last with no data
+    #[pyo3(get)]
+    pub missing_partitions: Vec<String>, // oldest first, at most 100 listed
+    #[pyo3(get)]
+    pub longest_gap: usize, // consecutive missing days/months
+    #[pyo3(get)]
+    pub future_partitions: Vec<String>,
+    #[pyo3(get)]
+    pub null_partitions: Vec<String>, // e.g. "country=__HIVE_DEFAULT_PARTITION__"
+    #[pyo3(get)]
+    pub null_partition_file_count: usize,
+    #[pyo3(get)]
+    pub null_partition_size_bytes: u64,
+}
+
+impl PartitionAnomalies {
+    /// Data-quality findings shared by the Delta Lake and Iceberg analyzers
+    pub fn recommendations(&self) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        let column = self.date_column.as_deref().unwrap_or("date");
+        let unit = self.granularity.as_deref().unwrap_or("day");
+        if self.missing_partition_count > 0 {
+            recommendations.push(format!(
+                "Date partitions on {} have {} missing {}s between {} and {} (longest gap: {} {}s, first missing: {}). Check whether upstream loads failed or data landed in the wrong partition.",
+                column,
+                self.missing_partition_count,
+                unit,
+                self.first_partition_date.as_deref().unwrap_or("-"),
+                self.last_partition_date.as_deref().unwrap_or("-"),
+                self.longest_gap,
+                unit,
+                self.missing_partitions.first().map(String::as_str).unwrap_or("-")
+            ));
+        }
+        if !self.future_partitions.is_empty() {
+            recommendations.push(format!(
+                "Found {} partitions dated in the future on {} ({}). These usually come from timezone or date-parsing bugs in writers.",
+                self.future_partitions.len(),
+                column,
+                self.future_partitions.join(", ")
+            ));
+        }
+        if !self.null_partitions.is_empty() {
+            recommendations.push(format!(
+                "{} files ({:.2} MB) are in null partitions ({}). Rows with a null partition value are often unexpected; check the writers and consider filling or filtering them.",
+                self.null_partition_file_count,
+                self.null_partition_size_bytes as f64 / (1024.0 * 1024.0),
+                self.null_partitions.join(", ")
+            ));
+        }
+        recommendations
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct FreshnessMetrics {
+    #[pyo3(get)]
+    pub last_commit_timestamp: Option<DateTime<Utc>>,
+    #[pyo3(get)]
+    pub hours_since_last_commit: Option<f64>,
+    #[pyo3(get)]
+    pub last_data_write_timestamp: Option<DateTime<Utc>>, // newest data file in storage
+    #[pyo3(get)]
+    pub hours_since_last_data_write: Option<f64>,
+    #[pyo3(get)]
+    pub commit_count: usize, // commits/snapshots with a known timestamp
+    #[pyo3(get)]
+    pub commit_interval_p50_hours: Option<f64>,
+    #[pyo3(get)]
+    pub commit_interval_p90_hours: Option<f64>,
+    #[pyo3(get)]
+    pub commit_interval_p99_hours: Option<f64>,
+    #[pyo3(get)]
+    pub sla_hours: Option<f64>,
+    #[pyo3(get)]
+    pub is_stale: bool, // last commit is older than the SLA
 }
 
-impl HealthReport {
+impl FreshnessMetrics {
+    /// Build from commit (Delta) or snapshot (Iceberg) timestamps in any order
+    pub fn from_commit_times(
+        mut commit_times: Vec<DateTime<Utc>>,
+        last_data_write: Option<DateTime<Utc>>,
+        analysis_time: DateTime<Utc>,
+        sla_hours: Option<f64>,
+    ) -> Self {
+        commit_times.sort();
+        let hours_since =
+            |time: DateTime<Utc>| (analysis_time - time).num_seconds().max(0) as f64 / 3600.0;
+
+        let mut intervals: Vec<f64> = commit_times
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).num_seconds() as f64 / 3600.0)
+            .collect();
+        intervals.sort_by(|a, b| a.total_cmp(b));
+        // Nearest-rank percentile
+        let percentile = |p: f64| {
+            let rank = ((p * intervals.len() as f64).ceil() as usize).max(1);
+            intervals.get(rank - 1).copied()
+        };
+
+        let last_commit_timestamp = commit_times.last().copied();
+        let hours_since_last_commit = last_commit_timestamp.map(hours_since);
+        let is_stale = match (sla_hours, hours_since_last_commit) {
+            (Some(sla), Some(hours)) => hours > sla,
+            _ => false,
+        };
+
+        Self {
+            last_commit_timestamp,
+            hours_since_last_commit,
+            last_data_write_timestamp: last_data_write,
+            hours_since_last_data_write: last_data_write.map(hours_since),
+            commit_count: commit_times.len(),
+            commit_interval_p50_hours: percentile(0.5),
+            commit_interval_p90_hours: percentile(0.9),
+            commit_interval_p99_hours: percentile(0.99),
+            sla_hours,
+            is_stale,
+        }
+    }
+
+    /// Staleness finding shared by the Delta Lake and Iceberg analyzers
+    pub fn recommendation(&self) -> Option<String> {
+        if !self.is_stale {
+            return None;
+        }
+        Some(format!(
+            "Table is stale: the last commit was {:.1} hours ago, beyond the freshness SLA of {:.1} hours (commits usually land every {:.1} hours). Check the upstream pipeline.",
+            self.hours_since_last_commit.unwrap_or_default(),
+            self.sla_hours.unwrap_or_default(),
+            self.commit_interval_p50_hours.unwrap_or_default()
+        ))
+    }
+}
+
+/// Share of the table's bytes never read during the log window that warrants tiering
+const COLD_DATA_TIERING_RATIO: f64 = 0.2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct FileAccess {
+    #[pyo3(get)]
+    pub path: String,
+    #[pyo3(get)]
+    pub size_bytes: u64,
+    #[pyo3(get)]
+    pub read_count: usize, // successful GETs during the log window
+    #[pyo3(get)]
+    pub last_read: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct AccessPatternMetrics {
+    #[pyo3(get)]
+    pub log_objects_read: usize,
+    #[pyo3(get)]
+    pub truncated: bool, // only the newest log objects were read
+    #[pyo3(get)]
+    pub table_reads: usize, // GETs of the table's data files
+    #[pyo3(get)]
+    pub window_start: Option<DateTime<Utc>>, // oldest read in the logs
+    #[pyo3(get)]
+    pub window_end: Option<DateTime<Utc>>,
+    #[pyo3(get)]
+    pub never_read_file_count: usize,
+    #[pyo3(get)]
+    pub never_read_size_bytes: u64,
+    #[pyo3(get)]
+    pub never_read_files: Vec<String>, // first 100
+    #[pyo3(get)]
+    pub cold_partitions: Vec<String>, // partition directories with no reads at all
+    #[pyo3(get)]
+    pub cold_partition_size_bytes: u64,
+    #[pyo3(get)]
+    pub hot_files: Vec<FileAccess>, // most-read files, at least 100 reads each
+}
+
+impl AccessPatternMetrics {
+    /// Days covered by the reads in the logs
+    pub fn window_days(&self) -> f64 {
+        match (self.window_start, self.window_end) {
+            (Some(start), Some(end)) => (end - start).num_seconds() as f64 / 86400.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Tiering, archiving and caching findings shared by the Delta Lake and Iceberg analyzers
+    pub fn recommendations(&self, total_size_bytes: u64) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        let gb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+        let never_read_ratio = if total_size_bytes > 0 {
+            self.never_read_size_bytes as f64 / total_size_bytes as f64
+        } else {
+            0.0
+        };
+        if never_read_ratio >= COLD_DATA_TIERING_RATIO {
+            recommendations.push(format!(
+                "{} data files ({:.2} GB, {:.0}% of the table) were not read in {:.1} days of access logs. Move them to S3 Intelligent-Tiering or Glacier Instant Retrieval with a lifecycle rule.",
+                self.never_read_file_count,
+                gb(self.never_read_size_bytes),
+                never_read_ratio * 100.0,
+                self.window_days()
+            ));
+        }
+        if !self.cold_partitions.is_empty() {
+            recommendations.push(format!(
+                "{} partitions ({:.2} GB) had no reads in the access logs. Consider archiving them or expiring them with a retention policy.",
+                self.cold_partitions.len(),
+                gb(self.cold_partition_size_bytes)
+            ));
+        }
+        if let Some(hottest) = self.hot_files.first() {
+            recommendations.push(format!(
+                "{} data files are read at least {} times in the access logs (hottest: {} with {} reads). Cache them in the query engine, or compact them so each scan issues fewer GETs.",
+                self.hot_files.len(),
+                crate::access_logs::HOT_FILE_MIN_READS,
+                hottest.path,
+                hottest.read_count
+            ));
+        }
+        recommendations
+    }
+}
+
+const MAX_LISTED_SIZE_MISMATCHES: usize = 100;
+
+/// A live data file whose size in storage differs from the size its table metadata records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct SizeMismatch {
+    #[pyo3(get)]
+    pub path: String,
+    #[pyo3(get)]
+    pub recorded_size_bytes: u64, // Delta add action `size` or Iceberg `file_size_in_bytes`
+    #[pyo3(get)]
+    pub actual_size_bytes: u64,
+}
+
+/// Agreement between the table metadata (Delta add actions, Iceberg manifest entries) and the
+/// objects in storage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct LogConsistencyMetrics {
+    #[pyo3(get)]
+    pub partitions_from_log: bool, // paths carry no partition values; partitions use the add actions'
+    #[pyo3(get)]
+    pub checked_files: usize, // live files found in the listing whose metadata records a size
+    #[pyo3(get)]
+    pub size_mismatched_files: usize,
+    #[pyo3(get)]
+    pub size_mismatch_paths: Vec<String>, // first 100
+    #[pyo3(get)]
+    pub truncated_files: usize, // smaller than recorded: interrupted uploads or corruption
+    #[pyo3(get)]
+    pub overwritten_files: usize, // larger than recorded: replaced after the commit
+    #[pyo3(get)]
+    pub size_mismatches: Vec<SizeMismatch>, // first 100, with both sizes
+}
+
+impl LogConsistencyMetrics {
+    pub fn new() -> Self {
+        Self {
+            partitions_from_log: false,
+            checked_files: 0,
+            size_mismatched_files: 0,
+            size_mismatch_paths: Vec::new(),
+            truncated_files: 0,
+            overwritten_files: 0,
+            size_mismatches: Vec::new(),
+        }
+    }
+
+    /// Compare a live file's size in storage with the size its metadata records
+    pub fn check_size(&mut self, path: &str, recorded_size_bytes: u64, actual_size_bytes: u64) {
+        self.checked_files += 1;
+        if recorded_size_bytes == actual_size_bytes {
+            return;
+        }
+        self.size_mismatched_files += 1;
+        if actual_size_bytes < recorded_size_bytes {
+            self.truncated_files += 1;
+        } else {
+            self.overwritten_files += 1;
+        }
+        if self.size_mismatches.len() < MAX_LISTED_SIZE_MISMATCHES {
+            self.size_mismatch_paths.push(path.to_string());
+            self.size_mismatches.push(SizeMismatch {
+                path: path.to_string(),
+                recorded_size_bytes,
+                actual_size_bytes,
+            });
+        }
+    }
+
+    pub fn recommendation(&self) -> Option<String> {
+        if self.size_mismatched_files == 0 {
+            return None;
+        }
+        Some(format!(
+            "{} of {} live data files differ in size from the size recorded in the table metadata ({} smaller, from interrupted multipart uploads or corruption; {} larger, from files overwritten after their commit). Readers will fail on them. Restore the original files or rewrite the affected partitions.",
+            self.size_mismatched_files,
+            self.checked_files,
+            self.truncated_files,
+            self.overwritten_files
+        ))
+    }
+}
+
+impl Default for LogConsistencyMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// HEAD requests for every file the current snapshot references
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ExistenceCheckMetrics {
+    #[pyo3(get)]
+    pub checked_files: usize, // answered present or missing
+    #[pyo3(get)]
+    pub missing_files: usize,
+    #[pyo3(get)]
+    pub missing_paths: Vec<String>, // first 100
+    #[pyo3(get)]
+    pub unverified_files: usize, // still throttled after retries, or failing otherwise (e.g. access denied)
+    #[pyo3(get)]
+    pub throttled_requests: usize,
+    #[pyo3(get)]
+    pub final_concurrency: usize, // HEAD requests per batch the pacing settled on
+}
+
+impl ExistenceCheckMetrics {
+    pub fn recommendations(&self, table_type: &str) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        if self.missing_files > 0 {
+            let repair = match table_type {
+                "delta" => "run FSCK REPAIR TABLE to drop them from the log",
+                _ => "drop them from the table with a DeleteFiles commit",
+            };
+            recommendations.push(format!(
+                "{} of {} files referenced by the current snapshot are missing from storage, and queries reading them fail. Restore them from object versioning or a backup, or {}.",
+                self.missing_files, self.checked_files, repair
+            ));
+        }
+        if self.unverified_files > 0 {
+            recommendations.push(format!(
+                "{} referenced files could not be checked (throttling or access denied). Grant s3:GetObject on the table's data and re-run the existence check off-peak.",
+                self.unverified_files
+            ));
+        }
+        recommendations
+    }
+}
+
+/// Multipart uploads under the table that were started but never completed or aborted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct IncompleteUploadMetrics {
+    #[pyo3(get)]
+    pub incomplete_uploads: usize,
+    #[pyo3(get)]
+    pub stale_uploads: usize, // initiated over 7 days ago
+    #[pyo3(get)]
+    pub oldest_upload_age_days: Option<f64>,
+    #[pyo3(get)]
+    pub sized_uploads: usize, // oldest uploads whose parts were listed, at most 100
+    #[pyo3(get)]
+    pub uploaded_part_bytes: u64, // parts of the sized uploads, billed until aborted
+    #[pyo3(get)]
+    pub upload_keys: Vec<String>, // first 100, table-relative
+    #[pyo3(get)]
+    pub lifecycle_rule: Option<String>, // S3 lifecycle configuration aborting them, when any are stale
+}
+
+impl IncompleteUploadMetrics {
+    pub fn recommendations(&self) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        if let Some(ref rule) = self.lifecycle_rule {
+            recommendations.push(format!(
+                "{} multipart uploads under the table were started over {} days ago and never completed ({:.1} MB of parts in the {} sized). Their parts are billed but never listed. Abort them with `aws s3api abort-multipart-upload` and add a lifecycle rule so abandoned uploads are aborted automatically: {}",
+                self.stale_uploads,
+                crate::multipart::STALE_UPLOAD_DAYS,
+                self.uploaded_part_bytes as f64 / (1024.0 * 1024.0),
+                self.sized_uploads,
+                rule
+            ));
+        }
+        recommendations
+    }
+}
+
+/// The newest Iceberg partition statistics file and how far the table has moved on since
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct PartitionStatisticsMetrics {
+    #[pyo3(get)]
+    pub statistics_path: String,
+    #[pyo3(get)]
+    pub snapshot_id: i64, // snapshot the statistics were computed for
+    #[pyo3(get)]
+    pub snapshots_behind: usize, // snapshots committed since; 0 when current
+    #[pyo3(get)]
+    pub staleness_hours: f64, // between that snapshot and the current one
+    #[pyo3(get)]
+    pub partition_count: usize,
+    #[pyo3(get)]
+    pub data_record_count: u64,
+    #[pyo3(get)]
+    pub data_file_count: u64,
+    #[pyo3(get)]
+    pub total_data_file_size_bytes: u64,
+    #[pyo3(get)]
+    pub partitions_with_deletes: usize, // with position or equality delete files
+}
+
+impl PartitionStatisticsMetrics {
+    pub fn recommendation(&self) -> Option<String> {
+        (self.snapshots_behind > 0).then(|| {
+            format!(
+                "Partition statistics are {} snapshot(s) ({:.1} hours) behind the current snapshot, so engines planning with them see old partition sizes. Recompute them after writes (e.g. Spark's compute_partition_stats procedure).",
+                self.snapshots_behind, self.staleness_hours
+            )
+        })
+    }
+}
+
+/// Data file formats recognized by extension
+const DATA_FILE_FORMATS: [(&str, &str); 3] =
+    [(".parquet", "parquet"), (".orc", "orc"), (".avro", "avro")];
+const MAX_LISTED_MIXED_PARTITIONS: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct FileFormatMetrics {
+    #[pyo3(get)]
+    pub files_by_format: HashMap<String, usize>, // "parquet", "orc" or "avro"
+    #[pyo3(get)]
+    pub bytes_by_format: HashMap<String, u64>,
+    #[pyo3(get)]
+    pub dominant_format: String, // the format holding the most bytes
+    #[pyo3(get)]
+    pub mixed_partitions: usize, // directories holding files of more than one format
+    #[pyo3(get)]
+    pub mixed_partition_paths: Vec<String>, // first 100
+    #[pyo3(get)]
+    pub unsupported_files: usize, // in a format the table format can't reference (ORC or Avro in Delta)
+    #[pyo3(get)]
+    pub unsupported_size_bytes: u64,
+}
+
+impl FileFormatMetrics {
+    /// Format of a data file, by its extension
+    pub fn format_of(key: &str) -> Option<&'static str> {
+        let key = key.to_ascii_lowercase();
+        DATA_FILE_FORMATS
+            .iter()
+            .find(|(extension, _)| key.ends_with(extension))
+            .map(|(_, format)| *format)
+    }
+
+    /// Tally `files` (other objects are ignored) by format and by the directory under
+    /// `table_prefix` they are in. `supported` are the formats the table format can read.
+    /// None when there are no data files.
+    pub fn from_files(
+        files: &[&crate::s3_client::ObjectInfo],
+        table_prefix: &str,
+        supported: &[&str],
+    ) -> Option<Self> {
+        let mut metrics = Self {
+            files_by_format: HashMap::new(),
+            bytes_by_format: HashMap::new(),
+            dominant_format: String::new(),
+            mixed_partitions: 0,
+            mixed_partition_paths: Vec::new(),
+            unsupported_files: 0,
+            unsupported_size_bytes: 0,
+        };
+        let mut partition_formats: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+        for file in files {
+            let Some(format) = Self::format_of(&file.key) else {
+                continue;
+            };
+            let size = file.size as u64;
+            *metrics
+                .files_by_format
+                .entry(format.to_string())
+                .or_default() += 1;
+            *metrics
+                .bytes_by_format
+                .entry(format.to_string())
+                .or_default() += size;
+            if !supported.contains(&format) {
+                metrics.unsupported_files += 1;
+                metrics.unsupported_size_bytes += size;
+            }
+            let relative = crate::s3_client::relative_key(table_prefix, &file.key);
+            let partition = relative.rsplit_once('/').map_or(".", |(dir, _)| dir);
+            partition_formats
+                .entry(partition)
+                .or_default()
+                .insert(format);
+        }
+        if metrics.files_by_format.is_empty() {
+            return None;
+        }
+
+        let mut mixed: Vec<&str> = partition_formats
+            .into_iter()
+            .filter(|(_, formats)| formats.len() > 1)
+            .map(|(partition, _)| partition)
+            .collect();
+        mixed.sort();
+        metrics.mixed_partitions = mixed.len();
+        metrics.mixed_partition_paths = mixed
+            .into_iter()
+            .take(MAX_LISTED_MIXED_PARTITIONS)
+            .map(str::to_string)
+            .collect();
+        metrics.dominant_format = metrics
+            .bytes_by_format
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(format, _)| format.clone())
+            .unwrap_or_default();
+        Some(metrics)
+    }
+
+    /// Whether the table holds more than one format
+    pub fn is_mixed(&self) -> bool {
+        self.files_by_format.len() > 1
+    }
+
+    /// Share of bytes per format, largest first, e.g. "parquet 92.0%, orc 8.0%"
+    fn format_shares(&self) -> String {
+        let total: u64 = self.bytes_by_format.values().sum();
+        let mut formats: Vec<(&String, &u64)> = self.bytes_by_format.iter().collect();
+        formats.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        formats
+            .into_iter()
+            .map(|(format, bytes)| {
+                format!(
+                    "{} {:.1}%",
+                    format,
+                    *bytes as f64 / total.max(1) as f64 * 100.0
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Mixed-format findings; `table_type` is "delta" or "iceberg"
+    pub fn recommendations(&self, table_type: &str) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        if self.unsupported_files > 0 {
+            recommendations.push(format!(
+                "{} ORC or Avro files ({:.2} MB) sit in the Delta table directory. Delta only reads Parquet, so queries never see this data. Load it into the table or move the files out.",
+                self.unsupported_files,
+                self.unsupported_size_bytes as f64 / (1024.0 * 1024.0)
+            ));
+        } else if self.is_mixed() && table_type == "iceberg" {
+            recommendations.push(format!(
+                "Data files use several formats ({}), mixed within {} partition(s). Engines without an ORC or Avro reader can't query the table. Set write.format.default and run rewrite_data_files to converge on {}.",
+                self.format_shares(),
+                self.mixed_partitions,
+                self.dominant_format
+            ));
+        }
+        recommendations
+    }
+}
+
+/// Suffixes and directories of files writers leave behind while writing
+const TEMPORARY_SUFFIXES: [&str; 5] = [".tmp", "_tmp", ".temp", ".inprogress", ".pending"];
+const TEMPORARY_DIRS: [&str; 3] = ["_temporary/", "_tmp/", ".spark-staging"];
+const MAX_NAMING_EXAMPLES: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct FileNamingMetrics {
+    #[pyo3(get)]
+    pub checksum_files: usize, // Hadoop `.crc` files, e.g. `.part-0.parquet.crc`
+    #[pyo3(get)]
+    pub temporary_files: usize, // `.tmp`, `_tmp`, `.inprogress`, ... or under `_temporary/`
+    #[pyo3(get)]
+    pub double_extension_files: usize, // e.g. `part-0.parquet.bak`, `part-0.parquet.parquet`
+    #[pyo3(get)]
+    pub zero_byte_files: usize, // empty data files
+    #[pyo3(get)]
+    pub unexpected_extension_files: usize, // other files that aren't writer markers
+    #[pyo3(get)]
+    pub unexpected_extensions: HashMap<String, usize>, // e.g. {".csv": 3}; "" for none
+    #[pyo3(get)]
+    pub anomalous_size_bytes: u64,
+    #[pyo3(get)]
+    pub leftover_size_bytes: u64, // checksum, temporary and double extension files only
+    #[pyo3(get)]
+    pub example_paths: HashMap<String, Vec<String>>, // up to 5 table-relative paths per kind
+}
+
+impl FileNamingMetrics {
+    /// Kind of naming anomaly of a file in the table's data paths, if any
+    fn anomaly_of(relative: &str, size: i64) -> Option<&'static str> {
+        let relative = relative.to_ascii_lowercase();
+        let name = relative.rsplit('/').next().unwrap_or_default();
+        if name.is_empty() {
+            return None; // directory marker
+        }
+        if name.ends_with(".crc") {
+            return Some("checksum");
+        }
+        if TEMPORARY_SUFFIXES
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+            || TEMPORARY_DIRS.iter().any(|dir| relative.contains(dir))
+        {
+            return Some("temporary");
+        }
+        if DATA_FILE_FORMATS
+            .iter()
+            .any(|(extension, _)| name.contains(&format!("{}.", extension)))
+        {
+            return Some("double_extension");
+        }
+        if FileFormatMetrics::format_of(name).is_some() {
+            return (size == 0).then_some("zero_byte");
+        }
+        // `_SUCCESS`, `_committed_*` and hidden files are writer bookkeeping; Delta keeps
+        // deletion vectors next to the data
+        if name.starts_with('_')
+            || name.starts_with('.')
+            || (name.starts_with("deletion_vector_") && name.ends_with(".bin"))
+        {
+            return None;
+        }
+        Some("unexpected_extension")
+    }
+
+    /// Classify `files`, the objects in the table's data paths (metadata directories
+    /// excluded). None when there are no such objects.
+    pub fn from_files(files: &[&crate::s3_client::ObjectInfo], table_prefix: &str) -> Option<Self> {
+        if files.is_empty() {
+            return None;
+        }
+        let mut metrics = Self {
+            checksum_files: 0,
+            temporary_files: 0,
+            double_extension_files: 0,
+            zero_byte_files: 0,
+            unexpected_extension_files: 0,
+            unexpected_extensions: HashMap::new(),
+            anomalous_size_bytes: 0,
+            leftover_size_bytes: 0,
+            example_paths: HashMap::new(),
+        };
+        for file in files {
+            let relative = crate::s3_client::relative_key(table_prefix, &file.key);
+            let Some(kind) = Self::anomaly_of(relative, file.size) else {
+                continue;
+            };
+            match kind {
+                "checksum" => metrics.checksum_files += 1,
+                "temporary" => metrics.temporary_files += 1,
+                "double_extension" => metrics.double_extension_files += 1,
+                "zero_byte" => metrics.zero_byte_files += 1,
+                _ => {
+                    metrics.unexpected_extension_files += 1;
+                    let name = relative.rsplit('/').next().unwrap_or_default();
+                    let extension = name
+                        .rsplit_once('.')
+                        .map(|(_, extension)| format!(".{}", extension.to_ascii_lowercase()))
+                        .unwrap_or_default();
+                    *metrics.unexpected_extensions.entry(extension).or_default() += 1;
+                }
+            }
+            metrics.anomalous_size_bytes += file.size.max(0) as u64;
+            if matches!(kind, "checksum" | "temporary" | "double_extension") {
+                metrics.leftover_size_bytes += file.size.max(0) as u64;
+            }
+            let examples = metrics.example_paths.entry(kind.to_string()).or_default();
+            if examples.len() < MAX_NAMING_EXAMPLES {
+                examples.push(relative.to_string());
+            }
+        }
+        Some(metrics)
+    }
+
+    pub fn anomalous_files(&self) -> usize {
+        self.checksum_files
+            + self.temporary_files
+            + self.double_extension_files
+            + self.zero_byte_files
+            + self.unexpected_extension_files
+    }
+
+    fn examples(&self, kinds: &[&str]) -> String {
+        kinds
+            .iter()
+            .filter_map(|kind| self.example_paths.get(*kind))
+            .flatten()
+            .take(MAX_NAMING_EXAMPLES)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn recommendations(&self) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        if self.zero_byte_files > 0 {
+            recommendations.push(format!(
+                "{} data files are empty (0 bytes), e.g. {}. Readers fail on them with errors such as \"not a Parquet file\"; remove them from the table and storage.",
+                self.zero_byte_files,
+                self.examples(&["zero_byte"])
+            ));
+        }
+        let leftovers = self.checksum_files + self.temporary_files + self.double_extension_files;
+        if leftovers > 0 {
+            recommendations.push(format!(
+                "{} files in data paths look like writer leftovers ({} checksum, {} temporary, {} with a double extension; {:.1} MB), e.g. {}. Readers that list directories instead of the table's metadata pick them up and fail; delete them once no write is running.",
+                leftovers,
+                self.checksum_files,
+                self.temporary_files,
+                self.double_extension_files,
+                self.leftover_size_bytes as f64 / (1024.0 * 1024.0),
+                self.examples(&["checksum", "temporary", "double_extension"])
+            ));
+        }
+        if self.unexpected_extension_files > 0 {
+            let mut extensions: Vec<(&String, &usize)> =
+                self.unexpected_extensions.iter().collect();
+            extensions.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            recommendations.push(format!(
+                "{} files in data paths aren't data files ({}), e.g. {}. Move them out of the table directory.",
+                self.unexpected_extension_files,
+                extensions
+                    .into_iter()
+                    .map(|(extension, count)| format!(
+                        "{} {}",
+                        count,
+                        if extension.is_empty() { "without extension" } else { extension }
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                self.examples(&["unexpected_extension"])
+            ));
+        }
+        recommendations
+    }
+}
+
+/// Smallest well-formed file of each format: Parquet's `PAR1` magic, footer length and
+/// trailing `PAR1`; ORC's `ORC` magic and postscript length; Avro's magic, an empty
+/// metadata map and the 16-byte sync marker
+const MIN_DATA_FILE_BYTES: [(&str, i64); 3] = [("parquet", 12), ("orc", 4), ("avro", 21)];
+const MAX_LISTED_INTEGRITY_FILES: usize = 100;
+
+const MAX_LISTED_DUPLICATE_GROUPS: usize = 20;
+
+/// Data files with the same content (size and ETag) stored under different keys
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct DuplicateFileGroup {
+    #[pyo3(get)]
+    pub size_bytes: u64,
+    #[pyo3(get)]
+    pub etag: String,
+    #[pyo3(get)]
+    pub paths: Vec<String>,
+    #[pyo3(get)]
+    pub live_copies: usize, // referenced by the current snapshot
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct DuplicateFileMetrics {
+    #[pyo3(get)]
+    pub duplicate_groups: usize, // contents stored under more than one key
+    #[pyo3(get)]
+    pub duplicate_files: usize, // copies beyond the first of each content
+    #[pyo3(get)]
+    pub live_duplicate_groups: usize, // committed more than once; queries read the rows twice
+    #[pyo3(get)]
+    pub reclaimable_bytes: u64, // copies the current snapshot doesn't reference, keeping one
+    #[pyo3(get)]
+    pub groups: Vec<DuplicateFileGroup>, // up to 20, most wasted bytes first
+}
+
+impl DuplicateFileMetrics {
+    /// Group non-empty data files by size and ETag; `is_live` tells whether the current
+    /// snapshot references a file. None when no content is stored twice.
+    pub fn from_files(
+        data_files: &[&crate::s3_client::ObjectInfo],
+        is_live: impl Fn(&crate::s3_client::ObjectInfo) -> bool,
+    ) -> Option<Self> {
+        let mut by_content: HashMap<(i64, &str), Vec<&crate::s3_client::ObjectInfo>> =
+            HashMap::new();
+        for file in data_files {
+            // Every empty object has the same ETag
+            if file.size <= 0 {
+                continue;
+            }
+            if let Some(ref etag) = file.etag {
+                by_content
+                    .entry((file.size, etag.trim_matches('"')))
+                    .or_default()
+                    .push(file);
+            }
+        }
+
+        let mut groups: Vec<DuplicateFileGroup> = Vec::new();
+        let mut metrics = Self {
+            duplicate_groups: 0,
+            duplicate_files: 0,
+            live_duplicate_groups: 0,
+            reclaimable_bytes: 0,
+            groups: Vec::new(),
+        };
+        for ((size, etag), mut files) in by_content {
+            if files.len() < 2 {
+                continue;
+            }
+            files.sort_by(|a, b| a.key.cmp(&b.key));
+            let live_copies = files.iter().filter(|file| is_live(file)).count();
+            metrics.duplicate_groups += 1;
+            metrics.duplicate_files += files.len() - 1;
+            if live_copies > 1 {
+                metrics.live_duplicate_groups += 1;
+            }
+            // Keep a live copy when there is one, otherwise any one copy
+            let reclaimable_copies = files.len() - live_copies.max(1);
+            metrics.reclaimable_bytes += size as u64 * reclaimable_copies as u64;
+            groups.push(DuplicateFileGroup {
+                size_bytes: size as u64,
+                etag: etag.to_string(),
+                paths: files.iter().map(|file| file.key.clone()).collect(),
+                live_copies,
+            });
+        }
+        if groups.is_empty() {
+            return None;
+        }
+        let wasted = |group: &DuplicateFileGroup| group.size_bytes * (group.paths.len() as u64 - 1);
+        groups.sort_by(|a, b| {
+            wasted(b)
+                .cmp(&wasted(a))
+                .then_with(|| a.paths[0].cmp(&b.paths[0]))
+        });
+        groups.truncate(MAX_LISTED_DUPLICATE_GROUPS);
+        metrics.groups = groups;
+        Some(metrics)
+    }
+
+    /// Duplicate findings; `table_type` ("delta" or "iceberg") picks the cleanup command
+    pub fn recommendations(&self, table_type: &str) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        if self.live_duplicate_groups > 0 {
+            let example = self
+                .groups
+                .iter()
+                .find(|group| group.live_copies > 1)
+                .map(|group| group.paths.join(", "))
+                .unwrap_or_default();
+            recommendations.push(format!(
+                "{} data files are committed more than once under different keys (e.g. {}), typically by a retried write whose first attempt also committed. Queries read their rows twice; remove the extra copies from the table and deduplicate the affected partitions.",
+                self.live_duplicate_groups, example
+            ));
+        }
+        if self.reclaimable_bytes > 0 {
+            let cleanup = match table_type {
+                "delta" => "VACUUM",
+                _ => "expire_snapshots and remove_orphan_files",
+            };
+            recommendations.push(format!(
+                "{} data files duplicate the content of other files ({:.1} MB reclaimable), left by failed retries or copy-on-write rewrites. Copies the current snapshot doesn't reference are removed by {}.",
+                self.duplicate_files,
+                self.reclaimable_bytes as f64 / (1024.0 * 1024.0),
+                cleanup
+            ));
+        }
+        recommendations
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct FileIntegrityMetrics {
+    #[pyo3(get)]
+    pub referenced_zero_byte_files: usize, // in the current snapshot; queries reading them fail
+    #[pyo3(get)]
+    pub referenced_truncated_files: usize, // smaller than the format's footer
+    #[pyo3(get)]
+    pub referenced_paths: Vec<String>, // first 100
+    #[pyo3(get)]
+    pub unreferenced_zero_byte_files: usize, // left by interrupted writes; safe to delete
+    #[pyo3(get)]
+    pub unreferenced_truncated_files: usize,
+    #[pyo3(get)]
+    pub unreferenced_paths: Vec<String>, // first 100
+}
+
+impl FileIntegrityMetrics {
+    /// Find empty and truncated data files, split by whether the current snapshot
+    /// references them (`is_live`). None when there are no data files.
+    pub fn from_files(
+        data_files: &[&crate::s3_client::ObjectInfo],
+        is_live: impl Fn(&crate::s3_client::ObjectInfo) -> bool,
+    ) -> Option<Self> {
+        if data_files.is_empty() {
+            return None;
+        }
+        let mut metrics = Self {
+            referenced_zero_byte_files: 0,
+            referenced_truncated_files: 0,
+            referenced_paths: Vec::new(),
+            unreferenced_zero_byte_files: 0,
+            unreferenced_truncated_files: 0,
+            unreferenced_paths: Vec::new(),
+        };
+        for file in data_files {
+            let Some(min_bytes) = FileFormatMetrics::format_of(&file.key).and_then(|format| {
+                MIN_DATA_FILE_BYTES
+                    .iter()
+                    .find(|(name, _)| *name == format)
+                    .map(|(_, min_bytes)| *min_bytes)
+            }) else {
+                continue;
+            };
+            if file.size >= min_bytes {
+                continue;
+            }
+            let (zero_byte, truncated, paths) = if is_live(file) {
+                (
+                    &mut metrics.referenced_zero_byte_files,
+                    &mut metrics.referenced_truncated_files,
+                    &mut metrics.referenced_paths,
+                )
+            } else {
+                (
+                    &mut metrics.unreferenced_zero_byte_files,
+                    &mut metrics.unreferenced_truncated_files,
+                    &mut metrics.unreferenced_paths,
+                )
+            };
+            if file.size <= 0 {
+                *zero_byte += 1;
+            } else {
+                *truncated += 1;
+            }
+            if paths.len() < MAX_LISTED_INTEGRITY_FILES {
+                paths.push(file.key.clone());
+            }
+        }
+        Some(metrics)
+    }
+
+    pub fn referenced_files(&self) -> usize {
+        self.referenced_zero_byte_files + self.referenced_truncated_files
+    }
+
+    pub fn unreferenced_files(&self) -> usize {
+        self.unreferenced_zero_byte_files + self.unreferenced_truncated_files
+    }
+
+    /// Integrity findings; `table_type` ("delta" or "iceberg") picks the cleanup command
+    pub fn recommendations(&self, table_type: &str) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        let examples = |paths: &[String]| {
+            paths
+                .iter()
+                .take(MAX_NAMING_EXAMPLES)
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        if self.referenced_files() > 0 {
+            recommendations.push(format!(
+                "{} data files referenced by the current snapshot are empty or truncated ({} zero-byte, {} smaller than the format's footer), e.g. {}. They were committed by interrupted writes and every query that reads them will fail; restore them from the source or remove them from the table.",
+                self.referenced_files(),
+                self.referenced_zero_byte_files,
+                self.referenced_truncated_files,
+                examples(&self.referenced_paths)
+            ));
+        }
+        if self.unreferenced_files() > 0 {
+            recommendations.push(format!(
+                "{} unreferenced data files are empty or truncated ({} zero-byte, {} smaller than the format's footer), e.g. {}. They are leftovers of failed writes; delete them with {}.",
+                self.unreferenced_files(),
+                self.unreferenced_zero_byte_files,
+                self.unreferenced_truncated_files,
+                examples(&self.unreferenced_paths),
+                if table_type == "delta" {
+                    "VACUUM"
+                } else {
+                    "remove_orphan_files"
+                }
+            ));
+        }
+        recommendations
+    }
+}
+
+/// Part of the storage bill a data file belongs to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CostCategory {
+    Live,    // in the current snapshot
+    History, // removed from the table but kept for time travel
+    Orphan,  // not referenced by the table at all
+}
+
+/// Share of the storage cost taken by time-travel history that warrants shorter retention
+const HISTORY_COST_SHARE: f64 = 0.25;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct CostMetrics {
+    #[pyo3(get)]
+    pub pricing: String, // e.g. "aws:us-east-1"
+    #[pyo3(get)]
+    pub currency: String, // of every cost below, e.g. "USD"
+    #[pyo3(get)]
+    pub live_data_bytes: u64,
+    #[pyo3(get)]
+    pub history_bytes: u64,
+    #[pyo3(get)]
+    pub orphan_bytes: u64,
+    #[pyo3(get)]
+    pub metadata_bytes: u64,
+    #[pyo3(get)]
+    pub live_data_monthly_cost: f64,
+    #[pyo3(get)]
+    pub history_monthly_cost: f64,
+    #[pyo3(get)]
+    pub orphan_monthly_cost: f64,
+    #[pyo3(get)]
+    pub metadata_monthly_cost: f64, // priced as STANDARD
+    #[pyo3(get)]
+    pub total_monthly_cost: f64,
+    #[pyo3(get)]
+    pub cost_by_class: HashMap<String, f64>, // data files only
+}
+
+impl CostMetrics {
+    /// Price data files by storage class and split them with `category`
+    pub fn from_files(
+        data_files: &[&crate::s3_client::ObjectInfo],
+        category: impl Fn(&crate::s3_client::ObjectInfo) -> CostCategory,
+        metadata_bytes: u64,
+        pricing: &crate::config::PricingProfile,
+    ) -> Self {
+        let gb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+        let metadata_monthly_cost = gb(metadata_bytes) * pricing.price("STANDARD");
+        let mut metrics = Self {
+            pricing: pricing.name.clone(),
+            currency: pricing.currency.clone(),
+            live_data_bytes: 0,
+            history_bytes: 0,
+            orphan_bytes: 0,
+            metadata_bytes,
+            live_data_monthly_cost: 0.0,
+            history_monthly_cost: 0.0,
+            orphan_monthly_cost: 0.0,
+            metadata_monthly_cost,
+            total_monthly_cost: metadata_monthly_cost,
+            cost_by_class: HashMap::new(),
+        };
+        for file in data_files {
+            let class = file.storage_class.as_deref().unwrap_or("STANDARD");
+            let size = file.size as u64;
+            let cost = gb(size) * pricing.price(class);
+            let (bytes, category_cost) = match category(file) {
+                CostCategory::Live => (
+                    &mut metrics.live_data_bytes,
+                    &mut metrics.live_data_monthly_cost,
+                ),
+                CostCategory::History => (
+                    &mut metrics.history_bytes,
+                    &mut metrics.history_monthly_cost,
+                ),
+                CostCategory::Orphan => {
+                    (&mut metrics.orphan_bytes, &mut metrics.orphan_monthly_cost)
+                }
+            };
+            *bytes += size;
+            *category_cost += cost;
+            *metrics.cost_by_class.entry(class.to_string()).or_default() += cost;
+            metrics.total_monthly_cost += cost;
+        }
+        metrics
+    }
+
+    /// Monthly cost of `bytes` of orphaned data, at the orphans' average price
+    pub fn orphan_cost(&self, bytes: u64) -> f64 {
+        if self.orphan_bytes == 0 {
+            return 0.0;
+        }
+        self.orphan_monthly_cost * bytes as f64 / self.orphan_bytes as f64
+    }
+
+    /// Retention finding shared by the Delta Lake and Iceberg analyzers
+    pub fn recommendation(&self) -> Option<String> {
+        if self.total_monthly_cost <= 0.0
+            || self.history_monthly_cost / self.total_monthly_cost < HISTORY_COST_SHARE
+        {
+            return None;
+        }
+        Some(format!(
+            "Time-travel history ({:.2} GB of files no longer in the current snapshot) costs about {}/month, {:.0}% of the table's storage cost. Shorten the retention period and remove expired files (VACUUM for Delta Lake, expire_snapshots for Iceberg).",
+            self.history_bytes as f64 / (1024.0 * 1024.0 * 1024.0),
+            crate::config::format_amount(self.history_monthly_cost, &self.currency, 2),
+            self.history_monthly_cost / self.total_monthly_cost * 100.0
+        ))
+    }
+}
+
+/// Storage classes whose objects must be restored before they can be read
+const ARCHIVE_STORAGE_CLASSES: &[&str] = &["GLACIER", "DEEP_ARCHIVE"];
+/// Standard-IA bills at least 30 days of storage and 128 KB per object
+const IA_MIN_AGE_DAYS: f64 = 30.0;
+const IA_MIN_OBJECT_BYTES: u64 = 128 * 1024;
+const MAX_LISTED_ARCHIVED_FILES: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct StorageClassMetrics {
+    #[pyo3(get)]
+    pub bytes_by_class: HashMap<String, u64>, // data files; "UNKNOWN" when the listing has no class
+    #[pyo3(get)]
+    pub files_by_class: HashMap<String, usize>,
+    #[pyo3(get)]
+    pub archived_live_files: usize, // referenced by the current snapshot but in GLACIER or DEEP_ARCHIVE
+    #[pyo3(get)]
+    pub archived_live_size_bytes: u64,
+    #[pyo3(get)]
+    pub archived_live_paths: Vec<String>, // first 100
+    #[pyo3(get)]
+    pub ia_eligible_files: usize, // STANDARD, at least 30 days old and 128 KB
+    #[pyo3(get)]
+    pub ia_eligible_size_bytes: u64,
+    #[pyo3(get)]
+    pub restore_status: Option<RestoreStatusMetrics>, // of the listed archived live files
+}
+
+impl StorageClassMetrics {
+    /// Tally data files by storage class. `is_live` tells whether the current snapshot
+    /// references a file.
+    pub fn from_files(
+        data_files: &[&crate::s3_client::ObjectInfo],
+        is_live: impl Fn(&crate::s3_client::ObjectInfo) -> bool,
+        analysis_time: DateTime<Utc>,
+    ) -> Self {
+        let mut metrics = Self {
+            bytes_by_class: HashMap::new(),
+            files_by_class: HashMap::new(),
+            archived_live_files: 0,
+            archived_live_size_bytes: 0,
+            archived_live_paths: Vec::new(),
+            ia_eligible_files: 0,
+            ia_eligible_size_bytes: 0,
+            restore_status: None,
+        };
+        for file in data_files {
+            let class = file.storage_class.as_deref().unwrap_or("UNKNOWN");
+            let size = file.size as u64;
+            *metrics.bytes_by_class.entry(class.to_string()).or_default() += size;
+            *metrics.files_by_class.entry(class.to_string()).or_default() += 1;
+
+            if ARCHIVE_STORAGE_CLASSES.contains(&class) && is_live(file) {
+                metrics.archived_live_files += 1;
+                metrics.archived_live_size_bytes += size;
+                if metrics.archived_live_paths.len() < MAX_LISTED_ARCHIVED_FILES {
+                    metrics.archived_live_paths.push(file.key.clone());
+                }
+            }
+            let age_days = file
+                .last_modified
+                .map(|modified| (analysis_time - modified).num_seconds() as f64 / 86400.0);
+            if class == "STANDARD"
+                && size >= IA_MIN_OBJECT_BYTES
+                && age_days.is_some_and(|days| days >= IA_MIN_AGE_DAYS)
+            {
+                metrics.ia_eligible_files += 1;
+                metrics.ia_eligible_size_bytes += size;
+            }
+        }
+        metrics
+    }
+
+    /// Whether every archived live file was checked and has a restored copy to read
+    pub fn all_archived_readable(&self) -> bool {
+        self.restore_status
+            .as_ref()
+            .is_some_and(|restore| restore.restored_files == self.archived_live_files)
+    }
+
+    /// Archived-data and tiering findings shared by the Delta Lake and Iceberg analyzers
+    pub fn recommendations(&self) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        let gb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+        match self.restore_status {
+            _ if self.archived_live_files == 0 => {}
+            Some(ref restore) if self.all_archived_readable() => {
+                recommendations.push(format!(
+                    "{} data files ({:.2} GB) referenced by the current snapshot are in Glacier or Deep Archive. They are readable from restored copies, but the first copy expires at {}; rewrite them into a readable class before then and exclude the table's live data from archive lifecycle rules.",
+                    self.archived_live_files,
+                    gb(self.archived_live_size_bytes),
+                    restore
+                        .earliest_expiry
+                        .map_or("an unknown time".to_string(), |expiry| expiry.to_rfc3339())
+                ));
+            }
+            Some(ref restore) => {
+                recommendations.push(format!(
+                    "{} data files ({:.2} GB) referenced by the current snapshot are in Glacier or Deep Archive, so queries reading them fail. Of {} checked, {} have no restore requested, {} are being restored and {} are readable from restored copies. Restore them and exclude the table's live data from archive lifecycle rules.",
+                    self.archived_live_files,
+                    gb(self.archived_live_size_bytes),
+                    restore.checked_files,
+                    restore.not_restored_files,
+                    restore.restoring_files,
+                    restore.restored_files
+                ));
+            }
+            None => {
+                recommendations.push(format!(
+                    "{} data files ({:.2} GB) referenced by the current snapshot are in Glacier or Deep Archive, so queries reading them fail. Restore them and exclude the table's live data from archive lifecycle rules.",
+                    self.archived_live_files,
+                    gb(self.archived_live_size_bytes)
+                ));
+            }
+        }
+        if self.ia_eligible_files > 0 {
+            recommendations.push(format!(
+                "{} Standard-class data files ({:.2} GB) haven't been modified in {:.0}+ days. Transition them to Standard-IA or Intelligent-Tiering to cut their storage cost by roughly 45%.",
+                self.ia_eligible_files,
+                gb(self.ia_eligible_size_bytes),
+                IA_MIN_AGE_DAYS
+            ));
+        }
+        recommendations
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct RestoreStatusMetrics {
+    #[pyo3(get)]
+    pub checked_files: usize, // HEAD requests for the first 100 archived live files
+    #[pyo3(get)]
+    pub restored_files: usize, // readable from a restored copy until it expires
+    #[pyo3(get)]
+    pub restoring_files: usize, // restore requested but not finished
+    #[pyo3(get)]
+    pub not_restored_files: usize, // no restore requested; unreadable
+    #[pyo3(get)]
+    pub failed_checks: usize,
+    #[pyo3(get)]
+    pub earliest_expiry: Option<DateTime<Utc>>, // when the first restored copy expires
+}
+
+impl RestoreStatusMetrics {
+    /// Tally the `x-amz-restore` headers of archived objects: None when no restore was
+    /// requested, `ongoing-request="true"` while one runs and `ongoing-request="false",
+    /// expiry-date="Fri, 21 Dec 2012 00:00:00 GMT"` once the copy is readable
+    pub fn from_headers(headers: Vec<anyhow::Result<Option<String>>>) -> Self {
+        let mut metrics = Self {
+            checked_files: headers.len(),
+            restored_files: 0,
+            restoring_files: 0,
+            not_restored_files: 0,
+            failed_checks: 0,
+            earliest_expiry: None,
+        };
+        for header in headers {
+            let header = match header {
+                Ok(Some(header)) => header,
+                Ok(None) => {
+                    metrics.not_restored_files += 1;
+                    continue;
+                }
+                Err(_) => {
+                    metrics.failed_checks += 1;
+                    continue;
+                }
+            };
+            if header.contains("ongoing-request=\"true\"") {
+                metrics.restoring_files += 1;
+                continue;
+            }
+            metrics.restored_files += 1;
+            let expiry = header
+                .split_once("expiry-date=\"")
+                .and_then(|(_, rest)| rest.split_once('"'))
+                .and_then(|(date, _)| DateTime::parse_from_rfc2822(date).ok())
+                .map(|date| date.with_timezone(&Utc));
+            if let Some(expiry) = expiry {
+                metrics.earliest_expiry = Some(
+                    metrics
+                        .earliest_expiry
+                        .map_or(expiry, |earliest| earliest.min(expiry)),
+                );
+            }
+        }
+        metrics
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct RemediationEstimate {
+    #[pyo3(get)]
+    pub action: String, // e.g. "Compact small files"
+    #[pyo3(get)]
+    pub operation: String, // engine command, e.g. "OPTIMIZE" or "rewrite_data_files"
+    #[pyo3(get)]
+    pub engine: String,
+    #[pyo3(get)]
+    pub files_to_rewrite: usize,
+    #[pyo3(get)]
+    pub bytes_to_rewrite: u64,
+    #[pyo3(get)]
+    pub files_to_delete: usize,
+    #[pyo3(get)]
+    pub bytes_saved: u64, // storage expected to be freed
+    #[pyo3(get)]
+    pub estimated_runtime_seconds: f64, // wall clock with the engine's cores
+    #[pyo3(get)]
+    pub estimated_core_hours: f64,
+    #[pyo3(get)]
+    pub effort: String, // "low" (< 10 min), "medium" (< 2 h) or "high"
+}
+
+/// The report as if remediations had been applied, with what they gain and cost
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct Simulation {
+    #[pyo3(get)]
+    pub actions: Vec<String>, // "compaction", "vacuum" and/or "expire_snapshots"
+    #[pyo3(get)]
+    pub baseline_health_score: f64, // the report's `health_score`
+    #[pyo3(get)]
+    pub health_score: f64, // moved by the change in the built-in score once the actions apply
+    #[pyo3(get)]
+    pub score_improvement: f64,
+    #[pyo3(get)]
+    pub sub_scores: HealthSubScores,
+    #[pyo3(get)]
+    pub remediations: Vec<RemediationEstimate>, // the estimates of the applied actions
+    #[pyo3(get)]
+    pub estimated_core_hours: f64,
+    #[pyo3(get)]
+    pub bytes_saved: u64,
+    #[pyo3(get)]
+    pub report: HealthReport, // the simulated report
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct EngineLimitMetrics {
+    #[pyo3(get)]
+    pub engine: String,
+    #[pyo3(get)]
+    pub max_files_per_query: usize,
+    #[pyo3(get)]
+    pub table_files: usize,
+    #[pyo3(get)]
+    pub table_exceeds_limit: bool, // a full scan plans more files than the engine can
+    #[pyo3(get)]
+    pub avg_files_per_partition: f64,
+    #[pyo3(get)]
+    pub largest_partition_files: usize,
+    #[pyo3(get)]
+    pub partitions_over_limit: usize, // even a query pruned to one of them exceeds the limit
+    #[pyo3(get)]
+    pub partitions_over_limit_paths: Vec<String>, // first 100, largest first
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct PartitionSpecMismatchMetrics {
+    #[pyo3(get)]
+    pub expected_partition_columns: Vec<String>,
+    #[pyo3(get)]
+    pub expected_depth: usize,
+    #[pyo3(get)]
+    pub mismatched_file_count: usize,
+    #[pyo3(get)]
+    pub mismatched_size_bytes: u64,
+    #[pyo3(get)]
+    pub offending_paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct TableFeatures {
+    #[pyo3(get)]
+    pub min_reader_version: u32,
+    #[pyo3(get)]
+    pub min_writer_version: u32,
+    #[pyo3(get)]
+    pub reader_features: Vec<String>,
+    #[pyo3(get)]
+    pub writer_features: Vec<String>,
+    #[pyo3(get)]
+    pub enabled_features: Vec<String>,
+    #[pyo3(get)]
+    pub uses_table_features: bool, // reader v3 / writer v7 explicit feature lists
+    #[pyo3(get)]
+    pub compatibility_blockers: Vec<String>, // reader features older engines can't handle
+    #[pyo3(get)]
+    pub features_requiring_upgrade: Vec<String>, // requested via properties, unsupported by protocol
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ParquetColumnInfo {
+    #[pyo3(get)]
+    pub column: String,
+    #[pyo3(get)]
+    pub codec: String, // most common codec across the column's sampled chunks
+    #[pyo3(get)]
+    pub compressed_size_bytes: u64,
+    #[pyo3(get)]
+    pub uncompressed_size_bytes: u64,
+    #[pyo3(get)]
+    pub compression_ratio: f64, // uncompressed / compressed
+    #[pyo3(get)]
+    pub dictionary_encoded: bool, // every sampled chunk uses a dictionary
+    #[pyo3(get)]
+    pub high_cardinality: bool,
+    #[pyo3(get)]
+    pub bloom_filter_coverage: f64, // fraction of sampled chunks with a bloom filter
+    #[pyo3(get)]
+    pub page_index_coverage: f64, // fraction of sampled chunks with column and offset indexes
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct OrcFooterMetrics {
+    #[pyo3(get)]
+    pub sampled_files: usize, // tails decoded
+    #[pyo3(get)]
+    pub undecoded_files: usize, // footers compressed with a codec other than zlib; only the codec is known
+    #[pyo3(get)]
+    pub failed_files: usize, // tails that couldn't be fetched or parsed
+    #[pyo3(get)]
+    pub total_stripes: usize,
+    #[pyo3(get)]
+    pub avg_stripes_per_file: f64,
+    #[pyo3(get)]
+    pub avg_stripe_size_bytes: f64, // index, data and stripe footer, as stored
+    #[pyo3(get)]
+    pub min_stripe_size_bytes: u64,
+    #[pyo3(get)]
+    pub max_stripe_size_bytes: u64,
+    #[pyo3(get)]
+    pub avg_rows_per_stripe: f64,
+    #[pyo3(get)]
+    pub codecs: HashMap<String, usize>, // sampled files per compression codec
+    #[pyo3(get)]
+    pub stats_completeness: f64, // share of top-level columns with file statistics
+    #[pyo3(get)]
+    pub columns_missing_stats: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ParquetFooterMetrics {
+    #[pyo3(get)]
+    pub sampled_files: usize,
+    #[pyo3(get)]
+    pub failed_files: usize, // footers that couldn't be fetched or decoded
+    #[pyo3(get)]
+    pub total_row_groups: usize,
+    #[pyo3(get)]
+    pub avg_row_groups_per_file: f64,
+    #[pyo3(get)]
+    pub avg_row_group_size_bytes: f64, // compressed, as stored
+    #[pyo3(get)]
+    pub min_row_group_size_bytes: u64,
+    #[pyo3(get)]
+    pub max_row_group_size_bytes: u64,
+    #[pyo3(get)]
+    pub avg_rows_per_row_group: f64,
+    #[pyo3(get)]
+    pub codecs: HashMap<String, usize>, // column chunks per compression codec
+    #[pyo3(get)]
+    pub encodings: HashMap<String, usize>, // column chunks using each encoding
+    #[pyo3(get)]
+    pub created_by: HashMap<String, usize>, // sampled files per writer
+    #[pyo3(get)]
+    pub stats_completeness: f64, // fraction of column chunks with min/max statistics
+    #[pyo3(get)]
+    pub columns_missing_stats: Vec<String>,
+    #[pyo3(get)]
+    pub compression_ratio: f64, // uncompressed / compressed, across all sampled chunks
+    #[pyo3(get)]
+    pub columns: Vec<ParquetColumnInfo>, // largest first
+    #[pyo3(get)]
+    pub projected_codec_savings_bytes: u64, // table-wide estimate from switching to zstd
+    #[pyo3(get)]
+    pub projected_dictionary_savings_bytes: u64, // table-wide estimate from dictionary encoding
+    #[pyo3(get)]
+    pub high_cardinality_columns: Vec<String>,
+    #[pyo3(get)]
+    pub bloom_filter_coverage: f64, // fraction of files with bloom filters on all high-cardinality columns
+    #[pyo3(get)]
+    pub page_index_coverage: f64, // fraction of files with page indexes on all high-cardinality columns
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct FileAttributionMetrics {
+    #[pyo3(get)]
+    pub bytes_added_per_day: BTreeMap<String, u64>, // keyed by UTC date, YYYY-MM-DD
+    #[pyo3(get)]
+    pub files_added_per_day: BTreeMap<String, usize>,
+    #[pyo3(get)]
+    pub attributed_file_count: usize, // files traced to the commit/snapshot that added them
+    #[pyo3(get)]
+    pub unattributed_file_count: usize,
+    #[pyo3(get)]
+    pub tombstoned_file_count: usize, // removed from the table but still in storage
+    #[pyo3(get)]
+    pub tombstoned_size_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct SchemaTypeMismatch {
+    #[pyo3(get)]
+    pub column: String,
+    #[pyo3(get)]
+    pub catalog_type: String,
+    #[pyo3(get)]
+    pub physical_type: String,
+}
+
+/// A column both schemas have, with what differs between them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ColumnDifference {
+    #[pyo3(get)]
+    pub column: String,
+    #[pyo3(get)]
+    pub value_a: String,
+    #[pyo3(get)]
+    pub value_b: String,
+}
+
+/// Structural differences between the current schemas of two tables (`compare_schemas`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct SchemaComparison {
+    #[pyo3(get)]
+    pub fingerprint_a: String,
+    #[pyo3(get)]
+    pub fingerprint_b: String,
+    #[pyo3(get)]
+    pub columns_only_in_a: Vec<String>,
+    #[pyo3(get)]
+    pub columns_only_in_b: Vec<String>,
+    #[pyo3(get)]
+    pub type_differences: Vec<ColumnDifference>, // data types
+    #[pyo3(get)]
+    pub nullability_differences: Vec<ColumnDifference>, // "nullable" / "required"
+    #[pyo3(get)]
+    pub column_order_differs: bool, // shared columns appear in a different order
+    #[pyo3(get)]
+    pub partition_columns_a: Vec<String>,
+    #[pyo3(get)]
+    pub partition_columns_b: Vec<String>,
+    #[pyo3(get)]
+    pub is_identical: bool,
+}
+
+impl SchemaComparison {
+    /// Compare two schemas, matching columns by name (field ids differ between tables)
+    pub fn between(a: &TableSchema, b: &TableSchema) -> Self {
+        let find = |schema: &TableSchema, name: &str| {
+            schema.columns.iter().position(|column| column.name == name)
+        };
+        let only_in = |schema: &TableSchema, other: &TableSchema| -> Vec<String> {
+            schema
+                .columns
+                .iter()
+                .filter(|column| find(other, &column.name).is_none())
+                .map(|column| column.name.clone())
+                .collect()
+        };
+        let shared: Vec<(&ColumnInfo, &ColumnInfo)> = a
+            .columns
+            .iter()
+            .filter_map(|column| Some((column, &b.columns[find(b, &column.name)?])))
+            .collect();
+        let nullability = |column: &ColumnInfo| {
+            if column.nullable {
+                "nullable"
+            } else {
+                "required"
+            }
+            .to_string()
+        };
+        let type_differences: Vec<ColumnDifference> = shared
+            .iter()
+            .filter(|(column_a, column_b)| column_a.data_type != column_b.data_type)
+            .map(|(column_a, column_b)| ColumnDifference {
+                column: column_a.name.clone(),
+                value_a: column_a.data_type.clone(),
+                value_b: column_b.data_type.clone(),
+            })
+            .collect();
+        let nullability_differences: Vec<ColumnDifference> = shared
+            .iter()
+            .filter(|(column_a, column_b)| column_a.nullable != column_b.nullable)
+            .map(|(column_a, column_b)| ColumnDifference {
+                column: column_a.name.clone(),
+                value_a: nullability(column_a),
+                value_b: nullability(column_b),
+            })
+            .collect();
+        // Shared columns in `a`'s order should also be in ascending position in `b`
+        let positions_in_b: Vec<usize> = shared
+            .iter()
+            .filter_map(|(column, _)| find(b, &column.name))
+            .collect();
+        let column_order_differs = positions_in_b.windows(2).any(|pair| pair[0] > pair[1]);
+
+        let mut comparison = Self {
+            fingerprint_a: a.fingerprint(),
+            fingerprint_b: b.fingerprint(),
+            columns_only_in_a: only_in(a, b),
+            columns_only_in_b: only_in(b, a),
+            type_differences,
+            nullability_differences,
+            column_order_differs,
+            partition_columns_a: a.partition_columns.clone(),
+            partition_columns_b: b.partition_columns.clone(),
+            is_identical: false,
+        };
+        comparison.is_identical = comparison.columns_only_in_a.is_empty()
+            && comparison.columns_only_in_b.is_empty()
+            && comparison.type_differences.is_empty()
+            && comparison.nullability_differences.is_empty()
+            && !comparison.column_order_differs
+            && comparison.partition_columns_a == comparison.partition_columns_b;
+        comparison
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct SchemaDriftMetrics {
+    #[pyo3(get)]
+    pub catalog_schema_id: Option<i64>,
+    #[pyo3(get)]
+    pub physical_schema_id: Option<i64>, // current schema in the table metadata on storage
+    #[pyo3(get)]
+    pub columns_missing_from_catalog: Vec<String>, // in the table metadata only
+    #[pyo3(get)]
+    pub columns_missing_from_metadata: Vec<String>, // registered in the catalog only
+    #[pyo3(get)]
+    pub file_columns_missing_from_catalog: Vec<String>, // seen in sampled Parquet footers
+    #[pyo3(get)]
+    pub type_mismatches: Vec<SchemaTypeMismatch>,
+    #[pyo3(get)]
+    pub has_drift: bool,
+}
+
+impl SchemaDriftMetrics {
+    /// Compare the schema a catalog has registered against the physical one. Columns are
+    /// matched by name; `file_columns` are Parquet column paths from sampled footers.
+    pub fn between(catalog: &TableSchema, physical: &TableSchema, file_columns: &[String]) -> Self {
+        let catalog_column = |name: &str| catalog.columns.iter().find(|c| c.name == name);
+        let columns_missing_from_catalog: Vec<String> = physical
+            .columns
+            .iter()
+            .filter(|column| catalog_column(&column.name).is_none())
+            .map(|column| column.name.clone())
+            .collect();
+        let columns_missing_from_metadata = catalog
+            .columns
+            .iter()
+            .filter(|column| !physical.columns.iter().any(|c| c.name == column.name))
+            .map(|column| column.name.clone())
+            .collect();
+        let type_mismatches: Vec<SchemaTypeMismatch> = physical
+            .columns
+            .iter()
+            .filter_map(|column| {
+                let registered = catalog_column(&column.name)?;
+                let (Some(catalog_type), Some(physical_type)) = (
+                    comparable_type(&registered.data_type),
+                    comparable_type(&column.data_type),
+                ) else {
+                    return None;
+                };
+                (catalog_type != physical_type).then(|| SchemaTypeMismatch {
+                    column: column.name.clone(),
+                    catalog_type: registered.data_type.clone(),
+                    physical_type: column.data_type.clone(),
+                })
+            })
+            .collect();
+
+        // Nested Parquet paths (`address.city`, `tags.list.element`) belong to their top-level column
+        let mut file_columns_missing_from_catalog: Vec<String> = file_columns
+            .iter()
+            .map(|path| path.split('.').next().unwrap_or(path).to_string())
+            .filter(|name| catalog_column(name).is_none())
+            .collect();
+        file_columns_missing_from_catalog.sort();
+        file_columns_missing_from_catalog.dedup();
+
+        let mut drift = Self {
+            catalog_schema_id: catalog.schema_id,
+            physical_schema_id: physical.schema_id,
+            columns_missing_from_catalog,
+            columns_missing_from_metadata,
+            file_columns_missing_from_catalog,
+            type_mismatches,
+            has_drift: false,
+        };
+        drift.has_drift = !drift.columns_missing_from_catalog.is_empty()
+            || !drift.columns_missing_from_metadata.is_empty()
+            || !drift.file_columns_missing_from_catalog.is_empty()
+            || !drift.type_mismatches.is_empty();
+        drift
+    }
+
+    /// Recommendations for the drift found
+    pub fn recommendations(&self) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        if !self.columns_missing_from_catalog.is_empty()
+            || !self.columns_missing_from_metadata.is_empty()
+            || !self.type_mismatches.is_empty()
+        {
+            recommendations.push(format!(
+                "The catalog's schema doesn't match the table metadata ({} columns missing from the catalog, {} missing from the metadata, {} type mismatches). Engines reading through the catalog see a different table than the one on storage; refresh or re-register the table in the catalog.",
+                self.columns_missing_from_catalog.len(),
+                self.columns_missing_from_metadata.len(),
+                self.type_mismatches.len()
+            ));
+        }
+        if !self.file_columns_missing_from_catalog.is_empty() {
+            recommendations.push(format!(
+                "Sampled data files contain columns the catalog doesn't register: {}. Check for writers bypassing the catalog, or columns renamed since those files were written.",
+                self.file_columns_missing_from_catalog.join(", ")
+            ));
+        }
+        recommendations
+    }
+}
+
+/// A column type spelled the same whether it comes from Delta, Iceberg or a Hive-style
+/// catalog (Glue, Hive Metastore): `bigint` and `long` compare equal, as do `int` and
+/// `integer`. None for nested Hive types (`array<string>`), which have no spelling in
+/// common with the JSON type definitions of Delta and Iceberg.
+fn comparable_type(data_type: &str) -> Option<String> {
+    if data_type.starts_with('{') {
+        return Some(data_type.to_string());
+    }
+    if data_type.contains('<') {
+        return None;
+    }
+    let data_type: String = data_type
+        .to_ascii_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    Some(
+        match data_type.as_str() {
+            "integer" => "int",
+            "bigint" => "long",
+            "smallint" => "short",
+            "tinyint" => "byte",
+            "real" => "float",
+            t if t.starts_with("varchar") || t.starts_with("char") => "string",
+            t => t,
+        }
+        .to_string(),
+    )
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ChangeDataFeedMetrics {
+    #[pyo3(get)]
+    pub is_enabled: bool, // delta.enableChangeDataFeed
+    #[pyo3(get)]
+    pub change_file_count: usize,
+    #[pyo3(get)]
+    pub change_size_bytes: u64,
+    #[pyo3(get)]
+    pub oldest_change_file_age_days: f64,
+    #[pyo3(get)]
+    pub retention_days: f64, // delta.deletedFileRetentionDuration, 7 days by default
+    #[pyo3(get)]
+    pub files_beyond_retention: usize,
+    #[pyo3(get)]
+    pub bytes_beyond_retention: u64,
+    #[pyo3(get)]
+    pub is_retention_unbounded: bool, // change files outlive the retention window
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ColumnMappingMetrics {
+    #[pyo3(get)]
+    pub mode: String, // "none", "name" or "id"
+    #[pyo3(get)]
+    pub is_enabled: bool,
+    #[pyo3(get)]
+    pub max_column_id: u64,
+    #[pyo3(get)]
+    pub renamed_columns: Vec<String>, // "old_name -> new_name"
+    #[pyo3(get)]
+    pub dropped_columns: Vec<String>,
+    #[pyo3(get)]
+    pub files_with_dropped_columns: usize, // live files written before a drop
+    #[pyo3(get)]
+    pub reclaimable_bytes: u64, // estimated share of those files taken by dropped columns
+}
+
+/// Whether Delta UniForm can generate Iceberg metadata for a Delta table
+/// (`assess_iceberg_migration`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct IcebergMigrationMetrics {
+    #[pyo3(get)]
+    pub readiness_score: f64, // 1.0 = ready, lowered by each blocker and warning
+    #[pyo3(get)]
+    pub is_ready: bool, // no blockers
+    #[pyo3(get)]
+    pub blockers: Vec<String>, // must be fixed before UniForm can be enabled
+    #[pyo3(get)]
+    pub warnings: Vec<String>, // conversions that work but change what Iceberg readers see
+    #[pyo3(get)]
+    pub uniform_enabled: bool, // delta.universalFormat.enabledFormats includes iceberg
+    #[pyo3(get)]
+    pub deletion_vectors_enabled: bool,
+    #[pyo3(get)]
+    pub column_mapping_mode: String, // "none", "name" or "id"
+    #[pyo3(get)]
+    pub unsupported_columns: Vec<String>, // "path (type)"
+    #[pyo3(get)]
+    pub iceberg_metadata_path: Option<String>, // newest existing metadata.json, table-relative
+    #[pyo3(get)]
+    pub converted_delta_version: Option<String>, // Delta version UniForm last converted
+    #[pyo3(get)]
+    pub versions_behind: Option<u64>, // Delta commits not yet in the Iceberg metadata
+}
+
+impl IcebergMigrationMetrics {
+    /// Steps toward UniForm Iceberg, or to keep its metadata current
+    pub fn recommendations(&self) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        if self.deletion_vectors_enabled {
+            recommendations.push("Before enabling UniForm Iceberg, disable deletion vectors (ALTER TABLE ... SET TBLPROPERTIES ('delta.enableDeletionVectors' = false)) and rewrite files that have them with REORG TABLE ... APPLY (PURGE).".to_string());
+        }
+        if self.column_mapping_mode == "none" {
+            recommendations.push("Before enabling UniForm Iceberg, enable column mapping (ALTER TABLE ... SET TBLPROPERTIES ('delta.columnMapping.mode' = 'name')). This upgrades the table protocol, which can't be reverted.".to_string());
+        }
+        if !self.unsupported_columns.is_empty() {
+            recommendations.push(format!(
+                "Cast or drop columns Iceberg can't represent before enabling UniForm Iceberg: {}.",
+                self.unsupported_columns.join(", ")
+            ));
+        }
+        if self.is_ready && !self.uniform_enabled {
+            recommendations.push("The table is ready for Iceberg readers through UniForm: ALTER TABLE ... SET TBLPROPERTIES ('delta.enableIcebergCompatV2' = 'true', 'delta.universalFormat.enabledFormats' = 'iceberg').".to_string());
+        }
+        if self.uniform_enabled {
+            if let Some(behind) = self.versions_behind.filter(|behind| *behind > 0) {
+                recommendations.push(format!(
+                    "The UniForm Iceberg metadata is {} Delta versions behind; Iceberg readers see stale data. Regenerate it with MSCK REPAIR TABLE ... SYNC METADATA and check the conversion for errors.",
+                    behind
+                ));
+            }
+        }
+        recommendations
+    }
+}
+
+/// Iceberg format version, v1 leftovers in the metadata, and what upgrading to v2 changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct FormatVersionMetrics {
+    #[pyo3(get)]
+    pub format_version: u64,
+    #[pyo3(get)]
+    pub legacy_manifest_snapshots: usize, // v1 snapshots listing manifests inline, without a manifest list
+    #[pyo3(get)]
+    pub legacy_metadata_fields: Vec<String>, // v1 fields ("schema", "partition-spec") without their v2 lists
+    #[pyo3(get)]
+    pub row_level_deletes_supported: bool, // position and equality delete files (v2+)
+    #[pyo3(get)]
+    pub delete_files: u64, // delete files in the current snapshot
+    #[pyo3(get)]
+    pub copy_on_write_rewrites: usize, // overwrite commits that rewrote data files
+    #[pyo3(get)]
+    pub copy_on_write_rewritten_bytes: u64, // bytes those commits removed
+    #[pyo3(get)]
+    pub merge_on_read_operations: Vec<String>, // "delete", "update", "merge" writing delete files
+    #[pyo3(get)]
+    pub upgrade_recommended: bool,
+}
+
+impl FormatVersionMetrics {
+    /// Upgrading v1 tables, and writing delete files once a v2 table rewrites a lot
+    pub fn recommendations(&self) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        let rewritten_gb = self.copy_on_write_rewritten_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+        if self.upgrade_recommended {
+            let impact = match self.copy_on_write_rewrites {
+                0 => String::new(),
+                rewrites => format!(
+                    " Its history has {} copy-on-write overwrites that rewrote {:.2} GB; with position deletes they can write delete files instead.",
+                    rewrites, rewritten_gb
+                ),
+            };
+            recommendations.push(format!(
+                "Table uses Iceberg format version {}. Upgrade to v2 (ALTER TABLE ... SET TBLPROPERTIES ('format-version' = '2')) for row-level deletes, sequence numbers and stricter metadata; every reader and writer must support v2, and the upgrade can't be reverted.{}",
+                self.format_version, impact
+            ));
+        } else if self.copy_on_write_rewrites > 0 && self.merge_on_read_operations.is_empty() {
+            recommendations.push(format!(
+                "{} copy-on-write overwrites rewrote {:.2} GB of data files. If they come from frequent row-level changes, set write.delete.mode, write.update.mode and write.merge.mode to merge-on-read to write delete files instead, and compact them regularly.",
+                self.copy_on_write_rewrites, rewritten_gb
+            ));
+        }
+        recommendations
+    }
+}
+
+/// Delta transaction log growth: how often it is checkpointed, how much JSON readers replay on
+/// top of the latest checkpoint, and how much history the log keeps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct DeltaLogMetrics {
+    #[pyo3(get)]
+    pub latest_version: u64,
+    #[pyo3(get)]
+    pub checkpoint_count: usize,
+    #[pyo3(get)]
+    pub latest_checkpoint_version: Option<u64>,
+    #[pyo3(get)]
+    pub commits_since_checkpoint: u64, // JSON commits replayed on top of the latest checkpoint
+    #[pyo3(get)]
+    pub bytes_since_checkpoint: u64,
+    #[pyo3(get)]
+    pub avg_commits_per_checkpoint: Option<f64>, // mean gap between consecutive checkpoints
+    #[pyo3(get)]
+    pub json_log_files: usize,
+    #[pyo3(get)]
+    pub json_log_size_bytes: u64,
+    #[pyo3(get)]
+    pub oldest_json_log_age_days: Option<f64>,
+    #[pyo3(get)]
+    pub commits_per_day: Option<f64>,
+    #[pyo3(get)]
+    pub expired_json_log_files: usize, // past log retention and covered by a checkpoint, yet not cleaned up
+    #[pyo3(get)]
+    pub checkpoint_interval: u64, // delta.checkpointInterval, 10 when unset
+    #[pyo3(get)]
+    pub log_retention_days: f64, // delta.logRetentionDuration, 30 when unset
+    #[pyo3(get)]
+    pub recommended_checkpoint_interval: Option<u64>, // set when denser checkpoints are needed
+    #[pyo3(get)]
+    pub recommended_log_retention_days: Option<u64>, // set when the log keeps too many commits
+    #[pyo3(get)]
+    pub is_pathological: bool, // the log has grown far enough to slow every reader
+}
+
+impl DeltaLogMetrics {
+    pub fn recommendations(&self) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        if self.commits_since_checkpoint > 2 * self.checkpoint_interval {
+            recommendations.push(format!(
+                "{} commits ({:.1} MB of JSON) were made since the latest checkpoint{}, though checkpointInterval is {}. Readers replay all of them; check that writers create checkpoints (some engines and Delta clients never do).",
+                self.commits_since_checkpoint,
+                self.bytes_since_checkpoint as f64 / (1024.0 * 1024.0),
+                self.latest_checkpoint_version
+                    .map_or(" (the log has none)".to_string(), |v| format!(" at version {}", v)),
+                self.checkpoint_interval
+            ));
+        }
+        if let Some(interval) = self.recommended_checkpoint_interval {
+            recommendations.push(format!(
+                "Commits average {:.1} MB of JSON, so {} commits between checkpoints make readers replay a lot. Checkpoint more often: ALTER TABLE ... SET TBLPROPERTIES ('delta.checkpointInterval' = '{}').",
+                self.json_log_size_bytes as f64 / self.json_log_files.max(1) as f64 / (1024.0 * 1024.0),
+                self.checkpoint_interval,
+                interval
+            ));
+        }
+        if let Some(days) = self.recommended_log_retention_days {
+            recommendations.push(format!(
+                "The log keeps {} JSON commits ({:.0} per day over {:.0} days of retention). Shorten log retention: ALTER TABLE ... SET TBLPROPERTIES ('delta.logRetentionDuration' = 'interval {} days'). Time travel then reaches back {} days.",
+                self.json_log_files,
+                self.commits_per_day.unwrap_or_default(),
+                self.log_retention_days,
+                days,
+                days
+            ));
+        }
+        if self.expired_json_log_files > 0 {
+            recommendations.push(format!(
+                "{} JSON commits are past log retention ({:.0} days) and covered by a checkpoint but were not cleaned up. Log cleanup runs when a checkpoint is written; check that delta.enableExpiredLogCleanup isn't false and that writers checkpoint.",
+                self.expired_json_log_files, self.log_retention_days
+            ));
+        }
+        recommendations
+    }
+}
+
+/// Delta versions still in the log whose data files were already vacuumed, so time travel
+/// to them fails
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct TimeTravelIntegrityMetrics {
+    #[pyo3(get)]
+    pub oldest_log_version: u64, // oldest JSON commit in the log
+    #[pyo3(get)]
+    pub latest_version: u64,
+    #[pyo3(get)]
+    pub earliest_intact_version: u64, // every version from here on can be read
+    #[pyo3(get)]
+    pub broken_version_count: usize, // versions in the log reading at least one vacuumed file
+    #[pyo3(get)]
+    pub vacuumed_file_count: usize,
+    #[pyo3(get)]
+    pub vacuumed_size_bytes: u64, // as recorded by the add actions
+    #[pyo3(get)]
+    pub vacuumed_paths: Vec<String>, // first 100
+    #[pyo3(get)]
+    pub deleted_file_retention_days: f64, // delta.deletedFileRetentionDuration, 7 when unset
+    #[pyo3(get)]
+    pub log_retention_days: f64, // delta.logRetentionDuration, 30 when unset
+}
+
+impl TimeTravelIntegrityMetrics {
+    pub fn recommendations(&self) -> Vec<String> {
+        if self.broken_version_count == 0 {
+            return Vec::new();
+        }
+        let cause = if self.deleted_file_retention_days < self.log_retention_days {
+            format!(
+                "VACUUM keeps removed files for {:.0} days (delta.deletedFileRetentionDuration) while the log keeps commits for {:.0} days (delta.logRetentionDuration). Raise the former to match, or shorten the latter so the log only keeps versions that can be read.",
+                self.deleted_file_retention_days, self.log_retention_days
+            )
+        } else {
+            "Files were deleted before their retention ran out: check for VACUUM with a short RETAIN or with spark.databricks.delta.retentionDurationCheck.enabled = false.".to_string()
+        };
+        vec![format!(
+            "Time travel is broken before version {}: {} of the versions {}-{} still in the log read {} data files that were already vacuumed, so queries AS OF them fail. {}",
+            self.earliest_intact_version,
+            self.broken_version_count,
+            self.oldest_log_version,
+            self.earliest_intact_version.saturating_sub(1),
+            self.vacuumed_file_count,
+            cause
+        )]
+    }
+}
+
+/// A table property that differs from the best-practice ruleset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct PropertyFinding {
+    #[pyo3(get)]
+    pub property: String,
+    #[pyo3(get)]
+    pub current: Option<String>, // None when unset
+    #[pyo3(get)]
+    pub recommended: String,
+    #[pyo3(get)]
+    pub reason: String,
+    #[pyo3(get)]
+    pub statement: String, // ALTER TABLE ... SET TBLPROPERTIES setting the recommended value
+}
+
+/// Table properties compared with the best-practice ruleset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct TablePropertiesMetrics {
+    #[pyo3(get)]
+    pub properties: HashMap<String, String>, // as set on the table
+    #[pyo3(get)]
+    pub findings: Vec<PropertyFinding>,
+    #[pyo3(get)]
+    pub statement: Option<String>, // one ALTER TABLE setting every recommended value
+}
+
+impl TablePropertiesMetrics {
+    pub fn recommendations(&self) -> Vec<String> {
+        self.findings
+            .iter()
+            .map(|finding| {
+                format!(
+                    "Table property {} is {}: {}. Set it to {}: {}",
+                    finding.property,
+                    finding
+                        .current
+                        .as_ref()
+                        .map_or("unset".to_string(), |v| format!("'{}'", v)),
+                    finding.reason,
+                    finding.recommended,
+                    finding.statement
+                )
+            })
+            .collect()
+    }
+}
+
+#[pymethods]
+impl HealthReport {
+    /// Copy of the report with a redaction policy applied
+    pub fn redacted(&self, policy: crate::redaction::RedactionPolicy) -> HealthReport {
+        use crate::redaction::ReportRedactor;
+        let mut report = self.clone();
+        policy.redact(&mut report);
+        report
+    }
+
+    /// Serialize the report to JSON, applying the redaction policy first if one is given
+    #[pyo3(signature = (redaction=None))]
+    pub fn to_json(
+        &self,
+        redaction: Option<crate::redaction::RedactionPolicy>,
+    ) -> PyResult<String> {
+        let report = match redaction {
+            Some(policy) => self.redacted(policy),
+            None => self.clone(),
+        };
+        serde_json::to_string_pretty(&report).map_err(|e| {
+            pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to serialize report: {}", e))
+        })
+    }
+
+    /// SARIF log of the report's findings for code-scanning dashboards. `artifact_uri` is the
+    /// repository file results are attached to (e.g. the one defining the table); by default
+    /// they point at the table path.
+    #[pyo3(signature = (artifact_uri=None))]
+    pub fn to_sarif(&self, artifact_uri: Option<&str>) -> PyResult<String> {
+        serde_json::to_string_pretty(&crate::sarif::to_sarif(self, artifact_uri)).map_err(|e| {
+            pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to serialize report: {}", e))
+        })
+    }
+
+    /// dbt `sources.yml` (YAML) declaring the table under `source_name`, with its freshness
+    /// SLA and a `drainage_finding` test per finding. `table_name` defaults to the last
+    /// segment of the table path.
+    #[pyo3(signature = (source_name="drainage", table_name=None))]
+    pub fn to_dbt_sources(&self, source_name: &str, table_name: Option<&str>) -> PyResult<String> {
+        let sources = crate::data_quality::to_dbt_sources(self, source_name, table_name);
+        serde_yaml::to_string(&sources).map_err(|e| {
+            pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to serialize report: {}", e))
+        })
+    }
+
+    /// Great Expectations validation result (JSON) with an expectation per score and finding
+    pub fn to_great_expectations(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(&crate::data_quality::to_great_expectations(self)).map_err(
+            |e| {
+                pyo3::exceptions::PyRuntimeError::new_err(format!(
+                    "Failed to serialize report: {}",
+                    e
+                ))
+            },
+        )
+    }
+
+    /// Raise an AssertionError listing the key metrics that worsened against `baseline` (a
+    /// report or its `to_json()`) by more than their tolerance, 0 unless given
+    #[pyo3(signature = (baseline, tolerances=None))]
+    pub fn assert_no_regression(
+        &self,
+        baseline: &PyAny,
+        tolerances: Option<std::collections::HashMap<String, f64>>,
+    ) -> PyResult<()> {
+        let baseline = match baseline.extract::<String>() {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Invalid baseline report: {}", e))
+            })?,
+            Err(_) => baseline.extract::<HealthReport>()?,
+        };
+        let regressions =
+            crate::regression::regressions(self, &baseline, &tolerances.unwrap_or_default())
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        if regressions.is_empty() {
+            return Ok(());
+        }
+        Err(pyo3::exceptions::PyAssertionError::new_err(
+            crate::regression::describe(&regressions, &baseline),
+        ))
+    }
+
+    /// Write a zip archive with the report as JSON and HTML, its file and partition lists
+    /// as Parquet and the analysis log, applying the redaction policy first if one is given
+    #[pyo3(signature = (path, redaction=None))]
+    pub fn save_bundle(
+        &self,
+        path: std::path::PathBuf,
+        redaction: Option<crate::redaction::RedactionPolicy>,
+    ) -> PyResult<()> {
+        let report = match redaction {
+            Some(policy) => self.redacted(policy),
+            None => self.clone(),
+        };
+        std::fs::File::create(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| crate::bundle::write_bundle(&report, std::io::BufWriter::new(file)))
+            .map_err(|e| {
+                pyo3::exceptions::PyIOError::new_err(format!(
+                    "Failed to write bundle {}: {:#}",
+                    path.display(),
+                    e
+                ))
+            })
+    }
+
+    /// SVG badge with the health score and its letter grade, for wikis and READMEs
+    #[pyo3(signature = (label="table health"))]
+    pub fn badge_svg(&self, label: &str) -> String {
+        crate::badge::render(label, self.health_score)
+    }
+
+    /// The report's metrics and scores as if the remediations in `apply` ("compaction",
+    /// "vacuum", "expire_snapshots"; all of them by default) had been run, with their cost
+    #[pyo3(signature = (apply=None))]
+    pub fn simulate(&self, apply: Option<Vec<String>>) -> PyResult<Simulation> {
+        crate::simulation::simulate(self, &apply.unwrap_or_default())
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+}
+
+impl HealthReport {
+    /// Unreferenced files found, including those the result limits left out
+    pub fn unreferenced_file_count(&self) -> usize {
+        self.result_truncation
+            .as_ref()
+            .map_or(self.metrics.unreferenced_files.len(), |t| {
+                t.unreferenced_files_total
+            })
+    }
+
+    /// The metrics' health score, counting the unreferenced files the result limits left out
+    pub fn calculate_health_score(&self) -> f64 {
+        self.metrics
+            .health_score_with(self.unreferenced_file_count())
+    }
+
+    /// The metrics' sub-scores, counting the unreferenced files the result limits left out
+    pub fn calculate_sub_scores(&self) -> HealthSubScores {
+        self.metrics.sub_scores_with(self.unreferenced_file_count())
+    }
+
+    /// Cut the unreferenced files, partitions, partition and compaction file lists and
+    /// recommendations to `limits`, keeping the largest files and partitions and recording the full lengths
+    pub fn apply_result_limits(&mut self, limits: &crate::config::ResultLimits) {
+        if !limits.is_set() {
+            return;
+        }
+        let metrics = &mut self.metrics;
+        let mut truncation = ResultTruncation {
+            truncated: false,
+            unreferenced_files_total: metrics.unreferenced_files.len(),
+            unreferenced_files_truncated: false,
+            partitions_total: metrics.partitions.len(),
+            partitions_truncated: false,
+            recommendations_total: metrics.recommendations.len(),
+            recommendations_truncated: false,
+            partition_files_total: metrics.partitions.iter().map(|p| p.files.len()).sum(),
+            partition_files_truncated: false,
+            unreferenced_files_omitted: 0,
+            partition_files_omitted: 0,
+            compaction_files_truncated: false,
+        };
+        let largest_first =
+            |a: &FileInfo, b: &FileInfo| b.size_bytes.cmp(&a.size_bytes).then(a.path.cmp(&b.path));
+        let max_unreferenced_files = match (limits.max_unreferenced_files, limits.file_list_limit())
+        {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        if let Some(max) = max_unreferenced_files {
+            if metrics.unreferenced_files.len() > max {
+                metrics.unreferenced_files.sort_by(largest_first);
+                metrics.unreferenced_files.truncate(max);
+                truncation.unreferenced_files_truncated = true;
+            }
+        }
+        if let Some(max) = limits.max_partitions {
+            if metrics.partitions.len() > max {
+                metrics.partitions.sort_by_cached_key(|partition| {
+                    (
+                        std::cmp::Reverse(partition.total_size_bytes),
+                        partition.path(),
+                    )
+                });
+                metrics.partitions.truncate(max);
+                truncation.partitions_truncated = true;
+            }
+        }
+        if let Some(max) = limits.file_list_limit() {
+            for partition in &mut metrics.partitions {
+                if partition.files.len() > max {
+                    partition.files.sort_by(largest_first);
+                    partition.files.truncate(max);
+                    truncation.partition_files_truncated = true;
+                }
+            }
+            // `input_file_count` keeps the full count
+            let plan = metrics.file_compaction.iter_mut();
+            for task in plan.flat_map(|c| c.compaction_plan.iter_mut()) {
+                if task.input_files.len() > max {
+                    task.input_files.truncate(max);
+                    truncation.compaction_files_truncated = true;
+                }
+            }
+        }
+        if let Some(max) = limits.max_recommendations {
+            if metrics.recommendations.len() > max {
+                metrics.recommendations.truncate(max);
+                truncation.recommendations_truncated = true;
+            }
+        }
+        truncation.unreferenced_files_omitted =
+            truncation.unreferenced_files_total - metrics.unreferenced_files.len();
+        truncation.partition_files_omitted = truncation.partition_files_total
+            - metrics
+                .partitions
+                .iter()
+                .map(|p| p.files.len())
+                .sum::<usize>();
+        // Warm starts compare against every partition's files
+        if truncation.partitions_truncated || truncation.partition_files_truncated {
+            self.warm_start = None;
+        }
+        truncation.truncated = truncation.unreferenced_files_truncated
+            || truncation.partitions_truncated
+            || truncation.partition_files_truncated
+            || truncation.compaction_files_truncated
+            || truncation.recommendations_truncated;
+        self.result_truncation = Some(truncation);
+    }
+
+    /// Put lists built from hash maps in a stable order and drop wall-clock timings, so
+    /// analyzing an unchanged table twice gives equal reports
+    pub fn make_deterministic(&mut self) {
+        for partition in &mut self.metrics.partitions {
+            partition.files.sort_by(|a, b| a.path.cmp(&b.path));
+        }
+        self.metrics
+            .partitions
+            .sort_by_cached_key(PartitionInfo::path);
+        self.metrics
+            .unreferenced_files
+            .sort_by(|a, b| a.path.cmp(&b.path));
+        if let Some(ref mut stats) = self.analysis_stats {
+            stats.duration_seconds = 0.0;
+        }
+        for entry in self.analysis_log.iter_mut().flatten() {
+            entry.elapsed_seconds = None;
+        }
+    }
+
     pub fn new(table_path: String, table_type: String) -> Self {
         Self {
             table_path,
             table_type,
-            analysis_timestamp: chrono::Utc::now().to_rfc3339(),
+            analysis_timestamp: Utc::now(),
             metrics: HealthMetrics::new(),
             health_score: 0.0,
+            current_schema: None,
+            partition_filter: None,
+            tags: None,
+            warm_start: None,
+            analysis_stats: None,
+            inventory: None,
+            truncated: None,
+            result_truncation: None,
+            failed_phases: None,
+            analysis_log: None,
+            history_regressions: None,
+            custom_checks: None,
+            compliance: None,
         }
     }
 }
@@ -501,6 +4653,16 @@ mod tests {
     use super::*;
     use std::collections::HashMap;
 
+    #[test]
+    fn test_days_since_commit_with_clock_before_commit() {
+        let now: DateTime<Utc> = "2024-06-01T00:00:00Z".parse().unwrap();
+        let two_days_ago = (now.timestamp() - 2 * 86400) as u64 * 1000;
+        assert_eq!(days_since_commit(now, two_days_ago), 2.0);
+        // A fixed `now=` before the newest commit
+        let next_day = (now.timestamp() + 86400) as u64 * 1000;
+        assert_eq!(days_since_commit(now, next_day), 0.0);
+    }
+
     #[test]
     fn test_health_metrics_new() {
         let metrics = HealthMetrics::new();
@@ -539,6 +4701,12 @@ mod tests {
             smallest_partition_size: 1000,
             avg_partition_size: 1000,
             partition_size_std_dev: 0.0,
+            partition_row_skew_score: 0.0,
+            file_row_skew_score: 0.0,
+            largest_partition_rows: 0,
+            smallest_partition_rows: 0,
+            avg_partition_rows: 0,
+            files_with_row_counts: 0,
         };
         metrics.snapshot_health = SnapshotHealth {
             snapshot_count: 5,
@@ -566,12 +4734,24 @@ mod tests {
                 size_bytes: 1000,
                 last_modified: None,
                 is_referenced: false,
+                created: None,
+                age_days: None,
+                commit_id: None,
+                removed_commit_id: None,
+                record_count: None,
+                storage_class: None,
             },
             FileInfo {
                 path: "unreferenced2.parquet".to_string(),
                 size_bytes: 2000,
                 last_modified: None,
                 is_referenced: false,
+                created: None,
+                age_days: None,
+                commit_id: None,
+                removed_commit_id: None,
+                record_count: None,
+                storage_class: None,
             },
         ];
         metrics.file_size_distribution = FileSizeDistribution {
@@ -588,6 +4768,12 @@ mod tests {
             smallest_partition_size: 1000,
             avg_partition_size: 1000,
             partition_size_std_dev: 0.0,
+            partition_row_skew_score: 0.0,
+            file_row_skew_score: 0.0,
+            largest_partition_rows: 0,
+            smallest_partition_rows: 0,
+            avg_partition_rows: 0,
+            files_with_row_counts: 0,
         };
         metrics.snapshot_health = SnapshotHealth {
             snapshot_count: 5,
@@ -665,14 +4851,16 @@ mod tests {
             crate::s3_client::ObjectInfo {
                 key: "metadata1.json".to_string(),
                 size: 1000,
-                last_modified: Some("2023-01-01T00:00:00Z".to_string()),
+                last_modified: Some("2023-01-01T00:00:00Z".parse().unwrap()),
                 etag: Some("etag1".to_string()),
+                storage_class: None,
             },
             crate::s3_client::ObjectInfo {
                 key: "metadata2.json".to_string(),
                 size: 2000,
-                last_modified: Some("2023-01-02T00:00:00Z".to_string()),
+                last_modified: Some("2023-01-02T00:00:00Z".parse().unwrap()),
                 etag: Some("etag2".to_string()),
+                storage_class: None,
             },
         ];
 
@@ -693,14 +4881,1082 @@ mod tests {
         assert_eq!(metrics.snapshot_health.snapshot_retention_risk, 0.0);
     }
 
+    #[test]
+    fn test_calculate_partition_spec_mismatch_detects_depth_drift() {
+        let mut metrics = HealthMetrics::new();
+        let files = [
+            crate::s3_client::ObjectInfo {
+                key: "warehouse/events/year=2024/month=01/part-0.parquet".to_string(),
+                size: 100,
+                last_modified: None,
+                etag: None,
+                storage_class: None,
+            },
+            crate::s3_client::ObjectInfo {
+                key: "warehouse/events/year=2024/part-1.parquet".to_string(),
+                size: 200,
+                last_modified: None,
+                etag: None,
+                storage_class: None,
+            },
+            crate::s3_client::ObjectInfo {
+                key: "warehouse/events/month=01/year=2024/part-2.parquet".to_string(),
+                size: 300,
+                last_modified: None,
+                etag: None,
+                storage_class: None,
+            },
+        ];
+        let file_refs: Vec<&crate::s3_client::ObjectInfo> = files.iter().collect();
+        let columns = vec!["year".to_string(), "month".to_string()];
+
+        metrics.calculate_partition_spec_mismatch(&file_refs, "warehouse/events", &columns);
+
+        let mismatch = metrics.partition_spec_mismatch.unwrap();
+        assert_eq!(mismatch.expected_depth, 2);
+        assert_eq!(mismatch.mismatched_file_count, 2);
+        assert_eq!(mismatch.mismatched_size_bytes, 500);
+        assert_eq!(
+            mismatch.offending_paths,
+            vec![
+                "warehouse/events/year=2024/part-1.parquet".to_string(),
+                "warehouse/events/month=01/year=2024/part-2.parquet".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_calculate_partition_spec_mismatch_unpartitioned_table() {
+        let mut metrics = HealthMetrics::new();
+        let files = [crate::s3_client::ObjectInfo {
+            key: "events/part-0.parquet".to_string(),
+            size: 100,
+            last_modified: None,
+            etag: None,
+            storage_class: None,
+        }];
+        let file_refs: Vec<&crate::s3_client::ObjectInfo> = files.iter().collect();
+
+        metrics.calculate_partition_spec_mismatch(&file_refs, "events/", &[]);
+
+        assert!(metrics.partition_spec_mismatch.is_none());
+    }
+
     #[test]
     fn test_health_report_new() {
         let report = HealthReport::new("s3://bucket/table".to_string(), "delta".to_string());
 
         assert_eq!(report.table_path, "s3://bucket/table");
         assert_eq!(report.table_type, "delta");
-        assert!(!report.analysis_timestamp.is_empty());
+        assert!(report.analysis_timestamp <= Utc::now());
         assert_eq!(report.health_score, 0.0);
         assert_eq!(report.metrics.total_files, 0);
     }
+
+    #[test]
+    fn test_file_info_new_age_days() {
+        let as_of: DateTime<Utc> = "2023-01-11T12:00:00Z".parse().unwrap();
+        let file = FileInfo::new(
+            "part-0.parquet".to_string(),
+            1024,
+            Some("2023-01-01T00:00:00Z".parse().unwrap()),
+            true,
+            as_of,
+        );
+
+        assert_eq!(file.age_days, Some(10.5));
+        assert!(file.commit_id.is_none());
+
+        let unknown = FileInfo::new("part-1.parquet".to_string(), 1024, None, true, as_of);
+        assert!(unknown.age_days.is_none());
+    }
+
+    #[test]
+    fn test_in_flight_unreferenced_files_are_not_penalized() {
+        let as_of: DateTime<Utc> = "2023-01-10T00:00:00Z".parse().unwrap();
+        let mut metrics = HealthMetrics::new();
+        metrics.total_files = 10;
+        metrics.unreferenced_files = vec![
+            FileInfo::new(
+                "fresh.parquet".to_string(),
+                100,
+                Some("2023-01-09T22:00:00Z".parse().unwrap()),
+                false,
+                as_of,
+            ),
+            FileInfo::new(
+                "stale.parquet".to_string(),
+                200,
+                Some("2023-01-01T00:00:00Z".parse().unwrap()),
+                false,
+                as_of,
+            ),
+            FileInfo::new("unknown.parquet".to_string(), 300, None, false, as_of),
+        ];
+        let penalized_score = metrics.calculate_health_score();
+
+        metrics.calculate_in_flight_unreferenced(24.0);
+
+        assert_eq!(metrics.in_flight_unreferenced_files, 1);
+        assert_eq!(metrics.in_flight_unreferenced_size_bytes, 100);
+        assert!(metrics.calculate_health_score() > penalized_score);
+
+        metrics.calculate_in_flight_unreferenced(0.0);
+        assert_eq!(metrics.in_flight_unreferenced_files, 0);
+    }
+
+    fn attributed_file(
+        path: &str,
+        created: &str,
+        commit_id: i64,
+        removed: Option<i64>,
+    ) -> FileInfo {
+        let mut file = FileInfo::new(path.to_string(), 100, None, true, Utc::now());
+        file.created = Some(created.parse().unwrap());
+        file.commit_id = Some(commit_id);
+        file.removed_commit_id = removed;
+        file
+    }
+
+    fn counted_partition(name: &str, file_rows: &[Option<u64>]) -> PartitionInfo {
+        let files: Vec<FileInfo> = file_rows
+            .iter()
+            .map(|&rows| {
+                let mut file =
+                    FileInfo::new(format!("{}.parquet", name), 100, None, true, Utc::now());
+                file.record_count = rows;
+                file
+            })
+            .collect();
+        PartitionInfo {
+            partition_values: HashMap::from([("p".to_string(), name.to_string())]),
+            file_count: files.len(),
+            total_size_bytes: 100 * files.len() as u64,
+            avg_file_size_bytes: 100.0,
+            files,
+        }
+    }
+
+    #[test]
+    fn test_calculate_data_skew_by_row_count() {
+        let mut metrics = HealthMetrics::new();
+        // Equal bytes everywhere, but partition "b" holds far more rows
+        metrics.partitions = vec![
+            counted_partition("a", &[Some(10), Some(10)]),
+            counted_partition("b", &[Some(1000), Some(1000)]),
+            counted_partition("c", &[Some(10), None]),
+        ];
+        metrics.calculate_data_skew();
+
+        let skew = &metrics.data_skew;
+        assert_eq!(skew.partition_skew_score, 0.0);
+        assert_eq!(skew.files_with_row_counts, 5);
+        // Partition "c" has an uncounted file, so only "a" and "b" are compared
+        assert_eq!(skew.largest_partition_rows, 2000);
+        assert_eq!(skew.smallest_partition_rows, 20);
+        assert_eq!(skew.avg_partition_rows, 1010);
+        assert!(skew.partition_row_skew_score > 0.9);
+        assert!(skew.file_row_skew_score > 0.9);
+        let recommendation = skew.row_skew_recommendation().unwrap();
+        assert!(recommendation.starts_with("Partitions are balanced by size"));
+        assert!(recommendation.contains("100x"));
+    }
+
+    #[test]
+    fn test_namespace_report_summary_isolates_failures() {
+        let report = |table: &str, score: f64, size: u64| {
+            let mut report =
+                HealthReport::new(format!("s3://lake/{}", table), "iceberg".to_string());
+            report.health_score = score;
+            report.metrics.total_size_bytes = size;
+            let requests = crate::s3_client::RequestCounts::default();
+            requests.record_get(10);
+            requests.record_cached_get();
+            report.analysis_stats = Some(AnalysisStats::new(
+                &requests,
+                std::time::Duration::ZERO,
+                &crate::config::PricingProfile::default(),
+            ));
+            TableAnalysis {
+                table: table.to_string(),
+                report: Some(report),
+                error: None,
+                error_code: None,
+            }
+        };
+        let tables = vec![
+            report("analytics.a", 0.9, 100),
+            TableAnalysis {
+                table: "analytics.b".to_string(),
+                report: None,
+                error: Some("Access Denied".to_string()),
+                error_code: Some("ACCESS_DENIED".to_string()),
+            },
+            report("analytics.c", 0.5, 300),
+        ];
+
+        let namespace = NamespaceReport::new("analytics.*".to_string(), tables);
+        let summary = &namespace.summary;
+        assert_eq!(summary.table_count, 3);
+        assert_eq!(summary.analyzed_count, 2);
+        assert_eq!(summary.failed_count, 1);
+        assert!((summary.avg_health_score - 0.7).abs() < 1e-9);
+        assert_eq!(summary.min_health_score, 0.5);
+        assert_eq!(summary.total_size_bytes, 400);
+        assert_eq!(
+            summary.lowest_scoring_tables,
+            vec!["analytics.c", "analytics.a"]
+        );
+        assert_eq!(summary.cached_gets, 2);
+        assert_eq!(summary.cache_hit_ratio, 0.5);
+    }
+
+    #[test]
+    fn test_calculate_file_attribution() {
+        let mut metrics = HealthMetrics::new();
+        metrics.partitions = vec![PartitionInfo {
+            partition_values: HashMap::new(),
+            file_count: 4,
+            total_size_bytes: 400,
+            avg_file_size_bytes: 100.0,
+            files: vec![
+                attributed_file("a.parquet", "2023-01-01T01:00:00Z", 0, Some(2)),
+                attributed_file("b.parquet", "2023-01-01T23:00:00Z", 1, None),
+                attributed_file("c.parquet", "2023-01-02T00:00:00Z", 2, None),
+                FileInfo::new("d.parquet".to_string(), 100, None, true, Utc::now()),
+            ],
+        }];
+        metrics.calculate_file_attribution();
+
+        let attribution = metrics.file_attribution.as_ref().unwrap();
+        assert_eq!(attribution.bytes_added_per_day["2023-01-01"], 200);
+        assert_eq!(attribution.bytes_added_per_day["2023-01-02"], 100);
+        assert_eq!(attribution.files_added_per_day["2023-01-01"], 2);
+        assert_eq!(attribution.attributed_file_count, 3);
+        assert_eq!(attribution.unattributed_file_count, 1);
+        assert_eq!(attribution.tombstoned_file_count, 1);
+        assert_eq!(attribution.tombstoned_size_bytes, 100);
+
+        let start: DateTime<Utc> = "2023-01-01T12:00:00Z".parse().unwrap();
+        let end: DateTime<Utc> = "2023-01-02T00:00:00Z".parse().unwrap();
+        let window = metrics.files_added_between(Some(start), Some(end));
+        assert_eq!(window.len(), 1);
+        assert_eq!(window[0].path, "b.parquet");
+        assert_eq!(metrics.files_added_between(None, None).len(), 3);
+    }
+
+    #[test]
+    fn test_schema_change_between_matches_columns_by_id() {
+        let old = serde_json::json!([
+            {"id": 1, "name": "id", "required": true, "type": "long"},
+            {"id": 2, "name": "amount", "required": false, "type": "int"},
+            {"id": 3, "name": "legacy", "required": false, "type": "string"}
+        ]);
+        let new = serde_json::json!([
+            {"id": 1, "name": "id", "required": false, "type": "long"},
+            {"id": 2, "name": "total", "required": false, "type": "long"},
+            {"id": 4, "name": "country", "required": false, "type": "string"}
+        ]);
+        let changes =
+            SchemaChange::between(3, None, old.as_array().unwrap(), new.as_array().unwrap());
+        let summary: Vec<(&str, &str, bool)> = changes
+            .iter()
+            .map(|c| (c.change_type.as_str(), c.column.as_str(), c.is_breaking))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("nullability", "id", true),
+                ("renamed", "total", true),
+                ("retyped", "total", true),
+                ("dropped", "legacy", true),
+                ("added", "country", false),
+            ]
+        );
+        assert_eq!(changes[1].previous.as_deref(), Some("amount"));
+        assert_eq!(changes[2].current.as_deref(), Some("long"));
+        assert!(changes.iter().all(|c| c.version == 3));
+    }
+
+    #[test]
+    fn test_schema_change_between_without_ids_matches_by_name() {
+        let old = serde_json::json!([{"name": "a", "type": "string", "nullable": true}]);
+        let new = serde_json::json!([{"name": "b", "type": "string", "nullable": true}]);
+        let changes =
+            SchemaChange::between(1, None, old.as_array().unwrap(), new.as_array().unwrap());
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].change_type, "dropped");
+        assert_eq!(changes[1].change_type, "added");
+    }
+
+    #[test]
+    fn test_table_schema_from_fields() {
+        let fields = serde_json::json!([
+            {"name": "id", "type": "long", "nullable": false, "metadata": {"delta.columnMapping.id": 1}},
+            {"name": "tags", "type": {"type": "array", "elementType": "string", "containsNull": true}, "nullable": true, "metadata": {}}
+        ]);
+        let schema =
+            TableSchema::from_fields(None, fields.as_array().unwrap(), vec!["date".to_string()]);
+
+        assert_eq!(schema.columns.len(), 2);
+        assert_eq!(schema.columns[0].name, "id");
+        assert_eq!(schema.columns[0].data_type, "long");
+        assert!(!schema.columns[0].nullable);
+        assert_eq!(schema.columns[0].field_id, Some(1));
+        assert!(schema.columns[1].data_type.contains("\"array\""));
+        assert_eq!(schema.partition_columns, vec!["date"]);
+    }
+
+    #[test]
+    fn test_schema_drift_between_catalog_and_metadata() {
+        let catalog_fields = serde_json::json!([
+            {"id": 1, "name": "id", "type": "long", "required": true},
+            {"id": 2, "name": "amount", "type": "int", "required": false},
+            {"id": 3, "name": "region", "type": "string", "required": false}
+        ]);
+        let physical_fields = serde_json::json!([
+            {"id": 1, "name": "id", "type": "long", "required": true},
+            {"id": 2, "name": "amount", "type": "long", "required": false},
+            {"id": 4, "name": "channel", "type": "string", "required": false}
+        ]);
+        let catalog = TableSchema::from_fields(Some(1), catalog_fields.as_array().unwrap(), vec![]);
+        let physical =
+            TableSchema::from_fields(Some(2), physical_fields.as_array().unwrap(), vec![]);
+        let file_columns = vec![
+            "id".to_string(),
+            "legacy.city".to_string(),
+            "legacy.zip".to_string(),
+        ];
+
+        let drift = SchemaDriftMetrics::between(&catalog, &physical, &file_columns);
+        assert!(drift.has_drift);
+        assert_eq!(drift.physical_schema_id, Some(2));
+        assert_eq!(drift.columns_missing_from_catalog, vec!["channel"]);
+        assert_eq!(drift.columns_missing_from_metadata, vec!["region"]);
+        assert_eq!(drift.file_columns_missing_from_catalog, vec!["legacy"]);
+        assert_eq!(drift.type_mismatches.len(), 1);
+        assert_eq!(drift.type_mismatches[0].column, "amount");
+        assert_eq!(drift.type_mismatches[0].catalog_type, "int");
+        assert_eq!(drift.type_mismatches[0].physical_type, "long");
+
+        assert!(!SchemaDriftMetrics::between(&catalog, &catalog, &[]).has_drift);
+    }
+
+    #[test]
+    fn test_schema_drift_between_hive_columns_and_delta_schema() {
+        let hive = TableSchema {
+            schema_id: None,
+            columns: [
+                ("id", "bigint"),
+                ("amount", "decimal(10, 2)"),
+                ("tags", "array<string>"),
+                ("day", "date"),
+            ]
+            .iter()
+            .map(|(name, data_type)| ColumnInfo {
+                name: name.to_string(),
+                data_type: data_type.to_string(),
+                nullable: true,
+                field_id: None,
+            })
+            .collect(),
+            partition_columns: vec!["day".to_string()],
+        };
+        let delta_fields = serde_json::json!([
+            {"name": "id", "type": "long", "nullable": false, "metadata": {}},
+            {"name": "amount", "type": "decimal(10,2)", "nullable": true, "metadata": {}},
+            {"name": "tags", "type": {"type": "array", "elementType": "string", "containsNull": true},
+             "nullable": true, "metadata": {}},
+            {"name": "day", "type": "integer", "nullable": true, "metadata": {}}
+        ]);
+        let delta = TableSchema::from_fields(None, delta_fields.as_array().unwrap(), vec![]);
+
+        let mut metrics = HealthMetrics::new();
+        metrics.calculate_schema_drift(&hive, &delta);
+        let drift = metrics.schema_drift.unwrap();
+        // Hive spellings of the same type and nested types aren't mismatches
+        assert_eq!(drift.type_mismatches.len(), 1);
+        assert_eq!(drift.type_mismatches[0].column, "day");
+        assert!(drift.columns_missing_from_catalog.is_empty());
+        assert!(drift.columns_missing_from_metadata.is_empty());
+        assert!(drift.has_drift);
+        assert!(!drift.recommendations().is_empty());
+    }
+
+    #[test]
+    fn test_schema_comparison_between_mirrored_tables() {
+        let source_fields = serde_json::json!([
+            {"id": 1, "name": "id", "type": "long", "required": true},
+            {"id": 2, "name": "amount", "type": "int", "required": false},
+            {"id": 3, "name": "region", "type": "string", "required": false}
+        ]);
+        // A CTAS copy: new field ids, same columns
+        let copy_fields = serde_json::json!([
+            {"id": 7, "name": "id", "type": "long", "required": true},
+            {"id": 8, "name": "amount", "type": "int", "required": false},
+            {"id": 9, "name": "region", "type": "string", "required": false}
+        ]);
+        let drifted_fields = serde_json::json!([
+            {"id": 1, "name": "region", "type": "string", "required": false},
+            {"id": 2, "name": "id", "type": "long", "required": false},
+            {"id": 3, "name": "amount", "type": "long", "required": false},
+            {"id": 4, "name": "channel", "type": "string", "required": false}
+        ]);
+        let partitions = vec!["region".to_string()];
+        let source = TableSchema::from_fields(
+            Some(0),
+            source_fields.as_array().unwrap(),
+            partitions.clone(),
+        );
+        let copy = TableSchema::from_fields(Some(3), copy_fields.as_array().unwrap(), partitions);
+        let drifted = TableSchema::from_fields(Some(0), drifted_fields.as_array().unwrap(), vec![]);
+
+        let same = SchemaComparison::between(&source, &copy);
+        assert!(same.is_identical);
+        assert_eq!(same.fingerprint_a, same.fingerprint_b);
+
+        let drift = SchemaComparison::between(&source, &drifted);
+        assert!(!drift.is_identical);
+        assert_ne!(drift.fingerprint_a, drift.fingerprint_b);
+        assert!(drift.columns_only_in_a.is_empty());
+        assert_eq!(drift.columns_only_in_b, ["channel"]);
+        assert_eq!(drift.type_differences[0].column, "amount");
+        assert_eq!(drift.type_differences[0].value_b, "long");
+        assert_eq!(drift.nullability_differences[0].value_a, "required");
+        assert!(drift.column_order_differs);
+        assert!(drift.partition_columns_b.is_empty());
+    }
+
+    fn partition(values: &[(&str, &str)], file_count: usize) -> PartitionInfo {
+        PartitionInfo {
+            partition_values: values
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            file_count,
+            total_size_bytes: file_count as u64 * 1024,
+            avg_file_size_bytes: 1024.0,
+            files: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_calculate_partition_anomalies_finds_gaps_future_and_nulls() {
+        let now = "2024-03-10T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let mut metrics = HealthMetrics::new();
+        metrics.partitions = vec![
+            partition(&[("event_date", "2024-03-01"), ("region", "eu")], 2),
+            partition(&[("event_date", "2024-03-01"), ("region", "us")], 2),
+            partition(&[("event_date", "2024-03-02"), ("region", "eu")], 1),
+            partition(&[("event_date", "2024-03-05"), ("region", "eu")], 1),
+            partition(&[("event_date", "2099-01-01"), ("region", "eu")], 1),
+            partition(
+                &[
+                    ("event_date", "2024-03-05"),
+                    ("region", HIVE_DEFAULT_PARTITION),
+                ],
+                3,
+            ),
+        ];
+
+        metrics.calculate_partition_anomalies(now);
+        let anomalies = metrics.partition_anomalies.as_ref().unwrap();
+        assert_eq!(anomalies.date_column.as_deref(), Some("event_date"));
+        assert_eq!(anomalies.granularity.as_deref(), Some("day"));
+        assert_eq!(
+            anomalies.first_partition_date.as_deref(),
+            Some("2024-03-01")
+        );
+        assert_eq!(anomalies.last_partition_date.as_deref(), Some("2024-03-05"));
+        assert_eq!(
+            anomalies.missing_partitions,
+            vec!["2024-03-03", "2024-03-04"]
+        );
+        assert_eq!(anomalies.longest_gap, 2);
+        assert_eq!(anomalies.future_partitions, vec!["2099-01-01"]);
+        assert_eq!(
+            anomalies.null_partitions,
+            vec!["region=__HIVE_DEFAULT_PARTITION__"]
+        );
+        assert_eq!(anomalies.null_partition_file_count, 3);
+        assert_eq!(anomalies.recommendations().len(), 3);
+    }
+
+    #[test]
+    fn test_calculate_partition_anomalies_hierarchical_months() {
+        let now = "2024-03-10T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let mut metrics = HealthMetrics::new();
+        metrics.partitions = vec![
+            partition(&[("year", "2023"), ("month", "11")], 1),
+            partition(&[("year", "2024"), ("month", "02")], 1),
+        ];
+
+        metrics.calculate_partition_anomalies(now);
+        let anomalies = metrics.partition_anomalies.as_ref().unwrap();
+        assert_eq!(anomalies.date_column.as_deref(), Some("year/month"));
+        assert_eq!(anomalies.missing_partitions, vec!["2023-12", "2024-01"]);
+        assert!(anomalies.future_partitions.is_empty());
+
+        metrics.partitions = vec![partition(&[("region", "eu")], 1)];
+        metrics.calculate_partition_anomalies(now);
+        assert!(metrics.partition_anomalies.is_none());
+    }
+
+    #[test]
+    fn test_sub_scores_split_the_health_score_deductions() {
+        let mut metrics = HealthMetrics::new();
+        metrics.total_files = 10;
+        metrics.file_size_distribution.small_files = 10;
+        metrics.snapshot_health.snapshot_retention_risk = 0.5;
+
+        let sub_scores = metrics.calculate_sub_scores();
+        assert!((sub_scores.query_performance_score - 0.8).abs() < 1e-9);
+        assert!((sub_scores.storage_cost_score - 0.95).abs() < 1e-9);
+        assert_eq!(sub_scores.reliability_score, 1.0);
+        assert_eq!(sub_scores.weakest_dimension, "query_performance");
+        assert!((metrics.calculate_health_score() - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_partitioning_advice_drops_high_cardinality_columns() {
+        let mb = 1024 * 1024;
+        let mut metrics = HealthMetrics::new();
+        // 2 regions x 60 days of 20 MB partitions
+        for region in ["eu", "us"] {
+            for day in 0..60 {
+                let date = format!("2024-{:02}-{:02}", day / 30 + 1, day % 30 + 1);
+                let mut p = partition(&[("region", region), ("date", &date)], 2);
+                p.total_size_bytes = 20 * mb;
+                metrics.partitions.push(p);
+            }
+        }
+        metrics.total_files = 240;
+        metrics.total_size_bytes = 120 * 20 * mb;
+
+        metrics.calculate_partitioning_advice();
+        let advice = metrics.partitioning_advice.as_ref().unwrap();
+        assert_eq!(advice.verdict, "over_partitioned");
+        assert_eq!(advice.column_cardinality["date"], 60);
+        assert_eq!(advice.column_cardinality["region"], 2);
+        assert_eq!(advice.columns_to_drop, vec!["date"]);
+        assert_eq!(advice.suggested_partition_columns, vec!["region"]);
+        assert_eq!(advice.projected_partition_count, 2);
+        assert!(advice.recommendation().unwrap().contains("Drop [date]"));
+    }
+
+    #[test]
+    fn test_partitioning_advice_under_partitioned() {
+        let tb = 1024u64 * 1024 * 1024 * 1024;
+        let mut metrics = HealthMetrics::new();
+        let mut p = partition(&[], 5000);
+        p.total_size_bytes = 4 * tb;
+        metrics.partitions.push(p);
+        metrics.total_files = 5000;
+        metrics.total_size_bytes = 4 * tb;
+        metrics.clustering = Some(ClusteringInfo {
+            clustering_columns: vec!["country".to_string()],
+            cluster_count: 1,
+            avg_files_per_cluster: 5000.0,
+            avg_cluster_size_bytes: 4.0 * tb as f64,
+        });
+
+        metrics.calculate_partitioning_advice();
+        let advice = metrics.partitioning_advice.as_ref().unwrap();
+        assert_eq!(advice.verdict, "under_partitioned");
+        assert_eq!(advice.candidate_columns_to_add, vec!["country"]);
+        assert_eq!(advice.projected_partition_count, 4096);
+    }
+
+    #[test]
+    fn test_freshness_metrics_from_commit_times() {
+        let at = |hour: i64| {
+            "2024-03-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap() + chrono::Duration::hours(hour)
+        };
+        let commits = vec![at(6), at(0), at(1), at(2), at(3)];
+
+        let freshness =
+            FreshnessMetrics::from_commit_times(commits.clone(), Some(at(7)), at(30), Some(12.0));
+        assert_eq!(freshness.last_commit_timestamp, Some(at(6)));
+        assert_eq!(freshness.hours_since_last_commit, Some(24.0));
+        assert_eq!(freshness.hours_since_last_data_write, Some(23.0));
+        assert_eq!(freshness.commit_count, 5);
+        assert_eq!(freshness.commit_interval_p50_hours, Some(1.0));
+        assert_eq!(freshness.commit_interval_p99_hours, Some(3.0));
+        assert!(freshness.is_stale);
+        assert!(freshness.recommendation().is_some());
+
+        let no_sla = FreshnessMetrics::from_commit_times(commits, None, at(30), None);
+        assert!(!no_sla.is_stale);
+        assert!(no_sla.recommendation().is_none());
+    }
+
+    #[test]
+    fn test_file_size_buckets_and_percentiles() {
+        use crate::config::FileSizeBuckets;
+        let mb = 1024 * 1024;
+        let buckets = FileSizeBuckets::from_mb(&[128.0, 1024.0, 4096.0]).unwrap();
+        assert!(FileSizeBuckets::from_mb(&[128.0, 64.0, 4096.0]).is_err());
+        assert!(FileSizeBuckets::from_mb(&[16.0, 128.0]).is_err());
+
+        let sizes: Vec<u64> = (1..=20).map(|i| i * 100 * mb).collect();
+        let mut distribution = HealthMetrics::new().file_size_distribution;
+        for size in &sizes {
+            distribution.add(*size, &buckets);
+        }
+        assert_eq!(distribution.small_files, 1); // 100MB
+        assert_eq!(distribution.medium_files, 9); // 200MB - 1000MB
+        assert_eq!(distribution.large_files, 10);
+        assert_eq!(distribution.very_large_files, 0);
+
+        let histogram = FileSizeHistogram::from_sizes(sizes, &buckets).unwrap();
+        assert_eq!(histogram.small_file_bytes, 128 * mb);
+        assert_eq!(histogram.min_bytes, 100 * mb);
+        assert_eq!(histogram.p5_bytes, 100 * mb);
+        assert_eq!(histogram.p50_bytes, 1000 * mb);
+        assert_eq!(histogram.p95_bytes, 1900 * mb);
+        assert_eq!(histogram.max_bytes, 2000 * mb);
+        assert!(FileSizeHistogram::from_sizes(Vec::new(), &buckets).is_none());
+    }
+
+    #[test]
+    fn test_size_class_scales_thresholds() {
+        assert_eq!(SizeClass::of(10 * MIB).size_class, "tiny");
+        assert_eq!(SizeClass::of(10 * GIB).size_class, "small");
+        assert_eq!(SizeClass::of(TIB).size_class, "medium");
+        assert_eq!(SizeClass::of(500 * TIB).size_class, "huge");
+
+        // 150 snapshots are a high risk for a medium table and a low one for a huge table
+        let mut metrics = HealthMetrics::new();
+        metrics.calculate_snapshot_health(150);
+        assert_eq!(metrics.snapshot_health.snapshot_retention_risk, 0.8);
+        metrics.size_class = Some(SizeClass::of(500 * TIB));
+        metrics.calculate_snapshot_health(150);
+        assert_eq!(metrics.snapshot_health.snapshot_retention_risk, 0.2);
+
+        metrics.metadata_health.metadata_total_size_bytes = 200 * MIB;
+        let huge_score = metrics.calculate_health_score();
+        metrics.size_class = None;
+        assert!(metrics.calculate_health_score() < huge_score);
+
+        let config = crate::config::AnalysisConfig::default();
+        let tiny = SizeClass::of(10 * MIB);
+        assert_eq!(config.file_size_buckets(Some(&tiny)).small_bytes, MIB);
+        assert_eq!(config.file_size_buckets(None).small_bytes, 16 * MIB);
+    }
+
+    #[test]
+    fn test_compaction_plan_bin_packs_small_files_per_partition() {
+        let buckets = crate::config::FileSizeBuckets::from_mb(&[16.0, 32.0, 1024.0]).unwrap();
+        let files = [
+            ("date=2024-01-01/a.parquet", 10 * MIB),
+            ("date=2024-01-01/b.parquet", 12 * MIB),
+            ("date=2024-01-01/c.parquet", 15 * MIB),
+            ("date=2024-01-01/d.parquet", 8 * MIB),
+            ("date=2024-01-01/big.parquet", 200 * MIB),
+            ("date=2024-01-02/e.parquet", 2 * MIB),
+            ("date=2024-01-03/f.parquet", MIB),
+            ("date=2024-01-03/g.parquet", MIB),
+            ("h.parquet", 3 * MIB),
+        ];
+        let plan = CompactionTask::plan(&files, &buckets);
+
+        // 15+12 and 10+8 fill two 32MB bins; a lone small file isn't worth a rewrite
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan[0].partition, "date=2024-01-01");
+        assert_eq!(
+            plan[0].input_files,
+            [
+                "date=2024-01-01/c.parquet",
+                "date=2024-01-01/b.parquet",
+                "date=2024-01-01/a.parquet",
+                "date=2024-01-01/d.parquet"
+            ]
+        );
+        assert_eq!(plan[0].estimated_job_count, 2);
+        assert_eq!(plan[0].expected_output_size_bytes, 45 * MIB);
+        assert_eq!(plan[1].partition, "date=2024-01-03");
+        assert_eq!(plan[1].estimated_job_count, 1);
+    }
+
+    #[test]
+    fn test_cost_metrics_split_by_category_and_class() {
+        let gb = 1024 * 1024 * 1024;
+        let object = |key: &str, class: &str| crate::s3_client::ObjectInfo {
+            key: key.to_string(),
+            size: gb,
+            last_modified: None,
+            etag: None,
+            storage_class: Some(class.to_string()),
+        };
+        let files = [
+            object("live-1", "STANDARD"),
+            object("live-2", "GLACIER"),
+            object("history-1", "STANDARD"),
+            object("history-2", "STANDARD"),
+            object("orphan-1", "STANDARD_IA"),
+        ];
+        let data_files: Vec<&crate::s3_client::ObjectInfo> = files.iter().collect();
+        let category = |file: &crate::s3_client::ObjectInfo| match file.key.split('-').next() {
+            Some("live") => CostCategory::Live,
+            Some("history") => CostCategory::History,
+            _ => CostCategory::Orphan,
+        };
+        let pricing = crate::config::PricingProfile::default();
+
+        let cost = CostMetrics::from_files(&data_files, category, gb as u64, &pricing);
+        assert_eq!(cost.live_data_bytes, 2 * gb as u64);
+        assert!((cost.live_data_monthly_cost - (0.023 + 0.0036)).abs() < 1e-9);
+        assert!((cost.history_monthly_cost - 0.046).abs() < 1e-9);
+        assert!((cost.orphan_monthly_cost - 0.0125).abs() < 1e-9);
+        assert!((cost.metadata_monthly_cost - 0.023).abs() < 1e-9);
+        assert!((cost.total_monthly_cost - 0.1081).abs() < 1e-9);
+        assert!((cost.cost_by_class["STANDARD"] - 0.069).abs() < 1e-9);
+        assert!((cost.orphan_cost(gb as u64 / 2) - 0.00625).abs() < 1e-9);
+        // History is over a quarter of the bill
+        assert!(cost.recommendation().is_some());
+    }
+
+    #[test]
+    fn test_analysis_stats_price_requests() {
+        let requests = crate::s3_client::RequestCounts::default();
+        for _ in 0..2000 {
+            requests.record_list();
+        }
+        for _ in 0..5000 {
+            requests.record_get(1024);
+        }
+        requests.record_head();
+        let pricing = crate::config::PricingProfile::default();
+
+        let stats = AnalysisStats::new(&requests, std::time::Duration::from_millis(1500), &pricing);
+        assert_eq!(
+            (stats.list_requests, stats.get_requests, stats.head_requests),
+            (2000, 5000, 1)
+        );
+        assert_eq!(stats.bytes_downloaded, 5000 * 1024);
+        assert_eq!(stats.duration_seconds, 1.5);
+        assert!((stats.estimated_request_cost - (0.01 + 0.0020004)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_storage_class_metrics_flag_archived_live_files() {
+        let now = "2024-03-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let object = |key: &str, class: Option<&str>, age_days: i64| crate::s3_client::ObjectInfo {
+            key: key.to_string(),
+            size: 1024 * 1024,
+            last_modified: Some(now - chrono::Duration::days(age_days)),
+            etag: None,
+            storage_class: class.map(str::to_string),
+        };
+        let files = [
+            object("t/live-glacier.parquet", Some("GLACIER"), 400),
+            object("t/removed-glacier.parquet", Some("GLACIER"), 400),
+            object("t/old.parquet", Some("STANDARD"), 90),
+            object("t/new.parquet", Some("STANDARD"), 2),
+            object("t/other.parquet", None, 90),
+        ];
+        let data_files: Vec<&crate::s3_client::ObjectInfo> = files.iter().collect();
+
+        let metrics =
+            StorageClassMetrics::from_files(&data_files, |f| !f.key.contains("removed"), now);
+        assert_eq!(metrics.files_by_class["GLACIER"], 2);
+        assert_eq!(metrics.bytes_by_class["STANDARD"], 2 * 1024 * 1024);
+        assert_eq!(metrics.files_by_class["UNKNOWN"], 1);
+        assert_eq!(metrics.archived_live_paths, vec!["t/live-glacier.parquet"]);
+        assert_eq!(metrics.ia_eligible_files, 1);
+        assert_eq!(metrics.recommendations().len(), 2);
+
+        let mut health = HealthMetrics::new();
+        let before = health.calculate_sub_scores().reliability_score;
+        health.storage_classes = Some(metrics);
+        assert!(health.calculate_sub_scores().reliability_score < before);
+    }
+
+    #[test]
+    fn test_restore_status_metrics_from_headers() {
+        let restore = RestoreStatusMetrics::from_headers(vec![
+            Ok(None),
+            Ok(Some(r#"ongoing-request="true""#.to_string())),
+            Ok(Some(
+                r#"ongoing-request="false", expiry-date="Fri, 21 Dec 2012 00:00:00 GMT""#
+                    .to_string(),
+            )),
+            Ok(Some(
+                r#"ongoing-request="false", expiry-date="Wed, 19 Dec 2012 00:00:00 GMT""#
+                    .to_string(),
+            )),
+            Err(anyhow::anyhow!("Access Denied")),
+        ]);
+        assert_eq!(restore.checked_files, 5);
+        assert_eq!(restore.not_restored_files, 1);
+        assert_eq!(restore.restoring_files, 1);
+        assert_eq!(restore.restored_files, 2);
+        assert_eq!(restore.failed_checks, 1);
+        assert_eq!(
+            restore.earliest_expiry,
+            Some("2012-12-19T00:00:00Z".parse().unwrap())
+        );
+
+        // Archived live files all readable from restored copies don't cost reliability
+        let mut storage_classes = StorageClassMetrics::from_files(&[], |_| true, Utc::now());
+        storage_classes.archived_live_files = 2;
+        storage_classes.restore_status = Some(RestoreStatusMetrics {
+            not_restored_files: 0,
+            restoring_files: 0,
+            failed_checks: 0,
+            checked_files: 2,
+            ..restore
+        });
+        assert!(storage_classes.all_archived_readable());
+        let mut health = HealthMetrics::new();
+        let before = health.calculate_sub_scores().reliability_score;
+        health.storage_classes = Some(storage_classes);
+        assert_eq!(health.calculate_sub_scores().reliability_score, before);
+    }
+
+    #[test]
+    fn test_file_format_metrics_find_mixed_partitions() {
+        let object = |key: &str, size: i64| crate::s3_client::ObjectInfo {
+            key: key.to_string(),
+            size,
+            last_modified: None,
+            etag: None,
+            storage_class: None,
+        };
+        let files = [
+            object("t/data/date=2024-01-01/a.parquet", 300),
+            object("t/data/date=2024-01-01/b.orc", 100),
+            object("t/data/date=2024-01-02/c.parquet", 300),
+            object("t/data/date=2024-01-02/_SUCCESS", 0),
+            object("t/legacy.ORC", 50),
+        ];
+        let data_files: Vec<&crate::s3_client::ObjectInfo> = files.iter().collect();
+
+        let iceberg =
+            FileFormatMetrics::from_files(&data_files, "t", &["parquet", "orc", "avro"]).unwrap();
+        assert_eq!(iceberg.files_by_format["orc"], 2);
+        assert_eq!(iceberg.bytes_by_format["parquet"], 600);
+        assert_eq!(iceberg.dominant_format, "parquet");
+        assert_eq!(iceberg.mixed_partition_paths, ["data/date=2024-01-01"]);
+        assert_eq!(iceberg.unsupported_files, 0);
+        assert!(iceberg.recommendations("iceberg")[0].contains("parquet 80.0%, orc 20.0%"));
+
+        let delta = FileFormatMetrics::from_files(&data_files, "t", &["parquet"]).unwrap();
+        assert_eq!(delta.unsupported_files, 2);
+        assert_eq!(delta.unsupported_size_bytes, 150);
+        assert_eq!(delta.recommendations("delta").len(), 1);
+
+        assert!(FileFormatMetrics::from_files(&data_files[3..4], "t", &["parquet"]).is_none());
+    }
+
+    #[test]
+    fn test_file_naming_metrics_flag_leftovers_and_empty_files() {
+        let object = |key: &str, size: i64| crate::s3_client::ObjectInfo {
+            key: key.to_string(),
+            size,
+            last_modified: None,
+            etag: None,
+            storage_class: None,
+        };
+        let files = [
+            object("t/date=2024-01-01/part-0.snappy.parquet", 300),
+            object("t/date=2024-01-01/.part-0.snappy.parquet.crc", 12),
+            object("t/date=2024-01-01/_SUCCESS", 0),
+            object("t/date=2024-01-01/part-1.parquet", 0),
+            object("t/date=2024-01-02/part-2.parquet.tmp", 40),
+            object("t/_temporary/0/part-3.parquet", 50),
+            object("t/date=2024-01-02/part-5.parquet_tmp", 30),
+            object("t/date=2024-01-02/part-4.parquet.parquet", 60),
+            object("t/date=2024-01-02/export.CSV", 70),
+            object("t/README", 5),
+            object("t/deletion_vector_0a1b.bin", 80),
+        ];
+        let files: Vec<&crate::s3_client::ObjectInfo> = files.iter().collect();
+
+        let naming = FileNamingMetrics::from_files(&files, "t").unwrap();
+        assert_eq!(naming.checksum_files, 1);
+        assert_eq!(naming.temporary_files, 3);
+        assert_eq!(naming.double_extension_files, 1);
+        assert_eq!(naming.zero_byte_files, 1);
+        assert_eq!(
+            naming.example_paths["zero_byte"],
+            ["date=2024-01-01/part-1.parquet"]
+        );
+        assert_eq!(naming.unexpected_extension_files, 2);
+        assert_eq!(naming.unexpected_extensions[".csv"], 1);
+        assert_eq!(naming.unexpected_extensions[""], 1);
+        assert_eq!(naming.anomalous_files(), 8);
+        assert_eq!(naming.anomalous_size_bytes, 267);
+        assert_eq!(naming.leftover_size_bytes, 192);
+        assert_eq!(naming.recommendations().len(), 3);
+    }
+
+    #[test]
+    fn test_duplicate_file_metrics_group_by_size_and_etag() {
+        let object = |key: &str, size: i64, etag: &str| crate::s3_client::ObjectInfo {
+            key: key.to_string(),
+            size,
+            last_modified: None,
+            etag: Some(format!("\"{}\"", etag)),
+            storage_class: None,
+        };
+        let files = [
+            // A retried write that committed both attempts
+            object("t/part-0-attempt-0.parquet", 100, "aaa"),
+            object("t/part-0-attempt-1.parquet", 100, "aaa"),
+            // An orphaned first attempt of a committed file
+            object("t/part-1-attempt-0.parquet", 400, "bbb"),
+            object("t/part-1-attempt-1.parquet", 400, "bbb"),
+            // Same ETag, different size: different content
+            object("t/part-2.parquet", 401, "bbb"),
+            object("t/empty-0.parquet", 0, "d41d8cd9"),
+            object("t/empty-1.parquet", 0, "d41d8cd9"),
+        ];
+        let files: Vec<&crate::s3_client::ObjectInfo> = files.iter().collect();
+        let live = [
+            "t/part-0-attempt-0.parquet",
+            "t/part-0-attempt-1.parquet",
+            "t/part-1-attempt-1.parquet",
+        ];
+
+        let duplicates =
+            DuplicateFileMetrics::from_files(&files, |file| live.contains(&file.key.as_str()))
+                .unwrap();
+        assert_eq!(duplicates.duplicate_groups, 2);
+        assert_eq!(duplicates.duplicate_files, 2);
+        assert_eq!(duplicates.live_duplicate_groups, 1);
+        assert_eq!(duplicates.reclaimable_bytes, 400);
+        assert_eq!(duplicates.groups[0].etag, "bbb");
+        assert_eq!(duplicates.groups[0].live_copies, 1);
+        assert_eq!(duplicates.groups[1].live_copies, 2);
+        assert_eq!(duplicates.recommendations("delta").len(), 2);
+
+        assert!(DuplicateFileMetrics::from_files(&files[4..], |_| true).is_none());
+    }
+
+    #[test]
+    fn test_file_integrity_metrics_split_referenced_files() {
+        let object = |key: &str, size: i64| crate::s3_client::ObjectInfo {
+            key: key.to_string(),
+            size,
+            last_modified: None,
+            etag: None,
+            storage_class: None,
+        };
+        let files = [
+            object("t/data/part-0.parquet", 4096),
+            object("t/data/part-1.parquet", 0),
+            object("t/data/part-2.parquet", 8),
+            object("t/data/part-3.orc", 3),
+            object("t/data/part-4.avro", 40),
+            object("t/data/part-5.parquet", 0),
+        ];
+        let files: Vec<&crate::s3_client::ObjectInfo> = files.iter().collect();
+
+        let integrity =
+            FileIntegrityMetrics::from_files(&files, |file| !file.key.ends_with("part-5.parquet"))
+                .unwrap();
+        assert_eq!(integrity.referenced_zero_byte_files, 1);
+        assert_eq!(integrity.referenced_truncated_files, 2);
+        assert_eq!(
+            integrity.referenced_paths,
+            [
+                "t/data/part-1.parquet",
+                "t/data/part-2.parquet",
+                "t/data/part-3.orc"
+            ]
+        );
+        assert_eq!(integrity.unreferenced_zero_byte_files, 1);
+        assert_eq!(integrity.unreferenced_truncated_files, 0);
+        assert_eq!(integrity.recommendations("delta").len(), 2);
+
+        let mut metrics = HealthMetrics::new();
+        let baseline = metrics.calculate_health_score();
+        metrics.file_integrity = Some(integrity);
+        assert!(metrics.calculate_health_score() < baseline);
+    }
+
+    #[test]
+    fn test_apply_result_limits_keeps_largest_and_totals() {
+        let now = Utc::now();
+        let mut report = HealthReport::new("s3://lake/t".to_string(), "delta".to_string());
+        report.metrics.unreferenced_files = (1..=5)
+            .map(|i| FileInfo::new(format!("part-{}.parquet", i), i * 100, None, false, now))
+            .collect();
+        report.metrics.partitions = (1..=3)
+            .map(|i| PartitionInfo {
+                partition_values: HashMap::from([("day".to_string(), i.to_string())]),
+                file_count: 1,
+                total_size_bytes: i * 10,
+                avg_file_size_bytes: (i * 10) as f64,
+                files: Vec::new(),
+            })
+            .collect();
+        report.metrics.recommendations = vec!["a".to_string(), "b".to_string()];
+        report.warm_start = Some(crate::warm_start::partition_digests(std::iter::empty()));
+
+        report.apply_result_limits(&crate::config::ResultLimits {
+            max_unreferenced_files: Some(2),
+            max_partitions: Some(1),
+            max_recommendations: Some(5),
+            ..Default::default()
+        });
+        let truncation = report.result_truncation.clone().unwrap();
+        assert!(truncation.truncated);
+        assert_eq!(truncation.unreferenced_files_total, 5);
+        assert!(!truncation.recommendations_truncated);
+        assert_eq!(report.unreferenced_file_count(), 5);
+        let kept: Vec<u64> = report
+            .metrics
+            .unreferenced_files
+            .iter()
+            .map(|f| f.size_bytes)
+            .collect();
+        assert_eq!(kept, [500, 400]);
+        assert_eq!(report.metrics.partitions[0].total_size_bytes, 30);
+        assert!(report.warm_start.is_none());
+    }
+
+    #[test]
+    fn test_file_list_limits_keep_aggregates() {
+        let now = Utc::now();
+        let files = |n: u64| -> Vec<FileInfo> {
+            (1..=n)
+                .map(|i| FileInfo::new(format!("part-{}.parquet", i), i * 100, None, false, now))
+                .collect()
+        };
+        let mut report = HealthReport::new("s3://lake/t".to_string(), "delta".to_string());
+        report.metrics.unreferenced_files = files(4);
+        report.metrics.partitions = vec![PartitionInfo {
+            partition_values: HashMap::from([("day".to_string(), "1".to_string())]),
+            file_count: 3,
+            total_size_bytes: 600,
+            avg_file_size_bytes: 200.0,
+            files: files(3),
+        }];
+        let mut limited = report.clone();
+        limited.apply_result_limits(&crate::config::ResultLimits {
+            max_unreferenced_files: Some(3),
+            max_files_in_report: Some(1),
+            ..Default::default()
+        });
+        let truncation = limited.result_truncation.clone().unwrap();
+        assert!(truncation.partition_files_truncated);
+        assert_eq!(truncation.unreferenced_files_omitted, 3);
+        assert_eq!(truncation.partition_files_omitted, 2);
+        assert_eq!(limited.metrics.partitions[0].files[0].size_bytes, 300);
+        assert_eq!(limited.metrics.partitions[0].file_count, 3);
+        assert_eq!(limited.unreferenced_file_count(), 4);
+
+        report.apply_result_limits(&crate::config::ResultLimits {
+            omit_file_lists: true,
+            max_files_in_report: Some(10),
+            ..Default::default()
+        });
+        assert!(report.metrics.unreferenced_files.is_empty());
+        assert!(report.metrics.partitions[0].files.is_empty());
+        assert_eq!(report.result_truncation.unwrap().partition_files_omitted, 3);
+    }
 }
@@ -0,0 +1,305 @@
+//! Databricks Unity Catalog client: lists catalogs, schemas and tables through the Unity
+//! Catalog REST API and resolves a `catalog.schema.table` to its storage location and format.
+//! Temporary read credentials are requested for every table; a workspace that doesn't vend
+//! them leaves the table to the default credential chain.
+
+use crate::catalog::{
+    parse_table_identifier, request_failed, Catalog, LoadedTable, StorageCredentials,
+};
+use crate::network::NetworkSettings;
+use crate::types::TableSchema;
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+use url::Url;
+
+/// Path of the Unity Catalog API under the workspace host
+const API_PATH: &str = "api/2.1/unity-catalog/";
+
+/// How to reach and authenticate against a Unity Catalog workspace
+#[derive(Debug, Clone, Default)]
+#[pyclass(name = "UnityCatalog")]
+pub struct UnityCatalogConfig {
+    /// Workspace URL, e.g. `https://dbc-1234.cloud.databricks.com`
+    #[pyo3(get)]
+    pub host: String,
+    /// Personal access token or OAuth token (never exposed back to Python)
+    pub token: String,
+}
+
+#[pymethods]
+impl UnityCatalogConfig {
+    #[new]
+    pub fn py_new(host: String, token: String) -> Self {
+        Self { host, token }
+    }
+}
+
+pub struct UnityCatalogClient {
+    http: reqwest::Client,
+    api: Url,
+    token: String,
+}
+
+/// Schema of a Unity Catalog `TableInfo`'s `columns`. Each column's `type_json` is a Spark
+/// struct field, spelled like a Delta `schemaString` field; `type_text` stands in without it.
+fn parse_columns(table: &Value) -> Option<TableSchema> {
+    let columns = table.get("columns")?.as_array()?;
+    let fields: Vec<Value> = columns
+        .iter()
+        .filter_map(|column| {
+            let type_json = column.get("type_json").and_then(|t| t.as_str());
+            type_json
+                .and_then(|field| serde_json::from_str(field).ok())
+                .or_else(|| {
+                    Some(json!({
+                        "name": column.get("name")?,
+                        "type": column.get("type_text")?,
+                        "nullable": column.get("nullable").cloned().unwrap_or(json!(true)),
+                    }))
+                })
+        })
+        .collect();
+    let mut partition_columns: Vec<(i64, &str)> = columns
+        .iter()
+        .filter_map(|column| {
+            let index = column.get("partition_index")?.as_i64()?;
+            Some((index, column.get("name")?.as_str()?))
+        })
+        .collect();
+    partition_columns.sort();
+    Some(TableSchema::from_fields(
+        None,
+        &fields,
+        partition_columns
+            .into_iter()
+            .map(|(_, name)| name.to_string())
+            .collect(),
+    ))
+}
+
+/// Location, format and schema of a Unity Catalog `TableInfo`
+pub fn parse_table(table: &Value) -> Result<LoadedTable> {
+    let location = table
+        .get("storage_location")
+        .and_then(|v| v.as_str())
+        .filter(|location| !location.is_empty())
+        .context("Unity Catalog table has no storage_location")?;
+    let table_type = match table.get("data_source_format").and_then(|v| v.as_str()) {
+        Some(format) if format.eq_ignore_ascii_case("delta") => Some("delta"),
+        Some(format) if format.eq_ignore_ascii_case("iceberg") => Some("iceberg"),
+        _ => None,
+    };
+    Ok(LoadedTable {
+        location: location.to_string(),
+        table_type,
+        credentials: None,
+        schema: parse_columns(table),
+    })
+}
+
+/// AWS credentials of a `temporary-table-credentials` response
+pub fn temporary_credentials(response: &Value) -> Option<StorageCredentials> {
+    let aws = response.get("aws_temp_credentials")?;
+    let field = |key: &str| aws.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    Some(StorageCredentials {
+        access_key_id: field("access_key_id")?,
+        secret_access_key: field("secret_access_key")?,
+        session_token: field("session_token"),
+        region: None,
+    })
+}
+
+impl UnityCatalogClient {
+    pub fn connect(config: &UnityCatalogConfig, network: &NetworkSettings) -> Result<Self> {
+        let host = Url::parse(&config.host).context("Invalid Unity Catalog host")?;
+        crate::sanitize::register(&config.token);
+        Ok(Self {
+            http: network.http_client()?,
+            api: host.join(API_PATH)?,
+            token: config.token.clone(),
+        })
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder, what: &str) -> Result<Value> {
+        let response = request.bearer_auth(&self.token).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(request_failed(what, status, &body));
+        }
+        Ok(response.json().await?)
+    }
+
+    /// GET a paginated listing of `resource` filtered by `query`, collecting `key` from
+    /// every page
+    async fn get_paginated(
+        &self,
+        resource: &str,
+        query: &[(&str, &str)],
+        key: &str,
+    ) -> Result<Vec<Value>> {
+        let mut items = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut url = self.api.join(resource)?;
+            url.query_pairs_mut().extend_pairs(query);
+            if let Some(ref token) = page_token {
+                url.query_pairs_mut().append_pair("page_token", token);
+            }
+            let page = self.send(self.http.get(url), resource).await?;
+            if let Some(values) = page.get(key).and_then(|v| v.as_array()) {
+                items.extend(values.iter().cloned());
+            }
+            page_token = page
+                .get("next_page_token")
+                .and_then(|v| v.as_str())
+                .filter(|token| !token.is_empty())
+                .map(str::to_string);
+            if page_token.is_none() {
+                return Ok(items);
+            }
+        }
+    }
+
+    /// Read credentials for the table `table_id`, when the workspace vends them
+    async fn table_credentials(&self, table_id: &str) -> Option<StorageCredentials> {
+        let request = self
+            .http
+            .post(self.api.join("temporary-table-credentials").ok()?)
+            .json(&json!({ "table_id": table_id, "operation": "READ" }));
+        match self.send(request, "temporary-table-credentials").await {
+            Ok(response) => temporary_credentials(&response),
+            Err(e) => {
+                tracing::debug!("No temporary credentials for table {}: {}", table_id, e);
+                None
+            }
+        }
+    }
+}
+
+/// Names of listed catalogs, schemas or tables
+fn names(entries: &[Value]) -> impl Iterator<Item = String> + '_ {
+    entries
+        .iter()
+        .filter_map(|entry| Some(entry.get("name")?.as_str()?.to_string()))
+}
+
+impl Catalog for UnityCatalogClient {
+    fn namespaces<'a>(&'a self, parent: &'a [String]) -> BoxFuture<'a, Result<Vec<Vec<String>>>> {
+        async move {
+            Ok(match parent {
+                [] => names(&self.get_paginated("catalogs", &[], "catalogs").await?)
+                    .map(|catalog| vec![catalog])
+                    .collect(),
+                [catalog] => {
+                    let query = [("catalog_name", catalog.as_str())];
+                    names(&self.get_paginated("schemas", &query, "schemas").await?)
+                        .map(|schema| vec![catalog.clone(), schema])
+                        .collect()
+                }
+                // Schemas don't nest
+                _ => Vec::new(),
+            })
+        }
+        .boxed()
+    }
+
+    fn tables<'a>(&'a self, namespace: &'a [String]) -> BoxFuture<'a, Result<Vec<String>>> {
+        async move {
+            let [catalog, schema] = namespace else {
+                return Ok(Vec::new());
+            };
+            let query = [
+                ("catalog_name", catalog.as_str()),
+                ("schema_name", schema.as_str()),
+            ];
+            Ok(names(&self.get_paginated("tables", &query, "tables").await?).collect())
+        }
+        .boxed()
+    }
+
+    fn load_table<'a>(&'a self, identifier: &'a str) -> BoxFuture<'a, Result<LoadedTable>> {
+        async move {
+            let (namespace, _) = parse_table_identifier(identifier)?;
+            if namespace.len() != 2 {
+                anyhow::bail!(
+                    "Invalid Unity Catalog table identifier '{}': expected catalog.schema.table",
+                    identifier
+                );
+            }
+            let url = self.api.join("tables/")?.join(identifier)?;
+            let table = self.send(self.http.get(url), "tables").await?;
+            let mut loaded = parse_table(&table)
+                .with_context(|| format!("Cannot analyze Unity Catalog table {}", identifier))?;
+            if let Some(table_id) = table.get("table_id").and_then(|v| v.as_str()) {
+                loaded.credentials = self.table_credentials(table_id).await;
+            }
+            Ok(loaded)
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unity_catalog_tables() {
+        let table = json!({
+            "name": "events",
+            "catalog_name": "main",
+            "schema_name": "analytics",
+            "table_type": "MANAGED",
+            "data_source_format": "DELTA",
+            "storage_location": "s3://uc-root/tables/5f1c",
+            "table_id": "5f1c",
+            "columns": [
+                { "name": "id", "type_text": "bigint", "position": 0, "nullable": false,
+                  "type_json": "{\"name\":\"id\",\"type\":\"long\",\"nullable\":false,\"metadata\":{}}" },
+                { "name": "day", "type_text": "date", "position": 1, "partition_index": 0,
+                  "type_json": "{\"name\":\"day\",\"type\":\"date\",\"nullable\":true,\"metadata\":{}}" },
+                { "name": "note", "type_text": "string", "position": 2 },
+            ],
+        });
+        let loaded = parse_table(&table).unwrap();
+        assert_eq!(loaded.location, "s3://uc-root/tables/5f1c");
+        assert_eq!(loaded.table_type, Some("delta"));
+        let schema = loaded.schema.unwrap();
+        let columns: Vec<(&str, &str, bool)> = schema
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.data_type.as_str(), c.nullable))
+            .collect();
+        assert_eq!(
+            columns,
+            [
+                ("id", "long", false),
+                ("day", "date", true),
+                ("note", "string", true)
+            ]
+        );
+        assert_eq!(schema.partition_columns, ["day"]);
+
+        let view = json!({ "name": "v", "table_type": "VIEW" });
+        assert!(parse_table(&view).is_err());
+        let csv = json!({ "storage_location": "s3://raw/clicks", "data_source_format": "CSV" });
+        assert_eq!(parse_table(&csv).unwrap().table_type, None);
+
+        let credentials = temporary_credentials(&json!({
+            "aws_temp_credentials": {
+                "access_key_id": "ASIAEXAMPLE",
+                "secret_access_key": "secret",
+                "session_token": "token",
+            },
+            "expiration_time": 1700000000000u64,
+        }))
+        .unwrap();
+        assert_eq!(credentials.access_key_id, "ASIAEXAMPLE");
+        assert_eq!(credentials.session_token.as_deref(), Some("token"));
+        assert!(temporary_credentials(&json!({ "azure_user_delegation_sas": {} })).is_none());
+    }
+}
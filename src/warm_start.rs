@@ -0,0 +1,209 @@
+//! Warm start: analyze a table again from its previous report.
+//!
+//! Every report carries a digest of the live files the table metadata places in each
+//! directory. On the next analysis the metadata is read first; directories whose digest is
+//! unchanged take their objects from the previous report instead of being listed again,
+//! and a deep scan reuses the previous footer sample when nothing changed at all.
+//!
+//! Objects that appear or disappear in an unchanged directory without a commit (new
+//! orphans, vacuumed files) are only picked up by an analysis without a previous report.
+
+use crate::config::AnalysisConfig;
+use crate::s3_client::{relative_key, ObjectInfo, S3ClientWrapper};
+use crate::types::{HealthReport, ParquetFooterMetrics, WarmStart};
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Directory of a table-relative path, "" for files at the table root
+fn directory(relative: &str) -> &str {
+    relative.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+/// Whether `dir` is `parent` or one of its subdirectories
+fn is_within(dir: &str, parent: &str) -> bool {
+    parent.is_empty()
+        || dir == parent
+        || (dir.starts_with(parent) && dir.as_bytes().get(parent.len()) == Some(&b'/'))
+}
+
+/// Digest of the live files in each directory, from table-relative paths
+pub fn partition_digests<'a>(live_paths: impl IntoIterator<Item = &'a str>) -> WarmStart {
+    let mut by_directory: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for path in live_paths {
+        by_directory.entry(directory(path)).or_default().push(path);
+    }
+    let partition_digests = by_directory
+        .into_iter()
+        .map(|(dir, mut paths)| {
+            paths.sort_unstable();
+            let mut hasher = Sha256::new();
+            for path in paths {
+                hasher.update(path.as_bytes());
+                hasher.update(b"\n");
+            }
+            (dir.to_string(), hex::encode(hasher.finalize()))
+        })
+        .collect();
+    WarmStart {
+        partition_digests,
+        used_previous_report: false,
+        reused_partitions: 0,
+        relisted_partitions: 0,
+        reused_footer_sample: false,
+    }
+}
+
+/// The configured previous report, if it describes the same table analyzed the same way
+pub fn previous_report<'a>(
+    config: &'a AnalysisConfig,
+    report: &HealthReport,
+) -> Option<&'a HealthReport> {
+    config.previous_report.as_deref().filter(|previous| {
+        previous.table_path == report.table_path
+            && previous.table_type == report.table_type
+            && previous.partition_filter == report.partition_filter
+            && previous.warm_start.is_some()
+    })
+}
+
+/// Data objects of the table: those in directories unchanged since `previous` come from its
+/// partitions, the rest are listed. Returns None when the previous report can't be used
+/// (redacted paths), in which case the caller lists the whole table.
+pub async fn list_data_objects(
+    s3_client: &S3ClientWrapper,
+    previous: &HealthReport,
+    current: &mut WarmStart,
+    metadata_dirs: &[&str],
+    config: &AnalysisConfig,
+) -> Result<Option<Vec<ObjectInfo>>> {
+    let table_prefix = s3_client.get_prefix();
+    let empty = HashMap::new();
+    let previous_digests = previous
+        .warm_start
+        .as_ref()
+        .map_or(&empty, |w| &w.partition_digests);
+
+    let mut changed: Vec<&str> = current
+        .partition_digests
+        .iter()
+        .filter(|(dir, digest)| previous_digests.get(*dir) != Some(*digest))
+        .chain(
+            previous_digests
+                .iter()
+                .filter(|(dir, _)| !current.partition_digests.contains_key(*dir)),
+        )
+        .map(|(dir, _)| dir.as_str())
+        .collect();
+    changed.sort_unstable();
+
+    // Listing a directory lists its subdirectories too; sorting puts parents first
+    let mut listed_dirs: Vec<&str> = Vec::new();
+    for dir in changed {
+        if !listed_dirs.iter().any(|parent| is_within(dir, parent)) {
+            listed_dirs.push(dir);
+        }
+    }
+
+    let file_prefix = format!("{}/", table_prefix);
+    let mut objects: HashMap<String, ObjectInfo> = HashMap::new();
+    for file in previous.metrics.partitions.iter().flat_map(|p| &p.files) {
+        let Some(key) = file.path.strip_prefix(&file_prefix) else {
+            return Ok(None);
+        };
+        let dir = directory(relative_key(table_prefix, key));
+        if !listed_dirs.iter().any(|listed| is_within(dir, listed)) {
+            objects.insert(
+                key.to_string(),
+                ObjectInfo {
+                    key: key.to_string(),
+                    size: file.size_bytes as i64,
+                    last_modified: file.last_modified,
+                    etag: None,
+                    storage_class: file.storage_class.clone(),
+                },
+            );
+        }
+    }
+    let reused_partitions = objects
+        .keys()
+        .map(|key| directory(relative_key(table_prefix, key)))
+        .collect::<HashSet<_>>()
+        .len();
+
+    for dir in &listed_dirs {
+        let prefix = match *dir {
+            "" => table_prefix.to_string(),
+            dir => s3_client.table_key(&format!("{}/", dir)),
+        };
+        for object in s3_client.list_objects(&prefix).await? {
+            let relative = relative_key(table_prefix, &object.key);
+            if metadata_dirs.iter().any(|d| relative.starts_with(d))
+                || !config.partition_filter.matches(relative)
+            {
+                continue;
+            }
+            objects.insert(object.key.clone(), object);
+        }
+    }
+
+    current.used_previous_report = true;
+    current.reused_partitions = reused_partitions;
+    current.relisted_partitions = listed_dirs.len();
+    Ok(Some(objects.into_values().collect()))
+}
+
+/// The previous footer sample, when no directory had to be listed again
+pub fn reusable_footers(
+    previous: Option<&HealthReport>,
+    current: &WarmStart,
+) -> Option<ParquetFooterMetrics> {
+    if !current.used_previous_report || current.relisted_partitions > 0 {
+        return None;
+    }
+    previous?.metrics.parquet_footers.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_digests_change_only_with_the_directory() {
+        let before = partition_digests(["date=1/a.parquet", "date=2/b.parquet", "c.parquet"]);
+        let after = partition_digests([
+            "date=2/b.parquet",
+            "date=1/a.parquet",
+            "date=1/d.parquet",
+            "c.parquet",
+        ]);
+        assert_eq!(before.partition_digests.len(), 3);
+        assert_ne!(
+            before.partition_digests["date=1"],
+            after.partition_digests["date=1"]
+        );
+        assert_eq!(
+            before.partition_digests["date=2"],
+            after.partition_digests["date=2"]
+        );
+        assert_eq!(before.partition_digests[""], after.partition_digests[""]);
+    }
+
+    #[test]
+    fn test_previous_report_must_match_the_table() {
+        let mut previous = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        previous.warm_start = Some(partition_digests(["a.parquet"]));
+        let config = AnalysisConfig {
+            previous_report: Some(std::sync::Arc::new(previous)),
+            ..AnalysisConfig::default()
+        };
+
+        let same = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        assert!(previous_report(&config, &same).is_some());
+        let other = HealthReport::new("s3://lake/orders".to_string(), "delta".to_string());
+        assert!(previous_report(&config, &other).is_none());
+        let mut scoped = HealthReport::new("s3://lake/events".to_string(), "delta".to_string());
+        scoped.partition_filter = Some(HashMap::from([("date".to_string(), "1".to_string())]));
+        assert!(previous_report(&config, &scoped).is_none());
+    }
+}
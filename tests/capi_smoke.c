@@ -0,0 +1,21 @@
+/*
+ * Smoke test for the C ABI: links against a `--features capi` build of libdrainage,
+ * which must load without Python, and checks a failed analysis comes back as an
+ * {"error": ...} report.
+ */
+#include <stdio.h>
+#include <string.h>
+
+#include "drainage.h"
+
+int main(void) {
+    char *json = drainage_analyze_json(NULL, NULL, NULL);
+    if (json == NULL || strstr(json, "\"error\"") == NULL) {
+        fprintf(stderr, "unexpected result: %s\n", json ? json : "(null)");
+        return 1;
+    }
+    printf("%s\n", json);
+    drainage_string_free(json);
+    drainage_string_free(NULL);
+    return 0;
+}